@@ -0,0 +1,165 @@
+//! Bilateral consent for allowances. A spender requiring acceptance only gets an increased
+//! allowance once it accepts it with `accept_allowance`, until then the approval is kept as an
+//! offer and the allowance in force is unchanged. Lowering an allowance takes effect right away, so
+//! owners can always reduce what a spender may take.
+#![cfg_attr(not(feature = "allowance-acceptance"), allow(dead_code))]
+
+use pbc_contract_common::address::Address;
+#[cfg(feature = "allowance-acceptance")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "allowance-acceptance")]
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "allowance-acceptance"))]
+mod tests;
+
+impl TashiTokenState {
+    /// Withdraws the allowance `owner` offered `spender`, if any.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account which owns the funds.
+    ///   * `spender`: [`Address`], account the allowance is offered to.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`Option`]<[`u128`]> amount that was offered.
+    pub fn withdraw_allowance_offer(&mut self, owner: &Address, spender: &Address) -> Option<u128> {
+        let offers = self.allowance_offers.get_mut(spender)?;
+        let amount = offers.remove(owner);
+        if offers.is_empty() {
+            self.allowance_offers.remove(spender);
+        }
+        amount
+    }
+
+    /// Offers `spender` an allowance of `amount` from `owner`, if the spender requires acceptance
+    /// and `amount` increases its allowance. Any previous offer is replaced or withdrawn.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account which owns the funds.
+    ///   * `spender`: [`Address`], account which will spend the funds.
+    ///   * `amount`: [`u128`], amount to allot to `spender`.
+    ///
+    /// ### Returns:
+    ///
+    /// True if the allowance waits for acceptance, false if it can be set right away.
+    #[cfg(feature = "allowance-acceptance")]
+    pub fn offer_allowance(&mut self, owner: Address, spender: Address, amount: u128) -> bool {
+        self.withdraw_allowance_offer(&owner, &spender);
+        if !self.acceptance_required.contains(&spender)
+            || amount <= self.allowance(&owner, &spender)
+        {
+            return false;
+        }
+        if !self.allowance_offers.contains_key(&spender) {
+            self.allowance_offers.insert(spender, SortedVecMap::new());
+        }
+        self.allowance_offers
+            .get_mut(&spender)
+            .unwrap()
+            .insert(owner, amount);
+        true
+    }
+}
+
+/// Set whether increases of allowances to caller address must be accepted by the caller before
+/// they take effect. Offers made while acceptance was required are kept.
+///
+/// Panics if `required` does not change whether the caller requires acceptance.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `required`: [`bool`], whether allowances must be accepted.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "allowance-acceptance")]
+#[action(shortname = 0xca)]
+fn require_allowance_acceptance(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    required: bool,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "require_allowance_acceptance");
+    assert!(
+        state.acceptance_required.contains(&ctx.sender) != required,
+        "Acceptance is already {}.",
+        if required { "required" } else { "not required" }
+    );
+    if required {
+        state.acceptance_required.push(ctx.sender);
+    } else {
+        state
+            .acceptance_required
+            .retain(|spender| *spender != ctx.sender);
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Accept the allowance `owner` offered caller address, setting it as the allowance of the caller.
+///
+/// Panics if `owner` offered the caller no allowance.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `owner`: [`Address`], account which offered the allowance.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "allowance-acceptance")]
+#[action(shortname = 0xcb)]
+fn accept_allowance(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    owner: Address,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "accept_allowance");
+    let amount = state
+        .withdraw_allowance_offer(&owner, &ctx.sender)
+        .expect("No allowance was offered by this owner.");
+    state.update_allowance(owner, ctx.sender, amount);
+    state.assert_invariants();
+
+    state
+}
+
+/// Decline the allowance `owner` offered caller address, leaving its allowance unchanged.
+///
+/// Panics if `owner` offered the caller no allowance.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `owner`: [`Address`], account which offered the allowance.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "allowance-acceptance")]
+#[action(shortname = 0xcc)]
+fn decline_allowance(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    owner: Address,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "decline_allowance");
+    state
+        .withdraw_allowance_offer(&owner, &ctx.sender)
+        .expect("No allowance was offered by this owner.");
+    state.assert_invariants();
+
+    state
+}
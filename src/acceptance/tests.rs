@@ -0,0 +1,69 @@
+//! Tests of allowance acceptance: increases offered until accepted or declined, and decreases that
+//! take effect right away.
+
+use super::{accept_allowance, decline_allowance, require_allowance_acceptance};
+use crate::allowance::{approve, transfer_from};
+use crate::testing::{account, context, new_state};
+use crate::token::{stored_amount, TashiTokenState};
+
+/// A token whose owner approved account 1 for `approved`, before account 1 required acceptance and
+/// the owner approved it for `offered`.
+fn offered_state(approved: u128, offered: u128) -> TashiTokenState {
+    let state = approve(
+        context(account(0)),
+        new_state(),
+        account(1),
+        stored_amount(approved),
+    );
+    let state = require_allowance_acceptance(context(account(1)), state, true);
+    approve(
+        context(account(0)),
+        state,
+        account(1),
+        stored_amount(offered),
+    )
+}
+
+#[test]
+fn increase_waits_for_acceptance() {
+    let state = offered_state(100, 500);
+    assert_eq!(state.allowance(&account(0), &account(1)), 100);
+
+    let state = accept_allowance(context(account(1)), state, account(0));
+    assert_eq!(state.allowance(&account(0), &account(1)), 500);
+    assert!(state.allowance_offers.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Insufficient allowance: 100, minimum required allowance: 500")]
+fn offered_allowance_cannot_be_spent() {
+    transfer_from(
+        context(account(1)),
+        offered_state(100, 500),
+        account(0),
+        account(2),
+        stored_amount(500),
+    );
+}
+
+#[test]
+fn decrease_takes_effect_right_away() {
+    let state = offered_state(500, 200);
+
+    assert_eq!(state.allowance(&account(0), &account(1)), 200);
+    assert!(state.allowance_offers.is_empty());
+}
+
+#[test]
+fn declined_offer_leaves_the_allowance_unchanged() {
+    let state = decline_allowance(context(account(1)), offered_state(100, 500), account(0));
+
+    assert_eq!(state.allowance(&account(0), &account(1)), 100);
+    assert!(state.allowance_offers.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "No allowance was offered by this owner.")]
+fn allowance_cannot_be_accepted_without_an_offer() {
+    accept_allowance(context(account(1)), offered_state(500, 200), account(0));
+}
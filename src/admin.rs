@@ -0,0 +1,235 @@
+//! Ownership of the contract and the roles the owner grants to other accounts.
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+use crate::token::TashiTokenState;
+
+/// A privileged role the owner can grant to accounts.
+#[derive(
+    ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Role {
+    /// Updates the interest index balances are scaled by.
+    #[discriminant(0)]
+    RateSetter {},
+    /// Verified as a distinct person, can vote on proposals using quadratic voting.
+    #[discriminant(1)]
+    Voter {},
+    /// Freezes accounts and force transfers their funds for regulatory enforcement.
+    #[discriminant(2)]
+    Enforcer {},
+    /// Sweeps the dust balances of other accounts, burns collected fees and records balance roots
+    /// and state hashes.
+    #[discriminant(3)]
+    Keeper {},
+    /// Queues, executes and cancels clawbacks of transfers, freezes parts of balances and
+    /// registers the jurisdictions of accounts.
+    #[discriminant(4)]
+    Compliance {},
+    /// Triggers emergency pauses that lift themselves after a while.
+    #[discriminant(5)]
+    Guardian {},
+    /// Pays out of the treasury held by the contract itself, within the spending limit.
+    #[discriminant(6)]
+    Treasurer {},
+    /// Freezes, reverses and settles held reversible transfers.
+    #[discriminant(7)]
+    Arbiter {},
+    /// Approves the milestones of milestone grants, releasing their payments.
+    #[discriminant(8)]
+    Reviewer {},
+}
+
+impl TashiTokenState {
+    /// Checks that `caller` is the owner of the contract.
+    ///
+    /// Panics if `caller` is not the owner.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `caller`: [`Address`], account calling a privileged action.
+    pub fn assert_owner(&self, caller: &Address) {
+        assert!(
+            *caller == self.owner,
+            "Only the owner can perform this action."
+        );
+    }
+
+    /// Checks that `ctx` is a call of the owner. With the `admin-timelock` feature, the owner must
+    /// queue the action in the admin timelock instead, and only the contract itself calls it when
+    /// the queued action is executed.
+    ///
+    /// Panics if the caller is not the owner, or with the feature, if the action was not executed
+    /// from the admin timelock.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    pub fn assert_owner_or_timelock(&self, ctx: &ContractContext) {
+        if cfg!(feature = "admin-timelock") {
+            assert!(
+                ctx.sender == ctx.contract_address,
+                "This action must be queued in the admin timelock."
+            );
+        } else {
+            self.assert_owner(&ctx.sender);
+        }
+    }
+
+    /// Whether `account` has been granted `role`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `role`: [`Role`], the role to check.
+    ///   * `account`: [`Address`], the account to check.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`bool`], true if `account` has `role`.
+    pub fn has_role(&self, role: Role, account: &Address) -> bool {
+        self.roles
+            .get(&role)
+            .map(|members| members.contains(account))
+            .unwrap_or(false)
+    }
+
+    /// Checks that `caller` has been granted `role`.
+    ///
+    /// Panics if `caller` does not have `role`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `role`: [`Role`], the role the action requires.
+    ///   * `caller`: [`Address`], account calling a privileged action.
+    #[cfg_attr(
+        not(any(
+            feature = "interest",
+            feature = "governance",
+            feature = "enforcement",
+            feature = "dust-sweep",
+            feature = "clawback",
+            feature = "emergency-pause",
+            feature = "treasury",
+            feature = "fee-burn",
+            feature = "milestone-grants"
+        )),
+        allow(dead_code)
+    )]
+    pub fn assert_role(&self, role: Role, caller: &Address) {
+        assert!(
+            self.has_role(role, caller),
+            "Caller is missing role: {:?}",
+            role
+        );
+    }
+}
+
+/// Transfer ownership of the contract to `new_owner`. Only the owner can call this action, through
+/// the admin timelock with the `admin-timelock` feature.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `new_owner`: [`Address`], account to hand ownership to.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x15)]
+pub(crate) fn transfer_ownership(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    new_owner: Address,
+) -> TashiTokenState {
+    state.assert_owner_or_timelock(&ctx);
+    crate::audit::log_admin_action!(state, ctx, "transfer_ownership", new_owner);
+    state.owner = new_owner;
+    state.assert_invariants();
+
+    state
+}
+
+/// Grant `role` to `account`. Only the owner can call this action, through the admin timelock with
+/// the `admin-timelock` feature.
+///
+/// Panics if the caller is not the owner or if `account` already has `role`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `role`: [`Role`], the role to grant.
+///   * `account`: [`Address`], account to grant the role to.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x2b)]
+pub(crate) fn grant_role(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    role: Role,
+    account: Address,
+) -> TashiTokenState {
+    state.assert_owner_or_timelock(&ctx);
+    crate::audit::log_admin_action!(state, ctx, "grant_role", role, account);
+    assert!(
+        !state.has_role(role, &account),
+        "Account already has role: {:?}",
+        role
+    );
+    if !state.roles.contains_key(&role) {
+        state.roles.insert(role, Vec::new());
+    }
+    state.roles.get_mut(&role).unwrap().push(account);
+    state.assert_invariants();
+
+    state
+}
+
+/// Revoke `role` from `account`. Only the owner can call this action, through the admin timelock
+/// with the `admin-timelock` feature.
+///
+/// Panics if the caller is not the owner or if `account` does not have `role`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `role`: [`Role`], the role to revoke.
+///   * `account`: [`Address`], account to revoke the role from.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x2c)]
+pub(crate) fn revoke_role(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    role: Role,
+    account: Address,
+) -> TashiTokenState {
+    state.assert_owner_or_timelock(&ctx);
+    crate::audit::log_admin_action!(state, ctx, "revoke_role", role, account);
+    assert!(
+        state.has_role(role, &account),
+        "Account does not have role: {:?}",
+        role
+    );
+    let members = state.roles.get_mut(&role).unwrap();
+    members.retain(|member| *member != account);
+    if members.is_empty() {
+        state.roles.remove(&role);
+    }
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,537 @@
+//! Allowances, where an owner allows a spender to transfer up to an amount of its funds, and
+//! operators, which can spend all funds of an owner. Allowances follow the MPC-20 standard: nothing
+//! is set aside, the spender transfers from the balance of the owner and the transfer fails if the
+//! balance does not cover it.
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use read_write_rpc_derive::ReadWriteRPC;
+
+use crate::hooks::Transfer;
+use crate::token::{amount_value, stored_amount, Amount, BalanceMap, TashiTokenState};
+
+#[cfg(test)]
+mod tests;
+
+/// An allowance to set for a spender in a batch of approvals.
+///
+/// ### Fields:
+///
+///   * `spender`: [`Address`], account which will spend the funds.
+///   * `amount`: [`Amount`], amount to allot to `spender`.
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub struct SpenderAllowance {
+    pub spender: Address,
+    pub amount: Amount,
+}
+
+/// A receiver of a bulk transfer, the MPC-20 `Transfer` struct.
+///
+/// ### Fields:
+///
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`Amount`], amount to transfer.
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub struct TransferTarget {
+    pub to: Address,
+    pub amount: Amount,
+}
+
+impl TashiTokenState {
+    /// Gets the amount of tokens that an owner allotted to a spender.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account which owns the funds.
+    ///   * `spender`: [`Address`], account which will spend the funds.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount the `spender` is allowed to withdraw from the `owner`.
+    pub fn allowance(&self, owner: &Address, spender: &Address) -> u128 {
+        self.allowed
+            .get(owner)
+            .and_then(|owner_allowances| owner_allowances.get(spender))
+            .copied()
+            .map_or(0, amount_value)
+    }
+
+    /// Updates the balance an owner allots a spender to `amount`. An owner without allowances left
+    /// is removed from `allowed`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account which owns the funds.
+    ///   * `spender`: [`Address`], account which will spend the funds.
+    ///   * `amount`: [`u128`], amount to allot to `spender`.
+    pub fn update_allowance(&mut self, owner: Address, spender: Address, amount: u128) {
+        if !self.allowed.contains_key(&owner) {
+            self.allowed.insert(owner, SortedVecMap::new());
+        }
+        let owner_allowances = self.allowed.get_mut(&owner).unwrap();
+        owner_allowances.insert_balance(spender, stored_amount(amount));
+        if owner_allowances.is_empty() {
+            self.allowed.remove(&owner);
+        }
+    }
+
+    /// Removes the owners left without allowances from `allowed` and `burn_allowed`.
+    pub fn prune_empty_allowances(&mut self) {
+        let empty_owners: Vec<Address> = self
+            .allowed
+            .iter()
+            .filter(|(_, owner_allowances)| owner_allowances.is_empty())
+            .map(|(owner, _)| *owner)
+            .collect();
+        let empty_burn_owners: Vec<Address> = self
+            .burn_allowed
+            .iter()
+            .filter(|(_, owner_allowances)| owner_allowances.is_empty())
+            .map(|(owner, _)| *owner)
+            .collect();
+        for owner in &empty_owners {
+            self.allowed.remove(owner);
+        }
+        for owner in &empty_burn_owners {
+            self.burn_allowed.remove(owner);
+        }
+    }
+
+    /// Sets the allowance of `spender` from `owner` to `amount`, replacing the current allowance.
+    /// The allowance is not limited by the balance of `owner`. With the `allowance-acceptance`
+    /// feature, an increase for a spender requiring acceptance is only offered, see
+    /// [`TashiTokenState::offer_allowance`].
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account which owns the funds.
+    ///   * `spender`: [`Address`], account which will spend the funds.
+    ///   * `amount`: [`u128`], amount `spender` is allowed to transfer.
+    pub fn approve_spender(&mut self, owner: Address, spender: Address, amount: u128) {
+        #[cfg(feature = "allowance-acceptance")]
+        if self.offer_allowance(owner, spender, amount) {
+            return; // the spender must accept the allowance first
+        }
+        self.update_allowance(owner, spender, amount); // update spender allowance
+    }
+
+    /// Subtracts `amount` from the allowance an owner allotted a spender.
+    ///
+    /// Panics if there is insufficient allowance.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account which owns the funds.
+    ///   * `spender`: [`Address`], account which spends the funds.
+    ///   * `amount`: [`u128`], amount to spend.
+    pub fn spend_allowance(&mut self, owner: Address, spender: Address, amount: u128) {
+        let spender_allowance = self.allowance(&owner, &spender);
+        let spender_new_allowance = spender_allowance
+            .checked_sub(amount) // subtract amount from spender allowance
+            .unwrap_or_else(|| {
+                // panic if allowance < amount
+                panic!(
+                    "Insufficient allowance: {}, minimum required allowance: {}",
+                    spender_allowance, amount
+                )
+            });
+        self.update_allowance(owner, spender, spender_new_allowance); // update spender allowance
+    }
+
+    /// Lists the outstanding allowances `owner` has allotted, ordered by spender, one page at a
+    /// time. Other contracts read it with `query_allowances_of`, off-chain readers get the same
+    /// list from the `allowed` field of the serialized state.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account which owns the funds.
+    ///   * `offset`: [`usize`], number of allowances to skip.
+    ///   * `limit`: [`usize`], maximum number of allowances to return.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`Vec`]<[`SpenderAllowance`]> of at most `limit` allowances.
+    pub fn allowances_of(
+        &self,
+        owner: &Address,
+        offset: usize,
+        limit: usize,
+    ) -> Vec<SpenderAllowance> {
+        self.allowed
+            .get(owner)
+            .map(|owner_allowances| {
+                owner_allowances
+                    .iter()
+                    .skip(offset)
+                    .take(limit)
+                    .map(|(spender, amount)| SpenderAllowance {
+                        spender: *spender,
+                        amount: *amount,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether `operator` can transfer any amount on behalf of `owner`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account which owns the funds.
+    ///   * `operator`: [`Address`], account to check.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`bool`], true if `operator` is an operator of `owner`.
+    pub fn is_operator(&self, owner: &Address, operator: &Address) -> bool {
+        self.operators
+            .get(owner)
+            .map(|owner_operators| owner_operators.contains(operator))
+            .unwrap_or(false)
+    }
+
+    /// Revokes everything `owner` authorized `spender` to do: its allowance, any allowance offered
+    /// to it, its burn allowance and its operator approval.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account which owns the funds.
+    ///   * `spender`: [`Address`], account to revoke.
+    pub fn revoke_spender(&mut self, owner: Address, spender: Address) {
+        self.update_allowance(owner, spender, 0);
+        self.withdraw_allowance_offer(&owner, &spender);
+
+        #[cfg(feature = "burnable")]
+        self.update_burn_allowance(owner, spender, 0);
+
+        if let Some(owner_operators) = self.operators.get_mut(&owner) {
+            owner_operators.retain(|owner_operator| *owner_operator != spender);
+            if owner_operators.is_empty() {
+                self.operators.remove(&owner);
+            }
+        }
+    }
+
+    /// Lists every account `owner` gave an allowance, a burn allowance or an operator approval.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account which owns the funds.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`Vec`]<[`Address`]> of the authorized accounts, without duplicates.
+    pub fn authorized_spenders(&self, owner: &Address) -> Vec<Address> {
+        let mut spenders: Vec<Address> = self
+            .allowed
+            .get(owner)
+            .into_iter()
+            .chain(self.burn_allowed.get(owner))
+            .flat_map(|owner_allowances| owner_allowances.keys().copied())
+            .chain(self.operators.get(owner).into_iter().flatten().copied())
+            .collect();
+        spenders.sort();
+        spenders.dedup();
+        spenders
+    }
+}
+
+/// Transfer `amount` tokens to address `to` from address `from`. An operator of `from` transfers
+/// from its balance, any other caller spends its allowance.
+///
+/// Panics if there is insufficient allowance in caller account, insufficient balance in `from`
+/// account, or if adding `amount` causes an overflow.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `from`: [`Address`], account to transfer from.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`Amount`] - amount to transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[action(shortname = 0x03)]
+pub(crate) fn transfer_from(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    from: Address,
+    to: Address,
+    amount: Amount,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "transfer_from");
+    let supply = state.supply();
+    state.execute_transfer(
+        &ctx,
+        Transfer::new(from, to, amount_value(amount), Some(ctx.sender)),
+    );
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Transfer tokens from address `from` to many receivers in one transaction. Each entry is
+/// handled as by [`transfer_from`].
+///
+/// Panics if there is insufficient allowance in caller account or insufficient balance in `from`
+/// account for any of the transfers.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `from`: [`Address`], account to transfer from.
+///   * `transfers`: [`Vec`]<[`TransferTarget`]>, the receivers and amounts.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[action(shortname = 0x04)]
+fn bulk_transfer_from(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    from: Address,
+    transfers: Vec<TransferTarget>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "bulk_transfer_from");
+    let supply = state.supply();
+    for target in transfers {
+        state.execute_transfer(
+            &ctx,
+            Transfer::new(
+                from,
+                target.to,
+                amount_value(target.amount),
+                Some(ctx.sender),
+            ),
+        );
+    }
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Approve `amount` tokens for address `spender` from caller address. If no prior approval exists
+/// then a new entry is created with approval set as `amount`. Else `amount` replaces the current
+/// approval amount. Nothing is set aside, `spender` transfers from the caller balance.
+///
+/// Panics if the address guards reject `spender`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `spender`: [`Address`], account which will spend the funds.
+///   * `amount`: [`Amount`], amount `spender` is allowed to transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x05)]
+pub(crate) fn approve(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    spender: Address,
+    amount: Amount,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "approve");
+    state.assert_valid_destination(&ctx, &spender);
+    state.approve_spender(ctx.sender, spender, amount_value(amount));
+    state.assert_invariants();
+
+    state
+}
+
+/// Approve allowances for many spenders from caller address in one transaction. Each entry is
+/// handled as by [`approve`].
+///
+/// Panics if the address guards reject a spender.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `approvals`: [`Vec`]<[`SpenderAllowance`]>, the allowances to set.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x32)]
+fn bulk_approve(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    approvals: Vec<SpenderAllowance>,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "bulk_approve");
+    for approval in approvals {
+        state.assert_valid_destination(&ctx, &approval.spender);
+        state.approve_spender(ctx.sender, approval.spender, amount_value(approval.amount));
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Change the allowance for address `spender` from caller address by `delta`. If no prior approval
+/// exists the allowance starts at zero.
+///
+/// Panics if the allowance would become negative or overflow, or if the address guards reject
+/// `spender`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `spender`: [`Address`], account to update allowance for.
+///   * `delta`: [`i128`], amount to update allowance by.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x07)]
+pub(crate) fn approve_relative(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    spender: Address,
+    delta: i128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "approve_relative");
+    state.assert_valid_destination(&ctx, &spender);
+    let spender_allowance = state.allowance(&ctx.sender, &spender);
+    let spender_new_allowance = spender_allowance
+        .checked_add_signed(delta)
+        .unwrap_or_else(|| {
+            panic!(
+                "Allowance: {}, cannot be changed by: {}",
+                spender_allowance, delta
+            )
+        });
+    state.approve_spender(ctx.sender, spender, spender_new_allowance);
+    state.assert_invariants();
+
+    state
+}
+
+/// Authorize or deauthorize `operator` to transfer any amount from caller address. Unlike an
+/// allowance, the operator is not limited to an amount: it can spend the whole caller balance.
+///
+/// Panics if `enabled` does not change whether `operator` is an operator of the caller, or if the
+/// address guards reject `operator`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `operator`: [`Address`], account to authorize or deauthorize.
+///   * `enabled`: [`bool`], true to authorize `operator`, false to deauthorize it.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x31)]
+pub(crate) fn set_operator(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    operator: Address,
+    enabled: bool,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "set_operator");
+    assert!(
+        state.is_operator(&ctx.sender, &operator) != enabled,
+        "Operator is already {}.",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    if enabled {
+        state.assert_valid_destination(&ctx, &operator);
+        if !state.operators.contains_key(&ctx.sender) {
+            state.operators.insert(ctx.sender, Vec::new());
+        }
+        state.operators.get_mut(&ctx.sender).unwrap().push(operator);
+    } else {
+        let owner_operators = state.operators.get_mut(&ctx.sender).unwrap();
+        owner_operators.retain(|owner_operator| *owner_operator != operator);
+        if owner_operators.is_empty() {
+            state.operators.remove(&ctx.sender);
+        }
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Revoke every allowance, burn allowance and operator approval caller address has granted.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x56)]
+fn revoke_all_allowances(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "revoke_all_allowances");
+    for spender in state.authorized_spenders(&ctx.sender) {
+        state.revoke_spender(ctx.sender, spender);
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Revoke the allowance, burn allowance and operator approval caller address has granted each of
+/// `spenders`. Spenders without any are skipped.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `spenders`: [`Vec`]<[`Address`]>, accounts to revoke.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x57)]
+fn revoke_allowances(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    spenders: Vec<Address>,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "revoke_allowances");
+    for spender in spenders {
+        state.revoke_spender(ctx.sender, spender);
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Remove the owners left without allowances by earlier versions of the contract, shrinking the
+/// state. Anyone can call this action.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x58)]
+fn gc_allowances(_ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "gc_allowances");
+    state.prune_empty_allowances();
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,225 @@
+//! Tests of operators, which transfer any amount of the owner funds without an allowance, of
+//! allowances approved and spent in bulk, and of revoking every authorization of an owner.
+
+use super::{
+    approve, bulk_approve, bulk_transfer_from, revoke_all_allowances, revoke_allowances,
+    set_operator, transfer_from, SpenderAllowance, TransferTarget,
+};
+use crate::testing::{account, context, new_state, INITIAL_SUPPLY};
+use crate::token::{stored_amount, TashiTokenState};
+
+/// A token whose owner made account 1 its operator.
+fn operator_state() -> TashiTokenState {
+    set_operator(context(account(0)), new_state(), account(1), true)
+}
+
+#[test]
+fn operator_transfers_the_whole_balance_without_an_allowance() {
+    let (state, _) = transfer_from(
+        context(account(1)),
+        operator_state(),
+        account(0),
+        account(2),
+        stored_amount(INITIAL_SUPPLY),
+    );
+
+    assert_eq!(state.balance_of(&account(0)), 0);
+    assert_eq!(state.balance_of(&account(2)), INITIAL_SUPPLY);
+    assert_eq!(state.allowance(&account(0), &account(1)), 0);
+}
+
+#[test]
+fn operator_does_not_spend_its_allowance() {
+    let state = approve(
+        context(account(0)),
+        operator_state(),
+        account(1),
+        stored_amount(1_000),
+    );
+    let (state, _) = transfer_from(
+        context(account(1)),
+        state,
+        account(0),
+        account(2),
+        stored_amount(5_000),
+    );
+
+    assert_eq!(state.balance_of(&account(2)), 5_000);
+    assert_eq!(state.allowance(&account(0), &account(1)), 1_000);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient allowance: 0, minimum required allowance: 1")]
+fn disabled_operator_needs_an_allowance() {
+    let state = set_operator(context(account(0)), operator_state(), account(1), false);
+    assert!(state.operators.is_empty());
+    transfer_from(
+        context(account(1)),
+        state,
+        account(0),
+        account(2),
+        stored_amount(1),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Insufficient allowance: 0, minimum required allowance: 1")]
+fn operator_of_another_owner_needs_an_allowance() {
+    let (state, _) = transfer_from(
+        context(account(1)),
+        operator_state(),
+        account(0),
+        account(3),
+        stored_amount(10),
+    );
+    transfer_from(
+        context(account(1)),
+        state,
+        account(3),
+        account(2),
+        stored_amount(1),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Operator is already enabled.")]
+fn operator_cannot_be_enabled_twice() {
+    set_operator(context(account(0)), operator_state(), account(1), true);
+}
+
+#[test]
+#[should_panic(expected = "Operator is already disabled.")]
+fn unknown_operator_cannot_be_disabled() {
+    set_operator(context(account(0)), new_state(), account(1), false);
+}
+
+/// A token whose owner allotted 1_000 to account 1 and 2_000 to account 2 in one batch.
+fn bulk_approved_state() -> TashiTokenState {
+    bulk_approve(
+        context(account(0)),
+        new_state(),
+        vec![
+            SpenderAllowance {
+                spender: account(1),
+                amount: stored_amount(1_000),
+            },
+            SpenderAllowance {
+                spender: account(2),
+                amount: stored_amount(2_000),
+            },
+        ],
+    )
+}
+
+#[test]
+fn bulk_approve_sets_every_allowance() {
+    let state = bulk_approved_state();
+
+    assert_eq!(state.allowance(&account(0), &account(1)), 1_000);
+    assert_eq!(state.allowance(&account(0), &account(2)), 2_000);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY);
+}
+
+#[test]
+fn later_approval_of_the_same_spender_replaces_the_earlier() {
+    let state = bulk_approve(
+        context(account(0)),
+        bulk_approved_state(),
+        vec![
+            SpenderAllowance {
+                spender: account(1),
+                amount: stored_amount(500),
+            },
+            SpenderAllowance {
+                spender: account(1),
+                amount: stored_amount(0),
+            },
+        ],
+    );
+
+    assert_eq!(state.allowance(&account(0), &account(1)), 0);
+    assert_eq!(state.allowance(&account(0), &account(2)), 2_000);
+}
+
+#[test]
+fn bulk_transfer_from_spends_the_allowance_of_every_transfer() {
+    let (state, _) = bulk_transfer_from(
+        context(account(2)),
+        bulk_approved_state(),
+        account(0),
+        vec![
+            TransferTarget {
+                to: account(3),
+                amount: stored_amount(1_500),
+            },
+            TransferTarget {
+                to: account(4),
+                amount: stored_amount(500),
+            },
+        ],
+    );
+
+    assert_eq!(state.allowance(&account(0), &account(2)), 0);
+    assert_eq!(state.balance_of(&account(3)), 1_500);
+    assert_eq!(state.balance_of(&account(4)), 500);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 2_000);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient allowance: 500, minimum required allowance: 501")]
+fn bulk_transfer_from_beyond_the_allowance_panics() {
+    bulk_transfer_from(
+        context(account(1)),
+        bulk_approved_state(),
+        account(0),
+        vec![
+            TransferTarget {
+                to: account(3),
+                amount: stored_amount(500),
+            },
+            TransferTarget {
+                to: account(4),
+                amount: stored_amount(501),
+            },
+        ],
+    );
+}
+
+#[test]
+fn revoke_all_removes_every_allowance_and_operator() {
+    let state = set_operator(context(account(0)), bulk_approved_state(), account(3), true);
+    let state = revoke_all_allowances(context(account(0)), state);
+
+    assert_eq!(state.allowance(&account(0), &account(1)), 0);
+    assert_eq!(state.allowance(&account(0), &account(2)), 0);
+    assert!(!state.is_operator(&account(0), &account(3)));
+    assert!(state.authorized_spenders(&account(0)).is_empty());
+    assert!(state.allowed.is_empty());
+}
+
+#[test]
+fn revoke_removes_only_the_given_spenders() {
+    let state = set_operator(context(account(0)), bulk_approved_state(), account(3), true);
+    let state = revoke_allowances(
+        context(account(0)),
+        state,
+        vec![account(1), account(3), account(4)],
+    );
+
+    assert_eq!(state.allowance(&account(0), &account(1)), 0);
+    assert_eq!(state.allowance(&account(0), &account(2)), 2_000);
+    assert_eq!(state.authorized_spenders(&account(0)), vec![account(2)]);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient allowance: 0, minimum required allowance: 1")]
+fn revoked_operator_needs_an_allowance() {
+    let state = revoke_all_allowances(context(account(0)), operator_state());
+    transfer_from(
+        context(account(1)),
+        state,
+        account(0),
+        account(2),
+        stored_amount(1),
+    );
+}
@@ -0,0 +1,498 @@
+//! A constant product liquidity pool between the token and the native coin, giving the token
+//! on-chain liquidity from day one. The native coin is held through its MPC-20 contract, e.g. the
+//! BYOC twin of the chain's coin. Liquidity providers deposit both sides and receive pool shares,
+//! and swaps keep the product of the reserves constant after a fee that stays in the pool.
+//!
+//! The coin side of deposits and swaps is pulled with `transfer_from` and only used once the
+//! transfer is confirmed in a callback, priced at the reserves of that moment. A deposit takes
+//! shares for the smaller side of the current ratio, the excess of the other side staying in the
+//! pool. The first deposit locks [`MINIMUM_LIQUIDITY`] of its shares in the pool for good, so the
+//! pool can never be drained back to a handful of shares whose price is easy to inflate.
+#![cfg_attr(not(feature = "amm"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "amm")]
+use pbc_contract_common::context::{CallbackContext, ContractContext};
+#[cfg(feature = "amm")]
+use pbc_contract_common::events::EventGroup;
+#[cfg(feature = "amm")]
+use pbc_contract_common::shortname::ShortnameCallback;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "amm")]
+use crate::mpc20::{call_transfer, call_transfer_from};
+#[cfg(feature = "amm")]
+use crate::token::TashiTokenState;
+use crate::token::{mul_div, mul_div_wide, sqrt_of_product, BalanceMap, BPS_DENOMINATOR};
+
+#[cfg(all(test, feature = "amm"))]
+mod tests;
+
+/// Pool shares of the first deposit locked in the pool, owned by no provider.
+pub const MINIMUM_LIQUIDITY: u128 = 1_000;
+
+/// The highest swap fee the owner can configure, in basis points (1%).
+const MAX_SWAP_FEE_BPS: u16 = 100;
+
+/// Shortname of the callback confirming the coin of a deposit was received.
+#[cfg(feature = "amm")]
+const ADD_LIQUIDITY_CALLBACK_SHORTNAME: u32 = 0x0b;
+
+/// Shortname of the callback confirming the coin of a swap was received.
+#[cfg(feature = "amm")]
+const SWAP_COIN_FOR_TOKEN_CALLBACK_SHORTNAME: u32 = 0x0c;
+
+/// The liquidity pool.
+///
+/// ### Fields:
+///
+///   * `coin`: [`Address`], MPC-20 contract of the native coin.
+///   * `fee_bps`: [`u16`], fee charged on the input of every swap, in basis points.
+///   * `token_reserve`: [`u128`], tokens held by the pool.
+///   * `coin_reserve`: [`u128`], coins held by the pool.
+///   * `total_shares`: [`u128`], pool shares of all providers and the locked minimum liquidity.
+///   * `shares`: [`SortedVecMap`]<[`Address`], [`u128`]>, pool shares of each provider.
+///   * `pending_tokens`: [`u128`], tokens of deposits waiting for their coin to be confirmed.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AmmPool {
+    pub coin: Address,
+    pub fee_bps: u16,
+    pub token_reserve: u128,
+    pub coin_reserve: u128,
+    pub total_shares: u128,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub shares: SortedVecMap<Address, u128>,
+    pub pending_tokens: u128,
+}
+
+impl AmmPool {
+    /// Gets the pool shares of `provider`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `provider`: [`Address`], the liquidity provider.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount of shares.
+    pub fn shares_of(&self, provider: &Address) -> u128 {
+        self.shares.get(provider).copied().unwrap_or(0)
+    }
+
+    /// Computes the output of a swap of `amount_in` against the reserves, after the fee.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `amount_in`: [`u128`], amount put into the pool.
+    ///   * `reserve_in`: [`u128`], reserve of the input side.
+    ///   * `reserve_out`: [`u128`], reserve of the output side.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u128`] amount taken out of the pool.
+    pub fn swap_output(&self, amount_in: u128, reserve_in: u128, reserve_out: u128) -> u128 {
+        let after_fee = mul_div(
+            amount_in,
+            BPS_DENOMINATOR - u128::from(self.fee_bps),
+            BPS_DENOMINATOR,
+        );
+        let reserve_after = reserve_in
+            .checked_add(after_fee)
+            .expect("Overflow when adding to the pool reserve.");
+        mul_div_wide(reserve_out, after_fee, reserve_after)
+    }
+
+    /// Computes the shares a deposit of `tokens` and `coins` is worth at the current reserves. The
+    /// first deposit is worth the geometric mean of both amounts, less the [`MINIMUM_LIQUIDITY`]
+    /// locked in the pool.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `tokens`: [`u128`], tokens deposited.
+    ///   * `coins`: [`u128`], coins deposited.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u128`] shares added to the total, and the [`u128`] shares issued to the provider.
+    pub fn shares_for(&self, tokens: u128, coins: u128) -> (u128, u128) {
+        if self.total_shares == 0 {
+            let minted = sqrt_of_product(tokens, coins);
+            (minted, minted.saturating_sub(MINIMUM_LIQUIDITY))
+        } else {
+            let issued = mul_div_wide(tokens, self.total_shares, self.token_reserve)
+                .min(mul_div_wide(coins, self.total_shares, self.coin_reserve));
+            (issued, issued)
+        }
+    }
+
+    /// Adds a deposit of `tokens` and `coins` to the reserves, issuing shares to `provider`.
+    ///
+    /// Panics if the deposit is worth no shares or if adding it to the reserves overflows.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `provider`: [`Address`], the liquidity provider.
+    ///   * `tokens`: [`u128`], tokens deposited.
+    ///   * `coins`: [`u128`], coins deposited.
+    pub fn deposit(&mut self, provider: Address, tokens: u128, coins: u128) {
+        let (minted, issued) = self.shares_for(tokens, coins);
+        assert!(issued > 0, "Deposit is worth no pool shares.");
+        self.token_reserve = self
+            .token_reserve
+            .checked_add(tokens)
+            .expect("Overflow when adding to the pool reserve.");
+        self.coin_reserve = self
+            .coin_reserve
+            .checked_add(coins)
+            .expect("Overflow when adding to the pool reserve.");
+        self.total_shares += minted;
+        let shares = self.shares_of(&provider) + issued;
+        self.shares.insert_balance(provider, shares);
+    }
+}
+
+/// Set up the pool against the native coin contract `coin` with a swap fee of `fee_bps`, or change
+/// the fee of an existing pool. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner, if the fee exceeds the maximum, or if the coin contract
+/// changes while the pool holds liquidity.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `coin`: [`Address`], MPC-20 contract of the native coin.
+///   * `fee_bps`: [`u16`], fee charged on the input of every swap, in basis points.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "amm")]
+#[action(shortname = 0xb7)]
+fn set_amm_pool(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    coin: Address,
+    fee_bps: u16,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_amm_pool", coin, fee_bps);
+    assert!(
+        fee_bps <= MAX_SWAP_FEE_BPS,
+        "Swap fee: {}, maximum allowed swap fee: {}",
+        fee_bps,
+        MAX_SWAP_FEE_BPS
+    );
+    match state.amm_pool.as_mut() {
+        Some(pool) if pool.coin == coin => pool.fee_bps = fee_bps,
+        Some(pool) => {
+            assert!(
+                pool.total_shares == 0 && pool.pending_tokens == 0,
+                "Cannot change the coin of a pool holding liquidity."
+            );
+            pool.coin = coin;
+            pool.fee_bps = fee_bps;
+        }
+        None => {
+            state.amm_pool = Some(AmmPool {
+                coin,
+                fee_bps,
+                token_reserve: 0,
+                coin_reserve: 0,
+                total_shares: 0,
+                shares: SortedVecMap::new(),
+                pending_tokens: 0,
+            })
+        }
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Deposit `tokens` and `coins` into the pool. The caller must first approve this contract for
+/// `coins` on the coin contract. The tokens are taken right away and the shares issued once the
+/// coin transfer is confirmed, or the tokens returned if it fails.
+///
+/// Panics if no pool is set up, if either amount is zero, or if there is insufficient balance in
+/// caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `tokens`: [`u128`], tokens to deposit.
+///   * `coins`: [`u128`], coins to deposit.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the coin transfer interaction.
+#[cfg(feature = "amm")]
+#[action(shortname = 0xb8)]
+fn add_liquidity(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    tokens: u128,
+    coins: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "add_liquidity");
+    assert!(tokens > 0 && coins > 0, "Deposited amounts cannot be zero.");
+    state.escrow(&ctx, ctx.sender, tokens);
+    let pool = state.amm_pool.as_mut().expect("No pool is set up.");
+    pool.pending_tokens += tokens;
+
+    let mut event_group = EventGroup::builder();
+    call_transfer_from(
+        &mut event_group,
+        pool.coin,
+        ctx.sender,
+        ctx.contract_address,
+        coins,
+    );
+    event_group
+        .with_callback(ShortnameCallback::from_u32(
+            ADD_LIQUIDITY_CALLBACK_SHORTNAME,
+        ))
+        .argument(ctx.sender)
+        .argument(tokens)
+        .argument(coins)
+        .done();
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Issue the shares of a deposit once its coin transfer is confirmed, or return its tokens if the
+/// transfer failed. A deposit worth no shares at the reserves of that moment is returned in full.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the coin transfer.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `provider`: [`Address`], account that deposited.
+///   * `tokens`: [`u128`], tokens deposited.
+///   * `coins`: [`u128`], coins deposited.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the refund interaction, if any.
+#[cfg(feature = "amm")]
+#[callback(shortname = 0x0b)]
+fn add_liquidity_callback(
+    ctx: ContractContext,
+    callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+    provider: Address,
+    tokens: u128,
+    coins: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let pool = state.amm_pool.as_mut().unwrap();
+    pool.pending_tokens -= tokens;
+    let mut events = vec![];
+    if callback_ctx.success && pool.shares_for(tokens, coins).1 > 0 {
+        pool.deposit(provider, tokens, coins);
+    } else {
+        if callback_ctx.success {
+            let mut event_group = EventGroup::builder();
+            call_transfer(&mut event_group, pool.coin, provider, coins);
+            events.push(event_group.build());
+        }
+        state.release(&ctx, provider, tokens);
+    }
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Withdraw `shares` of the pool, receiving their part of both reserves.
+///
+/// Panics if no pool is set up, if `shares` is zero, or if the caller holds fewer shares.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `shares`: [`u128`], pool shares to withdraw.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the coin transfer interaction.
+#[cfg(feature = "amm")]
+#[action(shortname = 0xb9)]
+fn remove_liquidity(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    shares: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "remove_liquidity");
+    let pool = state.amm_pool.as_mut().expect("No pool is set up.");
+    assert!(shares > 0, "Withdrawn shares cannot be zero.");
+    let held = pool.shares_of(&ctx.sender);
+    assert!(
+        shares <= held,
+        "Insufficient pool shares: {}, minimum required pool shares: {}",
+        held,
+        shares
+    );
+    let tokens = mul_div_wide(pool.token_reserve, shares, pool.total_shares);
+    let coins = mul_div_wide(pool.coin_reserve, shares, pool.total_shares);
+    pool.token_reserve -= tokens;
+    pool.coin_reserve -= coins;
+    pool.total_shares -= shares;
+    pool.shares.insert_balance(ctx.sender, held - shares);
+    let coin = pool.coin;
+    state.release(&ctx, ctx.sender, tokens);
+
+    let mut event_group = EventGroup::builder();
+    call_transfer(&mut event_group, coin, ctx.sender, coins);
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Swap `amount` tokens of caller address for coins.
+///
+/// Panics if no pool is set up or holds no liquidity, if the output is below `min_out`, or if
+/// there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], tokens to swap.
+///   * `min_out`: [`u128`], fewest coins the caller accepts to receive.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the coin transfer interaction.
+#[cfg(feature = "amm")]
+#[action(shortname = 0xba)]
+fn swap_token_for_coin(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+    min_out: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "swap_token_for_coin");
+    state.escrow(&ctx, ctx.sender, amount);
+    let pool = state.amm_pool.as_mut().expect("No pool is set up.");
+    assert!(pool.total_shares > 0, "The pool holds no liquidity.");
+    let out = pool.swap_output(amount, pool.token_reserve, pool.coin_reserve);
+    assert!(
+        out >= min_out,
+        "Output: {}, below minimum output: {}",
+        out,
+        min_out
+    );
+    pool.token_reserve += amount;
+    pool.coin_reserve -= out;
+
+    let mut event_group = EventGroup::builder();
+    call_transfer(&mut event_group, pool.coin, ctx.sender, out);
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Swap `amount` coins of caller address for tokens. The caller must first approve this contract
+/// for `amount` on the coin contract. The swap is priced once the coin transfer is confirmed, and
+/// the coins are sent back if the output is then below `min_out`.
+///
+/// Panics if no pool is set up or if `amount` is zero.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], coins to swap.
+///   * `min_out`: [`u128`], fewest tokens the caller accepts to receive.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state and the coin transfer interaction.
+#[cfg(feature = "amm")]
+#[action(shortname = 0xbb)]
+fn swap_coin_for_token(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+    min_out: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "swap_coin_for_token");
+    let pool = state.amm_pool.as_ref().expect("No pool is set up.");
+    assert!(amount > 0, "Swapped amount cannot be zero.");
+
+    let mut event_group = EventGroup::builder();
+    call_transfer_from(
+        &mut event_group,
+        pool.coin,
+        ctx.sender,
+        ctx.contract_address,
+        amount,
+    );
+    event_group
+        .with_callback(ShortnameCallback::from_u32(
+            SWAP_COIN_FOR_TOKEN_CALLBACK_SHORTNAME,
+        ))
+        .argument(ctx.sender)
+        .argument(amount)
+        .argument(min_out)
+        .done();
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Pay the tokens of a coin swap once its coin transfer is confirmed, or send the coins back if the
+/// output at the current reserves is below the minimum. A failed transfer needs no rollback.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the coin transfer.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `trader`: [`Address`], account that swapped.
+///   * `amount`: [`u128`], coins swapped.
+///   * `min_out`: [`u128`], fewest tokens the trader accepts to receive.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the refund interaction, if any.
+#[cfg(feature = "amm")]
+#[callback(shortname = 0x0c)]
+fn swap_coin_for_token_callback(
+    ctx: ContractContext,
+    callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+    trader: Address,
+    amount: u128,
+    min_out: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    if !callback_ctx.success {
+        state.assert_invariants();
+        return (state, vec![]);
+    }
+    let pool = state.amm_pool.as_mut().unwrap();
+    let out = if pool.total_shares > 0 {
+        pool.swap_output(amount, pool.coin_reserve, pool.token_reserve)
+    } else {
+        0
+    };
+    let mut events = vec![];
+    if out > 0 && out >= min_out {
+        pool.coin_reserve += amount;
+        pool.token_reserve -= out;
+        state.release(&ctx, trader, out);
+    } else {
+        let mut event_group = EventGroup::builder();
+        call_transfer(&mut event_group, pool.coin, trader, amount);
+        events.push(event_group.build());
+    }
+    state.assert_invariants();
+
+    (state, events)
+}
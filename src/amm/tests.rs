@@ -0,0 +1,156 @@
+//! Tests of the liquidity pool: shares of deposits and the locked minimum liquidity, refunds of
+//! deposits that fail or are worth nothing, withdrawals, and pricing at 18 decimal magnitudes.
+
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+
+use super::{
+    add_liquidity, add_liquidity_callback, remove_liquidity, set_amm_pool, AmmPool,
+    MINIMUM_LIQUIDITY,
+};
+use crate::testing::{
+    account, callback_context, context, contract, new_state, token_address, INITIAL_SUPPLY,
+};
+use crate::token::TashiTokenState;
+
+/// One whole token or coin with 18 decimals.
+const WHOLE: u128 = 1_000_000_000_000_000_000;
+
+/// A token with an empty pool against coin contract 1, charging a 0.3% swap fee.
+fn pool_state() -> TashiTokenState {
+    set_amm_pool(context(account(0)), new_state(), contract(1), 30)
+}
+
+/// Deposits `tokens` and `coins` from the owner and confirms the coin transfer with `success`.
+fn deposit(
+    state: TashiTokenState,
+    tokens: u128,
+    coins: u128,
+    success: bool,
+) -> (TashiTokenState, usize) {
+    let (state, _) = add_liquidity(context(account(0)), state, tokens, coins);
+    let (state, events) = add_liquidity_callback(
+        context(token_address()),
+        callback_context(success),
+        state,
+        account(0),
+        tokens,
+        coins,
+    );
+    (state, events.len())
+}
+
+/// An empty pool with a 0.3% swap fee, outside of any token state.
+fn empty_pool() -> AmmPool {
+    AmmPool {
+        coin: contract(1),
+        fee_bps: 30,
+        token_reserve: 0,
+        coin_reserve: 0,
+        total_shares: 0,
+        shares: SortedVecMap::new(),
+        pending_tokens: 0,
+    }
+}
+
+#[test]
+fn first_deposit_locks_minimum_liquidity() {
+    let (state, refunds) = deposit(pool_state(), 4_000, 9_000, true);
+
+    let pool = state.amm_pool.as_ref().unwrap();
+    assert_eq!(refunds, 0);
+    assert_eq!(pool.total_shares, 6_000);
+    assert_eq!(pool.shares_of(&account(0)), 6_000 - MINIMUM_LIQUIDITY);
+    assert_eq!(pool.token_reserve, 4_000);
+    assert_eq!(pool.coin_reserve, 9_000);
+    assert_eq!(pool.pending_tokens, 0);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 4_000);
+}
+
+#[test]
+fn later_deposit_takes_shares_for_the_smaller_side() {
+    let (state, _) = deposit(pool_state(), 4_000, 9_000, true);
+    let (state, _) = deposit(state, 2_000, 9_000, true);
+
+    let pool = state.amm_pool.as_ref().unwrap();
+    assert_eq!(pool.total_shares, 9_000);
+    assert_eq!(pool.shares_of(&account(0)), 8_000);
+    assert_eq!(pool.coin_reserve, 18_000);
+}
+
+#[test]
+fn deposit_worth_no_shares_is_returned() {
+    let (state, refunds) = deposit(pool_state(), 100, 100, true);
+
+    let pool = state.amm_pool.as_ref().unwrap();
+    assert_eq!(refunds, 1);
+    assert_eq!(pool.total_shares, 0);
+    assert_eq!(pool.token_reserve, 0);
+    assert_eq!(pool.pending_tokens, 0);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY);
+}
+
+#[test]
+fn failed_coin_transfer_returns_the_tokens() {
+    let (state, refunds) = deposit(pool_state(), 4_000, 9_000, false);
+
+    let pool = state.amm_pool.as_ref().unwrap();
+    assert_eq!(refunds, 0);
+    assert_eq!(pool.total_shares, 0);
+    assert_eq!(pool.pending_tokens, 0);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY);
+}
+
+#[test]
+fn withdrawal_returns_its_part_of_the_reserves() {
+    let (state, _) = deposit(pool_state(), 4_000, 9_000, true);
+    let (state, events) = remove_liquidity(context(account(0)), state, 3_000);
+
+    let pool = state.amm_pool.as_ref().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(pool.total_shares, 3_000);
+    assert_eq!(pool.shares_of(&account(0)), 2_000);
+    assert_eq!(pool.token_reserve, 2_000);
+    assert_eq!(pool.coin_reserve, 4_500);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 2_000);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient pool shares: 5000, minimum required pool shares: 6000")]
+fn locked_minimum_liquidity_cannot_be_withdrawn() {
+    let (state, _) = deposit(pool_state(), 4_000, 9_000, true);
+    remove_liquidity(context(account(0)), state, 6_000);
+}
+
+#[test]
+fn deposits_at_18_decimals_do_not_overflow() {
+    let mut pool = empty_pool();
+    pool.deposit(account(1), 1_000_000_000 * WHOLE, 4_000_000_000 * WHOLE);
+    assert_eq!(pool.total_shares, 2_000_000_000 * WHOLE);
+    assert_eq!(
+        pool.shares_of(&account(1)),
+        2_000_000_000 * WHOLE - MINIMUM_LIQUIDITY
+    );
+
+    pool.deposit(account(2), 500_000_000 * WHOLE, 2_000_000_000 * WHOLE);
+    assert_eq!(pool.total_shares, 3_000_000_000 * WHOLE);
+    assert_eq!(pool.shares_of(&account(2)), 1_000_000_000 * WHOLE);
+}
+
+#[test]
+fn swap_output_at_18_decimals_does_not_overflow() {
+    let pool = empty_pool();
+    let output = pool.swap_output(
+        100_000_000 * WHOLE,
+        1_000_000_000 * WHOLE,
+        1_000_000_000 * WHOLE,
+    );
+    assert_eq!(output, 90_661_089_388_014_913_158_134_036);
+}
+
+#[test]
+#[should_panic(expected = "Overflow when adding to the pool reserve.")]
+fn deposit_overflowing_the_reserves_panics() {
+    let mut pool = empty_pool();
+    pool.deposit(account(1), u128::MAX / 2 + 1, WHOLE);
+    pool.deposit(account(2), u128::MAX / 2 + 1, WHOLE);
+}
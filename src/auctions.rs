@@ -0,0 +1,374 @@
+//! Sealed-bid auctions paid in the token, e.g. treasury auctions of an off-chain lot. Bidders
+//! commit the hash of their bid during the commit phase together with a deposit of at least the
+//! bid, and reveal the bid during the reveal phase. Once the reveal phase is over, the highest
+//! revealed bid wins and is paid to the seller, the rest of its deposit is refunded, and every
+//! other deposit is refunded in full, revealed or not. Equal bids are won by the earliest reveal.
+//!
+//! The commitment is built by [`bid_commitment`]. Deposits are public, so a deposit reveals an
+//! upper bound of the bid, and bidders hide their bid by depositing more than they bid. Bids
+//! hidden entirely need a zero knowledge contract, which this public contract cannot be.
+#![cfg_attr(not(feature = "auctions"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "auctions")]
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use pbc_contract_common::Hash;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "auctions")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "auctions"))]
+mod tests;
+
+/// The largest number of bids an auction accepts, bounding the cost of settling it.
+pub const MAX_AUCTION_BIDS: usize = 256;
+
+/// A sealed bid on an auction.
+///
+/// ### Fields:
+///
+///   * `commitment`: [`Hash`], the commitment to the bid, built by [`bid_commitment`].
+///   * `deposit`: [`u128`], tokens escrowed with the bid, at least the bid.
+///   * `revealed`: [`Option`]<[`u128`]>, the bid once revealed.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SealedBid {
+    pub commitment: Hash,
+    pub deposit: u128,
+    pub revealed: Option<u128>,
+}
+
+/// The status of an auction.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AuctionStatus {
+    /// Taking or revealing bids, or waiting to be settled.
+    #[discriminant(0)]
+    Open {},
+    /// Settled, `winner` paying `price` to the seller. `winner` is `None` if no bid was revealed.
+    #[discriminant(1)]
+    Settled {
+        winner: Option<Address>,
+        price: u128,
+    },
+}
+
+/// A sealed-bid auction.
+///
+/// ### Fields:
+///
+///   * `seller`: [`Address`], account that created the auction and is paid the winning bid.
+///   * `lot`: [`String`], description of what is auctioned.
+///   * `min_bid`: [`u128`], the lowest bid that can be revealed.
+///   * `commit_deadline`: [`i64`], block production time the commit phase ends and the reveal
+///     phase starts at, in milliseconds.
+///   * `reveal_deadline`: [`i64`], block production time the reveal phase ends at, in
+///     milliseconds.
+///   * `bids`: [`SortedVecMap`]<[`Address`], [`SealedBid`]>, bids of each bidder, cleared on
+///     settlement.
+///   * `highest_bidder`: [`Option`]<[`Address`]>, bidder of the highest revealed bid.
+///   * `highest_bid`: [`u128`], the highest revealed bid.
+///   * `status`: [`AuctionStatus`], whether the auction is open or settled.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Auction {
+    pub seller: Address,
+    pub lot: String,
+    pub min_bid: u128,
+    pub commit_deadline: i64,
+    pub reveal_deadline: i64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub bids: SortedVecMap<Address, SealedBid>,
+    pub highest_bidder: Option<Address>,
+    pub highest_bid: u128,
+    pub status: AuctionStatus,
+}
+
+/// Computes the commitment a bidder submits for a bid.
+///
+/// ### Parameters:
+///
+///   * `auction_id`: [`u64`], id of the auction.
+///   * `bidder`: [`Address`], account bidding.
+///   * `amount`: [`u128`], the bid.
+///   * `salt`: [`Vec`]<[`u8`]>, random bytes chosen by the bidder, keeping the bid from being
+///     guessed.
+///
+/// ### Returns:
+///
+/// The commitment [`Hash`].
+pub fn bid_commitment(auction_id: u64, bidder: &Address, amount: u128, salt: &[u8]) -> Hash {
+    let mut payload = auction_id.to_be_bytes().to_vec();
+    pbc_traits::ReadWriteRPC::rpc_write_to(bidder, &mut payload)
+        .expect("Writing to a vector cannot fail.");
+    payload.extend_from_slice(&amount.to_be_bytes());
+    payload.extend_from_slice(salt);
+    Hash::digest(payload)
+}
+
+#[cfg(feature = "auctions")]
+impl TashiTokenState {
+    /// Gets the open auction with id `auction_id`.
+    ///
+    /// Panics if there is no such auction or if it is settled.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `auction_id`: [`u64`], id of the auction.
+    ///
+    /// ### Returns:
+    ///
+    /// A mutable reference to the [`Auction`].
+    pub fn open_auction(&mut self, auction_id: u64) -> &mut Auction {
+        let auction = self
+            .auctions
+            .get_mut(&auction_id)
+            .unwrap_or_else(|| panic!("No auction with id: {}", auction_id));
+        assert!(
+            auction.status == AuctionStatus::Open {},
+            "Auction is already settled."
+        );
+        auction
+    }
+}
+
+/// Create a sealed-bid auction of `lot` paid to caller address. The id of the auction is the
+/// `next_auction_id` of the state before the call.
+///
+/// Panics if the commit deadline has already passed or if the reveal deadline is not after it.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `lot`: [`String`], description of what is auctioned.
+///   * `min_bid`: [`u128`], the lowest bid that can be revealed.
+///   * `commit_deadline`: [`i64`], block production time the commit phase ends at, in
+///     milliseconds.
+///   * `reveal_deadline`: [`i64`], block production time the reveal phase ends at, in
+///     milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "auctions")]
+#[action(shortname = 0x78)]
+fn create_auction(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    lot: String,
+    min_bid: u128,
+    commit_deadline: i64,
+    reveal_deadline: i64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "create_auction");
+    assert!(
+        commit_deadline > ctx.block_production_time,
+        "Commit deadline has already passed."
+    );
+    assert!(
+        reveal_deadline > commit_deadline,
+        "Reveal deadline must be after the commit deadline."
+    );
+
+    let auction_id = state.next_auction_id;
+    state.next_auction_id += 1;
+    state.auctions.insert(
+        auction_id,
+        Auction {
+            seller: ctx.sender,
+            lot,
+            min_bid,
+            commit_deadline,
+            reveal_deadline,
+            bids: SortedVecMap::new(),
+            highest_bidder: None,
+            highest_bid: 0,
+            status: AuctionStatus::Open {},
+        },
+    );
+    state.assert_invariants();
+
+    state
+}
+
+/// Commit a sealed bid of caller address on auction `auction_id`, escrowing `deposit` tokens. The
+/// deposit must cover the bid that will be revealed.
+///
+/// Panics if the auction is not open, if the commit phase is over, if the caller is the seller or
+/// already bid, if the auction has [`MAX_AUCTION_BIDS`] bids, if `deposit` is zero, or if there is
+/// insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `auction_id`: [`u64`], id of the auction.
+///   * `commitment`: [`Hash`], the commitment to the bid, built by [`bid_commitment`].
+///   * `deposit`: [`u128`], tokens to escrow with the bid.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "auctions")]
+#[action(shortname = 0x79)]
+fn commit_bid(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    auction_id: u64,
+    commitment: Hash,
+    deposit: u128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "commit_bid");
+    assert!(deposit > 0, "Bid deposit cannot be zero.");
+    let auction = state.open_auction(auction_id);
+    assert!(
+        ctx.block_production_time < auction.commit_deadline,
+        "Commit phase ended at: {}",
+        auction.commit_deadline
+    );
+    assert!(
+        auction.seller != ctx.sender,
+        "The seller cannot bid on its auction."
+    );
+    assert!(
+        !auction.bids.contains_key(&ctx.sender),
+        "Caller already bid on the auction."
+    );
+    assert!(
+        auction.bids.len() < MAX_AUCTION_BIDS,
+        "Auction already has: {} bids",
+        MAX_AUCTION_BIDS
+    );
+    auction.bids.insert(
+        ctx.sender,
+        SealedBid {
+            commitment,
+            deposit,
+            revealed: None,
+        },
+    );
+    state.escrow(&ctx, ctx.sender, deposit); // escrow the deposit
+    state.assert_invariants();
+
+    state
+}
+
+/// Reveal the bid of caller address on auction `auction_id`.
+///
+/// Panics if the auction is not open, if it is not in its reveal phase, if the caller has no
+/// unrevealed bid, if `amount` and `salt` do not match the commitment, or if `amount` is below the
+/// minimum bid or above the deposit.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `auction_id`: [`u64`], id of the auction.
+///   * `amount`: [`u128`], the bid.
+///   * `salt`: [`Vec`]<[`u8`]>, the salt of the commitment.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "auctions")]
+#[action(shortname = 0x7a)]
+fn reveal_bid(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    auction_id: u64,
+    amount: u128,
+    salt: Vec<u8>,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "reveal_bid");
+    let auction = state.open_auction(auction_id);
+    let now = ctx.block_production_time;
+    assert!(
+        now >= auction.commit_deadline && now < auction.reveal_deadline,
+        "Reveal phase is from: {} to: {}",
+        auction.commit_deadline,
+        auction.reveal_deadline
+    );
+    assert!(
+        amount >= auction.min_bid,
+        "Bid: {}, is below the minimum bid: {}",
+        amount,
+        auction.min_bid
+    );
+    let bid = auction
+        .bids
+        .get_mut(&ctx.sender)
+        .expect("Caller has no bid on the auction.");
+    assert!(bid.revealed.is_none(), "Bid is already revealed.");
+    assert!(
+        bid.commitment == bid_commitment(auction_id, &ctx.sender, amount, &salt),
+        "Bid does not match its commitment."
+    );
+    assert!(
+        amount <= bid.deposit,
+        "Bid: {}, exceeds its deposit: {}",
+        amount,
+        bid.deposit
+    );
+    bid.revealed = Some(amount);
+
+    if auction.highest_bidder.is_none() || amount > auction.highest_bid {
+        auction.highest_bidder = Some(ctx.sender);
+        auction.highest_bid = amount;
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Settle auction `auction_id` once its reveal phase is over, paying the winning bid to the seller
+/// and refunding every deposit beyond it. Anyone can call this action.
+///
+/// Panics if the auction is not open or if its reveal phase is not over.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `auction_id`: [`u64`], id of the auction.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "auctions")]
+#[action(shortname = 0x7b)]
+fn settle_auction(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    auction_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "settle_auction");
+    let auction = state.open_auction(auction_id);
+    assert!(
+        ctx.block_production_time >= auction.reveal_deadline,
+        "Reveal phase ends at: {}",
+        auction.reveal_deadline
+    );
+    let seller = auction.seller;
+    let winner = auction.highest_bidder;
+    let price = auction.highest_bid;
+    auction.status = AuctionStatus::Settled { winner, price };
+    let bids = std::mem::replace(&mut auction.bids, SortedVecMap::new());
+
+    for (bidder, bid) in bids.iter() {
+        let refund = if Some(*bidder) == winner {
+            bid.deposit - price
+        } else {
+            bid.deposit
+        };
+        state.release(&ctx, *bidder, refund);
+    }
+    state.payout(&ctx, winner.unwrap_or(seller), seller, price);
+    state.assert_invariants();
+
+    state
+}
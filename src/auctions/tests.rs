@@ -0,0 +1,146 @@
+//! Tests of sealed-bid auctions: the winner paying its bid, refunds of every other deposit,
+//! equal bids, and reveals which do not match their commitment or deposit.
+
+use super::{
+    bid_commitment, commit_bid, create_auction, reveal_bid, settle_auction, AuctionStatus,
+};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state};
+use crate::token::TashiTokenState;
+
+/// Block production time the commit phase ends at.
+const COMMIT_DEADLINE: i64 = 1_000;
+
+/// Block production time the reveal phase ends at.
+const REVEAL_DEADLINE: i64 = 2_000;
+
+/// A token where accounts 1 to 3 hold 10_000 each and account 5 auctions a lot with a minimum bid
+/// of 100.
+fn auction_state() -> TashiTokenState {
+    let mut state = new_state();
+    for index in 1..=3 {
+        state.execute_transfer(
+            &context(account(0)),
+            Transfer::new(account(0), account(index), 10_000, None),
+        );
+    }
+    create_auction(
+        context(account(5)),
+        state,
+        "Lot 1".to_string(),
+        100,
+        COMMIT_DEADLINE,
+        REVEAL_DEADLINE,
+    )
+}
+
+/// Commits a bid of `amount` from `bidder` on auction 0, depositing `deposit`.
+fn commit(state: TashiTokenState, bidder: u8, amount: u128, deposit: u128) -> TashiTokenState {
+    let commitment = bid_commitment(0, &account(bidder), amount, &[bidder]);
+    commit_bid(context(account(bidder)), state, 0, commitment, deposit)
+}
+
+/// Reveals the bid of `amount` from `bidder` on auction 0.
+fn reveal(state: TashiTokenState, bidder: u8, amount: u128) -> TashiTokenState {
+    reveal_bid(
+        context_at(account(bidder), COMMIT_DEADLINE),
+        state,
+        0,
+        amount,
+        vec![bidder],
+    )
+}
+
+/// Settles auction 0 once its reveal phase is over.
+fn settle(state: TashiTokenState) -> TashiTokenState {
+    settle_auction(context_at(account(4), REVEAL_DEADLINE), state, 0)
+}
+
+#[test]
+fn highest_bid_is_paid_and_every_other_deposit_refunded() {
+    let state = commit(auction_state(), 1, 500, 800);
+    let state = commit(state, 2, 600, 600);
+    let state = commit(state, 3, 300, 400);
+    assert_eq!(state.balance_of(&account(1)), 9_200);
+
+    let state = reveal(state, 1, 500);
+    let state = reveal(state, 2, 600);
+    let state = settle(state);
+
+    assert_eq!(state.balance_of(&account(5)), 600);
+    assert_eq!(state.balance_of(&account(1)), 10_000);
+    assert_eq!(state.balance_of(&account(2)), 9_400);
+    assert_eq!(state.balance_of(&account(3)), 10_000);
+    let auction = state.auctions.get(&0).unwrap();
+    assert!(
+        auction.status
+            == AuctionStatus::Settled {
+                winner: Some(account(2)),
+                price: 600
+            }
+    );
+    assert!(auction.bids.is_empty());
+}
+
+#[test]
+fn equal_bids_are_won_by_the_earliest_reveal() {
+    let state = commit(auction_state(), 1, 500, 500);
+    let state = commit(state, 2, 500, 900);
+    let state = reveal(state, 2, 500);
+    let state = reveal(state, 1, 500);
+    let state = settle(state);
+
+    assert_eq!(state.balance_of(&account(1)), 10_000);
+    assert_eq!(state.balance_of(&account(2)), 9_500);
+}
+
+#[test]
+fn auction_without_reveals_refunds_everyone() {
+    let state = settle(commit(auction_state(), 1, 500, 800));
+
+    assert_eq!(state.balance_of(&account(1)), 10_000);
+    assert_eq!(state.balance_of(&account(5)), 0);
+}
+
+#[test]
+#[should_panic(expected = "Bid does not match its commitment.")]
+fn reveal_of_another_bid_is_rejected() {
+    let state = commit(auction_state(), 1, 500, 800);
+    reveal(state, 1, 700);
+}
+
+#[test]
+#[should_panic(expected = "Bid: 900, exceeds its deposit: 800")]
+fn bid_above_its_deposit_is_rejected() {
+    let state = commit(auction_state(), 1, 900, 800);
+    reveal(state, 1, 900);
+}
+
+#[test]
+#[should_panic(expected = "Commit phase ended at: 1000")]
+fn bid_after_the_commit_phase_is_rejected() {
+    let commitment = bid_commitment(0, &account(1), 500, &[1]);
+    commit_bid(
+        context_at(account(1), COMMIT_DEADLINE),
+        auction_state(),
+        0,
+        commitment,
+        500,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Reveal phase ends at: 2000")]
+fn auction_is_not_settled_during_the_reveal_phase() {
+    settle_auction(
+        context_at(account(4), REVEAL_DEADLINE - 1),
+        auction_state(),
+        0,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Auction is already settled.")]
+fn auction_is_settled_once() {
+    settle(settle(commit(auction_state(), 1, 500, 800)));
+}
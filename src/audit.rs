@@ -0,0 +1,73 @@
+//! An audit log of privileged actions. Every action of the owner, of a role or executed by
+//! governance is recorded with its caller, the hash of its arguments and the block time. The log
+//! keeps the latest [`ADMIN_LOG_CAPACITY`] entries.
+#![cfg_attr(not(feature = "audit-log"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::Hash;
+use read_write_state_derive::ReadWriteState;
+
+use crate::token::TashiTokenState;
+
+/// The number of entries the admin log keeps, older entries are dropped.
+pub const ADMIN_LOG_CAPACITY: usize = 256;
+
+/// A privileged action recorded in the admin log.
+///
+/// ### Fields:
+///
+///   * `actor`: [`Address`], account that called the action.
+///   * `action`: [`String`], name of the action.
+///   * `args_hash`: [`Hash`], hash of the RPC serialized arguments of the action.
+///   * `timestamp`: [`i64`], block production time of the action, in milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdminLogEntry {
+    pub actor: Address,
+    pub action: String,
+    pub args_hash: Hash,
+    pub timestamp: i64,
+}
+
+/// Records a privileged action in the admin log, hashing the RPC serialization of its arguments
+/// in order, and counts it in the usage metrics. Does nothing without the `audit-log` and
+/// `usage-metrics` features.
+macro_rules! log_admin_action {
+    ($state:expr, $ctx:expr, $action:expr $(, $arg:expr)* $(,)?) => {
+        crate::metrics::count_action!($state, $action);
+        if cfg!(feature = "audit-log") {
+            #[allow(unused_mut)]
+            let mut args: Vec<u8> = Vec::new();
+            $(
+                pbc_traits::ReadWriteRPC::rpc_write_to(&$arg, &mut args)
+                    .expect("Writing to a vector cannot fail.");
+            )*
+            $state.record_admin_action(&$ctx, $action, pbc_contract_common::Hash::digest(args));
+        }
+    };
+}
+pub(crate) use log_admin_action;
+
+impl TashiTokenState {
+    /// Appends an entry to the admin log, dropping the oldest entry when the log is full.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `action`: [`str`], name of the action.
+    ///   * `args_hash`: [`Hash`], hash of the RPC serialized arguments of the action.
+    pub fn record_admin_action(&mut self, ctx: &ContractContext, action: &str, args_hash: Hash) {
+        if self.admin_log.len() >= ADMIN_LOG_CAPACITY {
+            self.admin_log.remove(0);
+        }
+        self.admin_log.push(AdminLogEntry {
+            actor: ctx.sender,
+            action: action.to_string(),
+            args_hash,
+            timestamp: ctx.block_production_time,
+        });
+        self.admin_actions_recorded += 1;
+    }
+}
@@ -0,0 +1,123 @@
+//! Merkle roots of the balances, letting light clients and layer 2 systems verify the balance of
+//! an account with a compact proof against a root recorded on-chain. The owner or a keeper records
+//! the root of the current balances, and the latest [`BALANCE_ROOT_HISTORY`] roots are kept with
+//! the block they were computed at.
+//!
+//! The leaves are the accounts with a balance, ordered by address, each the SHA-256 hash of `0x00
+//! || address || balance` with the address in its RPC encoding and the balance as a big-endian
+//! `u128`. A node is the SHA-256 hash of `0x01 || left || right`, and the last node of a level
+//! without a sibling moves up unchanged. The root of no balances is the hash of the empty string.
+#![cfg_attr(not(feature = "balance-roots"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+#[cfg(feature = "balance-roots")]
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::Hash;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "balance-roots")]
+use crate::admin::Role;
+use crate::shards::BalanceEntries;
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "balance-roots"))]
+mod tests;
+
+/// The number of balance roots kept, older roots are dropped.
+pub const BALANCE_ROOT_HISTORY: usize = 16;
+
+/// A merkle root of the balances.
+///
+/// ### Fields:
+///
+///   * `root`: [`Hash`], root of the balances.
+///   * `holders`: [`u32`], number of leaves, accounts with a balance.
+///   * `block_time`: [`i64`], block the root was computed at.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BalanceRoot {
+    pub root: Hash,
+    pub holders: u32,
+    pub block_time: i64,
+}
+
+/// Hashes a node of the tree from its two children.
+fn hash_node(left: &Hash, right: &Hash) -> Hash {
+    let mut node = vec![1u8];
+    node.extend_from_slice(&left.bytes);
+    node.extend_from_slice(&right.bytes);
+    Hash::digest(node)
+}
+
+impl TashiTokenState {
+    /// Computes the merkle root of the current balances, as described in the module
+    /// documentation.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`Hash`] root and the [`u32`] number of leaves.
+    pub fn balance_root(&self) -> (Hash, u32) {
+        let mut level: Vec<Hash> = self
+            .balances
+            .entries()
+            .iter()
+            .map(|(account, _)| {
+                let mut leaf = vec![0u8];
+                pbc_traits::ReadWriteRPC::rpc_write_to(account, &mut leaf)
+                    .expect("Writing to a vector cannot fail.");
+                leaf.extend_from_slice(&self.balance_of(account).to_be_bytes());
+                Hash::digest(leaf)
+            })
+            .collect();
+        let holders = level.len() as u32;
+        if level.is_empty() {
+            return (Hash::digest(Vec::new()), holders);
+        }
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_node(left, right),
+                    [single] => single.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        (level.remove(0), holders)
+    }
+}
+
+/// Record the merkle root of the current balances, dropping the oldest root when
+/// [`BALANCE_ROOT_HISTORY`] roots are kept. Only the owner and accounts with the [`Role::Keeper`]
+/// role can call this action.
+///
+/// Panics if the caller is neither the owner nor has the role.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "balance-roots")]
+#[action(shortname = 0xdb)]
+fn record_balance_root(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "record_balance_root");
+    if !state.has_role(Role::Keeper {}, &ctx.sender) {
+        state.assert_owner(&ctx.sender);
+    }
+    let (root, holders) = state.balance_root();
+    if state.balance_roots.len() >= BALANCE_ROOT_HISTORY {
+        state.balance_roots.remove(0);
+    }
+    state.balance_roots.push(BalanceRoot {
+        root,
+        holders,
+        block_time: ctx.block_time,
+    });
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,137 @@
+//! Tests of the merkle roots of the balances: the roots of no, one and an odd number of holders,
+//! proofs of a balance checked against a recorded root, and the bounded history of roots.
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::Hash;
+
+use super::{hash_node, record_balance_root, BALANCE_ROOT_HISTORY};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::{stored_amount, BalanceMap, TashiTokenState};
+
+/// The leaf of `account` holding `balance`, as described in the module documentation.
+fn leaf(account: &Address, balance: u128) -> Hash {
+    let mut leaf = vec![0x00, 0x00];
+    leaf.extend_from_slice(&account.identifier);
+    leaf.extend_from_slice(&balance.to_be_bytes());
+    Hash::digest(leaf)
+}
+
+/// Computes the root `proof` leads to from `leaf`, at position `index` among the leaves. Each
+/// step of the proof is the sibling of the node on the path, `None` where the node has no sibling
+/// and moves up unchanged.
+fn root_of_proof(leaf: Hash, mut index: usize, proof: &[Option<Hash>]) -> Hash {
+    let mut node = leaf;
+    for sibling in proof {
+        if let Some(sibling) = sibling {
+            node = if index % 2 == 0 {
+                hash_node(&node, sibling)
+            } else {
+                hash_node(sibling, &node)
+            };
+        }
+        index /= 2;
+    }
+    node
+}
+
+/// A token where the owner sent 10 tokens to each account from 1 to `holders - 1`.
+fn state_with_holders(holders: u8) -> TashiTokenState {
+    let mut state = new_state();
+    for index in 1..holders {
+        state.execute_transfer(
+            &context(account(0)),
+            Transfer::new(account(0), account(index), 10, None),
+        );
+    }
+    state
+}
+
+#[test]
+fn root_of_no_balances_is_the_hash_of_the_empty_string() {
+    let mut state = new_state();
+    state.balances.insert_balance(account(0), stored_amount(0));
+
+    assert_eq!(state.balance_root(), (Hash::digest(Vec::new()), 0));
+}
+
+#[test]
+fn root_of_a_single_holder_is_its_leaf() {
+    let state = new_state();
+
+    assert_eq!(state.balance_root(), (leaf(&account(0), INITIAL_SUPPLY), 1));
+}
+
+#[test]
+fn last_node_of_an_odd_level_moves_up() {
+    let state = state_with_holders(3);
+    let owner = leaf(&account(0), INITIAL_SUPPLY - 20);
+    let expected = hash_node(
+        &hash_node(&owner, &leaf(&account(1), 10)),
+        &leaf(&account(2), 10),
+    );
+
+    assert_eq!(state.balance_root(), (expected, 3));
+}
+
+#[test]
+fn proofs_verify_against_the_recorded_root() {
+    // five leaves: the fifth moves up twice before it is paired with the root of the first four
+    let state = record_balance_root(context(account(0)), state_with_holders(5));
+    let recorded = &state.balance_roots[0];
+    let leaves: Vec<Hash> = (0..5)
+        .map(|index| {
+            leaf(
+                &account(index),
+                if index == 0 { INITIAL_SUPPLY - 40 } else { 10 },
+            )
+        })
+        .collect();
+    let first_pair = hash_node(&leaves[0], &leaves[1]);
+    let second_pair = hash_node(&leaves[2], &leaves[3]);
+    let first_four = hash_node(&first_pair, &second_pair);
+
+    assert_eq!(recorded.holders, 5);
+    assert_eq!(
+        root_of_proof(
+            leaves[1].clone(),
+            1,
+            &[
+                Some(leaves[0].clone()),
+                Some(second_pair),
+                Some(leaves[4].clone())
+            ]
+        ),
+        recorded.root
+    );
+    assert_eq!(
+        root_of_proof(
+            leaves[4].clone(),
+            4,
+            &[None, None, Some(first_four.clone())]
+        ),
+        recorded.root
+    );
+    // a proof of another balance does not lead to the root
+    assert_ne!(
+        root_of_proof(leaf(&account(4), 11), 4, &[None, None, Some(first_four)]),
+        recorded.root
+    );
+}
+
+#[test]
+fn oldest_root_is_dropped_from_a_full_history() {
+    let mut state = state_with_holders(2);
+    for time in 0..=BALANCE_ROOT_HISTORY as i64 {
+        state = record_balance_root(context_at(account(0), time), state);
+    }
+
+    assert_eq!(state.balance_roots.len(), BALANCE_ROOT_HISTORY);
+    assert_eq!(state.balance_roots[0].root, state.balance_root().0);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can perform this action.")]
+fn only_the_owner_or_a_keeper_records_a_root() {
+    record_balance_root(context(account(1)), new_state());
+}
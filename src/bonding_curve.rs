@@ -0,0 +1,313 @@
+//! A linear bonding curve, minting tokens against a reserve of another MPC-20 token and burning
+//! them to return the reserve.
+#![cfg_attr(not(feature = "bonding-curve"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "bonding-curve")]
+use pbc_contract_common::context::{CallbackContext, ContractContext};
+#[cfg(feature = "bonding-curve")]
+use pbc_contract_common::events::EventGroup;
+#[cfg(feature = "bonding-curve")]
+use pbc_contract_common::shortname::ShortnameCallback;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "bonding-curve")]
+use crate::mpc20::{call_transfer, call_transfer_from};
+#[cfg(feature = "bonding-curve")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "bonding-curve"))]
+mod tests;
+
+/// Fixed point scale of curve prices, prices are in reserve units per `PRICE_SCALE` tokens.
+const PRICE_SCALE: u128 = 1_000_000_000_000_000_000;
+
+/// Shortname of the callback confirming a curve purchase was paid.
+#[cfg(feature = "bonding-curve")]
+const BUY_FROM_CURVE_CALLBACK_SHORTNAME: u32 = 0x02;
+
+/// A linear bonding curve where the price of the next token is `base_price + slope * supply`.
+///
+/// ### Fields:
+///
+///   * `reserve_token`: [`Address`], MPC-20 token the curve is priced in.
+///   * `base_price`: [`u128`], price of the first token, scaled by `PRICE_SCALE`.
+///   * `slope`: [`u128`], price increase per token minted, scaled by `PRICE_SCALE`.
+///   * `supply`: [`u128`], tokens minted by the curve and not yet sold back.
+///   * `reserve`: [`u128`], amount of the reserve token held for the curve.
+///   * `pending`: [`u128`], tokens of purchases waiting for their payment to be confirmed.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BondingCurve {
+    pub reserve_token: Address,
+    pub base_price: u128,
+    pub slope: u128,
+    pub supply: u128,
+    pub reserve: u128,
+    pub pending: u128,
+}
+
+impl BondingCurve {
+    /// Gets the scaled area under the curve between `supply` and `supply + amount`.
+    ///
+    /// Panics if the calculation overflows.
+    fn scaled_cost(&self, supply: u128, amount: u128) -> u128 {
+        // integral of base_price + slope * s from supply to supply + amount
+        let span = supply
+            .checked_mul(2)
+            .and_then(|double_supply| double_supply.checked_add(amount))
+            .and_then(|span| span.checked_mul(amount))
+            .expect("Overflow when pricing the bonding curve.");
+        let linear = self
+            .slope
+            .checked_mul(span / 2)
+            .and_then(|linear| linear.checked_add(self.slope * (span % 2) / 2))
+            .expect("Overflow when pricing the bonding curve.");
+        amount
+            .checked_mul(self.base_price)
+            .and_then(|base| base.checked_add(linear))
+            .expect("Overflow when pricing the bonding curve.")
+    }
+
+    /// Gets the reserve paid for minting `amount` tokens at the current supply, rounded up.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `amount`: [`u128`], amount of tokens to mint.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount of the reserve token.
+    pub fn buy_cost(&self, amount: u128) -> u128 {
+        self.scaled_cost(self.supply, amount).div_ceil(PRICE_SCALE)
+    }
+
+    /// Gets the reserve returned for burning `amount` tokens at the current supply, rounded down.
+    ///
+    /// Panics if `amount` exceeds the curve supply.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `amount`: [`u128`], amount of tokens to burn.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount of the reserve token.
+    pub fn sell_return(&self, amount: u128) -> u128 {
+        let supply = self
+            .supply
+            .checked_sub(amount)
+            .expect("Amount exceeds the bonding curve supply.");
+        self.scaled_cost(supply, amount) / PRICE_SCALE
+    }
+}
+
+/// Configure the bonding curve. Only the owner can call this action, and only while the curve has
+/// no supply.
+///
+/// Panics if the caller is not the owner or if the curve already has supply.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `reserve_token`: [`Address`], MPC-20 token the curve is priced in.
+///   * `base_price`: [`u128`], price of the first token, scaled by 10^18.
+///   * `slope`: [`u128`], price increase per token minted, scaled by 10^18.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "bonding-curve")]
+#[action(shortname = 0x22)]
+fn configure_bonding_curve(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    reserve_token: Address,
+    base_price: u128,
+    slope: u128,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(
+        state,
+        ctx,
+        "configure_bonding_curve",
+        reserve_token,
+        base_price,
+        slope
+    );
+    if let Some(curve) = &state.bonding_curve {
+        assert!(
+            curve.supply == 0 && curve.pending == 0,
+            "Bonding curve already has supply."
+        );
+    }
+    state.bonding_curve = Some(BondingCurve {
+        reserve_token,
+        base_price,
+        slope,
+        supply: 0,
+        reserve: 0,
+        pending: 0,
+    });
+    state.assert_invariants();
+
+    state
+}
+
+/// Mint `amount` tokens from the bonding curve to caller address, paying the reserve token. The
+/// caller must first approve this contract to spend `max_cost` on the reserve token contract.
+///
+/// Panics if no curve is configured or if the cost exceeds `max_cost`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount of tokens to mint.
+///   * `max_cost`: [`u128`], highest amount of the reserve token the caller accepts to pay.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the payment interaction.
+#[cfg(feature = "bonding-curve")]
+#[action(shortname = 0x23)]
+fn buy_from_curve(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+    max_cost: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "buy_from_curve");
+    let curve = state
+        .bonding_curve
+        .as_mut()
+        .expect("No bonding curve is configured.");
+    assert!(amount > 0, "Purchase amount cannot be zero.");
+    let cost = curve.buy_cost(amount);
+    assert!(
+        cost <= max_cost,
+        "Cost: {}, exceeds maximum cost: {}",
+        cost,
+        max_cost
+    );
+    // price later trades as if the purchase went through until it is confirmed
+    curve.supply += amount;
+    curve.pending += amount;
+
+    let mut event_group = EventGroup::builder();
+    call_transfer_from(
+        &mut event_group,
+        curve.reserve_token,
+        ctx.sender,
+        ctx.contract_address,
+        cost,
+    );
+    event_group
+        .with_callback(ShortnameCallback::from_u32(
+            BUY_FROM_CURVE_CALLBACK_SHORTNAME,
+        ))
+        .argument(ctx.sender)
+        .argument(amount)
+        .argument(cost)
+        .done();
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Mint the tokens of a curve purchase once its payment is confirmed, or release them if the
+/// payment failed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the payment interaction.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `buyer`: [`Address`], account that bought tokens.
+///   * `amount`: [`u128`], amount of tokens bought.
+///   * `cost`: [`u128`], amount of the reserve token paid.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "bonding-curve")]
+#[callback(shortname = 0x02)]
+fn buy_from_curve_callback(
+    _ctx: ContractContext,
+    callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+    buyer: Address,
+    amount: u128,
+    cost: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let supply = state.supply();
+    let curve = state.bonding_curve.as_mut().unwrap();
+    curve.pending -= amount;
+    if callback_ctx.success {
+        curve.reserve += cost;
+        state.mint_balance(buyer, amount);
+    } else {
+        curve.supply -= amount;
+    }
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Burn `amount` tokens of caller address into the bonding curve, returning reserve tokens.
+///
+/// Panics if no curve is configured, if a purchase is waiting for its payment, if the return is
+/// below `min_return`, or if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount of tokens to burn.
+///   * `min_return`: [`u128`], lowest amount of the reserve token the caller accepts to receive.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state, the payout interaction and the supply notifications.
+#[cfg(feature = "bonding-curve")]
+#[action(shortname = 0x24)]
+fn sell_to_curve(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+    min_return: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "sell_to_curve");
+    let curve = state
+        .bonding_curve
+        .as_mut()
+        .expect("No bonding curve is configured.");
+    // pending purchases could still fail, which would leave the reserve short
+    assert!(curve.pending == 0, "A bonding curve purchase is pending.");
+    let payout = curve.sell_return(amount);
+    assert!(
+        payout >= min_return,
+        "Return: {}, below minimum return: {}",
+        payout,
+        min_return
+    );
+    curve.supply -= amount;
+    curve.reserve -= payout;
+    let reserve_token = curve.reserve_token;
+    let supply = state.supply();
+    state.assert_can_spend(&ctx, &ctx.sender, amount);
+    state.burn_balance(ctx.sender, amount, ctx.block_production_time);
+
+    let mut event_group = EventGroup::builder();
+    call_transfer(&mut event_group, reserve_token, ctx.sender, payout);
+
+    let mut events = vec![event_group.build()];
+    events.extend(state.supply_change_events(supply));
+    state.assert_invariants();
+
+    (state, events)
+}
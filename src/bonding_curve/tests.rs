@@ -0,0 +1,103 @@
+//! Tests of the bonding curve: purchases minted once paid, sales returning the reserve, and
+//! rounding in favour of the reserve.
+
+use super::{buy_from_curve, buy_from_curve_callback, configure_bonding_curve, sell_to_curve};
+use crate::testing::{
+    account, callback_context, context, contract, new_state, token_address, INITIAL_SUPPLY,
+};
+use crate::token::TashiTokenState;
+
+/// A token on a curve in the reserve token of contract 1, starting at one unit per token and
+/// rising by a hundredth of a unit per token minted.
+fn curve_state() -> TashiTokenState {
+    configure_bonding_curve(
+        context(account(0)),
+        new_state(),
+        contract(1),
+        1_000_000_000_000_000_000,
+        10_000_000_000_000_000,
+    )
+}
+
+/// Buys `amount` tokens for account 1 at a cost of `cost` and confirms the payment with
+/// `success`.
+fn bought_state(amount: u128, cost: u128, success: bool) -> TashiTokenState {
+    let (state, events) = buy_from_curve(context(account(1)), curve_state(), amount, cost);
+    assert_eq!(events.len(), 1);
+    buy_from_curve_callback(
+        context(token_address()),
+        callback_context(success),
+        state,
+        account(1),
+        amount,
+        cost,
+    )
+    .0
+}
+
+#[test]
+fn purchase_is_minted_once_paid() {
+    let state = bought_state(100, 150, true);
+
+    let curve = state.bonding_curve.as_ref().unwrap();
+    assert_eq!(curve.supply, 100);
+    assert_eq!(curve.reserve, 150);
+    assert_eq!(curve.pending, 0);
+    assert_eq!(state.balance_of(&account(1)), 100);
+    assert_eq!(state.supply(), INITIAL_SUPPLY + 100);
+}
+
+#[test]
+#[should_panic(expected = "Cost: 150, exceeds maximum cost: 149")]
+fn purchase_above_the_maximum_cost_is_rejected() {
+    buy_from_curve(context(account(1)), curve_state(), 100, 149);
+}
+
+#[test]
+fn failed_payment_releases_the_purchase() {
+    let state = bought_state(100, 150, false);
+
+    let curve = state.bonding_curve.as_ref().unwrap();
+    assert_eq!(curve.supply, 0);
+    assert_eq!(curve.reserve, 0);
+    assert_eq!(curve.pending, 0);
+    assert_eq!(state.balance_of(&account(1)), 0);
+}
+
+#[test]
+fn sale_burns_the_tokens_and_returns_the_reserve() {
+    let (state, events) =
+        sell_to_curve(context(account(1)), bought_state(100, 150, true), 100, 150);
+
+    let curve = state.bonding_curve.as_ref().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(curve.supply, 0);
+    assert_eq!(curve.reserve, 0);
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.supply(), INITIAL_SUPPLY);
+}
+
+#[test]
+fn rounding_favours_the_reserve() {
+    // one token costs 1.005 units, paid as 2 and returned as 1
+    let state = bought_state(1, 2, true);
+    let (state, _) = sell_to_curve(context(account(1)), state, 1, 1);
+
+    let curve = state.bonding_curve.as_ref().unwrap();
+    assert_eq!(curve.supply, 0);
+    assert_eq!(curve.reserve, 1);
+}
+
+#[test]
+#[should_panic(expected = "Return: 150, below minimum return: 151")]
+fn sale_below_the_minimum_return_is_rejected() {
+    sell_to_curve(context(account(1)), bought_state(100, 150, true), 100, 151);
+}
+
+#[test]
+#[should_panic(expected = "A bonding curve purchase is pending.")]
+fn sale_while_a_purchase_is_pending_is_rejected() {
+    let state = bought_state(100, 150, true);
+    let (state, _) = buy_from_curve(context(account(2)), state, 10, 1_000);
+    sell_to_curve(context(account(1)), state, 100, 0);
+}
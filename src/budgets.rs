@@ -0,0 +1,344 @@
+//! Budget sub-accounts, for teams running departmental budgets on a single treasury address. An
+//! account creates named budgets, each with an allocation its designated spending keys can
+//! transfer from the balance of the account, and optionally a refill resetting the allocation
+//! every period. The tokens stay in the balance of the account, a budget only bounds what its keys
+//! may spend, and the account keeps full control of its balance.
+#![cfg_attr(not(feature = "budgets"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "budgets")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "budgets")]
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "budgets")]
+use crate::hooks::Transfer;
+#[cfg(feature = "budgets")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "budgets"))]
+mod tests;
+
+/// The longest name of a budget, in bytes.
+pub const MAX_BUDGET_NAME_LENGTH: usize = 32;
+
+/// The largest number of spending keys a budget can have.
+pub const MAX_BUDGET_SPENDERS: usize = 16;
+
+/// The refill of a budget.
+///
+/// ### Fields:
+///
+///   * `amount`: [`u128`], allocation the budget is reset to every period.
+///   * `period`: [`i64`], length of a period, in milliseconds.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BudgetRefill {
+    pub amount: u128,
+    pub period: i64,
+}
+
+/// A named budget of an account.
+///
+/// ### Fields:
+///
+///   * `owner`: [`Address`], account the budget is spent from.
+///   * `name`: [`String`], name of the budget, unique among the budgets of the owner.
+///   * `spenders`: [`Vec`]<[`Address`]>, keys that can spend from the budget.
+///   * `remaining`: [`u128`], amount the keys can still spend.
+///   * `refill`: [`Option`]<[`BudgetRefill`]>, the refill of the budget, if any.
+///   * `period_start`: [`i64`], block production time the current refill period started at, in
+///     milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Budget {
+    pub owner: Address,
+    pub name: String,
+    pub spenders: Vec<Address>,
+    pub remaining: u128,
+    pub refill: Option<BudgetRefill>,
+    pub period_start: i64,
+}
+
+impl Budget {
+    /// Resets the allocation if one or more refill periods have passed since the current one
+    /// started.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    pub fn refill(&mut self, now: i64) {
+        if let Some(refill) = self.refill {
+            let elapsed = now - self.period_start;
+            if elapsed >= refill.period {
+                self.remaining = refill.amount;
+                self.period_start += elapsed - elapsed % refill.period;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "budgets")]
+impl TashiTokenState {
+    /// Gets the budget with id `budget_id`, checking that `caller` owns it.
+    ///
+    /// Panics if there is no such budget or if `caller` is not its owner.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `budget_id`: [`u64`], id of the budget.
+    ///   * `caller`: [`Address`], account managing the budget.
+    ///
+    /// ### Returns:
+    ///
+    /// A mutable reference to the [`Budget`].
+    fn owned_budget(&mut self, budget_id: u64, caller: &Address) -> &mut Budget {
+        let budget = self
+            .budgets
+            .get_mut(&budget_id)
+            .unwrap_or_else(|| panic!("No budget with id: {}", budget_id));
+        assert!(
+            budget.owner == *caller,
+            "Only the owner of the budget can manage it."
+        );
+        budget
+    }
+}
+
+/// Create a budget named `name` on the balance of caller address with an allocation of
+/// `allocation`, optionally reset to the refill amount every period. The budget gets the next free
+/// id and has no spending keys yet.
+///
+/// Panics if the name is empty, longer than [`MAX_BUDGET_NAME_LENGTH`] or already used by a budget
+/// of the caller, or if the refill period is not positive.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `name`: [`String`], name of the budget.
+///   * `allocation`: [`u128`], amount the keys of the budget can spend.
+///   * `refill`: [`Option`]<[`BudgetRefill`]>, the refill of the budget, `None` for a one-off
+///     allocation.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "budgets")]
+#[action(shortname = 0xe9)]
+fn create_budget(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    name: String,
+    allocation: u128,
+    refill: Option<BudgetRefill>,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "create_budget");
+    assert!(
+        !name.is_empty() && name.len() <= MAX_BUDGET_NAME_LENGTH,
+        "Budget name must be between 1 and {} bytes.",
+        MAX_BUDGET_NAME_LENGTH
+    );
+    assert!(
+        !state
+            .budgets
+            .iter()
+            .any(|(_, budget)| budget.owner == ctx.sender && budget.name == name),
+        "Budget name is already used: {}",
+        name
+    );
+    if let Some(refill) = refill {
+        assert!(refill.period > 0, "Refill period must be positive.");
+    }
+    state.budgets.insert(
+        state.next_budget_id,
+        Budget {
+            owner: ctx.sender,
+            name,
+            spenders: Vec::new(),
+            remaining: allocation,
+            refill,
+            period_start: ctx.block_production_time,
+        },
+    );
+    state.next_budget_id += 1;
+    state.assert_invariants();
+
+    state
+}
+
+/// Replace the allocation and refill of budget `budget_id`, starting a new refill period. Only the
+/// owner of the budget can call this action.
+///
+/// Panics if there is no such budget, if the caller is not its owner, or if the refill period is
+/// not positive.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `budget_id`: [`u64`], id of the budget.
+///   * `allocation`: [`u128`], amount the keys of the budget can spend.
+///   * `refill`: [`Option`]<[`BudgetRefill`]>, the refill of the budget, `None` for a one-off
+///     allocation.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "budgets")]
+#[action(shortname = 0xea)]
+fn set_budget_allocation(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    budget_id: u64,
+    allocation: u128,
+    refill: Option<BudgetRefill>,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "set_budget_allocation");
+    if let Some(refill) = refill {
+        assert!(refill.period > 0, "Refill period must be positive.");
+    }
+    let budget = state.owned_budget(budget_id, &ctx.sender);
+    budget.remaining = allocation;
+    budget.refill = refill;
+    budget.period_start = ctx.block_production_time;
+    state.assert_invariants();
+
+    state
+}
+
+/// Designate `spender` as a spending key of budget `budget_id`, or remove it. Only the owner of
+/// the budget can call this action.
+///
+/// Panics if there is no such budget, if the caller is not its owner, if `allowed` does not change
+/// whether `spender` is a key of the budget, or if the budget already has
+/// [`MAX_BUDGET_SPENDERS`] keys.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `budget_id`: [`u64`], id of the budget.
+///   * `spender`: [`Address`], the spending key.
+///   * `allowed`: [`bool`], whether `spender` can spend from the budget.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "budgets")]
+#[action(shortname = 0xeb)]
+fn set_budget_spender(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    budget_id: u64,
+    spender: Address,
+    allowed: bool,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "set_budget_spender");
+    let budget = state.owned_budget(budget_id, &ctx.sender);
+    let position = budget.spenders.iter().position(|key| *key == spender);
+    match (allowed, position) {
+        (true, None) => {
+            assert!(
+                budget.spenders.len() < MAX_BUDGET_SPENDERS,
+                "Budget already has the maximum of: {} spending keys",
+                MAX_BUDGET_SPENDERS
+            );
+            budget.spenders.push(spender);
+        }
+        (false, Some(position)) => {
+            budget.spenders.remove(position);
+        }
+        _ => panic!("Spending key is already set to: {}", allowed),
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Close budget `budget_id`, removing it and its spending keys. Only the owner of the budget can
+/// call this action.
+///
+/// Panics if there is no such budget or if the caller is not its owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `budget_id`: [`u64`], id of the budget.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "budgets")]
+#[action(shortname = 0xec)]
+fn close_budget(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    budget_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "close_budget");
+    state.owned_budget(budget_id, &ctx.sender);
+    state.budgets.remove(&budget_id);
+    state.assert_invariants();
+
+    state
+}
+
+/// Transfer `amount` tokens to address `to` from the owner of budget `budget_id`, spending from
+/// the budget. Only a spending key of the budget can call this action. Otherwise handled as a
+/// `transfer` sent by the owner.
+///
+/// Panics if there is no such budget, if the caller is not one of its spending keys, if `amount`
+/// exceeds what is left of the budget in the current period, or if the transfer fails.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `budget_id`: [`u64`], id of the budget.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`u128`], amount to transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "budgets")]
+#[action(shortname = 0xed)]
+fn spend_from_budget(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    budget_id: u64,
+    to: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "spend_from_budget");
+    let budget = state
+        .budgets
+        .get_mut(&budget_id)
+        .unwrap_or_else(|| panic!("No budget with id: {}", budget_id));
+    assert!(
+        budget.spenders.contains(&ctx.sender),
+        "Caller is not a spending key of the budget."
+    );
+    budget.refill(ctx.block_production_time);
+    assert!(
+        amount <= budget.remaining,
+        "Amount: {}, exceeds the remaining budget: {}",
+        amount,
+        budget.remaining
+    );
+    budget.remaining -= amount;
+    let owner = budget.owner;
+
+    let supply = state.supply();
+    state.execute_transfer(&ctx, Transfer::new(owner, to, amount, None));
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
@@ -0,0 +1,82 @@
+//! Tests of budget sub-accounts: spending keys transferring from the balance of the owner, the
+//! allocation bounding them, and refills resetting it every period.
+
+use super::{create_budget, set_budget_spender, spend_from_budget, BudgetRefill};
+use crate::testing::{account, context, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// The refill period of the budget.
+const DAY: i64 = 86_400_000;
+
+/// A token where the owner created budget 0 of 1_000 a day, spent by account 5.
+fn budget_state() -> TashiTokenState {
+    let state = create_budget(
+        context(account(0)),
+        new_state(),
+        "Marketing".to_string(),
+        1_000,
+        Some(BudgetRefill {
+            amount: 1_000,
+            period: DAY,
+        }),
+    );
+    set_budget_spender(context(account(0)), state, 0, account(5), true)
+}
+
+/// Spends `amount` of budget 0 on account 1 at block production time `time`.
+fn spend(state: TashiTokenState, amount: u128, time: i64) -> TashiTokenState {
+    spend_from_budget(context_at(account(5), time), state, 0, account(1), amount).0
+}
+
+#[test]
+fn spending_key_transfers_from_the_owner() {
+    let state = spend(budget_state(), 600, 0);
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 600);
+    assert_eq!(state.balance_of(&account(1)), 600);
+    assert_eq!(state.balance_of(&account(5)), 0);
+    assert_eq!(state.budgets.get(&0).unwrap().remaining, 400);
+}
+
+#[test]
+#[should_panic(expected = "Amount: 500, exceeds the remaining budget: 400")]
+fn spending_beyond_the_allocation_is_rejected() {
+    let state = spend(budget_state(), 600, 0);
+    spend(state, 500, DAY - 1);
+}
+
+#[test]
+fn refill_resets_the_allocation_every_period() {
+    let state = spend(budget_state(), 600, 0);
+    let state = spend(state, 1_000, 2 * DAY + 1);
+
+    let budget = state.budgets.get(&0).unwrap();
+    assert_eq!(budget.remaining, 0);
+    assert_eq!(budget.period_start, 2 * DAY);
+    assert_eq!(state.balance_of(&account(1)), 1_600);
+}
+
+#[test]
+#[should_panic(expected = "Caller is not a spending key of the budget.")]
+fn removed_key_cannot_spend() {
+    let state = set_budget_spender(context(account(0)), budget_state(), 0, account(5), false);
+    spend(state, 100, 0);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner of the budget can manage it.")]
+fn only_the_owner_manages_the_budget() {
+    set_budget_spender(context(account(5)), budget_state(), 0, account(6), true);
+}
+
+#[test]
+#[should_panic(expected = "Budget name is already used: Marketing")]
+fn budget_names_are_unique_per_owner() {
+    create_budget(
+        context(account(0)),
+        budget_state(),
+        "Marketing".to_string(),
+        1_000,
+        None,
+    );
+}
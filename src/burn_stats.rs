@@ -0,0 +1,95 @@
+//! Burn statistics, kept by every path that destroys tokens so dashboards can read the deflation
+//! of the token without re-scanning its history. Tokens burned on transfer, by holders, by burn
+//! allowances, by sales to the bonding curve, by redemptions, by dust sweeps, by raffles and by flash
+//! mint repayments all count. Burns with a reason code are also counted per reason, attributing
+//! them to the sink that consumed the tokens.
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+use crate::token::TashiTokenState;
+use crate::views::return_value;
+
+/// Cumulative statistics of the burned tokens.
+///
+/// ### Fields:
+///
+///   * `total_burned`: [`u128`], amount burned since initialization.
+///   * `burn_count`: [`u64`], number of burns of a non-zero amount.
+///   * `last_burn_at`: [`i64`], block production time of the latest burn, zero if nothing was
+///     burned, in milliseconds.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BurnStats {
+    pub total_burned: u128,
+    pub burn_count: u64,
+    pub last_burn_at: i64,
+}
+
+impl BurnStats {
+    /// Counts a burn of `amount` at time `now`. Burns of nothing are not counted.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `amount`: [`u128`], amount burned.
+    ///   * `now`: [`i64`], the current block production time.
+    pub fn record(&mut self, amount: u128, now: i64) {
+        if amount == 0 {
+            return;
+        }
+        self.total_burned = self.total_burned.saturating_add(amount);
+        self.burn_count += 1;
+        self.last_burn_at = now;
+    }
+}
+
+impl TashiTokenState {
+    /// Counts a burn of `amount` at time `now` for `reason_code`, on top of the burn statistics
+    /// of the token. Burns of nothing are not counted.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `reason_code`: [`u32`], why the tokens were burned.
+    ///   * `amount`: [`u128`], amount burned.
+    ///   * `now`: [`i64`], the current block production time.
+    #[cfg_attr(not(feature = "burnable"), allow(dead_code))]
+    pub fn record_burn_reason(&mut self, reason_code: u32, amount: u128, now: i64) {
+        if amount == 0 {
+            return;
+        }
+        let mut stats = self
+            .burns_by_reason
+            .get(&reason_code)
+            .copied()
+            .unwrap_or(BurnStats {
+                total_burned: 0,
+                burn_count: 0,
+                last_burn_at: 0,
+            });
+        stats.record(amount, now);
+        self.burns_by_reason.insert(reason_code, stats);
+    }
+}
+
+/// Return the burn statistics of the token to the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the statistics as [`BurnStats`] in the return data.
+#[action(shortname = 0xa2)]
+fn query_burn_stats(
+    _ctx: ContractContext,
+    state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let events = return_value(state.burn_stats);
+    state.assert_invariants();
+    (state, events)
+}
@@ -0,0 +1,255 @@
+//! Burning tokens, both explicitly and as a share of every transfer, and burn allowances letting
+//! a spender destroy tokens without being able to transfer them.
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::{mul_div, BalanceMap, TashiTokenState, BPS_DENOMINATOR};
+
+#[cfg(test)]
+mod tests;
+
+/// The highest burn rate the owner can configure, in basis points (5%).
+const MAX_BURN_RATE_BPS: u16 = 500;
+
+impl TashiTokenState {
+    /// Sets the share of every transfer that is burned.
+    ///
+    /// Panics if `rate_bps` exceeds the maximum burn rate.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `rate_bps`: [`u16`], new burn rate in basis points.
+    pub fn set_burn_rate_bps(&mut self, rate_bps: u16) {
+        assert!(
+            rate_bps <= MAX_BURN_RATE_BPS,
+            "Burn rate: {}, maximum allowed burn rate: {}",
+            rate_bps,
+            MAX_BURN_RATE_BPS
+        );
+        self.burn_rate_bps = rate_bps;
+    }
+
+    /// Gets the amount of tokens that an owner allotted a spender to burn.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account which owns the funds.
+    ///   * `spender`: [`Address`], account which will burn the funds.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount the `spender` is allowed to burn from the `owner`.
+    pub fn burn_allowance(&self, owner: &Address, spender: &Address) -> u128 {
+        self.burn_allowed
+            .get(owner)
+            .and_then(|owner_allowances| owner_allowances.get(spender))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Updates the balance an owner allots a spender to burn to `amount`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account which owns the funds.
+    ///   * `spender`: [`Address`], account which will burn the funds.
+    ///   * `amount`: [`u128`], amount to allot to `spender`.
+    pub fn update_burn_allowance(&mut self, owner: Address, spender: Address, amount: u128) {
+        if !self.burn_allowed.contains_key(&owner) {
+            self.burn_allowed.insert(owner, SortedVecMap::new());
+        }
+        let owner_allowances = self.burn_allowed.get_mut(&owner).unwrap();
+        owner_allowances.insert_balance(spender, amount);
+        if owner_allowances.is_empty() {
+            self.burn_allowed.remove(&owner);
+        }
+    }
+}
+
+/// Burns `burn_rate_bps` of every transfer, reducing the total supply.
+pub struct BurnOnTransfer;
+
+impl TransferHook for BurnOnTransfer {
+    fn before_transfer(
+        ctx: &ContractContext,
+        state: &mut TashiTokenState,
+        transfer: &mut Transfer,
+    ) {
+        let burned = mul_div(
+            transfer.received,
+            u128::from(state.burn_rate_bps),
+            BPS_DENOMINATOR,
+        );
+        transfer.take(burned);
+        // burned is at most 5% of amount, which is part of the supply
+        state.decrease_supply(burned, ctx.block_production_time);
+    }
+}
+
+/// Burn `amount` tokens from caller address, reducing the total supply.
+///
+/// Panics if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to burn.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[action(shortname = 0x12)]
+pub(crate) fn burn(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "burn");
+    let supply = state.supply();
+    state.burn_balance(ctx.sender, amount, ctx.block_production_time);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Set the share of every transfer that is burned, reducing the total supply. Only the owner can
+/// call this action, through the admin timelock with the `admin-timelock` feature.
+///
+/// Panics if the caller is not the owner or if `rate_bps` exceeds the maximum burn rate.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `rate_bps`: [`u16`], new burn rate in basis points.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x10)]
+pub(crate) fn set_burn_rate(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    rate_bps: u16,
+) -> TashiTokenState {
+    state.assert_owner_or_timelock(&ctx);
+    crate::audit::log_admin_action!(state, ctx, "set_burn_rate", rate_bps);
+    state.set_burn_rate_bps(rate_bps);
+    state.assert_invariants();
+
+    state
+}
+
+/// Allot `amount` tokens from caller address to address `spender` to burn with [`burn_from`],
+/// replacing any prior burn allowance of `spender`. As with `approve`, nothing is set aside: the
+/// tokens are burned from the balance of the caller, and the burn fails if the balance does not
+/// cover it.
+///
+/// Panics if the address guards reject `spender`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `spender`: [`Address`], account which will burn the funds.
+///   * `amount`: [`u128`], amount to allot to `spender`.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x3c)]
+fn approve_burn(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    spender: Address,
+    amount: u128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "approve_burn");
+    state.assert_valid_destination(&ctx, &spender);
+    state.update_burn_allowance(ctx.sender, spender, amount);
+    state.assert_invariants();
+
+    state
+}
+
+/// Burn `amount` tokens of address `owner` from the burn allowance of the caller, reducing the
+/// total supply.
+///
+/// Panics if there is insufficient burn allowance in caller account, or if `owner` cannot spend
+/// `amount` or has insufficient balance.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `owner`: [`Address`], account whose tokens are burned.
+///   * `amount`: [`u128`], amount to burn.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[action(shortname = 0x3d)]
+fn burn_from(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    owner: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "burn_from");
+    let allowance = state.burn_allowance(&owner, &ctx.sender);
+    let new_allowance = allowance.checked_sub(amount).unwrap_or_else(|| {
+        panic!(
+            "Insufficient burn allowance: {}, minimum required burn allowance: {}",
+            allowance, amount
+        )
+    });
+    state.update_burn_allowance(owner, ctx.sender, new_allowance);
+    let supply = state.supply();
+    state.assert_can_spend(&ctx, &owner, amount);
+    state.burn_balance(owner, amount, ctx.block_production_time);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Burn `amount` tokens from caller address for `reason_code`, reducing the total supply. The burn
+/// is counted in the statistics of the reason as well as in those of the token.
+///
+/// Panics if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to burn.
+///   * `reason_code`: [`u32`], why the tokens are burned, e.g. the sink consuming them.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[action(shortname = 0xae)]
+fn burn_with_reason(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+    reason_code: u32,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "burn_with_reason");
+    let supply = state.supply();
+    state.burn_balance(ctx.sender, amount, ctx.block_production_time);
+    state.record_burn_reason(reason_code, amount, ctx.block_production_time);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
@@ -0,0 +1,86 @@
+//! Tests of burn allowances, which set nothing aside and burn from the balance of the owner, and of
+//! burns counted per reason.
+
+use super::{approve_burn, burn_from, burn_with_reason};
+use crate::testing::{account, context, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token whose owner allotted account 1 a burn allowance of `amount`.
+fn approved_state(amount: u128) -> TashiTokenState {
+    approve_burn(context(account(0)), new_state(), account(1), amount)
+}
+
+#[test]
+fn approval_sets_nothing_aside() {
+    let state = approved_state(500);
+
+    assert_eq!(state.burn_allowance(&account(0), &account(1)), 500);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY);
+    assert_eq!(state.supply(), INITIAL_SUPPLY);
+    state.assert_invariants();
+}
+
+#[test]
+fn burn_takes_from_the_balance_of_the_owner() {
+    let (state, _) = burn_from(context(account(1)), approved_state(500), account(0), 300);
+
+    assert_eq!(state.burn_allowance(&account(0), &account(1)), 200);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 300);
+    assert_eq!(state.supply(), INITIAL_SUPPLY - 300);
+    assert_eq!(state.burn_stats.total_burned, 300);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient burn allowance: 200, minimum required burn allowance: 300")]
+fn approval_replaces_the_previous_burn_allowance() {
+    let state = approve_burn(context(account(0)), approved_state(500), account(1), 200);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY);
+    burn_from(context(account(1)), state, account(0), 300);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient balance: 1000000, minimum required balance: 1000001")]
+fn burn_beyond_the_balance_of_the_owner_is_rejected() {
+    let state = approved_state(INITIAL_SUPPLY + 1);
+    burn_from(context(account(1)), state, account(0), INITIAL_SUPPLY + 1);
+}
+
+#[test]
+fn revoked_burn_allowance_leaves_the_balance_untouched() {
+    let mut state = approved_state(500);
+    state.revoke_spender(account(0), account(1));
+
+    assert_eq!(state.burn_allowance(&account(0), &account(1)), 0);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY);
+    state.assert_invariants();
+}
+
+#[test]
+fn burns_are_counted_per_reason() {
+    let (state, _) = burn_with_reason(context_at(account(0), 1_000), new_state(), 300, 7);
+    let (state, _) = burn_with_reason(context_at(account(0), 2_000), state, 200, 7);
+    let (state, _) = burn_with_reason(context_at(account(0), 3_000), state, 100, 8);
+
+    let sink = state.burns_by_reason.get(&7).copied().unwrap();
+    assert_eq!(sink.total_burned, 500);
+    assert_eq!(sink.burn_count, 2);
+    assert_eq!(sink.last_burn_at, 2_000);
+    assert_eq!(state.burns_by_reason.get(&8).unwrap().total_burned, 100);
+    assert_eq!(state.burn_stats.total_burned, 600);
+    assert_eq!(state.burn_stats.burn_count, 3);
+    assert_eq!(state.supply(), INITIAL_SUPPLY - 600);
+}
+
+#[test]
+fn burning_nothing_is_not_counted() {
+    let (state, _) = burn_with_reason(context(account(0)), new_state(), 0, 7);
+
+    assert!(state.burns_by_reason.get(&7).is_none());
+    assert_eq!(state.burn_stats.burn_count, 0);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient balance: 0, minimum required balance: 1")]
+fn burn_with_reason_beyond_the_balance_is_rejected() {
+    burn_with_reason(context(account(1)), new_state(), 1, 7);
+}
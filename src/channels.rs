@@ -0,0 +1,328 @@
+//! Unidirectional payment channels. The sender locks a deposit and pays the recipient off-chain by
+//! signing increasing balance updates, only the final update is settled on-chain.
+#![cfg_attr(not(feature = "channels"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::Hash;
+use read_write_state_derive::ReadWriteState;
+
+use crate::signatures::{signed_message_hash, SchemeSignature, SignerKey};
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "channels", feature = "ed25519"))]
+mod tests;
+
+/// Domain separator prefixed to every signed balance update.
+const BALANCE_UPDATE_DOMAIN: &[u8] = b"TASHI_TOKEN_CHANNEL_BALANCE";
+
+/// The longest challenge period of a channel, a year in milliseconds.
+pub const MAX_CHALLENGE_PERIOD: i64 = 365 * 24 * 60 * 60 * 1000;
+
+/// A payment channel from `sender` to `recipient`.
+///
+/// ### Fields:
+///
+///   * `sender`: [`Address`], account that funded the channel.
+///   * `recipient`: [`Address`], account the channel pays out to.
+///   * `sender_key`: [`SignerKey`], key the sender signs balance updates with.
+///   * `deposit`: [`u128`], amount locked in the channel.
+///   * `challenge_period`: [`i64`], time the recipient has to challenge a close, in milliseconds.
+///   * `closing_amount`: [`u128`], amount owed to the recipient by the submitted balance update.
+///   * `closes_at`: [`Option`]<[`i64`]>, time the channel can be settled at, once a close started.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Channel {
+    pub sender: Address,
+    pub recipient: Address,
+    pub sender_key: SignerKey,
+    pub deposit: u128,
+    pub challenge_period: i64,
+    pub closing_amount: u128,
+    pub closes_at: Option<i64>,
+}
+
+impl Channel {
+    /// Checks that `signature` is the sender's signature of a balance update paying `amount` to the
+    /// recipient of channel `channel_id`.
+    ///
+    /// Panics if the signature is invalid or if `amount` exceeds the deposit.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `contract`: [`Address`], address of this contract.
+    ///   * `channel_id`: [`u64`], id of the channel.
+    ///   * `amount`: [`u128`], cumulative amount owed to the recipient.
+    ///   * `signature`: [`SchemeSignature`], the sender's signature of the update.
+    pub fn verify_balance_update(
+        &self,
+        contract: &Address,
+        channel_id: u64,
+        amount: u128,
+        signature: &SchemeSignature,
+    ) {
+        assert!(
+            amount <= self.deposit,
+            "Balance update: {}, exceeds channel deposit: {}",
+            amount,
+            self.deposit
+        );
+        let message = balance_update_hash(contract, channel_id, amount);
+        self.sender_key
+            .assert_signed(&message, signature, "Invalid balance update signature.");
+    }
+}
+
+/// Computes the hash the sender signs to pay `amount` to the recipient of a channel. The
+/// contract address and channel id are included so updates cannot be replayed elsewhere.
+///
+/// ### Parameters:
+///
+///   * `contract`: [`Address`], address of this contract.
+///   * `channel_id`: [`u64`], id of the channel.
+///   * `amount`: [`u128`], cumulative amount owed to the recipient.
+///
+/// ### Returns:
+///
+/// The [`Hash`] of the balance update.
+pub fn balance_update_hash(contract: &Address, channel_id: u64, amount: u128) -> Hash {
+    let mut payload = channel_id.to_be_bytes().to_vec();
+    payload.extend_from_slice(&amount.to_be_bytes());
+    signed_message_hash(BALANCE_UPDATE_DOMAIN, contract, &payload)
+}
+
+impl TashiTokenState {
+    /// Gets the payment channel with id `channel_id`.
+    ///
+    /// Panics if there is no such channel.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `channel_id`: [`u64`], id of the channel.
+    ///
+    /// ### Returns:
+    ///
+    /// A reference to the [`Channel`].
+    pub fn channel(&self, channel_id: u64) -> &Channel {
+        self.channels
+            .get(&channel_id)
+            .unwrap_or_else(|| panic!("No channel with id: {}", channel_id))
+    }
+
+    /// Closes channel `channel_id`, paying `amount` to the recipient and refunding the rest of the
+    /// deposit to the sender.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context.
+    ///   * `channel_id`: [`u64`], id of the channel.
+    ///   * `amount`: [`u128`], amount owed to the recipient.
+    pub fn settle_channel(&mut self, ctx: &ContractContext, channel_id: u64, amount: u128) {
+        let channel = self.channels.remove(&channel_id).unwrap();
+        self.payout(ctx, channel.sender, channel.recipient, amount); // pay the recipient
+        self.release(ctx, channel.sender, channel.deposit - amount); // refund the sender
+    }
+}
+
+/// Open a payment channel from caller address to `recipient`, locking `deposit` tokens. The caller
+/// pays the recipient off-chain by signing balance updates with `sender_key`.
+///
+/// Panics if `deposit` is zero, if `challenge_period` is negative or longer than
+/// [`MAX_CHALLENGE_PERIOD`], or if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `recipient`: [`Address`], account the channel pays out to.
+///   * `deposit`: [`u128`], amount to lock in the channel.
+///   * `sender_key`: [`SignerKey`], key balance updates will be signed with, secp256k1 or ed25519.
+///   * `challenge_period`: [`i64`], time the recipient has to challenge a close, in milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "channels")]
+#[action(shortname = 0x19)]
+fn open_channel(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    recipient: Address,
+    deposit: u128,
+    sender_key: SignerKey,
+    challenge_period: i64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "open_channel");
+    assert!(deposit > 0, "Channel must have a non-zero deposit.");
+    assert!(
+        challenge_period >= 0,
+        "Challenge period cannot be negative."
+    );
+    assert!(
+        challenge_period <= MAX_CHALLENGE_PERIOD,
+        "Challenge period: {}, exceeds the maximum challenge period: {}",
+        challenge_period,
+        MAX_CHALLENGE_PERIOD
+    );
+    state.escrow(&ctx, ctx.sender, deposit); // lock the deposit
+
+    let channel_id = state.next_channel_id;
+    state.next_channel_id += 1;
+    state.channels.insert(
+        channel_id,
+        Channel {
+            sender: ctx.sender,
+            recipient,
+            sender_key,
+            deposit,
+            challenge_period,
+            closing_amount: 0,
+            closes_at: None,
+        },
+    );
+    state.assert_invariants();
+
+    state
+}
+
+/// Close channel `channel_id` with the balance update paying `amount` to the recipient. When the
+/// recipient closes the channel it is settled immediately, as a later update could only pay the
+/// recipient more. When the sender closes it the challenge period starts, during which the
+/// recipient can submit a later update.
+///
+/// Panics if there is no such channel, if the caller is neither its sender nor its recipient, if
+/// a close was already started, or if the balance update is invalid.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `channel_id`: [`u64`], id of the channel to close.
+///   * `amount`: [`u128`], cumulative amount owed to the recipient.
+///   * `signature`: [`SchemeSignature`], the sender's signature of the balance update.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "channels")]
+#[action(shortname = 0x1a)]
+fn close_channel(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    channel_id: u64,
+    amount: u128,
+    signature: SchemeSignature,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "close_channel");
+    let channel = state.channel(channel_id);
+    assert!(
+        channel.sender == ctx.sender || channel.recipient == ctx.sender,
+        "Only the sender or recipient can close the channel."
+    );
+    assert!(channel.closes_at.is_none(), "Channel is already closing.");
+    channel.verify_balance_update(&ctx.contract_address, channel_id, amount, &signature);
+
+    if channel.recipient == ctx.sender {
+        state.settle_channel(&ctx, channel_id, amount);
+    } else {
+        let closes_at = ctx.block_production_time + channel.challenge_period;
+        let channel = state.channels.get_mut(&channel_id).unwrap();
+        channel.closing_amount = amount;
+        channel.closes_at = Some(closes_at);
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Challenge the close of channel `channel_id` with a later balance update paying `amount` to the
+/// recipient. The channel is settled immediately with the later update. Only the recipient can
+/// call this action, during the challenge period.
+///
+/// Panics if there is no such channel, if the caller is not its recipient, if the channel is not
+/// closing or the challenge period is over, if `amount` does not exceed the submitted update, or
+/// if the balance update is invalid.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `channel_id`: [`u64`], id of the channel to challenge.
+///   * `amount`: [`u128`], cumulative amount owed to the recipient.
+///   * `signature`: [`SchemeSignature`], the sender's signature of the balance update.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "channels")]
+#[action(shortname = 0x1b)]
+fn challenge_channel(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    channel_id: u64,
+    amount: u128,
+    signature: SchemeSignature,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "challenge_channel");
+    let channel = state.channel(channel_id);
+    assert!(
+        channel.recipient == ctx.sender,
+        "Only the recipient can challenge the channel."
+    );
+    let closes_at = channel.closes_at.expect("Channel is not closing.");
+    assert!(
+        ctx.block_production_time < closes_at,
+        "Challenge period is over."
+    );
+    assert!(
+        amount > channel.closing_amount,
+        "Balance update: {}, must exceed the submitted update: {}",
+        amount,
+        channel.closing_amount
+    );
+    channel.verify_balance_update(&ctx.contract_address, channel_id, amount, &signature);
+
+    state.settle_channel(&ctx, channel_id, amount);
+    state.assert_invariants();
+
+    state
+}
+
+/// Settle channel `channel_id` with the balance update submitted by the sender, once the
+/// challenge period is over. Anyone can call this action.
+///
+/// Panics if there is no such channel, if the channel is not closing, or if the challenge period
+/// is not over.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `channel_id`: [`u64`], id of the channel to settle.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "channels")]
+#[action(shortname = 0x1c)]
+fn settle_channel(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    channel_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "settle_channel");
+    let channel = state.channel(channel_id);
+    let closes_at = channel.closes_at.expect("Channel is not closing.");
+    assert!(
+        ctx.block_production_time >= closes_at,
+        "Challenge period ends at: {}",
+        closes_at
+    );
+    let amount = channel.closing_amount;
+
+    state.settle_channel(&ctx, channel_id, amount);
+    state.assert_invariants();
+
+    state
+}
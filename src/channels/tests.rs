@@ -0,0 +1,130 @@
+//! Tests of payment channels: the bounded challenge period, and closes settled by the recipient,
+//! by a challenge or once the challenge period is over.
+
+use ed25519_compact::{KeyPair, Seed};
+
+use super::{
+    balance_update_hash, challenge_channel, close_channel, open_channel, settle_channel,
+    MAX_CHALLENGE_PERIOD,
+};
+use crate::hooks::Transfer;
+use crate::signatures::{SchemeSignature, SignerKey};
+use crate::testing::{account, context, context_at, new_state, token_address};
+use crate::token::TashiTokenState;
+
+/// The key pair account 1 signs balance updates with.
+fn key_pair() -> KeyPair {
+    KeyPair::from_seed(Seed::new([1; 32]))
+}
+
+/// The signature of a balance update paying `amount` from channel 0.
+fn signed_update(amount: u128) -> SchemeSignature {
+    let message = balance_update_hash(&token_address(), 0, amount);
+    SchemeSignature::Ed25519 {
+        signature: *key_pair().sk.sign(message.bytes, None),
+    }
+}
+
+/// A token where account 1 opened channel 0 to account 2 with a deposit of 600 of its 1000 tokens
+/// and `challenge_period`.
+fn channel_state(challenge_period: i64) -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 1_000, None),
+    );
+    let sender_key = SignerKey::Ed25519 {
+        key: *key_pair().pk,
+    };
+    open_channel(
+        context(account(1)),
+        state,
+        account(2),
+        600,
+        sender_key,
+        challenge_period,
+    )
+}
+
+#[test]
+#[should_panic(
+    expected = "Challenge period: 31536000001, exceeds the maximum challenge period: 31536000000"
+)]
+fn challenge_period_beyond_the_maximum_is_rejected() {
+    channel_state(MAX_CHALLENGE_PERIOD + 1);
+}
+
+#[test]
+#[should_panic(expected = "Challenge period cannot be negative.")]
+fn negative_challenge_period_is_rejected() {
+    channel_state(-1);
+}
+
+#[test]
+fn recipient_close_settles_immediately() {
+    let state = close_channel(
+        context(account(2)),
+        channel_state(1_000),
+        0,
+        250,
+        signed_update(250),
+    );
+
+    assert!(state.channels.is_empty());
+    assert_eq!(state.balance_of(&account(1)), 750);
+    assert_eq!(state.balance_of(&account(2)), 250);
+}
+
+#[test]
+fn sender_close_settles_after_the_longest_challenge_period() {
+    let closed_at = i64::MAX - MAX_CHALLENGE_PERIOD;
+    let state = close_channel(
+        context_at(account(1), closed_at),
+        channel_state(MAX_CHALLENGE_PERIOD),
+        0,
+        100,
+        signed_update(100),
+    );
+    assert_eq!(state.channels.get(&0).unwrap().closes_at, Some(i64::MAX));
+    assert_eq!(state.balance_of(&account(1)), 400);
+
+    let state = settle_channel(context_at(account(3), i64::MAX), state, 0);
+    assert!(state.channels.is_empty());
+    assert_eq!(state.balance_of(&account(1)), 900);
+    assert_eq!(state.balance_of(&account(2)), 100);
+}
+
+#[test]
+fn challenge_settles_with_the_later_update() {
+    let state = close_channel(
+        context(account(1)),
+        channel_state(1_000),
+        0,
+        100,
+        signed_update(100),
+    );
+    let state = challenge_channel(
+        context_at(account(2), 999),
+        state,
+        0,
+        300,
+        signed_update(300),
+    );
+
+    assert!(state.channels.is_empty());
+    assert_eq!(state.balance_of(&account(1)), 700);
+    assert_eq!(state.balance_of(&account(2)), 300);
+}
+
+#[test]
+#[should_panic(expected = "Challenge period ends at: 1000")]
+fn settling_during_the_challenge_period_is_rejected() {
+    let state = close_channel(
+        context(account(1)),
+        channel_state(1_000),
+        0,
+        100,
+        signed_update(100),
+    );
+    settle_channel(context_at(account(3), 999), state, 0);
+}
@@ -0,0 +1,148 @@
+//! The circulating supply, the number aggregators list next to the total supply. The owner
+//! registers the accounts whose balances do not circulate, such as the treasury, vesting pools and
+//! locked reserves, and the circulating supply is the total supply less their balances. Tokens
+//! escrowed by the contract itself are not deducted.
+#![cfg_attr(not(feature = "circulating-supply"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+#[cfg(feature = "circulating-supply")]
+use pbc_contract_common::address::Address;
+#[cfg(feature = "circulating-supply")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "circulating-supply")]
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "circulating-supply")]
+use crate::token::stored_amount;
+use crate::token::TashiTokenState;
+#[cfg(feature = "circulating-supply")]
+use crate::views::return_value;
+
+/// The largest number of accounts that can be registered as non-circulating.
+pub const MAX_NON_CIRCULATING: usize = 64;
+
+/// Why the balance of an account does not circulate.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NonCirculatingKind {
+    /// The treasury of the project.
+    #[discriminant(0)]
+    Treasury {},
+    /// A pool of tokens vesting to team members or investors.
+    #[discriminant(1)]
+    Vesting {},
+    /// A reserve locked by the project.
+    #[discriminant(2)]
+    LockedReserve {},
+}
+
+impl TashiTokenState {
+    /// Computes the circulating supply, the total supply less the balances of the non-circulating
+    /// accounts.
+    ///
+    /// ### Returns:
+    ///
+    /// The circulating supply as a [`u128`].
+    pub fn circulating_supply(&self) -> u128 {
+        let locked: u128 = self
+            .non_circulating
+            .iter()
+            .map(|(account, _)| self.balance_of(account))
+            .sum();
+        self.supply().saturating_sub(locked)
+    }
+}
+
+/// Register the balance of `account` as non-circulating because of `kind`, replacing its previous
+/// kind if it is already registered. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if [`MAX_NON_CIRCULATING`] accounts are already
+/// registered.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], the account to register.
+///   * `kind`: [`NonCirculatingKind`], why its balance does not circulate.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "circulating-supply")]
+#[action(shortname = 0xa3)]
+fn set_non_circulating(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+    kind: NonCirculatingKind,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_non_circulating", account, kind);
+    assert!(
+        state.non_circulating.contains_key(&account)
+            || state.non_circulating.len() < MAX_NON_CIRCULATING,
+        "Non-circulating accounts: {}, maximum is: {}",
+        state.non_circulating.len(),
+        MAX_NON_CIRCULATING
+    );
+    state.non_circulating.insert(account, kind);
+    state.assert_invariants();
+
+    state
+}
+
+/// Count the balance of `account` as circulating again. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if the account is not registered.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], the account to unregister.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "circulating-supply")]
+#[action(shortname = 0xa4)]
+fn remove_non_circulating(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "remove_non_circulating", account);
+    state
+        .non_circulating
+        .remove(&account)
+        .expect("Account is not registered as non-circulating.");
+    state.assert_invariants();
+
+    state
+}
+
+/// Return the circulating supply of the token to the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the circulating supply as an [`Amount`] in the return
+/// data.
+#[cfg(feature = "circulating-supply")]
+#[action(shortname = 0xa5)]
+fn query_circulating_supply(
+    _ctx: ContractContext,
+    state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let events = return_value(stored_amount(state.circulating_supply()));
+    state.assert_invariants();
+    (state, events)
+}
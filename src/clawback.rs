@@ -0,0 +1,312 @@
+//! Clawbacks, reversals of specific transfers for risk and compliance teams, gentler than a forced
+//! transfer. A compliance officer queues the reversal of a transfer, holding the amount from the
+//! receiver. The reversal can only execute once the [`CLAWBACK_DISPUTE_WINDOW`] has passed, and
+//! until then the receiver can challenge it. A challenged clawback waits for the owner to uphold
+//! or reject it. Every clawback is kept in state with its outcome.
+#![cfg_attr(not(feature = "clawback"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "clawback")]
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::Hash;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "clawback")]
+use crate::admin::Role;
+#[cfg(feature = "clawback")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "clawback"))]
+mod tests;
+
+/// Time the receiver of a clawed back transfer has to challenge it, seven days in milliseconds.
+pub const CLAWBACK_DISPUTE_WINDOW: i64 = 7 * 24 * 60 * 60 * 1000;
+
+/// The progress of a clawback.
+#[derive(ReadWriteState, CreateTypeSpec, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClawbackStatus {
+    /// Held from the receiver, waiting for the dispute window to pass.
+    #[discriminant(0)]
+    Pending {},
+    /// Challenged by the receiver, waiting for the owner.
+    #[discriminant(1)]
+    Challenged {},
+    /// Returned to the sender of the transfer.
+    #[discriminant(2)]
+    Executed {},
+    /// Returned to the receiver, cancelled by a compliance officer or rejected by the owner.
+    #[discriminant(3)]
+    Cancelled {},
+}
+
+/// The reversal of a transfer.
+///
+/// ### Fields:
+///
+///   * `transaction`: [`Hash`], transaction of the reversed transfer.
+///   * `from`: [`Address`], sender of the transfer, receiving the amount back.
+///   * `to`: [`Address`], receiver of the transfer, the amount is held from.
+///   * `amount`: [`u128`], amount reversed.
+///   * `reason`: [`String`], why the transfer is reversed.
+///   * `queued_by`: [`Address`], compliance officer that queued the clawback.
+///   * `executable_at`: [`i64`], block production time the dispute window ends at, in
+///     milliseconds.
+///   * `status`: [`ClawbackStatus`], progress of the clawback.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Clawback {
+    pub transaction: Hash,
+    pub from: Address,
+    pub to: Address,
+    pub amount: u128,
+    pub reason: String,
+    pub queued_by: Address,
+    pub executable_at: i64,
+    pub status: ClawbackStatus,
+}
+
+#[cfg(feature = "clawback")]
+impl TashiTokenState {
+    /// Gets the clawback `clawback_id`, checking that it has `status`.
+    ///
+    /// Panics if no clawback has the id or if it has another status.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `clawback_id`: [`u64`], the id of the clawback.
+    ///   * `status`: [`ClawbackStatus`], the status the clawback must have.
+    ///
+    /// ### Returns:
+    ///
+    /// A mutable reference to the [`Clawback`].
+    fn clawback_with_status(&mut self, clawback_id: u64, status: ClawbackStatus) -> &mut Clawback {
+        let clawback = self
+            .clawbacks
+            .get_mut(&clawback_id)
+            .expect("Unknown clawback.");
+        assert!(
+            clawback.status == status,
+            "Clawback is not {:?}, status: {:?}",
+            status,
+            clawback.status
+        );
+        clawback
+    }
+}
+
+/// Queue the reversal of the transfer of `amount` tokens from `from` to `to` in `transaction`,
+/// holding the amount from `to` until it executes. Only accounts with the [`Role::Compliance`]
+/// role can call this action.
+///
+/// Panics if the caller does not have the role, if `amount` is zero, if `from` and `to` are the
+/// same, or if there is insufficient balance in `to` account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `transaction`: [`Hash`], transaction of the transfer to reverse.
+///   * `from`: [`Address`], sender of the transfer.
+///   * `to`: [`Address`], receiver of the transfer.
+///   * `amount`: [`u128`], amount to reverse.
+///   * `reason`: [`String`], why the transfer is reversed.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "clawback")]
+#[action(shortname = 0x94)]
+fn queue_clawback(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    transaction: Hash,
+    from: Address,
+    to: Address,
+    amount: u128,
+    reason: String,
+) -> TashiTokenState {
+    state.assert_role(Role::Compliance {}, &ctx.sender);
+    crate::audit::log_admin_action!(
+        state,
+        ctx,
+        "queue_clawback",
+        transaction,
+        from,
+        to,
+        amount,
+        reason
+    );
+    assert!(amount > 0, "Clawback amount cannot be zero.");
+    assert!(from != to, "Sender and receiver cannot be the same.");
+    state.debit(to, amount); // hold the amount until the clawback executes
+    state.clawbacks.insert(
+        state.next_clawback_id,
+        Clawback {
+            transaction,
+            from,
+            to,
+            amount,
+            reason,
+            queued_by: ctx.sender,
+            executable_at: ctx.block_production_time + CLAWBACK_DISPUTE_WINDOW,
+            status: ClawbackStatus::Pending {},
+        },
+    );
+    state.next_clawback_id += 1;
+    state.assert_invariants();
+
+    state
+}
+
+/// Challenge the pending clawback `clawback_id` as the receiver of the reversed transfer, leaving
+/// the decision to the owner.
+///
+/// Panics if the clawback is not pending, if the caller is not its receiver, or if the dispute
+/// window has passed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `clawback_id`: [`u64`], the id of the clawback.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "clawback")]
+#[action(shortname = 0x95)]
+fn challenge_clawback(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    clawback_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "challenge_clawback");
+    let clawback = state.clawback_with_status(clawback_id, ClawbackStatus::Pending {});
+    assert!(
+        ctx.sender == clawback.to,
+        "Only the receiver of the transfer can challenge its clawback."
+    );
+    assert!(
+        ctx.block_production_time < clawback.executable_at,
+        "Dispute window ended at: {}",
+        clawback.executable_at
+    );
+    clawback.status = ClawbackStatus::Challenged {};
+    state.assert_invariants();
+
+    state
+}
+
+/// Execute the pending clawback `clawback_id` once its dispute window has passed, returning the
+/// amount to the sender of the transfer. Only accounts with the [`Role::Compliance`] role can call
+/// this action.
+///
+/// Panics if the caller does not have the role, if the clawback is not pending, or if the dispute
+/// window has not passed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `clawback_id`: [`u64`], the id of the clawback.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "clawback")]
+#[action(shortname = 0x96)]
+fn execute_clawback(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    clawback_id: u64,
+) -> TashiTokenState {
+    state.assert_role(Role::Compliance {}, &ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "execute_clawback", clawback_id);
+    let clawback = state.clawback_with_status(clawback_id, ClawbackStatus::Pending {});
+    assert!(
+        ctx.block_production_time >= clawback.executable_at,
+        "Clawback can be executed from: {}",
+        clawback.executable_at
+    );
+    clawback.status = ClawbackStatus::Executed {};
+    let (from, amount) = (clawback.from, clawback.amount);
+    state.credit(from, amount);
+    state.assert_invariants();
+
+    state
+}
+
+/// Cancel the pending clawback `clawback_id`, returning the amount to the receiver of the
+/// transfer. Only accounts with the [`Role::Compliance`] role can call this action.
+///
+/// Panics if the caller does not have the role or if the clawback is not pending.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `clawback_id`: [`u64`], the id of the clawback.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "clawback")]
+#[action(shortname = 0x97)]
+fn cancel_clawback(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    clawback_id: u64,
+) -> TashiTokenState {
+    state.assert_role(Role::Compliance {}, &ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "cancel_clawback", clawback_id);
+    let clawback = state.clawback_with_status(clawback_id, ClawbackStatus::Pending {});
+    clawback.status = ClawbackStatus::Cancelled {};
+    let (to, amount) = (clawback.to, clawback.amount);
+    state.credit(to, amount);
+    state.assert_invariants();
+
+    state
+}
+
+/// Decide the challenged clawback `clawback_id`, returning the amount to the sender of the
+/// transfer if `uphold` is true and to its receiver otherwise. Only the owner can call this
+/// action.
+///
+/// Panics if the caller is not the owner or if the clawback is not challenged.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `clawback_id`: [`u64`], the id of the clawback.
+///   * `uphold`: [`bool`], true to execute the clawback, false to reject it.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "clawback")]
+#[action(shortname = 0x98)]
+fn resolve_clawback(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    clawback_id: u64,
+    uphold: bool,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "resolve_clawback", clawback_id, uphold);
+    let clawback = state.clawback_with_status(clawback_id, ClawbackStatus::Challenged {});
+    let receiver = if uphold {
+        clawback.status = ClawbackStatus::Executed {};
+        clawback.from
+    } else {
+        clawback.status = ClawbackStatus::Cancelled {};
+        clawback.to
+    };
+    let amount = clawback.amount;
+    state.credit(receiver, amount);
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,111 @@
+//! Tests of clawbacks: holding the amount, executing after the dispute window, cancelling, and
+//! challenges resolved by the owner.
+
+use pbc_contract_common::Hash;
+
+use super::{
+    cancel_clawback, challenge_clawback, execute_clawback, queue_clawback, resolve_clawback,
+    ClawbackStatus, CLAWBACK_DISPUTE_WINDOW,
+};
+use crate::admin::{grant_role, Role};
+use crate::hooks::Transfer;
+use crate::testing::{account, admin_context, context, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token where the owner sent account 1 1_000 and compliance officer account 9 queued the
+/// reversal of 600 of it.
+fn queued_state() -> TashiTokenState {
+    let mut state = grant_role(
+        admin_context(),
+        new_state(),
+        Role::Compliance {},
+        account(9),
+    );
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 1_000, None),
+    );
+    queue_clawback(
+        context(account(9)),
+        state,
+        Hash::digest(vec![1]),
+        account(0),
+        account(1),
+        600,
+        "Fraudulent payment".to_string(),
+    )
+}
+
+/// The status of clawback 0.
+fn status(state: &TashiTokenState) -> ClawbackStatus {
+    state.clawbacks.get(&0).unwrap().status
+}
+
+#[test]
+fn clawback_returns_the_amount_after_the_dispute_window() {
+    let state = queued_state();
+    assert_eq!(state.balance_of(&account(1)), 400);
+
+    let state = execute_clawback(context_at(account(9), CLAWBACK_DISPUTE_WINDOW), state, 0);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 400);
+    assert_eq!(state.balance_of(&account(1)), 400);
+    assert_eq!(status(&state), ClawbackStatus::Executed {});
+}
+
+#[test]
+#[should_panic(expected = "Clawback can be executed from: 604800000")]
+fn clawback_waits_for_the_dispute_window() {
+    execute_clawback(
+        context_at(account(9), CLAWBACK_DISPUTE_WINDOW - 1),
+        queued_state(),
+        0,
+    );
+}
+
+#[test]
+fn cancelled_clawback_returns_the_amount_to_the_receiver() {
+    let state = cancel_clawback(context(account(9)), queued_state(), 0);
+
+    assert_eq!(state.balance_of(&account(1)), 1_000);
+    assert_eq!(status(&state), ClawbackStatus::Cancelled {});
+}
+
+#[test]
+fn owner_upholds_or_rejects_a_challenge() {
+    let state = challenge_clawback(context(account(1)), queued_state(), 0);
+    let upheld = resolve_clawback(context(account(0)), state, 0, true);
+    assert_eq!(upheld.balance_of(&account(1)), 400);
+    assert_eq!(upheld.balance_of(&account(0)), INITIAL_SUPPLY - 400);
+
+    let state = challenge_clawback(context(account(1)), queued_state(), 0);
+    let rejected = resolve_clawback(context(account(0)), state, 0, false);
+    assert_eq!(rejected.balance_of(&account(1)), 1_000);
+    assert_eq!(status(&rejected), ClawbackStatus::Cancelled {});
+}
+
+#[test]
+#[should_panic(expected = "Clawback is not Pending, status: Challenged")]
+fn challenged_clawback_cannot_be_executed() {
+    let state = challenge_clawback(context(account(1)), queued_state(), 0);
+    execute_clawback(context_at(account(9), CLAWBACK_DISPUTE_WINDOW), state, 0);
+}
+
+#[test]
+#[should_panic(expected = "Only the receiver of the transfer can challenge its clawback.")]
+fn only_the_receiver_challenges() {
+    challenge_clawback(context(account(2)), queued_state(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Caller is missing role: Compliance")]
+fn only_compliance_officers_queue_clawbacks() {
+    queue_clawback(
+        context(account(2)),
+        new_state(),
+        Hash::digest(vec![1]),
+        account(0),
+        account(1),
+        600,
+        String::new(),
+    );
+}
@@ -0,0 +1,1049 @@
+//! Typed payload builders for off-chain services, so transactions to the token are built from the
+//! same argument types as the contract instead of a hand-rolled byte layout. Each builder returns
+//! the RPC payload of one action: its shortname, LEB128 encoded, followed by its arguments in the
+//! RPC format. Builders are grouped by the contract module defining the action, and exist for every
+//! action whatever the features of this build, so only actions enabled in the deployed contract
+//! should be sent. Compiled outside of WASM only, with the `client` feature.
+
+pub use pbc_contract_common::address::Address;
+pub use pbc_contract_common::public_key::PublicKey;
+pub use pbc_contract_common::signature::Signature;
+pub use pbc_contract_common::Hash;
+
+pub use crate::admin::Role;
+pub use crate::allowance::{SpenderAllowance, TransferTarget};
+pub use crate::budgets::BudgetRefill;
+pub use crate::circulating::NonCirculatingKind;
+pub use crate::compliance::ComplianceRule;
+pub use crate::cosigner::CoSigner;
+pub use crate::demurrage::DemurrageConfig;
+pub use crate::dust::DustDestination;
+pub use crate::external_compliance::ExternalCompliance;
+pub use crate::fee_burn::FeeBurnConfig;
+pub use crate::governance::{GovernanceConfig, ProposalAction};
+pub use crate::grants::MilestoneSpec;
+pub use crate::guards::AddressGuards;
+pub use crate::lending::LendingConfig;
+pub use crate::migration::MigrationMode;
+pub use crate::mint_limit::MintRateLimit;
+pub use crate::multi_token::IdTransfer;
+pub use crate::multicall::Call;
+pub use crate::organizations::{OrgAction, OrgSigner};
+pub use crate::referrals::BonusSource;
+pub use crate::reserves::ReserveAttester;
+pub use crate::reversible::ReversiblePolicy;
+pub use crate::sessions::{SessionAction, SessionScope};
+pub use crate::settlement::TransferInstruction;
+pub use crate::signatures::{SchemeSignature, SignerKey};
+pub use crate::split::SplitShare;
+pub use crate::sponsorship::SponsoredAction;
+pub use crate::timelock::AdminAction;
+pub use crate::token::Amount;
+pub use crate::treasury::TreasurySpendingLimit;
+
+#[cfg(test)]
+mod tests;
+
+/// The shortname the initialization of the contract is invoked at, sent as the LEB128 bytes
+/// `ff ff ff ff 0f` when deploying.
+pub const INIT_SHORTNAME: u32 = 0xffffffff;
+
+/// Writes `shortname` to `payload` in LEB128, seven bits per byte with the high bit set on every
+/// byte but the last.
+///
+/// ### Parameters:
+///
+///   * `shortname`: [`u32`], shortname of the action.
+///   * `payload`: [`Vec`]<[`u8`]>, payload to append to.
+fn write_shortname(shortname: u32, payload: &mut Vec<u8>) {
+    let mut rest = shortname;
+    while rest >= 0x80 {
+        payload.push((rest & 0x7f) as u8 | 0x80);
+        rest >>= 7;
+    }
+    payload.push(rest as u8);
+}
+
+/// Defines a builder per action, returning the shortname of the action followed by the RPC
+/// serialization of its arguments in order.
+macro_rules! payloads {
+    ($($name:ident($($arg:ident: $ty:ty),* $(,)?) = $shortname:expr;)*) => {
+        $(
+            #[doc = concat!(
+                "The payload of `",
+                stringify!($name),
+                "`, shortname `",
+                stringify!($shortname),
+                "`."
+            )]
+            #[allow(clippy::too_many_arguments)]
+            pub fn $name($($arg: $ty),*) -> Vec<u8> {
+                let mut payload = Vec::new();
+                super::write_shortname($shortname, &mut payload);
+                $(
+                    pbc_traits::ReadWriteRPC::rpc_write_to(&$arg, &mut payload)
+                        .expect("Writing to a vector cannot fail.");
+                )*
+                payload
+            }
+        )*
+    };
+}
+
+/// Payloads of the actions of the `acceptance` module.
+pub mod acceptance {
+    use super::*;
+
+    payloads! {
+        require_allowance_acceptance(required: bool) = 0xca;
+        accept_allowance(owner: Address) = 0xcb;
+        decline_allowance(owner: Address) = 0xcc;
+    }
+}
+
+/// Payloads of the actions of the `admin` module.
+pub mod admin {
+    use super::*;
+
+    payloads! {
+        transfer_ownership(new_owner: Address) = 0x15;
+        grant_role(role: Role, account: Address) = 0x2b;
+        revoke_role(role: Role, account: Address) = 0x2c;
+    }
+}
+
+/// Payloads of the actions of the `allowance` module.
+pub mod allowance {
+    use super::*;
+
+    payloads! {
+        transfer_from(from: Address, to: Address, amount: Amount) = 0x03;
+        bulk_transfer_from(from: Address, transfers: Vec<TransferTarget>) = 0x04;
+        approve(spender: Address, amount: Amount) = 0x05;
+        approve_relative(spender: Address, delta: i128) = 0x07;
+        set_operator(operator: Address, enabled: bool) = 0x31;
+        bulk_approve(approvals: Vec<SpenderAllowance>) = 0x32;
+        revoke_all_allowances() = 0x56;
+        revoke_allowances(spenders: Vec<Address>) = 0x57;
+        gc_allowances() = 0x58;
+    }
+}
+
+/// Payloads of the actions of the `amm` module.
+pub mod amm {
+    use super::*;
+
+    payloads! {
+        set_amm_pool(coin: Address, fee_bps: u16) = 0xb7;
+        add_liquidity(tokens: u128, coins: u128) = 0xb8;
+        remove_liquidity(shares: u128) = 0xb9;
+        swap_token_for_coin(amount: u128, min_out: u128) = 0xba;
+        swap_coin_for_token(amount: u128, min_out: u128) = 0xbb;
+    }
+}
+
+/// Payloads of the actions of the `auctions` module.
+pub mod auctions {
+    use super::*;
+
+    payloads! {
+        create_auction(
+            lot: String,
+            min_bid: u128,
+            commit_deadline: i64,
+            reveal_deadline: i64,
+        ) = 0x78;
+        commit_bid(auction_id: u64, commitment: Hash, deposit: u128) = 0x79;
+        reveal_bid(auction_id: u64, amount: u128, salt: Vec<u8>) = 0x7a;
+        settle_auction(auction_id: u64) = 0x7b;
+    }
+}
+
+/// Payloads of the actions of the `balance_roots` module.
+pub mod balance_roots {
+    payloads! {
+        record_balance_root() = 0xdb;
+    }
+}
+
+/// Payloads of the actions of the `bonding_curve` module.
+pub mod bonding_curve {
+    use super::*;
+
+    payloads! {
+        configure_bonding_curve(reserve_token: Address, base_price: u128, slope: u128) = 0x22;
+        buy_from_curve(amount: u128, max_cost: u128) = 0x23;
+        sell_to_curve(amount: u128, min_return: u128) = 0x24;
+    }
+}
+
+/// Payloads of the actions of the `budgets` module.
+pub mod budgets {
+    use super::*;
+
+    payloads! {
+        create_budget(name: String, allocation: u128, refill: Option<BudgetRefill>) = 0xe9;
+        set_budget_allocation(
+            budget_id: u64,
+            allocation: u128,
+            refill: Option<BudgetRefill>,
+        ) = 0xea;
+        set_budget_spender(budget_id: u64, spender: Address, allowed: bool) = 0xeb;
+        close_budget(budget_id: u64) = 0xec;
+        spend_from_budget(budget_id: u64, to: Address, amount: u128) = 0xed;
+    }
+}
+
+/// Payloads of the actions of the `burn_stats` module.
+pub mod burn_stats {
+    payloads! {
+        query_burn_stats() = 0xa2;
+    }
+}
+
+/// Payloads of the actions of the `burnable` module.
+pub mod burnable {
+    use super::*;
+
+    payloads! {
+        set_burn_rate(rate_bps: u16) = 0x10;
+        burn(amount: u128) = 0x12;
+        approve_burn(spender: Address, amount: u128) = 0x3c;
+        burn_from(owner: Address, amount: u128) = 0x3d;
+        burn_with_reason(amount: u128, reason_code: u32) = 0xae;
+    }
+}
+
+/// Payloads of the actions of the `channels` module.
+pub mod channels {
+    use super::*;
+
+    payloads! {
+        open_channel(
+            recipient: Address,
+            deposit: u128,
+            sender_key: SignerKey,
+            challenge_period: i64,
+        ) = 0x19;
+        close_channel(channel_id: u64, amount: u128, signature: SchemeSignature) = 0x1a;
+        challenge_channel(channel_id: u64, amount: u128, signature: SchemeSignature) = 0x1b;
+        settle_channel(channel_id: u64) = 0x1c;
+    }
+}
+
+/// Payloads of the actions of the `circulating` module.
+pub mod circulating {
+    use super::*;
+
+    payloads! {
+        set_non_circulating(account: Address, kind: NonCirculatingKind) = 0xa3;
+        remove_non_circulating(account: Address) = 0xa4;
+        query_circulating_supply() = 0xa5;
+    }
+}
+
+/// Payloads of the actions of the `clawback` module.
+pub mod clawback {
+    use super::*;
+
+    payloads! {
+        queue_clawback(
+            transaction: Hash,
+            from: Address,
+            to: Address,
+            amount: u128,
+            reason: String,
+        ) = 0x94;
+        challenge_clawback(clawback_id: u64) = 0x95;
+        execute_clawback(clawback_id: u64) = 0x96;
+        cancel_clawback(clawback_id: u64) = 0x97;
+        resolve_clawback(clawback_id: u64, uphold: bool) = 0x98;
+    }
+}
+
+/// Payloads of the actions of the `compliance` module.
+pub mod compliance {
+    use super::*;
+
+    payloads! {
+        set_compliance_rule(name: String, rule: Option<ComplianceRule>) = 0x48;
+        set_jurisdiction(account: Address, jurisdiction: Option<u16>) = 0x49;
+    }
+}
+
+/// Payloads of the actions of the `conditional` module.
+pub mod conditional {
+    use super::*;
+
+    payloads! {
+        transfer_if(
+            condition_contract: Address,
+            condition_payload: Vec<u8>,
+            to: Address,
+            amount: u128,
+        ) = 0x6c;
+    }
+}
+
+/// Payloads of the actions of the `cooldown` module.
+pub mod cooldown {
+    payloads! {
+        set_transfer_cooldown(cooldown: i64) = 0x35;
+    }
+}
+
+/// Payloads of the actions of the `cosigner` module.
+pub mod cosigner {
+    use super::*;
+
+    payloads! {
+        set_cosigner(cosigner: CoSigner) = 0xe4;
+        remove_cosigner(account: Address) = 0xe5;
+        transfer_cosigned(to: Address, amount: u128) = 0xe6;
+        confirm_cosigned_transfer(transfer_id: u64) = 0xe7;
+        cancel_cosigned_transfer(transfer_id: u64) = 0xe8;
+    }
+}
+
+/// Payloads of the actions of the `crowdsale` module.
+pub mod crowdsale {
+    use super::*;
+
+    payloads! {
+        configure_sale(
+            payment_token: Address,
+            rate_numerator: u128,
+            rate_denominator: u128,
+            soft_cap: u128,
+            hard_cap: u128,
+            start_time: i64,
+            end_time: i64,
+        ) = 0x1d;
+        buy(amount: u128) = 0x1e;
+        claim_sale_tokens() = 0x1f;
+        claim_refund() = 0x20;
+        close_sale() = 0x21;
+    }
+}
+
+/// Payloads of the actions of the `demurrage` module.
+pub mod demurrage {
+    use super::*;
+
+    payloads! {
+        set_demurrage(config: Option<DemurrageConfig>) = 0xac;
+        accrue_demurrage() = 0xad;
+    }
+}
+
+/// Payloads of the actions of the `deposits` module.
+pub mod deposits {
+    use super::*;
+
+    payloads! {
+        deposit_token(token: Address, amount: u128) = 0x51;
+        withdraw_token(token: Address, amount: u128) = 0x52;
+    }
+}
+
+/// Payloads of the actions of the `dormancy` module.
+pub mod dormancy {
+    use super::*;
+
+    payloads! {
+        set_dormancy_policy(
+            inactivity_period: i64,
+            grace_period: i64,
+            notifier: Option<Address>,
+        ) = 0x8f;
+        confirm_activity() = 0x90;
+        flag_dormant(account: Address) = 0x91;
+        sweep_dormant(account: Address) = 0x92;
+        reclaim_escheated() = 0x93;
+    }
+}
+
+/// Payloads of the actions of the `dust` module.
+pub mod dust {
+    use super::*;
+
+    payloads! {
+        configure_dust_sweep(threshold: u128, destination: DustDestination) = 0x59;
+        sweep_own_dust() = 0x5a;
+        sweep_dust(accounts: Vec<Address>) = 0x5b;
+    }
+}
+
+/// Payloads of the actions of the `emergency` module.
+pub mod emergency {
+    payloads! {
+        emergency_pause() = 0x99;
+        extend_emergency_pause(until: i64) = 0x9a;
+        set_emergency_pause_duration(duration: i64) = 0x9b;
+    }
+}
+
+/// Payloads of the actions of the `emission` module.
+pub mod emission {
+    use super::*;
+
+    payloads! {
+        configure_emission(
+            destination: Address,
+            rate_per_epoch: u128,
+            epoch_length: i64,
+            end_time: i64,
+        ) = 0x38;
+        tick_emission() = 0x39;
+    }
+}
+
+/// Payloads of the actions of the `enforcement` module.
+pub mod enforcement {
+    use super::*;
+
+    payloads! {
+        freeze_account(account: Address, frozen: bool) = 0x46;
+        force_transfer(case_id: String, from: Address, to: Address, amount: u128) = 0x47;
+        freeze_amount(account: Address, amount: u128) = 0xc5;
+    }
+}
+
+/// Payloads of the actions of the `external_compliance` module.
+pub mod external_compliance {
+    use super::*;
+
+    payloads! {
+        set_external_compliance(compliance: Option<ExternalCompliance>) = 0xd5;
+        transfer_checked(to: Address, amount: u128) = 0xd6;
+    }
+}
+
+/// Payloads of the actions of the `fee_burn` module.
+pub mod fee_burn {
+    use super::*;
+
+    payloads! {
+        set_fee_burn_config(config: Option<FeeBurnConfig>) = 0xa8;
+        burn_collected_fees() = 0xa9;
+    }
+}
+
+/// Payloads of the actions of the `flash_mint` module.
+pub mod flash_mint {
+    use super::*;
+
+    payloads! {
+        flash_mint(amount: u128, target_contract: Address, payload: Vec<u8>) = 0x28;
+        set_flash_mint_config(max_amount: u128, fee_bps: u16) = 0x29;
+        set_flash_mint_receiver(receiver: Address, allowed: bool) = 0x100;
+    }
+}
+
+/// Payloads of the actions of the `governance` module.
+pub mod governance {
+    use super::*;
+
+    payloads! {
+        propose(description: String, actions: Vec<ProposalAction>) = 0x2d;
+        cast_vote(proposal_id: u64, support: bool, weight: u128) = 0x2e;
+        withdraw_votes(proposal_id: u64) = 0x2f;
+        execute_proposal(proposal_id: u64) = 0x30;
+    }
+}
+
+/// Payloads of the actions of the `grants` module.
+pub mod grants {
+    use super::*;
+
+    payloads! {
+        create_milestone_grant(recipient: Address, milestones: Vec<MilestoneSpec>) = 0xdf;
+        approve_milestone(grant_id: u64, milestone: u32) = 0xe0;
+        claw_back_milestones(grant_id: u64) = 0xe1;
+    }
+}
+
+/// Payloads of the actions of the `guards` module.
+pub mod guards {
+    use super::*;
+
+    payloads! {
+        configure_address_guards(guards: AddressGuards) = 0x3a;
+        set_known_receiver(receiver: Address, known: bool) = 0x3b;
+    }
+}
+
+/// Payloads of the actions of the `history` module.
+pub mod history {
+    payloads! {
+        query_recent_transfers() = 0xbe;
+    }
+}
+
+/// Payloads of the actions of the `htlc` module.
+pub mod htlc {
+    use super::*;
+
+    payloads! {
+        lock_htlc(receiver: Address, amount: u128, hashlock: Hash, timelock: i64) = 0x25;
+        claim_htlc(preimage: Vec<u8>) = 0x26;
+        refund_htlc(hashlock: Hash) = 0x27;
+    }
+}
+
+/// Payloads of the actions of the `idempotency` module.
+pub mod idempotency {
+    use super::*;
+
+    payloads! {
+        transfer_idempotent(to: Address, amount: u128, key: Option<u128>) = 0xfd;
+    }
+}
+
+/// Payloads of the actions of the `interest` module.
+pub mod interest {
+    payloads! {
+        update_interest_index(new_index: u128) = 0x2a;
+    }
+}
+
+/// Payloads of the actions of the `invoices` module.
+pub mod invoices {
+    use super::*;
+
+    payloads! {
+        create_invoice(payer: Address, amount: u128, reference: String, expires_at: i64) = 0x68;
+        pay_invoice(invoice_id: u64) = 0x69;
+        cancel_invoice(invoice_id: u64) = 0x6a;
+        query_invoice_status(invoice_id: u64) = 0x6b;
+    }
+}
+
+/// Payloads of the actions of the `labels` module.
+pub mod labels {
+    payloads! {
+        set_label_fee(fee: u128) = 0xc6;
+        claim_label(label: String) = 0xc7;
+        release_label() = 0xc8;
+        transfer_to_label(label: String, amount: u128) = 0xc9;
+    }
+}
+
+/// Payloads of the actions of the `launch` module.
+pub mod launch {
+    use super::*;
+
+    payloads! {
+        enable_trading() = 0x36;
+        set_launch_allowlisted(account: Address, allowed: bool) = 0x37;
+    }
+}
+
+/// Payloads of the actions of the `lending` module.
+pub mod lending {
+    use super::*;
+
+    payloads! {
+        set_lending_config(config: LendingConfig) = 0xf3;
+        deposit_to_lending_pool(amount: u128) = 0xf4;
+        withdraw_from_lending_pool(shares: u128) = 0xf5;
+        deposit_collateral(amount: u128) = 0xf6;
+        withdraw_collateral(amount: u128) = 0xf7;
+        borrow_from_lending_pool(amount: u128) = 0xf8;
+        repay_loan(amount: u128) = 0xf9;
+        liquidate_loan(borrower: Address) = 0xfa;
+    }
+}
+
+/// Payloads of the actions of the `limits` module.
+pub mod limits {
+    use super::*;
+
+    payloads! {
+        set_transfer_limits(
+            max_transfer_amount: Option<u128>,
+            max_wallet_balance: Option<u128>,
+        ) = 0x33;
+        set_limit_exempt(account: Address, exempt: bool) = 0x34;
+        set_min_transfer_amount(min_transfer_amount: u128) = 0x5c;
+    }
+}
+
+/// Payloads of the actions of the `lockbox` module.
+pub mod lockbox {
+    payloads! {
+        lock_until(amount: u128, until: i64) = 0xe2;
+        extend_lock(until: i64) = 0xe3;
+    }
+}
+
+/// Payloads of the actions of the `memos` module.
+pub mod memos {
+    use super::*;
+
+    payloads! {
+        transfer_with_memo(to: Address, amount: Amount, memo: Vec<u8>) = 0xbc;
+        transfer_from_with_memo(from: Address, to: Address, amount: Amount, memo: Vec<u8>) = 0xbd;
+    }
+}
+
+/// Payloads of the actions of the `metrics` module.
+pub mod metrics {
+    payloads! {
+        query_usage_metrics() = 0xfe;
+    }
+}
+
+/// Payloads of the actions of the `migration` module.
+pub mod migration {
+    use super::*;
+
+    payloads! {
+        configure_migration(legacy_token: Address, mode: MigrationMode) = 0x43;
+        claim_migration(amount: u128, proof: Vec<Hash>) = 0x44;
+        migrate_legacy_tokens(amount: u128) = 0x45;
+    }
+}
+
+/// Payloads of the actions of the `mint_limit` module.
+pub mod mint_limit {
+    use super::*;
+
+    payloads! {
+        set_mint_rate_limit(limit: Option<MintRateLimit>) = 0x9f;
+    }
+}
+
+/// Payloads of the actions of the `mintable` module.
+pub mod mintable {
+    use super::*;
+
+    payloads! {
+        mint(receiver: Address, amount: u128) = 0x11;
+    }
+}
+
+/// Payloads of the actions of the `multi_token` module.
+pub mod multi_token {
+    use super::*;
+
+    payloads! {
+        create_token_id(name: String, symbol: String, decimals: u8, initial_supply: u128) = 0xaf;
+        mint_token_id(id: u64, receiver: Address, amount: u128) = 0xb0;
+        burn_token_id(id: u64, amount: u128) = 0xb1;
+        batch_transfer_ids(transfers: Vec<IdTransfer>) = 0xb2;
+        batch_transfer_ids_from(from: Address, transfers: Vec<IdTransfer>) = 0xb3;
+    }
+}
+
+/// Payloads of the actions of the `multicall` module.
+pub mod multicall {
+    use super::*;
+
+    payloads! {
+        multicall(calls: Vec<Call>) = 0x80;
+    }
+}
+
+/// Payloads of the actions of the `nonces` module.
+pub mod nonces {
+    use super::*;
+
+    payloads! {
+        set_nonces_on_transfers(enabled: bool) = 0xbf;
+        query_nonce(account: Address) = 0xc0;
+    }
+}
+
+/// Payloads of the actions of the `observers` module.
+pub mod observers {
+    use super::*;
+
+    payloads! {
+        add_supply_observer(observer: Address) = 0x54;
+        remove_supply_observer(observer: Address) = 0x55;
+    }
+}
+
+/// Payloads of the actions of the `oracle_fees` module.
+pub mod oracle_fees {
+    use super::*;
+
+    payloads! {
+        set_price_oracle(oracle: Option<Address>) = 0x6e;
+        push_price(price: u128) = 0x6f;
+        set_reference_fees(flat_fee: u128, min_transfer: u128) = 0x70;
+    }
+}
+
+/// Payloads of the actions of the `organizations` module.
+pub mod organizations {
+    use super::*;
+
+    payloads! {
+        create_organization(signers: Vec<OrgSigner>, required_confirmations: u32) = 0xee;
+        deposit_to_organization(org_id: u64, amount: u128) = 0xef;
+        propose_org_action(org_id: u64, action: OrgAction) = 0xf0;
+        confirm_org_action(org_id: u64, action_id: u64) = 0xf1;
+        cancel_org_action(org_id: u64, action_id: u64) = 0xf2;
+    }
+}
+
+/// Payloads of the actions of the `pausable` module.
+pub mod pausable {
+    payloads! {
+        pause() = 0x13;
+        unpause() = 0x14;
+    }
+}
+
+/// Payloads of the actions of the `payroll` module.
+pub mod payroll {
+    use super::*;
+
+    payloads! {
+        set_payroll_recipient(
+            recipient: Address,
+            amount_per_period: u128,
+            period: i64,
+            first_payment_at: i64,
+        ) = 0x62;
+        fund_payroll(amount: u128) = 0x63;
+        run_payroll() = 0x64;
+    }
+}
+
+/// Payloads of the actions of the `pending` module.
+pub mod pending {
+    use super::*;
+
+    payloads! {
+        transfer_pending(to: Address, amount: u128, expiry: i64) = 0xcd;
+        claim_pending_transfer(transfer_id: u64) = 0xce;
+        reject_pending_transfer(transfer_id: u64) = 0xcf;
+        refund_pending_transfer(transfer_id: u64) = 0xd0;
+    }
+}
+
+/// Payloads of the actions of the `raffles` module.
+pub mod raffles {
+    use super::*;
+
+    payloads! {
+        create_raffle(
+            ticket_price: u128,
+            sales_end: i64,
+            draw_deadline: i64,
+            winner_count: u32,
+            burn_bps: u16,
+            treasury_bps: u16,
+            seed_commitment: Hash,
+        ) = 0x7c;
+        buy_tickets(raffle_id: u64, count: u32) = 0x7d;
+        draw_raffle(raffle_id: u64, seed: Vec<u8>) = 0x7e;
+        cancel_raffle(raffle_id: u64) = 0x7f;
+    }
+}
+
+/// Payloads of the actions of the `recovery` module.
+pub mod recovery {
+    use super::*;
+
+    payloads! {
+        set_guardians(guardians: Vec<Address>, threshold: u32, delay: i64) = 0x89;
+        remove_guardians() = 0x8a;
+        approve_recovery(account: Address, new_address: Address) = 0x8b;
+        veto_recovery() = 0x8c;
+        execute_recovery(account: Address) = 0x8d;
+    }
+}
+
+/// Payloads of the actions of the `redemption` module.
+pub mod redemption {
+    use super::*;
+
+    payloads! {
+        configure_reserve(token: Address) = 0x71;
+        fund_reserve(amount: u128) = 0x72;
+        redeem(amount: u128) = 0x73;
+    }
+}
+
+/// Payloads of the actions of the `redenomination` module.
+pub mod redenomination {
+    payloads! {
+        redenominate(decimals: u8) = 0xfb;
+    }
+}
+
+/// Payloads of the actions of the `referrals` module.
+pub mod referrals {
+    use super::*;
+
+    payloads! {
+        set_referrer(referrer: Address) = 0x40;
+        configure_referrals(bonus_bps: u16, source: BonusSource) = 0x41;
+        fund_referral_pool(amount: u128) = 0x42;
+    }
+}
+
+/// Payloads of the actions of the `reserves` module.
+pub mod reserves {
+    use super::*;
+
+    payloads! {
+        set_reserve_attester(attester: Option<ReserveAttester>) = 0xc2;
+        attest_reserve(reserve: u128, attested_at: i64, signature: SchemeSignature) = 0xc3;
+        query_reserve_attestation() = 0xc4;
+    }
+}
+
+/// Payloads of the actions of the `reversible` module.
+pub mod reversible {
+    use super::*;
+
+    payloads! {
+        set_reversible_policy(policy: Option<ReversiblePolicy>) = 0xd1;
+        freeze_reversible_transfer(transfer_id: u64) = 0xd2;
+        reverse_reversible_transfer(transfer_id: u64) = 0xd3;
+        settle_reversible_transfer(transfer_id: u64) = 0xd4;
+    }
+}
+
+/// Payloads of the actions of the `rewards` module.
+pub mod rewards {
+    payloads! {
+        fund_rewards(amount: u128) = 0x3e;
+        claim_rewards() = 0x3f;
+    }
+}
+
+/// Payloads of the actions of the `round_up` module.
+pub mod round_up {
+    use super::*;
+
+    payloads! {
+        set_charity(charity: Option<Address>) = 0xaa;
+        set_round_up(precision: u128) = 0xab;
+    }
+}
+
+/// Payloads of the actions of the `scheduled_mints` module.
+pub mod scheduled_mints {
+    use super::*;
+
+    payloads! {
+        schedule_mint(destination: Address, amount: u128, execute_at: i64) = 0xa0;
+        trigger_scheduled_mint(mint_id: u64) = 0xa1;
+    }
+}
+
+/// Payloads of the actions of the `sessions` module.
+pub mod sessions {
+    use super::*;
+
+    payloads! {
+        register_session_key(key: Address, scope: SessionScope) = 0x81;
+        revoke_session_key(key: Address) = 0x82;
+        session_transfer(account: Address, to: Address, amount: u128) = 0x83;
+        session_approve(account: Address, spender: Address, amount: u128) = 0x84;
+    }
+}
+
+/// Payloads of the actions of the `settlement` module.
+pub mod settlement {
+    use super::*;
+
+    payloads! {
+        settle_batch(instructions: Vec<TransferInstruction>) = 0xfc;
+    }
+}
+
+/// Payloads of the actions of the `shards` module.
+pub mod shards {
+    use super::*;
+
+    payloads! {
+        reshard_balances(shard_count: u16) = 0x102;
+    }
+}
+
+/// Payloads of the actions of the `soulbound` module.
+pub mod soulbound {
+    use super::*;
+
+    payloads! {
+        set_soulbound(soulbound: bool) = 0xd7;
+        set_account_soulbound(account: Address, soulbound: bool) = 0xd8;
+        approve_soulbound_migration(account: Address, new_account: Option<Address>) = 0xd9;
+        migrate_soulbound_balance() = 0xda;
+    }
+}
+
+/// Payloads of the actions of the `split` module.
+pub mod split {
+    use super::*;
+
+    payloads! {
+        transfer_split(recipients: Vec<SplitShare>, amount: u128) = 0x61;
+    }
+}
+
+/// Payloads of the actions of the `sponsorship` module.
+pub mod sponsorship {
+    use super::*;
+
+    payloads! {
+        register_sponsor(actions: Vec<SponsoredAction>, cohort: Option<Vec<Address>>) = 0x74;
+        unregister_sponsor() = 0x75;
+        sponsored_transfer(
+            to: Address,
+            amount: u128,
+            nonce: u64,
+            key: SignerKey,
+            signature: SchemeSignature,
+        ) = 0x76;
+        sponsored_approve(
+            spender: Address,
+            amount: u128,
+            nonce: u64,
+            key: SignerKey,
+            signature: SchemeSignature,
+        ) = 0x77;
+    }
+}
+
+/// Payloads of the actions of the `state_hash` module.
+pub mod state_hash {
+    payloads! {
+        record_state_hash() = 0xff;
+    }
+}
+
+/// Payloads of the actions of the `streaming` module.
+pub mod streaming {
+    use super::*;
+
+    payloads! {
+        create_stream(recipient: Address, total: u128, start_time: i64, end_time: i64) = 0x16;
+        withdraw_from_stream(stream_id: u64) = 0x17;
+        cancel_stream(stream_id: u64) = 0x18;
+    }
+}
+
+/// Payloads of the actions of the `subscriptions` module.
+pub mod subscriptions {
+    use super::*;
+
+    payloads! {
+        subscribe(merchant: Address, amount_per_period: u128, period: i64) = 0x65;
+        charge(subscriber: Address) = 0x66;
+        cancel_subscription(merchant: Address) = 0x67;
+    }
+}
+
+/// Payloads of the actions of the `timelock` module.
+pub mod timelock {
+    use super::*;
+
+    payloads! {
+        queue_action(action: AdminAction) = 0x9c;
+        execute_action(action_id: u64) = 0x9d;
+        cancel_action(action_id: u64) = 0x9e;
+    }
+}
+
+/// Payloads of the actions of the `token` module.
+pub mod token {
+    use super::*;
+
+    payloads! {
+        transfer(to: Address, amount: Amount) = 0x01;
+        bulk_transfer(transfers: Vec<TransferTarget>) = 0x02;
+        initialize(
+            name: String,
+            symbol: String,
+            decimals: u8,
+            total_supply: Amount,
+        ) = INIT_SHORTNAME;
+    }
+}
+
+/// Payloads of the actions of the `treasury` module.
+pub mod treasury {
+    use super::*;
+
+    payloads! {
+        treasury_transfer(receiver: Address, amount: u128, memo: String) = 0xa6;
+        set_treasury_spending_limit(limit: Option<TreasurySpendingLimit>) = 0xa7;
+    }
+}
+
+/// Payloads of the actions of the `units` module.
+pub mod units {
+    use super::*;
+
+    payloads! {
+        transfer_display(receiver: Address, amount: String) = 0x5d;
+        transfer_from_display(from: Address, receiver: Address, amount: String) = 0x5e;
+        approve_display(spender: Address, amount: String) = 0x5f;
+        query_display_balance_of(owner: Address) = 0x60;
+    }
+}
+
+/// Payloads of the actions of the `vesting` module.
+pub mod vesting {
+    use super::*;
+
+    payloads! {
+        create_vesting_grant(
+            beneficiary: Address,
+            total: u128,
+            start_time: i64,
+            cliff_time: i64,
+            end_time: i64,
+            revocable: bool,
+        ) = 0xdc;
+        claim_vested(grant_id: u64) = 0xdd;
+        revoke_vesting_grant(grant_id: u64) = 0xde;
+    }
+}
+
+/// Payloads of the actions of the `views` module.
+pub mod views {
+    use super::*;
+
+    payloads! {
+        query_name() = 0x4a;
+        query_symbol() = 0x4b;
+        query_decimals() = 0x4c;
+        query_total_supply() = 0x4d;
+        query_balance_of(owner: Address) = 0x4e;
+        query_allowance(owner: Address, spender: Address) = 0x4f;
+        query_balances_of(owners: Vec<Address>) = 0xc1;
+        query_allowances_of(owner: Address, offset: u32, limit: u32) = 0x101;
+    }
+}
+
+/// Payloads of the actions of the `vote_escrow` module.
+pub mod vote_escrow {
+    use super::*;
+
+    payloads! {
+        create_lock(amount: u128, unlock_at: i64) = 0x85;
+        extend_lock(amount: u128, unlock_at: i64) = 0x86;
+        withdraw_after_expiry() = 0x87;
+        query_ve_weight(account: Address) = 0x88;
+    }
+}
+
+/// Payloads of the actions of the `wrapper` module.
+pub mod wrapper {
+    use super::*;
+
+    payloads! {
+        set_underlying_token(token: Address) = 0xb4;
+        wrap(amount: u128) = 0xb5;
+        unwrap(amount: u128) = 0xb6;
+    }
+}
@@ -0,0 +1,156 @@
+//! Golden tests of the payload builders: the exact bytes of the MPC-20 actions, of the
+//! initialization and of a few extensions, so a change of a shortname or of an argument type
+//! breaks the build of off-chain services here rather than on-chain.
+
+use super::{allowance, dust, shards, token, views, write_shortname, DustDestination};
+use crate::allowance::TransferTarget;
+use crate::testing::account;
+use crate::token::stored_amount;
+
+/// The RPC bytes of [`account`] `index`: the account address type, then the identifier.
+fn account_bytes(index: u8) -> Vec<u8> {
+    [vec![0x00], vec![index; 20]].concat()
+}
+
+/// The RPC bytes of an amount stored as [`crate::token::Amount`], big endian.
+#[cfg(not(feature = "u64-amounts"))]
+fn amount_bytes(amount: u128) -> Vec<u8> {
+    amount.to_be_bytes().to_vec()
+}
+
+/// The RPC bytes of an amount stored as [`crate::token::Amount`], big endian.
+#[cfg(feature = "u64-amounts")]
+fn amount_bytes(amount: u128) -> Vec<u8> {
+    (amount as u64).to_be_bytes().to_vec()
+}
+
+#[test]
+fn shortnames_are_leb128_encoded() {
+    let encoded = |shortname: u32| {
+        let mut payload = Vec::new();
+        write_shortname(shortname, &mut payload);
+        payload
+    };
+
+    assert_eq!(encoded(0x01), vec![0x01]);
+    assert_eq!(encoded(0x7f), vec![0x7f]);
+    assert_eq!(encoded(0x80), vec![0x80, 0x01]);
+    assert_eq!(encoded(0x102), vec![0x82, 0x02]);
+    assert_eq!(encoded(0xffffffff), vec![0xff, 0xff, 0xff, 0xff, 0x0f]);
+}
+
+#[test]
+fn initialize_payload() {
+    let expected = [
+        vec![0xff, 0xff, 0xff, 0xff, 0x0f],
+        vec![0x00, 0x00, 0x00, 0x0b],
+        b"Tashi Token".to_vec(),
+        vec![0x00, 0x00, 0x00, 0x03],
+        b"TAS".to_vec(),
+        vec![0x08],
+        amount_bytes(1_000_000),
+    ]
+    .concat();
+
+    assert_eq!(
+        token::initialize(
+            "Tashi Token".to_string(),
+            "TAS".to_string(),
+            8,
+            stored_amount(1_000_000),
+        ),
+        expected
+    );
+}
+
+#[test]
+fn transfer_payload() {
+    let expected = [vec![0x01], account_bytes(1), amount_bytes(100)].concat();
+
+    assert_eq!(token::transfer(account(1), stored_amount(100)), expected);
+}
+
+#[test]
+#[cfg(not(feature = "u64-amounts"))]
+fn transfer_payload_bytes() {
+    let mut expected = vec![0x01, 0x00];
+    expected.extend([0x01; 20]);
+    expected.extend([0x00; 15]);
+    expected.push(0x64);
+
+    assert_eq!(token::transfer(account(1), 100), expected);
+}
+
+#[test]
+fn bulk_transfer_payload() {
+    let targets = vec![
+        TransferTarget {
+            to: account(1),
+            amount: stored_amount(5),
+        },
+        TransferTarget {
+            to: account(2),
+            amount: stored_amount(6),
+        },
+    ];
+    let expected = [
+        vec![0x02],
+        vec![0x00, 0x00, 0x00, 0x02],
+        account_bytes(1),
+        amount_bytes(5),
+        account_bytes(2),
+        amount_bytes(6),
+    ]
+    .concat();
+
+    assert_eq!(token::bulk_transfer(targets), expected);
+}
+
+#[test]
+fn transfer_from_payload() {
+    let expected = [
+        vec![0x03],
+        account_bytes(1),
+        account_bytes(2),
+        amount_bytes(300),
+    ]
+    .concat();
+
+    assert_eq!(
+        allowance::transfer_from(account(1), account(2), stored_amount(300)),
+        expected
+    );
+}
+
+#[test]
+fn approve_payload() {
+    let expected = [vec![0x05], account_bytes(2), amount_bytes(300)].concat();
+
+    assert_eq!(allowance::approve(account(2), stored_amount(300)), expected);
+}
+
+#[test]
+fn approve_relative_payload_is_twos_complement() {
+    let expected = [vec![0x07], account_bytes(2), vec![0xff; 15], vec![0xf6]].concat();
+
+    assert_eq!(allowance::approve_relative(account(2), -10), expected);
+}
+
+#[test]
+fn extension_payloads() {
+    assert_eq!(
+        dust::configure_dust_sweep(100, DustDestination::Burn {}),
+        [vec![0x59], 100u128.to_be_bytes().to_vec(), vec![0x01]].concat()
+    );
+    assert_eq!(
+        views::query_allowances_of(account(3), 2, 5),
+        [
+            vec![0x81, 0x02],
+            account_bytes(3),
+            vec![0x00, 0x00, 0x00, 0x02],
+            vec![0x00, 0x00, 0x00, 0x05],
+        ]
+        .concat()
+    );
+    assert_eq!(shards::reshard_balances(64), vec![0x82, 0x02, 0x00, 0x40]);
+}
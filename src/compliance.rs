@@ -0,0 +1,186 @@
+//! A compliance rule engine. The owner enables named rules, each with its own parameters, and
+//! every transfer is checked against all enabled rules by a single `check_transfer` pipeline, as
+//! are funds escrowed by an account and paid out of escrow to another, see
+//! [`crate::policy`]. The owner and the contract itself are not subject to the rules.
+#![cfg_attr(not(feature = "compliance"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "compliance")]
+use crate::admin::Role;
+use crate::hooks::Transfer;
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "compliance"))]
+mod tests;
+
+/// A pair of jurisdictions transfers may be made between, in this direction.
+///
+/// ### Fields:
+///
+///   * `from`: [`u16`], jurisdiction code of the sender.
+///   * `to`: [`u16`], jurisdiction code of the receiver.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JurisdictionPair {
+    pub from: u16,
+    pub to: u16,
+}
+
+/// A compliance rule and its parameters.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ComplianceRule {
+    /// No transfer can leave its receiver with more than `max`.
+    #[discriminant(0)]
+    MaxBalance { max: u128 },
+    /// Both parties must be registered in one of the `allowed` jurisdictions.
+    #[discriminant(1)]
+    Jurisdiction { allowed: Vec<u16> },
+    /// Both parties must be one of `accounts`.
+    #[discriminant(2)]
+    Allowlist { accounts: Vec<Address> },
+    /// No transfers before `until`, a block production time in milliseconds.
+    #[discriminant(3)]
+    Lockup { until: i64 },
+    /// The jurisdictions of the sender and the receiver must be one of the `allowed` pairs. Funds
+    /// paid out of escrow are checked as sent by the account that escrowed them.
+    #[discriminant(4)]
+    JurisdictionPairs { allowed: Vec<JurisdictionPair> },
+}
+
+impl TashiTokenState {
+    /// Checks `transfer` against every enabled compliance rule, in order of rule name.
+    ///
+    /// Panics with the name of the first rule the transfer breaks.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `transfer`: [`Transfer`], the executed transfer.
+    pub fn check_transfer(&self, ctx: &ContractContext, transfer: &Transfer) {
+        let subject =
+            |account: &Address| *account != self.owner && *account != ctx.contract_address;
+        let parties: Vec<&Address> = [&transfer.from, &transfer.to]
+            .into_iter()
+            .filter(|account| subject(account))
+            .collect();
+        for (name, rule) in self.compliance_rules.iter() {
+            let compliant = match rule {
+                ComplianceRule::MaxBalance { max } => {
+                    !subject(&transfer.to) || self.balance_of(&transfer.to) <= *max
+                }
+                ComplianceRule::Jurisdiction { allowed } => parties.iter().all(|account| {
+                    self.jurisdictions
+                        .get(account)
+                        .is_some_and(|jurisdiction| allowed.contains(jurisdiction))
+                }),
+                ComplianceRule::Allowlist { accounts } => {
+                    parties.iter().all(|account| accounts.contains(account))
+                }
+                ComplianceRule::Lockup { until } => {
+                    parties.is_empty() || ctx.block_production_time >= *until
+                }
+                ComplianceRule::JurisdictionPairs { allowed } => {
+                    parties.len() < 2
+                        || match (
+                            self.jurisdictions.get(&transfer.from),
+                            self.jurisdictions.get(&transfer.to),
+                        ) {
+                            (Some(&from), Some(&to)) => {
+                                allowed.contains(&JurisdictionPair { from, to })
+                            }
+                            _ => false,
+                        }
+                }
+            };
+            assert!(compliant, "Transfer breaks compliance rule: {}", name);
+        }
+    }
+}
+
+/// Enable the compliance rule `name` with its parameters, replacing a rule of the same name, or
+/// disable it. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if a rule that is not enabled is disabled.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `name`: [`String`], name of the rule.
+///   * `rule`: [`Option`]<[`ComplianceRule`]>, the rule to enable, `None` to disable it.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "compliance")]
+#[action(shortname = 0x48)]
+fn set_compliance_rule(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    name: String,
+    rule: Option<ComplianceRule>,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_compliance_rule", name, rule);
+    match rule {
+        Some(rule) => {
+            state.compliance_rules.insert(name, rule);
+        }
+        None => {
+            state
+                .compliance_rules
+                .remove(&name)
+                .unwrap_or_else(|| panic!("Compliance rule is not enabled: {}", name));
+        }
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Register the jurisdiction of `account`, or remove it. Only the owner and accounts with the
+/// [`Role::Compliance`] role can call this action.
+///
+/// Panics if the caller is neither the owner nor has the role.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account to register.
+///   * `jurisdiction`: [`Option`]<[`u16`]>, code of the jurisdiction of `account`, `None` to
+///     remove it.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "compliance")]
+#[action(shortname = 0x49)]
+fn set_jurisdiction(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+    jurisdiction: Option<u16>,
+) -> TashiTokenState {
+    if !state.has_role(Role::Compliance {}, &ctx.sender) {
+        state.assert_owner(&ctx.sender);
+    }
+    crate::audit::log_admin_action!(state, ctx, "set_jurisdiction", account, jurisdiction);
+    match jurisdiction {
+        Some(jurisdiction) => {
+            state.jurisdictions.insert(account, jurisdiction);
+        }
+        None => {
+            state.jurisdictions.remove(&account);
+        }
+    }
+    state.assert_invariants();
+
+    state
+}
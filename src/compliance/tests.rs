@@ -0,0 +1,135 @@
+//! Tests of jurisdiction rules: transfers between registered jurisdictions, allowed pairs in one
+//! direction only, also for payouts out of escrow, and transfers from the owner which are not
+//! subject to the rules.
+
+#[cfg(feature = "htlc")]
+use pbc_contract_common::Hash;
+
+use super::{set_compliance_rule, set_jurisdiction, ComplianceRule, JurisdictionPair};
+use crate::admin::{grant_role, Role};
+use crate::hooks::Transfer;
+#[cfg(feature = "htlc")]
+use crate::htlc::{claim_htlc, lock_htlc};
+use crate::testing::{account, admin_context, context, new_state};
+use crate::token::TashiTokenState;
+
+/// A token where accounts 1 to 3 are registered in jurisdictions 1 to 3 by compliance officer
+/// account 9, and account 1 holds 10_000.
+fn registered_state() -> TashiTokenState {
+    let mut state = grant_role(
+        admin_context(),
+        new_state(),
+        Role::Compliance {},
+        account(9),
+    );
+    for index in 1..=3 {
+        state = set_jurisdiction(
+            context(account(9)),
+            state,
+            account(index),
+            Some(u16::from(index)),
+        );
+    }
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    state
+}
+
+/// The state of [`registered_state`] allowing transfers from jurisdiction 1 to jurisdiction 2.
+fn pairs_state() -> TashiTokenState {
+    set_compliance_rule(
+        context(account(0)),
+        registered_state(),
+        "pairs".to_string(),
+        Some(ComplianceRule::JurisdictionPairs {
+            allowed: vec![JurisdictionPair { from: 1, to: 2 }],
+        }),
+    )
+}
+
+/// Transfers 100 from `from` to `to`.
+fn send(state: &mut TashiTokenState, from: u8, to: u8) {
+    state.execute_transfer(
+        &context(account(from)),
+        Transfer::new(account(from), account(to), 100, None),
+    );
+}
+
+/// Locks 100 from account 1 for `receiver` in a hash time locked transfer, which the receiver
+/// then claims.
+#[cfg(feature = "htlc")]
+fn swap(state: TashiTokenState, receiver: u8) -> TashiTokenState {
+    let preimage = b"swap secret".to_vec();
+    let state = lock_htlc(
+        context(account(1)),
+        state,
+        account(receiver),
+        100,
+        Hash::digest(preimage.clone()),
+        1_000,
+    );
+    claim_htlc(context(account(receiver)), state, preimage)
+}
+
+#[test]
+fn transfer_between_an_allowed_pair_is_executed() {
+    let mut state = pairs_state();
+    send(&mut state, 1, 2);
+
+    assert_eq!(state.balance_of(&account(2)), 100);
+}
+
+#[test]
+#[should_panic(expected = "Transfer breaks compliance rule: pairs")]
+fn pair_is_allowed_in_one_direction_only() {
+    let mut state = pairs_state();
+    send(&mut state, 1, 2);
+    send(&mut state, 2, 1);
+}
+
+#[test]
+#[should_panic(expected = "Transfer breaks compliance rule: pairs")]
+fn unregistered_receiver_is_rejected() {
+    let mut state = pairs_state();
+    send(&mut state, 1, 4);
+}
+
+#[test]
+fn transfers_from_the_owner_are_not_subject_to_the_rules() {
+    let mut state = pairs_state();
+    send(&mut state, 0, 4);
+
+    assert_eq!(state.balance_of(&account(4)), 100);
+}
+
+#[test]
+#[should_panic(expected = "Transfer breaks compliance rule: regions")]
+fn party_outside_the_allowed_jurisdictions_is_rejected() {
+    let mut state = set_compliance_rule(
+        context(account(0)),
+        registered_state(),
+        "regions".to_string(),
+        Some(ComplianceRule::Jurisdiction {
+            allowed: vec![1, 2],
+        }),
+    );
+    send(&mut state, 1, 2);
+    send(&mut state, 1, 3);
+}
+
+#[test]
+#[cfg(feature = "htlc")]
+fn escrow_payout_between_an_allowed_pair_is_executed() {
+    let state = swap(pairs_state(), 2);
+
+    assert_eq!(state.balance_of(&account(2)), 100);
+}
+
+#[test]
+#[cfg(feature = "htlc")]
+#[should_panic(expected = "Transfer breaks compliance rule: pairs")]
+fn escrow_payout_outside_the_allowed_pairs_is_rejected() {
+    swap(pairs_state(), 3);
+}
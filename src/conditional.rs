@@ -0,0 +1,116 @@
+//! Conditional transfers, completed only if a condition contract approves them. The amount is
+//! escrowed, then the condition contract is invoked at [`CONDITION_SHORTNAME`] with the argument
+//! `(condition_payload: Vec<u8>)` and must return a `bool`. The callback pays the receiver if the
+//! call succeeded and returned `true`, and refunds the sender otherwise. This makes
+//! delivery-versus-payment flows possible with an oracle or settlement contract as the condition.
+#![cfg_attr(not(feature = "conditional-transfers"), allow(dead_code))]
+
+#[cfg(feature = "conditional-transfers")]
+use pbc_contract_common::address::Address;
+#[cfg(feature = "conditional-transfers")]
+use pbc_contract_common::context::{CallbackContext, ContractContext};
+#[cfg(feature = "conditional-transfers")]
+use pbc_contract_common::events::EventGroup;
+#[cfg(feature = "conditional-transfers")]
+use pbc_contract_common::shortname::{Shortname, ShortnameCallback};
+
+#[cfg(feature = "conditional-transfers")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "conditional-transfers"))]
+mod tests;
+
+/// Shortname the condition contracts are invoked at.
+pub const CONDITION_SHORTNAME: u32 = 0x6d;
+
+/// Shortname of the callback completing or refunding a conditional transfer.
+#[cfg(feature = "conditional-transfers")]
+const TRANSFER_IF_CALLBACK_SHORTNAME: u32 = 0x07;
+
+/// Transfer `amount` tokens to address `to` from caller address if `condition_contract` approves
+/// `condition_payload`. The amount is escrowed right away, and paid to `to` or refunded to the
+/// caller once the condition contract answered.
+///
+/// Panics if `amount` is zero, if there is insufficient balance in caller account or if the
+/// address guards reject `to`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `condition_contract`: [`Address`], contract deciding whether the transfer completes.
+///   * `condition_payload`: [`Vec`]<[`u8`]>, condition passed to the condition contract.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`u128`], amount to transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "conditional-transfers")]
+#[action(shortname = 0x6c)]
+fn transfer_if(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    condition_contract: Address,
+    condition_payload: Vec<u8>,
+    to: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "transfer_if");
+    assert!(amount > 0, "Transferred amount cannot be zero.");
+    state.assert_valid_destination(&ctx, &to);
+    state.escrow(&ctx, ctx.sender, amount); // escrow the amount
+    state.pending_conditional_transfers += amount;
+
+    let mut event_group = EventGroup::builder();
+    event_group
+        .call(condition_contract, Shortname::from_u32(CONDITION_SHORTNAME))
+        .argument(condition_payload)
+        .done();
+    event_group
+        .with_callback(ShortnameCallback::from_u32(TRANSFER_IF_CALLBACK_SHORTNAME))
+        .argument(ctx.sender)
+        .argument(to)
+        .argument(amount)
+        .done();
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Pay an escrowed conditional transfer to its receiver if the condition contract returned `true`,
+/// and refund it to its sender otherwise.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the condition contract call.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `from`: [`Address`], account that made the transfer.
+///   * `to`: [`Address`], account the transfer is for.
+///   * `amount`: [`u128`], amount escrowed.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "conditional-transfers")]
+#[callback(shortname = 0x07)]
+fn transfer_if_callback(
+    ctx: ContractContext,
+    callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+    from: Address,
+    to: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let approved = callback_ctx.success && callback_ctx.results[0].get_return_data::<bool>();
+    state.pending_conditional_transfers -= amount;
+    if approved {
+        state.release(&ctx, to, amount);
+    } else {
+        state.release(&ctx, from, amount);
+    }
+    state.assert_invariants();
+
+    (state, vec![])
+}
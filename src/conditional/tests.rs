@@ -0,0 +1,96 @@
+//! Tests of conditional transfers: the escrow, payment when the condition contract approves, and
+//! refunds when it rejects or fails.
+
+use pbc_contract_common::context::{CallbackContext, ExecutionResult};
+
+use super::{transfer_if, transfer_if_callback};
+use crate::testing::{
+    account, callback_context, context, contract, new_state, token_address, INITIAL_SUPPLY,
+};
+use crate::token::TashiTokenState;
+
+/// The result of a condition contract call which succeeded and returned `approved`.
+fn condition_result(approved: bool) -> CallbackContext {
+    CallbackContext {
+        success: true,
+        results: vec![ExecutionResult {
+            succeeded: true,
+            return_data: vec![u8::from(approved)],
+        }],
+    }
+}
+
+/// A token where the owner sent 700 to account 1 on the condition of contract 1.
+fn escrowed_state() -> TashiTokenState {
+    let (state, events) = transfer_if(
+        context(account(0)),
+        new_state(),
+        contract(1),
+        vec![42],
+        account(1),
+        700,
+    );
+    assert_eq!(events.len(), 1);
+    state
+}
+
+/// Answers the conditional transfer of [`escrowed_state`] with `callback_ctx`.
+fn answer(callback_ctx: CallbackContext) -> TashiTokenState {
+    transfer_if_callback(
+        context(token_address()),
+        callback_ctx,
+        escrowed_state(),
+        account(0),
+        account(1),
+        700,
+    )
+    .0
+}
+
+#[test]
+fn amount_is_escrowed_until_the_condition_answers() {
+    let state = escrowed_state();
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 700);
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.pending_conditional_transfers, 700);
+}
+
+#[test]
+fn approved_transfer_pays_the_receiver() {
+    let state = answer(condition_result(true));
+
+    assert_eq!(state.balance_of(&account(1)), 700);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 700);
+    assert_eq!(state.pending_conditional_transfers, 0);
+}
+
+#[test]
+fn rejected_transfer_is_refunded() {
+    let state = answer(condition_result(false));
+
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY);
+    assert_eq!(state.pending_conditional_transfers, 0);
+}
+
+#[test]
+fn failed_condition_call_is_refunded() {
+    let state = answer(callback_context(false));
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY);
+    assert_eq!(state.pending_conditional_transfers, 0);
+}
+
+#[test]
+#[should_panic(expected = "Transferred amount cannot be zero.")]
+fn zero_amount_is_rejected() {
+    transfer_if(
+        context(account(0)),
+        new_state(),
+        contract(1),
+        vec![],
+        account(1),
+        0,
+    );
+}
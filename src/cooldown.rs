@@ -0,0 +1,91 @@
+//! A minimum delay between transfers from the same account, throttling bots during a launch. The
+//! owner, the contract itself and accounts exempt from the transfer limits are not throttled.
+#![cfg_attr(not(feature = "cooldown"), allow(dead_code))]
+
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "cooldown"))]
+mod tests;
+
+/// Rejects transfers from an account until `transfer_cooldown` has passed since its last transfer.
+pub struct TransferCooldown;
+
+impl TransferHook for TransferCooldown {
+    fn before_transfer(
+        ctx: &ContractContext,
+        state: &mut TashiTokenState,
+        transfer: &mut Transfer,
+    ) {
+        if state.transfer_cooldown == 0 || state.is_limit_exempt(ctx, &transfer.from) {
+            return;
+        }
+        if let Some(last_transfer_at) = state.last_transfer_at.get(&transfer.from) {
+            let next_transfer_at = last_transfer_at + state.transfer_cooldown;
+            assert!(
+                ctx.block_production_time >= next_transfer_at,
+                "Transfers are on cooldown until: {}",
+                next_transfer_at
+            );
+        }
+    }
+
+    fn after_transfer(ctx: &ContractContext, state: &mut TashiTokenState, transfer: &Transfer) {
+        if state.transfer_cooldown == 0 || state.is_limit_exempt(ctx, &transfer.from) {
+            return;
+        }
+        state
+            .last_transfer_at
+            .insert(transfer.from, ctx.block_production_time);
+    }
+}
+
+impl TashiTokenState {
+    /// Sets the minimum delay between transfers from the same account. Setting it to zero removes
+    /// the cooldown and forgets every recorded transfer time.
+    ///
+    /// Panics if `cooldown` is negative.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `cooldown`: [`i64`], the new cooldown, in milliseconds.
+    pub fn set_transfer_cooldown_ms(&mut self, cooldown: i64) {
+        assert!(cooldown >= 0, "Transfer cooldown cannot be negative.");
+        self.transfer_cooldown = cooldown;
+        if cooldown == 0 {
+            self.last_transfer_at = SortedVecMap::new();
+        }
+    }
+}
+
+/// Set the minimum delay between transfers from the same account, zero removes the cooldown. Only
+/// the owner can call this action, governance can change it with a proposal.
+///
+/// Panics if the caller is not the owner or if `cooldown` is negative.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `cooldown`: [`i64`], the new cooldown, in milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "cooldown")]
+#[action(shortname = 0x35)]
+fn set_transfer_cooldown(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    cooldown: i64,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_transfer_cooldown", cooldown);
+    state.set_transfer_cooldown_ms(cooldown);
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,70 @@
+//! Tests of the transfer cooldown: transfers on cooldown, the end of the cooldown, accounts which
+//! are not throttled, and removing the cooldown.
+
+use super::set_transfer_cooldown;
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state};
+use crate::token::TashiTokenState;
+
+/// A token with a cooldown of a minute, where account 1 holds 10_000.
+fn cooldown_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    set_transfer_cooldown(context(account(0)), state, 60_000)
+}
+
+/// Transfers 100 from `from` to account 2 at block production time `time`.
+fn transfer_at(state: &mut TashiTokenState, from: u8, time: i64) {
+    state.execute_transfer(
+        &context_at(account(from), time),
+        Transfer::new(account(from), account(2), 100, None),
+    );
+}
+
+#[test]
+fn transfer_after_the_cooldown_passes() {
+    let mut state = cooldown_state();
+    transfer_at(&mut state, 1, 1_000);
+    transfer_at(&mut state, 1, 61_000);
+
+    assert_eq!(state.balance_of(&account(2)), 200);
+    assert_eq!(state.last_transfer_at.get(&account(1)), Some(&61_000));
+}
+
+#[test]
+#[should_panic(expected = "Transfers are on cooldown until: 61000")]
+fn transfer_on_cooldown_is_rejected() {
+    let mut state = cooldown_state();
+    transfer_at(&mut state, 1, 1_000);
+    transfer_at(&mut state, 1, 60_999);
+}
+
+#[test]
+fn owner_is_not_throttled() {
+    let mut state = cooldown_state();
+    transfer_at(&mut state, 0, 1_000);
+    transfer_at(&mut state, 0, 1_000);
+
+    assert_eq!(state.balance_of(&account(2)), 200);
+    assert!(state.last_transfer_at.get(&account(0)).is_none());
+}
+
+#[test]
+fn removing_the_cooldown_forgets_the_transfer_times() {
+    let mut state = cooldown_state();
+    transfer_at(&mut state, 1, 1_000);
+    let mut state = set_transfer_cooldown(context(account(0)), state, 0);
+    transfer_at(&mut state, 1, 1_000);
+
+    assert!(state.last_transfer_at.is_empty());
+    assert_eq!(state.balance_of(&account(2)), 200);
+}
+
+#[test]
+#[should_panic(expected = "Transfer cooldown cannot be negative.")]
+fn negative_cooldown_is_rejected() {
+    set_transfer_cooldown(context(account(0)), new_state(), -1);
+}
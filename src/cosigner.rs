@@ -0,0 +1,279 @@
+//! Dual control of outgoing payments without a full multisig. An account registers a co-signer and
+//! a threshold, after which its transfers above the threshold must be requested with
+//! `transfer_cosigned` and are only executed once the co-signer confirms them, before they expire.
+//! Nothing is escrowed in between, the confirmation fails if the account can no longer cover the
+//! transfer. Only the co-signer can remove itself, so a compromised account key cannot lift the
+//! requirement.
+#![cfg_attr(not(feature = "cosigner"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "cosigner")]
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "cosigner"))]
+mod tests;
+
+/// The co-signer of an account.
+///
+/// ### Fields:
+///
+///   * `cosigner`: [`Address`], account confirming the large transfers.
+///   * `threshold`: [`u128`], transfers above this amount must be confirmed.
+///   * `confirmation_window`: [`i64`], how long a requested transfer can be confirmed, in
+///     milliseconds.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoSigner {
+    pub cosigner: Address,
+    pub threshold: u128,
+    pub confirmation_window: i64,
+}
+
+/// A transfer waiting for the confirmation of the co-signer of its sender.
+///
+/// ### Fields:
+///
+///   * `from`: [`Address`], account sending the transfer.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`u128`], amount to transfer.
+///   * `expiry`: [`i64`], block production time from which the transfer can no longer be
+///     confirmed, in milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoSignedTransfer {
+    pub from: Address,
+    pub to: Address,
+    pub amount: u128,
+    pub expiry: i64,
+}
+
+/// Rejects transfers above the threshold of the sender that were not confirmed by its co-signer.
+pub struct CoSignerGuard;
+
+impl TransferHook for CoSignerGuard {
+    fn before_transfer(
+        _ctx: &ContractContext,
+        state: &mut TashiTokenState,
+        transfer: &mut Transfer,
+    ) {
+        if let Some(cosigner) = state.cosigners.get(&transfer.from) {
+            assert!(
+                transfer.amount <= cosigner.threshold || transfer.cosigned,
+                "Transfers above: {} must be confirmed by the co-signer, use transfer_cosigned.",
+                cosigner.threshold
+            );
+        }
+    }
+}
+
+/// Register `cosigner` as the co-signer of caller address.
+///
+/// Panics if the caller already has a co-signer, if it would co-sign for itself, or if the
+/// confirmation window is not positive.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `cosigner`: [`CoSigner`], the co-signer and its threshold.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "cosigner")]
+#[action(shortname = 0xe4)]
+fn set_cosigner(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    cosigner: CoSigner,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "set_cosigner");
+    assert!(
+        !state.cosigners.contains_key(&ctx.sender),
+        "Account already has a co-signer, it must remove itself first."
+    );
+    assert!(
+        cosigner.cosigner != ctx.sender,
+        "Account cannot co-sign for itself."
+    );
+    assert!(
+        cosigner.confirmation_window > 0,
+        "Confirmation window must be positive."
+    );
+    state.cosigners.insert(ctx.sender, cosigner);
+    state.assert_invariants();
+
+    state
+}
+
+/// Remove the co-signer of `account`. Only the co-signer itself can call this action.
+///
+/// Panics if the caller is not the co-signer of `account`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account the caller co-signs for.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "cosigner")]
+#[action(shortname = 0xe5)]
+fn remove_cosigner(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "remove_cosigner");
+    assert!(
+        state
+            .cosigners
+            .get(&account)
+            .is_some_and(|cosigner| cosigner.cosigner == ctx.sender),
+        "Only the co-signer can remove itself."
+    );
+    state.cosigners.remove(&account);
+    state.assert_invariants();
+
+    state
+}
+
+/// Request a transfer of `amount` tokens to address `to` from caller address, executed once the
+/// co-signer of the caller confirms it. The request gets the next free id.
+///
+/// Panics if the caller has no co-signer or if `amount` is zero.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`u128`], amount to transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "cosigner")]
+#[action(shortname = 0xe6)]
+fn transfer_cosigned(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    to: Address,
+    amount: u128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "transfer_cosigned");
+    assert!(amount > 0, "Transferred amount cannot be zero.");
+    let window = state
+        .cosigners
+        .get(&ctx.sender)
+        .map(|cosigner| cosigner.confirmation_window)
+        .expect("Account has no co-signer.");
+    state.cosigned_transfers.insert(
+        state.next_cosigned_transfer_id,
+        CoSignedTransfer {
+            from: ctx.sender,
+            to,
+            amount,
+            expiry: ctx.block_production_time + window,
+        },
+    );
+    state.next_cosigned_transfer_id += 1;
+    state.assert_invariants();
+
+    state
+}
+
+/// Confirm the requested transfer `transfer_id`, executing it. Only the co-signer of the sender
+/// can call this action.
+///
+/// Panics if there is no such request, if the caller is not the co-signer of the sender, if the
+/// request has expired, or if the transfer fails.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `transfer_id`: [`u64`], id of the requested transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "cosigner")]
+#[action(shortname = 0xe7)]
+fn confirm_cosigned_transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    transfer_id: u64,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "confirm_cosigned_transfer");
+    let request = state
+        .cosigned_transfers
+        .remove(&transfer_id)
+        .unwrap_or_else(|| panic!("Unknown co-signed transfer: {}", transfer_id));
+    assert!(
+        state
+            .cosigners
+            .get(&request.from)
+            .is_some_and(|cosigner| cosigner.cosigner == ctx.sender),
+        "Only the co-signer of the sender can confirm the transfer."
+    );
+    assert!(
+        ctx.block_production_time < request.expiry,
+        "Co-signed transfer expired at: {}",
+        request.expiry
+    );
+    let supply = state.supply();
+    let mut transfer = Transfer::new(request.from, request.to, request.amount, None);
+    transfer.cosigned = true;
+    state.execute_transfer(&ctx, transfer);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Cancel the requested transfer `transfer_id`. Its sender can cancel it at any time, anyone else
+/// once it has expired.
+///
+/// Panics if there is no such request, or if the caller is not its sender and it has not expired.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `transfer_id`: [`u64`], id of the requested transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "cosigner")]
+#[action(shortname = 0xe8)]
+fn cancel_cosigned_transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    transfer_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "cancel_cosigned_transfer");
+    let request = state
+        .cosigned_transfers
+        .remove(&transfer_id)
+        .unwrap_or_else(|| panic!("Unknown co-signed transfer: {}", transfer_id));
+    assert!(
+        request.from == ctx.sender || ctx.block_production_time >= request.expiry,
+        "Only the sender can cancel the transfer before it expires at: {}",
+        request.expiry
+    );
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,105 @@
+//! Tests of co-signers: large transfers rejected unless confirmed, confirmations by the co-signer
+//! before the request expires, and a co-signer the account cannot remove.
+
+use super::{
+    cancel_cosigned_transfer, confirm_cosigned_transfer, remove_cosigner, set_cosigner,
+    transfer_cosigned, CoSigner,
+};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state};
+use crate::token::TashiTokenState;
+
+/// A token where account 1 holds 10_000 and its transfers above 1_000 must be confirmed by account
+/// 2 within 1_000 milliseconds.
+fn cosigned_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    set_cosigner(
+        context(account(1)),
+        state,
+        CoSigner {
+            cosigner: account(2),
+            threshold: 1_000,
+            confirmation_window: 1_000,
+        },
+    )
+}
+
+/// The state of [`cosigned_state`] where account 1 requested a transfer of 5_000 to account 3 at
+/// time 0, as request 0.
+fn requested_state() -> TashiTokenState {
+    transfer_cosigned(context(account(1)), cosigned_state(), account(3), 5_000)
+}
+
+/// Transfers `amount` from account 1 to account 3.
+fn send(state: &mut TashiTokenState, amount: u128) {
+    state.execute_transfer(
+        &context(account(1)),
+        Transfer::new(account(1), account(3), amount, None),
+    );
+}
+
+#[test]
+fn transfer_up_to_the_threshold_is_executed() {
+    let mut state = cosigned_state();
+    send(&mut state, 1_000);
+
+    assert_eq!(state.balance_of(&account(3)), 1_000);
+}
+
+#[test]
+#[should_panic(
+    expected = "Transfers above: 1000 must be confirmed by the co-signer, use transfer_cosigned."
+)]
+fn unconfirmed_transfer_above_the_threshold_is_rejected() {
+    let mut state = cosigned_state();
+    send(&mut state, 1_001);
+}
+
+#[test]
+fn confirmed_transfer_is_executed() {
+    let state = requested_state();
+    assert_eq!(state.balance_of(&account(3)), 0);
+
+    let (state, _) = confirm_cosigned_transfer(context_at(account(2), 999), state, 0);
+    assert_eq!(state.balance_of(&account(1)), 5_000);
+    assert_eq!(state.balance_of(&account(3)), 5_000);
+    assert!(state.cosigned_transfers.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Only the co-signer of the sender can confirm the transfer.")]
+fn only_the_cosigner_confirms() {
+    confirm_cosigned_transfer(context(account(1)), requested_state(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Co-signed transfer expired at: 1000")]
+fn expired_request_cannot_be_confirmed() {
+    confirm_cosigned_transfer(context_at(account(2), 1_000), requested_state(), 0);
+}
+
+#[test]
+fn anyone_cancels_an_expired_request() {
+    let state = cancel_cosigned_transfer(context_at(account(5), 1_000), requested_state(), 0);
+
+    assert!(state.cosigned_transfers.is_empty());
+    assert_eq!(state.balance_of(&account(1)), 10_000);
+}
+
+#[test]
+#[should_panic(expected = "Only the co-signer can remove itself.")]
+fn account_cannot_remove_its_cosigner() {
+    remove_cosigner(context(account(1)), cosigned_state(), account(1));
+}
+
+#[test]
+fn removed_cosigner_lifts_the_requirement() {
+    let mut state = remove_cosigner(context(account(2)), cosigned_state(), account(1));
+    send(&mut state, 5_000);
+
+    assert_eq!(state.balance_of(&account(3)), 5_000);
+}
@@ -0,0 +1,401 @@
+//! Token sales, where buyers pay with another MPC-20 token (e.g. a BYOC coin) and receive tokens
+//! once the sale succeeds, or a refund if it misses its soft cap.
+#![cfg_attr(not(feature = "crowdsale"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "crowdsale")]
+use pbc_contract_common::context::{CallbackContext, ContractContext};
+#[cfg(feature = "crowdsale")]
+use pbc_contract_common::events::EventGroup;
+#[cfg(feature = "crowdsale")]
+use pbc_contract_common::shortname::ShortnameCallback;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "crowdsale")]
+use crate::mpc20::{call_transfer, call_transfer_from};
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "crowdsale"))]
+mod tests;
+
+/// Shortname of the callback confirming a purchase was paid.
+#[cfg(feature = "crowdsale")]
+const BUY_CALLBACK_SHORTNAME: u32 = 0x01;
+
+/// A token sale configured by the owner. Caps and raised amounts are in units of the payment
+/// token.
+///
+/// ### Fields:
+///
+///   * `payment_token`: [`Address`], MPC-20 token buyers pay with.
+///   * `rate_numerator`: [`u128`], tokens received per `rate_denominator` payment units.
+///   * `rate_denominator`: [`u128`], payment units paid per `rate_numerator` tokens.
+///   * `soft_cap`: [`u128`], amount that must be raised for the sale to succeed.
+///   * `hard_cap`: [`u128`], maximum amount that can be raised.
+///   * `start_time`: [`i64`], block production time the sale opens at, in milliseconds.
+///   * `end_time`: [`i64`], block production time the sale closes at, in milliseconds.
+///   * `raised`: [`u128`], amount paid by buyers so far.
+///   * `pending`: [`u128`], amount of purchases waiting for their payment to be confirmed.
+///   * `closed`: [`bool`], whether the owner has settled the sale.
+///   * `locked`: [`u128`], tokens for sale still held by the contract, not yet claimed or returned.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sale {
+    pub payment_token: Address,
+    pub rate_numerator: u128,
+    pub rate_denominator: u128,
+    pub soft_cap: u128,
+    pub hard_cap: u128,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub raised: u128,
+    pub pending: u128,
+    pub closed: bool,
+    pub locked: u128,
+}
+
+impl Sale {
+    /// Gets the amount of tokens bought by paying `paid` payment units.
+    ///
+    /// Panics if the calculation overflows.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `paid`: [`u128`], amount of the payment token paid.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount of tokens.
+    pub fn tokens_for(&self, paid: u128) -> u128 {
+        paid.checked_mul(self.rate_numerator)
+            .expect("Overflow when calculating sale tokens.")
+            / self.rate_denominator
+    }
+
+    /// Whether the sale raised at least its soft cap.
+    pub fn succeeded(&self) -> bool {
+        self.raised >= self.soft_cap
+    }
+}
+
+impl TashiTokenState {
+    /// Gets the configured sale.
+    ///
+    /// Panics if no sale is configured.
+    ///
+    /// ### Returns:
+    ///
+    /// A reference to the [`Sale`].
+    pub fn sale(&self) -> &Sale {
+        self.sale.as_ref().expect("No sale is configured.")
+    }
+
+    /// Checks that the configured sale has ended.
+    ///
+    /// Panics if no sale is configured or if it has not ended yet.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    fn assert_sale_ended(&self, now: i64) {
+        let sale = self.sale();
+        assert!(now >= sale.end_time, "Sale ends at: {}", sale.end_time);
+    }
+}
+
+/// Configure a token sale. The tokens for the full hard cap are taken from the owner up front.
+/// Only the owner can call this action.
+///
+/// Panics if the caller is not the owner, if a previous sale is still open or has unclaimed
+/// contributions, if the configuration is invalid, or if there is insufficient balance in the
+/// owner account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `payment_token`: [`Address`], MPC-20 token buyers pay with.
+///   * `rate_numerator`: [`u128`], tokens received per `rate_denominator` payment units.
+///   * `rate_denominator`: [`u128`], payment units paid per `rate_numerator` tokens.
+///   * `soft_cap`: [`u128`], amount that must be raised for the sale to succeed.
+///   * `hard_cap`: [`u128`], maximum amount that can be raised.
+///   * `start_time`: [`i64`], block production time the sale opens at, in milliseconds.
+///   * `end_time`: [`i64`], block production time the sale closes at, in milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "crowdsale")]
+#[action(shortname = 0x1d)]
+#[allow(clippy::too_many_arguments)]
+fn configure_sale(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    payment_token: Address,
+    rate_numerator: u128,
+    rate_denominator: u128,
+    soft_cap: u128,
+    hard_cap: u128,
+    start_time: i64,
+    end_time: i64,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(
+        state,
+        ctx,
+        "configure_sale",
+        payment_token,
+        rate_numerator,
+        rate_denominator,
+        soft_cap,
+        hard_cap,
+        start_time,
+        end_time
+    );
+    if let Some(sale) = &state.sale {
+        assert!(
+            sale.closed && state.sale_contributions.is_empty(),
+            "The previous sale is not settled."
+        );
+    }
+    assert!(rate_denominator > 0, "Rate denominator cannot be zero.");
+    assert!(soft_cap <= hard_cap, "Soft cap cannot exceed hard cap.");
+    assert!(end_time > start_time, "Sale must end after it starts.");
+
+    let mut sale = Sale {
+        payment_token,
+        rate_numerator,
+        rate_denominator,
+        soft_cap,
+        hard_cap,
+        start_time,
+        end_time,
+        raised: 0,
+        pending: 0,
+        closed: false,
+        locked: 0,
+    };
+    sale.locked = sale.tokens_for(hard_cap);
+    state.escrow(&ctx, ctx.sender, sale.locked); // lock the tokens for sale
+    state.sale = Some(sale);
+    state.assert_invariants();
+
+    state
+}
+
+/// Buy tokens by paying `amount` of the payment token. The caller must first approve this
+/// contract to spend `amount` on the payment token contract. The tokens can be claimed once the
+/// sale has ended successfully.
+///
+/// Panics if no sale is open or if `amount` would exceed the hard cap.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount of the payment token to pay.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the payment interaction.
+#[cfg(feature = "crowdsale")]
+#[action(shortname = 0x1e)]
+fn buy(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "buy");
+    let sale = state.sale.as_mut().expect("No sale is configured.");
+    let now = ctx.block_production_time;
+    assert!(
+        now >= sale.start_time && now < sale.end_time,
+        "Sale is not open."
+    );
+    assert!(amount > 0, "Purchase amount cannot be zero.");
+    let committed = sale.raised + sale.pending + amount;
+    assert!(
+        committed <= sale.hard_cap,
+        "Purchase exceeds the hard cap by: {}",
+        committed - sale.hard_cap
+    );
+    sale.pending += amount; // reserve the purchase until the payment is confirmed
+
+    let mut event_group = EventGroup::builder();
+    call_transfer_from(
+        &mut event_group,
+        sale.payment_token,
+        ctx.sender,
+        ctx.contract_address,
+        amount,
+    );
+    event_group
+        .with_callback(ShortnameCallback::from_u32(BUY_CALLBACK_SHORTNAME))
+        .argument(ctx.sender)
+        .argument(amount)
+        .done();
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Record a purchase once its payment is confirmed, or release the reservation if the payment
+/// failed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the payment interaction.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `buyer`: [`Address`], account that bought tokens.
+///   * `amount`: [`u128`], amount of the payment token paid.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "crowdsale")]
+#[callback(shortname = 0x01)]
+fn buy_callback(
+    _ctx: ContractContext,
+    callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+    buyer: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let sale = state.sale.as_mut().unwrap();
+    sale.pending -= amount;
+    if callback_ctx.success {
+        sale.raised += amount;
+        let contribution = state.sale_contributions.get(&buyer).copied().unwrap_or(0) + amount;
+        state.sale_contributions.insert(buyer, contribution);
+    }
+    state.assert_invariants();
+
+    (state, vec![])
+}
+
+/// Claim the tokens bought by caller address, once the sale has ended and reached its soft cap.
+///
+/// Panics if the sale has not ended, if it missed its soft cap, or if the caller has nothing to
+/// claim.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "crowdsale")]
+#[action(shortname = 0x1f)]
+fn claim_sale_tokens(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "claim_sale_tokens");
+    state.assert_sale_ended(ctx.block_production_time);
+    assert!(state.sale().succeeded(), "Sale did not reach its soft cap.");
+    let contribution = state
+        .sale_contributions
+        .remove(&ctx.sender)
+        .expect("Nothing to claim.");
+
+    let tokens = state.sale().tokens_for(contribution);
+    state.sale.as_mut().unwrap().locked -= tokens;
+    state.release(&ctx, ctx.sender, tokens);
+    state.assert_invariants();
+
+    state
+}
+
+/// Claim back the payment of caller address, once the sale has ended without reaching its soft
+/// cap.
+///
+/// Panics if the sale has not ended, if it reached its soft cap, or if the caller has nothing to
+/// refund.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the refund interaction.
+#[cfg(feature = "crowdsale")]
+#[action(shortname = 0x20)]
+fn claim_refund(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "claim_refund");
+    state.assert_sale_ended(ctx.block_production_time);
+    assert!(!state.sale().succeeded(), "Sale reached its soft cap.");
+    let contribution = state
+        .sale_contributions
+        .remove(&ctx.sender)
+        .expect("Nothing to refund.");
+
+    let mut event_group = EventGroup::builder();
+    call_transfer(
+        &mut event_group,
+        state.sale().payment_token,
+        ctx.sender,
+        contribution,
+    );
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Settle the sale once it has ended. If it reached its soft cap the proceeds are sent to the
+/// owner and every locked token not claimable by a buyer returned, otherwise all tokens for sale
+/// are returned. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner, if the sale has not ended, or if it is already settled.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the proceeds interaction.
+#[cfg(feature = "crowdsale")]
+#[action(shortname = 0x21)]
+fn close_sale(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "close_sale");
+    state.assert_sale_ended(ctx.block_production_time);
+    let sale = state.sale.as_mut().unwrap();
+    assert!(!sale.closed, "Sale is already settled.");
+    sale.closed = true;
+
+    let mut events = vec![];
+    let unsold = if sale.succeeded() {
+        let mut event_group = EventGroup::builder();
+        call_transfer(
+            &mut event_group,
+            sale.payment_token,
+            ctx.sender,
+            sale.raised,
+        );
+        events.push(event_group.build());
+        let claimable: u128 = state
+            .sale_contributions
+            .iter()
+            .map(|(_, contribution)| sale.tokens_for(*contribution))
+            .sum();
+        sale.locked - claimable
+    } else {
+        sale.locked
+    };
+    sale.locked -= unsold;
+    state.release(&ctx, ctx.sender, unsold); // return unsold tokens
+    state.assert_invariants();
+
+    (state, events)
+}
@@ -0,0 +1,126 @@
+//! Tests of token sales: purchases confirmed by their payment, claims and unsold tokens of a
+//! successful sale, rounding of the locked tokens, and refunds of a sale missing its soft cap.
+
+use super::{buy, buy_callback, claim_refund, claim_sale_tokens, close_sale, configure_sale};
+use crate::testing::{
+    account, callback_context, context_at, contract, new_state, token_address, INITIAL_SUPPLY,
+};
+use crate::token::TashiTokenState;
+
+/// Block production time the sales of the tests end at.
+const END: i64 = 1_000;
+
+/// A token selling `rate_numerator` tokens per `rate_denominator` units of contract 1, with a
+/// soft cap of 100 and a hard cap of 1000 units, open until [`END`].
+fn sale_state(rate_numerator: u128, rate_denominator: u128) -> TashiTokenState {
+    configure_sale(
+        context_at(account(0), 0),
+        new_state(),
+        contract(1),
+        rate_numerator,
+        rate_denominator,
+        100,
+        1_000,
+        0,
+        END,
+    )
+}
+
+/// Buys for `amount` units from `buyer` and confirms the payment with `success`.
+fn purchase(state: TashiTokenState, buyer: u8, amount: u128, success: bool) -> TashiTokenState {
+    let (state, events) = buy(context_at(account(buyer), END / 2), state, amount);
+    assert_eq!(events.len(), 1);
+    buy_callback(
+        context_at(token_address(), END / 2),
+        callback_context(success),
+        state,
+        account(buyer),
+        amount,
+    )
+    .0
+}
+
+#[test]
+fn tokens_for_the_hard_cap_are_locked() {
+    let state = sale_state(2, 1);
+
+    assert_eq!(state.sale().locked, 2_000);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 2_000);
+    state.assert_invariants();
+}
+
+#[test]
+fn successful_sale_pays_buyers_and_returns_unsold_tokens() {
+    let state = purchase(sale_state(2, 1), 1, 300, true);
+    let state = purchase(state, 2, 200, true);
+    assert_eq!(state.sale().raised, 500);
+
+    let (state, events) = close_sale(context_at(account(0), END), state);
+    assert_eq!(events.len(), 1);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 1_000);
+
+    let state = claim_sale_tokens(context_at(account(1), END), state);
+    let state = claim_sale_tokens(context_at(account(2), END), state);
+    assert_eq!(state.balance_of(&account(1)), 600);
+    assert_eq!(state.balance_of(&account(2)), 400);
+    assert_eq!(state.sale().locked, 0);
+    state.assert_invariants();
+}
+
+#[test]
+fn rounding_dust_is_returned_to_the_owner() {
+    // a third of a token per unit locks 333 tokens for the hard cap, and each buyer gets 33
+    let state = purchase(sale_state(1, 3), 1, 100, true);
+    let state = purchase(state, 2, 100, true);
+
+    let (state, _) = close_sale(context_at(account(0), END), state);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 66);
+
+    let state = claim_sale_tokens(context_at(account(1), END), state);
+    let state = claim_sale_tokens(context_at(account(2), END), state);
+    assert_eq!(state.balance_of(&account(1)), 33);
+    assert_eq!(state.balance_of(&account(2)), 33);
+    assert_eq!(state.sale().locked, 0);
+}
+
+#[test]
+fn failed_payment_releases_the_reservation() {
+    let state = purchase(sale_state(2, 1), 1, 300, false);
+
+    assert_eq!(state.sale().pending, 0);
+    assert_eq!(state.sale().raised, 0);
+    assert!(state.sale_contributions.is_empty());
+}
+
+#[test]
+fn sale_missing_its_soft_cap_is_refunded() {
+    let state = purchase(sale_state(2, 1), 1, 50, true);
+
+    let (state, events) = close_sale(context_at(account(0), END), state);
+    assert!(events.is_empty());
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY);
+
+    let (state, events) = claim_refund(context_at(account(1), END), state);
+    assert_eq!(events.len(), 1);
+    assert!(state.sale_contributions.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Sale did not reach its soft cap.")]
+fn tokens_of_a_failed_sale_cannot_be_claimed() {
+    let state = purchase(sale_state(2, 1), 1, 50, true);
+    claim_sale_tokens(context_at(account(1), END), state);
+}
+
+#[test]
+#[should_panic(expected = "Purchase exceeds the hard cap by: 1")]
+fn purchase_beyond_the_hard_cap_is_rejected() {
+    let state = purchase(sale_state(2, 1), 1, 600, true);
+    buy(context_at(account(2), END / 2), state, 401);
+}
+
+#[test]
+#[should_panic(expected = "Sale is not open.")]
+fn purchase_after_the_end_is_rejected() {
+    buy(context_at(account(1), END), sale_state(2, 1), 100);
+}
@@ -0,0 +1,162 @@
+//! Demurrage, balances losing value over time to encourage circulation, e.g. for local currencies.
+//! Built on the interest index of the `interest` feature: the index is lowered at the configured
+//! yearly rate for the time since it last decayed, shrinking every balance at once, and the decayed
+//! value is minted to the community pool so the supply is unchanged. The decay is applied lazily
+//! whenever balances are moved or read: before every transfer, escrow, release out of escrow and
+//! burn, and by the balance and supply getters. Anyone can also apply it with `accrue_demurrage`.
+//! It compounds yearly over
+//! a long gap and stops once the index reaches [`MIN_DEMURRAGE_INDEX`], so the shares minted to the
+//! pool always fit. Escrowed tokens do not decay.
+#![cfg_attr(not(feature = "demurrage"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "demurrage")]
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+use crate::hooks::{Transfer, TransferHook};
+use crate::interest::INDEX_SCALE;
+use crate::token::{mul_div, TashiTokenState, BPS_DENOMINATOR};
+
+#[cfg(all(test, feature = "demurrage", feature = "interest"))]
+mod tests;
+
+/// The length of a year the demurrage rate applies to, 365 days in milliseconds.
+pub const DEMURRAGE_YEAR: i64 = 365 * 24 * 60 * 60 * 1000;
+
+/// The lowest interest index demurrage decays to, a millionth of [`INDEX_SCALE`]. Minting the
+/// decayed value at a lower index would take more shares than the supply can hold.
+pub const MIN_DEMURRAGE_INDEX: u128 = INDEX_SCALE / 1_000_000;
+
+/// The demurrage of the token.
+///
+/// ### Fields:
+///
+///   * `rate_bps`: [`u16`], share of every balance decaying per year, in basis points.
+///   * `pool`: [`Address`], community pool the decayed value is minted to.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DemurrageConfig {
+    pub rate_bps: u16,
+    pub pool: Address,
+}
+
+/// Applies the demurrage due before every transfer, so it moves balances at the current index.
+pub struct DemurrageAccrual;
+
+impl TransferHook for DemurrageAccrual {
+    fn before_transfer(
+        ctx: &ContractContext,
+        state: &mut TashiTokenState,
+        _transfer: &mut Transfer,
+    ) {
+        state.accrue_demurrage_until(ctx.block_production_time);
+    }
+}
+
+impl TashiTokenState {
+    /// Lowers the interest index by the demurrage due since it last decayed and mints the decayed
+    /// value to the community pool. The decay compounds every year of a long gap, and stops at
+    /// [`MIN_DEMURRAGE_INDEX`]. Does nothing without demurrage.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    pub fn accrue_demurrage_until(&mut self, now: i64) {
+        let Some(config) = self.demurrage else {
+            return;
+        };
+        let elapsed = now - self.demurrage_accrued_at;
+        if elapsed <= 0 {
+            return;
+        }
+        self.demurrage_accrued_at = now;
+
+        let supply = self.supply();
+        // decay year by year, so a long gap compounds instead of wiping out the index
+        let mut index = self.interest_index;
+        let mut remaining = elapsed;
+        while remaining > 0 && index > MIN_DEMURRAGE_INDEX {
+            let step = remaining.min(DEMURRAGE_YEAR);
+            let decay = mul_div(
+                index,
+                u128::from(config.rate_bps) * step as u128,
+                BPS_DENOMINATOR * DEMURRAGE_YEAR as u128,
+            );
+            index = index.saturating_sub(decay).max(MIN_DEMURRAGE_INDEX);
+            remaining -= step;
+        }
+        self.set_interest_index(index);
+        let decayed = supply - self.supply();
+        self.mint_balance(config.pool, decayed);
+    }
+}
+
+/// Set the yearly demurrage rate and the community pool, none to stop the decay. The demurrage due
+/// under the previous configuration is applied first. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if the rate exceeds 100%.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `config`: [`Option`]<[`DemurrageConfig`]>, the new demurrage.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "demurrage")]
+#[action(shortname = 0xac)]
+fn set_demurrage(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    config: Option<DemurrageConfig>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_demurrage", config);
+    if let Some(config) = config {
+        assert!(
+            u128::from(config.rate_bps) <= BPS_DENOMINATOR,
+            "Demurrage rate cannot exceed 100%."
+        );
+    }
+    let supply = state.supply();
+    state.accrue_demurrage_until(ctx.block_production_time);
+    state.demurrage = config;
+    state.demurrage_accrued_at = ctx.block_production_time;
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Apply the demurrage due since the balances last decayed. Anyone can call this action.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "demurrage")]
+#[action(shortname = 0xad)]
+fn accrue_demurrage(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "accrue_demurrage");
+    let supply = state.supply();
+    state.accrue_demurrage_until(ctx.block_production_time);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
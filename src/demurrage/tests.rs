@@ -0,0 +1,103 @@
+//! Tests of demurrage: the decay of balances minted to the community pool, long gaps between
+//! accruals that must not wipe out the interest index, and the decay applied lazily by getters and
+//! escrows.
+
+use super::{
+    accrue_demurrage, set_demurrage, DemurrageConfig, DEMURRAGE_YEAR, MIN_DEMURRAGE_INDEX,
+};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+use crate::views::query_balance_of;
+
+/// A token whose balances decay at `rate_bps` a year into the pool of account 7.
+fn demurrage_state(rate_bps: u16) -> TashiTokenState {
+    let config = DemurrageConfig {
+        rate_bps,
+        pool: account(7),
+    };
+    let (state, _) = set_demurrage(context(account(0)), new_state(), Some(config));
+    state
+}
+
+#[test]
+fn decayed_value_is_minted_to_the_pool() {
+    let (state, _) = accrue_demurrage(
+        context_at(account(1), DEMURRAGE_YEAR / 2),
+        demurrage_state(1_000),
+    );
+
+    assert_eq!(state.interest_index, 950_000_000_000_000_000);
+    assert_eq!(state.balance_of(&account(0)), 950_000);
+    assert_eq!(state.balance_of(&account(7)), 49_999);
+}
+
+#[test]
+fn long_gap_compounds_yearly() {
+    let (state, _) = accrue_demurrage(
+        context_at(account(1), 2 * DEMURRAGE_YEAR),
+        demurrage_state(5_000),
+    );
+
+    assert_eq!(state.interest_index, 250_000_000_000_000_000);
+    assert_eq!(state.balance_of(&account(0)), 250_000);
+}
+
+#[test]
+fn long_gap_at_full_rate_stops_at_the_minimum_index() {
+    let (mut state, _) = accrue_demurrage(
+        context_at(account(1), 10 * DEMURRAGE_YEAR),
+        demurrage_state(10_000),
+    );
+
+    assert_eq!(state.interest_index, MIN_DEMURRAGE_INDEX);
+    assert_eq!(state.balance_of(&account(0)), 1);
+    assert_eq!(state.balance_of(&account(7)), INITIAL_SUPPLY - 1);
+    assert_eq!(state.supply(), INITIAL_SUPPLY);
+
+    // transfers keep working once the index no longer decays
+    state.execute_transfer(
+        &context_at(account(7), 20 * DEMURRAGE_YEAR),
+        Transfer::new(account(7), account(1), 500, None),
+    );
+    assert_eq!(state.interest_index, MIN_DEMURRAGE_INDEX);
+    assert_eq!(state.balance_of(&account(1)), 500);
+}
+
+#[test]
+fn balance_query_applies_the_decay_due() {
+    let (state, _) = query_balance_of(
+        context_at(account(1), DEMURRAGE_YEAR / 2),
+        demurrage_state(1_000),
+        account(0),
+    );
+
+    assert_eq!(state.interest_index, 950_000_000_000_000_000);
+    assert_eq!(state.demurrage_accrued_at, DEMURRAGE_YEAR / 2);
+    assert_eq!(state.balance_of(&account(0)), 950_000);
+    assert_eq!(state.balance_of(&account(7)), 49_999);
+}
+
+#[test]
+#[cfg(feature = "htlc")]
+fn escrowed_funds_are_refunded_without_decay() {
+    use pbc_contract_common::Hash;
+
+    use crate::htlc::{lock_htlc, refund_htlc};
+
+    let hashlock = Hash::digest(b"swap secret".to_vec());
+    let state = lock_htlc(
+        context(account(0)),
+        demurrage_state(1_000),
+        account(1),
+        100_000,
+        hashlock.clone(),
+        DEMURRAGE_YEAR / 2,
+    );
+    let state = refund_htlc(context_at(account(0), DEMURRAGE_YEAR / 2), state, hashlock);
+
+    // 900_000 decayed to 855_000, plus the 100_000 escrowed, less a token of rounding
+    assert_eq!(state.balance_of(&account(0)), 954_999);
+    assert_eq!(state.balance_of(&account(7)), 45_000);
+    state.assert_invariants();
+}
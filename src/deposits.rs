@@ -0,0 +1,232 @@
+//! Deposits of other MPC-20 tokens into this contract. A plain MPC-20 transfer to this contract
+//! cannot be attributed to its sender, so tokens are received by pulling them with `transfer_from`
+//! and crediting the depositor in the callback once the transfer is confirmed. Withdrawals are
+//! debited before the outgoing transfer and restored by the callback if it fails.
+#![cfg_attr(not(feature = "deposits"), allow(dead_code))]
+
+use pbc_contract_common::address::Address;
+#[cfg(feature = "deposits")]
+use pbc_contract_common::context::{CallbackContext, ContractContext};
+#[cfg(feature = "deposits")]
+use pbc_contract_common::events::EventGroup;
+#[cfg(feature = "deposits")]
+use pbc_contract_common::shortname::ShortnameCallback;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+
+#[cfg(feature = "deposits")]
+use crate::mpc20::{call_transfer, call_transfer_from};
+use crate::token::{BalanceMap, TashiTokenState};
+
+#[cfg(all(test, feature = "deposits"))]
+mod tests;
+
+/// Shortname of the callback confirming a deposit was received.
+#[cfg(feature = "deposits")]
+const DEPOSIT_CALLBACK_SHORTNAME: u32 = 0x05;
+
+/// Shortname of the callback confirming a withdrawal was sent.
+#[cfg(feature = "deposits")]
+const WITHDRAW_CALLBACK_SHORTNAME: u32 = 0x06;
+
+impl TashiTokenState {
+    /// Gets the amount of `token` that `account` has deposited.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `token`: [`Address`], the MPC-20 token contract.
+    ///   * `account`: [`Address`], the depositor.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount of `token` held for `account`.
+    pub fn deposit_of(&self, token: &Address, account: &Address) -> u128 {
+        self.deposits
+            .get(token)
+            .and_then(|token_deposits| token_deposits.get(account))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Updates the amount of `token` held for `account` to `amount`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `token`: [`Address`], the MPC-20 token contract.
+    ///   * `account`: [`Address`], the depositor.
+    ///   * `amount`: [`u128`], the new deposit.
+    pub fn update_deposit(&mut self, token: Address, account: Address, amount: u128) {
+        if !self.deposits.contains_key(&token) {
+            self.deposits.insert(token, SortedVecMap::new());
+        }
+        let token_deposits = self.deposits.get_mut(&token).unwrap();
+        token_deposits.insert_balance(account, amount);
+        if token_deposits.is_empty() {
+            self.deposits.remove(&token);
+        }
+    }
+}
+
+/// Deposit `amount` of `token` from caller address. The caller must first approve this contract
+/// for `amount` on `token`. The deposit is credited once the transfer is confirmed.
+///
+/// Panics if `amount` is zero or if `token` is this contract.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `token`: [`Address`], the MPC-20 token contract to deposit.
+///   * `amount`: [`u128`], amount to deposit.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "deposits")]
+#[action(shortname = 0x51)]
+fn deposit_token(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    token: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "deposit_token");
+    assert!(amount > 0, "Deposited amount cannot be zero.");
+    assert!(
+        token != ctx.contract_address,
+        "Cannot deposit this token into itself."
+    );
+
+    let mut event_group = EventGroup::builder();
+    call_transfer_from(
+        &mut event_group,
+        token,
+        ctx.sender,
+        ctx.contract_address,
+        amount,
+    );
+    event_group
+        .with_callback(ShortnameCallback::from_u32(DEPOSIT_CALLBACK_SHORTNAME))
+        .argument(token)
+        .argument(ctx.sender)
+        .argument(amount)
+        .done();
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Credit a deposit once its transfer is confirmed. Nothing was credited up front, so a failed
+/// transfer needs no rollback.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the incoming transfer.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `token`: [`Address`], the MPC-20 token contract deposited.
+///   * `depositor`: [`Address`], account that deposited.
+///   * `amount`: [`u128`], amount deposited.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "deposits")]
+#[callback(shortname = 0x05)]
+fn deposit_token_callback(
+    _ctx: ContractContext,
+    callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+    token: Address,
+    depositor: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    if callback_ctx.success {
+        let deposit = state
+            .deposit_of(&token, &depositor)
+            .checked_add(amount)
+            .expect("Overflow when adding to deposit.");
+        state.update_deposit(token, depositor, deposit);
+    }
+    state.assert_invariants();
+
+    (state, vec![])
+}
+
+/// Withdraw `amount` of `token` deposited by caller address. The deposit is debited right away and
+/// restored if the transfer fails.
+///
+/// Panics if the caller has deposited less than `amount` of `token`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `token`: [`Address`], the MPC-20 token contract to withdraw.
+///   * `amount`: [`u128`], amount to withdraw.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "deposits")]
+#[action(shortname = 0x52)]
+fn withdraw_token(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    token: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "withdraw_token");
+    let deposit = state.deposit_of(&token, &ctx.sender);
+    let remaining = deposit.checked_sub(amount).unwrap_or_else(|| {
+        panic!(
+            "Insufficient deposit: {}, minimum required deposit: {}",
+            deposit, amount
+        )
+    });
+    state.update_deposit(token, ctx.sender, remaining); // reserve the withdrawal
+
+    let mut event_group = EventGroup::builder();
+    call_transfer(&mut event_group, token, ctx.sender, amount);
+    event_group
+        .with_callback(ShortnameCallback::from_u32(WITHDRAW_CALLBACK_SHORTNAME))
+        .argument(token)
+        .argument(ctx.sender)
+        .argument(amount)
+        .done();
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Restore a withdrawal to its deposit if the transfer failed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the outgoing transfer.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `token`: [`Address`], the MPC-20 token contract withdrawn.
+///   * `depositor`: [`Address`], account that withdrew.
+///   * `amount`: [`u128`], amount withdrawn.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "deposits")]
+#[callback(shortname = 0x06)]
+fn withdraw_token_callback(
+    _ctx: ContractContext,
+    callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+    token: Address,
+    depositor: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    if !callback_ctx.success {
+        let deposit = state.deposit_of(&token, &depositor) + amount; // the amount was part of the deposit
+        state.update_deposit(token, depositor, deposit);
+    }
+    state.assert_invariants();
+
+    (state, vec![])
+}
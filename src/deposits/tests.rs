@@ -0,0 +1,82 @@
+//! Tests of deposits of other tokens: crediting a deposit once its transfer is confirmed, and
+//! withdrawals debited up front and restored when their transfer fails.
+
+use super::{deposit_token, deposit_token_callback, withdraw_token, withdraw_token_callback};
+use crate::testing::{account, callback_context, context, contract, new_state, token_address};
+use crate::token::TashiTokenState;
+
+/// A token where account 1 deposited 1_000 of token contract 1, confirmed with `success`.
+fn deposited_state(success: bool) -> TashiTokenState {
+    let (state, events) = deposit_token(context(account(1)), new_state(), contract(1), 1_000);
+    assert_eq!(events.len(), 1);
+    deposit_token_callback(
+        context(token_address()),
+        callback_context(success),
+        state,
+        contract(1),
+        account(1),
+        1_000,
+    )
+    .0
+}
+
+/// Withdraws 400 of token contract 1 deposited by account 1, confirmed with `success`.
+fn withdraw(state: TashiTokenState, success: bool) -> TashiTokenState {
+    let (state, _) = withdraw_token(context(account(1)), state, contract(1), 400);
+    assert_eq!(state.deposit_of(&contract(1), &account(1)), 600);
+    withdraw_token_callback(
+        context(token_address()),
+        callback_context(success),
+        state,
+        contract(1),
+        account(1),
+        400,
+    )
+    .0
+}
+
+#[test]
+fn confirmed_deposit_is_credited() {
+    let state = deposited_state(true);
+
+    assert_eq!(state.deposit_of(&contract(1), &account(1)), 1_000);
+}
+
+#[test]
+fn failed_deposit_is_not_credited() {
+    let state = deposited_state(false);
+
+    assert_eq!(state.deposit_of(&contract(1), &account(1)), 0);
+    assert!(state.deposits.is_empty());
+}
+
+#[test]
+fn confirmed_withdrawal_stays_debited() {
+    let state = withdraw(deposited_state(true), true);
+
+    assert_eq!(state.deposit_of(&contract(1), &account(1)), 600);
+}
+
+#[test]
+fn failed_withdrawal_is_restored() {
+    let state = withdraw(deposited_state(true), false);
+
+    assert_eq!(state.deposit_of(&contract(1), &account(1)), 1_000);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient deposit: 1000, minimum required deposit: 1001")]
+fn withdrawal_beyond_the_deposit_is_rejected() {
+    withdraw_token(
+        context(account(1)),
+        deposited_state(true),
+        contract(1),
+        1_001,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Cannot deposit this token into itself.")]
+fn token_cannot_be_deposited_into_itself() {
+    deposit_token(context(account(1)), new_state(), token_address(), 1_000);
+}
@@ -0,0 +1,350 @@
+//! Dormant account sweeps, for deployments that must escheat unclaimed property. Once the owner
+//! sets a dormancy policy, transfers and `confirm_activity` record when each account was last
+//! active. An account inactive for the inactivity period can be flagged by anyone, which notifies
+//! the notifier contract of the policy, and once the grace period after the flag has passed its
+//! balance is swept to the recovery pool of the contract. The former holder can reclaim the swept
+//! tokens at any time. Every flag, sweep, reclaim and cleared flag is recorded in state for good.
+//!
+//! The notifier is invoked at [`DORMANCY_NOTICE_SHORTNAME`] with the arguments
+//! `(account: Address, sweepable_at: i64)`, without a callback.
+#![cfg_attr(not(feature = "dormancy"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "dormancy")]
+use pbc_contract_common::events::EventGroup;
+#[cfg(feature = "dormancy")]
+use pbc_contract_common::shortname::Shortname;
+use read_write_state_derive::ReadWriteState;
+
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "dormancy"))]
+mod tests;
+
+/// Shortname the notifier of the dormancy policy is invoked at.
+pub const DORMANCY_NOTICE_SHORTNAME: u32 = 0x8e;
+
+/// Gas allotted to each dormancy notice.
+pub const DORMANCY_NOTICE_GAS: u64 = 5_000;
+
+/// The dormancy policy of the token.
+///
+/// ### Fields:
+///
+///   * `inactivity_period`: [`i64`], time without activity after which an account can be flagged,
+///     in milliseconds.
+///   * `grace_period`: [`i64`], time between flagging an account and sweeping it, in milliseconds.
+///   * `notifier`: [`Option`]<[`Address`]>, contract notified of every flagged account.
+///   * `active_since`: [`i64`], block production time the policy was first set, counted as the
+///     last activity of accounts without a recorded one, in milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DormancyPolicy {
+    pub inactivity_period: i64,
+    pub grace_period: i64,
+    pub notifier: Option<Address>,
+    pub active_since: i64,
+}
+
+/// The kind of an [`EscheatmentRecord`].
+#[derive(ReadWriteState, CreateTypeSpec, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EscheatmentKind {
+    /// The account was flagged as dormant.
+    #[discriminant(0)]
+    Flagged {},
+    /// The flag was cleared by activity of the account.
+    #[discriminant(1)]
+    Cleared {},
+    /// The balance of the account was swept to the recovery pool.
+    #[discriminant(2)]
+    Swept {},
+    /// The account reclaimed its swept tokens.
+    #[discriminant(3)]
+    Reclaimed {},
+}
+
+/// The audit record of a step of the dormancy process.
+///
+/// ### Fields:
+///
+///   * `kind`: [`EscheatmentKind`], the step.
+///   * `account`: [`Address`], the dormant account.
+///   * `amount`: [`u128`], amount swept or reclaimed, zero for flags.
+///   * `timestamp`: [`i64`], block production time of the step, in milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EscheatmentRecord {
+    pub kind: EscheatmentKind,
+    pub account: Address,
+    pub amount: u128,
+    pub timestamp: i64,
+}
+
+/// Records the sender of every transfer as active, clearing any dormancy flag.
+pub struct DormancyTracker;
+
+impl TransferHook for DormancyTracker {
+    fn after_transfer(ctx: &ContractContext, state: &mut TashiTokenState, transfer: &Transfer) {
+        state.record_activity(ctx, transfer.from);
+    }
+}
+
+impl TashiTokenState {
+    /// Records `account` as active now, clearing its dormancy flag. Does nothing without a
+    /// dormancy policy.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `account`: [`Address`], the active account.
+    pub fn record_activity(&mut self, ctx: &ContractContext, account: Address) {
+        if self.dormancy_policy.is_none() {
+            return;
+        }
+        self.last_active_at
+            .insert(account, ctx.block_production_time);
+        if self.dormant_accounts.remove(&account).is_some() {
+            self.record_escheatment(ctx, EscheatmentKind::Cleared {}, account, 0);
+        }
+    }
+
+    /// Appends a step of the dormancy process to the escheatment records.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `kind`: [`EscheatmentKind`], the step.
+    ///   * `account`: [`Address`], the dormant account.
+    ///   * `amount`: [`u128`], amount swept or reclaimed.
+    fn record_escheatment(
+        &mut self,
+        ctx: &ContractContext,
+        kind: EscheatmentKind,
+        account: Address,
+        amount: u128,
+    ) {
+        self.escheatment_records.push(EscheatmentRecord {
+            kind,
+            account,
+            amount,
+            timestamp: ctx.block_production_time,
+        });
+    }
+}
+
+/// Set the dormancy policy of the token. The first policy starts the inactivity period of every
+/// account without recorded activity. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner, or if either period is not positive.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `inactivity_period`: [`i64`], time without activity after which an account can be flagged,
+///     in milliseconds.
+///   * `grace_period`: [`i64`], time between flagging an account and sweeping it, in milliseconds.
+///   * `notifier`: [`Option`]<[`Address`]>, contract to notify of every flagged account.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "dormancy")]
+#[action(shortname = 0x8f)]
+fn set_dormancy_policy(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    inactivity_period: i64,
+    grace_period: i64,
+    notifier: Option<Address>,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(
+        state,
+        ctx,
+        "set_dormancy_policy",
+        inactivity_period,
+        grace_period,
+        notifier
+    );
+    assert!(
+        inactivity_period > 0 && grace_period > 0,
+        "Inactivity and grace periods must be positive."
+    );
+    let active_since = state
+        .dormancy_policy
+        .as_ref()
+        .map_or(ctx.block_production_time, |policy| policy.active_since);
+    state.dormancy_policy = Some(DormancyPolicy {
+        inactivity_period,
+        grace_period,
+        notifier,
+        active_since,
+    });
+    state.assert_invariants();
+
+    state
+}
+
+/// Record caller address as active without transferring, clearing any dormancy flag.
+///
+/// Panics if there is no dormancy policy.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "dormancy")]
+#[action(shortname = 0x90)]
+fn confirm_activity(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "confirm_activity");
+    assert!(
+        state.dormancy_policy.is_some(),
+        "No dormancy policy is set."
+    );
+    state.record_activity(&ctx, ctx.sender);
+    state.assert_invariants();
+
+    state
+}
+
+/// Flag `account` as dormant, notifying the notifier of the policy. Its balance can be swept once
+/// the grace period has passed, unless the account is active before.
+///
+/// Panics if there is no dormancy policy, if `account` is already flagged or holds no tokens, or
+/// if it was active within the inactivity period.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account to flag.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the dormancy notice.
+#[cfg(feature = "dormancy")]
+#[action(shortname = 0x91)]
+fn flag_dormant(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "flag_dormant");
+    let policy = state
+        .dormancy_policy
+        .as_ref()
+        .expect("No dormancy policy is set.");
+    assert!(
+        !state.dormant_accounts.contains_key(&account),
+        "Account is already flagged."
+    );
+    assert!(state.balance_of(&account) > 0, "Account holds no tokens.");
+    let last_active_at = state
+        .last_active_at
+        .get(&account)
+        .copied()
+        .unwrap_or(policy.active_since);
+    let dormant_from = last_active_at + policy.inactivity_period;
+    assert!(
+        ctx.block_production_time >= dormant_from,
+        "Account is not dormant until: {}",
+        dormant_from
+    );
+
+    let sweepable_at = ctx.block_production_time + policy.grace_period;
+    let mut events = vec![];
+    if let Some(notifier) = policy.notifier {
+        let mut event_group = EventGroup::builder();
+        event_group
+            .call(notifier, Shortname::from_u32(DORMANCY_NOTICE_SHORTNAME))
+            .argument(account)
+            .argument(sweepable_at)
+            .with_cost(DORMANCY_NOTICE_GAS)
+            .done();
+        events.push(event_group.build());
+    }
+    state.dormant_accounts.insert(account, sweepable_at);
+    state.record_escheatment(&ctx, EscheatmentKind::Flagged {}, account, 0);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Sweep the balance of flagged `account` to the recovery pool once its grace period has passed.
+/// Transfer hooks do not apply.
+///
+/// Panics if `account` is not flagged or its grace period has not passed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account to sweep.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "dormancy")]
+#[action(shortname = 0x92)]
+fn sweep_dormant(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "sweep_dormant");
+    let sweepable_at = state
+        .dormant_accounts
+        .remove(&account)
+        .expect("Account is not flagged as dormant.");
+    assert!(
+        ctx.block_production_time >= sweepable_at,
+        "Account can be swept from: {}",
+        sweepable_at
+    );
+    let amount = state.balance_of(&account);
+    state.debit(account, amount); // move the balance to the recovery pool
+    let escheated = state.escheated.get(&account).copied().unwrap_or(0) + amount;
+    state.escheated.insert(account, escheated);
+    state.record_escheatment(&ctx, EscheatmentKind::Swept {}, account, amount);
+    state.assert_invariants();
+
+    state
+}
+
+/// Reclaim the tokens swept from caller address to the recovery pool, recording the caller as
+/// active.
+///
+/// Panics if nothing was swept from the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "dormancy")]
+#[action(shortname = 0x93)]
+fn reclaim_escheated(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "reclaim_escheated");
+    let amount = state
+        .escheated
+        .remove(&ctx.sender)
+        .expect("Nothing was swept from the caller.");
+    state.credit(ctx.sender, amount);
+    state.record_escheatment(&ctx, EscheatmentKind::Reclaimed {}, ctx.sender, amount);
+    state.record_activity(&ctx, ctx.sender);
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,102 @@
+//! Tests of dormant account sweeps: flagging an inactive account, sweeping it after the grace
+//! period, reclaiming the swept tokens, and activity clearing the flag.
+
+use super::{
+    confirm_activity, flag_dormant, reclaim_escheated, set_dormancy_policy, sweep_dormant,
+    EscheatmentKind,
+};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, contract, new_state};
+use crate::token::TashiTokenState;
+
+/// Time without activity after which an account can be flagged.
+const INACTIVITY: i64 = 365 * 86_400_000;
+
+/// Time between flagging an account and sweeping it.
+const GRACE: i64 = 30 * 86_400_000;
+
+/// A token with a dormancy policy notifying contract 1, where account 1 was given 10_000 and was
+/// flagged once inactive.
+fn flagged_state() -> TashiTokenState {
+    let mut state = set_dormancy_policy(
+        context(account(0)),
+        new_state(),
+        INACTIVITY,
+        GRACE,
+        Some(contract(1)),
+    );
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    let (state, events) = flag_dormant(context_at(account(5), INACTIVITY), state, account(1));
+    assert_eq!(events.len(), 1);
+    state
+}
+
+#[test]
+fn swept_tokens_are_reclaimed() {
+    let state = sweep_dormant(
+        context_at(account(5), INACTIVITY + GRACE),
+        flagged_state(),
+        account(1),
+    );
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.escheated.get(&account(1)).copied(), Some(10_000));
+
+    let state = reclaim_escheated(context_at(account(1), 2 * INACTIVITY), state);
+    assert_eq!(state.balance_of(&account(1)), 10_000);
+    assert!(state.escheated.is_empty());
+    let kinds: Vec<EscheatmentKind> = state
+        .escheatment_records
+        .iter()
+        .map(|record| record.kind)
+        .collect();
+    assert!(
+        kinds
+            == vec![
+                EscheatmentKind::Flagged {},
+                EscheatmentKind::Swept {},
+                EscheatmentKind::Reclaimed {}
+            ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Account can be swept from: 34128000000")]
+fn account_is_not_swept_during_the_grace_period() {
+    sweep_dormant(
+        context_at(account(5), INACTIVITY + GRACE - 1),
+        flagged_state(),
+        account(1),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Account is not flagged as dormant.")]
+fn activity_clears_the_flag() {
+    let state = confirm_activity(context_at(account(1), INACTIVITY + 1), flagged_state());
+    assert_eq!(state.escheatment_records.len(), 2);
+    sweep_dormant(
+        context_at(account(5), INACTIVITY + GRACE),
+        state,
+        account(1),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Account is not dormant until: 31536000000")]
+fn active_account_cannot_be_flagged() {
+    let mut state = set_dormancy_policy(context(account(0)), new_state(), INACTIVITY, GRACE, None);
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    flag_dormant(context_at(account(5), INACTIVITY - 1), state, account(1));
+}
+
+#[test]
+#[should_panic(expected = "Nothing was swept from the caller.")]
+fn nothing_to_reclaim_is_rejected() {
+    reclaim_escheated(context(account(1)), flagged_state());
+}
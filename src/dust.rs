@@ -0,0 +1,175 @@
+//! Sweeping of dust balances. Once the owner sets a dust threshold, balances below it can be moved
+//! to the treasury, the balance of the contract itself, or burned, removing the account from
+//! `balances`. Holders sweep their own dust, keepers sweep the dust of any account. The owner, the
+//! contract and frozen accounts are never swept.
+#![cfg_attr(not(feature = "dust-sweep"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "dust-sweep")]
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "dust-sweep")]
+use crate::admin::Role;
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "dust-sweep"))]
+mod tests;
+
+/// Where swept dust goes.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DustDestination {
+    /// Credited to the treasury, the balance of the contract itself.
+    #[discriminant(0)]
+    Treasury {},
+    /// Burned, reducing the total supply.
+    #[discriminant(1)]
+    Burn {},
+}
+
+impl TashiTokenState {
+    /// Whether the balance of `account` is dust that can be swept.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `account`: [`Address`], the account to check.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`bool`], true if `account` holds a non-zero balance below the dust threshold and is not
+    /// the owner, the contract or frozen.
+    pub fn is_dust(&self, ctx: &ContractContext, account: &Address) -> bool {
+        let balance = self.balance_of(account);
+        balance > 0
+            && balance < self.dust_threshold
+            && *account != self.owner
+            && *account != ctx.contract_address
+            && !self.frozen_accounts.contains(account)
+    }
+
+    /// Moves the whole balance of `account` to the dust destination.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `account`: [`Address`], the account to sweep.
+    pub fn sweep_dust_of(&mut self, ctx: &ContractContext, account: Address) {
+        let balance = self.balance_of(&account);
+        match self.dust_destination {
+            DustDestination::Treasury {} => {
+                self.debit(account, balance);
+                self.credit(ctx.contract_address, balance);
+            }
+            DustDestination::Burn {} => {
+                self.burn_balance(account, balance, ctx.block_production_time)
+            }
+        }
+    }
+}
+
+/// Configure the dust sweep. Balances below `threshold` can be swept to `destination`, a
+/// threshold of zero disables sweeping. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `threshold`: [`u128`], balances below this amount are dust.
+///   * `destination`: [`DustDestination`], where swept dust goes.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "dust-sweep")]
+#[action(shortname = 0x59)]
+fn configure_dust_sweep(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    threshold: u128,
+    destination: DustDestination,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "configure_dust_sweep", threshold, destination);
+    state.dust_threshold = threshold;
+    state.dust_destination = destination;
+    state.assert_invariants();
+
+    state
+}
+
+/// Sweep the dust balance of caller address to the dust destination.
+///
+/// Panics if the balance of the caller is not dust.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "dust-sweep")]
+#[action(shortname = 0x5a)]
+fn sweep_own_dust(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "sweep_own_dust");
+    assert!(
+        state.is_dust(&ctx, &ctx.sender),
+        "Balance: {}, is not below the dust threshold: {}",
+        state.balance_of(&ctx.sender),
+        state.dust_threshold
+    );
+    let supply = state.supply();
+    state.sweep_dust_of(&ctx, ctx.sender);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Sweep the dust balances of `accounts` to the dust destination, skipping accounts without dust.
+/// Only accounts with the [`Role::Keeper`] role can call this action.
+///
+/// Panics if the caller does not have the role.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `accounts`: [`Vec`]<[`Address`]>, accounts to sweep.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "dust-sweep")]
+#[action(shortname = 0x5b)]
+fn sweep_dust(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    accounts: Vec<Address>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "sweep_dust");
+    state.assert_role(Role::Keeper {}, &ctx.sender);
+    let supply = state.supply();
+    for account in accounts {
+        if state.is_dust(&ctx, &account) {
+            state.sweep_dust_of(&ctx, account);
+        }
+    }
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
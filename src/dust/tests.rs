@@ -0,0 +1,79 @@
+//! Tests of the dust sweep: sweeping to the treasury or burning, the accounts keepers skip, and
+//! balances which are not dust.
+
+use super::{configure_dust_sweep, sweep_dust, sweep_own_dust, DustDestination};
+use crate::admin::{grant_role, Role};
+use crate::hooks::Transfer;
+use crate::shards::BalanceEntries;
+use crate::testing::{account, admin_context, context, new_state, token_address, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token sweeping balances below 100 to `destination`, where accounts 1 to 3 hold 50, 99 and
+/// 100, and account 9 is a keeper.
+fn dust_state(destination: DustDestination) -> TashiTokenState {
+    let mut state = grant_role(admin_context(), new_state(), Role::Keeper {}, account(9));
+    for (index, amount) in [(1, 50), (2, 99), (3, 100)] {
+        state.execute_transfer(
+            &context(account(0)),
+            Transfer::new(account(0), account(index), amount, None),
+        );
+    }
+    configure_dust_sweep(context(account(0)), state, 100, destination)
+}
+
+#[test]
+fn own_dust_is_swept_to_the_treasury() {
+    let (state, _) = sweep_own_dust(
+        context(account(1)),
+        dust_state(DustDestination::Treasury {}),
+    );
+
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert!(state.balances.entry(&account(1)).is_none());
+    assert_eq!(state.balance_of(&token_address()), 50);
+    assert_eq!(state.supply(), INITIAL_SUPPLY);
+}
+
+#[test]
+fn keeper_burns_the_dust_and_skips_other_accounts() {
+    let mut state = dust_state(DustDestination::Burn {});
+    state.frozen_accounts.push(account(2));
+    let (state, _) = sweep_dust(
+        context(account(9)),
+        state,
+        vec![account(0), account(1), account(2), account(3), account(4)],
+    );
+
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.balance_of(&account(2)), 99);
+    assert_eq!(state.balance_of(&account(3)), 100);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 249);
+    assert_eq!(state.supply(), INITIAL_SUPPLY - 50);
+}
+
+#[test]
+#[should_panic(expected = "Balance: 100, is not below the dust threshold: 100")]
+fn balance_at_the_threshold_is_not_dust() {
+    sweep_own_dust(context(account(3)), dust_state(DustDestination::Burn {}));
+}
+
+#[test]
+#[should_panic(expected = "Balance: 50, is not below the dust threshold: 0")]
+fn nothing_is_dust_without_a_threshold() {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 50, None),
+    );
+    sweep_own_dust(context(account(1)), state);
+}
+
+#[test]
+#[should_panic(expected = "Caller is missing role: Keeper")]
+fn only_keepers_sweep_other_accounts() {
+    sweep_dust(
+        context(account(1)),
+        dust_state(DustDestination::Burn {}),
+        vec![account(2)],
+    );
+}
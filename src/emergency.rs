@@ -0,0 +1,141 @@
+//! Emergency pauses, a circuit breaker for fast incident response. Accounts with the
+//! [`Role::Guardian`] role pause every transfer instantly, but the pause lifts itself after the
+//! emergency pause duration unless the owner or governance extends it. A compromised guardian key
+//! can thus only halt the token for a bounded time. The pause is enforced by the pause guard of the
+//! `pausable` feature, which this feature enables.
+#![cfg_attr(not(feature = "emergency-pause"), allow(dead_code))]
+
+#[cfg(feature = "emergency-pause")]
+use pbc_contract_common::context::ContractContext;
+
+#[cfg(feature = "emergency-pause")]
+use crate::admin::Role;
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "emergency-pause"))]
+mod tests;
+
+/// How long an emergency pause lasts unless extended, one day in milliseconds.
+pub const DEFAULT_EMERGENCY_PAUSE_DURATION: i64 = 24 * 60 * 60 * 1000;
+
+impl TashiTokenState {
+    /// Whether an emergency pause is in effect at time `now`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`bool`], true if transfers are paused in an emergency.
+    pub fn is_emergency_paused(&self, now: i64) -> bool {
+        now < self.emergency_paused_until
+    }
+
+    /// Moves the end of the emergency pause to `until`.
+    ///
+    /// Panics if `until` is not later than the current end of the emergency pause.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `until`: [`i64`], block production time the emergency pause ends at, in milliseconds.
+    pub fn extend_emergency_pause_until(&mut self, until: i64) {
+        assert!(
+            until > self.emergency_paused_until,
+            "Emergency pause already lasts until: {}",
+            self.emergency_paused_until
+        );
+        self.emergency_paused_until = until;
+    }
+}
+
+/// Pause all transfers in an emergency for the emergency pause duration. Only accounts with the
+/// [`Role::Guardian`] role can call this action.
+///
+/// Panics if the caller does not have the role or if an emergency pause is already in effect.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "emergency-pause")]
+#[action(shortname = 0x99)]
+fn emergency_pause(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    state.assert_role(Role::Guardian {}, &ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "emergency_pause");
+    assert!(
+        !state.is_emergency_paused(ctx.block_production_time),
+        "Transfers are already paused in an emergency."
+    );
+    state.emergency_paused_until = ctx.block_production_time + state.emergency_pause_duration;
+    state.assert_invariants();
+
+    state
+}
+
+/// Extend the emergency pause in effect until `until`. Only the owner can call this action,
+/// governance can extend it with a proposal.
+///
+/// Panics if the caller is not the owner, if no emergency pause is in effect, or if `until` is not
+/// later than its current end.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `until`: [`i64`], block production time the emergency pause ends at, in milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "emergency-pause")]
+#[action(shortname = 0x9a)]
+fn extend_emergency_pause(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    until: i64,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "extend_emergency_pause", until);
+    assert!(
+        state.is_emergency_paused(ctx.block_production_time),
+        "No emergency pause is in effect."
+    );
+    state.extend_emergency_pause_until(until);
+    state.assert_invariants();
+
+    state
+}
+
+/// Set how long an emergency pause lasts unless extended. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if `duration` is not positive.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `duration`: [`i64`], the new duration, in milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "emergency-pause")]
+#[action(shortname = 0x9b)]
+fn set_emergency_pause_duration(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    duration: i64,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_emergency_pause_duration", duration);
+    assert!(duration > 0, "Emergency pause duration must be positive.");
+    state.emergency_pause_duration = duration;
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,58 @@
+//! Tests of emergency pauses: transfers rejected while a guardian pause is in effect, the pause
+//! lifting itself after its duration, and extensions by the owner.
+
+use super::{emergency_pause, extend_emergency_pause, DEFAULT_EMERGENCY_PAUSE_DURATION};
+use crate::admin::{grant_role, Role};
+use crate::hooks::Transfer;
+use crate::testing::{account, admin_context, context, context_at, new_state};
+use crate::token::TashiTokenState;
+
+/// A token paused in an emergency at time 1_000 by guardian account 9.
+fn paused_state() -> TashiTokenState {
+    let state = grant_role(admin_context(), new_state(), Role::Guardian {}, account(9));
+    emergency_pause(context_at(account(9), 1_000), state)
+}
+
+/// Transfers 100 from the owner to account 1 at block production time `time`.
+fn send(state: &mut TashiTokenState, time: i64) {
+    state.execute_transfer(
+        &context_at(account(0), time),
+        Transfer::new(account(0), account(1), 100, None),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Transfers are paused in an emergency until: 86401000")]
+fn transfers_are_rejected_during_the_pause() {
+    send(
+        &mut paused_state(),
+        1_000 + DEFAULT_EMERGENCY_PAUSE_DURATION - 1,
+    );
+}
+
+#[test]
+fn pause_lifts_itself_after_its_duration() {
+    let mut state = paused_state();
+    send(&mut state, 1_000 + DEFAULT_EMERGENCY_PAUSE_DURATION);
+
+    assert_eq!(state.balance_of(&account(1)), 100);
+}
+
+#[test]
+#[should_panic(expected = "Transfers are paused in an emergency until: 200000000")]
+fn extended_pause_keeps_rejecting_transfers() {
+    let mut state = extend_emergency_pause(context(account(0)), paused_state(), 200_000_000);
+    send(&mut state, 1_000 + DEFAULT_EMERGENCY_PAUSE_DURATION);
+}
+
+#[test]
+#[should_panic(expected = "Transfers are already paused in an emergency.")]
+fn guardian_cannot_renew_a_pause_in_effect() {
+    emergency_pause(context_at(account(9), 2_000), paused_state());
+}
+
+#[test]
+#[should_panic(expected = "Caller is missing role: Guardian")]
+fn only_guardians_pause() {
+    emergency_pause(context(account(1)), new_state());
+}
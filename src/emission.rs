@@ -0,0 +1,179 @@
+//! Scheduled emission, minting a fixed amount of new tokens to a destination every epoch until an
+//! end time. Anyone can tick the schedule to mint what has accrued.
+#![cfg_attr(not(feature = "emission"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "emission")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "emission")]
+use pbc_contract_common::events::EventGroup;
+use read_write_state_derive::ReadWriteState;
+
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "emission"))]
+mod tests;
+
+/// A schedule minting `rate_per_epoch` tokens to `destination` for every full epoch that passes.
+///
+/// ### Fields:
+///
+///   * `destination`: [`Address`], account receiving the emission, e.g. a staking contract.
+///   * `rate_per_epoch`: [`u128`], amount minted per epoch.
+///   * `epoch_length`: [`i64`], length of an epoch, in milliseconds.
+///   * `end_time`: [`i64`], block production time the emission ends at, in milliseconds.
+///   * `last_tick`: [`i64`], block production time up to which the emission has been minted, in
+///     milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EmissionSchedule {
+    pub destination: Address,
+    pub rate_per_epoch: u128,
+    pub epoch_length: i64,
+    pub end_time: i64,
+    pub last_tick: i64,
+}
+
+impl EmissionSchedule {
+    /// Gets the number of full epochs that passed since the last tick at time `now`, never
+    /// counting past the end time.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] number of epochs.
+    pub fn elapsed_epochs(&self, now: i64) -> u128 {
+        let until = now.min(self.end_time);
+        if until <= self.last_tick {
+            0
+        } else {
+            ((until - self.last_tick) / self.epoch_length) as u128
+        }
+    }
+}
+
+impl TashiTokenState {
+    /// Mints the emission accrued up to time `now` to its destination.
+    ///
+    /// Panics if minting causes an overflow.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u128`] amount minted.
+    pub fn tick_emission_schedule(&mut self, now: i64) -> u128 {
+        let Some(schedule) = self.emission.as_mut() else {
+            return 0;
+        };
+        let epochs = schedule.elapsed_epochs(now);
+        if epochs == 0 {
+            return 0;
+        }
+        schedule.last_tick += epochs as i64 * schedule.epoch_length; // keep the partial epoch
+        let destination = schedule.destination;
+        let amount = schedule
+            .rate_per_epoch
+            .checked_mul(epochs)
+            .expect("Overflow when computing the emission.");
+        self.mint_balance(destination, amount);
+
+        amount
+    }
+}
+
+/// Replace the emission schedule, starting its first epoch now. Emission accrued under the current
+/// schedule is minted first. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner, if `epoch_length` is not positive, or if `end_time` has
+/// already passed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `destination`: [`Address`], account receiving the emission.
+///   * `rate_per_epoch`: [`u128`], amount minted per epoch.
+///   * `epoch_length`: [`i64`], length of an epoch, in milliseconds.
+///   * `end_time`: [`i64`], block production time the emission ends at, in milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "emission")]
+#[action(shortname = 0x38)]
+fn configure_emission(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    destination: Address,
+    rate_per_epoch: u128,
+    epoch_length: i64,
+    end_time: i64,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(
+        state,
+        ctx,
+        "configure_emission",
+        destination,
+        rate_per_epoch,
+        epoch_length,
+        end_time
+    );
+    assert!(epoch_length > 0, "Epoch length must be positive.");
+    assert!(
+        end_time > ctx.block_production_time,
+        "Emission end time has already passed."
+    );
+    let supply = state.supply();
+    state.tick_emission_schedule(ctx.block_production_time);
+
+    state.emission = Some(EmissionSchedule {
+        destination,
+        rate_per_epoch,
+        epoch_length,
+        end_time,
+        last_tick: ctx.block_production_time,
+    });
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Mint the emission accrued since the last tick to its destination. Anyone can call this action.
+///
+/// Panics if there is no emission schedule or if nothing has accrued.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "emission")]
+#[action(shortname = 0x39)]
+fn tick_emission(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "tick_emission");
+    let supply = state.supply();
+    assert!(state.emission.is_some(), "No emission is scheduled.");
+    let minted = state.tick_emission_schedule(ctx.block_production_time);
+    assert!(minted > 0, "No emission has accrued since the last tick.");
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
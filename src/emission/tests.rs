@@ -0,0 +1,85 @@
+//! Tests of scheduled emission: minting full epochs only, stopping at the end time, and minting
+//! what accrued before the schedule is replaced.
+
+use super::{configure_emission, tick_emission};
+use crate::testing::{account, context, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// One epoch of emission, in milliseconds.
+const EPOCH: i64 = 86_400_000;
+
+/// A token emitting 1_000 a day to account 1 for ten days.
+fn emission_state() -> TashiTokenState {
+    let (state, _) = configure_emission(
+        context(account(0)),
+        new_state(),
+        account(1),
+        1_000,
+        EPOCH,
+        10 * EPOCH,
+    );
+    state
+}
+
+#[test]
+fn tick_mints_the_full_epochs_and_keeps_the_partial_one() {
+    let (state, _) = tick_emission(
+        context_at(account(2), 2 * EPOCH + EPOCH / 2),
+        emission_state(),
+    );
+    assert_eq!(state.balance_of(&account(1)), 2_000);
+    assert_eq!(state.supply(), INITIAL_SUPPLY + 2_000);
+
+    let (state, _) = tick_emission(context_at(account(2), 3 * EPOCH), state);
+    assert_eq!(state.balance_of(&account(1)), 3_000);
+    assert_eq!(state.emission.as_ref().unwrap().last_tick, 3 * EPOCH);
+}
+
+#[test]
+fn emission_stops_at_the_end_time() {
+    let (state, _) = tick_emission(context_at(account(2), 25 * EPOCH), emission_state());
+
+    assert_eq!(state.balance_of(&account(1)), 10_000);
+    assert_eq!(state.supply(), INITIAL_SUPPLY + 10_000);
+}
+
+#[test]
+#[should_panic(expected = "No emission has accrued since the last tick.")]
+fn tick_within_an_epoch_is_rejected() {
+    tick_emission(context_at(account(2), EPOCH - 1), emission_state());
+}
+
+#[test]
+fn replacing_the_schedule_mints_what_accrued() {
+    let (state, _) = configure_emission(
+        context_at(account(0), 4 * EPOCH),
+        emission_state(),
+        account(3),
+        500,
+        EPOCH,
+        20 * EPOCH,
+    );
+    let (state, _) = tick_emission(context_at(account(2), 6 * EPOCH), state);
+
+    assert_eq!(state.balance_of(&account(1)), 4_000);
+    assert_eq!(state.balance_of(&account(3)), 1_000);
+}
+
+#[test]
+#[should_panic(expected = "No emission is scheduled.")]
+fn tick_without_a_schedule_is_rejected() {
+    tick_emission(context(account(2)), new_state());
+}
+
+#[test]
+#[should_panic(expected = "Epoch length must be positive.")]
+fn epoch_length_must_be_positive() {
+    configure_emission(
+        context(account(0)),
+        new_state(),
+        account(1),
+        1_000,
+        0,
+        EPOCH,
+    );
+}
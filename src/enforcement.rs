@@ -0,0 +1,244 @@
+//! Regulatory enforcement for regulated assets. Enforcers freeze accounts, and move the funds of
+//! frozen accounts to recovery addresses with a forced transfer that is recorded in state for good.
+//! Compliance officers freeze part of a balance instead, the account can only transfer what exceeds
+//! the frozen amount. A frozen account can neither move its balance into an escrow, such as a
+//! stream or an HTLC, nor redeem or unwrap it, and neither can an account move the frozen part of
+//! its balance, see [`TashiTokenState::assert_can_spend`].
+#![cfg_attr(not(feature = "enforcement"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "enforcement")]
+use crate::admin::Role;
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "enforcement"))]
+mod tests;
+
+/// The audit record of a forced transfer.
+///
+/// ### Fields:
+///
+///   * `case_id`: [`String`], legal case the transfer was ordered under.
+///   * `enforcer`: [`Address`], enforcer that executed the transfer.
+///   * `from`: [`Address`], frozen account the funds were taken from.
+///   * `to`: [`Address`], recovery address the funds were moved to.
+///   * `amount`: [`u128`], amount moved.
+///   * `timestamp`: [`i64`], block production time of the transfer, in milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnforcementRecord {
+    pub case_id: String,
+    pub enforcer: Address,
+    pub from: Address,
+    pub to: Address,
+    pub amount: u128,
+    pub timestamp: i64,
+}
+
+/// Rejects every transfer sent, received or spent by a frozen account, and every transfer leaving
+/// its sender with less than its frozen amount.
+pub struct FreezeGuard;
+
+impl TransferHook for FreezeGuard {
+    fn before_transfer(
+        _ctx: &ContractContext,
+        state: &mut TashiTokenState,
+        transfer: &mut Transfer,
+    ) {
+        let involved = [Some(transfer.from), Some(transfer.to), transfer.spender];
+        for account in involved.iter().flatten() {
+            state.assert_not_frozen(account);
+        }
+    }
+
+    fn after_transfer(_ctx: &ContractContext, state: &mut TashiTokenState, transfer: &Transfer) {
+        state.assert_frozen_amount_kept(&transfer.from, 0);
+    }
+}
+
+impl TashiTokenState {
+    /// Checks that `account` is not frozen.
+    ///
+    /// Panics if `account` is frozen.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], the account to check.
+    pub fn assert_not_frozen(&self, account: &Address) {
+        assert!(
+            !self.frozen_accounts.contains(account),
+            "Account is frozen."
+        );
+    }
+
+    /// Gets the amount of the balance of `account` frozen by a compliance officer.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account to query the frozen amount of.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u128`] frozen amount, zero if none.
+    pub fn frozen_amount(&self, account: &Address) -> u128 {
+        self.frozen_amounts.get(account).copied().unwrap_or(0)
+    }
+
+    /// Checks that `account` keeps at least its frozen amount once `amount` is taken from its
+    /// balance.
+    ///
+    /// Panics if less than the frozen amount would be left.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], the account to check.
+    ///   * `amount`: [`u128`], amount still to be taken from the balance.
+    pub fn assert_frozen_amount_kept(&self, account: &Address, amount: u128) {
+        let frozen = self.frozen_amount(account);
+        assert!(
+            self.balance_of(account).saturating_sub(amount) >= frozen,
+            "Transfer exceeds transferable balance, frozen amount: {}",
+            frozen
+        );
+    }
+}
+
+/// Freeze or unfreeze `account`. Only accounts with the [`Role::Enforcer`] role can call this
+/// action.
+///
+/// Panics if the caller does not have the role or if `frozen` does not change whether `account` is
+/// frozen.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account to freeze or unfreeze.
+///   * `frozen`: [`bool`], true to freeze `account`, false to unfreeze it.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "enforcement")]
+#[action(shortname = 0x46)]
+fn freeze_account(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+    frozen: bool,
+) -> TashiTokenState {
+    state.assert_role(Role::Enforcer {}, &ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "freeze_account", account, frozen);
+    assert!(
+        state.frozen_accounts.contains(&account) != frozen,
+        "Account is already {}.",
+        if frozen { "frozen" } else { "unfrozen" }
+    );
+    if frozen {
+        state.frozen_accounts.push(account);
+    } else {
+        state
+            .frozen_accounts
+            .retain(|frozen_account| *frozen_account != account);
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Move `amount` tokens from frozen account `from` to recovery address `to`, recording the
+/// transfer under `case_id`. Transfer hooks do not apply. Only accounts with the
+/// [`Role::Enforcer`] role can call this action.
+///
+/// Panics if the caller does not have the role, if `from` is not frozen, if `to` is frozen, or if
+/// there is insufficient balance in `from` account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `case_id`: [`String`], legal case the transfer is ordered under.
+///   * `from`: [`Address`], frozen account to take the funds from.
+///   * `to`: [`Address`], recovery address to move the funds to.
+///   * `amount`: [`u128`], amount to move.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "enforcement")]
+#[action(shortname = 0x47)]
+fn force_transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    case_id: String,
+    from: Address,
+    to: Address,
+    amount: u128,
+) -> TashiTokenState {
+    state.assert_role(Role::Enforcer {}, &ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "force_transfer", case_id, from, to, amount);
+    assert!(
+        state.frozen_accounts.contains(&from),
+        "Only frozen accounts can be force transferred from."
+    );
+    assert!(
+        !state.frozen_accounts.contains(&to),
+        "Recovery address is frozen."
+    );
+    state.debit(from, amount);
+    state.credit(to, amount);
+
+    state.enforcement_records.push(EnforcementRecord {
+        case_id,
+        enforcer: ctx.sender,
+        from,
+        to,
+        amount,
+        timestamp: ctx.block_production_time,
+    });
+    state.assert_invariants();
+
+    state
+}
+
+/// Freeze `amount` of the balance of `account`, replacing its frozen amount, zero unfreezes it.
+/// The account can only transfer, escrow or redeem what exceeds the frozen amount. The amount may exceed the balance,
+/// freezing incoming funds up to it. Only accounts with the [`Role::Compliance`] role can call this
+/// action.
+///
+/// Panics if the caller does not have the role.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account to freeze part of the balance of.
+///   * `amount`: [`u128`], amount to freeze.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "enforcement")]
+#[action(shortname = 0xc5)]
+fn freeze_amount(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+    amount: u128,
+) -> TashiTokenState {
+    state.assert_role(Role::Compliance {}, &ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "freeze_amount", account, amount);
+    if amount == 0 {
+        state.frozen_amounts.remove(&account);
+    } else {
+        state.frozen_amounts.insert(account, amount);
+    }
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,101 @@
+//! Tests of account freezes, on transfers and on spends that skip the transfer hooks, such as
+//! escrows.
+
+use super::{force_transfer, freeze_account, freeze_amount};
+use crate::admin::{grant_role, Role};
+use crate::hooks::Transfer;
+use crate::testing::{account, admin_context, context, new_state};
+use crate::token::TashiTokenState;
+
+/// Balance of account 1 in the tests.
+const BALANCE: u128 = 1_000;
+
+/// A token where account 1 holds [`BALANCE`], account 9 is an enforcer and a compliance officer.
+fn funded_state() -> TashiTokenState {
+    let state = grant_role(admin_context(), new_state(), Role::Enforcer {}, account(9));
+    let mut state = grant_role(admin_context(), state, Role::Compliance {}, account(9));
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), BALANCE, None),
+    );
+    state
+}
+
+/// The token of [`funded_state`] with account 1 frozen.
+fn frozen_state() -> TashiTokenState {
+    freeze_account(context(account(9)), funded_state(), account(1), true)
+}
+
+/// The token of [`funded_state`] with half the balance of account 1 frozen.
+fn partially_frozen_state() -> TashiTokenState {
+    freeze_amount(context(account(9)), funded_state(), account(1), BALANCE / 2)
+}
+
+#[test]
+#[should_panic(expected = "Account is frozen.")]
+fn frozen_account_cannot_transfer() {
+    let mut state = frozen_state();
+    state.execute_transfer(
+        &context(account(1)),
+        Transfer::new(account(1), account(2), 1, None),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Account is frozen.")]
+fn frozen_account_cannot_escrow() {
+    let mut state = frozen_state();
+    state.escrow(&context(account(1)), account(1), 1);
+}
+
+#[test]
+#[should_panic(expected = "Account is frozen.")]
+fn frozen_account_cannot_spend() {
+    frozen_state().assert_can_spend(&context(account(1)), &account(1), 1);
+}
+
+#[test]
+fn unfrozen_account_can_escrow() {
+    let mut state = freeze_account(context(account(9)), frozen_state(), account(1), false);
+    state.escrow(&context(account(1)), account(1), BALANCE);
+    assert_eq!(state.balance_of(&account(1)), 0);
+}
+
+#[test]
+fn frozen_funds_can_be_force_transferred() {
+    let state = force_transfer(
+        context(account(9)),
+        frozen_state(),
+        "case".to_string(),
+        account(1),
+        account(2),
+        BALANCE,
+    );
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.balance_of(&account(2)), BALANCE);
+    assert_eq!(state.enforcement_records.len(), 1);
+}
+
+#[test]
+fn balance_above_frozen_amount_can_be_escrowed() {
+    let mut state = partially_frozen_state();
+    state.escrow(&context(account(1)), account(1), BALANCE / 2);
+    assert_eq!(state.balance_of(&account(1)), BALANCE / 2);
+}
+
+#[test]
+#[should_panic(expected = "Transfer exceeds transferable balance, frozen amount: 500")]
+fn frozen_amount_cannot_be_escrowed() {
+    let mut state = partially_frozen_state();
+    state.escrow(&context(account(1)), account(1), BALANCE / 2 + 1);
+}
+
+#[test]
+#[should_panic(expected = "Transfer exceeds transferable balance, frozen amount: 500")]
+fn frozen_amount_cannot_be_transferred() {
+    let mut state = partially_frozen_state();
+    state.execute_transfer(
+        &context(account(1)),
+        Transfer::new(account(1), account(2), BALANCE / 2 + 1, None),
+    );
+}
@@ -0,0 +1,194 @@
+//! Transfers approved by an external compliance contract, for issuers whose rules are managed by
+//! a separate service rather than baked into the token. Transfers above the configured threshold
+//! must be sent with `transfer_checked`, which asks the compliance contract at
+//! [`COMPLIANCE_CHECK_SHORTNAME`] with the arguments `(from: Address, to: Address, amount: u128)`.
+//! The compliance contract must return a `bool`, and the callback executes the transfer if the call
+//! succeeded and returned `true`. Nothing is escrowed in between, the transfer is aborted if the
+//! sender can no longer cover it.
+#![cfg_attr(not(feature = "external-compliance"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "external-compliance")]
+use pbc_contract_common::context::{CallbackContext, ContractContext};
+#[cfg(feature = "external-compliance")]
+use pbc_contract_common::events::EventGroup;
+#[cfg(feature = "external-compliance")]
+use pbc_contract_common::shortname::{Shortname, ShortnameCallback};
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "external-compliance")]
+use crate::hooks::{Transfer, TransferHook};
+#[cfg(feature = "external-compliance")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "external-compliance"))]
+mod tests;
+
+/// Shortname the compliance contract is invoked at.
+pub const COMPLIANCE_CHECK_SHORTNAME: u32 = 0x6e;
+
+/// Shortname of the callback executing an approved transfer.
+#[cfg(feature = "external-compliance")]
+const TRANSFER_CHECKED_CALLBACK_SHORTNAME: u32 = 0x0d;
+
+/// The external compliance contract and the transfers it checks.
+///
+/// ### Fields:
+///
+///   * `contract`: [`Address`], contract approving transfers.
+///   * `threshold`: [`u128`], transfers above this amount must be approved.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExternalCompliance {
+    pub contract: Address,
+    pub threshold: u128,
+}
+
+/// Rejects transfers above the threshold that were not approved by the compliance contract. The
+/// contract itself is not subject to the check.
+#[cfg(feature = "external-compliance")]
+pub struct ExternalComplianceGuard;
+
+#[cfg(feature = "external-compliance")]
+impl TransferHook for ExternalComplianceGuard {
+    fn before_transfer(
+        ctx: &ContractContext,
+        state: &mut TashiTokenState,
+        transfer: &mut Transfer,
+    ) {
+        if let Some(compliance) = &state.external_compliance {
+            assert!(
+                transfer.amount <= compliance.threshold
+                    || transfer.compliance_approved
+                    || transfer.from == ctx.contract_address,
+                "Transfers above: {} must be approved with transfer_checked.",
+                compliance.threshold
+            );
+        }
+    }
+}
+
+/// Set the external compliance contract and the threshold above which it approves transfers, or
+/// remove it with `None`. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `compliance`: [`Option`]<[`ExternalCompliance`]>, the compliance contract, `None` to remove it.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "external-compliance")]
+#[action(shortname = 0xd5)]
+fn set_external_compliance(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    compliance: Option<ExternalCompliance>,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_external_compliance", compliance);
+    state.external_compliance = compliance;
+    state.assert_invariants();
+
+    state
+}
+
+/// Transfer `amount` tokens to address `to` from caller address once the compliance contract
+/// approves it. The transfer is executed by the callback, otherwise handled as by `transfer`.
+///
+/// Panics if no compliance contract is set.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`u128`], amount to transfer.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state and the compliance check interaction.
+#[cfg(feature = "external-compliance")]
+#[action(shortname = 0xd6)]
+fn transfer_checked(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    to: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "transfer_checked");
+    let compliance = state
+        .external_compliance
+        .as_ref()
+        .expect("No compliance contract is set.");
+
+    let mut event_group = EventGroup::builder();
+    event_group
+        .call(
+            compliance.contract,
+            Shortname::from_u32(COMPLIANCE_CHECK_SHORTNAME),
+        )
+        .argument(ctx.sender)
+        .argument(to)
+        .argument(amount)
+        .done();
+    event_group
+        .with_callback(ShortnameCallback::from_u32(
+            TRANSFER_CHECKED_CALLBACK_SHORTNAME,
+        ))
+        .argument(ctx.sender)
+        .argument(to)
+        .argument(amount)
+        .done();
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Execute a checked transfer if the compliance contract returned `true`, and abort it otherwise.
+///
+/// Panics if the transfer was not approved, or if it fails.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the compliance contract call.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `from`: [`Address`], account sending the transfer.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`u128`], amount to transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "external-compliance")]
+#[callback(shortname = 0x0d)]
+fn transfer_checked_callback(
+    ctx: ContractContext,
+    callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+    from: Address,
+    to: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let approved = callback_ctx.success && callback_ctx.results[0].get_return_data::<bool>();
+    assert!(
+        approved,
+        "Transfer was not approved by the compliance contract."
+    );
+    let supply = state.supply();
+    let mut transfer = Transfer::new(from, to, amount, None);
+    transfer.compliance_approved = true;
+    state.execute_transfer(&ctx, transfer);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
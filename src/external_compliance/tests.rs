@@ -0,0 +1,99 @@
+//! Tests of external compliance checks: transfers above the threshold rejected unless checked, and
+//! checked transfers executed only once the compliance contract approves them.
+
+use pbc_contract_common::context::{CallbackContext, ExecutionResult};
+
+use super::{
+    set_external_compliance, transfer_checked, transfer_checked_callback, ExternalCompliance,
+};
+use crate::hooks::Transfer;
+use crate::testing::{
+    account, callback_context, context, contract, new_state, token_address, INITIAL_SUPPLY,
+};
+use crate::token::TashiTokenState;
+
+/// The result of a compliance contract call which succeeded and returned `approved`.
+fn compliance_result(approved: bool) -> CallbackContext {
+    CallbackContext {
+        success: true,
+        results: vec![ExecutionResult {
+            succeeded: true,
+            return_data: vec![u8::from(approved)],
+        }],
+    }
+}
+
+/// A token whose transfers above 1_000 are approved by compliance contract 1.
+fn compliance_state() -> TashiTokenState {
+    set_external_compliance(
+        context(account(0)),
+        new_state(),
+        Some(ExternalCompliance {
+            contract: contract(1),
+            threshold: 1_000,
+        }),
+    )
+}
+
+/// Answers a checked transfer of 5_000 from the owner to account 1 with `callback_ctx`.
+fn answer(callback_ctx: CallbackContext) -> TashiTokenState {
+    transfer_checked_callback(
+        context(token_address()),
+        callback_ctx,
+        compliance_state(),
+        account(0),
+        account(1),
+        5_000,
+    )
+    .0
+}
+
+#[test]
+fn transfer_up_to_the_threshold_is_not_checked() {
+    let mut state = compliance_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 1_000, None),
+    );
+
+    assert_eq!(state.balance_of(&account(1)), 1_000);
+}
+
+#[test]
+#[should_panic(expected = "Transfers above: 1000 must be approved with transfer_checked.")]
+fn unchecked_transfer_above_the_threshold_is_rejected() {
+    let mut state = compliance_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 1_001, None),
+    );
+}
+
+#[test]
+fn checked_transfer_waits_for_the_compliance_contract() {
+    let (state, events) =
+        transfer_checked(context(account(0)), compliance_state(), account(1), 5_000);
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY);
+}
+
+#[test]
+fn approved_transfer_is_executed() {
+    let state = answer(compliance_result(true));
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 5_000);
+    assert_eq!(state.balance_of(&account(1)), 5_000);
+}
+
+#[test]
+#[should_panic(expected = "Transfer was not approved by the compliance contract.")]
+fn rejected_transfer_is_aborted() {
+    answer(compliance_result(false));
+}
+
+#[test]
+#[should_panic(expected = "Transfer was not approved by the compliance contract.")]
+fn failed_check_aborts_the_transfer() {
+    answer(callback_context(false));
+}
@@ -0,0 +1,120 @@
+//! Burning of collected fees, closing the fee lifecycle without manual burns by the owner. Transfer
+//! fees credited to the treasury held by the contract itself are counted as collected. Once they
+//! reach the threshold set by the owner, a keeper burns the configured share of them and pays the
+//! rest to the configured recipient.
+#![cfg_attr(not(feature = "fee-burn"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "fee-burn")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "fee-burn")]
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "fee-burn")]
+use crate::admin::Role;
+#[cfg(feature = "fee-burn")]
+use crate::token::{mul_div, TashiTokenState, BPS_DENOMINATOR};
+
+#[cfg(all(test, feature = "fee-burn"))]
+mod tests;
+
+/// How collected fees are burned.
+///
+/// ### Fields:
+///
+///   * `burn_bps`: [`u16`], share of the collected fees that is burned, in basis points.
+///   * `recipient`: [`Address`], account paid the rest of the collected fees.
+///   * `min_amount`: [`u128`], least amount of collected fees processed at once.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeeBurnConfig {
+    pub burn_bps: u16,
+    pub recipient: Address,
+    pub min_amount: u128,
+}
+
+/// Set how collected fees are burned, none to stop burning them. Only the owner can call this
+/// action.
+///
+/// Panics if the caller is not the owner, if the burned share exceeds 100%, or if the threshold is
+/// zero.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `config`: [`Option`]<[`FeeBurnConfig`]>, the new configuration.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "fee-burn")]
+#[action(shortname = 0xa8)]
+fn set_fee_burn_config(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    config: Option<FeeBurnConfig>,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_fee_burn_config", config);
+    if let Some(config) = config {
+        assert!(
+            u128::from(config.burn_bps) <= BPS_DENOMINATOR,
+            "Burned share cannot exceed 100%."
+        );
+        assert!(config.min_amount > 0, "Threshold cannot be zero.");
+    }
+    state.fee_burn = config;
+    state.assert_invariants();
+
+    state
+}
+
+/// Burn the configured share of the fees collected in the treasury and pay the rest to the
+/// configured recipient. Only accounts with the [`Role::Keeper`] role can call this action.
+///
+/// Panics if the caller does not have the role, if fee burning is not configured, or if the
+/// collected fees still in the treasury are below the threshold.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "fee-burn")]
+#[action(shortname = 0xa9)]
+fn burn_collected_fees(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "burn_collected_fees");
+    state.assert_role(Role::Keeper {}, &ctx.sender);
+    let config = state.fee_burn.expect("Fee burning is not configured.");
+    // fees already paid out of the treasury cannot be burned
+    let amount = state
+        .collected_fees
+        .min(state.balance_of(&ctx.contract_address));
+    assert!(
+        amount >= config.min_amount,
+        "Collected fees: {}, are below the threshold: {}",
+        amount,
+        config.min_amount
+    );
+    let supply = state.supply();
+    state.collected_fees = 0;
+    let burned = mul_div(amount, u128::from(config.burn_bps), BPS_DENOMINATOR);
+    state.burn_balance(ctx.contract_address, burned, ctx.block_production_time);
+    state.debit(ctx.contract_address, amount - burned);
+    state.credit(config.recipient, amount - burned);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
@@ -0,0 +1,76 @@
+//! Tests of burning collected fees: the burned share and the rest paid out, the threshold, and fees
+//! already paid out of the treasury.
+
+use super::{burn_collected_fees, set_fee_burn_config, FeeBurnConfig};
+use crate::admin::{grant_role, Role};
+use crate::hooks::Transfer;
+use crate::testing::{account, admin_context, context, new_state, token_address, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token burning 25% of at least 1_000 collected fees and paying the rest to account 2, whose
+/// treasury holds `collected` fees and whose keeper is account 9.
+fn collected_state(collected: u128) -> TashiTokenState {
+    let mut state = grant_role(admin_context(), new_state(), Role::Keeper {}, account(9));
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), token_address(), collected, None),
+    );
+    state.collected_fees = collected;
+    set_fee_burn_config(
+        context(account(0)),
+        state,
+        Some(FeeBurnConfig {
+            burn_bps: 2_500,
+            recipient: account(2),
+            min_amount: 1_000,
+        }),
+    )
+}
+
+#[test]
+fn collected_fees_are_burned_and_paid_out() {
+    let (state, _) = burn_collected_fees(context(account(9)), collected_state(4_000));
+
+    assert_eq!(state.balance_of(&token_address()), 0);
+    assert_eq!(state.balance_of(&account(2)), 3_000);
+    assert_eq!(state.supply(), INITIAL_SUPPLY - 1_000);
+    assert_eq!(state.collected_fees, 0);
+}
+
+#[test]
+fn fees_paid_out_of_the_treasury_are_not_burned() {
+    let mut state = collected_state(4_000);
+    state.debit(token_address(), 2_000);
+    state.credit(account(1), 2_000);
+    let (state, _) = burn_collected_fees(context(account(9)), state);
+
+    assert_eq!(state.balance_of(&account(2)), 1_500);
+    assert_eq!(state.supply(), INITIAL_SUPPLY - 500);
+    assert_eq!(state.collected_fees, 0);
+}
+
+#[test]
+#[should_panic(expected = "Collected fees: 999, are below the threshold: 1000")]
+fn fees_below_the_threshold_are_kept() {
+    burn_collected_fees(context(account(9)), collected_state(999));
+}
+
+#[test]
+#[should_panic(expected = "Caller is missing role: Keeper")]
+fn only_keepers_burn_fees() {
+    burn_collected_fees(context(account(1)), collected_state(4_000));
+}
+
+#[test]
+#[should_panic(expected = "Burned share cannot exceed 100%.")]
+fn burned_share_cannot_exceed_everything() {
+    set_fee_burn_config(
+        context(account(0)),
+        new_state(),
+        Some(FeeBurnConfig {
+            burn_bps: 10_001,
+            recipient: account(2),
+            min_amount: 1_000,
+        }),
+    );
+}
@@ -0,0 +1,252 @@
+//! Flash mints, where tokens are minted to a contract for the duration of one interaction and must
+//! be returned, plus a fee, by the time the callback runs.
+//!
+//! Only receiving contracts the owner allowlisted can be flash minted to. The receiving contract is
+//! invoked at [`FLASH_MINT_RECEIVER_SHORTNAME`] with the arguments
+//! `(initiator: Address, amount: u128, fee: u128, payload: Vec<u8>)`, and repays by approving this
+//! contract's own address to spend `amount + fee` of its balance. The callback spends that
+//! allowance and burns the repayment from the receiver's balance. As the blockchain cannot roll
+//! back the interaction, whatever the receiver did not approve or no longer holds is recorded as a
+//! debt barring it from further flash mints.
+#![cfg_attr(not(feature = "flash-mint"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "flash-mint")]
+use pbc_contract_common::context::{CallbackContext, ContractContext};
+#[cfg(feature = "flash-mint")]
+use pbc_contract_common::events::EventGroup;
+#[cfg(feature = "flash-mint")]
+use pbc_contract_common::shortname::{Shortname, ShortnameCallback};
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "flash-mint")]
+use crate::token::{mul_div, TashiTokenState, BPS_DENOMINATOR};
+
+#[cfg(all(test, feature = "flash-mint"))]
+mod tests;
+
+/// Shortname the receiving contract of a flash mint is invoked at.
+pub const FLASH_MINT_RECEIVER_SHORTNAME: u32 = 0x50;
+
+/// Shortname of the callback settling a flash mint.
+#[cfg(feature = "flash-mint")]
+const FLASH_MINT_CALLBACK_SHORTNAME: u32 = 0x03;
+
+/// A flash mint waiting to be settled.
+///
+/// ### Fields:
+///
+///   * `receiver`: [`Address`], contract the tokens were minted to.
+///   * `amount`: [`u128`], amount minted.
+///   * `fee`: [`u128`], fee owed on top of `amount`.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlashMint {
+    pub receiver: Address,
+    pub amount: u128,
+    pub fee: u128,
+}
+
+/// Configure flash mints. A `max_amount` of zero disables them. Only the owner can call this
+/// action, through the admin timelock with the `admin-timelock` feature.
+///
+/// Panics if the caller is not the owner or if `fee_bps` exceeds 100%.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `max_amount`: [`u128`], largest amount a single flash mint can mint.
+///   * `fee_bps`: [`u16`], fee owed on top of the minted amount, in basis points.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "flash-mint")]
+#[action(shortname = 0x29)]
+pub(crate) fn set_flash_mint_config(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    max_amount: u128,
+    fee_bps: u16,
+) -> TashiTokenState {
+    state.assert_owner_or_timelock(&ctx);
+    crate::audit::log_admin_action!(state, ctx, "set_flash_mint_config", max_amount, fee_bps);
+    assert!(
+        u128::from(fee_bps) <= BPS_DENOMINATOR,
+        "Flash mint fee cannot exceed 100%."
+    );
+    state.flash_mint_max = max_amount;
+    state.flash_mint_fee_bps = fee_bps;
+    state.assert_invariants();
+
+    state
+}
+
+/// Allow flash mints to `receiver`, or remove it from the allowlist. Only the owner can call this
+/// action, through the admin timelock with the `admin-timelock` feature.
+///
+/// Panics if the caller is not the owner, or if `allowed` does not change whether `receiver` is
+/// allowlisted.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `receiver`: [`Address`], contract to add to or remove from the allowlist.
+///   * `allowed`: [`bool`], true to add `receiver`, false to remove it.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "flash-mint")]
+#[action(shortname = 0x100)]
+pub(crate) fn set_flash_mint_receiver(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    receiver: Address,
+    allowed: bool,
+) -> TashiTokenState {
+    state.assert_owner_or_timelock(&ctx);
+    crate::audit::log_admin_action!(state, ctx, "set_flash_mint_receiver", receiver, allowed);
+    assert!(
+        state.flash_mint_receivers.contains(&receiver) != allowed,
+        "Receiver is already {}.",
+        if allowed {
+            "allowlisted"
+        } else {
+            "not allowlisted"
+        }
+    );
+    if allowed {
+        state.flash_mint_receivers.push(receiver);
+    } else {
+        state
+            .flash_mint_receivers
+            .retain(|allowlisted| *allowlisted != receiver);
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Mint `amount` tokens to `target_contract` and invoke it with `payload`. Before the callback
+/// settles the flash mint, the target must approve this contract's own address to spend `amount`
+/// plus the fee.
+///
+/// Panics if another flash mint is in progress, if `amount` exceeds the configured maximum, if the
+/// target is not allowlisted, or if it has an outstanding flash mint debt.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to mint.
+///   * `target_contract`: [`Address`], contract to mint to and invoke.
+///   * `payload`: [`Vec`]<[`u8`]>, opaque data passed on to the target.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state, the interaction with the target and the supply notifications.
+#[cfg(feature = "flash-mint")]
+#[action(shortname = 0x28)]
+fn flash_mint(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+    target_contract: Address,
+    payload: Vec<u8>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "flash_mint");
+    assert!(
+        state.active_flash_mint.is_none(),
+        "A flash mint is in progress."
+    );
+    assert!(
+        amount <= state.flash_mint_max,
+        "Flash mint amount: {}, maximum allowed amount: {}",
+        amount,
+        state.flash_mint_max
+    );
+    assert!(
+        state.flash_mint_receivers.contains(&target_contract),
+        "Target is not an allowlisted flash mint receiver."
+    );
+    assert!(
+        !state.flash_mint_debts.contains_key(&target_contract),
+        "Target has an outstanding flash mint debt."
+    );
+
+    let fee = mul_div(
+        amount,
+        u128::from(state.flash_mint_fee_bps),
+        BPS_DENOMINATOR,
+    );
+    state.active_flash_mint = Some(FlashMint {
+        receiver: target_contract,
+        amount,
+        fee,
+    });
+    let supply = state.supply();
+    state.mint_balance(target_contract, amount);
+
+    let mut event_group = EventGroup::builder();
+    event_group
+        .call(
+            target_contract,
+            Shortname::from_u32(FLASH_MINT_RECEIVER_SHORTNAME),
+        )
+        .argument(ctx.sender)
+        .argument(amount)
+        .argument(fee)
+        .argument(payload)
+        .done();
+    event_group
+        .with_callback(ShortnameCallback::from_u32(FLASH_MINT_CALLBACK_SHORTNAME))
+        .done();
+
+    let mut events = vec![event_group.build()];
+    events.extend(state.supply_change_events(supply));
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Settle the flash mint in progress by spending the receiver's allowance to this contract and
+/// burning the repayment from its balance. What the receiver did not approve or no longer holds is
+/// recorded as its debt.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the interaction with the target.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "flash-mint")]
+#[callback(shortname = 0x03)]
+fn flash_mint_callback(
+    ctx: ContractContext,
+    _callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let supply = state.supply();
+    let flash = state.active_flash_mint.take().unwrap();
+    let owed = flash.amount + flash.fee;
+    let repaid = owed
+        .min(state.allowance(&flash.receiver, &ctx.contract_address))
+        .min(state.balance_of(&flash.receiver));
+    state.spend_allowance(flash.receiver, ctx.contract_address, repaid);
+    state.burn_balance(flash.receiver, repaid, ctx.block_production_time);
+    if owed > repaid {
+        state.flash_mint_debts.insert(flash.receiver, owed - repaid);
+    }
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
@@ -0,0 +1,126 @@
+//! Tests of flash mints: the receiver allowlist, repayment through an allowance and the debt of a
+//! receiver that does not repay.
+
+use super::{flash_mint, flash_mint_callback, set_flash_mint_receiver};
+use crate::hooks::Transfer;
+use crate::testing::{
+    account, admin_context, callback_context, context, contract, new_state, token_address,
+    INITIAL_SUPPLY,
+};
+use crate::token::TashiTokenState;
+
+/// Amount minted by the flash mints of the tests.
+const AMOUNT: u128 = 10_000;
+
+/// Fee owed on [`AMOUNT`] at the configured 1% fee.
+const FEE: u128 = 100;
+
+/// A token allowing flash mints of up to [`AMOUNT`] at a 1% fee to allowlisted contract 1, which
+/// holds enough to pay the fee.
+fn flash_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.flash_mint_max = AMOUNT;
+    state.flash_mint_fee_bps = 100;
+    let mut state = set_flash_mint_receiver(admin_context(), state, contract(1), true);
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), contract(1), FEE, None),
+    );
+    state
+}
+
+/// Runs a flash mint of [`AMOUNT`] to contract 1, lets `interact` act as the receiver, and
+/// settles the flash mint.
+fn run_flash_mint(
+    state: TashiTokenState,
+    interact: impl FnOnce(&mut TashiTokenState),
+) -> TashiTokenState {
+    let (mut state, _) = flash_mint(context(account(2)), state, AMOUNT, contract(1), vec![]);
+    assert_eq!(state.balance_of(&contract(1)), AMOUNT + FEE);
+    interact(&mut state);
+    flash_mint_callback(context(contract(1)), callback_context(true), state).0
+}
+
+#[test]
+fn repayment_is_spent_from_the_receiver_allowance() {
+    let state = run_flash_mint(flash_state(), |state| {
+        state.approve_spender(contract(1), token_address(), AMOUNT + FEE);
+    });
+
+    assert_eq!(state.balance_of(&contract(1)), 0);
+    assert_eq!(state.allowance(&contract(1), &token_address()), 0);
+    assert_eq!(state.supply(), INITIAL_SUPPLY - FEE);
+    assert!(state.flash_mint_debts.is_empty());
+    assert!(state.active_flash_mint.is_none());
+}
+
+#[test]
+fn missing_allowance_is_recorded_as_debt() {
+    let state = run_flash_mint(flash_state(), |state| {
+        state.approve_spender(contract(1), token_address(), AMOUNT);
+    });
+
+    assert_eq!(state.balance_of(&contract(1)), FEE);
+    assert_eq!(state.supply(), INITIAL_SUPPLY);
+    assert_eq!(state.flash_mint_debts.get(&contract(1)), Some(&FEE));
+}
+
+#[test]
+fn tokens_moved_away_are_recorded_as_debt() {
+    let state = run_flash_mint(flash_state(), |state| {
+        state.approve_spender(contract(1), token_address(), AMOUNT + FEE);
+        state.execute_transfer(
+            &context(contract(1)),
+            Transfer::new(contract(1), account(3), AMOUNT, None),
+        );
+    });
+
+    assert_eq!(state.balance_of(&account(3)), AMOUNT);
+    assert_eq!(state.balance_of(&contract(1)), 0);
+    assert_eq!(state.supply(), INITIAL_SUPPLY + AMOUNT - FEE);
+    assert_eq!(state.flash_mint_debts.get(&contract(1)), Some(&AMOUNT));
+}
+
+#[test]
+#[should_panic(expected = "Target has an outstanding flash mint debt.")]
+fn receiver_with_debt_cannot_flash_mint() {
+    let state = run_flash_mint(flash_state(), |_| {});
+    flash_mint(context(account(2)), state, AMOUNT, contract(1), vec![]);
+}
+
+#[test]
+#[should_panic(expected = "Target is not an allowlisted flash mint receiver.")]
+fn unlisted_target_cannot_flash_mint() {
+    flash_mint(
+        context(account(2)),
+        flash_state(),
+        AMOUNT,
+        contract(2),
+        vec![],
+    );
+}
+
+#[test]
+#[should_panic(expected = "Target is not an allowlisted flash mint receiver.")]
+fn removed_target_cannot_flash_mint() {
+    let state = set_flash_mint_receiver(admin_context(), flash_state(), contract(1), false);
+    flash_mint(context(account(2)), state, AMOUNT, contract(1), vec![]);
+}
+
+#[test]
+#[should_panic(expected = "Flash mint amount: 10001, maximum allowed amount: 10000")]
+fn amount_above_maximum_is_rejected() {
+    flash_mint(
+        context(account(2)),
+        flash_state(),
+        AMOUNT + 1,
+        contract(1),
+        vec![],
+    );
+}
+
+#[test]
+#[should_panic]
+fn only_the_owner_can_allowlist_receivers() {
+    set_flash_mint_receiver(context(account(2)), new_state(), contract(1), true);
+}
@@ -0,0 +1,519 @@
+//! On-chain governance, letting token holders change the parameters of the token themselves.
+//! Holders create proposals, vote with tokens that stay locked until voting ends, and proposals
+//! that pass are executed after a timelock. Votes count linearly with the locked tokens, or as
+//! their square root in quadratic mode, where only verified voters can vote. In vote-escrowed mode
+//! holders vote with the weight of their vote-escrow lock instead, and lock nothing more.
+#![cfg_attr(not(feature = "governance"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "governance")]
+use crate::admin::Role;
+use crate::mint_limit::MintRateLimit;
+#[cfg(feature = "governance")]
+use crate::token::mul_div;
+use crate::token::{TashiTokenState, BPS_DENOMINATOR};
+
+#[cfg(all(test, feature = "governance"))]
+mod tests;
+
+/// Parameters of the governance process.
+///
+/// ### Fields:
+///
+///   * `voting_period`: [`i64`], how long a proposal is open for voting, in milliseconds.
+///   * `timelock`: [`i64`], delay between the end of voting and execution, in milliseconds.
+///   * `quorum_bps`: [`u16`], share of the total supply that must vote on a proposal for it to
+///     pass, in basis points.
+///   * `proposal_threshold_bps`: [`u16`], share of the total supply an account must hold to
+///     create a proposal, in basis points.
+///   * `voting_mode`: [`VotingMode`], how locked tokens are counted as votes on new proposals.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GovernanceConfig {
+    pub voting_period: i64,
+    pub timelock: i64,
+    pub quorum_bps: u16,
+    pub proposal_threshold_bps: u16,
+    pub voting_mode: VotingMode,
+}
+
+/// How the tokens a voter locks are counted as votes.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VotingMode {
+    /// Every locked token is one vote.
+    #[discriminant(0)]
+    Linear {},
+    /// Locked tokens count as their square root. To stop holders splitting their tokens over many
+    /// accounts, only accounts with the [`Role::Voter`] role can vote.
+    #[discriminant(1)]
+    Quadratic {},
+    /// Voters vote with the weight of their vote-escrow lock at the time of voting, and lock no
+    /// tokens. Requires the `vote-escrow` feature.
+    #[discriminant(2)]
+    VoteEscrowed {},
+}
+
+impl VotingMode {
+    /// Gets the votes `weight` locked tokens are worth.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `weight`: [`u128`], amount of tokens locked.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount of votes.
+    pub fn votes(&self, weight: u128) -> u128 {
+        match self {
+            VotingMode::Linear {} => weight,
+            VotingMode::Quadratic {} => weight.isqrt(),
+            VotingMode::VoteEscrowed {} => weight,
+        }
+    }
+}
+
+/// Governance parameters of a newly initialized token: three days of voting, a two day timelock, a
+/// 4% quorum, a 1% proposal threshold and linear voting.
+pub const DEFAULT_GOVERNANCE_CONFIG: GovernanceConfig = GovernanceConfig {
+    voting_period: 3 * 24 * 60 * 60 * 1000,
+    timelock: 2 * 24 * 60 * 60 * 1000,
+    quorum_bps: 400,
+    proposal_threshold_bps: 100,
+    voting_mode: VotingMode::Linear {},
+};
+
+/// A parameter change a proposal executes once it passes.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProposalAction {
+    /// Set the share of every transfer that is burned, requires the `burnable` feature.
+    #[discriminant(0)]
+    SetBurnRate { rate_bps: u16 },
+    /// Pause or resume all transfers, requires the `pausable` feature.
+    #[discriminant(1)]
+    SetPaused { paused: bool },
+    /// Pay `amount` tokens held by the contract itself to `receiver`.
+    #[discriminant(2)]
+    TransferTreasury { receiver: Address, amount: u128 },
+    /// Replace the parameters of the governance process.
+    #[discriminant(3)]
+    SetGovernanceConfig { config: GovernanceConfig },
+    /// Set the minimum delay between transfers from the same account, requires the `cooldown`
+    /// feature.
+    #[discriminant(4)]
+    SetTransferCooldown { cooldown: i64 },
+    /// Extend the emergency pause until `until`, requires the `emergency-pause` feature.
+    #[discriminant(5)]
+    ExtendEmergencyPause { until: i64 },
+    /// Cancel the action `action_id` queued in the admin timelock, requires the `admin-timelock`
+    /// feature.
+    #[discriminant(6)]
+    CancelAdminAction { action_id: u64 },
+    /// Set the mint rate limit, none to remove it, requires the `mint-rate-limit` feature.
+    #[discriminant(7)]
+    SetMintRateLimit { limit: Option<MintRateLimit> },
+    /// Approve milestone `milestone` of grant `grant_id`, paying it to the recipient, requires the
+    /// `milestone-grants` feature.
+    #[discriminant(8)]
+    ApproveMilestone { grant_id: u64, milestone: u32 },
+    /// Raise the decimals of the token to `decimals`, scaling every amount, requires the
+    /// `redenomination` feature.
+    #[discriminant(9)]
+    Redenominate { decimals: u8 },
+}
+
+/// A proposal to execute `actions`, voted on by token holders.
+///
+/// ### Fields:
+///
+///   * `proposer`: [`Address`], account that created the proposal.
+///   * `description`: [`String`], human readable description of the proposal.
+///   * `actions`: [`Vec`]<[`ProposalAction`]>, parameter changes executed when the proposal passes.
+///   * `voting_ends_at`: [`i64`], block production time voting closes at, in milliseconds.
+///   * `executable_at`: [`i64`], block production time the proposal can be executed from, in
+///     milliseconds.
+///   * `voting_mode`: [`VotingMode`], how locked tokens are counted as votes.
+///   * `quorum`: [`u128`], amount of tokens that must be locked for the proposal to pass.
+///   * `turnout`: [`u128`], amount of tokens locked by all voters, or their vote-escrow weight in
+///     vote-escrowed mode.
+///   * `votes_for`: [`u128`], votes in favor of the proposal.
+///   * `votes_against`: [`u128`], votes against the proposal.
+///   * `locked_votes`: [`SortedVecMap`]<[`Address`], [`u128`]>, tokens each voter has locked and
+///     not yet withdrawn.
+///   * `executed`: [`bool`], whether the proposal has been executed.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Proposal {
+    pub proposer: Address,
+    pub description: String,
+    pub actions: Vec<ProposalAction>,
+    pub voting_ends_at: i64,
+    pub executable_at: i64,
+    pub voting_mode: VotingMode,
+    pub quorum: u128,
+    pub turnout: u128,
+    pub votes_for: u128,
+    pub votes_against: u128,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub locked_votes: SortedVecMap<Address, u128>,
+    pub executed: bool,
+}
+
+impl Proposal {
+    /// Whether the proposal reached quorum with more votes for than against. Quorum counts locked
+    /// tokens in the linear and quadratic modes, and vote-escrow weight in vote-escrowed mode.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`bool`], true if the proposal passed.
+    pub fn passed(&self) -> bool {
+        self.votes_for > self.votes_against && self.turnout >= self.quorum
+    }
+}
+
+impl TashiTokenState {
+    /// Gets the proposal with id `proposal_id`.
+    ///
+    /// Panics if the proposal does not exist.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `proposal_id`: [`u64`], id of the proposal.
+    ///
+    /// ### Returns:
+    ///
+    /// A mutable reference to the [`Proposal`].
+    pub fn proposal(&mut self, proposal_id: u64) -> &mut Proposal {
+        self.proposals
+            .get_mut(&proposal_id)
+            .unwrap_or_else(|| panic!("Proposal not found: {}", proposal_id))
+    }
+
+    /// Applies a parameter change of a passed proposal.
+    ///
+    /// Panics if the change is invalid or targets a feature that is not enabled.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context, the contract holds the treasury.
+    ///   * `action`: [`ProposalAction`], the change to apply.
+    pub fn apply_proposal_action(&mut self, ctx: &ContractContext, action: ProposalAction) {
+        let (contract, now) = (ctx.contract_address, ctx.block_production_time);
+        match action {
+            ProposalAction::SetBurnRate { rate_bps } => {
+                #[cfg(feature = "burnable")]
+                self.set_burn_rate_bps(rate_bps);
+                #[cfg(not(feature = "burnable"))]
+                panic!(
+                    "Burn on transfer is not enabled, cannot set burn rate: {}",
+                    rate_bps
+                );
+            }
+            ProposalAction::SetPaused { paused } => {
+                #[cfg(feature = "pausable")]
+                {
+                    self.paused = paused;
+                    if !paused {
+                        self.emergency_paused_until = 0;
+                    }
+                }
+                #[cfg(not(feature = "pausable"))]
+                panic!("Pausing is not enabled, cannot set paused: {}", paused);
+            }
+            ProposalAction::TransferTreasury { receiver, amount } => {
+                self.debit(contract, amount);
+                self.credit(receiver, amount);
+                self.record_disbursement(receiver, amount, String::new(), contract, now);
+            }
+            ProposalAction::SetGovernanceConfig { config } => {
+                assert!(
+                    config.voting_period > 0 && config.timelock >= 0,
+                    "Voting period must be positive and timelock cannot be negative."
+                );
+                assert!(
+                    u128::from(config.quorum_bps) <= BPS_DENOMINATOR
+                        && u128::from(config.proposal_threshold_bps) <= BPS_DENOMINATOR,
+                    "Quorum and proposal threshold cannot exceed the total supply."
+                );
+                assert!(
+                    cfg!(feature = "vote-escrow")
+                        || config.voting_mode != (VotingMode::VoteEscrowed {}),
+                    "Vote escrow is not enabled, cannot use vote-escrowed voting."
+                );
+                self.governance = config;
+            }
+            ProposalAction::SetTransferCooldown { cooldown } => {
+                #[cfg(feature = "cooldown")]
+                self.set_transfer_cooldown_ms(cooldown);
+                #[cfg(not(feature = "cooldown"))]
+                panic!(
+                    "Transfer cooldown is not enabled, cannot set cooldown: {}",
+                    cooldown
+                );
+            }
+            ProposalAction::ExtendEmergencyPause { until } => {
+                #[cfg(feature = "emergency-pause")]
+                self.extend_emergency_pause_until(until);
+                #[cfg(not(feature = "emergency-pause"))]
+                panic!(
+                    "Emergency pause is not enabled, cannot extend it until: {}",
+                    until
+                );
+            }
+            ProposalAction::CancelAdminAction { action_id } => {
+                #[cfg(feature = "admin-timelock")]
+                self.cancel_queued_action(action_id);
+                #[cfg(not(feature = "admin-timelock"))]
+                panic!(
+                    "Admin timelock is not enabled, cannot cancel action: {}",
+                    action_id
+                );
+            }
+            ProposalAction::SetMintRateLimit { limit } => {
+                #[cfg(feature = "mint-rate-limit")]
+                self.set_mint_rate_limit_config(limit);
+                #[cfg(not(feature = "mint-rate-limit"))]
+                panic!(
+                    "Mint rate limit is not enabled, cannot set limit: {}",
+                    limit.map_or(0, |limit| limit.max_per_epoch)
+                );
+            }
+            ProposalAction::ApproveMilestone {
+                grant_id,
+                milestone,
+            } => {
+                #[cfg(feature = "milestone-grants")]
+                self.release_milestone(ctx, grant_id, milestone);
+                #[cfg(not(feature = "milestone-grants"))]
+                panic!(
+                    "Milestone grants are not enabled, cannot approve milestone: {}, of grant: {}",
+                    milestone, grant_id
+                );
+            }
+            ProposalAction::Redenominate { decimals } => {
+                #[cfg(feature = "redenomination")]
+                self.redenominate_to(decimals, now);
+                #[cfg(not(feature = "redenomination"))]
+                panic!(
+                    "Redenomination is not enabled, cannot redenominate to: {}",
+                    decimals
+                );
+            }
+        }
+    }
+}
+
+/// Create a proposal to execute `actions`. Voting opens immediately and runs for the configured
+/// voting period.
+///
+/// Panics if `actions` is empty or if the caller holds less than the proposal threshold.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `description`: [`String`], human readable description of the proposal.
+///   * `actions`: [`Vec`]<[`ProposalAction`]>, parameter changes executed when the proposal passes.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "governance")]
+#[action(shortname = 0x2d)]
+fn propose(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    description: String,
+    actions: Vec<ProposalAction>,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "propose");
+    assert!(!actions.is_empty(), "A proposal needs at least one action.");
+    let supply = state.supply();
+    let threshold = mul_div(
+        supply,
+        u128::from(state.governance.proposal_threshold_bps),
+        BPS_DENOMINATOR,
+    );
+    let balance = state.balance_of(&ctx.sender);
+    assert!(
+        balance >= threshold,
+        "Insufficient balance: {}, minimum required balance to propose: {}",
+        balance,
+        threshold
+    );
+
+    let voting_ends_at = ctx.block_production_time + state.governance.voting_period;
+    let proposal = Proposal {
+        proposer: ctx.sender,
+        description,
+        actions,
+        voting_ends_at,
+        executable_at: voting_ends_at + state.governance.timelock,
+        voting_mode: state.governance.voting_mode,
+        quorum: mul_div(
+            supply,
+            u128::from(state.governance.quorum_bps),
+            BPS_DENOMINATOR,
+        ),
+        turnout: 0,
+        votes_for: 0,
+        votes_against: 0,
+        locked_votes: SortedVecMap::new(),
+        executed: false,
+    };
+    state.proposals.insert(state.next_proposal_id, proposal);
+    state.next_proposal_id += 1;
+    state.assert_invariants();
+
+    state
+}
+
+/// Vote on proposal `proposal_id` with `weight` tokens from caller address. The tokens are locked
+/// until voting ends, so they cannot be voted with twice. If the proposal uses vote-escrowed
+/// voting, `weight` is ignored and the caller votes with the weight of its vote-escrow lock.
+///
+/// Panics if voting has ended, if the caller already voted, if the weight is zero, if the proposal
+/// uses quadratic voting and the caller is not a verified voter, or if there is insufficient
+/// balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `proposal_id`: [`u64`], id of the proposal.
+///   * `support`: [`bool`], true to vote for the proposal, false to vote against it.
+///   * `weight`: [`u128`], amount of tokens to vote with.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "governance")]
+#[action(shortname = 0x2e)]
+fn cast_vote(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    proposal_id: u64,
+    support: bool,
+    weight: u128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "cast_vote");
+    let voting_mode = state.proposal(proposal_id).voting_mode;
+    if voting_mode == (VotingMode::Quadratic {}) {
+        state.assert_role(Role::Voter {}, &ctx.sender);
+    }
+    let escrowed = voting_mode == (VotingMode::VoteEscrowed {});
+    let weight = if escrowed {
+        state.ve_weight(&ctx.sender, ctx.block_production_time)
+    } else {
+        weight
+    };
+    assert!(weight > 0, "Vote weight cannot be zero.");
+    let votes = voting_mode.votes(weight);
+    let locked = if escrowed { 0 } else { weight }; // escrowed weight is locked already
+
+    let proposal = state.proposal(proposal_id);
+    assert!(
+        ctx.block_production_time < proposal.voting_ends_at,
+        "Voting has ended."
+    );
+    assert!(
+        !proposal.locked_votes.contains_key(&ctx.sender),
+        "Caller has already voted."
+    );
+    proposal.locked_votes.insert(ctx.sender, locked);
+    proposal.turnout += weight;
+    if support {
+        proposal.votes_for += votes;
+    } else {
+        proposal.votes_against += votes;
+    }
+    state.escrow(&ctx, ctx.sender, locked); // lock the votes
+    state.assert_invariants();
+
+    state
+}
+
+/// Withdraw the tokens caller address voted with on proposal `proposal_id`, once voting has ended.
+///
+/// Panics if voting has not ended or if the caller has no locked votes.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `proposal_id`: [`u64`], id of the proposal.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "governance")]
+#[action(shortname = 0x2f)]
+fn withdraw_votes(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    proposal_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "withdraw_votes");
+    let proposal = state.proposal(proposal_id);
+    assert!(
+        ctx.block_production_time >= proposal.voting_ends_at,
+        "Voting ends at: {}",
+        proposal.voting_ends_at
+    );
+    let weight = proposal
+        .locked_votes
+        .remove(&ctx.sender)
+        .expect("Caller has no locked votes.");
+    state.release(&ctx, ctx.sender, weight);
+    state.assert_invariants();
+
+    state
+}
+
+/// Execute the actions of proposal `proposal_id`. Anyone can call this action once the proposal
+/// has passed and its timelock has expired.
+///
+/// Panics if the proposal did not pass, was already executed, if the timelock has not expired, or
+/// if one of its actions is invalid.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `proposal_id`: [`u64`], id of the proposal.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "governance")]
+#[action(shortname = 0x30)]
+fn execute_proposal(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    proposal_id: u64,
+) -> TashiTokenState {
+    crate::audit::log_admin_action!(state, ctx, "execute_proposal", proposal_id);
+    let proposal = state.proposal(proposal_id);
+    assert!(
+        ctx.block_production_time >= proposal.executable_at,
+        "Proposal can be executed from: {}",
+        proposal.executable_at
+    );
+    assert!(!proposal.executed, "Proposal has already been executed.");
+    assert!(proposal.passed(), "Proposal did not pass.");
+    proposal.executed = true;
+    let actions = proposal.actions.clone();
+    for action in actions {
+        state.apply_proposal_action(&ctx, action);
+    }
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,191 @@
+//! Tests of governance: proposals that pass and execute after the timelock, tokens locked while
+//! voting, the proposal threshold and quorum, and quadratic voting by verified voters.
+
+use super::{
+    cast_vote, execute_proposal, propose, withdraw_votes, ProposalAction, DEFAULT_GOVERNANCE_CONFIG,
+};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state, token_address};
+use crate::token::TashiTokenState;
+
+/// Block production time voting on the first proposal ends at.
+const VOTING_ENDS_AT: i64 = DEFAULT_GOVERNANCE_CONFIG.voting_period;
+
+/// Block production time the first proposal can be executed from.
+const EXECUTABLE_AT: i64 = VOTING_ENDS_AT + DEFAULT_GOVERNANCE_CONFIG.timelock;
+
+/// Tokens held by the treasury, the contract itself.
+const TREASURY: u128 = 10_000;
+
+/// A token where accounts 1 and 2 hold 100000 tokens each, account 3 holds 5000 and the treasury
+/// holds [`TREASURY`], with the default quorum of 40000 and proposal threshold of 10000.
+fn funded_state() -> TashiTokenState {
+    let mut state = new_state();
+    for (receiver, amount) in [
+        (account(1), 100_000),
+        (account(2), 100_000),
+        (account(3), 5_000),
+        (token_address(), TREASURY),
+    ] {
+        state.execute_transfer(
+            &context(account(0)),
+            Transfer::new(account(0), receiver, amount, None),
+        );
+    }
+    state
+}
+
+/// A proposal by account 1 to pay 1000 tokens of the treasury to account 4, as proposal 0.
+fn proposed_state() -> TashiTokenState {
+    proposed_state_from(funded_state())
+}
+
+/// A proposal by account 1 of `state` to pay 1000 tokens of the treasury to account 4, as
+/// proposal 0.
+fn proposed_state_from(state: TashiTokenState) -> TashiTokenState {
+    propose(
+        context(account(1)),
+        state,
+        "Pay account 4".to_string(),
+        vec![ProposalAction::TransferTreasury {
+            receiver: account(4),
+            amount: 1_000,
+        }],
+    )
+}
+
+/// Casts a vote of `weight` from `voter` on proposal 0.
+fn vote(state: TashiTokenState, voter: u8, support: bool, weight: u128) -> TashiTokenState {
+    cast_vote(context(account(voter)), state, 0, support, weight)
+}
+
+#[test]
+fn passed_proposal_is_executed_after_the_timelock() {
+    let state = vote(proposed_state(), 1, true, 50_000);
+    let state = vote(state, 2, false, 20_000);
+    assert!(state.proposals.get(&0).unwrap().passed());
+
+    let state = execute_proposal(context_at(account(9), EXECUTABLE_AT), state, 0);
+    assert!(state.proposals.get(&0).unwrap().executed);
+    assert_eq!(state.balance_of(&account(4)), 1_000);
+    assert_eq!(state.balance_of(&token_address()), TREASURY - 1_000);
+}
+
+#[test]
+fn votes_are_locked_until_voting_ends() {
+    let state = vote(proposed_state(), 1, true, 50_000);
+    assert_eq!(state.balance_of(&account(1)), 50_000);
+    assert_eq!(state.escrowed(), 50_000);
+    state.assert_invariants();
+
+    let state = withdraw_votes(context_at(account(1), VOTING_ENDS_AT), state, 0);
+    assert_eq!(state.balance_of(&account(1)), 100_000);
+    assert_eq!(state.escrowed(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Voting ends at: 259200000")]
+fn votes_cannot_be_withdrawn_while_voting() {
+    let state = vote(proposed_state(), 1, true, 50_000);
+    withdraw_votes(context_at(account(1), VOTING_ENDS_AT - 1), state, 0);
+}
+
+#[test]
+#[should_panic(expected = "Caller has already voted.")]
+fn voter_cannot_vote_twice() {
+    let state = vote(proposed_state(), 1, true, 50_000);
+    vote(state, 1, true, 50_000);
+}
+
+#[test]
+#[should_panic(expected = "Voting has ended.")]
+fn vote_after_voting_ends_is_rejected() {
+    cast_vote(
+        context_at(account(1), VOTING_ENDS_AT),
+        proposed_state(),
+        0,
+        true,
+        50_000,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Insufficient balance: 5000, minimum required balance to propose: 10000")]
+fn proposer_below_the_threshold_is_rejected() {
+    propose(
+        context(account(3)),
+        funded_state(),
+        "Pay account 3".to_string(),
+        vec![ProposalAction::TransferTreasury {
+            receiver: account(3),
+            amount: 1_000,
+        }],
+    );
+}
+
+#[test]
+#[should_panic(expected = "Proposal did not pass.")]
+fn proposal_below_quorum_does_not_pass() {
+    let state = vote(proposed_state(), 1, true, 30_000);
+    execute_proposal(context_at(account(9), EXECUTABLE_AT), state, 0);
+}
+
+#[test]
+#[should_panic(expected = "Proposal did not pass.")]
+fn proposal_with_more_votes_against_does_not_pass() {
+    let state = vote(proposed_state(), 1, true, 50_000);
+    let state = vote(state, 2, false, 50_000);
+    execute_proposal(context_at(account(9), EXECUTABLE_AT), state, 0);
+}
+
+#[test]
+#[should_panic(expected = "Proposal can be executed from: 432000000")]
+fn proposal_cannot_be_executed_during_the_timelock() {
+    let state = vote(proposed_state(), 1, true, 50_000);
+    execute_proposal(context_at(account(9), EXECUTABLE_AT - 1), state, 0);
+}
+
+#[test]
+#[should_panic(expected = "Proposal has already been executed.")]
+fn proposal_cannot_be_executed_twice() {
+    let state = vote(proposed_state(), 1, true, 50_000);
+    let state = execute_proposal(context_at(account(9), EXECUTABLE_AT), state, 0);
+    execute_proposal(context_at(account(9), EXECUTABLE_AT), state, 0);
+}
+
+/// A quadratic voting proposal by account 1, where accounts 1 and 2 are verified voters.
+fn quadratic_state() -> TashiTokenState {
+    let mut state = funded_state();
+    state.governance.voting_mode = VotingMode::Quadratic {};
+    let state = grant_role(admin_context(), state, Role::Voter {}, account(1));
+    let state = grant_role(admin_context(), state, Role::Voter {}, account(2));
+    proposed_state_from(state)
+}
+
+#[test]
+fn quadratic_votes_count_the_square_root_of_the_locked_tokens() {
+    let state = vote(quadratic_state(), 1, true, 40_000);
+    let state = vote(state, 2, false, 10_000);
+
+    let proposal = state.proposals.get(&0).unwrap();
+    assert_eq!(proposal.votes_for, 200);
+    assert_eq!(proposal.votes_against, 100);
+    assert_eq!(proposal.turnout, 50_000);
+    assert!(proposal.passed());
+    assert_eq!(state.balance_of(&account(1)), 60_000);
+}
+
+#[test]
+#[should_panic(expected = "Caller is missing role: Voter")]
+fn quadratic_vote_of_an_unverified_voter_is_rejected() {
+    vote(quadratic_state(), 3, true, 5_000);
+}
+
+#[test]
+fn proposal_keeps_the_voting_mode_it_was_created_with() {
+    let mut state = quadratic_state();
+    state.governance.voting_mode = VotingMode::Linear {};
+    let state = vote(state, 1, true, 40_000);
+
+    assert_eq!(state.proposals.get(&0).unwrap().votes_for, 200);
+}
@@ -0,0 +1,247 @@
+//! Grants released per milestone, for ecosystem funds paying builders as their work is delivered.
+//! The funder commits the whole grant up front, split into milestones. Each milestone is paid to
+//! the recipient once approved, by an account with the [`Role::Reviewer`] role or by a governance
+//! proposal. The funder can claw back the milestones not yet released, while those already
+//! released stay with the recipient.
+#![cfg_attr(not(feature = "milestone-grants"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "milestone-grants")]
+use crate::admin::Role;
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "milestone-grants"))]
+mod tests;
+
+/// A milestone of a grant, as proposed by its funder.
+///
+/// ### Fields:
+///
+///   * `description`: [`String`], human readable description of the deliverable.
+///   * `amount`: [`u128`], amount paid once the milestone is approved.
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub struct MilestoneSpec {
+    pub description: String,
+    pub amount: u128,
+}
+
+/// A milestone of a grant.
+///
+/// ### Fields:
+///
+///   * `description`: [`String`], human readable description of the deliverable.
+///   * `amount`: [`u128`], amount paid once the milestone is approved.
+///   * `released`: [`bool`], whether the milestone was approved and paid to the recipient.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Milestone {
+    pub description: String,
+    pub amount: u128,
+    pub released: bool,
+}
+
+/// Tokens granted to `recipient`, released milestone by milestone.
+///
+/// ### Fields:
+///
+///   * `funder`: [`Address`], account that funded the grant.
+///   * `recipient`: [`Address`], account the milestones are paid to.
+///   * `milestones`: [`Vec`]<[`Milestone`]>, the milestones, in order.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MilestoneGrant {
+    pub funder: Address,
+    pub recipient: Address,
+    pub milestones: Vec<Milestone>,
+}
+
+impl MilestoneGrant {
+    /// Whether every milestone has been released.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`bool`], true if no milestone is left to release.
+    pub fn is_released(&self) -> bool {
+        self.milestones.iter().all(|milestone| milestone.released)
+    }
+}
+
+impl TashiTokenState {
+    /// Releases milestone `milestone` of grant `grant_id`, paying its amount to the recipient. The
+    /// grant is removed once every milestone is released.
+    ///
+    /// Panics if there is no such grant or milestone, or if the milestone was already released.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context.
+    ///   * `grant_id`: [`u64`], id of the grant.
+    ///   * `milestone`: [`u32`], index of the milestone in the grant.
+    pub fn release_milestone(&mut self, ctx: &ContractContext, grant_id: u64, milestone: u32) {
+        let grant = self
+            .milestone_grants
+            .get_mut(&grant_id)
+            .unwrap_or_else(|| panic!("No milestone grant with id: {}", grant_id));
+        let entry = grant
+            .milestones
+            .get_mut(milestone as usize)
+            .unwrap_or_else(|| panic!("No milestone: {}, in grant: {}", milestone, grant_id));
+        assert!(
+            !entry.released,
+            "Milestone is already released: {}",
+            milestone
+        );
+        entry.released = true;
+        let amount = entry.amount;
+        let (funder, recipient) = (grant.funder, grant.recipient);
+        if grant.is_released() {
+            self.milestone_grants.remove(&grant_id);
+        }
+        self.payout(ctx, funder, recipient, amount); // pay out the milestone
+    }
+}
+
+/// Grant tokens from caller address to `recipient`, split into `milestones`. The sum of the
+/// milestones is taken from the caller up front, and each milestone is paid once approved.
+///
+/// Panics if there are no milestones, if a milestone amount is zero, or if there is insufficient
+/// balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `recipient`: [`Address`], account the milestones are paid to.
+///   * `milestones`: [`Vec`]<[`MilestoneSpec`]>, the milestones, in order.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "milestone-grants")]
+#[action(shortname = 0xdf)]
+fn create_milestone_grant(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    recipient: Address,
+    milestones: Vec<MilestoneSpec>,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "create_milestone_grant");
+    assert!(
+        !milestones.is_empty(),
+        "A grant needs at least one milestone."
+    );
+    let mut total: u128 = 0;
+    for milestone in &milestones {
+        assert!(milestone.amount > 0, "Milestone amount cannot be zero.");
+        total = total
+            .checked_add(milestone.amount)
+            .expect("Overflow when adding up milestones.");
+    }
+    state.escrow(&ctx, ctx.sender, total); // escrow the grant
+
+    state.milestone_grants.insert(
+        state.next_milestone_grant_id,
+        MilestoneGrant {
+            funder: ctx.sender,
+            recipient,
+            milestones: milestones
+                .into_iter()
+                .map(
+                    |MilestoneSpec {
+                         description,
+                         amount,
+                     }| Milestone {
+                        description,
+                        amount,
+                        released: false,
+                    },
+                )
+                .collect(),
+        },
+    );
+    state.next_milestone_grant_id += 1;
+    state.assert_invariants();
+
+    state
+}
+
+/// Approve milestone `milestone` of grant `grant_id`, paying its amount to the recipient. Only
+/// accounts with the [`Role::Reviewer`] role can call this action, governance approves milestones
+/// with a proposal instead.
+///
+/// Panics if the caller is not a reviewer, if there is no such grant or milestone, or if the
+/// milestone was already released.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `grant_id`: [`u64`], id of the grant.
+///   * `milestone`: [`u32`], index of the milestone in the grant.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "milestone-grants")]
+#[action(shortname = 0xe0)]
+fn approve_milestone(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    grant_id: u64,
+    milestone: u32,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "approve_milestone");
+    state.assert_role(Role::Reviewer {}, &ctx.sender);
+    state.release_milestone(&ctx, grant_id, milestone);
+    state.assert_invariants();
+
+    state
+}
+
+/// Claw back every milestone of grant `grant_id` not yet released, returning their amounts to the
+/// caller. Released milestones stay with the recipient. Only the funder of the grant can call this
+/// action.
+///
+/// Panics if there is no such grant or if the caller is not its funder.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `grant_id`: [`u64`], id of the grant.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "milestone-grants")]
+#[action(shortname = 0xe1)]
+fn claw_back_milestones(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    grant_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "claw_back_milestones");
+    let grant = state
+        .milestone_grants
+        .remove(&grant_id)
+        .unwrap_or_else(|| panic!("No milestone grant with id: {}", grant_id));
+    assert!(
+        grant.funder == ctx.sender,
+        "Only the funder can claw back the grant."
+    );
+    let returned: u128 = grant
+        .milestones
+        .iter()
+        .filter(|milestone| !milestone.released)
+        .map(|milestone| milestone.amount)
+        .sum();
+    state.release(&ctx, ctx.sender, returned); // return the unreleased milestones
+    state.assert_invariants();
+
+    state
+}
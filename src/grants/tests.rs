@@ -0,0 +1,75 @@
+//! Tests of milestone grants: the grant taken up front, milestones paid once approved by a
+//! reviewer, and clawing back the milestones not yet released.
+
+use super::{approve_milestone, claw_back_milestones, create_milestone_grant, MilestoneSpec};
+use crate::admin::{grant_role, Role};
+use crate::testing::{account, admin_context, context, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token where the owner granted account 1 milestones of 300 and 700, as grant 0, and whose
+/// reviewer is account 9.
+fn granted_state() -> TashiTokenState {
+    let state = grant_role(admin_context(), new_state(), Role::Reviewer {}, account(9));
+    create_milestone_grant(
+        context(account(0)),
+        state,
+        account(1),
+        vec![
+            MilestoneSpec {
+                description: "Prototype".to_string(),
+                amount: 300,
+            },
+            MilestoneSpec {
+                description: "Launch".to_string(),
+                amount: 700,
+            },
+        ],
+    )
+}
+
+#[test]
+fn grant_is_taken_from_the_funder() {
+    let state = granted_state();
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 1_000);
+    assert_eq!(state.milestone_grants.get(&0).unwrap().milestones.len(), 2);
+}
+
+#[test]
+fn approved_milestones_are_paid_and_the_grant_removed() {
+    let state = approve_milestone(context(account(9)), granted_state(), 0, 1);
+    assert_eq!(state.balance_of(&account(1)), 700);
+
+    let state = approve_milestone(context(account(9)), state, 0, 0);
+    assert_eq!(state.balance_of(&account(1)), 1_000);
+    assert!(state.milestone_grants.get(&0).is_none());
+}
+
+#[test]
+#[should_panic(expected = "Milestone is already released: 0")]
+fn milestone_is_released_once() {
+    let state = approve_milestone(context(account(9)), granted_state(), 0, 0);
+    approve_milestone(context(account(9)), state, 0, 0);
+}
+
+#[test]
+#[should_panic(expected = "Caller is missing role: Reviewer")]
+fn only_reviewers_approve() {
+    approve_milestone(context(account(1)), granted_state(), 0, 0);
+}
+
+#[test]
+fn claw_back_returns_the_unreleased_milestones() {
+    let state = approve_milestone(context(account(9)), granted_state(), 0, 0);
+    let state = claw_back_milestones(context(account(0)), state, 0);
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 300);
+    assert_eq!(state.balance_of(&account(1)), 300);
+    assert!(state.milestone_grants.get(&0).is_none());
+}
+
+#[test]
+#[should_panic(expected = "Only the funder can claw back the grant.")]
+fn only_the_funder_claws_back() {
+    claw_back_milestones(context(account(1)), granted_state(), 0);
+}
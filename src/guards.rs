@@ -0,0 +1,165 @@
+//! Validation of transfer and approval destinations, rejecting the zero address, system contracts,
+//! the token contract itself and, when configured, contracts not registered as known receivers.
+#![cfg_attr(not(feature = "address-guards"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::{Address, AddressType};
+use pbc_contract_common::context::ContractContext;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+use crate::hooks::Transfer;
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "address-guards"))]
+mod tests;
+
+/// Which destinations the address guards reject.
+///
+/// ### Fields:
+///
+///   * `reject_system`: [`bool`], reject the zero address and system contracts.
+///   * `reject_self`: [`bool`], reject the token contract itself, except from the owner funding the
+///     treasury.
+///   * `require_known_contracts`: [`bool`], reject transfers to contracts that are not registered
+///     known receivers.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AddressGuards {
+    pub reject_system: bool,
+    pub reject_self: bool,
+    pub require_known_contracts: bool,
+}
+
+/// Address guards of a newly initialized token: the zero address, system contracts and the token
+/// contract itself are rejected, other contracts are allowed.
+pub const DEFAULT_ADDRESS_GUARDS: AddressGuards = AddressGuards {
+    reject_system: true,
+    reject_self: true,
+    require_known_contracts: false,
+};
+
+impl TashiTokenState {
+    /// Checks that the address guards do not reject the receiver of `transfer`.
+    ///
+    /// Panics if the receiver is rejected, or if it is a contract that is not a known receiver
+    /// when known receivers are required.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `transfer`: [`Transfer`], the transfer to check.
+    pub fn assert_valid_receiver(&self, ctx: &ContractContext, transfer: &Transfer) {
+        let to = &transfer.to;
+        if !(transfer.from == self.owner && *to == ctx.contract_address) {
+            self.assert_valid_destination(ctx, to);
+        }
+        let is_contract = matches!(
+            to.address_type,
+            AddressType::PublicContract | AddressType::ZkContract
+        );
+        assert!(
+            !self.address_guards.require_known_contracts
+                || !is_contract
+                || *to == ctx.contract_address
+                || self.known_receivers.contains(to),
+            "Receiver is a contract that is not a known receiver."
+        );
+    }
+
+    /// Checks that the address guards do not reject `destination` as a receiver or spender of
+    /// funds.
+    ///
+    /// Panics if `destination` is rejected.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `destination`: [`Address`], the account to check.
+    pub fn assert_valid_destination(&self, ctx: &ContractContext, destination: &Address) {
+        if !cfg!(feature = "address-guards") {
+            return;
+        }
+        assert!(
+            !self.address_guards.reject_system
+                || (destination.identifier != [0; 20]
+                    && destination.address_type != AddressType::SystemContract),
+            "Destination is the zero address or a system contract."
+        );
+        assert!(
+            !self.address_guards.reject_self || *destination != ctx.contract_address,
+            "Destination is the token contract itself."
+        );
+    }
+}
+
+/// Configure which destinations the address guards reject. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `guards`: [`AddressGuards`], the destinations to reject.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "address-guards")]
+#[action(shortname = 0x3a)]
+fn configure_address_guards(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    guards: AddressGuards,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "configure_address_guards", guards);
+    state.address_guards = guards;
+    state.assert_invariants();
+
+    state
+}
+
+/// Register `receiver` as a contract that can receive transfers, or remove its registration. Only
+/// the owner can call this action.
+///
+/// Panics if the caller is not the owner or if `known` does not change the registration of
+/// `receiver`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `receiver`: [`Address`], contract to register or unregister.
+///   * `known`: [`bool`], true to register `receiver`, false to remove its registration.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "address-guards")]
+#[action(shortname = 0x3b)]
+fn set_known_receiver(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    receiver: Address,
+    known: bool,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_known_receiver", receiver, known);
+    assert!(
+        state.known_receivers.contains(&receiver) != known,
+        "Receiver is already {}.",
+        if known { "known" } else { "unknown" }
+    );
+    if known {
+        state.known_receivers.push(receiver);
+    } else {
+        state
+            .known_receivers
+            .retain(|known_receiver| *known_receiver != receiver);
+    }
+    state.assert_invariants();
+
+    state
+}
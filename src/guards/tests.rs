@@ -0,0 +1,113 @@
+//! Tests of the address guards: system contracts and the token contract rejected as receivers,
+//! the owner funding the treasury, and contracts that must be registered as known receivers.
+
+use pbc_contract_common::address::{Address, AddressType};
+
+use super::{configure_address_guards, set_known_receiver, AddressGuards};
+use crate::allowance::approve;
+use crate::hooks::Transfer;
+use crate::testing::{account, context, contract, new_state, token_address};
+use crate::token::{stored_amount, TashiTokenState};
+
+/// A system contract.
+fn system_contract() -> Address {
+    Address {
+        address_type: AddressType::SystemContract,
+        identifier: [1; 20],
+    }
+}
+
+/// A token where account 1 holds 10_000.
+fn funded_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    state
+}
+
+/// The state of [`funded_state`] only allowing transfers to contracts registered as known
+/// receivers.
+fn known_contracts_state() -> TashiTokenState {
+    configure_address_guards(
+        context(account(0)),
+        funded_state(),
+        AddressGuards {
+            reject_system: true,
+            reject_self: true,
+            require_known_contracts: true,
+        },
+    )
+}
+
+/// Transfers 100 from account 1 to `to`.
+fn send(state: &mut TashiTokenState, to: Address) {
+    state.execute_transfer(
+        &context(account(1)),
+        Transfer::new(account(1), to, 100, None),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Destination is the zero address or a system contract.")]
+fn system_contract_is_rejected() {
+    send(&mut funded_state(), system_contract());
+}
+
+#[test]
+#[should_panic(expected = "Destination is the token contract itself.")]
+fn token_contract_is_rejected() {
+    send(&mut funded_state(), token_address());
+}
+
+#[test]
+fn owner_funds_the_treasury() {
+    let mut state = funded_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), token_address(), 100, None),
+    );
+
+    assert_eq!(state.balance_of(&token_address()), 100);
+}
+
+#[test]
+#[should_panic(expected = "Destination is the zero address or a system contract.")]
+fn system_contract_cannot_be_approved() {
+    approve(
+        context(account(1)),
+        funded_state(),
+        system_contract(),
+        stored_amount(100),
+    );
+}
+
+#[test]
+fn other_contracts_are_allowed_by_default() {
+    let mut state = funded_state();
+    send(&mut state, contract(1));
+
+    assert_eq!(state.balance_of(&contract(1)), 100);
+}
+
+#[test]
+#[should_panic(expected = "Receiver is a contract that is not a known receiver.")]
+fn unknown_contract_is_rejected() {
+    send(&mut known_contracts_state(), contract(1));
+}
+
+#[test]
+fn known_receivers_and_accounts_are_allowed() {
+    let mut state = set_known_receiver(
+        context(account(0)),
+        known_contracts_state(),
+        contract(1),
+        true,
+    );
+    send(&mut state, contract(1));
+    send(&mut state, account(2));
+
+    assert_eq!(state.balance_of(&contract(1)), 100);
+    assert_eq!(state.balance_of(&account(2)), 100);
+}
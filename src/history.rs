@@ -0,0 +1,93 @@
+//! A ring buffer of the latest transfers, letting light clients and support staff inspect recent
+//! activity without an indexer. It holds at most [`TRANSFER_HISTORY_CAPACITY`] transfers, each new
+//! transfer overwriting the oldest one, so the state does not grow with the number of transfers.
+#![cfg_attr(not(feature = "transfer-history"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "transfer-history")]
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::TashiTokenState;
+#[cfg(feature = "transfer-history")]
+use crate::views::return_value;
+
+/// The number of transfers the history keeps.
+pub const TRANSFER_HISTORY_CAPACITY: usize = 128;
+
+/// A transfer recorded in the history.
+///
+/// ### Fields:
+///
+///   * `from`: [`Address`], sender of the transfer.
+///   * `to`: [`Address`], receiver of the transfer.
+///   * `amount`: [`u128`], amount taken from the sender.
+///   * `block_time`: [`i64`], block time of the transfer.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransferRecord {
+    pub from: Address,
+    pub to: Address,
+    pub amount: u128,
+    pub block_time: i64,
+}
+
+/// Records every transfer in the history.
+pub struct TransferHistory;
+
+impl TransferHook for TransferHistory {
+    fn after_transfer(ctx: &ContractContext, state: &mut TashiTokenState, transfer: &Transfer) {
+        let record = TransferRecord {
+            from: transfer.from,
+            to: transfer.to,
+            amount: transfer.amount,
+            block_time: ctx.block_time,
+        };
+        if state.transfer_history.len() < TRANSFER_HISTORY_CAPACITY {
+            state.transfer_history.push(record);
+        } else {
+            state.transfer_history[state.transfer_history_head as usize] = record;
+        }
+        state.transfer_history_head =
+            (state.transfer_history_head + 1) % TRANSFER_HISTORY_CAPACITY as u32;
+    }
+}
+
+impl TashiTokenState {
+    /// Gets the transfers of the history, oldest first.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`Vec`]<[`TransferRecord`]> of the latest transfers.
+    pub fn recent_transfers(&self) -> Vec<TransferRecord> {
+        let head = self.transfer_history_head as usize % self.transfer_history.len().max(1);
+        let (newest, oldest) = self.transfer_history.split_at(head);
+        oldest.iter().chain(newest).cloned().collect()
+    }
+}
+
+/// Return the latest transfers, oldest first, to the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the transfers as a [`Vec`]<[`TransferRecord`]> in the
+/// return data.
+#[cfg(feature = "transfer-history")]
+#[action(shortname = 0xbe)]
+fn query_recent_transfers(
+    _ctx: ContractContext,
+    state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let events = return_value(state.recent_transfers());
+    state.assert_invariants();
+    (state, events)
+}
@@ -0,0 +1,220 @@
+//! The transfer pipeline extension point. Optional behaviors plug into every transfer by
+//! implementing [`TransferHook`] and registering in the `run_*_transfer_hooks` functions.
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+
+#[cfg(feature = "burnable")]
+use crate::burnable::BurnOnTransfer;
+#[cfg(feature = "cooldown")]
+use crate::cooldown::TransferCooldown;
+#[cfg(feature = "cosigner")]
+use crate::cosigner::CoSignerGuard;
+#[cfg(feature = "demurrage")]
+use crate::demurrage::DemurrageAccrual;
+#[cfg(feature = "dormancy")]
+use crate::dormancy::DormancyTracker;
+#[cfg(feature = "enforcement")]
+use crate::enforcement::FreezeGuard;
+#[cfg(feature = "external-compliance")]
+use crate::external_compliance::ExternalComplianceGuard;
+#[cfg(feature = "transfer-history")]
+use crate::history::TransferHistory;
+#[cfg(feature = "lockbox")]
+use crate::lockbox::LockboxGuard;
+#[cfg(feature = "usage-metrics")]
+use crate::metrics::UsageMetricsRecorder;
+#[cfg(feature = "nonces")]
+use crate::nonces::TransferNonces;
+#[cfg(feature = "oracle-fees")]
+use crate::oracle_fees::ReferenceFees;
+use crate::policy::TransferPolicy;
+#[cfg(feature = "referrals")]
+use crate::referrals::ReferralBonus;
+#[cfg(feature = "reversible-transfers")]
+use crate::reversible::ReversibleHold;
+#[cfg(feature = "round-up")]
+use crate::round_up::CharityRoundUp;
+#[cfg(feature = "soulbound")]
+use crate::soulbound::SoulboundGuard;
+use crate::token::TashiTokenState;
+
+/// A transfer of tokens being executed by the contract.
+///
+/// ### Fields:
+///
+///   * `from`: [`Address`], account the funds belong to.
+///   * `to`: [`Address`], account receiving the funds.
+///   * `amount`: [`u128`], amount taken from `from`.
+///   * `received`: [`u128`], amount credited to `to`, `amount` less whatever hooks took.
+///   * `spender`: [`Option`]<[`Address`]>, account spending its allowance from `from`, if any.
+///   * `compliance_approved`: [`bool`], whether the external compliance contract approved the
+///     transfer, with the `external-compliance` feature.
+///   * `cosigned`: [`bool`], whether the co-signer of `from` confirmed the transfer, with the
+///     `cosigner` feature.
+///   * `nonce_consumed`: [`bool`], whether the transfer was authorized by a signed message that
+///     already consumed the nonce of `from`, with the `nonces` feature.
+pub struct Transfer {
+    pub from: Address,
+    pub to: Address,
+    pub amount: u128,
+    pub received: u128,
+    pub spender: Option<Address>,
+    #[cfg(feature = "external-compliance")]
+    pub compliance_approved: bool,
+    #[cfg(feature = "cosigner")]
+    pub cosigned: bool,
+    #[cfg(feature = "nonces")]
+    pub nonce_consumed: bool,
+}
+
+impl Transfer {
+    /// Creates a transfer of `amount` tokens where the receiver is credited the full amount.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `from`: [`Address`], account the funds belong to.
+    ///   * `to`: [`Address`], account receiving the funds.
+    ///   * `amount`: [`u128`], amount to transfer.
+    ///   * `spender`: [`Option`]<[`Address`]>, account spending its allowance from `from`, if any.
+    ///
+    /// ### Returns:
+    ///
+    /// The new [`Transfer`].
+    pub fn new(from: Address, to: Address, amount: u128, spender: Option<Address>) -> Transfer {
+        Transfer {
+            from,
+            to,
+            amount,
+            received: amount,
+            spender,
+            #[cfg(feature = "external-compliance")]
+            compliance_approved: false,
+            #[cfg(feature = "cosigner")]
+            cosigned: false,
+            #[cfg(feature = "nonces")]
+            nonce_consumed: false,
+        }
+    }
+
+    /// Takes a share of the transfer away from the receiver. The hook taking the share is
+    /// responsible for accounting for it, e.g. by burning it or crediting it elsewhere.
+    ///
+    /// Panics if `share` is greater than what the receiver is still credited.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `share`: [`u128`], amount to take from the receiver.
+    #[cfg_attr(
+        not(any(feature = "burnable", feature = "reversible-transfers")),
+        allow(dead_code)
+    )]
+    pub fn take(&mut self, share: u128) {
+        self.received = self
+            .received
+            .checked_sub(share)
+            .expect("Hooks took more than the transferred amount.");
+    }
+}
+
+/// An extension of the transfer path. Hooks only implement the policy they add, the balance math
+/// is done once by [`TashiTokenState::execute_transfer`].
+///
+/// Both functions may panic to reject the transfer.
+pub trait TransferHook {
+    /// Called before any balance is updated. May take a share of the transfer with
+    /// [`Transfer::take`].
+    fn before_transfer(
+        _ctx: &ContractContext,
+        _state: &mut TashiTokenState,
+        _transfer: &mut Transfer,
+    ) {
+    }
+
+    /// Called after the balances of the transfer have been updated.
+    fn after_transfer(_ctx: &ContractContext, _state: &mut TashiTokenState, _transfer: &Transfer) {}
+}
+
+/// Runs the `before_transfer` function of every registered [`TransferHook`] in order.
+#[allow(unused_variables)]
+pub fn run_before_transfer_hooks(
+    ctx: &ContractContext,
+    state: &mut TashiTokenState,
+    transfer: &mut Transfer,
+) {
+    #[cfg(feature = "demurrage")]
+    DemurrageAccrual::before_transfer(ctx, state, transfer);
+    TransferPolicy::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "enforcement")]
+    FreezeGuard::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "oracle-fees")]
+    ReferenceFees::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "cooldown")]
+    TransferCooldown::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "burnable")]
+    BurnOnTransfer::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "referrals")]
+    ReferralBonus::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "dormancy")]
+    DormancyTracker::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "round-up")]
+    CharityRoundUp::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "transfer-history")]
+    TransferHistory::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "nonces")]
+    TransferNonces::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "reversible-transfers")]
+    ReversibleHold::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "external-compliance")]
+    ExternalComplianceGuard::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "soulbound")]
+    SoulboundGuard::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "lockbox")]
+    LockboxGuard::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "cosigner")]
+    CoSignerGuard::before_transfer(ctx, state, transfer);
+    #[cfg(feature = "usage-metrics")]
+    UsageMetricsRecorder::before_transfer(ctx, state, transfer);
+}
+
+/// Runs the `after_transfer` function of every registered [`TransferHook`] in order.
+#[allow(unused_variables)]
+pub fn run_after_transfer_hooks(
+    ctx: &ContractContext,
+    state: &mut TashiTokenState,
+    transfer: &Transfer,
+) {
+    #[cfg(feature = "demurrage")]
+    DemurrageAccrual::after_transfer(ctx, state, transfer);
+    TransferPolicy::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "enforcement")]
+    FreezeGuard::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "oracle-fees")]
+    ReferenceFees::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "cooldown")]
+    TransferCooldown::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "burnable")]
+    BurnOnTransfer::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "referrals")]
+    ReferralBonus::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "dormancy")]
+    DormancyTracker::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "round-up")]
+    CharityRoundUp::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "transfer-history")]
+    TransferHistory::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "nonces")]
+    TransferNonces::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "reversible-transfers")]
+    ReversibleHold::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "external-compliance")]
+    ExternalComplianceGuard::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "soulbound")]
+    SoulboundGuard::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "lockbox")]
+    LockboxGuard::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "cosigner")]
+    CoSignerGuard::after_transfer(ctx, state, transfer);
+    #[cfg(feature = "usage-metrics")]
+    UsageMetricsRecorder::after_transfer(ctx, state, transfer);
+}
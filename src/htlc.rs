@@ -0,0 +1,164 @@
+//! Hash time locked transfers, enabling trustless swaps against assets on other chains. Locked
+//! funds go to the receiver when the preimage of the hashlock is revealed before the timelock, and
+//! back to the sender otherwise.
+#![cfg_attr(not(feature = "htlc"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "htlc")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "htlc")]
+use pbc_contract_common::Hash;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "htlc")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "htlc"))]
+mod tests;
+
+/// Funds locked until the preimage of a hashlock is revealed or the timelock expires.
+///
+/// ### Fields:
+///
+///   * `sender`: [`Address`], account that locked the funds.
+///   * `receiver`: [`Address`], account receiving the funds when the preimage is revealed.
+///   * `amount`: [`u128`], amount locked.
+///   * `timelock`: [`i64`], block production time after which the sender can refund, in
+///     milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Htlc {
+    pub sender: Address,
+    pub receiver: Address,
+    pub amount: u128,
+    pub timelock: i64,
+}
+
+/// Lock `amount` tokens from caller address for `receiver` under `hashlock`.
+///
+/// Panics if `amount` is zero, if the timelock has already passed, if `hashlock` is already in
+/// use, or if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `receiver`: [`Address`], account receiving the funds when the preimage is revealed.
+///   * `amount`: [`u128`], amount to lock.
+///   * `hashlock`: [`Hash`], hash of the secret preimage.
+///   * `timelock`: [`i64`], block production time after which the caller can refund, in
+///     milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "htlc")]
+#[action(shortname = 0x25)]
+pub(crate) fn lock_htlc(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    receiver: Address,
+    amount: u128,
+    hashlock: Hash,
+    timelock: i64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "lock_htlc");
+    assert!(amount > 0, "Locked amount cannot be zero.");
+    assert!(
+        timelock > ctx.block_production_time,
+        "Timelock has already passed."
+    );
+    assert!(
+        !state.htlcs.contains_key(&hashlock),
+        "Hashlock is already in use."
+    );
+    state.escrow(&ctx, ctx.sender, amount); // lock the funds
+
+    state.htlcs.insert(
+        hashlock,
+        Htlc {
+            sender: ctx.sender,
+            receiver,
+            amount,
+            timelock,
+        },
+    );
+    state.assert_invariants();
+
+    state
+}
+
+/// Claim the funds locked under the hash of `preimage` for their receiver. Anyone can call this
+/// action, the funds always go to the receiver.
+///
+/// Panics if no funds are locked under the hash of `preimage` or if the timelock has passed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `preimage`: [`Vec`]<[`u8`]>, the secret the funds are locked under.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "htlc")]
+#[action(shortname = 0x26)]
+pub(crate) fn claim_htlc(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    preimage: Vec<u8>,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "claim_htlc");
+    let hashlock = Hash::digest(preimage);
+    let htlc = state
+        .htlcs
+        .remove(&hashlock)
+        .expect("No funds are locked under the preimage.");
+    assert!(
+        ctx.block_production_time < htlc.timelock,
+        "Timelock has passed."
+    );
+    state.payout(&ctx, htlc.sender, htlc.receiver, htlc.amount);
+    state.assert_invariants();
+
+    state
+}
+
+/// Refund the funds locked under `hashlock` to their sender, once the timelock has passed. Anyone
+/// can call this action, the funds always go to the sender.
+///
+/// Panics if no funds are locked under `hashlock` or if the timelock has not passed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `hashlock`: [`Hash`], hash the funds are locked under.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "htlc")]
+#[action(shortname = 0x27)]
+pub(crate) fn refund_htlc(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    hashlock: Hash,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "refund_htlc");
+    let htlc = state
+        .htlcs
+        .remove(&hashlock)
+        .expect("No funds are locked under the hashlock.");
+    assert!(
+        ctx.block_production_time >= htlc.timelock,
+        "Timelock ends at: {}",
+        htlc.timelock
+    );
+    state.release(&ctx, htlc.sender, htlc.amount);
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,144 @@
+//! Tests of hash time locked transfers: claims with the preimage before the timelock, refunds
+//! after it, and the transfer policies checked when funds are locked and claimed.
+
+use pbc_contract_common::Hash;
+
+use super::{claim_htlc, lock_htlc, refund_htlc};
+use crate::testing::{account, context, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// Block production time the transfers of the tests are locked until.
+const TIMELOCK: i64 = 1_000;
+
+/// The secret the transfers of the tests are locked under.
+fn preimage() -> Vec<u8> {
+    b"swap secret".to_vec()
+}
+
+/// A token where the owner locked 500 tokens for account 1 under the hash of [`preimage`].
+fn locked_state() -> TashiTokenState {
+    lock_htlc(
+        context(account(0)),
+        new_state(),
+        account(1),
+        500,
+        Hash::digest(preimage()),
+        TIMELOCK,
+    )
+}
+
+#[test]
+fn lock_escrows_the_funds() {
+    let state = locked_state();
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 500);
+    assert_eq!(state.htlcs.len(), 1);
+    state.assert_invariants();
+}
+
+#[test]
+fn preimage_pays_the_receiver() {
+    // anyone can reveal the preimage, the funds go to the receiver
+    let state = claim_htlc(
+        context_at(account(2), TIMELOCK - 1),
+        locked_state(),
+        preimage(),
+    );
+
+    assert!(state.htlcs.is_empty());
+    assert_eq!(state.balance_of(&account(1)), 500);
+    assert_eq!(state.balance_of(&account(2)), 0);
+}
+
+#[test]
+#[should_panic(expected = "Timelock has passed.")]
+fn preimage_after_the_timelock_is_rejected() {
+    claim_htlc(context_at(account(1), TIMELOCK), locked_state(), preimage());
+}
+
+#[test]
+#[should_panic(expected = "No funds are locked under the preimage.")]
+fn wrong_preimage_is_rejected() {
+    claim_htlc(context(account(1)), locked_state(), b"guess".to_vec());
+}
+
+#[test]
+fn sender_is_refunded_after_the_timelock() {
+    let state = refund_htlc(
+        context_at(account(2), TIMELOCK),
+        locked_state(),
+        Hash::digest(preimage()),
+    );
+
+    assert!(state.htlcs.is_empty());
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY);
+}
+
+#[test]
+#[should_panic(expected = "Timelock ends at: 1000")]
+fn refund_before_the_timelock_is_rejected() {
+    refund_htlc(
+        context_at(account(0), TIMELOCK - 1),
+        locked_state(),
+        Hash::digest(preimage()),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Hashlock is already in use.")]
+fn hashlock_cannot_be_reused_while_locked() {
+    lock_htlc(
+        context(account(0)),
+        locked_state(),
+        account(2),
+        100,
+        Hash::digest(preimage()),
+        TIMELOCK,
+    );
+}
+
+#[test]
+#[cfg(feature = "launch-guard")]
+#[should_panic(expected = "Trading is not enabled yet.")]
+fn holders_cannot_lock_funds_before_the_launch() {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        crate::hooks::Transfer::new(account(0), account(1), 1_000, None),
+    );
+    state.trading_enabled = false;
+
+    lock_htlc(
+        context(account(1)),
+        state,
+        account(2),
+        500,
+        Hash::digest(preimage()),
+        TIMELOCK,
+    );
+}
+
+#[test]
+#[cfg(feature = "pausable")]
+#[should_panic(expected = "Transfers are paused in an emergency until: 1000")]
+fn claim_is_rejected_during_an_emergency_pause() {
+    let mut state = locked_state();
+    state.emergency_paused_until = TIMELOCK;
+
+    claim_htlc(context_at(account(1), TIMELOCK - 1), state, preimage());
+}
+
+#[test]
+#[cfg(feature = "compliance")]
+#[should_panic(expected = "Transfer breaks compliance rule: allowlist")]
+fn claim_to_a_receiver_outside_the_allowlist_is_rejected() {
+    let mut state = locked_state();
+    state.compliance_rules.insert(
+        "allowlist".to_string(),
+        crate::compliance::ComplianceRule::Allowlist {
+            accounts: vec![account(2)],
+        },
+    );
+
+    claim_htlc(context_at(account(1), TIMELOCK - 1), state, preimage());
+}
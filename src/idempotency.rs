@@ -0,0 +1,120 @@
+//! Idempotency keys for transfers, so payment backends can safely retry a transfer after a timeout.
+//! A transfer sent with a client-supplied key is recorded under that key for its sender, and a
+//! later transfer with the same key is not executed again but returns the recorded transfer. Each
+//! sender keeps its latest [`MAX_IDEMPOTENCY_KEYS`] keys, older keys are dropped and can no longer
+//! be recognized.
+#![cfg_attr(not(feature = "idempotency"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "idempotency")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "idempotency")]
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "idempotency")]
+use crate::hooks::Transfer;
+#[cfg(feature = "idempotency")]
+use crate::token::TashiTokenState;
+#[cfg(feature = "idempotency")]
+use crate::views::return_value;
+
+#[cfg(all(test, feature = "idempotency"))]
+mod tests;
+
+/// The number of idempotency keys kept for each sender.
+pub const MAX_IDEMPOTENCY_KEYS: usize = 64;
+
+/// A transfer recorded under its idempotency key.
+///
+/// ### Fields:
+///
+///   * `key`: [`u128`], the idempotency key, e.g. a UUID.
+///   * `to`: [`Address`], receiver of the transfer.
+///   * `amount`: [`u128`], amount transferred.
+///   * `timestamp`: [`i64`], block production time of the transfer, in milliseconds.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdempotentTransfer {
+    pub key: u128,
+    pub to: Address,
+    pub amount: u128,
+    pub timestamp: i64,
+}
+
+/// Transfer `amount` tokens to address `to` from caller address, unless the caller already sent a
+/// transfer with `key`. Otherwise handled as by `transfer`. Without a key the transfer is always
+/// executed.
+///
+/// Panics if `key` was used for a transfer to another receiver or of another amount, or if there is
+/// insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`u128`], amount to transfer.
+///   * `key`: [`Option`]<[`u128`]>, the idempotency key of the transfer, if any.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state, the transfer as an [`IdempotentTransfer`] in the return data,
+/// the original one for a repeated key, and the supply notifications.
+#[cfg(feature = "idempotency")]
+#[action(shortname = 0xfd)]
+fn transfer_idempotent(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    to: Address,
+    amount: u128,
+    key: Option<u128>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "transfer_idempotent");
+    let record = IdempotentTransfer {
+        key: key.unwrap_or(0),
+        to,
+        amount,
+        timestamp: ctx.block_production_time,
+    };
+    let original = key.and_then(|key| {
+        state
+            .idempotency_keys
+            .get(&ctx.sender)
+            .and_then(|records| records.iter().find(|record| record.key == key))
+            .cloned()
+    });
+    if let Some(original) = original {
+        assert!(
+            original.to == to && original.amount == amount,
+            "Idempotency key: {}, was used for another transfer.",
+            original.key
+        );
+        let events = return_value(original);
+        state.assert_invariants();
+
+        return (state, events);
+    }
+
+    let supply = state.supply();
+    state.execute_transfer(&ctx, Transfer::new(ctx.sender, to, amount, None));
+    if key.is_some() {
+        let mut records = state
+            .idempotency_keys
+            .remove(&ctx.sender)
+            .unwrap_or_default();
+        if records.len() >= MAX_IDEMPOTENCY_KEYS {
+            records.remove(0);
+        }
+        records.push(record.clone());
+        state.idempotency_keys.insert(ctx.sender, records);
+    }
+
+    let mut events = return_value(record);
+    events.extend(state.supply_change_events(supply));
+    state.assert_invariants();
+
+    (state, events)
+}
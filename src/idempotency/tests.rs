@@ -0,0 +1,58 @@
+//! Tests of idempotency keys: a retried transfer is executed once, a key cannot be reused for
+//! another transfer, and the oldest keys are dropped.
+
+use super::{transfer_idempotent, MAX_IDEMPOTENCY_KEYS};
+use crate::testing::{account, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// Transfers `amount` from the owner to account 1 with `key` at block production time `time`.
+fn send(state: TashiTokenState, amount: u128, key: Option<u128>, time: i64) -> TashiTokenState {
+    transfer_idempotent(context_at(account(0), time), state, account(1), amount, key).0
+}
+
+#[test]
+fn retried_transfer_is_executed_once() {
+    let state = send(new_state(), 500, Some(7), 1_000);
+    let state = send(state, 500, Some(7), 2_000);
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 500);
+    assert_eq!(state.balance_of(&account(1)), 500);
+    let records = state.idempotency_keys.get(&account(0)).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].timestamp, 1_000);
+}
+
+#[test]
+fn transfers_without_a_key_are_always_executed() {
+    let state = send(new_state(), 500, None, 1_000);
+    let state = send(state, 500, None, 2_000);
+
+    assert_eq!(state.balance_of(&account(1)), 1_000);
+    assert!(state.idempotency_keys.get(&account(0)).is_none());
+}
+
+#[test]
+#[should_panic(expected = "Idempotency key: 7, was used for another transfer.")]
+fn key_cannot_be_reused_for_another_amount() {
+    let state = send(new_state(), 500, Some(7), 1_000);
+    send(state, 600, Some(7), 2_000);
+}
+
+#[test]
+fn oldest_key_is_dropped() {
+    let mut state = new_state();
+    for key in 0..=MAX_IDEMPOTENCY_KEYS as u128 {
+        state = send(state, 1, Some(key), 0);
+    }
+    // the dropped key is no longer recognized, so the retry is executed again
+    let state = send(state, 1, Some(0), 0);
+
+    assert_eq!(
+        state.idempotency_keys.get(&account(0)).unwrap().len(),
+        MAX_IDEMPOTENCY_KEYS
+    );
+    assert_eq!(
+        state.balance_of(&account(1)),
+        MAX_IDEMPOTENCY_KEYS as u128 + 2
+    );
+}
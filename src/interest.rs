@@ -0,0 +1,135 @@
+//! Interest bearing balances. With the `interest` feature, `balances` and `total_supply` hold shares
+//! which are scaled by a global interest index, so every balance grows when the index is raised
+//! without touching any account. Tokens escrowed by the contract outside of balances are token
+//! amounts and do not accrue: whenever the index changes, the shares they are worth in
+//! `total_supply` are converted at the new index.
+#![cfg_attr(not(feature = "interest"), allow(dead_code))]
+
+#[cfg(feature = "interest")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "interest")]
+use pbc_contract_common::events::EventGroup;
+
+#[cfg(feature = "interest")]
+use crate::admin::Role;
+use crate::token::{
+    amount_value, mul_div, mul_div_ceil, mul_div_wide, stored_amount, TashiTokenState,
+};
+
+#[cfg(all(test, feature = "interest"))]
+mod tests;
+
+/// The interest index representing a balance equal to its shares.
+pub const INDEX_SCALE: u128 = 1_000_000_000_000_000_000;
+
+impl TashiTokenState {
+    /// Gets the amount of tokens `shares` are worth at the current interest index.
+    ///
+    /// Panics if the calculation overflows.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `shares`: [`u128`], shares to convert.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount of tokens.
+    pub fn amount_of_shares(&self, shares: u128) -> u128 {
+        if !cfg!(feature = "interest") {
+            return shares;
+        }
+        mul_div_wide(shares, self.interest_index, INDEX_SCALE)
+    }
+
+    /// Gets the shares worth `amount` tokens at the current interest index, rounded down. Used
+    /// when crediting so rounding never creates tokens.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `amount`: [`u128`], amount of tokens to convert.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount of shares.
+    pub fn shares_rounded_down(&self, amount: u128) -> u128 {
+        if !cfg!(feature = "interest") {
+            return amount;
+        }
+        mul_div(amount, INDEX_SCALE, self.interest_index)
+    }
+
+    /// Gets the shares worth `amount` tokens at the current interest index, rounded up. Used
+    /// when debiting so rounding never creates tokens.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `amount`: [`u128`], amount of tokens to convert.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount of shares.
+    pub fn shares_rounded_up(&self, amount: u128) -> u128 {
+        if !cfg!(feature = "interest") {
+            return amount;
+        }
+        mul_div_ceil(amount, INDEX_SCALE, self.interest_index)
+    }
+
+    /// Sets the interest index to `new_index`, converting the shares the escrowed tokens are worth
+    /// in `total_supply` at the new index, so only balances accrue.
+    ///
+    /// Panics if the converted total supply overflows.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `new_index`: [`u128`], the new interest index, scaled by 10^18.
+    pub fn set_interest_index(&mut self, new_index: u128) {
+        let escrowed = self.escrowed();
+        let shares_before = self.shares_rounded_down(escrowed);
+        self.interest_index = new_index;
+        let shares_after = self.shares_rounded_down(escrowed);
+        let total_supply = (amount_value(self.total_supply) - shares_before)
+            .checked_add(shares_after)
+            .expect("Overflow when adding to total supply.");
+        self.total_supply = stored_amount(total_supply);
+    }
+}
+
+/// Raise the interest index to `new_index`, growing every balance by `new_index / interest_index`.
+/// Escrowed tokens do not grow.
+/// Only accounts with the [`Role::RateSetter`] role can call this action.
+///
+/// Panics if the caller does not have the role or if `new_index` is below the current index.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `new_index`: [`u128`], the new interest index, scaled by 10^18.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "interest")]
+#[action(shortname = 0x2a)]
+fn update_interest_index(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    new_index: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    state.assert_role(Role::RateSetter {}, &ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "update_interest_index", new_index);
+    assert!(
+        new_index >= state.interest_index,
+        "Interest index: {}, cannot decrease to: {}",
+        state.interest_index,
+        new_index
+    );
+    let supply = state.supply();
+    state.set_interest_index(new_index);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
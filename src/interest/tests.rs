@@ -0,0 +1,73 @@
+//! Tests of interest bearing balances: raising the index, escrowed tokens which do not accrue, and
+//! converting shares at large indexes.
+
+use super::{update_interest_index, INDEX_SCALE};
+use crate::admin::{grant_role, Role};
+use crate::testing::{account, admin_context, context, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token whose interest index is raised to `new_index` by account 9, the rate setter, after the
+/// owner moved `escrowed` into the payroll pool.
+fn accrued_state(escrowed: u128, new_index: u128) -> TashiTokenState {
+    let mut state = grant_role(
+        admin_context(),
+        new_state(),
+        Role::RateSetter {},
+        account(9),
+    );
+    state.debit(account(0), escrowed);
+    state.payroll_pool += escrowed;
+    let (state, _) = update_interest_index(context(account(9)), state, new_index);
+    state
+}
+
+#[test]
+fn raising_the_index_grows_every_balance() {
+    let state = accrued_state(0, 2 * INDEX_SCALE);
+
+    assert_eq!(state.balance_of(&account(0)), 2 * INITIAL_SUPPLY);
+    assert_eq!(state.supply(), 2 * INITIAL_SUPPLY);
+}
+
+#[test]
+fn escrowed_tokens_do_not_accrue() {
+    let mut state = accrued_state(100_000, 2 * INDEX_SCALE);
+
+    assert_eq!(state.balance_of(&account(0)), 1_800_000);
+    assert_eq!(state.supply(), 1_900_000);
+    state.assert_invariants();
+
+    // releasing the escrow credits its tokens at the new index, leaving the supply unchanged
+    state.payroll_pool -= 100_000;
+    state.credit(account(1), 100_000);
+    assert_eq!(state.balance_of(&account(1)), 100_000);
+    assert_eq!(state.supply(), 1_900_000);
+    state.assert_invariants();
+}
+
+#[test]
+#[should_panic(expected = "Interest index: 2000000000000000000, cannot decrease to: 1")]
+fn index_cannot_decrease() {
+    let state = accrued_state(0, 2 * INDEX_SCALE);
+    update_interest_index(context(account(9)), state, 1);
+}
+
+#[test]
+fn shares_convert_at_large_indexes() {
+    let mut state = new_state();
+    state.interest_index = 1_000 * INDEX_SCALE;
+
+    let shares = 1_000_000_000_000 * INDEX_SCALE + (INDEX_SCALE - 1);
+    assert_eq!(
+        state.amount_of_shares(shares),
+        1_000_000_000_000_000 * INDEX_SCALE + 999
+    );
+}
+
+#[test]
+#[should_panic(expected = "Overflow when scaling amount.")]
+fn converting_shares_beyond_the_largest_amount_panics() {
+    let mut state = new_state();
+    state.interest_index = 2 * INDEX_SCALE;
+    state.amount_of_shares(u128::MAX);
+}
@@ -0,0 +1,232 @@
+//! Strict invariant checks, run at the end of every action, callback and initialization with the
+//! `strict-invariants` feature. The check re-sums the balances against the total supply and
+//! validates that no zero entries or empty inner maps are left in the amount maps, panicking with
+//! the violated invariant. It is meant for test builds, where it turns silent bookkeeping drift
+//! into a failed transaction, and does nothing without the feature.
+//!
+//! Tokens escrowed by a feature, such as streams, channels, a sale, time locks, governance votes,
+//! funded pools, conditional transfers, auction bids, raffle pots, vote-escrow locks, the recovery
+//! pool of dormant accounts, pending clawbacks, the liquidity pool, pending two-phase transfers,
+//! held reversible transfers, vesting grants, milestone grants, organization accounts or the
+//! lending pool and its collateral, are held outside of `balances` and summed by
+//! [`TashiTokenState::escrowed`]. The balances and the escrowed tokens must add up exactly to the
+//! total supply. Balances accruing interest are shares and lose rounding dust on transfers, so with
+//! the `interest` feature they must add up to at most the total supply, the escrowed tokens
+//! converted to shares. The balances of every other token of a multi-token contract must add up
+//! exactly to its total supply, and the wrapped supply of a wrapper must be backed by its wrap
+//! reserve.
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+
+use crate::clawback::ClawbackStatus;
+use crate::raffles::RaffleStatus;
+use crate::reversible::ReversibleStatus;
+use crate::shards::BalanceEntries;
+use crate::token::{amount_value, Amount, TashiTokenState};
+
+#[cfg(test)]
+mod tests;
+
+/// Whether balances are shares accruing interest, which lose rounding dust on transfers.
+const BALANCES_ACCRUE_INTEREST: bool = cfg!(feature = "interest");
+
+/// Panics if a map of amounts by owner and address has an empty inner map or a zero amount.
+///
+/// ### Parameters:
+///
+///   * `name`: [`str`], name of the map in the state.
+///   * `map`: [`SortedVecMap`]<[`Address`], [`SortedVecMap`]<[`Address`], `V`>>, the map.
+///   * `value`: function converting an entry of the map to its amount.
+fn assert_nested_hygiene<V>(
+    name: &str,
+    map: &SortedVecMap<Address, SortedVecMap<Address, V>>,
+    value: impl Fn(&V) -> u128,
+) {
+    for (owner, inner) in map.iter() {
+        assert!(
+            !inner.is_empty(),
+            "Invariant violated: empty entry of {:?} in {}",
+            owner,
+            name
+        );
+        for (key, entry) in inner.iter() {
+            assert!(
+                value(entry) > 0,
+                "Invariant violated: zero entry of {:?} for {:?} in {}",
+                owner,
+                key,
+                name
+            );
+        }
+    }
+}
+
+impl TashiTokenState {
+    /// Gets the tokens escrowed by every feature, held by the contract outside of `balances` on
+    /// behalf of accounts.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u128`] amount escrowed.
+    pub fn escrowed(&self) -> u128 {
+        let streams = self
+            .streams
+            .iter()
+            .map(|(_, stream)| stream.deposit - stream.withdrawn);
+        let channels = self.channels.iter().map(|(_, channel)| channel.deposit);
+        let sale = self.sale.iter().map(|sale| sale.locked);
+        let htlcs = self.htlcs.iter().map(|(_, htlc)| htlc.amount);
+        let votes = self
+            .proposals
+            .iter()
+            .flat_map(|(_, proposal)| proposal.locked_votes.iter().map(|(_, votes)| *votes));
+        let pools = [
+            self.rewards.balance,
+            self.referral_pool,
+            self.payroll_pool,
+            self.pending_conditional_transfers,
+            self.lending_pool.cash,
+        ];
+        let bids = self
+            .auctions
+            .iter()
+            .flat_map(|(_, auction)| auction.bids.iter().map(|(_, bid)| bid.deposit));
+        let pots = self
+            .raffles
+            .iter()
+            .filter(|(_, raffle)| raffle.status == RaffleStatus::Open {})
+            .map(|(_, raffle)| raffle.pot);
+        let locks = self.ve_locks.iter().map(|(_, lock)| lock.amount);
+        let escheated = self.escheated.iter().map(|(_, amount)| *amount);
+        let clawbacks = self
+            .clawbacks
+            .iter()
+            .filter(|(_, clawback)| {
+                matches!(
+                    clawback.status,
+                    ClawbackStatus::Pending {} | ClawbackStatus::Challenged {}
+                )
+            })
+            .map(|(_, clawback)| clawback.amount);
+        let pool = self
+            .amm_pool
+            .iter()
+            .map(|pool| pool.token_reserve + pool.pending_tokens);
+        let pending = self
+            .pending_transfers
+            .iter()
+            .map(|(_, transfer)| transfer.amount);
+        let reversible = self
+            .reversible_transfers
+            .iter()
+            .filter(|(_, transfer)| {
+                matches!(
+                    transfer.status,
+                    ReversibleStatus::Pending {} | ReversibleStatus::Frozen {}
+                )
+            })
+            .map(|(_, transfer)| transfer.amount);
+        let vesting = self
+            .vesting_grants
+            .iter()
+            .map(|(_, grant)| grant.total - grant.claimed);
+        let milestones = self.milestone_grants.iter().flat_map(|(_, grant)| {
+            grant
+                .milestones
+                .iter()
+                .filter(|milestone| !milestone.released)
+                .map(|milestone| milestone.amount)
+        });
+        let organizations = self.organizations.iter().map(|(_, org)| org.balance);
+        let collateral = self.loans.iter().map(|(_, loan)| loan.collateral);
+
+        streams
+            .chain(channels)
+            .chain(sale)
+            .chain(htlcs)
+            .chain(votes)
+            .chain(pools)
+            .chain(bids)
+            .chain(pots)
+            .chain(locks)
+            .chain(escheated)
+            .chain(clawbacks)
+            .chain(pool)
+            .chain(pending)
+            .chain(reversible)
+            .chain(vesting)
+            .chain(milestones)
+            .chain(organizations)
+            .chain(collateral)
+            .try_fold(0u128, u128::checked_add)
+            .expect("Invariant violated: escrowed amounts overflow.")
+    }
+
+    /// Checks the bookkeeping invariants of the state. Does nothing without the
+    /// `strict-invariants` feature.
+    ///
+    /// Panics if the balances do not add up to the total supply as described in the module
+    /// documentation, if the balances of another token do not add up to its total supply, if the
+    /// wrapped supply exceeds the wrap reserve, or if `balances`, `allowed`, `burn_allowed` or
+    /// `deposits` hold a zero amount or an empty inner map.
+    pub fn assert_invariants(&self) {
+        if !cfg!(feature = "strict-invariants") {
+            return;
+        }
+
+        let mut held: u128 = 0;
+        for (account, balance) in self.balances.entries() {
+            assert!(
+                amount_value(balance) > 0,
+                "Invariant violated: zero balance of {:?}",
+                account
+            );
+            held = held
+                .checked_add(amount_value(balance))
+                .expect("Invariant violated: balances overflow.");
+        }
+        assert_nested_hygiene("allowed", &self.allowed, |amount: &Amount| {
+            amount_value(*amount)
+        });
+        assert_nested_hygiene("burn_allowed", &self.burn_allowed, |amount: &u128| *amount);
+        assert_nested_hygiene("deposits", &self.deposits, |amount: &u128| *amount);
+        for (id, token) in self.sub_tokens.iter() {
+            let token_held: u128 = token.balances.iter().map(|(_, balance)| *balance).sum();
+            assert!(
+                token_held == token.total_supply,
+                "Invariant violated: balances: {}, of token: {}, do not add up to its total supply: {}",
+                token_held,
+                id,
+                token.total_supply
+            );
+        }
+        assert!(
+            self.wrapped_supply <= self.wrap_reserve,
+            "Invariant violated: wrapped supply: {}, exceeds the wrap reserve: {}",
+            self.wrapped_supply,
+            self.wrap_reserve
+        );
+
+        let total_supply = amount_value(self.total_supply);
+        let escrowed = self.escrowed();
+        if BALANCES_ACCRUE_INTEREST {
+            let outside = self.shares_rounded_down(escrowed);
+            assert!(
+                held + outside <= total_supply,
+                "Invariant violated: balances: {}, and escrowed tokens: {}, exceed total supply: {}",
+                held,
+                outside,
+                total_supply
+            );
+        } else {
+            assert!(
+                held + escrowed == total_supply,
+                "Invariant violated: balances: {}, and escrowed tokens: {}, do not add up to total supply: {}",
+                held,
+                escrowed,
+                total_supply
+            );
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Tests of the supply invariant: escrowed tokens are summed across features and must account for
+//! every token missing from the balances.
+
+use crate::testing::{account, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token whose owner moved `amount` into each of the referral and payroll pools.
+fn escrowed_state(amount: u128) -> TashiTokenState {
+    let mut state = new_state();
+    state.debit(account(0), 2 * amount);
+    state.referral_pool += amount;
+    state.payroll_pool += amount;
+    state
+}
+
+#[test]
+fn escrowed_sums_every_pool() {
+    let state = escrowed_state(1_000);
+    assert_eq!(state.escrowed(), 2_000);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 2_000);
+    state.assert_invariants();
+}
+
+#[test]
+#[cfg(all(feature = "strict-invariants", not(feature = "interest")))]
+#[should_panic(
+    expected = "Invariant violated: balances: 998000, and escrowed tokens: 1000, do not add up to total supply: 1000000"
+)]
+fn tokens_missing_from_escrow_are_detected() {
+    let mut state = escrowed_state(1_000);
+    state.payroll_pool = 0;
+    state.assert_invariants();
+}
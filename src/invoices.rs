@@ -0,0 +1,262 @@
+//! Invoices, payment requests from a payee to a payer carrying a reference for reconciliation. The
+//! payer settles an invoice by paying it, which transfers the amount and marks it paid. Invoices
+//! stay in state with their status so both sides can look them up.
+#![cfg_attr(not(feature = "invoices"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "invoices")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "invoices")]
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "invoices")]
+use crate::hooks::Transfer;
+#[cfg(feature = "invoices")]
+use crate::token::TashiTokenState;
+#[cfg(feature = "invoices")]
+use crate::views::return_value;
+
+#[cfg(all(test, feature = "invoices"))]
+mod tests;
+
+/// The status of an invoice.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InvoiceStatus {
+    /// Waiting to be paid.
+    #[discriminant(0)]
+    Open {},
+    /// Paid by the payer at block production time `paid_at`, in milliseconds.
+    #[discriminant(1)]
+    Paid { paid_at: i64 },
+    /// Cancelled by the payee.
+    #[discriminant(2)]
+    Cancelled {},
+    /// Not paid before it expired. Only reported by queries, never stored.
+    #[discriminant(3)]
+    Expired {},
+}
+
+/// A payment request from `payee` to `payer`.
+///
+/// ### Fields:
+///
+///   * `payee`: [`Address`], account that created the invoice and is paid.
+///   * `payer`: [`Address`], account asked to pay.
+///   * `amount`: [`u128`], amount to pay.
+///   * `reference`: [`String`], reference of the payee, e.g. an order number.
+///   * `expires_at`: [`i64`], block production time the invoice can no longer be paid at, in
+///     milliseconds.
+///   * `status`: [`InvoiceStatus`], whether the invoice is open, paid or cancelled.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Invoice {
+    pub payee: Address,
+    pub payer: Address,
+    pub amount: u128,
+    pub reference: String,
+    pub expires_at: i64,
+    pub status: InvoiceStatus,
+}
+
+impl Invoice {
+    /// Gets the status of the invoice at time `now`, reporting open invoices past their expiry as
+    /// expired.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`InvoiceStatus`] at `now`.
+    pub fn status_at(&self, now: i64) -> InvoiceStatus {
+        match self.status {
+            InvoiceStatus::Open {} if now >= self.expires_at => InvoiceStatus::Expired {},
+            status => status,
+        }
+    }
+}
+
+#[cfg(feature = "invoices")]
+impl TashiTokenState {
+    /// Gets the invoice with id `invoice_id`.
+    ///
+    /// Panics if there is no such invoice.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `invoice_id`: [`u64`], id of the invoice.
+    ///
+    /// ### Returns:
+    ///
+    /// A mutable reference to the [`Invoice`].
+    pub fn invoice(&mut self, invoice_id: u64) -> &mut Invoice {
+        self.invoices
+            .get_mut(&invoice_id)
+            .unwrap_or_else(|| panic!("No invoice with id: {}", invoice_id))
+    }
+}
+
+/// Create an invoice asking `payer` to pay `amount` to caller address by `expires_at`. The id of
+/// the invoice is the `next_invoice_id` of the state before the call.
+///
+/// Panics if `amount` is zero or if `expires_at` has already passed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `payer`: [`Address`], account asked to pay.
+///   * `amount`: [`u128`], amount to pay.
+///   * `reference`: [`String`], reference of the caller, e.g. an order number.
+///   * `expires_at`: [`i64`], block production time the invoice expires at, in milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "invoices")]
+#[action(shortname = 0x68)]
+fn create_invoice(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    payer: Address,
+    amount: u128,
+    reference: String,
+    expires_at: i64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "create_invoice");
+    assert!(amount > 0, "Invoice amount cannot be zero.");
+    assert!(
+        expires_at > ctx.block_production_time,
+        "Invoice expiry has already passed."
+    );
+
+    let invoice_id = state.next_invoice_id;
+    state.next_invoice_id += 1;
+    state.invoices.insert(
+        invoice_id,
+        Invoice {
+            payee: ctx.sender,
+            payer,
+            amount,
+            reference,
+            expires_at,
+            status: InvoiceStatus::Open {},
+        },
+    );
+    state.assert_invariants();
+
+    state
+}
+
+/// Pay invoice `invoice_id`, transferring its amount from caller address to the payee.
+///
+/// Panics if there is no such invoice, if the caller is not its payer, if it is not open or has
+/// expired, or if the transfer fails.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `invoice_id`: [`u64`], id of the invoice to pay.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "invoices")]
+#[action(shortname = 0x69)]
+fn pay_invoice(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    invoice_id: u64,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "pay_invoice");
+    let now = ctx.block_production_time;
+    let invoice = state.invoice(invoice_id);
+    assert!(
+        invoice.payer == ctx.sender,
+        "Only the payer can pay the invoice."
+    );
+    assert!(
+        invoice.status_at(now) == InvoiceStatus::Open {},
+        "Invoice is not open."
+    );
+    invoice.status = InvoiceStatus::Paid { paid_at: now };
+    let transfer = Transfer::new(ctx.sender, invoice.payee, invoice.amount, None);
+
+    let supply = state.supply();
+    state.execute_transfer(&ctx, transfer);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Cancel open invoice `invoice_id`. Only its payee can call this action.
+///
+/// Panics if there is no such invoice, if the caller is not its payee, or if it is not open.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `invoice_id`: [`u64`], id of the invoice to cancel.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "invoices")]
+#[action(shortname = 0x6a)]
+fn cancel_invoice(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    invoice_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "cancel_invoice");
+    let invoice = state.invoice(invoice_id);
+    assert!(
+        invoice.payee == ctx.sender,
+        "Only the payee can cancel the invoice."
+    );
+    assert!(
+        invoice.status == InvoiceStatus::Open {},
+        "Invoice is not open."
+    );
+    invoice.status = InvoiceStatus::Cancelled {};
+    state.assert_invariants();
+
+    state
+}
+
+/// Return the status of invoice `invoice_id` to the caller.
+///
+/// Panics if there is no such invoice.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `invoice_id`: [`u64`], id of the invoice.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the status as an [`InvoiceStatus`] in the return data.
+#[cfg(feature = "invoices")]
+#[action(shortname = 0x6b)]
+fn query_invoice_status(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    invoice_id: u64,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let status = state
+        .invoice(invoice_id)
+        .status_at(ctx.block_production_time);
+    let events = return_value(status);
+    state.assert_invariants();
+    (state, events)
+}
@@ -0,0 +1,77 @@
+//! Tests of invoices: paying an invoice once, expired and cancelled invoices, and who can pay and
+//! cancel them.
+
+use super::{cancel_invoice, create_invoice, pay_invoice, InvoiceStatus};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state};
+use crate::token::TashiTokenState;
+
+/// A token where account 1 holds 10_000 and merchant account 2 asked it for 700 in invoice 0,
+/// expiring at one hour.
+fn invoiced_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    create_invoice(
+        context(account(2)),
+        state,
+        account(1),
+        700,
+        "order-42".to_string(),
+        3_600_000,
+    )
+}
+
+#[test]
+fn paying_transfers_the_amount_and_marks_the_invoice_paid() {
+    let (mut state, _) = pay_invoice(context_at(account(1), 1_000), invoiced_state(), 0);
+
+    assert_eq!(state.balance_of(&account(1)), 9_300);
+    assert_eq!(state.balance_of(&account(2)), 700);
+    let invoice = state.invoice(0);
+    assert_eq!(invoice.reference, "order-42");
+    assert!(invoice.status == InvoiceStatus::Paid { paid_at: 1_000 });
+    assert_eq!(state.next_invoice_id, 1);
+}
+
+#[test]
+#[should_panic(expected = "Invoice is not open.")]
+fn invoice_is_paid_once() {
+    let (state, _) = pay_invoice(context(account(1)), invoiced_state(), 0);
+    pay_invoice(context(account(1)), state, 0);
+}
+
+#[test]
+#[should_panic(expected = "Invoice is not open.")]
+fn expired_invoice_cannot_be_paid() {
+    let mut state = invoiced_state();
+    assert!(state.invoice(0).status_at(3_600_000) == InvoiceStatus::Expired {});
+    pay_invoice(context_at(account(1), 3_600_000), state, 0);
+}
+
+#[test]
+#[should_panic(expected = "Invoice is not open.")]
+fn cancelled_invoice_cannot_be_paid() {
+    let state = cancel_invoice(context(account(2)), invoiced_state(), 0);
+    pay_invoice(context(account(1)), state, 0);
+}
+
+#[test]
+#[should_panic(expected = "Only the payer can pay the invoice.")]
+fn only_the_payer_pays() {
+    pay_invoice(context(account(0)), invoiced_state(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Only the payee can cancel the invoice.")]
+fn only_the_payee_cancels() {
+    cancel_invoice(context(account(1)), invoiced_state(), 0);
+}
+
+#[test]
+#[should_panic(expected = "No invoice with id: 1")]
+fn unknown_invoice_cannot_be_paid() {
+    pay_invoice(context(account(1)), invoiced_state(), 1);
+}
@@ -0,0 +1,190 @@
+//! A registry of human-readable account labels. An account claims a unique label by paying the
+//! label fee to the treasury held by the contract, which keeps squatting costly, and transfers can
+//! be addressed to a label instead of a 21-byte address. Registering is opt-in, an account holds at
+//! most one label, and a released label can be claimed by anyone.
+#![cfg_attr(not(feature = "labels"), allow(dead_code))]
+
+#[cfg(feature = "labels")]
+use pbc_contract_common::address::Address;
+#[cfg(feature = "labels")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "labels")]
+use pbc_contract_common::events::EventGroup;
+
+#[cfg(feature = "labels")]
+use crate::hooks::Transfer;
+#[cfg(feature = "labels")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "labels"))]
+mod tests;
+
+/// The shortest label, in bytes.
+pub const MIN_LABEL_LENGTH: usize = 3;
+
+/// The longest label, in bytes.
+pub const MAX_LABEL_LENGTH: usize = 32;
+
+/// Checks that `label` is a valid label: [`MIN_LABEL_LENGTH`] to [`MAX_LABEL_LENGTH`] lowercase
+/// ASCII letters, digits, `-` or `_`. Restricting the alphabet rules out labels that only differ
+/// by case or by look-alike characters.
+///
+/// Panics if the label is invalid.
+///
+/// ### Parameters:
+///
+///   * `label`: [`str`], the label to check.
+pub fn assert_valid_label(label: &str) {
+    assert!(
+        (MIN_LABEL_LENGTH..=MAX_LABEL_LENGTH).contains(&label.len()),
+        "Label length: {}, must be between: {} and: {}",
+        label.len(),
+        MIN_LABEL_LENGTH,
+        MAX_LABEL_LENGTH
+    );
+    assert!(
+        label.bytes().all(|byte| byte.is_ascii_lowercase()
+            || byte.is_ascii_digit()
+            || byte == b'-'
+            || byte == b'_'),
+        "Labels can only contain lowercase letters, digits, '-' and '_'."
+    );
+}
+
+#[cfg(feature = "labels")]
+impl TashiTokenState {
+    /// Releases the label of `account`, if it holds one.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account releasing its label.
+    fn release_label_of(&mut self, account: &Address) {
+        if let Some(label) = self.account_labels.remove(account) {
+            self.labels.remove(&label);
+        }
+    }
+}
+
+/// Set the fee paid to the treasury to claim a label. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `fee`: [`u128`], fee of a label.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "labels")]
+#[action(shortname = 0xc6)]
+fn set_label_fee(ctx: ContractContext, mut state: TashiTokenState, fee: u128) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_label_fee", fee);
+    state.label_fee = fee;
+    state.assert_invariants();
+
+    state
+}
+
+/// Claim `label` for caller address, paying the label fee to the treasury. The previous label of
+/// the caller, if any, is released.
+///
+/// Panics if the label is invalid or held by another account, or if there is insufficient balance
+/// in caller account to pay the fee.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `label`: [`String`], the label to claim.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "labels")]
+#[action(shortname = 0xc7)]
+fn claim_label(ctx: ContractContext, mut state: TashiTokenState, label: String) -> TashiTokenState {
+    crate::metrics::count_action!(state, "claim_label");
+    assert_valid_label(&label);
+    assert!(
+        !state.labels.contains_key(&label),
+        "Label is already taken: {}",
+        label
+    );
+    let fee = state.label_fee;
+    state.escrow(&ctx, ctx.sender, fee);
+    state.credit(ctx.contract_address, fee);
+
+    state.release_label_of(&ctx.sender);
+    state.labels.insert(label.clone(), ctx.sender);
+    state.account_labels.insert(ctx.sender, label);
+    state.assert_invariants();
+
+    state
+}
+
+/// Release the label of caller address, letting anyone claim it. The fee is not refunded.
+///
+/// Panics if the caller holds no label.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "labels")]
+#[action(shortname = 0xc8)]
+fn release_label(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "release_label");
+    assert!(
+        state.account_labels.contains_key(&ctx.sender),
+        "Caller holds no label."
+    );
+    state.release_label_of(&ctx.sender);
+    state.assert_invariants();
+
+    state
+}
+
+/// Transfer `amount` tokens from caller address to the account holding `label`. Otherwise handled
+/// as by `transfer`.
+///
+/// Panics if no account holds the label or if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `label`: [`String`], label of the account to transfer to.
+///   * `amount`: [`u128`], amount to transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "labels")]
+#[action(shortname = 0xc9)]
+fn transfer_to_label(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    label: String,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "transfer_to_label");
+    let to = *state
+        .labels
+        .get(&label)
+        .unwrap_or_else(|| panic!("Unknown label: {}", label));
+    let supply = state.supply();
+    state.execute_transfer(&ctx, Transfer::new(ctx.sender, to, amount, None));
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
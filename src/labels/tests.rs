@@ -0,0 +1,73 @@
+//! Tests of account labels: claiming a label for the fee, transfers addressed to a label, and
+//! labels that are released or already taken.
+
+use super::{claim_label, release_label, set_label_fee, transfer_to_label};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, new_state, token_address, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token charging 50 for a label, where account 1 held 1_000 and claimed the label "alice".
+fn labeled_state() -> TashiTokenState {
+    let mut state = set_label_fee(context(account(0)), new_state(), 50);
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 1_000, None),
+    );
+    claim_label(context(account(1)), state, "alice".to_string())
+}
+
+#[test]
+fn label_fee_is_paid_to_the_treasury() {
+    let state = labeled_state();
+
+    assert_eq!(state.balance_of(&account(1)), 950);
+    assert_eq!(state.balance_of(&token_address()), 50);
+    assert_eq!(
+        state.labels.get(&"alice".to_string()).copied(),
+        Some(account(1))
+    );
+}
+
+#[test]
+fn transfer_is_addressed_to_the_label() {
+    let (state, _) = transfer_to_label(
+        context(account(0)),
+        labeled_state(),
+        "alice".to_string(),
+        300,
+    );
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 1_300);
+    assert_eq!(state.balance_of(&account(1)), 1_250);
+}
+
+#[test]
+fn new_label_releases_the_previous_one() {
+    let state = claim_label(context(account(1)), labeled_state(), "alice-2".to_string());
+
+    assert!(state.labels.get(&"alice".to_string()).is_none());
+    assert_eq!(
+        state.labels.get(&"alice-2".to_string()).copied(),
+        Some(account(1))
+    );
+    assert_eq!(state.balance_of(&token_address()), 100);
+}
+
+#[test]
+#[should_panic(expected = "Label is already taken: alice")]
+fn taken_label_cannot_be_claimed() {
+    claim_label(context(account(0)), labeled_state(), "alice".to_string());
+}
+
+#[test]
+#[should_panic(expected = "Unknown label: alice")]
+fn released_label_no_longer_receives_transfers() {
+    let state = release_label(context(account(1)), labeled_state());
+    transfer_to_label(context(account(0)), state, "alice".to_string(), 300);
+}
+
+#[test]
+#[should_panic(expected = "Labels can only contain lowercase letters, digits, '-' and '_'.")]
+fn label_with_uppercase_letters_is_rejected() {
+    claim_label(context(account(0)), new_state(), "Alice".to_string());
+}
@@ -0,0 +1,112 @@
+//! A launch guard keeping transfers closed until the owner enables trading. Before that only the
+//! owner, the contract itself and allowlisted accounts, such as a sale or liquidity contract, can
+//! move tokens. Enabling trading is one-way, so the guard cannot trap holders later.
+#![cfg_attr(not(feature = "launch-guard"), allow(dead_code))]
+
+#[cfg(feature = "launch-guard")]
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+
+use crate::hooks::Transfer;
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "launch-guard"))]
+mod tests;
+
+impl TashiTokenState {
+    /// Checks that trading is enabled, or that `transfer` moves tokens of, or is initiated by, the
+    /// owner, the contract itself or an allowlisted account.
+    ///
+    /// Panics if trading is not enabled yet and the transfer is not allowed.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `transfer`: [`Transfer`], the transfer to check.
+    pub fn assert_trading_allowed(&self, ctx: &ContractContext, transfer: &Transfer) {
+        if self.trading_enabled {
+            return;
+        }
+        let initiator = transfer.spender.unwrap_or(transfer.from);
+        let allowed = [transfer.from, initiator].iter().any(|account| {
+            *account == self.owner
+                || *account == ctx.contract_address
+                || self.launch_allowlist.contains(account)
+        });
+        assert!(allowed, "Trading is not enabled yet.");
+    }
+}
+
+/// Enable trading for everyone. Only the owner can call this action, and trading cannot be
+/// disabled again.
+///
+/// Panics if the caller is not the owner or if trading is already enabled.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "launch-guard")]
+#[action(shortname = 0x36)]
+fn enable_trading(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "enable_trading");
+    assert!(!state.trading_enabled, "Trading is already enabled.");
+    state.trading_enabled = true;
+    state.launch_allowlist = Vec::new(); // the allowlist has no effect once trading is enabled
+    state.assert_invariants();
+
+    state
+}
+
+/// Allow `account` to move tokens before trading is enabled, or remove it from the allowlist. Only
+/// the owner can call this action.
+///
+/// Panics if the caller is not the owner, if trading is already enabled, or if `allowed` does not
+/// change whether `account` is allowlisted.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account to add to or remove from the allowlist.
+///   * `allowed`: [`bool`], true to add `account`, false to remove it.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "launch-guard")]
+#[action(shortname = 0x37)]
+fn set_launch_allowlisted(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+    allowed: bool,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_launch_allowlisted", account, allowed);
+    assert!(!state.trading_enabled, "Trading is already enabled.");
+    assert!(
+        state.launch_allowlist.contains(&account) != allowed,
+        "Account is already {}.",
+        if allowed {
+            "allowlisted"
+        } else {
+            "not allowlisted"
+        }
+    );
+    if allowed {
+        state.launch_allowlist.push(account);
+    } else {
+        state
+            .launch_allowlist
+            .retain(|allowlisted| *allowlisted != account);
+    }
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,72 @@
+//! Tests of the launch guard: transfers closed before trading is enabled, the owner and
+//! allowlisted accounts which move tokens anyway, and enabling trading once.
+
+use super::{enable_trading, set_launch_allowlisted};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, new_state};
+use crate::token::TashiTokenState;
+
+/// A token before its launch, where the owner gave account 1 10_000.
+fn launch_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.trading_enabled = false;
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    state
+}
+
+/// Transfers 100 from account 1 to account 2, as called by `caller`.
+fn transfer_from_holder(state: &mut TashiTokenState, caller: u8) {
+    let spender = (caller != 1).then(|| account(caller));
+    state.execute_transfer(
+        &context(account(caller)),
+        Transfer::new(account(1), account(2), 100, spender),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Trading is not enabled yet.")]
+fn holders_cannot_transfer_before_the_launch() {
+    transfer_from_holder(&mut launch_state(), 1);
+}
+
+#[test]
+fn allowlisted_account_moves_tokens_before_the_launch() {
+    let mut state = set_launch_allowlisted(context(account(0)), launch_state(), account(1), true);
+    transfer_from_holder(&mut state, 1);
+
+    assert_eq!(state.balance_of(&account(2)), 100);
+}
+
+#[test]
+fn allowlisted_spender_moves_tokens_before_the_launch() {
+    let mut state = set_launch_allowlisted(context(account(0)), launch_state(), account(3), true);
+    state.update_allowance(account(1), account(3), 100);
+    transfer_from_holder(&mut state, 3);
+
+    assert_eq!(state.balance_of(&account(2)), 100);
+}
+
+#[test]
+fn enabling_trading_opens_transfers_and_clears_the_allowlist() {
+    let state = set_launch_allowlisted(context(account(0)), launch_state(), account(3), true);
+    let mut state = enable_trading(context(account(0)), state);
+    transfer_from_holder(&mut state, 1);
+
+    assert!(state.launch_allowlist.is_empty());
+    assert_eq!(state.balance_of(&account(2)), 100);
+}
+
+#[test]
+#[should_panic(expected = "Trading is already enabled.")]
+fn trading_cannot_be_enabled_twice() {
+    enable_trading(context(account(0)), new_state());
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can perform this action.")]
+fn only_the_owner_enables_trading() {
+    enable_trading(context(account(1)), launch_state());
+}
@@ -0,0 +1,516 @@
+//! A minimal native credit facility. Depositors fund a lending pool and receive shares of it,
+//! borrowers lock tokens as collateral and borrow from the pool up to the maximum loan-to-value
+//! ratio, paying interest at the yearly rate set by the owner. Interest accrues on a global borrow
+//! index, raising the debt of every loan at once, and is earned by the depositors as the value of
+//! their shares grows. A loan whose debt grows beyond the liquidation loan-to-value ratio can be
+//! liquidated by anyone repaying it in full, for its collateral worth the debt plus a bonus.
+#![cfg_attr(not(feature = "lending"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+#[cfg(feature = "lending")]
+use pbc_contract_common::address::Address;
+#[cfg(feature = "lending")]
+use pbc_contract_common::context::ContractContext;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+use crate::interest::INDEX_SCALE;
+#[cfg(feature = "lending")]
+use crate::token::BalanceMap;
+use crate::token::{mul_div, mul_div_ceil, mul_div_wide, TashiTokenState, BPS_DENOMINATOR};
+
+#[cfg(all(test, feature = "lending"))]
+mod tests;
+
+/// The length of a year the lending rate applies to, 365 days in milliseconds.
+pub const LENDING_YEAR: i64 = 365 * 24 * 60 * 60 * 1000;
+
+/// The parameters of the lending pool.
+///
+/// ### Fields:
+///
+///   * `interest_rate_bps`: [`u16`], yearly interest rate of loans, in basis points.
+///   * `max_ltv_bps`: [`u16`], largest debt a borrower can take, as a share of its collateral in
+///     basis points.
+///   * `liquidation_ltv_bps`: [`u16`], debt from which a loan can be liquidated, as a share of its
+///     collateral in basis points.
+///   * `liquidation_bonus_bps`: [`u16`], collateral a liquidator receives on top of the debt it
+///     repays, as a share of the debt in basis points.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LendingConfig {
+    pub interest_rate_bps: u16,
+    pub max_ltv_bps: u16,
+    pub liquidation_ltv_bps: u16,
+    pub liquidation_bonus_bps: u16,
+}
+
+/// The totals of the lending pool.
+///
+/// ### Fields:
+///
+///   * `cash`: [`u128`], deposited tokens held by the contract and not lent out.
+///   * `total_shares`: [`u128`], shares of the pool held by all depositors.
+///   * `total_scaled_debt`: [`u128`], debt of all loans, divided by the borrow index.
+///   * `borrow_index`: [`u128`], growth of debts through interest, [`INDEX_SCALE`] at the start.
+///   * `accrued_at`: [`i64`], block production time interest last accrued at, in milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LendingPool {
+    pub cash: u128,
+    pub total_shares: u128,
+    pub total_scaled_debt: u128,
+    pub borrow_index: u128,
+    pub accrued_at: i64,
+}
+
+/// The lending pool of a newly initialized token, empty and without accrued interest.
+pub const EMPTY_LENDING_POOL: LendingPool = LendingPool {
+    cash: 0,
+    total_shares: 0,
+    total_scaled_debt: 0,
+    borrow_index: INDEX_SCALE,
+    accrued_at: 0,
+};
+
+/// The loan of a borrower.
+///
+/// ### Fields:
+///
+///   * `collateral`: [`u128`], tokens locked as collateral.
+///   * `scaled_debt`: [`u128`], debt of the loan, divided by the borrow index.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Loan {
+    pub collateral: u128,
+    pub scaled_debt: u128,
+}
+
+impl LendingPool {
+    /// Gets the amount owed for `scaled_debt` at the current borrow index, rounded up.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `scaled_debt`: [`u128`], debt divided by the borrow index.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount owed.
+    pub fn debt_of(&self, scaled_debt: u128) -> u128 {
+        mul_div_ceil(scaled_debt, self.borrow_index, INDEX_SCALE)
+    }
+
+    /// Gets the value of the pool, the tokens not lent out and the debt of all loans.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount the shares of the pool are worth.
+    pub fn value(&self) -> u128 {
+        self.cash + self.debt_of(self.total_scaled_debt)
+    }
+}
+
+impl TashiTokenState {
+    /// Raises the borrow index by the interest due since it last accrued.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    pub fn accrue_lending_interest(&mut self, now: i64) {
+        let elapsed = now - self.lending_pool.accrued_at;
+        if elapsed <= 0 {
+            return;
+        }
+        self.lending_pool.accrued_at = now;
+        if let Some(config) = self.lending_config {
+            let interest = mul_div(
+                self.lending_pool.borrow_index,
+                u128::from(config.interest_rate_bps) * elapsed as u128,
+                BPS_DENOMINATOR * LENDING_YEAR as u128,
+            );
+            self.lending_pool.borrow_index += interest;
+        }
+    }
+}
+
+/// Whether a loan with `collateral` and `debt` stays within `ltv_bps`.
+///
+/// ### Parameters:
+///
+///   * `collateral`: [`u128`], tokens locked as collateral.
+///   * `debt`: [`u128`], amount owed.
+///   * `ltv_bps`: [`u16`], largest debt as a share of the collateral, in basis points.
+///
+/// ### Returns:
+///
+/// A [`bool`], true if the debt is within the share of the collateral.
+fn within_ltv(collateral: u128, debt: u128, ltv_bps: u16) -> bool {
+    debt <= mul_div(collateral, u128::from(ltv_bps), BPS_DENOMINATOR)
+}
+
+/// Set the interest rate and the loan-to-value ratios of the lending pool. Interest due under the
+/// previous rate accrues first. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner, if the maximum loan-to-value ratio is not below the
+/// liquidation ratio, or if the liquidation ratio exceeds 100%.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `config`: [`LendingConfig`], the new parameters.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "lending")]
+#[action(shortname = 0xf3)]
+fn set_lending_config(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    config: LendingConfig,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_lending_config", config);
+    assert!(
+        config.max_ltv_bps < config.liquidation_ltv_bps
+            && u128::from(config.liquidation_ltv_bps) <= BPS_DENOMINATOR,
+        "Maximum loan-to-value must be below the liquidation loan-to-value, at most 100%."
+    );
+    state.accrue_lending_interest(ctx.block_production_time);
+    state.lending_config = Some(config);
+    state.assert_invariants();
+
+    state
+}
+
+/// Deposit `amount` tokens from caller address to the lending pool, for shares worth the same
+/// amount.
+///
+/// Panics if `amount` is zero or if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to deposit.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "lending")]
+#[action(shortname = 0xf4)]
+fn deposit_to_lending_pool(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "deposit_to_lending_pool");
+    assert!(amount > 0, "Deposited amount cannot be zero.");
+    state.accrue_lending_interest(ctx.block_production_time);
+    let pool = &mut state.lending_pool;
+    let shares = if pool.total_shares == 0 {
+        amount
+    } else {
+        mul_div_wide(amount, pool.total_shares, pool.value())
+    };
+    pool.cash += amount;
+    pool.total_shares += shares;
+    let held = state.lending_shares.get(&ctx.sender).copied().unwrap_or(0);
+    state
+        .lending_shares
+        .insert_balance(ctx.sender, held + shares);
+    state.escrow(&ctx, ctx.sender, amount); // escrow the deposit
+    state.assert_invariants();
+
+    state
+}
+
+/// Redeem `shares` of the lending pool of caller address for the tokens they are worth.
+///
+/// Panics if `shares` is zero or more than the caller holds, or if the pool has lent out too much
+/// to pay them.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `shares`: [`u128`], shares to redeem.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "lending")]
+#[action(shortname = 0xf5)]
+fn withdraw_from_lending_pool(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    shares: u128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "withdraw_from_lending_pool");
+    let held = state.lending_shares.get(&ctx.sender).copied().unwrap_or(0);
+    assert!(
+        shares > 0 && shares <= held,
+        "Shares: {}, must be positive and at most the shares held: {}",
+        shares,
+        held
+    );
+    state.accrue_lending_interest(ctx.block_production_time);
+    let pool = &mut state.lending_pool;
+    let amount = mul_div_wide(shares, pool.value(), pool.total_shares);
+    assert!(
+        amount <= pool.cash,
+        "Withdrawn amount: {}, exceeds the tokens not lent out: {}",
+        amount,
+        pool.cash
+    );
+    pool.cash -= amount;
+    pool.total_shares -= shares;
+    state
+        .lending_shares
+        .insert_balance(ctx.sender, held - shares);
+    state.release(&ctx, ctx.sender, amount); // pay out the shares
+    state.assert_invariants();
+
+    state
+}
+
+/// Lock `amount` tokens from caller address as collateral of its loan.
+///
+/// Panics if `amount` is zero or if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to lock.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "lending")]
+#[action(shortname = 0xf6)]
+fn deposit_collateral(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "deposit_collateral");
+    assert!(amount > 0, "Collateral amount cannot be zero.");
+    match state.loans.get_mut(&ctx.sender) {
+        Some(loan) => loan.collateral += amount,
+        None => {
+            state.loans.insert(
+                ctx.sender,
+                Loan {
+                    collateral: amount,
+                    scaled_debt: 0,
+                },
+            );
+        }
+    }
+    state.escrow(&ctx, ctx.sender, amount); // escrow the collateral
+    state.assert_invariants();
+
+    state
+}
+
+/// Unlock `amount` of the collateral of caller address, keeping its loan within the maximum
+/// loan-to-value ratio. The loan is removed once it has neither collateral nor debt.
+///
+/// Panics if the caller has less collateral, or if the remaining collateral does not cover its debt
+/// at the maximum loan-to-value ratio.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to unlock.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "lending")]
+#[action(shortname = 0xf7)]
+fn withdraw_collateral(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "withdraw_collateral");
+    state.accrue_lending_interest(ctx.block_production_time);
+    let pool = state.lending_pool;
+    let max_ltv_bps = state.lending_config.map_or(0, |config| config.max_ltv_bps);
+    let loan = state
+        .loans
+        .get_mut(&ctx.sender)
+        .expect("Caller has no loan.");
+    let collateral = loan.collateral;
+    loan.collateral = collateral.checked_sub(amount).unwrap_or_else(|| {
+        panic!(
+            "Insufficient collateral: {}, minimum required collateral: {}",
+            collateral, amount
+        )
+    });
+    let debt = pool.debt_of(loan.scaled_debt);
+    assert!(
+        debt == 0 || within_ltv(loan.collateral, debt, max_ltv_bps),
+        "Remaining collateral does not cover the debt: {}",
+        debt
+    );
+    if loan.collateral == 0 && loan.scaled_debt == 0 {
+        state.loans.remove(&ctx.sender);
+    }
+    state.release(&ctx, ctx.sender, amount); // release the collateral
+    state.assert_invariants();
+
+    state
+}
+
+/// Borrow `amount` tokens from the lending pool against the collateral of caller address.
+///
+/// Panics if lending is not configured, if `amount` is zero or exceeds the tokens not lent out, or
+/// if the debt would exceed the maximum loan-to-value ratio of the collateral.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to borrow.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "lending")]
+#[action(shortname = 0xf8)]
+fn borrow_from_lending_pool(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "borrow_from_lending_pool");
+    let config = state.lending_config.expect("Lending is not configured.");
+    assert!(amount > 0, "Borrowed amount cannot be zero.");
+    state.accrue_lending_interest(ctx.block_production_time);
+    let pool = &mut state.lending_pool;
+    assert!(
+        amount <= pool.cash,
+        "Borrowed amount: {}, exceeds the tokens not lent out: {}",
+        amount,
+        pool.cash
+    );
+    let scaled = mul_div_ceil(amount, INDEX_SCALE, pool.borrow_index);
+    pool.cash -= amount;
+    pool.total_scaled_debt += scaled;
+    let pool = *pool;
+    let loan = state
+        .loans
+        .get_mut(&ctx.sender)
+        .expect("Caller has no collateral.");
+    loan.scaled_debt += scaled;
+    let debt = pool.debt_of(loan.scaled_debt);
+    assert!(
+        within_ltv(loan.collateral, debt, config.max_ltv_bps),
+        "Debt: {}, exceeds the maximum loan-to-value of the collateral: {}",
+        debt,
+        loan.collateral
+    );
+    state.release(&ctx, ctx.sender, amount); // lend out of the pool
+    state.assert_invariants();
+
+    state
+}
+
+/// Repay up to `amount` of the debt of caller address, at most what it owes.
+///
+/// Panics if the caller has no loan or if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], largest amount to repay.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "lending")]
+#[action(shortname = 0xf9)]
+fn repay_loan(ctx: ContractContext, mut state: TashiTokenState, amount: u128) -> TashiTokenState {
+    crate::metrics::count_action!(state, "repay_loan");
+    state.accrue_lending_interest(ctx.block_production_time);
+    let pool = &mut state.lending_pool;
+    let loan = state
+        .loans
+        .get_mut(&ctx.sender)
+        .expect("Caller has no loan.");
+    let debt = pool.debt_of(loan.scaled_debt);
+    let repaid = amount.min(debt);
+    let scaled = if repaid == debt {
+        loan.scaled_debt
+    } else {
+        mul_div(repaid, INDEX_SCALE, pool.borrow_index)
+    };
+    loan.scaled_debt -= scaled;
+    pool.total_scaled_debt -= scaled;
+    pool.cash += repaid;
+    if loan.collateral == 0 && loan.scaled_debt == 0 {
+        state.loans.remove(&ctx.sender);
+    }
+    state.escrow(&ctx, ctx.sender, repaid); // repay the pool
+    state.assert_invariants();
+
+    state
+}
+
+/// Liquidate the loan of `borrower`, repaying its whole debt from caller address. The caller
+/// receives collateral worth the debt plus the liquidation bonus, at most all of it, and the rest
+/// of the collateral is returned to the borrower.
+///
+/// Panics if lending is not configured, if `borrower` has no loan or its debt is within the
+/// liquidation loan-to-value ratio, or if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `borrower`: [`Address`], account whose loan is liquidated.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "lending")]
+#[action(shortname = 0xfa)]
+fn liquidate_loan(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    borrower: Address,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "liquidate_loan");
+    let config = state.lending_config.expect("Lending is not configured.");
+    state.accrue_lending_interest(ctx.block_production_time);
+    let loan = state
+        .loans
+        .remove(&borrower)
+        .expect("Borrower has no loan.");
+    let debt = state.lending_pool.debt_of(loan.scaled_debt);
+    assert!(
+        debt > 0 && !within_ltv(loan.collateral, debt, config.liquidation_ltv_bps),
+        "Loan is within the liquidation loan-to-value, debt: {}",
+        debt
+    );
+    state.lending_pool.total_scaled_debt -= loan.scaled_debt;
+    state.lending_pool.cash += debt;
+    let seized = mul_div_wide(
+        debt,
+        BPS_DENOMINATOR + u128::from(config.liquidation_bonus_bps),
+        BPS_DENOMINATOR,
+    )
+    .min(loan.collateral);
+    state.escrow(&ctx, ctx.sender, debt); // repay the pool
+    state.release(&ctx, ctx.sender, seized); // seize the collateral
+    state.release(&ctx, borrower, loan.collateral - seized); // return the rest of the collateral
+    state.assert_invariants();
+
+    state
+}
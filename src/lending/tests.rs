@@ -0,0 +1,125 @@
+//! Tests of the lending pool: deposits, loans and liquidations, at small amounts and at the
+//! magnitudes of a token with 18 decimals.
+
+use super::{
+    borrow_from_lending_pool, deposit_collateral, deposit_to_lending_pool, liquidate_loan,
+    repay_loan, set_lending_config, withdraw_from_lending_pool, LendingConfig, LENDING_YEAR,
+};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::{initialize, stored_amount, TashiTokenState};
+
+/// One whole token with 18 decimals.
+const WHOLE: u128 = 1_000_000_000_000_000_000;
+
+/// Lending at 100% a year, up to a loan-to-value of 50%, liquidated from 80% with a 5% bonus.
+const CONFIG: LendingConfig = LendingConfig {
+    interest_rate_bps: 10_000,
+    max_ltv_bps: 5_000,
+    liquidation_ltv_bps: 8_000,
+    liquidation_bonus_bps: 500,
+};
+
+/// Configures lending on `state` and gives accounts 1 to 3 `funds` each.
+fn lending_state(state: TashiTokenState, funds: u128) -> TashiTokenState {
+    let mut state = set_lending_config(context(account(0)), state, CONFIG);
+    for index in 1..=3 {
+        state.execute_transfer(
+            &context(account(0)),
+            Transfer::new(account(0), account(index), funds, None),
+        );
+    }
+    state
+}
+
+/// A token with 18 decimals and a supply of a trillion whole tokens, with lending configured and
+/// a billion whole tokens held by each of accounts 1 to 3.
+#[cfg(not(feature = "u64-amounts"))]
+fn large_state() -> TashiTokenState {
+    let mut state = initialize(
+        context(account(0)),
+        "Tashi Token".to_string(),
+        "TAS".to_string(),
+        18,
+        stored_amount(1_000_000_000_000 * WHOLE),
+    );
+    state.trading_enabled = true;
+    lending_state(state, 1_000_000_000 * WHOLE)
+}
+
+#[test]
+fn deposit_is_repaid_with_interest() {
+    let state = lending_state(new_state(), 10_000);
+    let state = deposit_to_lending_pool(context(account(1)), state, 10_000);
+    let state = deposit_collateral(context(account(2)), state, 4_000);
+    let state = borrow_from_lending_pool(context(account(2)), state, 2_000);
+    assert_eq!(state.balance_of(&account(2)), 8_000);
+
+    let state = repay_loan(context_at(account(2), LENDING_YEAR / 2), state, 5_000);
+    assert_eq!(state.balance_of(&account(2)), 5_000);
+    assert_eq!(state.loans.get(&account(2)).unwrap().scaled_debt, 0);
+
+    let state = withdraw_from_lending_pool(context_at(account(1), LENDING_YEAR / 2), state, 10_000);
+    assert_eq!(state.balance_of(&account(1)), 11_000);
+    assert_eq!(state.lending_pool.cash, 0);
+    assert_eq!(state.lending_pool.total_shares, 0);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 30_000);
+}
+
+#[test]
+#[should_panic(expected = "Debt: 2001, exceeds the maximum loan-to-value of the collateral: 4000")]
+fn borrow_beyond_the_maximum_loan_to_value_is_rejected() {
+    let state = lending_state(new_state(), 10_000);
+    let state = deposit_to_lending_pool(context(account(1)), state, 10_000);
+    let state = deposit_collateral(context(account(2)), state, 4_000);
+    borrow_from_lending_pool(context(account(2)), state, 2_001);
+}
+
+#[test]
+#[should_panic(expected = "Loan is within the liquidation loan-to-value, debt: 2000")]
+fn healthy_loan_cannot_be_liquidated() {
+    let state = lending_state(new_state(), 10_000);
+    let state = deposit_to_lending_pool(context(account(1)), state, 10_000);
+    let state = deposit_collateral(context(account(2)), state, 4_000);
+    let state = borrow_from_lending_pool(context(account(2)), state, 2_000);
+    liquidate_loan(context(account(3)), state, account(2));
+}
+
+#[test]
+#[cfg(not(feature = "u64-amounts"))]
+fn shares_and_liquidations_at_18_decimals_do_not_overflow() {
+    let funds = 1_000_000_000 * WHOLE;
+    let state = deposit_to_lending_pool(context(account(1)), large_state(), funds);
+    assert_eq!(state.lending_pool.total_shares, funds);
+
+    let state = deposit_collateral(context(account(2)), state, funds);
+    let state = borrow_from_lending_pool(context(account(2)), state, funds / 2);
+
+    // 70% of a year at 100% a year raises the debt to 85% of the collateral
+    let state = liquidate_loan(
+        context_at(account(3), LENDING_YEAR / 10 * 7),
+        state,
+        account(2),
+    );
+    let debt = 850_000_000 * WHOLE;
+    let seized = 892_500_000 * WHOLE;
+    assert!(state.loans.get(&account(2)).is_none());
+    assert_eq!(state.balance_of(&account(3)), funds - debt + seized);
+    assert_eq!(
+        state.balance_of(&account(2)),
+        funds / 2 + 107_500_000 * WHOLE
+    );
+    assert_eq!(state.lending_pool.cash, 1_350_000_000 * WHOLE);
+
+    let state =
+        deposit_to_lending_pool(context_at(account(3), LENDING_YEAR / 10 * 7), state, seized);
+    assert_eq!(
+        state.lending_shares.get(&account(3)).copied(),
+        Some(661_111_111_111_111_111_111_111_111)
+    );
+
+    let state =
+        withdraw_from_lending_pool(context_at(account(1), LENDING_YEAR / 10 * 7), state, funds);
+    assert_eq!(state.balance_of(&account(1)), 1_350_000_000 * WHOLE);
+    assert_eq!(state.lending_pool.cash, seized);
+}
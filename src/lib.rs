@@ -1,347 +1,221 @@
 //! tashi_token
+//!
+//! The contract is split into modules, optional behaviors are gated behind Cargo features:
+//!
+//!   * `token`: state, balance bookkeeping, initialization and plain transfers.
+//!   * `hooks`: the [`hooks::TransferHook`] extension point of the transfer path.
+//!   * `policy`: the transfer policies shared by transfers and by escrows and payouts.
+//!   * `allowance`: allowances and transfers on behalf of an owner.
+//!   * `admin`: ownership of the contract and roles.
+//!   * `mpc20`: interactions with other MPC-20 token contracts.
+//!   * `signatures`: secp256k1 and ed25519 verification of domain separated signed messages.
+//!   * `views`: getters other contracts call to read the token on-chain.
+//!   * `mintable`: minting new tokens, feature `mintable`.
+//!   * `burnable`: burning tokens and burn-on-transfer, feature `burnable`.
+//!   * `pausable`: pausing all transfers, feature `pausable`.
+//!   * `streaming`: payment streams released over time, feature `streaming`.
+//!   * `channels`: unidirectional payment channels, feature `channels`.
+//!   * `crowdsale`: token sales paid for with another MPC-20 token, feature `crowdsale`.
+//!   * `bonding_curve`: a linear bonding curve against a reserve token, feature `bonding-curve`.
+//!   * `htlc`: hash time locked transfers, feature `htlc`.
+//!   * `flash_mint`: flash mints repaid within one interaction, feature `flash-mint`.
+//!   * `interest`: interest bearing balances scaled by a global index, feature `interest`.
+//!   * `governance`: token holder proposals executed after a timelock, feature `governance`.
+//!   * `limits`: anti-whale limits on transfers and wallet balances, feature `limits`.
+//!   * `cooldown`: a minimum delay between transfers from the same account, feature `cooldown`.
+//!   * `launch`: transfers closed until the owner enables trading, feature `launch-guard`.
+//!   * `emission`: scheduled emission minted every epoch, feature `emission`.
+//!   * `guards`: validation of transfer and approval destinations, feature `address-guards`.
+//!   * `rewards`: pull based rewards funded by the owner, feature `rewards`.
+//!   * `referrals`: referral bonuses on transfers, feature `referrals`.
+//!   * `migration`: migration from a legacy token by snapshot claims or swaps, feature `migration`.
+//!   * `enforcement`: account freezes and recorded forced transfers, feature `enforcement`.
+//!   * `compliance`: a rule engine checked on every transfer, feature `compliance`.
+//!   * `audit`: a capped log of privileged actions, feature `audit-log`.
+//!   * `deposits`: deposits of other MPC-20 tokens with confirmed callbacks, feature `deposits`.
+//!   * `observers`: contracts notified of every supply change, feature `supply-observers`.
+//!   * `dust`: sweeping of balances below a dust threshold, feature `dust-sweep`.
+//!   * `units`: amounts given as decimal strings in display units, feature `display-units`.
+//!   * `split`: transfers divided among recipients by basis points, feature `split-payments`.
+//!   * `payroll`: recurring payments from a funded payroll pool, feature `payroll`.
+//!   * `subscriptions`: merchants charging subscribers once per period, feature `subscriptions`.
+//!   * `invoices`: payment requests with references settled by the payer, feature `invoices`.
+//!   * `conditional`: transfers completed only if a condition contract approves, feature
+//!     `conditional-transfers`.
+//!   * `oracle_fees`: fees denominated in a reference unit priced by an oracle, feature `oracle-fees`.
+//!   * `redemption`: burning tokens for their share of a reserve, feature `redemption`.
+//!   * `sponsorship`: signed transfers and approvals submitted by gas sponsors, feature `sponsorship`.
+//!   * `invariants`: bookkeeping invariants checked after every entry point, feature
+//!     `strict-invariants`.
+//!   * `auctions`: sealed-bid auctions with committed and revealed bids, feature `auctions`.
+//!   * `raffles`: raffles with committed seeds and burned and treasury cuts, feature `raffles`.
+//!   * `multicall`: batches of the token's own actions in one transaction, feature `multicall`.
+//!   * `sessions`: scoped session keys acting for an account until they expire, feature
+//!     `session-keys`.
+//!   * `vote_escrow`: vote-escrow locks weighting votes by amount and time left, feature
+//!     `vote-escrow`.
+//!   * `recovery`: social recovery of lost accounts by their guardians, feature `social-recovery`.
+//!   * `dormancy`: sweeps of dormant accounts to a reclaimable recovery pool, feature `dormancy`.
+//!   * `clawback`: reversals of transfers after a dispute window, feature `clawback`.
+//!   * `emergency`: emergency pauses by guardians that lift themselves, feature `emergency-pause`.
+//!   * `timelock`: a delay queue for privileged actions, feature `admin-timelock`.
+//!   * `mint_limit`: a per epoch limit on minting, feature `mint-rate-limit`.
+//!   * `scheduled_mints`: mints scheduled by the owner and triggered by anyone once due, feature
+//!     `scheduled-mints`.
+//!   * `burn_stats`: cumulative statistics of the burned tokens.
+//!   * `circulating`: the circulating supply, less the balances of registered non-circulating
+//!     accounts, feature `circulating-supply`.
+//!   * `treasury`: treasurer payments out of the treasury within a spending limit, feature
+//!     `treasury`.
+//!   * `fee_burn`: burning a share of the collected transfer fees, feature `fee-burn`.
+//!   * `round_up`: transfers rounded up with the difference donated to a charity, feature
+//!     `round-up`.
+//!   * `demurrage`: balances decaying over time into a community pool, feature `demurrage`.
+//!   * `multi_token`: further tokens with their own ids next to the main token, feature
+//!     `multi-token`.
+//!   * `wrapper`: wrapping another MPC-20 token 1:1, feature `wrapper`.
+//!   * `amm`: a constant product liquidity pool against the native coin, feature `amm`.
+//!   * `memos`: transfers recording an opaque reference with them, feature `memos`.
+//!   * `history`: a ring buffer of the latest transfers, feature `transfer-history`.
+//!   * `nonces`: per-account nonces consumed by signed messages, feature `nonces`.
+//!   * `reserves`: signed proof-of-reserve attestations, feature `reserve-attestations`.
+//!   * `labels`: unique account labels transfers can be addressed to, feature `labels`.
+//!   * `acceptance`: allowances accepted by their spender, feature `allowance-acceptance`.
+//!   * `pending`: transfers held until claimed or rejected by the receiver, feature
+//!     `pending-transfers`.
+//!   * `reversible`: large transfers held for a dispute window, feature `reversible-transfers`.
+//!   * `external_compliance`: large transfers approved by a compliance contract, feature
+//!     `external-compliance`.
+//!   * `soulbound`: non-transferable tokens with approved migrations, feature `soulbound`.
+//!   * `balance_roots`: merkle roots of the balances, feature `balance-roots`.
+//!   * `vesting`: vesting grants the grantor can revoke, feature `vesting`.
+//!   * `grants`: grants released per approved milestone, feature `milestone-grants`.
+//!   * `lockbox`: holders locking part of their balance until a chosen time, feature `lockbox`.
+//!   * `cosigner`: co-signer confirmation of large transfers, feature `cosigner`.
+//!   * `budgets`: budget sub-accounts with designated spending keys, feature `budgets`.
+//!   * `organizations`: organization accounts controlled by a set of signers, feature
+//!     `organizations`.
+//!   * `lending`: loans against locked collateral from a pool funded by depositors, feature
+//!     `lending`.
+//!   * `redenomination`: raising the decimals by scaling every amount, feature `redenomination`.
+//!   * `settlement`: settlement batches skipping and reporting failing legs, feature `settlement`.
+//!   * `idempotency`: transfers with idempotency keys, safe to retry, feature `idempotency`.
+//!   * `metrics`: counters of transfers, volume, distinct senders and action calls, feature
+//!     `usage-metrics`.
+//!   * `client`: typed payload builders of every action for off-chain services, feature `client`,
+//!     outside of WASM only.
+//!   * `snapshot`: serde support for dumping and loading state snapshots, feature `serde`, outside
+//!     of WASM only.
+//!   * `state_hash`: deterministic hashes of the state for reconciliation, feature `state-hash`.
+//!   * `shards`: balances sharded by address on a lazily loaded map, feature `sharded-balances`.
+//!
+//! A feature gates the actions of its module, and the code only they reach is left out of the WASM,
+//! but the state keeps the fields of every feature, see `TashiTokenState` in `token`.
 
 #[macro_use]
 extern crate pbc_contract_codegen;
 extern crate pbc_lib as _;
 
-use pbc_contract_common::address::Address;
-use pbc_contract_common::context::ContractContext;
-use pbc_contract_common::sorted_vec_map::SortedVecMap;
-use std::ops::Sub;
-
-/// This is the state of the token which is persisted on chain.
-///
-/// ### Fields:
-///
-///   * `total_supply`: [`u128`], total supply of coins.
-///   * `name`: [`String`], name of the token.
-///   * `symbol`: [`String`], symbol of the token.
-///   * `balances`: [`SortedVecMap`]<[`Address`], [`u128`]>, balances of each address.
-///   * `allowed`: [`SortedVecMap`]<[`Address`], [`SortedVecMap`]<[`Address`], [`u128`]>, all balances allotted by an address to other addresses.
-///   * `decimals`: [`u8`], the number of decimals the token uses.
-///   * `owner`: [`Address`], the owner of the contract.
-///   * `_padding`: [[`u16`]; `5`], padding bytes to align the struct.
-#[state]
-#[repr(C)]
-struct TashiTokenState {
-    total_supply: u128,
-    name: String,
-    symbol: String,
-    balances: SortedVecMap<Address, u128>,
-    allowed: SortedVecMap<Address, SortedVecMap<Address, u128>>,
-    decimals: u8,
-    owner: Address,
-    _padding: [u8; 10],
-}
-
-/// A map that can store balances.
-trait BalanceMap<K, V>
-where
-    // K is a type that implements the Ord trait
-    K: Ord,
-{
-    fn insert_balance(&mut self, key: K, amount: V);
-}
-
-/// the type SortedVecMap<Address, V> should implement the trait BalanceMap<Address, V>, where V is a type that implements the trait Sub<V, Output = V>
-impl<V: Sub<V, Output = V> + PartialEq + Copy> BalanceMap<Address, V> for SortedVecMap<Address, V> {
-    #[allow(clippy::eq_op)]
-    fn insert_balance(&mut self, key: Address, amount: V) {
-        let zero = amount - amount; // can handle different zeroes for different types
-        if amount == zero {
-            self.remove(&key); // remove address with 0 balance
-        } else {
-            self.insert(key, amount); // update or insert address with new value
-        }
-    }
-}
-
-// implement struct specific functions
-impl TashiTokenState {
-    /// Gets the balance of the specified address.
-    ///
-    /// ### Parameters:
-    ///
-    ///   * `owner`: [`Address`], account to query balance of
-    ///
-    /// ### Returns:
-    ///
-    /// A [`u128`] amount owned by the account.
-    pub fn balance_of(&self, owner: &Address) -> u128 {
-        self.balances.get(owner).copied().unwrap_or(0)
-    }
-
-    /// Gets the amount of tokens that an owner allotted to a spender.
-    ///
-    /// ### Parameters:
-    ///
-    ///   * `owner`: [`Address`], account which owns the funds.
-    ///   * `spender`: [`Address`], account which will spend the funds.
-    ///
-    /// ### Returns:
-    ///
-    /// A [`u128`] amount the `spender` is allowed to withdraw from the `owner`.
-    pub fn allowance(&self, owner: &Address, spender: &Address) -> u128 {
-        self.allowed
-            .get(owner)
-            .and_then(|owner_allowances| owner_allowances.get(spender))
-            .copied()
-            .unwrap_or(0)
-    }
-
-    /// Updates the balance an owner allots a spender to `amount`.
-    ///
-    /// ### Parameters:
-    ///
-    ///   * `owner`: [`Address`], account which owns the funds.
-    ///   * `spender`: [`Address`], account which will spend the funds.
-    ///   * `amount`: [`u128`], amount to allot to `spender`.
-    pub fn update_allowance(&mut self, owner: Address, spender: Address, amount: u128) {
-        if !self.allowed.contains_key(&owner) {
-            self.allowed.insert(owner, SortedVecMap::new());
-        }
-        let owner_allowances = self.allowed.get_mut(&owner).unwrap();
-        owner_allowances.insert_balance(spender, amount);
-    }
-}
-
-/// Initial function to bootstrap the contract's state.
-///
-/// ### Parameters
-///
-///   * `ctx`: [`ContractContext`] - the contract context containing sender and chain information.
-///   * `name`: [`String`], name of the token.
-///   * `symbol`: [`String`], symbol of the token.
-///   * `total_supply`: [`u128`], total supply of the token.
-///
-/// ### Returns
-///
-/// The new [`TokenState`] state.
-#[init]
-fn initialize(
-    ctx: ContractContext,
-    total_supply: u128,
-    name: String,
-    symbol: String,
-    decimals: u8,
-) -> TashiTokenState {
-    let mut balances: SortedVecMap<Address, u128> = SortedVecMap::new();
-    balances.insert(ctx.sender, total_supply);
-    TashiTokenState {
-        total_supply,
-        name,
-        symbol,
-        balances,
-        allowed: SortedVecMap::new(),
-        decimals,
-        owner: ctx.sender,
-        _padding: [0; 10],
-    }
-}
-
-/// Transfer `amount` tokens to address `to` from caller address.
-///
-/// Panics if there is insufficient balance in caller account.
-///
-/// ### Parameters
-///
-///   * `ctx`: [`ContractContext`], current context for the action.
-///   * `state`: [`TokenState`], current state of the contract.
-///   * `to`: [`Address`], account to transfer to.
-///   * `amount`: [`u128`], amount to transfer.
-///
-/// ### Returns
-///
-/// The updated [`TokenState`] state.
-#[action(shortname = 0x01)]
-fn transfer(
-    ctx: ContractContext,
-    mut state: TashiTokenState,
-    receiver: Address,
-    amount: u128,
-) -> TashiTokenState {
-    let sender_balance = state.balance_of(&ctx.sender);
-    let new_sender_balance = sender_balance
-        .checked_sub(amount) // subtract amount from sender balance
-        .unwrap_or_else(|| {
-            // panic if balance < amount
-            panic!(
-                "Insufficient balance: {}, minimum required balance: {}",
-                sender_balance, amount
-            )
-        });
-    state
-        .balances
-        .insert_balance(ctx.sender, new_sender_balance); // update sender balance
-
-    let new_receiver_balance = state
-        .balance_of(&receiver)
-        .checked_add(amount) // add amount to receiver balance
-        .expect("Overflow when adding to balance.");
-
-    state
-        .balances
-        .insert_balance(receiver, new_receiver_balance); // update receiver balance
-
-    state
-}
-
-/// Transfer `value` tokens to address `to` from address `from`.
-///
-/// Panics if there is insufficient allowance in caller account or if adding `amount` causes an
-/// overflow.
-///
-/// ### Parameters
-///
-///   * `ctx`: [`ContractContext`], current context for the action.
-///   * `state`: [`TokenState`], current state of the contract.
-///   * `from`: [`Address`], account to transfer from.
-///   * `to`: [`Address`], account to transfer to.
-///   * `amount`: [`u128`] - amount to transfer.
-///
-/// ### Returns
-///
-/// The updated [`TokenState`] state.
-#[action(shortname = 0x03)]
-fn transfer_from(
-    ctx: ContractContext,
-    mut state: TashiTokenState,
-    from: Address,
-    receiver: Address,
-    amount: u128,
-) -> TashiTokenState {
-    let caller_allowance = state.allowance(&from, &ctx.sender);
-    let caller_new_allowance = caller_allowance
-        .checked_sub(amount) // subtract amount from caller allowance
-        .unwrap_or_else(|| {
-            // panic if allowance < amount
-            panic!(
-                "Insufficient allowance: {}, minimum required allowance: {}",
-                caller_allowance, amount
-            )
-        });
-    state.update_allowance(from, ctx.sender, caller_new_allowance); // update caller allowance
-
-    let new_receiver_balance = state
-        .balance_of(&receiver) // get balance of receiver
-        .checked_add(amount) // add amount to receiver balance
-        .expect("Overflow when adding to balance.");
-
-    state
-        .balances
-        .insert_balance(receiver, new_receiver_balance); // update receiver balance
-
-    state
-}
-
-/// Approve `amount` tokens for address `spender` from caller address. If no prior approval exists
-/// then a new entry is created with approval set as `amount`. Else `amount` replaces the current
-/// approval amount.
-///
-/// Panics if there is insufficient balance in caller account.
-///
-/// ### Parameters
-///
-///   * `ctx`: [`ContractContext`], current context for the action.
-///   * `state`: [`TokenState`], current state of the contract.
-///   * `from`: [`Address`], account to transfer from.
-///   * `to`: [`Address`], account to transfer to.
-///   * `amount`: [`u128`], amount to transfer.
-///
-/// ### Returns
-///
-/// The updated [`TokenState`] state.
-#[action(shortname = 0x05)]
-fn approve(
-    ctx: ContractContext,
-    mut state: TashiTokenState,
-    spender: Address,
-    amount: u128,
-) -> TashiTokenState {
-    let caller_balance = state.balance_of(&ctx.sender);
-    let caller_new_balance = caller_balance
-        .checked_sub(amount) // subtract amount from caller balance
-        .unwrap_or_else(|| {
-            // panic if balance < amount
-            panic!(
-                "Insufficient balance: {}, minimum required balance: {}",
-                caller_balance, amount
-            )
-        });
-    state
-        .balances
-        .insert_balance(ctx.sender, caller_new_balance); // update caller balance
-
-    state.update_allowance(ctx.sender, spender, amount); // update spender allowance
-
-    state
-}
-
-/// Update the allowance for address `spender` from caller address by amount `delta`. If no prior
-/// approval exists then a new entry is created with approval set as `delta`. In this case `delta`
-/// needs to be positive. `delta` can be negative if there is some allowance already. In this case
-/// if `delta` is greater than the allowance, the allowance is set to 0 and that many coins are
-/// returned to the caller.
-///
-/// Panics if there is insufficient allowance in `spender` account, insufficient balance in caller
-/// account, adding `delta` causes an overflow, or if converting to [`u128`] or [`i128] fails.
-///
-/// ### Parameters
-///
-///   * `ctx`: [`ContractContext`], current context for the action.
-///   * `state`: [`TokenState`], current state of the contract.
-///   * `spender`: [`Address`], account to update allowance for.
-///   * `delta`: [`i128`], amount to update allowance by.
-///
-/// ### Returns
-///
-/// The updated [`TokenState`] state.
-#[action(shortname = 0x06)]
-fn approve_relative(
-    ctx: ContractContext,
-    mut state: TashiTokenState,
-    spender: Address,
-    delta: i128,
-) -> TashiTokenState {
-    let caller_balance_result: Result<i128, _> = state.balance_of(&ctx.sender).try_into();
-    let caller_balance = match caller_balance_result {
-        Ok(balance) => balance,
-        Err(error) => panic!("u128 to i128 conversion failed: {}", error),
-    };
-
-    let spender_allowance_result: Result<i128, _> =
-        state.allowance(&ctx.sender, &spender).try_into();
-    let spender_allowance = match spender_allowance_result {
-        Ok(allowance) => allowance,
-        Err(error) => panic!("u128 to i128 conversion failed: {}", error),
-    };
-
-    let mut checked_delta = delta;
-    // return allowance back to caller
-    if delta.is_negative() {
-        let abs_delta = delta.checked_abs().unwrap_or(i128::MAX);
-        // spender has enough allowance to give delta back to caller
-        if abs_delta >= spender_allowance {
-            // return whatever allowance is left back to caller
-            checked_delta = -spender_allowance;
-        }
-    }
-
-    let caller_new_balance = caller_balance
-        .checked_sub(checked_delta) // add amount delta to caller balance
-        .expect("Overflow when updating caller balance.")
-        .try_into()
-        .unwrap_or_else(|error| panic!("i128 to u128 conversion failed: {}", error));
-    state
-        .balances
-        .insert_balance(ctx.sender, caller_new_balance); // update caller balance
-
-    let spender_new_allowance = spender_allowance
-        .checked_add(checked_delta)
-        .expect("Overflow when updating spender allowance.")
-        .try_into()
-        .unwrap_or_else(|error| panic!("i128 to u128 conversion failed: {}", error));
-    state.update_allowance(ctx.sender, spender, spender_new_allowance); // update spender allowance
-
-    state
-}
+#[cfg(all(feature = "serde", target_arch = "wasm32"))]
+compile_error!("The `serde` feature is for off-chain tooling and cannot be compiled to WASM.");
+
+#[cfg(all(feature = "serde", feature = "sharded-balances"))]
+compile_error!("State snapshots do not support the lazily loaded `sharded-balances` layout.");
+
+mod acceptance;
+mod admin;
+mod allowance;
+mod amm;
+mod auctions;
+mod audit;
+mod balance_roots;
+mod bonding_curve;
+mod budgets;
+mod burn_stats;
+#[cfg(feature = "burnable")]
+mod burnable;
+mod channels;
+mod circulating;
+mod clawback;
+#[cfg(all(feature = "client", not(target_arch = "wasm32")))]
+pub mod client;
+mod compliance;
+mod conditional;
+mod cooldown;
+mod cosigner;
+mod crowdsale;
+mod demurrage;
+mod deposits;
+mod dormancy;
+mod dust;
+mod emergency;
+mod emission;
+mod enforcement;
+mod external_compliance;
+mod fee_burn;
+mod flash_mint;
+mod governance;
+mod grants;
+mod guards;
+mod history;
+mod hooks;
+mod htlc;
+mod idempotency;
+mod interest;
+mod invariants;
+mod invoices;
+mod labels;
+mod launch;
+mod lending;
+mod limits;
+mod lockbox;
+mod memos;
+mod metrics;
+mod migration;
+mod mint_limit;
+#[cfg(feature = "mintable")]
+mod mintable;
+mod mpc20;
+mod multi_token;
+mod multicall;
+mod nonces;
+mod observers;
+mod oracle_fees;
+mod organizations;
+#[cfg(feature = "pausable")]
+mod pausable;
+mod payroll;
+mod pending;
+mod policy;
+mod raffles;
+mod recovery;
+mod redemption;
+mod redenomination;
+mod referrals;
+mod reserves;
+mod reversible;
+mod rewards;
+mod round_up;
+mod scheduled_mints;
+mod sessions;
+mod settlement;
+mod shards;
+mod signatures;
+#[cfg(feature = "serde")]
+mod snapshot;
+mod soulbound;
+mod split;
+mod sponsorship;
+mod state_hash;
+mod streaming;
+mod subscriptions;
+#[cfg(test)]
+mod testing;
+mod timelock;
+mod token;
+mod treasury;
+mod units;
+mod vesting;
+mod views;
+mod vote_escrow;
+mod wrapper;
@@ -0,0 +1,192 @@
+//! Anti-whale limits on the amount of a single transfer and on the balance of a single wallet, for
+//! fair launch distributions. The owner, the contract itself and exempted accounts are not limited.
+//! A minimum transfer amount rejects dust transfers from everyone, except transfers to or from the
+//! treasury held by the contract itself.
+#![cfg_attr(not(feature = "limits"), allow(dead_code))]
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+
+use crate::hooks::Transfer;
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "limits"))]
+mod tests;
+
+impl TashiTokenState {
+    /// Checks that the amount of `transfer` is within the transfer limits. Transfers to or from
+    /// the contract itself are not held to the minimum amount.
+    ///
+    /// Panics if the amount is below `min_transfer_amount`, or above `max_transfer_amount` and
+    /// the sender is not exempt.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `transfer`: [`Transfer`], the transfer to check.
+    pub fn assert_within_transfer_limits(&self, ctx: &ContractContext, transfer: &Transfer) {
+        let treasury_transfer =
+            transfer.from == ctx.contract_address || transfer.to == ctx.contract_address;
+        assert!(
+            treasury_transfer || transfer.amount >= self.min_transfer_amount,
+            "Transfer amount: {}, minimum allowed transfer amount: {}",
+            transfer.amount,
+            self.min_transfer_amount
+        );
+        if let Some(max_transfer_amount) = self.max_transfer_amount {
+            assert!(
+                self.is_limit_exempt(ctx, &transfer.from) || transfer.amount <= max_transfer_amount,
+                "Transfer amount: {}, maximum allowed transfer amount: {}",
+                transfer.amount,
+                max_transfer_amount
+            );
+        }
+    }
+
+    /// Checks that the receiver of `transfer` is left with at most `max_wallet_balance`, once the
+    /// balances are updated.
+    ///
+    /// Panics if the receiver holds more than `max_wallet_balance` and is not exempt.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `transfer`: [`Transfer`], the executed transfer.
+    pub fn assert_within_wallet_limit(&self, ctx: &ContractContext, transfer: &Transfer) {
+        if let Some(max_wallet_balance) = self.max_wallet_balance {
+            let balance = self.balance_of(&transfer.to);
+            assert!(
+                self.is_limit_exempt(ctx, &transfer.to) || balance <= max_wallet_balance,
+                "Receiver balance: {}, maximum allowed wallet balance: {}",
+                balance,
+                max_wallet_balance
+            );
+        }
+    }
+
+    /// Whether `account` is exempt from the transfer limits.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `account`: [`Address`], the account to check.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`bool`], true if `account` is the owner, the contract itself or exempted.
+    pub fn is_limit_exempt(&self, ctx: &ContractContext, account: &Address) -> bool {
+        *account == self.owner
+            || *account == ctx.contract_address
+            || self.limit_exempt.contains(account)
+    }
+}
+
+/// Set the maximum amount of a single transfer and the maximum balance of a single wallet. Only
+/// the owner can call this action.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `max_transfer_amount`: [`Option`]<[`u128`]>, maximum amount of a transfer, `None` for no
+///     limit.
+///   * `max_wallet_balance`: [`Option`]<[`u128`]>, maximum balance a transfer can leave its
+///     receiver with, `None` for no limit.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "limits")]
+#[action(shortname = 0x33)]
+fn set_transfer_limits(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    max_transfer_amount: Option<u128>,
+    max_wallet_balance: Option<u128>,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(
+        state,
+        ctx,
+        "set_transfer_limits",
+        max_transfer_amount,
+        max_wallet_balance
+    );
+    state.max_transfer_amount = max_transfer_amount;
+    state.max_wallet_balance = max_wallet_balance;
+    state.assert_invariants();
+
+    state
+}
+
+/// Exempt `account` from the transfer limits, or remove its exemption. Only the owner can call
+/// this action.
+///
+/// Panics if the caller is not the owner or if `exempt` does not change the exemption of
+/// `account`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account to exempt or unexempt.
+///   * `exempt`: [`bool`], true to exempt `account`, false to remove its exemption.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "limits")]
+#[action(shortname = 0x34)]
+fn set_limit_exempt(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+    exempt: bool,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_limit_exempt", account, exempt);
+    assert!(
+        state.limit_exempt.contains(&account) != exempt,
+        "Account is already {}.",
+        if exempt { "exempt" } else { "not exempt" }
+    );
+    if exempt {
+        state.limit_exempt.push(account);
+    } else {
+        state.limit_exempt.retain(|exempted| *exempted != account);
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Set the minimum amount of a single transfer, zero allows any amount. Transfers to or from the
+/// treasury held by the contract itself are not limited. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `min_transfer_amount`: [`u128`], minimum amount of a transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "limits")]
+#[action(shortname = 0x5c)]
+fn set_min_transfer_amount(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    min_transfer_amount: u128,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_min_transfer_amount", min_transfer_amount);
+    state.min_transfer_amount = min_transfer_amount;
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,95 @@
+//! Tests of the anti-whale limits: the maximum transfer and wallet balance, exempted accounts, and
+//! the minimum transfer amount which treasury transfers bypass.
+
+use super::{set_limit_exempt, set_min_transfer_amount, set_transfer_limits};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, new_state, token_address};
+use crate::token::TashiTokenState;
+
+/// A token limiting transfers to 1_000 and wallets to 1_500, where account 1 holds 10_000.
+fn limited_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    set_transfer_limits(context(account(0)), state, Some(1_000), Some(1_500))
+}
+
+/// Transfers `amount` from `from` to `to`, as called by `from`.
+fn transfer(state: &mut TashiTokenState, from: u8, to: u8, amount: u128) {
+    state.execute_transfer(
+        &context(account(from)),
+        Transfer::new(account(from), account(to), amount, None),
+    );
+}
+
+#[test]
+fn transfers_within_the_limits_pass() {
+    let mut state = limited_state();
+    transfer(&mut state, 1, 2, 1_000);
+    transfer(&mut state, 1, 2, 500);
+
+    assert_eq!(state.balance_of(&account(2)), 1_500);
+}
+
+#[test]
+#[should_panic(expected = "Transfer amount: 1001, maximum allowed transfer amount: 1000")]
+fn transfer_above_the_maximum_is_rejected() {
+    transfer(&mut limited_state(), 1, 2, 1_001);
+}
+
+#[test]
+#[should_panic(expected = "Receiver balance: 1501, maximum allowed wallet balance: 1500")]
+fn transfer_beyond_the_maximum_wallet_balance_is_rejected() {
+    let mut state = limited_state();
+    transfer(&mut state, 1, 2, 1_000);
+    transfer(&mut state, 1, 2, 501);
+}
+
+#[test]
+fn owner_and_exempt_accounts_are_not_limited() {
+    let mut state = set_limit_exempt(context(account(0)), limited_state(), account(3), true);
+    transfer(&mut state, 0, 3, 50_000);
+    transfer(&mut state, 3, 4, 1_000);
+
+    assert_eq!(state.balance_of(&account(3)), 49_000);
+    assert_eq!(state.balance_of(&account(4)), 1_000);
+}
+
+#[test]
+#[should_panic(expected = "Receiver balance: 2000, maximum allowed wallet balance: 1500")]
+fn removing_the_exemption_limits_the_account_again() {
+    let state = set_limit_exempt(context(account(0)), limited_state(), account(3), true);
+    let mut state = set_limit_exempt(context(account(0)), state, account(3), false);
+    transfer(&mut state, 0, 3, 2_000);
+}
+
+#[test]
+#[should_panic(expected = "Transfer amount: 9, minimum allowed transfer amount: 10")]
+fn transfer_below_the_minimum_is_rejected() {
+    let mut state = set_min_transfer_amount(context(account(0)), limited_state(), 10);
+    transfer(&mut state, 1, 2, 9);
+}
+
+#[test]
+fn treasury_transfers_bypass_the_minimum() {
+    let mut state = set_min_transfer_amount(context(account(0)), limited_state(), 10);
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), token_address(), 5, None),
+    );
+    state.execute_transfer(
+        &context(token_address()),
+        Transfer::new(token_address(), account(2), 1, None),
+    );
+
+    assert_eq!(state.balance_of(&token_address()), 4);
+    assert_eq!(state.balance_of(&account(2)), 1);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can perform this action.")]
+fn only_the_owner_sets_the_limits() {
+    set_transfer_limits(context(account(1)), new_state(), None, None);
+}
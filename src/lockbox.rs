@@ -0,0 +1,174 @@
+//! Self-imposed locks on part of a balance, a commitment device for holders guarding against their
+//! own impulsive selling. A holder locks an amount of its balance until a chosen time, and
+//! transfers, escrows and payouts leaving less than the locked amount are rejected until then. A
+//! lock can be topped up and extended, but never shortened or lifted early. Once it expires it no
+//! longer applies.
+#![cfg_attr(not(feature = "lockbox"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use read_write_state_derive::ReadWriteState;
+
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "lockbox"))]
+mod tests;
+
+/// Part of a balance its holder locked.
+///
+/// ### Fields:
+///
+///   * `amount`: [`u128`], amount of the balance that cannot be transferred.
+///   * `until`: [`i64`], block production time the lock ends at, in milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfLock {
+    pub amount: u128,
+    pub until: i64,
+}
+
+/// Rejects transfers leaving the sender with less than the amount it locked.
+pub struct LockboxGuard;
+
+impl TransferHook for LockboxGuard {
+    fn after_transfer(ctx: &ContractContext, state: &mut TashiTokenState, transfer: &Transfer) {
+        state.assert_self_locked_amount_kept(ctx, &transfer.from, 0);
+    }
+}
+
+impl TashiTokenState {
+    /// Gets the amount of the balance of `account` locked by itself at time `now`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account to query.
+    ///   * `now`: [`i64`], the current block production time.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount locked, zero if the lock has expired.
+    pub fn self_locked_amount(&self, account: &Address, now: i64) -> u128 {
+        self.self_locks
+            .get(account)
+            .filter(|lock| now < lock.until)
+            .map_or(0, |lock| lock.amount)
+    }
+
+    /// Checks that `account` keeps at least the amount it locked once `amount` is taken from its
+    /// balance.
+    ///
+    /// Panics if less than the locked amount would be left.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context.
+    ///   * `account`: [`Address`], the account to check.
+    ///   * `amount`: [`u128`], amount still to be taken from the balance.
+    pub fn assert_self_locked_amount_kept(
+        &self,
+        ctx: &ContractContext,
+        account: &Address,
+        amount: u128,
+    ) {
+        let locked = self.self_locked_amount(account, ctx.block_production_time);
+        assert!(
+            self.balance_of(account).saturating_sub(amount) >= locked,
+            "Transfer exceeds transferable balance, self-locked amount: {}",
+            locked
+        );
+    }
+}
+
+/// Lock `amount` of the balance of caller address until `until`, making it non-transferable until
+/// then. An unexpired lock is topped up with `amount`, and `until` cannot end it earlier. An
+/// expired lock is replaced. Locks cannot be lifted early.
+///
+/// Panics if `amount` is zero, if `until` is not in the future or would shorten the current lock,
+/// or if the caller holds less than the locked amount.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to lock.
+///   * `until`: [`i64`], block production time the lock ends at, in milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "lockbox")]
+#[action(shortname = 0xe2)]
+fn lock_until(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+    until: i64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "lock_until");
+    assert!(amount > 0, "Locked amount cannot be zero.");
+    let now = ctx.block_production_time;
+    assert!(until > now, "Lock must end in the future.");
+    let locked = state.self_locked_amount(&ctx.sender, now);
+    if locked > 0 {
+        let current_until = state.self_locks.get(&ctx.sender).unwrap().until;
+        assert!(
+            until >= current_until,
+            "Lock cannot be shortened, it ends at: {}",
+            current_until
+        );
+    }
+    let total = locked
+        .checked_add(amount)
+        .expect("Overflow when adding to locked amount.");
+    assert!(
+        state.balance_of(&ctx.sender) >= total,
+        "Insufficient balance: {}, minimum required balance: {}",
+        state.balance_of(&ctx.sender),
+        total
+    );
+    state.self_locks.insert(
+        ctx.sender,
+        SelfLock {
+            amount: total,
+            until,
+        },
+    );
+    state.assert_invariants();
+
+    state
+}
+
+/// Extend the lock of caller address until `until`.
+///
+/// Panics if the caller has no unexpired lock or if `until` does not end it later.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `until`: [`i64`], block production time the lock ends at, in milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "lockbox")]
+#[action(shortname = 0xe3)]
+fn extend_lock(ctx: ContractContext, mut state: TashiTokenState, until: i64) -> TashiTokenState {
+    crate::metrics::count_action!(state, "extend_lock");
+    assert!(
+        state.self_locked_amount(&ctx.sender, ctx.block_production_time) > 0,
+        "No active lock to extend."
+    );
+    let lock = state.self_locks.get_mut(&ctx.sender).unwrap();
+    assert!(
+        until > lock.until,
+        "Lock can only be extended, it ends at: {}",
+        lock.until
+    );
+    lock.until = until;
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,54 @@
+//! Tests of self-imposed locks, on transfers and on spends that skip the transfer hooks, such as
+//! escrows.
+
+use super::lock_until;
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state};
+use crate::token::TashiTokenState;
+
+/// Balance of account 1 in the tests.
+const BALANCE: u128 = 1_000;
+
+/// Block production time the lock of account 1 ends at.
+const UNTIL: i64 = 10_000;
+
+/// A token where account 1 holds [`BALANCE`] and locked half of it until [`UNTIL`].
+fn locked_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), BALANCE, None),
+    );
+    lock_until(context(account(1)), state, BALANCE / 2, UNTIL)
+}
+
+#[test]
+fn unlocked_part_can_be_escrowed() {
+    let mut state = locked_state();
+    state.escrow(&context(account(1)), account(1), BALANCE / 2);
+    assert_eq!(state.balance_of(&account(1)), BALANCE / 2);
+}
+
+#[test]
+#[should_panic(expected = "Transfer exceeds transferable balance, self-locked amount: 500")]
+fn locked_part_cannot_be_escrowed() {
+    let mut state = locked_state();
+    state.escrow(&context(account(1)), account(1), BALANCE / 2 + 1);
+}
+
+#[test]
+#[should_panic(expected = "Transfer exceeds transferable balance, self-locked amount: 500")]
+fn locked_part_cannot_be_transferred() {
+    let mut state = locked_state();
+    state.execute_transfer(
+        &context(account(1)),
+        Transfer::new(account(1), account(2), BALANCE / 2 + 1, None),
+    );
+}
+
+#[test]
+fn expired_lock_no_longer_applies() {
+    let mut state = locked_state();
+    state.escrow(&context_at(account(1), UNTIL), account(1), BALANCE);
+    assert_eq!(state.balance_of(&account(1)), 0);
+}
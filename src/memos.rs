@@ -0,0 +1,171 @@
+//! Transfers carrying a memo, an opaque reference such as the user id an exchange attributes a
+//! deposit to, sparing a deposit address per user. The memo is recorded in state with the transfer
+//! and the transaction that made it. The memo log keeps the latest [`MEMO_LOG_CAPACITY`] entries,
+//! indexers follow it as it grows.
+#![cfg_attr(not(feature = "memos"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "memos")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "memos")]
+use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::Hash;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "memos")]
+use crate::hooks::Transfer;
+#[cfg(feature = "memos")]
+use crate::token::{amount_value, Amount, TashiTokenState};
+
+#[cfg(all(test, feature = "memos"))]
+mod tests;
+
+/// The number of entries the memo log keeps, older entries are dropped.
+pub const MEMO_LOG_CAPACITY: usize = 1024;
+
+/// The longest memo of a transfer, in bytes.
+pub const MAX_TRANSFER_MEMO_LENGTH: usize = 64;
+
+/// A transfer with a memo, recorded in the memo log.
+///
+/// ### Fields:
+///
+///   * `transaction`: [`Hash`], transaction of the transfer.
+///   * `from`: [`Address`], sender of the transfer.
+///   * `to`: [`Address`], receiver of the transfer.
+///   * `amount`: [`u128`], amount transferred.
+///   * `memo`: [`Vec`]<[`u8`]>, the memo.
+///   * `timestamp`: [`i64`], block production time of the transfer, in milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoEntry {
+    pub transaction: Hash,
+    pub from: Address,
+    pub to: Address,
+    pub amount: u128,
+    pub memo: Vec<u8>,
+    pub timestamp: i64,
+}
+
+#[cfg(feature = "memos")]
+impl TashiTokenState {
+    /// Executes the transfer of `amount` tokens from `from` to `to` and records it with `memo`
+    /// in the memo log, dropping the oldest entry when the log is full.
+    ///
+    /// Panics if the memo is longer than [`MAX_TRANSFER_MEMO_LENGTH`] or if the transfer fails.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `transfer`: [`Transfer`], the transfer to execute.
+    ///   * `memo`: [`Vec`]<[`u8`]>, the memo.
+    fn execute_transfer_with_memo(
+        &mut self,
+        ctx: &ContractContext,
+        transfer: Transfer,
+        memo: Vec<u8>,
+    ) {
+        assert!(
+            memo.len() <= MAX_TRANSFER_MEMO_LENGTH,
+            "Memo length: {}, maximum is: {}",
+            memo.len(),
+            MAX_TRANSFER_MEMO_LENGTH
+        );
+        let entry = MemoEntry {
+            transaction: ctx.original_transaction,
+            from: transfer.from,
+            to: transfer.to,
+            amount: transfer.amount,
+            memo,
+            timestamp: ctx.block_production_time,
+        };
+        self.execute_transfer(ctx, transfer);
+        if self.memo_log.len() >= MEMO_LOG_CAPACITY {
+            self.memo_log.remove(0);
+        }
+        self.memo_log.push(entry);
+    }
+}
+
+/// Transfer `amount` tokens to address `to` from caller address, recording `memo` with the
+/// transfer. Otherwise handled as by `transfer`.
+///
+/// Panics if the memo is longer than [`MAX_TRANSFER_MEMO_LENGTH`] or if there is insufficient
+/// balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`Amount`], amount to transfer.
+///   * `memo`: [`Vec`]<[`u8`]>, the reference to record.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "memos")]
+#[action(shortname = 0xbc)]
+fn transfer_with_memo(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    to: Address,
+    amount: Amount,
+    memo: Vec<u8>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "transfer_with_memo");
+    let supply = state.supply();
+    state.execute_transfer_with_memo(
+        &ctx,
+        Transfer::new(ctx.sender, to, amount_value(amount), None),
+        memo,
+    );
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Transfer `amount` tokens from address `from` to address `to` on behalf of the caller, recording
+/// `memo` with the transfer. Otherwise handled as by `transfer_from`.
+///
+/// Panics if the memo is longer than [`MAX_TRANSFER_MEMO_LENGTH`], or if there is insufficient
+/// allowance in caller account or insufficient balance in `from` account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `from`: [`Address`], account to transfer from.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`Amount`], amount to transfer.
+///   * `memo`: [`Vec`]<[`u8`]>, the reference to record.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "memos")]
+#[action(shortname = 0xbd)]
+fn transfer_from_with_memo(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    from: Address,
+    to: Address,
+    amount: Amount,
+    memo: Vec<u8>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "transfer_from_with_memo");
+    let supply = state.supply();
+    state.execute_transfer_with_memo(
+        &ctx,
+        Transfer::new(from, to, amount_value(amount), Some(ctx.sender)),
+        memo,
+    );
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
@@ -0,0 +1,51 @@
+//! Tests of transfers with a memo: recording the memo with the transfer, the capacity of the memo
+//! log, and memos that are too long.
+
+use super::{transfer_with_memo, MEMO_LOG_CAPACITY};
+use crate::testing::{account, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::{stored_amount, TashiTokenState};
+
+/// Transfers `amount` from the owner to account 1 at block production time `time`, with the memo
+/// `memo`.
+fn send(state: TashiTokenState, amount: u128, time: i64, memo: &[u8]) -> TashiTokenState {
+    let (state, _) = transfer_with_memo(
+        context_at(account(0), time),
+        state,
+        account(1),
+        stored_amount(amount),
+        memo.to_vec(),
+    );
+    state
+}
+
+#[test]
+fn memo_is_recorded_with_the_transfer() {
+    let state = send(new_state(), 300, 1_000, b"user-42");
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 300);
+    assert_eq!(state.balance_of(&account(1)), 300);
+    let entry = &state.memo_log[0];
+    assert_eq!(entry.from, account(0));
+    assert_eq!(entry.to, account(1));
+    assert_eq!(entry.amount, 300);
+    assert_eq!(entry.memo, b"user-42");
+    assert_eq!(entry.timestamp, 1_000);
+}
+
+#[test]
+fn full_memo_log_drops_the_oldest_entry() {
+    let mut state = new_state();
+    for time in 0..=MEMO_LOG_CAPACITY as i64 {
+        state = send(state, 1, time, b"deposit");
+    }
+
+    assert_eq!(state.memo_log.len(), MEMO_LOG_CAPACITY);
+    assert_eq!(state.memo_log[0].timestamp, 1);
+    assert_eq!(state.balance_of(&account(1)), MEMO_LOG_CAPACITY as u128 + 1);
+}
+
+#[test]
+#[should_panic(expected = "Memo length: 65, maximum is: 64")]
+fn long_memo_is_rejected() {
+    send(new_state(), 300, 1_000, &[0; 65]);
+}
@@ -0,0 +1,231 @@
+//! Usage metrics, aggregate counters giving every deployment basic analytics without an indexer.
+//! Every transfer is counted with its volume, and its sender is added to a HyperLogLog sketch of
+//! [`SENDER_SKETCH_REGISTERS`] registers estimating the number of distinct senders within a few
+//! percent in constant space. Every action is also counted by name, privileged actions through
+//! the admin log macro. Getters are not counted, so reading the token leaves its state unchanged.
+#![cfg_attr(not(feature = "usage-metrics"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "usage-metrics")]
+use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::Hash;
+use read_write_rpc_derive::ReadWriteRPC;
+
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::TashiTokenState;
+#[cfg(feature = "usage-metrics")]
+use crate::views::return_value;
+
+#[cfg(all(test, feature = "usage-metrics"))]
+mod tests;
+
+/// The number of registers of the sketch of distinct senders, each of one byte.
+pub const SENDER_SKETCH_REGISTERS: usize = 256;
+
+/// Bits of the sender hash selecting the register.
+const REGISTER_INDEX_BITS: u32 = 8;
+
+/// The bias correction constant of the HyperLogLog estimate for 256 registers, in ten thousandths.
+const SKETCH_ALPHA: u128 = 7183;
+
+/// The natural logarithm of 2, in 32.32 fixed point.
+const LN_2: u128 = 2_977_044_472;
+
+/// Counts an action by name in the usage metrics. Does nothing without the `usage-metrics`
+/// feature.
+macro_rules! count_action {
+    ($state:expr, $action:expr) => {
+        if cfg!(feature = "usage-metrics") {
+            $state.count_action($action);
+        }
+    };
+}
+pub(crate) use count_action;
+
+/// The number of times an action was called.
+///
+/// ### Fields:
+///
+///   * `action`: [`String`], name of the action.
+///   * `count`: [`u64`], number of successful calls.
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub struct ActionCount {
+    pub action: String,
+    pub count: u64,
+}
+
+/// A snapshot of the usage metrics.
+///
+/// ### Fields:
+///
+///   * `transfer_count`: [`u64`], number of transfers since initialization.
+///   * `transfer_volume`: [`u128`], amount transferred since initialization.
+///   * `unique_senders`: [`u64`], estimated number of distinct accounts that sent a transfer.
+///   * `action_counts`: [`Vec`]<[`ActionCount`]>, calls of each counted action, by name.
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub struct UsageMetrics {
+    pub transfer_count: u64,
+    pub transfer_volume: u128,
+    pub unique_senders: u64,
+    pub action_counts: Vec<ActionCount>,
+}
+
+/// Base 2 logarithm of `x`, in 32.32 fixed point.
+///
+/// ### Parameters:
+///
+///   * `x`: [`u64`], a positive number.
+///
+/// ### Returns:
+///
+/// The [`u128`] logarithm, rounded down.
+fn log2_fixed(x: u64) -> u128 {
+    let integer = 63 - x.leading_zeros();
+    let mut mantissa = (u128::from(x) << 32) >> integer;
+    let mut log = u128::from(integer) << 32;
+    for bit in (0..32).rev() {
+        mantissa = (mantissa * mantissa) >> 32;
+        if mantissa >= 2 << 32 {
+            mantissa >>= 1;
+            log |= 1 << bit;
+        }
+    }
+    log
+}
+
+/// Counts every transfer and its sender in the usage metrics.
+pub struct UsageMetricsRecorder;
+
+impl TransferHook for UsageMetricsRecorder {
+    fn after_transfer(_ctx: &ContractContext, state: &mut TashiTokenState, transfer: &Transfer) {
+        state.transfer_count += 1;
+        state.transfer_volume = state.transfer_volume.saturating_add(transfer.amount);
+        state.add_to_sender_sketch(&transfer.from);
+    }
+}
+
+impl TashiTokenState {
+    /// Counts a call of `action` in the usage metrics.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `action`: [`str`], name of the action.
+    pub fn count_action(&mut self, action: &str) {
+        let count = self.action_count(action);
+        self.action_counts.insert(action.to_string(), count + 1);
+    }
+
+    /// Gets the number of calls of `action` counted in the usage metrics.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `action`: [`str`], name of the action.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u64`] number of calls.
+    pub fn action_count(&self, action: &str) -> u64 {
+        self.action_counts
+            .get(&action.to_string())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Adds `sender` to the sketch of distinct senders. The first bits of the hash of the sender
+    /// select a register, which keeps the largest rank of the first set bit among the remaining
+    /// bits.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `sender`: [`Address`], account that sent a transfer.
+    pub fn add_to_sender_sketch(&mut self, sender: &Address) {
+        let mut payload = Vec::new();
+        pbc_traits::ReadWriteRPC::rpc_write_to(sender, &mut payload)
+            .expect("Writing to a vector cannot fail.");
+        let digest = Hash::digest(payload);
+        let mut head = [0u8; 8];
+        head.copy_from_slice(&digest.bytes[..8]);
+        let hash = u64::from_be_bytes(head);
+
+        let index = (hash >> (64 - REGISTER_INDEX_BITS)) as usize;
+        let rank = ((hash << REGISTER_INDEX_BITS).leading_zeros() + 1)
+            .min(64 - REGISTER_INDEX_BITS + 1) as u8;
+        if self.sender_sketch.len() < SENDER_SKETCH_REGISTERS {
+            self.sender_sketch.resize(SENDER_SKETCH_REGISTERS, 0);
+        }
+        self.sender_sketch[index] = self.sender_sketch[index].max(rank);
+    }
+
+    /// Estimates the number of distinct senders from the sketch, with the HyperLogLog estimate
+    /// and linear counting while few registers are set.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u64`] estimated number of distinct senders.
+    pub fn unique_senders_estimate(&self) -> u64 {
+        if self.sender_sketch.is_empty() {
+            return 0;
+        }
+        let registers = SENDER_SKETCH_REGISTERS as u128;
+        let inverse_sum: u128 = self
+            .sender_sketch
+            .iter()
+            .map(|register| (1u128 << 64) >> register)
+            .sum();
+        let raw = SKETCH_ALPHA * registers * registers * (1 << 64) / (10_000 * inverse_sum);
+        let empty = self
+            .sender_sketch
+            .iter()
+            .filter(|register| **register == 0)
+            .count() as u64;
+        if raw <= registers * 5 / 2 && empty > 0 {
+            let log_ratio = log2_fixed(SENDER_SKETCH_REGISTERS as u64) - log2_fixed(empty);
+            return (registers * ((log_ratio * LN_2) >> 32) >> 32) as u64;
+        }
+        raw as u64
+    }
+
+    /// Gets a snapshot of the usage metrics.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`UsageMetrics`] of the token.
+    pub fn usage_metrics(&self) -> UsageMetrics {
+        UsageMetrics {
+            transfer_count: self.transfer_count,
+            transfer_volume: self.transfer_volume,
+            unique_senders: self.unique_senders_estimate(),
+            action_counts: self
+                .action_counts
+                .iter()
+                .map(|(action, count)| ActionCount {
+                    action: action.clone(),
+                    count: *count,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Return the usage metrics of the token to the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the metrics as [`UsageMetrics`] in the return data.
+#[cfg(feature = "usage-metrics")]
+#[action(shortname = 0xfe)]
+fn query_usage_metrics(
+    _ctx: ContractContext,
+    state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let events = return_value(state.usage_metrics());
+    state.assert_invariants();
+    (state, events)
+}
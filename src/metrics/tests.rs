@@ -0,0 +1,99 @@
+//! Tests of the usage metrics: the bounds of the estimate of distinct senders, merging sketches of
+//! overlapping senders, and the actions counted by name.
+
+use pbc_contract_common::address::{Address, AddressType};
+
+use crate::testing::{account, context, new_state};
+use crate::token::{stored_amount, transfer, TashiTokenState};
+use crate::views::query_balance_of;
+
+/// The account with `index` in the first four bytes of its identifier, to add more distinct
+/// senders than [`account`] has.
+fn sender(index: u32) -> Address {
+    let mut identifier = [0u8; 20];
+    identifier[..4].copy_from_slice(&index.to_be_bytes());
+    Address {
+        address_type: AddressType::Account,
+        identifier,
+    }
+}
+
+/// A token whose sketch holds every sender in `senders`.
+fn sketched_state(senders: std::ops::Range<u32>) -> TashiTokenState {
+    let mut state = new_state();
+    for index in senders {
+        state.add_to_sender_sketch(&sender(index));
+    }
+    state
+}
+
+/// Asserts that the estimate of the distinct senders of `state` is within 7% of `expected`.
+fn assert_estimate_near(state: &TashiTokenState, expected: u64) {
+    let estimate = state.unique_senders_estimate();
+    assert!(
+        estimate.abs_diff(expected) * 100 <= expected * 7,
+        "Estimate: {}, is not within 7% of: {}",
+        estimate,
+        expected
+    );
+}
+
+#[test]
+fn empty_sketch_estimates_no_senders() {
+    assert_eq!(new_state().unique_senders_estimate(), 0);
+}
+
+#[test]
+fn few_senders_are_counted_by_linear_counting() {
+    assert_eq!(sketched_state(0..1).unique_senders_estimate(), 1);
+    assert!(sketched_state(0..10).unique_senders_estimate().abs_diff(10) <= 1);
+}
+
+#[test]
+fn repeated_sender_is_counted_once() {
+    let mut state = sketched_state(0..100);
+    let sketch = state.sender_sketch.clone();
+    for _ in 0..10 {
+        state.add_to_sender_sketch(&sender(7));
+    }
+
+    assert_eq!(state.sender_sketch, sketch);
+}
+
+#[test]
+fn estimate_stays_within_bounds() {
+    for senders in [100, 1_000, 10_000] {
+        assert_estimate_near(&sketched_state(0..senders), u64::from(senders));
+    }
+}
+
+#[test]
+fn merged_sketches_estimate_the_union() {
+    let first = sketched_state(0..600);
+    let second = sketched_state(400..1_000);
+    let mut merged = new_state();
+    merged.sender_sketch = first
+        .sender_sketch
+        .iter()
+        .zip(second.sender_sketch.iter())
+        .map(|(a, b)| *a.max(b))
+        .collect();
+
+    assert_eq!(merged.sender_sketch, sketched_state(0..1_000).sender_sketch);
+    assert_estimate_near(&merged, 1_000);
+}
+
+#[test]
+fn actions_are_counted_but_getters_are_not() {
+    let (state, _) = transfer(
+        context(account(0)),
+        new_state(),
+        account(1),
+        stored_amount(10),
+    );
+    let (state, _) = query_balance_of(context(account(1)), state, account(1));
+
+    assert_eq!(state.action_count("transfer"), 1);
+    assert_eq!(state.action_count("query_balance_of"), 0);
+    assert_eq!(state.transfer_count, 1);
+}
@@ -0,0 +1,273 @@
+//! Migration from a legacy token contract. Either holders claim the balance a snapshot assigns
+//! them, by proving it against a merkle root, or they swap legacy tokens one to one, locking them
+//! in this contract for good.
+#![cfg_attr(not(feature = "migration"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "migration")]
+use pbc_contract_common::context::{CallbackContext, ContractContext};
+#[cfg(feature = "migration")]
+use pbc_contract_common::events::EventGroup;
+#[cfg(feature = "migration")]
+use pbc_contract_common::shortname::ShortnameCallback;
+use pbc_contract_common::Hash;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "migration")]
+use crate::mpc20::call_transfer_from;
+#[cfg(feature = "migration")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "migration"))]
+mod tests;
+
+/// Shortname of the callback confirming legacy tokens were locked.
+#[cfg(feature = "migration")]
+const MIGRATE_CALLBACK_SHORTNAME: u32 = 0x04;
+
+/// Domain separator prefixed to every snapshot leaf.
+const SNAPSHOT_LEAF_DOMAIN: &[u8] = b"TASHI_TOKEN_MIGRATION";
+
+/// How holders of the legacy token migrate.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MigrationMode {
+    /// Holders claim the balance assigned by a snapshot with merkle root `root`.
+    #[discriminant(0)]
+    Snapshot { root: Hash },
+    /// Holders swap legacy tokens one to one.
+    #[discriminant(1)]
+    Swap {},
+}
+
+/// A migration from `legacy_token`.
+///
+/// ### Fields:
+///
+///   * `legacy_token`: [`Address`], the MPC-20 token being replaced.
+///   * `mode`: [`MigrationMode`], how holders migrate.
+///   * `migrated`: [`u128`], amount minted to migrating holders so far.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Migration {
+    pub legacy_token: Address,
+    pub mode: MigrationMode,
+    pub migrated: u128,
+}
+
+/// Computes the snapshot leaf assigning `amount` tokens to `holder`.
+///
+/// ### Parameters:
+///
+///   * `holder`: [`Address`], the account in the snapshot.
+///   * `amount`: [`u128`], the balance assigned to `holder`.
+///
+/// ### Returns:
+///
+/// The [`Hash`] of the leaf.
+pub fn snapshot_leaf(holder: &Address, amount: u128) -> Hash {
+    let mut leaf = SNAPSHOT_LEAF_DOMAIN.to_vec();
+    leaf.extend_from_slice(&holder.identifier);
+    leaf.extend_from_slice(&amount.to_be_bytes());
+    Hash::digest(leaf)
+}
+
+/// Computes the merkle root `proof` leads to from `leaf`. Every pair of nodes is hashed in sorted
+/// order, so proofs need no left or right markers.
+///
+/// ### Parameters:
+///
+///   * `leaf`: [`Hash`], the leaf being proven.
+///   * `proof`: [`Vec`]<[`Hash`]>, the sibling of every node on the path to the root.
+///
+/// ### Returns:
+///
+/// The [`Hash`] of the root.
+pub fn merkle_root(leaf: Hash, proof: &[Hash]) -> Hash {
+    proof.iter().fold(leaf, |node, sibling| {
+        let (first, second) = if node <= *sibling {
+            (node, *sibling)
+        } else {
+            (*sibling, node)
+        };
+        let mut pair = first.bytes.to_vec();
+        pair.extend_from_slice(&second.bytes);
+        Hash::digest(pair)
+    })
+}
+
+/// Start a migration from `legacy_token`, replacing any migration in progress. Only the owner can
+/// call this action, through the admin timelock with the `admin-timelock` feature.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `legacy_token`: [`Address`], the MPC-20 token being replaced.
+///   * `mode`: [`MigrationMode`], how holders migrate.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "migration")]
+#[action(shortname = 0x43)]
+pub(crate) fn configure_migration(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    legacy_token: Address,
+    mode: MigrationMode,
+) -> TashiTokenState {
+    state.assert_owner_or_timelock(&ctx);
+    crate::audit::log_admin_action!(state, ctx, "configure_migration", legacy_token, mode);
+    let migrated = state
+        .migration
+        .as_ref()
+        .map_or(0, |migration| migration.migrated);
+    state.migration = Some(Migration {
+        legacy_token,
+        mode,
+        migrated,
+    });
+    state.assert_invariants();
+
+    state
+}
+
+/// Claim the `amount` tokens the snapshot assigns to caller address.
+///
+/// Panics if no snapshot migration is configured, if the caller already claimed, or if `proof`
+/// does not prove the assignment.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], the balance the snapshot assigns to the caller.
+///   * `proof`: [`Vec`]<[`Hash`]>, merkle proof of the assignment.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "migration")]
+#[action(shortname = 0x44)]
+fn claim_migration(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+    proof: Vec<Hash>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "claim_migration");
+    let supply = state.supply();
+    let migration = state
+        .migration
+        .as_mut()
+        .expect("No migration is configured.");
+    let MigrationMode::Snapshot { root } = migration.mode else {
+        panic!("The migration does not use a snapshot.");
+    };
+    assert!(
+        merkle_root(snapshot_leaf(&ctx.sender, amount), &proof) == root,
+        "Invalid migration proof."
+    );
+    assert!(
+        !state.migration_claims.contains_key(&ctx.sender),
+        "Caller has already claimed its migration."
+    );
+    migration.migrated += amount;
+    state.migration_claims.insert(ctx.sender, amount);
+    state.mint_balance(ctx.sender, amount);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Swap `amount` legacy tokens of caller address for new tokens. The legacy tokens are moved to
+/// this contract with `transfer_from`, so the caller must approve this contract on the legacy
+/// token first. New tokens are minted once the transfer is confirmed.
+///
+/// Panics if no swap migration is configured or if `amount` is zero.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount of legacy tokens to swap.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "migration")]
+#[action(shortname = 0x45)]
+fn migrate_legacy_tokens(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "migrate_legacy_tokens");
+    let migration = state
+        .migration
+        .as_ref()
+        .expect("No migration is configured.");
+    assert!(
+        migration.mode == MigrationMode::Swap {},
+        "The migration does not swap legacy tokens."
+    );
+    assert!(amount > 0, "Swapped amount cannot be zero.");
+
+    let mut event_group = EventGroup::builder();
+    call_transfer_from(
+        &mut event_group,
+        migration.legacy_token,
+        ctx.sender,
+        ctx.contract_address,
+        amount,
+    );
+    event_group
+        .with_callback(ShortnameCallback::from_u32(MIGRATE_CALLBACK_SHORTNAME))
+        .argument(ctx.sender)
+        .argument(amount)
+        .done();
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Mint the new tokens of a swap once its legacy tokens were locked in this contract.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the legacy token transfer.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `holder`: [`Address`], account swapping its legacy tokens.
+///   * `amount`: [`u128`], amount of legacy tokens swapped.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "migration")]
+#[callback(shortname = 0x04)]
+fn migrate_legacy_tokens_callback(
+    _ctx: ContractContext,
+    callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+    holder: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let supply = state.supply();
+    if callback_ctx.success {
+        state.migration.as_mut().unwrap().migrated += amount;
+        state.mint_balance(holder, amount);
+    }
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
@@ -0,0 +1,109 @@
+//! Tests of migrations: snapshot claims proven against a merkle root, claimed once, and swaps of
+//! legacy tokens minted only once their transfer is confirmed.
+
+use pbc_contract_common::Hash;
+
+use super::{
+    claim_migration, configure_migration, merkle_root, migrate_legacy_tokens,
+    migrate_legacy_tokens_callback, snapshot_leaf, MigrationMode,
+};
+use crate::testing::{
+    account, admin_context, callback_context, context, contract, new_state, token_address,
+    INITIAL_SUPPLY,
+};
+use crate::token::TashiTokenState;
+
+/// The snapshot leaves assigning 500 to account 1 and 700 to account 2.
+fn leaves() -> (Hash, Hash) {
+    (
+        snapshot_leaf(&account(1), 500),
+        snapshot_leaf(&account(2), 700),
+    )
+}
+
+/// A token migrating from legacy contract 1 with `mode`.
+fn migration_state(mode: MigrationMode) -> TashiTokenState {
+    configure_migration(admin_context(), new_state(), contract(1), mode)
+}
+
+/// A token migrating from the snapshot of [`leaves`].
+fn snapshot_state() -> TashiTokenState {
+    let (first, second) = leaves();
+    migration_state(MigrationMode::Snapshot {
+        root: merkle_root(first, &[second]),
+    })
+}
+
+#[test]
+fn holders_claim_their_snapshot_balances() {
+    let (first, second) = leaves();
+    let (state, _) = claim_migration(context(account(1)), snapshot_state(), 500, vec![second]);
+    let (state, _) = claim_migration(context(account(2)), state, 700, vec![first]);
+
+    assert_eq!(state.balance_of(&account(1)), 500);
+    assert_eq!(state.balance_of(&account(2)), 700);
+    assert_eq!(state.migration.as_ref().unwrap().migrated, 1_200);
+    assert_eq!(state.supply(), INITIAL_SUPPLY + 1_200);
+}
+
+#[test]
+#[should_panic(expected = "Invalid migration proof.")]
+fn claim_of_another_amount_is_rejected() {
+    let (_, second) = leaves();
+    claim_migration(context(account(1)), snapshot_state(), 501, vec![second]);
+}
+
+#[test]
+#[should_panic(expected = "Caller has already claimed its migration.")]
+fn snapshot_balance_is_claimed_once() {
+    let (_, second) = leaves();
+    let (state, _) = claim_migration(context(account(1)), snapshot_state(), 500, vec![second]);
+    claim_migration(context(account(1)), state, 500, vec![second]);
+}
+
+#[test]
+fn swap_mints_once_the_legacy_tokens_are_locked() {
+    let state = migration_state(MigrationMode::Swap {});
+    let (state, events) = migrate_legacy_tokens(context(account(1)), state, 300);
+    assert_eq!(events.len(), 1);
+    assert_eq!(state.balance_of(&account(1)), 0);
+
+    let (state, _) = migrate_legacy_tokens_callback(
+        context(token_address()),
+        callback_context(true),
+        state,
+        account(1),
+        300,
+    );
+    assert_eq!(state.balance_of(&account(1)), 300);
+    assert_eq!(state.migration.as_ref().unwrap().migrated, 300);
+}
+
+#[test]
+fn failed_swap_mints_nothing() {
+    let (state, _) = migrate_legacy_tokens_callback(
+        context(token_address()),
+        callback_context(false),
+        migration_state(MigrationMode::Swap {}),
+        account(1),
+        300,
+    );
+
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.supply(), INITIAL_SUPPLY);
+}
+
+#[test]
+#[should_panic(expected = "The migration does not swap legacy tokens.")]
+fn swap_of_a_snapshot_migration_is_rejected() {
+    migrate_legacy_tokens(context(account(1)), snapshot_state(), 300);
+}
+
+#[test]
+fn replacing_the_migration_keeps_the_migrated_amount() {
+    let (_, second) = leaves();
+    let (state, _) = claim_migration(context(account(1)), snapshot_state(), 500, vec![second]);
+    let state = configure_migration(admin_context(), state, contract(2), MigrationMode::Swap {});
+
+    assert_eq!(state.migration.as_ref().unwrap().migrated, 500);
+}
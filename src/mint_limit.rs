@@ -0,0 +1,128 @@
+//! A throttle on minting, bounding the damage of a compromised minting key. At most
+//! `max_per_epoch` tokens can be minted by `mint` per epoch of `epoch_length`, whoever sends it. An
+//! epoch starts with the first mint after the previous one ended. The owner can tighten the limit
+//! directly, but loosening or removing it takes the admin timelock or a governance proposal.
+#![cfg_attr(not(feature = "mint-rate-limit"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+#[cfg(feature = "mint-rate-limit")]
+use pbc_contract_common::context::ContractContext;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "mint-rate-limit", feature = "mintable"))]
+mod tests;
+
+/// The largest amount that can be minted per epoch.
+///
+/// ### Fields:
+///
+///   * `max_per_epoch`: [`u128`], amount that can be minted per epoch.
+///   * `epoch_length`: [`i64`], length of an epoch, in milliseconds.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MintRateLimit {
+    pub max_per_epoch: u128,
+    pub epoch_length: i64,
+}
+
+impl MintRateLimit {
+    /// Whether this limit allows at most what `other` allows, both per epoch and per time.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `other`: [`Option`]<[`MintRateLimit`]>, the limit to compare with, none if unlimited.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`bool`], true if this limit is at least as tight as `other`.
+    pub fn is_tighter_than(&self, other: &Option<MintRateLimit>) -> bool {
+        other.map_or(true, |other| {
+            self.max_per_epoch <= other.max_per_epoch && self.epoch_length >= other.epoch_length
+        })
+    }
+}
+
+impl TashiTokenState {
+    /// Replaces the mint rate limit, starting a new epoch with the next mint.
+    ///
+    /// Panics if the limit has a non-positive epoch length.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `limit`: [`Option`]<[`MintRateLimit`]>, the new limit, none to remove it.
+    pub fn set_mint_rate_limit_config(&mut self, limit: Option<MintRateLimit>) {
+        if let Some(limit) = limit {
+            assert!(limit.epoch_length > 0, "Epoch length must be positive.");
+        }
+        self.mint_rate_limit = limit;
+        self.mint_epoch_start = 0;
+        self.minted_in_epoch = 0;
+    }
+
+    /// Counts `amount` against the mint rate limit of the epoch at time `now`. Does nothing without
+    /// a limit.
+    ///
+    /// Panics if minting `amount` exceeds what is left of the limit in the epoch.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    ///   * `amount`: [`u128`], amount being minted.
+    pub fn consume_mint_quota(&mut self, now: i64, amount: u128) {
+        let Some(limit) = self.mint_rate_limit else {
+            return;
+        };
+        if self.minted_in_epoch == 0 || now >= self.mint_epoch_start + limit.epoch_length {
+            self.mint_epoch_start = now;
+            self.minted_in_epoch = 0;
+        }
+        let minted = self.minted_in_epoch.saturating_add(amount);
+        assert!(
+            minted <= limit.max_per_epoch,
+            "Mint of: {}, exceeds the rest of the epoch limit: {}, until: {}",
+            amount,
+            limit.max_per_epoch - self.minted_in_epoch,
+            self.mint_epoch_start + limit.epoch_length
+        );
+        self.minted_in_epoch = minted;
+    }
+}
+
+/// Set the mint rate limit, none to remove it. The owner can tighten the limit directly, a looser
+/// limit can only be set through the admin timelock or by governance.
+///
+/// Panics if the caller is neither the owner nor the admin timelock, if the owner loosens the
+/// limit, or if the limit has a non-positive epoch length.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `limit`: [`Option`]<[`MintRateLimit`]>, the new limit.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "mint-rate-limit")]
+#[action(shortname = 0x9f)]
+pub(crate) fn set_mint_rate_limit(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    limit: Option<MintRateLimit>,
+) -> TashiTokenState {
+    if ctx.sender != ctx.contract_address {
+        state.assert_owner(&ctx.sender);
+        assert!(
+            limit.is_some_and(|limit| limit.is_tighter_than(&state.mint_rate_limit)),
+            "Only the admin timelock or governance can loosen the mint rate limit."
+        );
+    }
+    crate::audit::log_admin_action!(state, ctx, "set_mint_rate_limit", limit);
+    state.set_mint_rate_limit_config(limit);
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,84 @@
+//! Tests of the mint rate limit: the quota of an epoch, the next epoch, and who can tighten or
+//! loosen the limit.
+
+use super::{set_mint_rate_limit, MintRateLimit};
+use crate::mintable::mint;
+use crate::testing::{account, admin_context, context, new_state, token_address};
+use crate::token::TashiTokenState;
+
+/// Length of an epoch of the limit.
+const DAY: i64 = 86_400_000;
+
+/// A limit of `max_per_epoch` a day.
+fn limit(max_per_epoch: u128) -> Option<MintRateLimit> {
+    Some(MintRateLimit {
+        max_per_epoch,
+        epoch_length: DAY,
+    })
+}
+
+/// A token limiting mints to 1_000 a day.
+fn limited_state() -> TashiTokenState {
+    set_mint_rate_limit(context(account(0)), new_state(), limit(1_000))
+}
+
+/// Mints `amount` to account 1 at block production time `time`.
+fn mint_at(state: TashiTokenState, amount: u128, time: i64) -> TashiTokenState {
+    let mut ctx = admin_context();
+    ctx.block_production_time = time;
+    mint(ctx, state, account(1), amount).0
+}
+
+#[test]
+fn mints_within_the_epoch_quota_pass() {
+    let state = mint_at(limited_state(), 600, 1_000);
+    let state = mint_at(state, 400, DAY);
+
+    assert_eq!(state.balance_of(&account(1)), 1_000);
+    assert_eq!(state.minted_in_epoch, 1_000);
+}
+
+#[test]
+#[should_panic(expected = "Mint of: 1, exceeds the rest of the epoch limit: 0, until: 86401000")]
+fn mint_beyond_the_epoch_quota_is_rejected() {
+    let state = mint_at(limited_state(), 1_000, 1_000);
+    mint_at(state, 1, DAY);
+}
+
+#[test]
+fn next_epoch_starts_with_a_full_quota() {
+    let state = mint_at(limited_state(), 1_000, 1_000);
+    let state = mint_at(state, 1_000, DAY + 1_000);
+
+    assert_eq!(state.balance_of(&account(1)), 2_000);
+    assert_eq!(state.mint_epoch_start, DAY + 1_000);
+}
+
+#[test]
+fn owner_tightens_the_limit() {
+    let state = set_mint_rate_limit(context(account(0)), limited_state(), limit(500));
+
+    assert_eq!(state.mint_rate_limit.unwrap().max_per_epoch, 500);
+}
+
+#[test]
+#[should_panic(expected = "Only the admin timelock or governance can loosen the mint rate limit.")]
+fn owner_cannot_loosen_the_limit() {
+    set_mint_rate_limit(context(account(0)), limited_state(), limit(2_000));
+}
+
+#[test]
+#[should_panic(expected = "Only the admin timelock or governance can loosen the mint rate limit.")]
+fn owner_cannot_remove_the_limit() {
+    set_mint_rate_limit(context(account(0)), limited_state(), None);
+}
+
+#[test]
+fn contract_loosens_and_removes_the_limit() {
+    let state = set_mint_rate_limit(context(token_address()), limited_state(), limit(2_000));
+    assert_eq!(state.mint_rate_limit.unwrap().max_per_epoch, 2_000);
+
+    let state = set_mint_rate_limit(context(token_address()), state, None);
+    let state = mint_at(state, 1_000_000, 0);
+    assert_eq!(state.balance_of(&account(1)), 1_000_000);
+}
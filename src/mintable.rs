@@ -0,0 +1,42 @@
+//! Minting new tokens into circulation.
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::events::EventGroup;
+
+use crate::token::TashiTokenState;
+
+/// Mint `amount` new tokens to address `receiver`, increasing the total supply. Only the owner can
+/// call this action, through the admin timelock with the `admin-timelock` feature.
+///
+/// Panics if the caller is not the owner, if `amount` exceeds what is left of the mint rate limit, or
+/// if adding `amount` causes an overflow.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `receiver`: [`Address`], account to mint to.
+///   * `amount`: [`u128`], amount to mint.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[action(shortname = 0x11)]
+pub(crate) fn mint(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    receiver: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    state.assert_owner_or_timelock(&ctx);
+    crate::audit::log_admin_action!(state, ctx, "mint", receiver, amount);
+    state.consume_mint_quota(ctx.block_production_time, amount);
+    let supply = state.supply();
+    state.mint_balance(receiver, amount);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
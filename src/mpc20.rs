@@ -0,0 +1,59 @@
+//! Interactions with other MPC-20 token contracts, such as BYOC coins.
+// only used by optional features
+#![allow(dead_code)]
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::events::EventGroupBuilder;
+use pbc_contract_common::shortname::Shortname;
+
+/// Shortname of the MPC-20 `transfer` action.
+const TRANSFER_SHORTNAME: u32 = 0x01;
+
+/// Shortname of the MPC-20 `transfer_from` action.
+const TRANSFER_FROM_SHORTNAME: u32 = 0x03;
+
+/// Adds an interaction transferring `amount` of `token` from this contract to `receiver`.
+///
+/// ### Parameters:
+///
+///   * `event_group`: [`EventGroupBuilder`], event group to add the interaction to.
+///   * `token`: [`Address`], the MPC-20 token contract.
+///   * `receiver`: [`Address`], account to transfer to.
+///   * `amount`: [`u128`], amount to transfer.
+pub fn call_transfer(
+    event_group: &mut EventGroupBuilder,
+    token: Address,
+    receiver: Address,
+    amount: u128,
+) {
+    event_group
+        .call(token, Shortname::from_u32(TRANSFER_SHORTNAME))
+        .argument(receiver)
+        .argument(amount)
+        .done();
+}
+
+/// Adds an interaction transferring `amount` of `token` from `from` to `receiver`, spending the
+/// allowance `from` gave this contract.
+///
+/// ### Parameters:
+///
+///   * `event_group`: [`EventGroupBuilder`], event group to add the interaction to.
+///   * `token`: [`Address`], the MPC-20 token contract.
+///   * `from`: [`Address`], account to transfer from.
+///   * `receiver`: [`Address`], account to transfer to.
+///   * `amount`: [`u128`], amount to transfer.
+pub fn call_transfer_from(
+    event_group: &mut EventGroupBuilder,
+    token: Address,
+    from: Address,
+    receiver: Address,
+    amount: u128,
+) {
+    event_group
+        .call(token, Shortname::from_u32(TRANSFER_FROM_SHORTNAME))
+        .argument(from)
+        .argument(receiver)
+        .argument(amount)
+        .done();
+}
@@ -0,0 +1,358 @@
+//! Multiple tokens in one contract, for issuers of many small-supply assets. The token of the
+//! contract keeps id [`MAIN_TOKEN_ID`] and all its actions, while the owner creates further tokens
+//! with their own name, symbol, decimals, supply and balances. Batch transfers move amounts of any
+//! ids at once, the main token through the full transfer path. Transfers of other ids only move
+//! balances, the transfer hooks of the main token do not apply to them.
+#![cfg_attr(not(feature = "multi-token"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "multi-token")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "multi-token")]
+use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "multi-token")]
+use crate::hooks::Transfer;
+use crate::token::BalanceMap;
+#[cfg(feature = "multi-token")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "multi-token"))]
+mod tests;
+
+/// The id of the token of the contract itself.
+pub const MAIN_TOKEN_ID: u64 = 0;
+
+/// A token of the contract other than the main token.
+///
+/// ### Fields:
+///
+///   * `name`: [`String`], name of the token.
+///   * `symbol`: [`String`], symbol of the token.
+///   * `decimals`: [`u8`], number of decimals of the token.
+///   * `total_supply`: [`u128`], amount of the token in existence.
+///   * `balances`: [`SortedVecMap`]<[`Address`], [`u128`]>, balance of each holder.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubToken {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub total_supply: u128,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub balances: SortedVecMap<Address, u128>,
+}
+
+impl SubToken {
+    /// Gets the balance of `owner`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account to query the balance of.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount owned by the account.
+    pub fn balance_of(&self, owner: &Address) -> u128 {
+        self.balances.get(owner).copied().unwrap_or(0)
+    }
+
+    /// Adds `amount` to the balance of `account`.
+    ///
+    /// Panics if adding `amount` causes an overflow.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account to credit.
+    ///   * `amount`: [`u128`], amount to add.
+    pub fn credit(&mut self, account: Address, amount: u128) {
+        let balance = self
+            .balance_of(&account)
+            .checked_add(amount)
+            .expect("Overflow when adding to balance.");
+        self.balances.insert_balance(account, balance);
+    }
+
+    /// Subtracts `amount` from the balance of `account`.
+    ///
+    /// Panics if there is insufficient balance in `account`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account to debit.
+    ///   * `amount`: [`u128`], amount to subtract.
+    pub fn debit(&mut self, account: Address, amount: u128) {
+        let balance = self.balance_of(&account);
+        let new_balance = balance.checked_sub(amount).unwrap_or_else(|| {
+            panic!(
+                "Insufficient balance: {}, minimum required balance: {}",
+                balance, amount
+            )
+        });
+        self.balances.insert_balance(account, new_balance);
+    }
+}
+
+/// A transfer of an amount of one of the tokens of the contract.
+///
+/// ### Fields:
+///
+///   * `id`: [`u64`], id of the token.
+///   * `to`: [`Address`], account receiving the tokens.
+///   * `amount`: [`u128`], amount to transfer.
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub struct IdTransfer {
+    pub id: u64,
+    pub to: Address,
+    pub amount: u128,
+}
+
+#[cfg(feature = "multi-token")]
+impl TashiTokenState {
+    /// Gets the token `id` other than the main token.
+    ///
+    /// Panics if no token has the id.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `id`: [`u64`], id of the token.
+    ///
+    /// ### Returns:
+    ///
+    /// A mutable reference to the [`SubToken`].
+    fn sub_token(&mut self, id: u64) -> &mut SubToken {
+        self.sub_tokens
+            .get_mut(&id)
+            .unwrap_or_else(|| panic!("Unknown token id: {}", id))
+    }
+
+    /// Executes the transfers of `transfers` from `from`, the main token through
+    /// [`TashiTokenState::execute_transfer`] and other tokens by moving their balances.
+    ///
+    /// Panics if a token id is unknown, if there is insufficient balance or allowance, or if a
+    /// hook rejects a transfer of the main token.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `from`: [`Address`], account the tokens belong to.
+    ///   * `transfers`: [`Vec`]<[`IdTransfer`]>, the transfers.
+    ///   * `spender`: [`Option`]<[`Address`]>, account transferring on behalf of `from`, if any.
+    fn execute_id_transfers(
+        &mut self,
+        ctx: &ContractContext,
+        from: Address,
+        transfers: Vec<IdTransfer>,
+        spender: Option<Address>,
+    ) {
+        for IdTransfer { id, to, amount } in transfers {
+            if id == MAIN_TOKEN_ID {
+                self.execute_transfer(ctx, Transfer::new(from, to, amount, spender));
+            } else {
+                let token = self.sub_token(id);
+                token.debit(from, amount);
+                token.credit(to, amount);
+            }
+        }
+    }
+}
+
+/// Create a token with its own `name`, `symbol` and `decimals`, minting `initial_supply` to the
+/// owner. The token gets the next free id. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `name`: [`String`], name of the token.
+///   * `symbol`: [`String`], symbol of the token.
+///   * `decimals`: [`u8`], number of decimals of the token.
+///   * `initial_supply`: [`u128`], amount minted to the owner.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "multi-token")]
+#[action(shortname = 0xaf)]
+fn create_token_id(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    name: String,
+    symbol: String,
+    decimals: u8,
+    initial_supply: u128,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(
+        state,
+        ctx,
+        "create_token_id",
+        name,
+        symbol,
+        decimals,
+        initial_supply
+    );
+    let mut token = SubToken {
+        name,
+        symbol,
+        decimals,
+        total_supply: initial_supply,
+        balances: SortedVecMap::new(),
+    };
+    token.credit(ctx.sender, initial_supply);
+    state.sub_tokens.insert(state.next_token_id, token);
+    state.next_token_id += 1;
+    state.assert_invariants();
+
+    state
+}
+
+/// Mint `amount` of the token `id` to `receiver`. Only the owner can call this action, the main
+/// token is minted with `mint`.
+///
+/// Panics if the caller is not the owner, if the id is unknown or is the main token, or if minting
+/// causes an overflow.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `id`: [`u64`], id of the token.
+///   * `receiver`: [`Address`], account receiving the tokens.
+///   * `amount`: [`u128`], amount to mint.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "multi-token")]
+#[action(shortname = 0xb0)]
+fn mint_token_id(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    id: u64,
+    receiver: Address,
+    amount: u128,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "mint_token_id", id, receiver, amount);
+    let token = state.sub_token(id);
+    token.total_supply = token
+        .total_supply
+        .checked_add(amount)
+        .expect("Overflow when adding to total supply.");
+    token.credit(receiver, amount);
+    state.assert_invariants();
+
+    state
+}
+
+/// Burn `amount` of the token `id` from caller address. The main token is burned with `burn`.
+///
+/// Panics if the id is unknown or is the main token, or if there is insufficient balance in caller
+/// account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `id`: [`u64`], id of the token.
+///   * `amount`: [`u128`], amount to burn.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "multi-token")]
+#[action(shortname = 0xb1)]
+fn burn_token_id(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    id: u64,
+    amount: u128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "burn_token_id");
+    let token = state.sub_token(id);
+    token.debit(ctx.sender, amount);
+    token.total_supply -= amount;
+    state.assert_invariants();
+
+    state
+}
+
+/// Transfer amounts of any tokens of the contract from caller address in one transaction.
+///
+/// Panics if a token id is unknown, if there is insufficient balance in caller account for any of
+/// the transfers, or if a hook rejects a transfer of the main token.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `transfers`: [`Vec`]<[`IdTransfer`]>, the transfers.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "multi-token")]
+#[action(shortname = 0xb2)]
+fn batch_transfer_ids(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    transfers: Vec<IdTransfer>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "batch_transfer_ids");
+    let supply = state.supply();
+    state.execute_id_transfers(&ctx, ctx.sender, transfers, None);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Transfer amounts of any tokens of the contract from `from` in one transaction. The caller must
+/// be an operator of `from`, or for the main token only, have enough allowance.
+///
+/// Panics if a token id is unknown, if the caller is not an operator of `from` and transfers
+/// another token than the main token, if there is insufficient balance or allowance, or if a hook
+/// rejects a transfer of the main token.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `from`: [`Address`], account the tokens belong to.
+///   * `transfers`: [`Vec`]<[`IdTransfer`]>, the transfers.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "multi-token")]
+#[action(shortname = 0xb3)]
+fn batch_transfer_ids_from(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    from: Address,
+    transfers: Vec<IdTransfer>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "batch_transfer_ids_from");
+    assert!(
+        state.is_operator(&from, &ctx.sender)
+            || transfers
+                .iter()
+                .all(|transfer| transfer.id == MAIN_TOKEN_ID),
+        "Only operators can transfer tokens other than the main token on behalf of an account."
+    );
+    let supply = state.supply();
+    state.execute_id_transfers(&ctx, from, transfers, Some(ctx.sender));
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
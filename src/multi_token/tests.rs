@@ -0,0 +1,123 @@
+//! Tests of multiple tokens: creating, minting and burning tokens other than the main token, and
+//! batch transfers across ids by holders and operators.
+
+use super::{
+    batch_transfer_ids, batch_transfer_ids_from, burn_token_id, create_token_id, mint_token_id,
+    IdTransfer, MAIN_TOKEN_ID,
+};
+use crate::allowance::set_operator;
+use crate::testing::{account, context, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token whose owner created token 1 with a supply of 500.
+fn multi_token_state() -> TashiTokenState {
+    create_token_id(
+        context(account(0)),
+        new_state(),
+        "Gem".to_string(),
+        "GEM".to_string(),
+        0,
+        500,
+    )
+}
+
+/// Moves 100 of the main token and 200 of token 1 to account 1.
+fn transfers() -> Vec<IdTransfer> {
+    vec![
+        IdTransfer {
+            id: MAIN_TOKEN_ID,
+            to: account(1),
+            amount: 100,
+        },
+        IdTransfer {
+            id: 1,
+            to: account(1),
+            amount: 200,
+        },
+    ]
+}
+
+#[test]
+fn created_token_is_minted_to_the_owner() {
+    let state = multi_token_state();
+
+    let token = state.sub_tokens.get(&1).unwrap();
+    assert_eq!(token.symbol, "GEM");
+    assert_eq!(token.total_supply, 500);
+    assert_eq!(token.balance_of(&account(0)), 500);
+    assert_eq!(state.next_token_id, 2);
+    assert_eq!(state.supply(), INITIAL_SUPPLY);
+}
+
+#[test]
+fn batch_transfer_moves_every_id() {
+    let (state, _) = batch_transfer_ids(context(account(0)), multi_token_state(), transfers());
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 100);
+    assert_eq!(state.balance_of(&account(1)), 100);
+    let token = state.sub_tokens.get(&1).unwrap();
+    assert_eq!(token.balance_of(&account(0)), 300);
+    assert_eq!(token.balance_of(&account(1)), 200);
+}
+
+#[test]
+fn operator_transfers_every_id() {
+    let state = set_operator(context(account(0)), multi_token_state(), account(2), true);
+    let (state, _) = batch_transfer_ids_from(context(account(2)), state, account(0), transfers());
+
+    assert_eq!(state.balance_of(&account(1)), 100);
+    assert_eq!(
+        state.sub_tokens.get(&1).unwrap().balance_of(&account(1)),
+        200
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "Only operators can transfer tokens other than the main token on behalf of an account."
+)]
+fn spender_cannot_transfer_other_ids() {
+    batch_transfer_ids_from(
+        context(account(2)),
+        multi_token_state(),
+        account(0),
+        transfers(),
+    );
+}
+
+#[test]
+fn minting_and_burning_change_the_supply_of_the_id() {
+    let state = mint_token_id(context(account(0)), multi_token_state(), 1, account(1), 300);
+    let state = burn_token_id(context(account(1)), state, 1, 100);
+
+    let token = state.sub_tokens.get(&1).unwrap();
+    assert_eq!(token.total_supply, 700);
+    assert_eq!(token.balance_of(&account(1)), 200);
+    assert_eq!(state.supply(), INITIAL_SUPPLY);
+}
+
+#[test]
+#[should_panic(expected = "Unknown token id: 0")]
+fn main_token_is_not_minted_by_id() {
+    mint_token_id(
+        context(account(0)),
+        multi_token_state(),
+        MAIN_TOKEN_ID,
+        account(1),
+        300,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Insufficient balance: 500, minimum required balance: 501")]
+fn batch_transfer_beyond_the_balance_of_an_id_is_rejected() {
+    batch_transfer_ids(
+        context(account(0)),
+        multi_token_state(),
+        vec![IdTransfer {
+            id: 1,
+            to: account(1),
+            amount: 501,
+        }],
+    );
+}
@@ -0,0 +1,131 @@
+//! Batches of this contract's own actions executed in one transaction, so a wallet can submit a
+//! common flow with a single signature. Each call is executed by the action it names, with the
+//! caller of the batch as sender, in order. A failing call fails the whole transaction, so the
+//! batch is atomic.
+#![cfg_attr(not(feature = "multicall"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "multicall")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "multicall")]
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+
+#[cfg(all(feature = "multicall", feature = "burnable"))]
+use crate::token::amount_value;
+use crate::token::Amount;
+#[cfg(feature = "multicall")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "multicall"))]
+mod tests;
+
+/// The largest number of calls in a batch.
+pub const MAX_MULTICALL_CALLS: usize = 32;
+
+/// A call of a batch, named after the action it executes and carrying its arguments.
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub enum Call {
+    /// Executes `transfer`.
+    #[discriminant(0)]
+    Transfer { to: Address, amount: Amount },
+    /// Executes `transfer_from`.
+    #[discriminant(1)]
+    TransferFrom {
+        from: Address,
+        to: Address,
+        amount: Amount,
+    },
+    /// Executes `approve`.
+    #[discriminant(2)]
+    Approve { spender: Address, amount: Amount },
+    /// Executes `approve_relative`.
+    #[discriminant(3)]
+    ApproveRelative { spender: Address, delta: i128 },
+    /// Executes `set_operator`.
+    #[discriminant(4)]
+    SetOperator { operator: Address, enabled: bool },
+    /// Executes `burn`, with the `burnable` feature.
+    #[cfg(feature = "burnable")]
+    #[discriminant(5)]
+    Burn { amount: Amount },
+    /// Executes `claim_rewards`, with the `rewards` feature.
+    #[cfg(feature = "rewards")]
+    #[discriminant(6)]
+    ClaimRewards {},
+}
+
+/// Copies the context of the batch for one of its calls.
+#[cfg(feature = "multicall")]
+fn call_context(ctx: &ContractContext) -> ContractContext {
+    ContractContext {
+        contract_address: ctx.contract_address,
+        sender: ctx.sender,
+        block_time: ctx.block_time,
+        block_production_time: ctx.block_production_time,
+        current_transaction: ctx.current_transaction,
+        original_transaction: ctx.original_transaction,
+    }
+}
+
+/// Execute `calls` in order on behalf of caller address, as if each was sent as its own action.
+/// The supply notifications of the calls are replaced by one for the whole batch.
+///
+/// Panics if `calls` is empty or has more than [`MAX_MULTICALL_CALLS`] calls, or if any call
+/// panics.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TashiTokenState`], current state of the contract.
+///   * `calls`: [`Vec`]<[`Call`]>, the calls to execute.
+///
+/// ### Returns
+///
+/// The updated [`TashiTokenState`] state and the supply notifications.
+#[cfg(feature = "multicall")]
+#[action(shortname = 0x80)]
+fn multicall(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    calls: Vec<Call>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "multicall");
+    assert!(!calls.is_empty(), "A batch must have a call.");
+    assert!(
+        calls.len() <= MAX_MULTICALL_CALLS,
+        "Batch has: {} calls, maximum is: {}",
+        calls.len(),
+        MAX_MULTICALL_CALLS
+    );
+
+    let supply = state.supply();
+    for call in calls {
+        let call_ctx = call_context(&ctx);
+        state = match call {
+            Call::Transfer { to, amount } => crate::token::transfer(call_ctx, state, to, amount).0,
+            Call::TransferFrom { from, to, amount } => {
+                crate::allowance::transfer_from(call_ctx, state, from, to, amount).0
+            }
+            Call::Approve { spender, amount } => {
+                crate::allowance::approve(call_ctx, state, spender, amount)
+            }
+            Call::ApproveRelative { spender, delta } => {
+                crate::allowance::approve_relative(call_ctx, state, spender, delta)
+            }
+            Call::SetOperator { operator, enabled } => {
+                crate::allowance::set_operator(call_ctx, state, operator, enabled)
+            }
+            #[cfg(feature = "burnable")]
+            Call::Burn { amount } => crate::burnable::burn(call_ctx, state, amount_value(amount)).0,
+            #[cfg(feature = "rewards")]
+            Call::ClaimRewards {} => crate::rewards::claim_rewards(call_ctx, state),
+        };
+    }
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
@@ -0,0 +1,92 @@
+//! Tests of batches: calls run in the order given, and a failing call fails the whole batch.
+
+use super::{multicall, Call, MAX_MULTICALL_CALLS};
+use crate::testing::{account, context, new_state, INITIAL_SUPPLY};
+use crate::token::{stored_amount, TashiTokenState};
+
+/// Runs `calls` as a batch sent by the owner.
+fn run(calls: Vec<Call>) -> TashiTokenState {
+    multicall(context(account(0)), new_state(), calls).0
+}
+
+#[test]
+fn calls_run_in_order() {
+    let approve = || Call::Approve {
+        spender: account(1),
+        amount: stored_amount(100),
+    };
+    let increase = || Call::ApproveRelative {
+        spender: account(1),
+        delta: 50,
+    };
+
+    let state = run(vec![approve(), increase()]);
+    assert_eq!(state.allowance(&account(0), &account(1)), 150);
+
+    let state = run(vec![increase(), approve()]);
+    assert_eq!(state.allowance(&account(0), &account(1)), 100);
+}
+
+#[test]
+fn later_calls_see_the_effects_of_earlier_ones() {
+    let state = run(vec![
+        Call::Transfer {
+            to: account(1),
+            amount: stored_amount(INITIAL_SUPPLY - 100),
+        },
+        Call::Transfer {
+            to: account(2),
+            amount: stored_amount(100),
+        },
+    ]);
+
+    assert_eq!(state.balance_of(&account(0)), 0);
+    assert_eq!(state.balance_of(&account(1)), INITIAL_SUPPLY - 100);
+    assert_eq!(state.balance_of(&account(2)), 100);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient balance: 0, minimum required balance: 1")]
+fn failing_call_fails_the_whole_batch() {
+    // the first transfer succeeds on its own, but the batch panics as one transaction, so the
+    // state it would have left behind is never committed
+    run(vec![
+        Call::Transfer {
+            to: account(1),
+            amount: stored_amount(INITIAL_SUPPLY),
+        },
+        Call::Transfer {
+            to: account(2),
+            amount: stored_amount(1),
+        },
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "A batch must have a call.")]
+fn empty_batch_is_rejected() {
+    run(vec![]);
+}
+
+#[test]
+#[should_panic(expected = "Batch has: 33 calls, maximum is: 32")]
+fn batch_beyond_the_maximum_is_rejected() {
+    let calls = (0..=MAX_MULTICALL_CALLS)
+        .map(|_| Call::Transfer {
+            to: account(1),
+            amount: stored_amount(1),
+        })
+        .collect();
+    run(calls);
+}
+
+#[test]
+#[cfg(feature = "burnable")]
+fn burn_takes_an_amount() {
+    let state = run(vec![Call::Burn {
+        amount: stored_amount(300),
+    }]);
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 300);
+    assert_eq!(state.supply(), INITIAL_SUPPLY - 300);
+}
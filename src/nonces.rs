@@ -0,0 +1,133 @@
+//! Per-account nonces, increasing by one with every signed message the account authorizes so a
+//! signature cannot be replayed. Backends follow the nonce of an account to detect transactions
+//! they missed. With the `nonces` feature the owner may also have every transfer bump the nonce of
+//! its sending account, and other contracts read nonces with a getter.
+#![cfg_attr(
+    not(any(feature = "nonces", feature = "sponsorship")),
+    allow(dead_code)
+)]
+
+use pbc_contract_common::address::Address;
+#[cfg(feature = "nonces")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "nonces")]
+use pbc_contract_common::events::EventGroup;
+
+#[cfg(feature = "nonces")]
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::TashiTokenState;
+#[cfg(feature = "nonces")]
+use crate::views::return_value;
+
+#[cfg(all(test, feature = "nonces"))]
+mod tests;
+
+/// Bumps the nonce of the sending account of every transfer, if enabled by the owner. A transfer
+/// authorized by a signed message already consumed the nonce and bumps it only once.
+#[cfg(feature = "nonces")]
+pub struct TransferNonces;
+
+#[cfg(feature = "nonces")]
+impl TransferHook for TransferNonces {
+    fn after_transfer(_ctx: &ContractContext, state: &mut TashiTokenState, transfer: &Transfer) {
+        if state.nonces_on_transfers && !transfer.nonce_consumed {
+            state.bump_nonce(transfer.from);
+        }
+    }
+}
+
+impl TashiTokenState {
+    /// Gets the next nonce of `account`, zero if it never used one.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account to query the nonce of.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u64`] nonce.
+    pub fn nonce_of(&self, account: &Address) -> u64 {
+        self.nonces.get(account).copied().unwrap_or(0)
+    }
+
+    /// Increments the nonce of `account`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account to bump the nonce of.
+    pub fn bump_nonce(&mut self, account: Address) {
+        let nonce = self.nonce_of(&account);
+        self.nonces.insert(account, nonce + 1);
+    }
+
+    /// Consumes `nonce` of `account`, a message signed with it cannot be used again.
+    ///
+    /// Panics if `nonce` is not the next nonce of the account.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account that signed `nonce`.
+    ///   * `nonce`: [`u64`], the signed nonce.
+    pub fn consume_nonce(&mut self, account: Address, nonce: u64) {
+        let expected_nonce = self.nonce_of(&account);
+        assert!(
+            nonce == expected_nonce,
+            "Nonce: {}, expected nonce: {}",
+            nonce,
+            expected_nonce
+        );
+        self.bump_nonce(account);
+    }
+}
+
+/// Set whether every transfer bumps the nonce of its sending account, besides signed messages.
+/// Only the owner can call this action.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `enabled`: [`bool`], whether transfers bump nonces.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "nonces")]
+#[action(shortname = 0xbf)]
+fn set_nonces_on_transfers(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    enabled: bool,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_nonces_on_transfers", enabled);
+    state.nonces_on_transfers = enabled;
+    state.assert_invariants();
+
+    state
+}
+
+/// Return the next nonce of `account` to the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account to query the nonce of.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the nonce as a [`u64`] in the return data.
+#[cfg(feature = "nonces")]
+#[action(shortname = 0xc0)]
+fn query_nonce(
+    _ctx: ContractContext,
+    state: TashiTokenState,
+    account: Address,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let events = return_value(state.nonce_of(&account));
+    state.assert_invariants();
+    (state, events)
+}
@@ -0,0 +1,59 @@
+//! Tests of nonces bumped by transfers, and of transfers authorized by a signed message that
+//! already consumed the nonce.
+
+use super::set_nonces_on_transfers;
+use crate::hooks::Transfer;
+use crate::testing::{account, context, new_state};
+use crate::token::TashiTokenState;
+
+/// A token whose transfers bump nonces if `enabled`.
+fn nonce_state(enabled: bool) -> TashiTokenState {
+    set_nonces_on_transfers(context(account(0)), new_state(), enabled)
+}
+
+/// Executes `transfer` as a call from the owner.
+fn transfer(state: &mut TashiTokenState, transfer: Transfer) {
+    state.execute_transfer(&context(account(0)), transfer);
+}
+
+#[test]
+fn transfers_bump_the_nonce_of_the_sender_when_enabled() {
+    let mut state = nonce_state(true);
+    transfer(&mut state, Transfer::new(account(0), account(1), 100, None));
+    transfer(&mut state, Transfer::new(account(0), account(1), 100, None));
+
+    assert_eq!(state.nonce_of(&account(0)), 2);
+    assert_eq!(state.nonce_of(&account(1)), 0);
+}
+
+#[test]
+fn transfers_leave_nonces_alone_when_disabled() {
+    let mut state = nonce_state(false);
+    transfer(&mut state, Transfer::new(account(0), account(1), 100, None));
+
+    assert_eq!(state.nonce_of(&account(0)), 0);
+}
+
+#[test]
+fn signed_transfer_bumps_the_nonce_once() {
+    let mut state = nonce_state(true);
+    state.consume_nonce(account(0), 0);
+    transfer(
+        &mut state,
+        Transfer {
+            nonce_consumed: true,
+            ..Transfer::new(account(0), account(1), 100, None)
+        },
+    );
+
+    assert_eq!(state.nonce_of(&account(0)), 1);
+    state.consume_nonce(account(0), 1);
+}
+
+#[test]
+#[should_panic(expected = "Nonce: 0, expected nonce: 1")]
+fn consumed_nonce_cannot_be_reused() {
+    let mut state = nonce_state(true);
+    state.consume_nonce(account(0), 0);
+    state.consume_nonce(account(0), 0);
+}
@@ -0,0 +1,134 @@
+//! Supply observers, contracts notified whenever the supply of the token changes.
+//!
+//! Every action changing the supply invokes each registered observer at
+//! [`SUPPLY_OBSERVER_SHORTNAME`] with the arguments `(previous_supply: u128, new_supply: u128)`.
+//! Notifications are sent without a callback, so a failing observer cannot block the token. The
+//! list is capped at [`MAX_SUPPLY_OBSERVERS`] and each notification at
+//! [`SUPPLY_OBSERVER_GAS`], bounding the cost observers add to every supply change.
+#![cfg_attr(not(feature = "supply-observers"), allow(dead_code))]
+
+#[cfg(feature = "supply-observers")]
+use pbc_contract_common::address::Address;
+#[cfg(feature = "supply-observers")]
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::shortname::Shortname;
+
+use crate::token::TashiTokenState;
+
+/// Shortname the observers of the supply are invoked at.
+pub const SUPPLY_OBSERVER_SHORTNAME: u32 = 0x53;
+
+/// The largest number of contracts that can observe the supply.
+pub const MAX_SUPPLY_OBSERVERS: usize = 8;
+
+/// Gas allotted to each supply notification.
+pub const SUPPLY_OBSERVER_GAS: u64 = 5_000;
+
+impl TashiTokenState {
+    /// Builds the notifications of every observer, if the supply changed from `previous_supply`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `previous_supply`: [`u128`], the supply before the action, as given by
+    ///     [`TashiTokenState::supply`].
+    ///
+    /// ### Returns:
+    ///
+    /// The [`Vec`]<[`EventGroup`]> to return from the action, empty if the supply is unchanged or
+    /// nobody observes it.
+    pub fn supply_change_events(&self, previous_supply: u128) -> Vec<EventGroup> {
+        let new_supply = self.supply();
+        if new_supply == previous_supply || self.supply_observers.is_empty() {
+            return vec![];
+        }
+
+        let mut event_group = EventGroup::builder();
+        for observer in &self.supply_observers {
+            event_group
+                .call(*observer, Shortname::from_u32(SUPPLY_OBSERVER_SHORTNAME))
+                .argument(previous_supply)
+                .argument(new_supply)
+                .with_cost(SUPPLY_OBSERVER_GAS)
+                .done();
+        }
+        vec![event_group.build()]
+    }
+}
+
+/// Register `observer` to be notified of every supply change. Only the owner can call this
+/// action.
+///
+/// Panics if the caller is not the owner, if `observer` is already registered or if the observer
+/// list is full.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `observer`: [`Address`], contract to notify.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "supply-observers")]
+#[action(shortname = 0x54)]
+fn add_supply_observer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    observer: Address,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "add_supply_observer", observer);
+    assert!(
+        !state.supply_observers.contains(&observer),
+        "Observer is already registered."
+    );
+    assert!(
+        state.supply_observers.len() < MAX_SUPPLY_OBSERVERS,
+        "Observers: {}, maximum allowed observers: {}",
+        state.supply_observers.len(),
+        MAX_SUPPLY_OBSERVERS
+    );
+    state.supply_observers.push(observer);
+    state.assert_invariants();
+
+    state
+}
+
+/// Stop notifying `observer` of supply changes. The owner can remove any observer, an observer
+/// can remove itself.
+///
+/// Panics if the caller is neither the owner nor `observer`, or if `observer` is not registered.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `observer`: [`Address`], contract to stop notifying.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "supply-observers")]
+#[action(shortname = 0x55)]
+fn remove_supply_observer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    observer: Address,
+) -> TashiTokenState {
+    if ctx.sender != observer {
+        state.assert_owner(&ctx.sender);
+        crate::audit::log_admin_action!(state, ctx, "remove_supply_observer", observer);
+    }
+    assert!(
+        state.supply_observers.contains(&observer),
+        "Observer is not registered."
+    );
+    state
+        .supply_observers
+        .retain(|registered| *registered != observer);
+    state.assert_invariants();
+
+    state
+}
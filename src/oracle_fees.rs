@@ -0,0 +1,168 @@
+//! Fees denominated in a reference unit such as a fiat currency. A price oracle pushes the price
+//! of one display unit of the token in reference units, and every transfer converts the flat fee
+//! and the minimum transfer amount from reference units to tokens at the latest price. The fee is
+//! taken from what the receiver is credited and goes to the treasury held by the contract itself.
+//! Until a price is pushed no reference fees apply, and transfers to or from the treasury are
+//! never charged.
+#![cfg_attr(not(feature = "oracle-fees"), allow(dead_code))]
+
+#[cfg(feature = "oracle-fees")]
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::{mul_div_ceil, TashiTokenState};
+use crate::units::unit_scale;
+
+#[cfg(all(test, feature = "oracle-fees"))]
+mod tests;
+
+/// Charges the flat reference fee on every transfer and rejects transfers below the reference
+/// minimum, both converted at the latest pushed price.
+pub struct ReferenceFees;
+
+impl TransferHook for ReferenceFees {
+    fn before_transfer(
+        ctx: &ContractContext,
+        state: &mut TashiTokenState,
+        transfer: &mut Transfer,
+    ) {
+        let treasury_transfer =
+            transfer.from == ctx.contract_address || transfer.to == ctx.contract_address;
+        if treasury_transfer || state.reference_price == 0 {
+            return;
+        }
+
+        let min_transfer_amount = state.reference_to_tokens(state.reference_min_transfer);
+        assert!(
+            transfer.amount >= min_transfer_amount,
+            "Transfer amount: {}, minimum allowed transfer amount: {}",
+            transfer.amount,
+            min_transfer_amount
+        );
+        let fee = state.reference_to_tokens(state.reference_flat_fee);
+        assert!(
+            fee <= transfer.received,
+            "Transfer amount: {}, does not cover the transfer fee: {}",
+            transfer.amount,
+            fee
+        );
+        transfer.take(fee);
+        state.credit(ctx.contract_address, fee);
+        state.collected_fees += fee;
+    }
+}
+
+impl TashiTokenState {
+    /// Converts an amount in reference units to tokens at the latest pushed price, rounding up.
+    ///
+    /// Panics if no price has been pushed.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `reference_amount`: [`u128`], the amount in reference units.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u128`] amount of tokens, in base units, worth `reference_amount`.
+    pub fn reference_to_tokens(&self, reference_amount: u128) -> u128 {
+        assert!(self.reference_price > 0, "No reference price is available.");
+        mul_div_ceil(
+            reference_amount,
+            unit_scale(self.decimals),
+            self.reference_price,
+        )
+    }
+}
+
+/// Set the oracle allowed to push the reference price, `None` removes it. The last pushed price
+/// stays in effect until the new oracle pushes one. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `oracle`: [`Option`]<[`Address`]>, account pushing the price.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "oracle-fees")]
+#[action(shortname = 0x6e)]
+fn set_price_oracle(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    oracle: Option<Address>,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_price_oracle", oracle);
+    state.price_oracle = oracle;
+    state.assert_invariants();
+
+    state
+}
+
+/// Push the price of one display unit of the token, in reference units. Only the price oracle can
+/// call this action.
+///
+/// Panics if the caller is not the price oracle or if `price` is zero.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `price`: [`u128`], reference units one display unit of the token is worth.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "oracle-fees")]
+#[action(shortname = 0x6f)]
+fn push_price(ctx: ContractContext, mut state: TashiTokenState, price: u128) -> TashiTokenState {
+    crate::metrics::count_action!(state, "push_price");
+    assert!(
+        state.price_oracle == Some(ctx.sender),
+        "Only the price oracle can push the price."
+    );
+    assert!(price > 0, "Price cannot be zero.");
+    state.reference_price = price;
+    state.reference_price_updated_at = ctx.block_production_time;
+    state.assert_invariants();
+
+    state
+}
+
+/// Set the flat fee of a transfer and the minimum amount of a single transfer, both in reference
+/// units. Zero disables either. Only the owner can call this action, through the admin timelock
+/// with the `admin-timelock` feature.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `flat_fee`: [`u128`], fee charged on every transfer, in reference units.
+///   * `min_transfer`: [`u128`], minimum amount of a transfer, in reference units.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "oracle-fees")]
+#[action(shortname = 0x70)]
+pub(crate) fn set_reference_fees(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    flat_fee: u128,
+    min_transfer: u128,
+) -> TashiTokenState {
+    state.assert_owner_or_timelock(&ctx);
+    crate::audit::log_admin_action!(state, ctx, "set_reference_fees", flat_fee, min_transfer);
+    state.reference_flat_fee = flat_fee;
+    state.reference_min_transfer = min_transfer;
+    state.assert_invariants();
+
+    state
+}
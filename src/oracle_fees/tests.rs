@@ -0,0 +1,80 @@
+//! Tests of reference fees: the fee and minimum converted at the pushed price, rounding in favor
+//! of the treasury, and transfers which are never charged.
+
+use super::{push_price, set_price_oracle, set_reference_fees};
+use crate::hooks::Transfer;
+use crate::testing::{account, admin_context, context, new_state, token_address, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// Reference units one display unit of the token is worth, so one base unit is worth 100.
+const PRICE: u128 = 10_000_000_000;
+
+/// A token charging a fee of 100_000 reference units on transfers of at least 1_000_000, whose
+/// price oracle, account 5, pushed `price`.
+fn priced_state(price: u128) -> TashiTokenState {
+    let state = set_price_oracle(context(account(0)), new_state(), Some(account(5)));
+    let state = set_reference_fees(admin_context(), state, 100_000, 1_000_000);
+    push_price(context(account(5)), state, price)
+}
+
+/// Transfers `amount` from the owner to account 1.
+fn transfer(state: &mut TashiTokenState, amount: u128) {
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), amount, None),
+    );
+}
+
+#[test]
+fn fee_goes_to_the_treasury() {
+    let mut state = priced_state(PRICE);
+    transfer(&mut state, 100_000);
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 100_000);
+    assert_eq!(state.balance_of(&account(1)), 99_000);
+    assert_eq!(state.balance_of(&token_address()), 1_000);
+    assert_eq!(state.collected_fees, 1_000);
+}
+
+#[test]
+fn fee_is_rounded_up() {
+    let mut state = priced_state(3 * PRICE);
+    transfer(&mut state, 100_000);
+
+    assert_eq!(state.balance_of(&token_address()), 334);
+    assert_eq!(state.balance_of(&account(1)), 99_666);
+}
+
+#[test]
+#[should_panic(expected = "Transfer amount: 9999, minimum allowed transfer amount: 10000")]
+fn transfer_below_the_reference_minimum_is_rejected() {
+    transfer(&mut priced_state(PRICE), 9_999);
+}
+
+#[test]
+fn no_fee_applies_before_a_price_is_pushed() {
+    let state = set_price_oracle(context(account(0)), new_state(), Some(account(5)));
+    let mut state = set_reference_fees(admin_context(), state, 100_000, 1_000_000);
+    transfer(&mut state, 10);
+
+    assert_eq!(state.balance_of(&account(1)), 10);
+    assert_eq!(state.collected_fees, 0);
+}
+
+#[test]
+fn treasury_transfers_are_not_charged() {
+    let mut state = priced_state(PRICE);
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), token_address(), 500, None),
+    );
+
+    assert_eq!(state.balance_of(&token_address()), 500);
+    assert_eq!(state.collected_fees, 0);
+}
+
+#[test]
+#[should_panic(expected = "Only the price oracle can push the price.")]
+fn only_the_oracle_pushes_the_price() {
+    push_price(context(account(1)), priced_state(PRICE), 1);
+}
@@ -0,0 +1,435 @@
+//! Organization accounts, a balance held by the contract and controlled by a set of signers,
+//! without handing one key the whole treasury. Anyone can deposit to an organization. Each signer
+//! has a spending limit it can pay out alone, and larger payments, as well as changes to the
+//! signers, take the confirmations of the required number of signers. Payments are credited
+//! directly, transfer hooks do not apply to them.
+#![cfg_attr(not(feature = "organizations"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "organizations")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "organizations"))]
+mod tests;
+
+/// The largest number of signers an organization can have.
+pub const MAX_ORGANIZATION_SIGNERS: usize = 16;
+
+/// A signer of an organization.
+///
+/// ### Fields:
+///
+///   * `account`: [`Address`], the signer.
+///   * `limit`: [`u128`], largest payment the signer can make without confirmations.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrgSigner {
+    pub account: Address,
+    pub limit: u128,
+}
+
+/// An action taken on behalf of an organization.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OrgAction {
+    /// Pay `amount` tokens of the organization to `to`.
+    #[discriminant(0)]
+    Transfer { to: Address, amount: u128 },
+    /// Add `signer` to the signers, or replace its spending limit.
+    #[discriminant(1)]
+    AddSigner { signer: OrgSigner },
+    /// Remove `account` from the signers.
+    #[discriminant(2)]
+    RemoveSigner { account: Address },
+    /// Set the number of confirmations actions require.
+    #[discriminant(3)]
+    SetRequiredConfirmations { required: u32 },
+}
+
+/// An action of an organization waiting for confirmations.
+///
+/// ### Fields:
+///
+///   * `action`: [`OrgAction`], the action to take.
+///   * `proposer`: [`Address`], signer that proposed the action.
+///   * `confirmations`: [`Vec`]<[`Address`]>, signers that confirmed the action, the proposer
+///     included.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PendingOrgAction {
+    pub action: OrgAction,
+    pub proposer: Address,
+    pub confirmations: Vec<Address>,
+}
+
+/// An organization account.
+///
+/// ### Fields:
+///
+///   * `signers`: [`SortedVecMap`]<[`Address`], [`u128`]>, spending limit of each signer.
+///   * `required_confirmations`: [`u32`], number of signers that must confirm an action above the
+///     limit of its proposer.
+///   * `balance`: [`u128`], tokens held for the organization.
+///   * `pending_actions`: [`SortedVecMap`]<[`u64`], [`PendingOrgAction`]>, actions waiting for
+///     confirmations, by id.
+///   * `next_action_id`: [`u64`], id of the next pending action.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Organization {
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub signers: SortedVecMap<Address, u128>,
+    pub required_confirmations: u32,
+    pub balance: u128,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub pending_actions: SortedVecMap<u64, PendingOrgAction>,
+    pub next_action_id: u64,
+}
+
+impl Organization {
+    /// Checks that `account` is a signer of the organization.
+    ///
+    /// Panics if `account` is not a signer.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account acting for the organization.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u128`] spending limit of the signer.
+    pub fn assert_signer(&self, account: &Address) -> u128 {
+        *self
+            .signers
+            .get(account)
+            .expect("Caller is not a signer of the organization.")
+    }
+
+    /// Gets the number of confirmations of `pending` by current signers.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `pending`: [`PendingOrgAction`], the action waiting for confirmations.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u32`] number of confirmations that count.
+    pub fn confirmations(&self, pending: &PendingOrgAction) -> u32 {
+        pending
+            .confirmations
+            .iter()
+            .filter(|account| self.signers.contains_key(account))
+            .count() as u32
+    }
+
+    /// Takes `action`.
+    ///
+    /// Panics if the action would leave fewer signers than the required confirmations, more than
+    /// [`MAX_ORGANIZATION_SIGNERS`] signers, or require no confirmations.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `action`: [`OrgAction`], the action to take.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`Option`]<([`Address`], [`u128`])> payment to credit, if the action is a transfer.
+    pub fn apply(&mut self, action: OrgAction) -> Option<(Address, u128)> {
+        match action {
+            OrgAction::Transfer { to, amount } => {
+                self.balance = self.balance.checked_sub(amount).unwrap_or_else(|| {
+                    panic!(
+                        "Insufficient organization balance: {}, minimum required balance: {}",
+                        self.balance, amount
+                    )
+                });
+                return Some((to, amount));
+            }
+            OrgAction::AddSigner { signer } => {
+                self.signers.insert(signer.account, signer.limit);
+                assert!(
+                    self.signers.len() <= MAX_ORGANIZATION_SIGNERS,
+                    "Organization already has the maximum of: {} signers",
+                    MAX_ORGANIZATION_SIGNERS
+                );
+            }
+            OrgAction::RemoveSigner { account } => {
+                self.signers
+                    .remove(&account)
+                    .expect("Account is not a signer of the organization.");
+            }
+            OrgAction::SetRequiredConfirmations { required } => {
+                self.required_confirmations = required;
+            }
+        }
+        assert!(
+            self.required_confirmations > 0
+                && self.required_confirmations as usize <= self.signers.len(),
+            "Required confirmations: {}, must be between 1 and the number of signers: {}",
+            self.required_confirmations,
+            self.signers.len()
+        );
+        None
+    }
+}
+
+#[cfg(feature = "organizations")]
+impl TashiTokenState {
+    /// Gets the organization with id `org_id`.
+    ///
+    /// Panics if there is no such organization.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `org_id`: [`u64`], id of the organization.
+    ///
+    /// ### Returns:
+    ///
+    /// A mutable reference to the [`Organization`].
+    fn organization(&mut self, org_id: u64) -> &mut Organization {
+        self.organizations
+            .get_mut(&org_id)
+            .unwrap_or_else(|| panic!("No organization with id: {}", org_id))
+    }
+
+    /// Takes `action` on behalf of organization `org_id`, crediting the payment of a transfer.
+    ///
+    /// Panics if the action is invalid or the organization cannot cover the transfer.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context.
+    ///   * `org_id`: [`u64`], id of the organization.
+    ///   * `action`: [`OrgAction`], the action to take.
+    fn apply_org_action(&mut self, ctx: &ContractContext, org_id: u64, action: OrgAction) {
+        if let Some((to, amount)) = self.organization(org_id).apply(action) {
+            // the organization's balance is pooled from the deposits of its signers
+            self.payout(ctx, ctx.contract_address, to, amount); // pay out of the organization
+        }
+    }
+}
+
+/// Create an organization controlled by `signers`, requiring `required_confirmations` of them for
+/// actions above the limit of their proposer. The organization gets the next free id and starts
+/// with no balance.
+///
+/// Panics if there are no signers or more than [`MAX_ORGANIZATION_SIGNERS`], or if the required
+/// confirmations are zero or more than the number of signers.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `signers`: [`Vec`]<[`OrgSigner`]>, the signers and their spending limits.
+///   * `required_confirmations`: [`u32`], number of signers that must confirm an action.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "organizations")]
+#[action(shortname = 0xee)]
+fn create_organization(
+    _ctx: ContractContext,
+    mut state: TashiTokenState,
+    signers: Vec<OrgSigner>,
+    required_confirmations: u32,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "create_organization");
+    let mut organization = Organization {
+        signers: SortedVecMap::new(),
+        required_confirmations,
+        balance: 0,
+        pending_actions: SortedVecMap::new(),
+        next_action_id: 0,
+    };
+    for signer in signers {
+        organization.signers.insert(signer.account, signer.limit);
+    }
+    assert!(
+        organization.signers.len() <= MAX_ORGANIZATION_SIGNERS,
+        "Organization can have at most: {} signers",
+        MAX_ORGANIZATION_SIGNERS
+    );
+    organization.apply(OrgAction::SetRequiredConfirmations {
+        required: required_confirmations,
+    });
+    state
+        .organizations
+        .insert(state.next_organization_id, organization);
+    state.next_organization_id += 1;
+    state.assert_invariants();
+
+    state
+}
+
+/// Deposit `amount` tokens from caller address to organization `org_id`.
+///
+/// Panics if there is no such organization or if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `org_id`: [`u64`], id of the organization.
+///   * `amount`: [`u128`], amount to deposit.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "organizations")]
+#[action(shortname = 0xef)]
+fn deposit_to_organization(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    org_id: u64,
+    amount: u128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "deposit_to_organization");
+    let organization = state.organization(org_id);
+    organization.balance = organization
+        .balance
+        .checked_add(amount)
+        .expect("Overflow when adding to organization balance.");
+    state.escrow(&ctx, ctx.sender, amount); // escrow the deposit
+    state.assert_invariants();
+
+    state
+}
+
+/// Propose `action` on behalf of organization `org_id`. A transfer within the spending limit of
+/// the caller is taken right away, any other action once confirmed by the required number of
+/// signers, the caller counting as the first. The pending action gets the next free id of the
+/// organization.
+///
+/// Panics if there is no such organization, if the caller is not one of its signers, or if an
+/// action taken right away is invalid.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `org_id`: [`u64`], id of the organization.
+///   * `action`: [`OrgAction`], the action to take.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "organizations")]
+#[action(shortname = 0xf0)]
+fn propose_org_action(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    org_id: u64,
+    action: OrgAction,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "propose_org_action");
+    let organization = state.organization(org_id);
+    let limit = organization.assert_signer(&ctx.sender);
+    let within_limit = matches!(action, OrgAction::Transfer { amount, .. } if amount <= limit);
+    if within_limit || organization.required_confirmations == 1 {
+        state.apply_org_action(&ctx, org_id, action);
+    } else {
+        organization.pending_actions.insert(
+            organization.next_action_id,
+            PendingOrgAction {
+                action,
+                proposer: ctx.sender,
+                confirmations: vec![ctx.sender],
+            },
+        );
+        organization.next_action_id += 1;
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Confirm the pending action `action_id` of organization `org_id`, taking it once the required
+/// number of signers confirmed it.
+///
+/// Panics if there is no such organization or pending action, if the caller is not a signer or
+/// already confirmed the action, or if the action is invalid when taken.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `org_id`: [`u64`], id of the organization.
+///   * `action_id`: [`u64`], id of the pending action.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "organizations")]
+#[action(shortname = 0xf1)]
+fn confirm_org_action(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    org_id: u64,
+    action_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "confirm_org_action");
+    let organization = state.organization(org_id);
+    organization.assert_signer(&ctx.sender);
+    let mut pending = organization
+        .pending_actions
+        .remove(&action_id)
+        .unwrap_or_else(|| panic!("No pending organization action: {}", action_id));
+    assert!(
+        !pending.confirmations.contains(&ctx.sender),
+        "Action is already confirmed by the caller."
+    );
+    pending.confirmations.push(ctx.sender);
+    if organization.confirmations(&pending) >= organization.required_confirmations {
+        state.apply_org_action(&ctx, org_id, pending.action);
+    } else {
+        organization.pending_actions.insert(action_id, pending);
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Cancel the pending action `action_id` of organization `org_id`. Only the signer that proposed
+/// the action can call this action.
+///
+/// Panics if there is no such organization or pending action, or if the caller is not its
+/// proposer.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `org_id`: [`u64`], id of the organization.
+///   * `action_id`: [`u64`], id of the pending action.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "organizations")]
+#[action(shortname = 0xf2)]
+fn cancel_org_action(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    org_id: u64,
+    action_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "cancel_org_action");
+    let pending = state
+        .organization(org_id)
+        .pending_actions
+        .remove(&action_id)
+        .unwrap_or_else(|| panic!("No pending organization action: {}", action_id));
+    assert!(
+        pending.proposer == ctx.sender,
+        "Only the proposer can cancel the action."
+    );
+    state.assert_invariants();
+
+    state
+}
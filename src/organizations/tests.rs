@@ -0,0 +1,111 @@
+//! Tests of organization accounts: deposits, payments within the limit of a signer, and payments
+//! and signer changes taken once confirmed by the required number of signers.
+
+use super::{
+    confirm_org_action, create_organization, deposit_to_organization, propose_org_action,
+    OrgAction, OrgSigner,
+};
+use crate::testing::{account, context, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token with organization 0 holding 1_000 deposited by the owner, controlled by accounts 1 to 3
+/// which can pay 100 alone and must be two to confirm anything else.
+fn organization_state() -> TashiTokenState {
+    let signers = (1..=3)
+        .map(|index| OrgSigner {
+            account: account(index),
+            limit: 100,
+        })
+        .collect();
+    let state = create_organization(context(account(0)), new_state(), signers, 2);
+    deposit_to_organization(context(account(0)), state, 0, 1_000)
+}
+
+/// Account 1 proposing to pay `amount` of organization 0 to account 5.
+fn propose_payment(state: TashiTokenState, amount: u128) -> TashiTokenState {
+    propose_org_action(
+        context(account(1)),
+        state,
+        0,
+        OrgAction::Transfer {
+            to: account(5),
+            amount,
+        },
+    )
+}
+
+#[test]
+fn deposit_is_held_for_the_organization() {
+    let state = organization_state();
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 1_000);
+    assert_eq!(state.organizations.get(&0).unwrap().balance, 1_000);
+}
+
+#[test]
+fn payment_within_the_limit_is_made_alone() {
+    let state = propose_payment(organization_state(), 100);
+
+    assert_eq!(state.balance_of(&account(5)), 100);
+    assert_eq!(state.organizations.get(&0).unwrap().balance, 900);
+}
+
+#[test]
+fn larger_payment_waits_for_confirmations() {
+    let state = propose_payment(organization_state(), 600);
+    assert_eq!(state.balance_of(&account(5)), 0);
+    assert_eq!(
+        state.organizations.get(&0).unwrap().pending_actions.len(),
+        1
+    );
+
+    let state = confirm_org_action(context(account(2)), state, 0, 0);
+    let organization = state.organizations.get(&0).unwrap();
+    assert_eq!(state.balance_of(&account(5)), 600);
+    assert_eq!(organization.balance, 400);
+    assert!(organization.pending_actions.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Action is already confirmed by the caller.")]
+fn proposer_cannot_confirm_again() {
+    let state = propose_payment(organization_state(), 600);
+    confirm_org_action(context(account(1)), state, 0, 0);
+}
+
+#[test]
+#[should_panic(expected = "Caller is not a signer of the organization.")]
+fn only_signers_propose() {
+    propose_org_action(
+        context(account(4)),
+        organization_state(),
+        0,
+        OrgAction::Transfer {
+            to: account(4),
+            amount: 1,
+        },
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "Insufficient organization balance: 1000, minimum required balance: 2000"
+)]
+fn payment_beyond_the_organization_balance_is_rejected() {
+    let state = propose_payment(organization_state(), 2_000);
+    confirm_org_action(context(account(2)), state, 0, 0);
+}
+
+#[test]
+#[should_panic(
+    expected = "Required confirmations: 4, must be between 1 and the number of signers: 3"
+)]
+fn required_confirmations_cannot_exceed_the_signers() {
+    let state = propose_org_action(
+        context(account(1)),
+        organization_state(),
+        0,
+        OrgAction::SetRequiredConfirmations { required: 4 },
+    );
+    confirm_org_action(context(account(2)), state, 0, 0);
+}
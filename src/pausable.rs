@@ -0,0 +1,75 @@
+//! Pausing all transfers in an emergency. Transfers are also rejected during an emergency pause
+//! of the `emergency-pause` feature.
+
+use pbc_contract_common::context::ContractContext;
+
+use crate::token::TashiTokenState;
+
+impl TashiTokenState {
+    /// Checks that funds can move: the token is not paused and no emergency pause is in effect.
+    ///
+    /// Panics if the token is paused or paused in an emergency.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    pub fn assert_not_paused(&self, ctx: &ContractContext) {
+        assert!(!self.paused, "Transfers are paused.");
+        assert!(
+            !self.is_emergency_paused(ctx.block_production_time),
+            "Transfers are paused in an emergency until: {}",
+            self.emergency_paused_until
+        );
+    }
+}
+
+/// Pause all transfers. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if the token is already paused.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x13)]
+fn pause(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "pause");
+    assert!(!state.paused, "Transfers are already paused.");
+    state.paused = true;
+    state.assert_invariants();
+
+    state
+}
+
+/// Resume all transfers, lifting any emergency pause. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if the token is neither paused nor paused in an
+/// emergency.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[action(shortname = 0x14)]
+fn unpause(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "unpause");
+    assert!(
+        state.paused || state.is_emergency_paused(ctx.block_production_time),
+        "Transfers are not paused."
+    );
+    state.paused = false;
+    state.emergency_paused_until = 0;
+    state.assert_invariants();
+
+    state
+}
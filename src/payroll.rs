@@ -0,0 +1,170 @@
+//! Recurring payroll. The owner registers recipients with an amount paid every period and funds a
+//! payroll pool, anyone can then run the payroll to pay every recipient the periods due since its
+//! last payment. Periods the pool cannot cover stay due until the pool is funded again.
+#![cfg_attr(not(feature = "payroll"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+#[cfg(feature = "payroll")]
+use pbc_contract_common::address::Address;
+#[cfg(feature = "payroll")]
+use pbc_contract_common::context::ContractContext;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "payroll")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "payroll"))]
+mod tests;
+
+/// A recipient of the payroll.
+///
+/// ### Fields:
+///
+///   * `amount_per_period`: [`u128`], amount paid every period.
+///   * `period`: [`i64`], length of a period, in milliseconds.
+///   * `next_payment_at`: [`i64`], block production time the next unpaid period is due at, in
+///     milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PayrollEntry {
+    pub amount_per_period: u128,
+    pub period: i64,
+    pub next_payment_at: i64,
+}
+
+impl PayrollEntry {
+    /// Gets the number of periods due at time `now`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u128`] number of unpaid periods that are due.
+    pub fn periods_due(&self, now: i64) -> u128 {
+        if now < self.next_payment_at {
+            return 0;
+        }
+        ((now - self.next_payment_at) / self.period) as u128 + 1
+    }
+}
+
+/// Register `recipient` to be paid `amount_per_period` every `period`, the first payment being due
+/// at `first_payment_at`. Replaces the registration of `recipient`, forgetting the periods it was
+/// still owed, and an `amount_per_period` of zero removes it. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if `period` is not positive.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `recipient`: [`Address`], account to pay.
+///   * `amount_per_period`: [`u128`], amount paid every period.
+///   * `period`: [`i64`], length of a period, in milliseconds.
+///   * `first_payment_at`: [`i64`], block production time the first payment is due at, in
+///     milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "payroll")]
+#[action(shortname = 0x62)]
+fn set_payroll_recipient(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    recipient: Address,
+    amount_per_period: u128,
+    period: i64,
+    first_payment_at: i64,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(
+        state,
+        ctx,
+        "set_payroll_recipient",
+        recipient,
+        amount_per_period,
+        period,
+        first_payment_at
+    );
+    if amount_per_period == 0 {
+        state.payroll.remove(&recipient);
+    } else {
+        assert!(period > 0, "Payroll period must be positive.");
+        state.payroll.insert(
+            recipient,
+            PayrollEntry {
+                amount_per_period,
+                period,
+                next_payment_at: first_payment_at,
+            },
+        );
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Add `amount` tokens from caller address to the payroll pool. Only the owner can call this
+/// action.
+///
+/// Panics if the caller is not the owner or if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to add to the pool.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "payroll")]
+#[action(shortname = 0x63)]
+fn fund_payroll(ctx: ContractContext, mut state: TashiTokenState, amount: u128) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "fund_payroll", amount);
+    state.escrow(&ctx, ctx.sender, amount);
+    state.payroll_pool += amount;
+    state.assert_invariants();
+
+    state
+}
+
+/// Pay every payroll recipient the periods due since its last payment, in order of recipient
+/// address, as far as the payroll pool covers them. Anyone can call this action.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "payroll")]
+#[action(shortname = 0x64)]
+fn run_payroll(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "run_payroll");
+    let now = ctx.block_production_time;
+    let mut payments: Vec<(Address, u128)> = Vec::new();
+    for (recipient, entry) in state.payroll.iter_mut() {
+        let affordable = state.payroll_pool / entry.amount_per_period;
+        let periods = entry.periods_due(now).min(affordable);
+        if periods == 0 {
+            continue;
+        }
+        let amount = entry.amount_per_period * periods; // at most the pool
+        state.payroll_pool -= amount;
+        entry.next_payment_at += entry.period * periods as i64;
+        payments.push((*recipient, amount));
+    }
+    for (recipient, amount) in payments {
+        state.payout(&ctx, ctx.contract_address, recipient, amount);
+    }
+    state.assert_invariants();
+
+    state
+}
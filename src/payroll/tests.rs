@@ -0,0 +1,65 @@
+//! Tests of the payroll: paying the periods due, periods the pool cannot cover which stay due, and
+//! removing recipients.
+
+use super::{fund_payroll, run_payroll, set_payroll_recipient};
+use crate::testing::{account, context, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// One payroll period, in milliseconds.
+const DAY: i64 = 86_400_000;
+
+/// A token paying account 1 100 and account 2 200 a day from day one, with 500 in the pool.
+fn payroll_state() -> TashiTokenState {
+    let state = set_payroll_recipient(context(account(0)), new_state(), account(1), 100, DAY, DAY);
+    let state = set_payroll_recipient(context(account(0)), state, account(2), 200, DAY, DAY);
+    fund_payroll(context(account(0)), state, 500)
+}
+
+#[test]
+fn nothing_is_paid_before_the_first_period() {
+    let state = run_payroll(context_at(account(3), DAY - 1), payroll_state());
+
+    assert_eq!(state.payroll_pool, 500);
+    assert_eq!(state.balance_of(&account(1)), 0);
+}
+
+#[test]
+fn periods_the_pool_cannot_cover_stay_due() {
+    let state = run_payroll(context_at(account(3), 2 * DAY), payroll_state());
+    assert_eq!(state.balance_of(&account(1)), 200);
+    assert_eq!(state.balance_of(&account(2)), 200);
+    assert_eq!(state.payroll_pool, 100);
+    assert_eq!(
+        state.payroll.get(&account(2)).unwrap().next_payment_at,
+        2 * DAY
+    );
+
+    let state = fund_payroll(context(account(0)), state, 1_000);
+    let state = run_payroll(context_at(account(3), 2 * DAY), state);
+    assert_eq!(state.balance_of(&account(1)), 200);
+    assert_eq!(state.balance_of(&account(2)), 400);
+    assert_eq!(state.payroll_pool, 900);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 1_500);
+}
+
+#[test]
+fn removed_recipient_is_not_paid() {
+    let state = set_payroll_recipient(context(account(0)), payroll_state(), account(2), 0, 0, 0);
+    let state = run_payroll(context_at(account(3), DAY), state);
+
+    assert!(state.payroll.get(&account(2)).is_none());
+    assert_eq!(state.balance_of(&account(1)), 100);
+    assert_eq!(state.payroll_pool, 400);
+}
+
+#[test]
+#[should_panic(expected = "Payroll period must be positive.")]
+fn period_must_be_positive() {
+    set_payroll_recipient(context(account(0)), new_state(), account(1), 100, 0, DAY);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can perform this action.")]
+fn only_the_owner_funds_the_payroll() {
+    fund_payroll(context(account(1)), new_state(), 100);
+}
@@ -0,0 +1,223 @@
+//! Two-phase transfers for payments to unverified counterparties. The sender's amount is held by
+//! the contract until the receiver claims it, and goes back to the sender if the receiver rejects
+//! it or does not claim it before it expires. A claim is settled as a transfer from the sender, so
+//! the transfer hooks apply to it.
+#![cfg_attr(not(feature = "pending-transfers"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "pending-transfers")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "pending-transfers")]
+use pbc_contract_common::events::EventGroup;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "pending-transfers")]
+use crate::hooks::Transfer;
+#[cfg(feature = "pending-transfers")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "pending-transfers"))]
+mod tests;
+
+/// A transfer held until its receiver claims it.
+///
+/// ### Fields:
+///
+///   * `sender`: [`Address`], account that sent the transfer.
+///   * `receiver`: [`Address`], account that can claim the transfer.
+///   * `amount`: [`u128`], amount held.
+///   * `expiry`: [`i64`], block production time from which the transfer can no longer be claimed,
+///     in milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PendingTransfer {
+    pub sender: Address,
+    pub receiver: Address,
+    pub amount: u128,
+    pub expiry: i64,
+}
+
+#[cfg(feature = "pending-transfers")]
+impl TashiTokenState {
+    /// Removes the pending transfer with id `transfer_id`.
+    ///
+    /// Panics if there is no such transfer.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `transfer_id`: [`u64`], id of the transfer.
+    ///
+    /// ### Returns:
+    ///
+    /// The removed [`PendingTransfer`].
+    fn take_pending_transfer(&mut self, transfer_id: u64) -> PendingTransfer {
+        self.pending_transfers
+            .remove(&transfer_id)
+            .unwrap_or_else(|| panic!("Unknown pending transfer: {}", transfer_id))
+    }
+}
+
+/// Send `amount` tokens from caller address to address `to`, held by the contract until `to`
+/// claims them. The transfer gets the next free id. Transfer hooks apply when it is claimed.
+///
+/// Panics if `amount` is zero, if `expiry` has already passed, if there is insufficient balance in
+/// caller account, if the caller cannot spend it or if the address guards reject `to`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `to`: [`Address`], account that can claim the transfer.
+///   * `amount`: [`u128`], amount to transfer.
+///   * `expiry`: [`i64`], block production time from which the transfer can no longer be claimed,
+///     in milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "pending-transfers")]
+#[action(shortname = 0xcd)]
+fn transfer_pending(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    to: Address,
+    amount: u128,
+    expiry: i64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "transfer_pending");
+    assert!(amount > 0, "Transferred amount cannot be zero.");
+    assert!(
+        expiry > ctx.block_production_time,
+        "Expiry has already passed."
+    );
+    state.assert_valid_destination(&ctx, &to);
+    state.escrow(&ctx, ctx.sender, amount); // hold the amount
+
+    state.pending_transfers.insert(
+        state.next_pending_transfer_id,
+        PendingTransfer {
+            sender: ctx.sender,
+            receiver: to,
+            amount,
+            expiry,
+        },
+    );
+    state.next_pending_transfer_id += 1;
+    state.assert_invariants();
+
+    state
+}
+
+/// Claim the pending transfer `transfer_id` sent to caller address. The held amount is returned to
+/// the sender and transferred from there to the caller, running every transfer hook.
+///
+/// Panics if there is no such transfer, if the caller is not its receiver, if it has expired, or
+/// if a transfer hook rejects the transfer.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `transfer_id`: [`u64`], id of the transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "pending-transfers")]
+#[action(shortname = 0xce)]
+fn claim_pending_transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    transfer_id: u64,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "claim_pending_transfer");
+    let transfer = state.take_pending_transfer(transfer_id);
+    assert!(
+        transfer.receiver == ctx.sender,
+        "Only the receiver can claim the transfer."
+    );
+    assert!(
+        ctx.block_production_time < transfer.expiry,
+        "Transfer expired at: {}",
+        transfer.expiry
+    );
+    let supply = state.supply();
+    state.release(&ctx, transfer.sender, transfer.amount); // release the hold
+    state.execute_transfer(
+        &ctx,
+        Transfer::new(transfer.sender, transfer.receiver, transfer.amount, None),
+    );
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Reject the pending transfer `transfer_id` sent to caller address, returning its amount to the
+/// sender.
+///
+/// Panics if there is no such transfer or if the caller is not its receiver.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `transfer_id`: [`u64`], id of the transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "pending-transfers")]
+#[action(shortname = 0xcf)]
+fn reject_pending_transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    transfer_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "reject_pending_transfer");
+    let transfer = state.take_pending_transfer(transfer_id);
+    assert!(
+        transfer.receiver == ctx.sender,
+        "Only the receiver can reject the transfer."
+    );
+    state.release(&ctx, transfer.sender, transfer.amount);
+    state.assert_invariants();
+
+    state
+}
+
+/// Return the expired pending transfer `transfer_id` to its sender. Anyone can call this action,
+/// the amount always goes to the sender.
+///
+/// Panics if there is no such transfer or if it has not expired.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `transfer_id`: [`u64`], id of the transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "pending-transfers")]
+#[action(shortname = 0xd0)]
+fn refund_pending_transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    transfer_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "refund_pending_transfer");
+    let transfer = state.take_pending_transfer(transfer_id);
+    assert!(
+        ctx.block_production_time >= transfer.expiry,
+        "Transfer expires at: {}",
+        transfer.expiry
+    );
+    state.release(&ctx, transfer.sender, transfer.amount);
+    state.assert_invariants();
+
+    state
+}
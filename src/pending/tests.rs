@@ -0,0 +1,77 @@
+//! Tests of pending transfers: holding the amount, settling a claim through the transfer hooks,
+//! and returning the amount to the sender.
+
+use super::{claim_pending_transfer, refund_pending_transfer, transfer_pending};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state};
+use crate::token::TashiTokenState;
+
+/// Balance of account 1 in the tests.
+const BALANCE: u128 = 1_000;
+
+/// Block production time the pending transfer expires at.
+const EXPIRY: i64 = 10_000;
+
+/// A token where account 1 holds [`BALANCE`] and sent all of it to account 2 as pending transfer 0.
+fn pending_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), BALANCE, None),
+    );
+    transfer_pending(context(account(1)), state, account(2), BALANCE, EXPIRY)
+}
+
+#[test]
+fn amount_is_held_until_claimed() {
+    let state = pending_state();
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.balance_of(&account(2)), 0);
+
+    let (state, _) = claim_pending_transfer(context(account(2)), state, 0);
+    assert_eq!(state.balance_of(&account(2)), BALANCE);
+    assert!(state.pending_transfers.is_empty());
+}
+
+#[cfg(feature = "burnable")]
+#[test]
+fn claim_runs_the_transfer_hooks() {
+    let mut state = pending_state();
+    state.burn_rate_bps = 100;
+    let supply = state.supply();
+
+    let (state, _) = claim_pending_transfer(context(account(2)), state, 0);
+    assert_eq!(state.balance_of(&account(2)), BALANCE - BALANCE / 100);
+    assert_eq!(state.supply(), supply - BALANCE / 100);
+}
+
+#[cfg(feature = "nonces")]
+#[test]
+fn claim_counts_as_a_transfer_of_the_sender() {
+    let mut state = pending_state();
+    state.nonces_on_transfers = true;
+    let (state, _) = claim_pending_transfer(context(account(2)), state, 0);
+    assert_eq!(state.nonce_of(&account(1)), 1);
+}
+
+#[cfg(feature = "pausable")]
+#[test]
+#[should_panic(expected = "Transfers are paused.")]
+fn claim_is_rejected_while_paused() {
+    let mut state = pending_state();
+    state.paused = true;
+    claim_pending_transfer(context(account(2)), state, 0);
+}
+
+#[test]
+#[should_panic(expected = "Transfer expired at: 10000")]
+fn expired_transfer_cannot_be_claimed() {
+    claim_pending_transfer(context_at(account(2), EXPIRY), pending_state(), 0);
+}
+
+#[test]
+fn expired_transfer_is_refunded_to_the_sender() {
+    let state = refund_pending_transfer(context_at(account(3), EXPIRY), pending_state(), 0);
+    assert_eq!(state.balance_of(&account(1)), BALANCE);
+    assert!(state.pending_transfers.is_empty());
+}
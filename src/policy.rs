@@ -0,0 +1,155 @@
+//! The transfer policies deciding whether funds can move, who can move them and where to: pausing,
+//! the launch guard, the address guards, the transfer limits and the compliance rules. Transfers
+//! check them as the [`TransferPolicy`] hook. Funds moving into and out of escrow do not run the
+//! transfer hooks, so [`TashiTokenState::escrow`] checks the sending side with
+//! [`TashiTokenState::assert_can_spend`] and [`TashiTokenState::payout`] checks the receiving side
+//! with [`TashiTokenState::assert_can_receive`], one implementation for all.
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::TashiTokenState;
+
+/// Checks every transfer against the policies of its sender before any balance is updated, and
+/// against the policies of its receiver once the balances are updated.
+pub struct TransferPolicy;
+
+impl TransferHook for TransferPolicy {
+    fn before_transfer(
+        ctx: &ContractContext,
+        state: &mut TashiTokenState,
+        transfer: &mut Transfer,
+    ) {
+        #[cfg(feature = "pausable")]
+        state.assert_not_paused(ctx);
+        state.assert_sender_policies(ctx, transfer);
+        state.assert_destination_policies(ctx, transfer);
+    }
+
+    fn after_transfer(ctx: &ContractContext, state: &mut TashiTokenState, transfer: &Transfer) {
+        state.assert_balance_policies(ctx, transfer);
+    }
+}
+
+impl TashiTokenState {
+    /// Checks that `account` can spend `amount` of its balance outside of a transfer, such as into
+    /// an escrow or for a payout. Such spends do not run the transfer hooks, so the policies of
+    /// the sender and the hooks restricting what an account can spend are checked here instead.
+    ///
+    /// Panics if transfers are paused, if trading is not enabled for `account`, if `amount` is
+    /// above the transfer limit, if spending breaks a compliance rule, if `account` is frozen or
+    /// would be left with less than its frozen or self-locked amount, or if its tokens are
+    /// soulbound.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context.
+    ///   * `account`: [`Address`], account spending its balance.
+    ///   * `amount`: [`u128`], amount to spend.
+    #[cfg_attr(
+        not(any(
+            feature = "streaming",
+            feature = "channels",
+            feature = "crowdsale",
+            feature = "htlc",
+            feature = "conditional-transfers",
+            feature = "raffles",
+            feature = "milestone-grants",
+            feature = "vesting",
+            feature = "pending-transfers",
+            feature = "auctions",
+            feature = "lending",
+            feature = "organizations",
+            feature = "burnable",
+            feature = "vote-escrow",
+            feature = "amm",
+            feature = "payroll",
+            feature = "governance",
+            feature = "labels",
+            feature = "rewards",
+            feature = "referrals",
+            feature = "redemption",
+            feature = "wrapper",
+            feature = "bonding-curve"
+        )),
+        allow(dead_code)
+    )]
+    #[allow(unused_variables)]
+    pub fn assert_can_spend(&self, ctx: &ContractContext, account: &Address, amount: u128) {
+        // the spent amount goes into the custody of the contract
+        let spend = Transfer::new(*account, ctx.contract_address, amount, None);
+        #[cfg(feature = "pausable")]
+        self.assert_not_paused(ctx);
+        self.assert_sender_policies(ctx, &spend);
+        #[cfg(feature = "compliance")]
+        self.check_transfer(ctx, &spend);
+        #[cfg(feature = "enforcement")]
+        {
+            self.assert_not_frozen(account);
+            self.assert_frozen_amount_kept(account, amount);
+        }
+        #[cfg(feature = "soulbound")]
+        self.assert_not_soulbound(ctx, account);
+        #[cfg(feature = "lockbox")]
+        self.assert_self_locked_amount_kept(ctx, account, amount);
+    }
+
+    /// Checks that the receiver of `transfer` can receive it, once its balance is credited. Used
+    /// for payouts out of escrow, which do not run the transfer hooks.
+    ///
+    /// Panics if transfers are paused, if the address guards reject the receiver, if the receiver
+    /// is left with more than the wallet limit or if the payout breaks a compliance rule.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context.
+    ///   * `transfer`: [`Transfer`], the executed payout, from the account that escrowed the funds.
+    #[cfg_attr(
+        not(any(
+            feature = "streaming",
+            feature = "channels",
+            feature = "htlc",
+            feature = "raffles",
+            feature = "milestone-grants",
+            feature = "vesting",
+            feature = "auctions",
+            feature = "organizations",
+            feature = "payroll"
+        )),
+        allow(dead_code)
+    )]
+    pub fn assert_can_receive(&self, ctx: &ContractContext, transfer: &Transfer) {
+        #[cfg(feature = "pausable")]
+        self.assert_not_paused(ctx);
+        self.assert_destination_policies(ctx, transfer);
+        self.assert_balance_policies(ctx, transfer);
+    }
+
+    /// Checks the policies of the sender of `transfer`: the launch guard and the transfer limits.
+    #[allow(unused_variables)]
+    fn assert_sender_policies(&self, ctx: &ContractContext, transfer: &Transfer) {
+        #[cfg(feature = "launch-guard")]
+        self.assert_trading_allowed(ctx, transfer);
+        #[cfg(feature = "limits")]
+        self.assert_within_transfer_limits(ctx, transfer);
+    }
+
+    /// Checks the policies of the receiver of `transfer` that do not depend on balances: the
+    /// address guards.
+    #[allow(unused_variables)]
+    fn assert_destination_policies(&self, ctx: &ContractContext, transfer: &Transfer) {
+        #[cfg(feature = "address-guards")]
+        self.assert_valid_receiver(ctx, transfer);
+    }
+
+    /// Checks the policies of `transfer` once the balances are updated: the wallet limit and the
+    /// compliance rules, which apply to both parties.
+    #[allow(unused_variables)]
+    fn assert_balance_policies(&self, ctx: &ContractContext, transfer: &Transfer) {
+        #[cfg(feature = "limits")]
+        self.assert_within_wallet_limit(ctx, transfer);
+        #[cfg(feature = "compliance")]
+        self.check_transfer(ctx, transfer);
+    }
+}
@@ -0,0 +1,415 @@
+//! Raffles paid in the token. The owner opens a raffle, accounts buy tickets until the sales end,
+//! and the draw picks distinct winning tickets. The pot of ticket sales is split into a burned
+//! share, a share for the treasury held by the contract itself and the prize, shared equally by
+//! the winners.
+//!
+//! The owner commits to a secret seed when opening the raffle and reveals it to draw. The
+//! randomness of the draw is the hash of the seed and of the transactions that bought tickets, so
+//! the owner cannot choose the winners and buyers cannot predict them. A raffle not drawn by its
+//! draw deadline can be cancelled by anyone, refunding every ticket, so withholding the seed only
+//! cancels the raffle.
+#![cfg_attr(not(feature = "raffles"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "raffles")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "raffles")]
+use pbc_contract_common::events::EventGroup;
+#[cfg(feature = "raffles")]
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use pbc_contract_common::Hash;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "raffles")]
+use crate::token::{mul_div, TashiTokenState, BPS_DENOMINATOR};
+
+#[cfg(all(test, feature = "raffles"))]
+mod tests;
+
+/// The largest number of tickets a raffle sells, bounding the size of the state and the cost of
+/// the draw.
+pub const MAX_RAFFLE_TICKETS: usize = 1_000;
+
+/// The status of a raffle.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RaffleStatus {
+    /// Selling tickets, or waiting to be drawn.
+    #[discriminant(0)]
+    Open {},
+    /// Drawn, each of `winners` having won `prize`.
+    #[discriminant(1)]
+    Drawn { winners: Vec<Address>, prize: u128 },
+    /// Cancelled and every ticket refunded.
+    #[discriminant(2)]
+    Cancelled {},
+}
+
+/// A raffle.
+///
+/// ### Fields:
+///
+///   * `ticket_price`: [`u128`], price of a ticket.
+///   * `sales_end`: [`i64`], block production time ticket sales end at, in milliseconds.
+///   * `draw_deadline`: [`i64`], block production time after which the raffle can be cancelled
+///     if not drawn, in milliseconds.
+///   * `winner_count`: [`u32`], number of winning tickets.
+///   * `burn_bps`: [`u16`], share of the pot burned, in basis points.
+///   * `treasury_bps`: [`u16`], share of the pot paid to the treasury, in basis points.
+///   * `seed_commitment`: [`Hash`], hash of the secret seed the owner reveals to draw.
+///   * `entropy`: [`Hash`], hash chain of the transactions that bought tickets.
+///   * `tickets`: [`Vec`]<[`Address`]>, holder of each ticket sold, cleared once drawn or
+///     cancelled.
+///   * `pot`: [`u128`], tokens paid for the tickets.
+///   * `status`: [`RaffleStatus`], whether the raffle is open, drawn or cancelled.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Raffle {
+    pub ticket_price: u128,
+    pub sales_end: i64,
+    pub draw_deadline: i64,
+    pub winner_count: u32,
+    pub burn_bps: u16,
+    pub treasury_bps: u16,
+    pub seed_commitment: Hash,
+    pub entropy: Hash,
+    pub tickets: Vec<Address>,
+    pub pot: u128,
+    pub status: RaffleStatus,
+}
+
+impl Raffle {
+    /// Picks the winning tickets from `randomness`, without picking a ticket twice. An account
+    /// holding several tickets can win several times.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `randomness`: [`Hash`], randomness of the draw.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`Vec`]<[`Address`]> of holders of the winning tickets, at most `winner_count` long.
+    pub fn pick_winners(&self, randomness: &Hash) -> Vec<Address> {
+        let mut remaining = self.tickets.clone();
+        let mut winners = Vec::new();
+        let mut round: u32 = 0;
+        while winners.len() < self.winner_count as usize && !remaining.is_empty() {
+            let mut payload = randomness.bytes.to_vec();
+            payload.extend_from_slice(&round.to_be_bytes());
+            let draw = Hash::digest(payload);
+            let mut value = [0u8; 16];
+            value.copy_from_slice(&draw.bytes[..16]);
+            let index = (u128::from_be_bytes(value) % remaining.len() as u128) as usize;
+            winners.push(remaining.swap_remove(index));
+            round += 1;
+        }
+        winners
+    }
+}
+
+/// Chains `transaction` into the entropy of a raffle.
+///
+/// ### Parameters:
+///
+///   * `entropy`: [`Hash`], the entropy so far.
+///   * `transaction`: [`Hash`], the transaction buying tickets.
+///
+/// ### Returns:
+///
+/// The new entropy [`Hash`].
+fn chain_entropy(entropy: &Hash, transaction: &Hash) -> Hash {
+    let mut payload = entropy.bytes.to_vec();
+    payload.extend_from_slice(&transaction.bytes);
+    Hash::digest(payload)
+}
+
+#[cfg(feature = "raffles")]
+impl TashiTokenState {
+    /// Gets the open raffle with id `raffle_id`.
+    ///
+    /// Panics if there is no such raffle or if it is drawn or cancelled.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `raffle_id`: [`u64`], id of the raffle.
+    ///
+    /// ### Returns:
+    ///
+    /// A mutable reference to the [`Raffle`].
+    pub fn open_raffle(&mut self, raffle_id: u64) -> &mut Raffle {
+        let raffle = self
+            .raffles
+            .get_mut(&raffle_id)
+            .unwrap_or_else(|| panic!("No raffle with id: {}", raffle_id));
+        assert!(
+            raffle.status == RaffleStatus::Open {},
+            "Raffle is already drawn or cancelled."
+        );
+        raffle
+    }
+}
+
+/// Open a raffle. The id of the raffle is the `next_raffle_id` of the state before the call. Only
+/// the owner can call this action.
+///
+/// Panics if the caller is not the owner, if `ticket_price` or `winner_count` is zero, if the
+/// sales end has already passed or the draw deadline is not after it, or if the burned and
+/// treasury shares exceed 100%.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `ticket_price`: [`u128`], price of a ticket.
+///   * `sales_end`: [`i64`], block production time ticket sales end at, in milliseconds.
+///   * `draw_deadline`: [`i64`], block production time after which the raffle can be cancelled
+///     if not drawn, in milliseconds.
+///   * `winner_count`: [`u32`], number of winning tickets.
+///   * `burn_bps`: [`u16`], share of the pot burned, in basis points.
+///   * `treasury_bps`: [`u16`], share of the pot paid to the treasury, in basis points.
+///   * `seed_commitment`: [`Hash`], SHA-256 hash of the secret seed revealed to draw.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "raffles")]
+#[action(shortname = 0x7c)]
+#[allow(clippy::too_many_arguments)]
+fn create_raffle(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    ticket_price: u128,
+    sales_end: i64,
+    draw_deadline: i64,
+    winner_count: u32,
+    burn_bps: u16,
+    treasury_bps: u16,
+    seed_commitment: Hash,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(
+        state,
+        ctx,
+        "create_raffle",
+        ticket_price,
+        sales_end,
+        draw_deadline,
+        winner_count,
+        burn_bps,
+        treasury_bps,
+        seed_commitment
+    );
+    assert!(ticket_price > 0, "Ticket price cannot be zero.");
+    assert!(winner_count > 0, "A raffle must have a winner.");
+    assert!(
+        sales_end > ctx.block_production_time,
+        "Ticket sales end has already passed."
+    );
+    assert!(
+        draw_deadline > sales_end,
+        "Draw deadline must be after the ticket sales end."
+    );
+    assert!(
+        u128::from(burn_bps) + u128::from(treasury_bps) <= BPS_DENOMINATOR,
+        "Burned and treasury shares cannot exceed 100%."
+    );
+
+    let raffle_id = state.next_raffle_id;
+    state.next_raffle_id += 1;
+    state.raffles.insert(
+        raffle_id,
+        Raffle {
+            ticket_price,
+            sales_end,
+            draw_deadline,
+            winner_count,
+            burn_bps,
+            treasury_bps,
+            seed_commitment,
+            entropy: seed_commitment,
+            tickets: Vec::new(),
+            pot: 0,
+            status: RaffleStatus::Open {},
+        },
+    );
+    state.assert_invariants();
+
+    state
+}
+
+/// Buy `count` tickets of raffle `raffle_id` for caller address, paying the ticket price for
+/// each.
+///
+/// Panics if the raffle is not open, if ticket sales are over, if `count` is zero, if the raffle
+/// would sell more than [`MAX_RAFFLE_TICKETS`] tickets, or if there is insufficient balance in
+/// caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `raffle_id`: [`u64`], id of the raffle.
+///   * `count`: [`u32`], number of tickets to buy.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "raffles")]
+#[action(shortname = 0x7d)]
+fn buy_tickets(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    raffle_id: u64,
+    count: u32,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "buy_tickets");
+    assert!(count > 0, "Ticket count cannot be zero.");
+    let raffle = state.open_raffle(raffle_id);
+    assert!(
+        ctx.block_production_time < raffle.sales_end,
+        "Ticket sales ended at: {}",
+        raffle.sales_end
+    );
+    let sold = raffle.tickets.len() + count as usize;
+    assert!(
+        sold <= MAX_RAFFLE_TICKETS,
+        "Tickets sold would be: {}, maximum is: {}",
+        sold,
+        MAX_RAFFLE_TICKETS
+    );
+    let price = raffle
+        .ticket_price
+        .checked_mul(u128::from(count))
+        .expect("Overflow when pricing tickets.");
+    raffle
+        .tickets
+        .extend(std::iter::repeat(ctx.sender).take(count as usize));
+    raffle.pot += price;
+    raffle.entropy = chain_entropy(&raffle.entropy, &ctx.current_transaction);
+    state.escrow(&ctx, ctx.sender, price); // pay into the pot
+    state.assert_invariants();
+
+    state
+}
+
+/// Draw raffle `raffle_id` by revealing the seed committed to when it was opened, paying the prize
+/// to the winners, the treasury share to the contract and burning the burned share. Only the owner
+/// can call this action.
+///
+/// Panics if the caller is not the owner, if the raffle is not open, if ticket sales are not over
+/// or the draw deadline has passed, or if `seed` does not match the commitment.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `raffle_id`: [`u64`], id of the raffle.
+///   * `seed`: [`Vec`]<[`u8`]>, the secret seed.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "raffles")]
+#[action(shortname = 0x7e)]
+fn draw_raffle(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    raffle_id: u64,
+    seed: Vec<u8>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "draw_raffle", raffle_id);
+    let raffle = state.open_raffle(raffle_id);
+    let now = ctx.block_production_time;
+    assert!(
+        now >= raffle.sales_end && now < raffle.draw_deadline,
+        "Raffle can be drawn from: {} to: {}",
+        raffle.sales_end,
+        raffle.draw_deadline
+    );
+    assert!(
+        Hash::digest(seed.clone()) == raffle.seed_commitment,
+        "Seed does not match its commitment."
+    );
+
+    let mut payload = seed;
+    payload.extend_from_slice(&raffle.entropy.bytes);
+    let winners = raffle.pick_winners(&Hash::digest(payload));
+    let pot = raffle.pot;
+    let burned = mul_div(pot, u128::from(raffle.burn_bps), BPS_DENOMINATOR);
+    let treasury = mul_div(pot, u128::from(raffle.treasury_bps), BPS_DENOMINATOR);
+    let prize_pool = pot - burned - treasury;
+    let prize = match winners.len() {
+        0 => 0, // nothing was sold, so the pot is empty
+        count => prize_pool / count as u128,
+    };
+    raffle.tickets.clear();
+    raffle.status = RaffleStatus::Drawn {
+        winners: winners.clone(),
+        prize,
+    };
+
+    let supply = state.supply();
+    for winner in &winners {
+        // the pot is pooled from every ticket bought
+        state.payout(&ctx, ctx.contract_address, *winner, prize);
+    }
+    // the rounding remainder of the prize goes to the treasury
+    state.credit(
+        ctx.contract_address,
+        treasury + prize_pool - prize * winners.len() as u128,
+    );
+    // the pot is already out of circulation
+    state.decrease_supply(burned, ctx.block_production_time);
+    state.assert_invariants();
+
+    let events = state.supply_change_events(supply);
+
+    (state, events)
+}
+
+/// Cancel raffle `raffle_id` once its draw deadline has passed without a draw, refunding every
+/// ticket. Anyone can call this action.
+///
+/// Panics if the raffle is not open or if its draw deadline has not passed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `raffle_id`: [`u64`], id of the raffle.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "raffles")]
+#[action(shortname = 0x7f)]
+fn cancel_raffle(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    raffle_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "cancel_raffle");
+    let raffle = state.open_raffle(raffle_id);
+    assert!(
+        ctx.block_production_time >= raffle.draw_deadline,
+        "Raffle can be drawn until: {}",
+        raffle.draw_deadline
+    );
+    let mut refunds: SortedVecMap<Address, u128> = SortedVecMap::new();
+    for holder in &raffle.tickets {
+        let refund = refunds.get(holder).copied().unwrap_or(0) + raffle.ticket_price;
+        refunds.insert(*holder, refund);
+    }
+    raffle.tickets.clear();
+    raffle.status = RaffleStatus::Cancelled {};
+
+    for (holder, refund) in refunds.iter() {
+        state.release(&ctx, *holder, *refund);
+    }
+    state.assert_invariants();
+
+    state
+}
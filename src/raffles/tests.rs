@@ -0,0 +1,121 @@
+//! Tests of raffles: splitting the pot between the burn, the treasury and the winners, refunds of
+//! cancelled raffles, and draws with the wrong seed.
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::Hash;
+
+use super::{buy_tickets, cancel_raffle, create_raffle, draw_raffle, RaffleStatus};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state, token_address, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// Block production time ticket sales end at.
+const SALES_END: i64 = 1_000;
+
+/// Block production time the raffle can be cancelled from.
+const DRAW_DEADLINE: i64 = 2_000;
+
+/// The secret seed of the raffle.
+fn seed() -> Vec<u8> {
+    vec![7]
+}
+
+/// A token where accounts 1 and 2 hold 10_000 each and bought 2 and 1 tickets at 101 of a raffle
+/// with `winner_count` winners, burning 10% of the pot and paying 20% to the treasury.
+fn raffle_state(winner_count: u32) -> TashiTokenState {
+    let mut state = new_state();
+    for index in 1..=2 {
+        state.execute_transfer(
+            &context(account(0)),
+            Transfer::new(account(0), account(index), 10_000, None),
+        );
+    }
+    let state = create_raffle(
+        context(account(0)),
+        state,
+        101,
+        SALES_END,
+        DRAW_DEADLINE,
+        winner_count,
+        1_000,
+        2_000,
+        Hash::digest(seed()),
+    );
+    let state = buy_tickets(context(account(1)), state, 0, 2);
+    buy_tickets(context(account(2)), state, 0, 1)
+}
+
+/// Draws raffle 0 with `seed`.
+fn draw(state: TashiTokenState, seed: Vec<u8>) -> TashiTokenState {
+    draw_raffle(context_at(account(0), SALES_END), state, 0, seed).0
+}
+
+/// The winners and prize of raffle 0, once drawn.
+fn result(state: &TashiTokenState) -> (Vec<Address>, u128) {
+    match &state.raffles.get(&0).unwrap().status {
+        RaffleStatus::Drawn { winners, prize } => (winners.clone(), *prize),
+        _ => panic!("Raffle is not drawn."),
+    }
+}
+
+#[test]
+fn pot_is_split_between_the_burn_the_treasury_and_the_winners() {
+    let state = raffle_state(2);
+    assert_eq!(state.balance_of(&account(1)), 9_798);
+    assert_eq!(state.raffles.get(&0).unwrap().pot, 303);
+
+    let state = draw(state, seed());
+    let (winners, prize) = result(&state);
+    assert_eq!(winners.len(), 2);
+    assert_eq!(prize, 106);
+    // the burned share is 30, the treasury gets 60 and the rounding remainder of the prize
+    assert_eq!(state.balance_of(&token_address()), 61);
+    assert_eq!(
+        state.balance_of(&account(1)) + state.balance_of(&account(2)),
+        20_000 - 303 + 212
+    );
+    assert_eq!(state.supply(), INITIAL_SUPPLY - 30);
+    assert!(state.raffles.get(&0).unwrap().tickets.is_empty());
+}
+
+#[test]
+fn every_ticket_wins_when_there_are_fewer_tickets_than_winners() {
+    let state = draw(raffle_state(5), seed());
+    let (mut winners, prize) = result(&state);
+    winners.sort();
+
+    assert_eq!(winners, vec![account(1), account(1), account(2)]);
+    assert_eq!(prize, 71);
+    assert_eq!(state.balance_of(&account(1)), 10_000 - 202 + 142);
+}
+
+#[test]
+#[should_panic(expected = "Seed does not match its commitment.")]
+fn draw_with_another_seed_is_rejected() {
+    draw(raffle_state(2), vec![8]);
+}
+
+#[test]
+fn undrawn_raffle_is_cancelled_and_refunded() {
+    let state = cancel_raffle(context_at(account(3), DRAW_DEADLINE), raffle_state(2), 0);
+
+    assert_eq!(state.balance_of(&account(1)), 10_000);
+    assert_eq!(state.balance_of(&account(2)), 10_000);
+    assert!(state.raffles.get(&0).unwrap().status == RaffleStatus::Cancelled {});
+}
+
+#[test]
+#[should_panic(expected = "Raffle can be drawn until: 2000")]
+fn raffle_is_not_cancelled_before_its_draw_deadline() {
+    cancel_raffle(
+        context_at(account(3), DRAW_DEADLINE - 1),
+        raffle_state(2),
+        0,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Ticket sales ended at: 1000")]
+fn tickets_are_not_sold_after_the_sales_end() {
+    buy_tickets(context_at(account(1), SALES_END), raffle_state(2), 0, 1);
+}
@@ -0,0 +1,321 @@
+//! Social recovery of accounts whose key is lost. An account registers guardians and a threshold
+//! of them needed to recover it. Guardians approve a new address for the account, and once enough
+//! of them agree on one, the recovery can be executed after a delay: the balance moves to the new
+//! address and the allowances, operators and session keys of the account are revoked. Until then
+//! the key of the account can veto the recovery, so guardians cannot take funds from a holder who
+//! still has their key.
+#![cfg_attr(not(feature = "social-recovery"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "social-recovery")]
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "social-recovery")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "social-recovery"))]
+mod tests;
+
+/// The largest number of guardians an account can have.
+pub const MAX_GUARDIANS: usize = 16;
+
+/// A recovery approved by enough guardians, waiting for its delay.
+///
+/// ### Fields:
+///
+///   * `new_address`: [`Address`], address the account is recovered to.
+///   * `executable_at`: [`i64`], block production time the recovery can be executed from, in
+///     milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PendingRecovery {
+    pub new_address: Address,
+    pub executable_at: i64,
+}
+
+/// The guardians of an account and their approvals.
+///
+/// ### Fields:
+///
+///   * `guardians`: [`Vec`]<[`Address`]>, accounts that can recover the account.
+///   * `threshold`: [`u32`], number of guardians that must approve the same new address.
+///   * `delay`: [`i64`], time the key of the account has to veto an approved recovery, in
+///     milliseconds.
+///   * `approvals`: [`SortedVecMap`]<[`Address`], [`Address`]>, new address each guardian approved.
+///   * `pending`: [`Option`]<[`PendingRecovery`]>, the recovery approved by enough guardians.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recovery {
+    pub guardians: Vec<Address>,
+    pub threshold: u32,
+    pub delay: i64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub approvals: SortedVecMap<Address, Address>,
+    pub pending: Option<PendingRecovery>,
+}
+
+impl Recovery {
+    /// Counts the guardians that approved `new_address`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `new_address`: [`Address`], the approved address.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u32`] number of approvals.
+    pub fn approvals_for(&self, new_address: &Address) -> u32 {
+        self.approvals
+            .iter()
+            .filter(|(_, approved)| *approved == new_address)
+            .count() as u32
+    }
+
+    /// Drops every approval and the pending recovery.
+    pub fn reset(&mut self) {
+        self.approvals = SortedVecMap::new();
+        self.pending = None;
+    }
+}
+
+/// Register `guardians` of caller address, `threshold` of which must approve the same new address
+/// to recover it after `delay`. Replaces any previous guardians, dropping their approvals and any
+/// pending recovery.
+///
+/// Panics if there are no guardians or more than [`MAX_GUARDIANS`], if they include the caller or
+/// a duplicate, if `threshold` is zero or exceeds the number of guardians, or if `delay` is not
+/// positive.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `guardians`: [`Vec`]<[`Address`]>, accounts that can recover the caller.
+///   * `threshold`: [`u32`], number of guardians that must approve the same new address.
+///   * `delay`: [`i64`], time the caller has to veto an approved recovery, in milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "social-recovery")]
+#[action(shortname = 0x89)]
+fn set_guardians(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    mut guardians: Vec<Address>,
+    threshold: u32,
+    delay: i64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "set_guardians");
+    assert!(
+        !guardians.is_empty() && guardians.len() <= MAX_GUARDIANS,
+        "Number of guardians must be between 1 and: {}",
+        MAX_GUARDIANS
+    );
+    assert!(
+        !guardians.contains(&ctx.sender),
+        "An account cannot be its own guardian."
+    );
+    guardians.sort();
+    guardians.dedup();
+    assert!(
+        threshold > 0 && threshold as usize <= guardians.len(),
+        "Threshold must be between 1 and the number of distinct guardians: {}",
+        guardians.len()
+    );
+    assert!(delay > 0, "Recovery delay must be positive.");
+    state.recoveries.insert(
+        ctx.sender,
+        Recovery {
+            guardians,
+            threshold,
+            delay,
+            approvals: SortedVecMap::new(),
+            pending: None,
+        },
+    );
+    state.assert_invariants();
+
+    state
+}
+
+/// Remove the guardians of caller address, dropping their approvals and any pending recovery.
+///
+/// Panics if the caller has no guardians.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "social-recovery")]
+#[action(shortname = 0x8a)]
+fn remove_guardians(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "remove_guardians");
+    state
+        .recoveries
+        .remove(&ctx.sender)
+        .expect("Caller has no guardians.");
+    state.assert_invariants();
+
+    state
+}
+
+/// Approve recovering `account` to `new_address` as one of its guardians, replacing any address
+/// the caller approved before. Once the threshold of guardians approve the same address, the
+/// recovery becomes executable after the delay of the account. A pending recovery that loses its
+/// approvals is dropped.
+///
+/// Panics if the caller is not a guardian of `account`, or if `new_address` is `account`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account to recover.
+///   * `new_address`: [`Address`], address to move the funds of `account` to.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "social-recovery")]
+#[action(shortname = 0x8b)]
+fn approve_recovery(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+    new_address: Address,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "approve_recovery");
+    assert!(
+        new_address != account,
+        "An account cannot be recovered to itself."
+    );
+    let recovery = state
+        .recoveries
+        .get_mut(&account)
+        .expect("Account has no guardians.");
+    assert!(
+        recovery.guardians.contains(&ctx.sender),
+        "Caller is not a guardian of the account."
+    );
+    recovery.approvals.insert(ctx.sender, new_address);
+
+    let still_approved = recovery
+        .pending
+        .take()
+        .filter(|pending| recovery.approvals_for(&pending.new_address) >= recovery.threshold);
+    recovery.pending = match still_approved {
+        Some(pending) if pending.new_address == new_address => Some(pending),
+        _ if recovery.approvals_for(&new_address) >= recovery.threshold => Some(PendingRecovery {
+            new_address,
+            executable_at: ctx.block_production_time + recovery.delay,
+        }),
+        still_approved => still_approved,
+    };
+    state.assert_invariants();
+
+    state
+}
+
+/// Veto the recovery of caller address, dropping every approval of its guardians.
+///
+/// Panics if the caller has no guardians or no guardian has approved a recovery.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "social-recovery")]
+#[action(shortname = 0x8c)]
+fn veto_recovery(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "veto_recovery");
+    let recovery = state
+        .recoveries
+        .get_mut(&ctx.sender)
+        .expect("Caller has no guardians.");
+    assert!(
+        !recovery.approvals.is_empty(),
+        "No recovery of the caller is approved."
+    );
+    recovery.reset();
+    state.assert_invariants();
+
+    state
+}
+
+/// Execute the pending recovery of `account` once its delay has passed. Every allowance, burn
+/// allowance, operator approval and session key of `account` is revoked, and its whole balance is
+/// moved to the new address. The guardians of `account` carry over to the new address unless it
+/// has guardians of its own. Transfer hooks do not apply.
+///
+/// Panics if `account` has no pending recovery, if its delay has not passed, or if `account` or the
+/// new address is frozen.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account to recover.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "social-recovery")]
+#[action(shortname = 0x8d)]
+fn execute_recovery(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "execute_recovery");
+    let mut recovery = state
+        .recoveries
+        .remove(&account)
+        .expect("Account has no guardians.");
+    let pending = recovery
+        .pending
+        .take()
+        .expect("Account has no approved recovery.");
+    assert!(
+        ctx.block_production_time >= pending.executable_at,
+        "Recovery can be executed from: {}",
+        pending.executable_at
+    );
+    let new_address = pending.new_address;
+    assert!(
+        !state.frozen_accounts.contains(&account) && !state.frozen_accounts.contains(&new_address),
+        "Account is frozen."
+    );
+
+    for spender in state.authorized_spenders(&account) {
+        state.revoke_spender(account, spender);
+    }
+    state.session_keys.remove(&account);
+    let balance = state.balance_of(&account);
+    state.debit(account, balance);
+    state.credit(new_address, balance);
+
+    if !state.recoveries.contains_key(&new_address) {
+        recovery.reset();
+        recovery
+            .guardians
+            .retain(|guardian| *guardian != new_address);
+        if recovery.threshold as usize <= recovery.guardians.len() {
+            state.recoveries.insert(new_address, recovery);
+        }
+    }
+    state.assert_invariants();
+
+    state
+}
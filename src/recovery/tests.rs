@@ -0,0 +1,102 @@
+//! Tests of social recovery: moving the balance once enough guardians agree and the delay passed,
+//! revoking the authorizations of the account, vetoes, and guardians changing their approval.
+
+use super::{approve_recovery, execute_recovery, set_guardians, veto_recovery};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state};
+use crate::token::TashiTokenState;
+
+/// Delay the key of the account has to veto a recovery.
+const DELAY: i64 = 86_400_000;
+
+/// A token where account 1 holds 10_000, approved account 7 for 500 and has guardians 2, 3 and
+/// 4, two of which must agree.
+fn guarded_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    state.update_allowance(account(1), account(7), 500);
+    set_guardians(
+        context(account(1)),
+        state,
+        vec![account(2), account(3), account(4)],
+        2,
+        DELAY,
+    )
+}
+
+/// Guardian `guardian` approves recovering account 1 to `new_address` at time zero.
+fn approve(state: TashiTokenState, guardian: u8, new_address: u8) -> TashiTokenState {
+    approve_recovery(
+        context(account(guardian)),
+        state,
+        account(1),
+        account(new_address),
+    )
+}
+
+#[test]
+fn recovery_moves_the_balance_and_revokes_the_authorizations() {
+    let state = approve(approve(guarded_state(), 2, 9), 3, 9);
+    let state = execute_recovery(context_at(account(5), DELAY), state, account(1));
+
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.balance_of(&account(9)), 10_000);
+    assert_eq!(state.allowance(&account(1), &account(7)), 0);
+    // the guardians carry over to the new address
+    assert!(state.recoveries.get(&account(1)).is_none());
+    assert_eq!(
+        state.recoveries.get(&account(9)).unwrap().guardians.len(),
+        3
+    );
+}
+
+#[test]
+#[should_panic(expected = "Recovery can be executed from: 86400000")]
+fn recovery_waits_for_its_delay() {
+    let state = approve(approve(guarded_state(), 2, 9), 3, 9);
+    execute_recovery(context_at(account(5), DELAY - 1), state, account(1));
+}
+
+#[test]
+#[should_panic(expected = "Account has no approved recovery.")]
+fn guardians_approving_different_addresses_do_not_recover() {
+    let state = approve(approve(guarded_state(), 2, 9), 3, 8);
+    execute_recovery(context_at(account(5), DELAY), state, account(1));
+}
+
+#[test]
+#[should_panic(expected = "Account has no approved recovery.")]
+fn guardian_changing_its_approval_drops_the_pending_recovery() {
+    let state = approve(approve(guarded_state(), 2, 9), 3, 9);
+    let state = approve(state, 3, 8);
+    execute_recovery(context_at(account(5), DELAY), state, account(1));
+}
+
+#[test]
+#[should_panic(expected = "Account has no approved recovery.")]
+fn vetoed_recovery_cannot_be_executed() {
+    let state = approve(approve(guarded_state(), 2, 9), 3, 9);
+    let state = veto_recovery(context(account(1)), state);
+    execute_recovery(context_at(account(5), DELAY), state, account(1));
+}
+
+#[test]
+#[should_panic(expected = "Caller is not a guardian of the account.")]
+fn only_guardians_approve_a_recovery() {
+    approve(guarded_state(), 5, 9);
+}
+
+#[test]
+#[should_panic(expected = "Threshold must be between 1 and the number of distinct guardians: 1")]
+fn threshold_counts_distinct_guardians() {
+    set_guardians(
+        context(account(1)),
+        new_state(),
+        vec![account(2), account(2)],
+        2,
+        DELAY,
+    );
+}
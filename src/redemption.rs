@@ -0,0 +1,283 @@
+//! Redemption of tokens against a reserve held by the contract, for asset-backed tokens. The
+//! reserve is another MPC-20 token, e.g. a BYOC coin, funded by anyone. A holder redeeming tokens
+//! burns them and is paid the pro-rata share of the reserve they stood for, so every remaining
+//! token stays backed by the same share of the reserve. If the payout fails, the tokens are minted
+//! again and the share returned to the reserve.
+#![cfg_attr(not(feature = "redemption"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "redemption")]
+use pbc_contract_common::context::{CallbackContext, ContractContext};
+#[cfg(feature = "redemption")]
+use pbc_contract_common::events::EventGroup;
+#[cfg(feature = "redemption")]
+use pbc_contract_common::shortname::ShortnameCallback;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "redemption")]
+use crate::mpc20::{call_transfer, call_transfer_from};
+use crate::token::mul_div_wide;
+#[cfg(feature = "redemption")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "redemption"))]
+mod tests;
+
+/// Shortname of the callback confirming the reserve was funded.
+#[cfg(feature = "redemption")]
+const FUND_RESERVE_CALLBACK_SHORTNAME: u32 = 0x08;
+
+/// Shortname of the callback rolling back a redemption whose payout failed.
+#[cfg(feature = "redemption")]
+const REDEEM_CALLBACK_SHORTNAME: u32 = 0x0e;
+
+/// The reserve backing the token.
+///
+/// ### Fields:
+///
+///   * `token`: [`Address`], MPC-20 token the reserve is held in.
+///   * `balance`: [`u128`], amount of the reserve token held.
+///   * `redeemed`: [`u128`], amount of tokens redeemed so far.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RedemptionReserve {
+    pub token: Address,
+    pub balance: u128,
+    pub redeemed: u128,
+}
+
+impl RedemptionReserve {
+    /// Gets the share of the reserve `amount` tokens out of `supply` stand for, rounded down.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `amount`: [`u128`], amount of tokens redeemed.
+    ///   * `supply`: [`u128`], the supply of the token, at least `amount`.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u128`] amount of the reserve token paid out.
+    pub fn payout_for(&self, amount: u128, supply: u128) -> u128 {
+        mul_div_wide(self.balance, amount, supply)
+    }
+}
+
+/// Set the MPC-20 token the reserve is held in. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if the current reserve still holds a balance.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `token`: [`Address`], MPC-20 token the reserve is held in.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "redemption")]
+#[action(shortname = 0x71)]
+fn configure_reserve(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    token: Address,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "configure_reserve", token);
+    if let Some(reserve) = &state.redemption_reserve {
+        assert!(reserve.balance == 0, "The reserve still holds a balance.");
+    }
+    assert!(
+        token != ctx.contract_address,
+        "The token cannot be its own reserve."
+    );
+    state.redemption_reserve = Some(RedemptionReserve {
+        token,
+        balance: 0,
+        redeemed: 0,
+    });
+    state.assert_invariants();
+
+    state
+}
+
+/// Add `amount` of the reserve token from caller address to the reserve. The caller must first
+/// approve this contract for `amount` on the reserve token. The reserve is credited once the
+/// transfer is confirmed. Anyone can call this action.
+///
+/// Panics if no reserve is configured or if `amount` is zero.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount of the reserve token to add.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "redemption")]
+#[action(shortname = 0x72)]
+fn fund_reserve(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "fund_reserve");
+    let reserve = state
+        .redemption_reserve
+        .as_ref()
+        .expect("No reserve is configured.");
+    assert!(amount > 0, "Funded amount cannot be zero.");
+
+    let mut event_group = EventGroup::builder();
+    call_transfer_from(
+        &mut event_group,
+        reserve.token,
+        ctx.sender,
+        ctx.contract_address,
+        amount,
+    );
+    event_group
+        .with_callback(ShortnameCallback::from_u32(FUND_RESERVE_CALLBACK_SHORTNAME))
+        .argument(reserve.token)
+        .argument(amount)
+        .done();
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Credit the reserve once its funding transfer is confirmed. Funding in a reserve token that was
+/// replaced in the meantime is not credited.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the incoming transfer.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `token`: [`Address`], the reserve token funded.
+///   * `amount`: [`u128`], amount funded.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "redemption")]
+#[callback(shortname = 0x08)]
+fn fund_reserve_callback(
+    _ctx: ContractContext,
+    callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+    token: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    if let Some(reserve) = state.redemption_reserve.as_mut() {
+        if callback_ctx.success && reserve.token == token {
+            reserve.balance += amount;
+        }
+    }
+    state.assert_invariants();
+
+    (state, vec![])
+}
+
+/// Burn `amount` tokens of caller address and pay out the share of the reserve they stand for.
+/// The tokens are minted again if the payout fails.
+///
+/// Panics if no reserve is configured, if `amount` is zero, if the share of the reserve rounds
+/// down to zero, or if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount of tokens to redeem.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state, the payout interaction and the supply notifications.
+#[cfg(feature = "redemption")]
+#[action(shortname = 0x73)]
+fn redeem(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "redeem");
+    assert!(amount > 0, "Redeemed amount cannot be zero.");
+    let supply = state.supply();
+    // amount is part of the supply once this succeeds
+    state.assert_can_spend(&ctx, &ctx.sender, amount);
+    state.burn_balance(ctx.sender, amount, ctx.block_production_time);
+    let reserve = state
+        .redemption_reserve
+        .as_mut()
+        .expect("No reserve is configured.");
+    let payout = reserve.payout_for(amount, supply);
+    assert!(payout > 0, "Redeemed amount is worth no reserve.");
+    reserve.balance -= payout;
+    reserve.redeemed += amount;
+    let reserve_token = reserve.token;
+
+    let mut event_group = EventGroup::builder();
+    call_transfer(&mut event_group, reserve_token, ctx.sender, payout);
+    event_group
+        .with_callback(ShortnameCallback::from_u32(REDEEM_CALLBACK_SHORTNAME))
+        .argument(ctx.sender)
+        .argument(amount)
+        .argument(reserve_token)
+        .argument(payout)
+        .done();
+
+    let mut events = vec![event_group.build()];
+    events.extend(state.supply_change_events(supply));
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Roll back a redemption whose payout failed, minting the tokens again and returning the payout
+/// to the reserve. The payout is not returned to a reserve whose token was replaced in the
+/// meantime.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the payout.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account that redeemed.
+///   * `amount`: [`u128`], amount of tokens redeemed.
+///   * `token`: [`Address`], the reserve token paid out.
+///   * `payout`: [`u128`], amount of the reserve token paid out.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "redemption")]
+#[callback(shortname = 0x0e)]
+fn redeem_callback(
+    _ctx: ContractContext,
+    callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+    account: Address,
+    amount: u128,
+    token: Address,
+    payout: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let supply = state.supply();
+    if !callback_ctx.success {
+        if let Some(reserve) = state.redemption_reserve.as_mut() {
+            if reserve.token == token {
+                reserve.balance += payout;
+                reserve.redeemed -= amount;
+            }
+        }
+        state.mint_balance(account, amount);
+    }
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
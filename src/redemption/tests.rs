@@ -0,0 +1,95 @@
+//! Tests of redemptions: the pro-rata payout, also at 18-decimal magnitudes, and the rollback of a
+//! failed payout.
+
+use super::{redeem, redeem_callback, RedemptionReserve};
+use crate::hooks::Transfer;
+use crate::testing::{
+    account, callback_context, context, contract, new_state, token_address, INITIAL_SUPPLY,
+};
+use crate::token::TashiTokenState;
+
+/// Amount of the reserve token held, ten times the supply.
+const RESERVE: u128 = 10 * INITIAL_SUPPLY;
+
+/// A token backed by [`RESERVE`] of contract 1, where account 1 holds a tenth of the supply.
+fn reserve_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.redemption_reserve = Some(RedemptionReserve {
+        token: contract(1),
+        balance: RESERVE,
+        redeemed: 0,
+    });
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), INITIAL_SUPPLY / 10, None),
+    );
+    state
+}
+
+#[test]
+fn payout_is_the_pro_rata_share_of_the_reserve() {
+    let (state, _) = redeem(context(account(1)), reserve_state(), INITIAL_SUPPLY / 10);
+    let reserve = state.redemption_reserve.as_ref().unwrap();
+    assert_eq!(reserve.balance, RESERVE - RESERVE / 10);
+    assert_eq!(reserve.redeemed, INITIAL_SUPPLY / 10);
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.supply(), INITIAL_SUPPLY - INITIAL_SUPPLY / 10);
+}
+
+#[test]
+fn failed_payout_is_rolled_back() {
+    let amount = INITIAL_SUPPLY / 10;
+    let (state, _) = redeem(context(account(1)), reserve_state(), amount);
+    let (state, _) = redeem_callback(
+        context(token_address()),
+        callback_context(false),
+        state,
+        account(1),
+        amount,
+        contract(1),
+        RESERVE / 10,
+    );
+    let reserve = state.redemption_reserve.as_ref().unwrap();
+    assert_eq!(reserve.balance, RESERVE);
+    assert_eq!(reserve.redeemed, 0);
+    assert_eq!(state.balance_of(&account(1)), amount);
+    assert_eq!(state.supply(), INITIAL_SUPPLY);
+}
+
+#[test]
+fn successful_payout_is_kept() {
+    let amount = INITIAL_SUPPLY / 10;
+    let (state, _) = redeem(context(account(1)), reserve_state(), amount);
+    let (state, _) = redeem_callback(
+        context(token_address()),
+        callback_context(true),
+        state,
+        account(1),
+        amount,
+        contract(1),
+        RESERVE / 10,
+    );
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.supply(), INITIAL_SUPPLY - amount);
+}
+
+#[test]
+fn payout_does_not_overflow_at_18_decimals() {
+    let reserve = RedemptionReserve {
+        token: contract(1),
+        balance: 3 * 10u128.pow(30) + 7,
+        redeemed: 0,
+    };
+    assert_eq!(
+        reserve.payout_for(10u128.pow(26) + 3, 10u128.pow(27) + 1),
+        300_000_000_000_000_000_000_000_008_700
+    );
+}
+
+#[test]
+#[should_panic(expected = "Redeemed amount is worth no reserve.")]
+fn redemption_worth_no_reserve_is_rejected() {
+    let mut state = reserve_state();
+    state.redemption_reserve.as_mut().unwrap().balance = 1;
+    redeem(context(account(1)), state, 1);
+}
@@ -0,0 +1,186 @@
+//! Redenomination, raising the decimals of the token after launch. Every balance, allowance and
+//! burn allowance and the total supply are multiplied by the same power of ten in one transaction,
+//! so each holder keeps its share of the supply, and the redenomination is recorded in state for
+//! indexers. Tokens held outside of `balances` would keep their old units, so a redenomination
+//! requires every token to sit in a balance. Amounts configured in other features, such as
+//! thresholds and fees, keep their old units and must be updated by the owner.
+#![cfg_attr(not(feature = "redenomination"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "redenomination")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "redenomination")]
+use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use read_write_state_derive::ReadWriteState;
+
+use crate::shards::BalanceEntries;
+use crate::token::{amount_value, stored_amount, BalanceMap, TashiTokenState};
+
+#[cfg(all(test, feature = "redenomination"))]
+mod tests;
+
+/// The largest number of decimals a redenomination can add at once.
+pub const MAX_REDENOMINATION_STEP: u8 = 18;
+
+/// The record of a redenomination.
+///
+/// ### Fields:
+///
+///   * `from_decimals`: [`u8`], decimals before the redenomination.
+///   * `to_decimals`: [`u8`], decimals after the redenomination.
+///   * `factor`: [`u128`], factor every amount was multiplied by.
+///   * `timestamp`: [`i64`], block production time of the redenomination, in milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Redenomination {
+    pub from_decimals: u8,
+    pub to_decimals: u8,
+    pub factor: u128,
+    pub timestamp: i64,
+}
+
+/// Multiplies `amount` by the redenomination `factor`.
+///
+/// Panics if the result overflows.
+///
+/// ### Parameters:
+///
+///   * `amount`: [`u128`], amount in the old units.
+///   * `factor`: [`u128`], factor of the redenomination.
+///
+/// ### Returns:
+///
+/// The [`u128`] amount in the new units.
+fn scale(amount: u128, factor: u128) -> u128 {
+    amount
+        .checked_mul(factor)
+        .expect("Overflow when redenominating.")
+}
+
+/// Multiplies every amount of `allowances` by the redenomination `factor`.
+///
+/// ### Parameters:
+///
+///   * `allowances`: [`SortedVecMap`]<[`Address`], [`SortedVecMap`]<[`Address`], `V`>>, amounts
+///     by owner and address.
+///   * `factor`: [`u128`], factor of the redenomination.
+///   * `from`: converts a stored amount to [`u128`].
+///   * `into`: converts a [`u128`] amount to its stored type.
+///
+/// ### Returns:
+///
+/// The scaled allowances.
+fn scale_allowances<V: Copy>(
+    allowances: &SortedVecMap<Address, SortedVecMap<Address, V>>,
+    factor: u128,
+    from: fn(V) -> u128,
+    into: fn(u128) -> V,
+) -> SortedVecMap<Address, SortedVecMap<Address, V>> {
+    let mut scaled = SortedVecMap::new();
+    for (owner, spenders) in allowances.iter() {
+        let mut scaled_spenders = SortedVecMap::new();
+        for (spender, amount) in spenders.iter() {
+            scaled_spenders.insert(*spender, into(scale(from(*amount), factor)));
+        }
+        scaled.insert(*owner, scaled_spenders);
+    }
+    scaled
+}
+
+impl TashiTokenState {
+    /// Raises the decimals of the token to `decimals`, multiplying every balance, allowance and
+    /// burn allowance and the total supply by the same power of ten, and records the
+    /// redenomination.
+    ///
+    /// Panics if `decimals` does not raise the decimals by 1 to [`MAX_REDENOMINATION_STEP`], if
+    /// tokens are held outside of `balances`, or if an amount overflows.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `decimals`: [`u8`], the new decimals.
+    ///   * `now`: [`i64`], the current block production time.
+    pub fn redenominate_to(&mut self, decimals: u8, now: i64) {
+        let step = decimals.checked_sub(self.decimals).unwrap_or(0);
+        assert!(
+            (1..=MAX_REDENOMINATION_STEP).contains(&step),
+            "Decimals: {}, must raise the current decimals: {}, by 1 to {}",
+            decimals,
+            self.decimals,
+            MAX_REDENOMINATION_STEP
+        );
+        let held: u128 = self
+            .balances
+            .entries()
+            .iter()
+            .map(|(_, balance)| amount_value(*balance))
+            .sum();
+        assert!(
+            held == amount_value(self.total_supply),
+            "Tokens are held outside of balances: {}",
+            amount_value(self.total_supply) - held
+        );
+        let factor = 10u128.pow(u32::from(step));
+
+        self.total_supply = stored_amount(scale(amount_value(self.total_supply), factor));
+        let balances: Vec<(Address, u128)> = self
+            .balances
+            .entries()
+            .iter()
+            .map(|(account, balance)| (*account, amount_value(*balance)))
+            .collect();
+        for (account, shares) in balances {
+            let new_shares = scale(shares, factor);
+            self.update_reward_shares(account, new_shares);
+            self.balances
+                .insert_balance(account, stored_amount(new_shares));
+        }
+        self.allowed = scale_allowances(&self.allowed, factor, amount_value, stored_amount);
+        self.burn_allowed = scale_allowances(&self.burn_allowed, factor, u128::from, u128::from);
+
+        self.redenominations.push(Redenomination {
+            from_decimals: self.decimals,
+            to_decimals: decimals,
+            factor,
+            timestamp: now,
+        });
+        self.decimals = decimals;
+    }
+}
+
+/// Redenominate the token to `decimals`, multiplying every balance, allowance and burn allowance
+/// and the total supply by the same power of ten. Only the owner can call this action, through the
+/// admin timelock with the `admin-timelock` feature, and governance can redenominate with a
+/// proposal.
+///
+/// Panics if the caller is not the owner, if `decimals` does not raise the decimals by 1 to
+/// [`MAX_REDENOMINATION_STEP`], if tokens are held outside of `balances`, or if an amount
+/// overflows.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `decimals`: [`u8`], the new decimals.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "redenomination")]
+#[action(shortname = 0xfb)]
+pub(crate) fn redenominate(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    decimals: u8,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    state.assert_owner_or_timelock(&ctx);
+    crate::audit::log_admin_action!(state, ctx, "redenominate", decimals);
+    let supply = state.supply();
+    state.redenominate_to(decimals, ctx.block_production_time);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
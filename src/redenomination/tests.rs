@@ -0,0 +1,51 @@
+//! Tests of redenomination: balances, allowances and the supply scaled by the same power of ten,
+//! the record kept in state, and redenominations that are rejected.
+
+use super::redenominate;
+use crate::allowance::approve;
+use crate::hooks::Transfer;
+use crate::testing::{account, admin_context, context, new_state, INITIAL_SUPPLY};
+use crate::token::{stored_amount, TashiTokenState};
+
+/// A token where account 1 holds 1_000 and allotted account 2 an allowance of 500.
+fn funded_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 1_000, None),
+    );
+    approve(context(account(1)), state, account(2), stored_amount(500))
+}
+
+#[test]
+fn every_amount_is_scaled_by_the_same_factor() {
+    let (state, _) = redenominate(admin_context(), funded_state(), 10);
+
+    assert_eq!(state.decimals, 10);
+    assert_eq!(state.supply(), INITIAL_SUPPLY * 100);
+    assert_eq!(
+        state.balance_of(&account(0)),
+        (INITIAL_SUPPLY - 1_000) * 100
+    );
+    assert_eq!(state.balance_of(&account(1)), 100_000);
+    assert_eq!(state.allowance(&account(1), &account(2)), 50_000);
+    let redenomination = &state.redenominations[0];
+    assert_eq!(redenomination.from_decimals, 8);
+    assert_eq!(redenomination.to_decimals, 10);
+    assert_eq!(redenomination.factor, 100);
+}
+
+#[test]
+#[should_panic(expected = "Decimals: 6, must raise the current decimals: 8, by 1 to 18")]
+fn decimals_cannot_be_lowered() {
+    redenominate(admin_context(), new_state(), 6);
+}
+
+#[test]
+#[should_panic(expected = "Tokens are held outside of balances: 100")]
+fn tokens_held_outside_of_balances_block_the_redenomination() {
+    let mut state = new_state();
+    state.debit(account(0), 100);
+    state.payroll_pool += 100;
+    redenominate(admin_context(), state, 10);
+}
@@ -0,0 +1,155 @@
+//! Referral attribution, paying a referrer a small bonus on every transfer sent by the accounts it
+//! referred. Bonuses are paid from an owner funded pool or minted.
+#![cfg_attr(not(feature = "referrals"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+#[cfg(feature = "referrals")]
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::{mul_div, TashiTokenState, BPS_DENOMINATOR};
+
+#[cfg(all(test, feature = "referrals"))]
+mod tests;
+
+/// The highest referral bonus the owner can configure, in basis points (1%).
+const MAX_REFERRAL_BONUS_BPS: u16 = 100;
+
+/// Where referral bonuses come from.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BonusSource {
+    /// Paid from the referral pool, while it lasts.
+    #[discriminant(0)]
+    Pool {},
+    /// Minted, increasing the total supply.
+    #[discriminant(1)]
+    Mint {},
+}
+
+/// Pays the referrer of the sender `referral_bonus_bps` of every transfer.
+pub struct ReferralBonus;
+
+impl TransferHook for ReferralBonus {
+    fn after_transfer(_ctx: &ContractContext, state: &mut TashiTokenState, transfer: &Transfer) {
+        let Some(referrer) = state.referrers.get(&transfer.from).copied() else {
+            return;
+        };
+        let bonus = mul_div(
+            transfer.amount,
+            u128::from(state.referral_bonus_bps),
+            BPS_DENOMINATOR,
+        );
+        match state.referral_source {
+            BonusSource::Pool {} => {
+                let paid = bonus.min(state.referral_pool);
+                state.referral_pool -= paid;
+                state.credit(referrer, paid);
+            }
+            BonusSource::Mint {} => state.mint_balance(referrer, bonus),
+        }
+    }
+}
+
+/// Register `referrer` as the account that referred the caller. A referrer can only be set once.
+///
+/// Panics if the caller already has a referrer or if `referrer` is the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `referrer`: [`Address`], account that referred the caller.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "referrals")]
+#[action(shortname = 0x40)]
+fn set_referrer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    referrer: Address,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "set_referrer");
+    assert!(referrer != ctx.sender, "Caller cannot refer itself.");
+    assert!(
+        !state.referrers.contains_key(&ctx.sender),
+        "Caller already has a referrer."
+    );
+    state.referrers.insert(ctx.sender, referrer);
+    state.assert_invariants();
+
+    state
+}
+
+/// Set the referral bonus and where it is paid from. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if `bonus_bps` exceeds the maximum referral bonus.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `bonus_bps`: [`u16`], share of every transfer paid to the referrer of the sender, in basis
+///     points.
+///   * `source`: [`BonusSource`], where bonuses are paid from.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "referrals")]
+#[action(shortname = 0x41)]
+fn configure_referrals(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    bonus_bps: u16,
+    source: BonusSource,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "configure_referrals", bonus_bps, source);
+    assert!(
+        bonus_bps <= MAX_REFERRAL_BONUS_BPS,
+        "Referral bonus: {}, maximum allowed referral bonus: {}",
+        bonus_bps,
+        MAX_REFERRAL_BONUS_BPS
+    );
+    state.referral_bonus_bps = bonus_bps;
+    state.referral_source = source;
+    state.assert_invariants();
+
+    state
+}
+
+/// Add `amount` tokens from caller address to the referral pool. Only the owner can call this
+/// action.
+///
+/// Panics if the caller is not the owner or if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to add to the pool.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "referrals")]
+#[action(shortname = 0x42)]
+fn fund_referral_pool(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "fund_referral_pool", amount);
+    state.escrow(&ctx, ctx.sender, amount);
+    state.referral_pool += amount;
+    state.assert_invariants();
+
+    state
+}
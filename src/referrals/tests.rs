@@ -0,0 +1,85 @@
+//! Tests of referral bonuses: paid from the pool while it lasts, minted, and the checks on
+//! referrers and on the bonus.
+
+use super::{configure_referrals, fund_referral_pool, set_referrer, BonusSource};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token paying a 1% bonus from `source`, where account 2, referred by account 1, holds 100_000
+/// and the referral pool holds 150.
+fn referral_state(source: BonusSource) -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(2), 100_000, None),
+    );
+    let state = set_referrer(context(account(2)), state, account(1));
+    let state = configure_referrals(context(account(0)), state, 100, source);
+    fund_referral_pool(context(account(0)), state, 150)
+}
+
+/// Transfers 10_000 from account 2 to account 3.
+fn transfer(state: &mut TashiTokenState) {
+    state.execute_transfer(
+        &context(account(2)),
+        Transfer::new(account(2), account(3), 10_000, None),
+    );
+}
+
+#[test]
+fn bonus_is_paid_from_the_pool_while_it_lasts() {
+    let mut state = referral_state(BonusSource::Pool {});
+    transfer(&mut state);
+    assert_eq!(state.balance_of(&account(1)), 100);
+    assert_eq!(state.referral_pool, 50);
+
+    transfer(&mut state);
+    transfer(&mut state);
+    assert_eq!(state.balance_of(&account(1)), 150);
+    assert_eq!(state.referral_pool, 0);
+    assert_eq!(state.supply(), INITIAL_SUPPLY);
+}
+
+#[test]
+fn bonus_is_minted() {
+    let mut state = referral_state(BonusSource::Mint {});
+    transfer(&mut state);
+
+    assert_eq!(state.balance_of(&account(1)), 100);
+    assert_eq!(state.referral_pool, 150);
+    assert_eq!(state.supply(), INITIAL_SUPPLY + 100);
+}
+
+#[test]
+fn transfers_to_a_referred_account_pay_no_bonus() {
+    let mut state = referral_state(BonusSource::Pool {});
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(2), 10_000, None),
+    );
+
+    assert_eq!(state.balance_of(&account(1)), 0);
+}
+
+#[test]
+#[should_panic(expected = "Caller already has a referrer.")]
+fn referrer_can_only_be_set_once() {
+    set_referrer(
+        context(account(2)),
+        referral_state(BonusSource::Pool {}),
+        account(4),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Caller cannot refer itself.")]
+fn caller_cannot_refer_itself() {
+    set_referrer(context(account(2)), new_state(), account(2));
+}
+
+#[test]
+#[should_panic(expected = "Referral bonus: 101, maximum allowed referral bonus: 100")]
+fn bonus_above_the_maximum_is_rejected() {
+    configure_referrals(context(account(0)), new_state(), 101, BonusSource::Mint {});
+}
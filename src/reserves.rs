@@ -0,0 +1,218 @@
+//! Proof-of-reserve attestations for backed and wrapped tokens. The owner registers the key of an
+//! attester and the interval it attests on. The attester signs the reserve backing the token at a
+//! point in time, anyone submits the signed attestation, and the contract records it with the
+//! backing ratio against the current supply. The latest attestation is kept in state and returned
+//! to other contracts by a getter.
+#![cfg_attr(not(feature = "reserve-attestations"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "reserve-attestations")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "reserve-attestations")]
+use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::Hash;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+use crate::signatures::{signed_message_hash, SchemeSignature, SignerKey};
+#[cfg(feature = "reserve-attestations")]
+use crate::token::TashiTokenState;
+use crate::token::{mul_div, BPS_DENOMINATOR};
+#[cfg(feature = "reserve-attestations")]
+use crate::views::return_value;
+
+/// Domain separator prefixed to every signed reserve attestation.
+const RESERVE_ATTESTATION_DOMAIN: &[u8] = b"TASHI_TOKEN_RESERVE_ATTESTATION";
+
+/// The attester of the reserve and its schedule.
+///
+/// ### Fields:
+///
+///   * `key`: [`SignerKey`], key the attester signs attestations with.
+///   * `interval`: [`i64`], time between two scheduled attestations, in milliseconds.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReserveAttester {
+    pub key: SignerKey,
+    pub interval: i64,
+}
+
+/// An attestation of the reserve backing the token.
+///
+/// ### Fields:
+///
+///   * `reserve`: [`u128`], amount of the reserve, in token units.
+///   * `supply`: [`u128`], supply of the token when the attestation was recorded.
+///   * `backing_ratio_bps`: [`u128`], reserve over supply in basis points, full backing without
+///     supply.
+///   * `attested_at`: [`i64`], time the attester observed the reserve, in milliseconds.
+///   * `next_due_at`: [`i64`], time the next scheduled attestation is due, in milliseconds.
+///   * `signature`: [`SchemeSignature`], signature of the attester.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReserveAttestation {
+    pub reserve: u128,
+    pub supply: u128,
+    pub backing_ratio_bps: u128,
+    pub attested_at: i64,
+    pub next_due_at: i64,
+    pub signature: SchemeSignature,
+}
+
+/// Gets the ratio of `reserve` to `supply` in basis points, full backing if there is no supply.
+///
+/// ### Parameters:
+///
+///   * `reserve`: [`u128`], amount of the reserve, in token units.
+///   * `supply`: [`u128`], supply of the token.
+///
+/// ### Returns:
+///
+/// The [`u128`] backing ratio in basis points.
+pub fn backing_ratio_bps(reserve: u128, supply: u128) -> u128 {
+    if supply == 0 {
+        BPS_DENOMINATOR
+    } else {
+        mul_div(reserve, BPS_DENOMINATOR, supply)
+    }
+}
+
+/// Computes the hash the attester signs to attest `reserve` at `attested_at`.
+///
+/// ### Parameters:
+///
+///   * `contract`: [`Address`], address of this contract.
+///   * `reserve`: [`u128`], amount of the reserve, in token units.
+///   * `attested_at`: [`i64`], time the attester observed the reserve, in milliseconds.
+///
+/// ### Returns:
+///
+/// The [`Hash`] to sign.
+pub fn reserve_attestation_hash(contract: &Address, reserve: u128, attested_at: i64) -> Hash {
+    let mut payload = reserve.to_be_bytes().to_vec();
+    payload.extend_from_slice(&attested_at.to_be_bytes());
+    signed_message_hash(RESERVE_ATTESTATION_DOMAIN, contract, &payload)
+}
+
+/// Set the attester of the reserve and the interval it attests on, or remove it with `None`. The
+/// latest attestation is kept. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if the interval is not positive.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `attester`: [`Option`]<[`ReserveAttester`]>, the attester, `None` to remove it.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "reserve-attestations")]
+#[action(shortname = 0xc2)]
+fn set_reserve_attester(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    attester: Option<ReserveAttester>,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    if let Some(attester) = &attester {
+        assert!(
+            attester.interval > 0,
+            "Attestation interval must be positive."
+        );
+    }
+    crate::audit::log_admin_action!(state, ctx, "set_reserve_attester", attester);
+    state.reserve_attester = attester;
+    state.assert_invariants();
+
+    state
+}
+
+/// Record an attestation of `reserve` signed by the attester, replacing the latest one. Anyone can
+/// submit an attestation.
+///
+/// Panics if no attester is set, if the signature is invalid, if `attested_at` is in the future, or
+/// if it is not after the latest attestation.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `reserve`: [`u128`], amount of the reserve, in token units.
+///   * `attested_at`: [`i64`], time the attester observed the reserve, in milliseconds.
+///   * `signature`: [`SchemeSignature`], signature of the attester, over the hash built by
+///     [`reserve_attestation_hash`].
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "reserve-attestations")]
+#[action(shortname = 0xc3)]
+fn attest_reserve(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    reserve: u128,
+    attested_at: i64,
+    signature: SchemeSignature,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "attest_reserve");
+    let attester = state
+        .reserve_attester
+        .as_ref()
+        .expect("No reserve attester is set.");
+    let message = reserve_attestation_hash(&ctx.contract_address, reserve, attested_at);
+    attester.key.assert_signed(
+        &message,
+        &signature,
+        "Invalid reserve attestation signature.",
+    );
+    assert!(
+        attested_at <= ctx.block_production_time,
+        "Attestation time: {} is in the future.",
+        attested_at
+    );
+    if let Some(latest) = &state.reserve_attestation {
+        assert!(
+            attested_at > latest.attested_at,
+            "Attestation time: {}, must be after the latest attestation: {}",
+            attested_at,
+            latest.attested_at
+        );
+    }
+    let supply = state.supply();
+    state.reserve_attestation = Some(ReserveAttestation {
+        reserve,
+        supply,
+        backing_ratio_bps: backing_ratio_bps(reserve, supply),
+        attested_at,
+        next_due_at: attested_at + attester.interval,
+        signature,
+    });
+    state.assert_invariants();
+
+    state
+}
+
+/// Return the latest reserve attestation, if any, to the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the attestation as an [`Option`]<[`ReserveAttestation`]>
+/// in the return data.
+#[cfg(feature = "reserve-attestations")]
+#[action(shortname = 0xc4)]
+fn query_reserve_attestation(
+    _ctx: ContractContext,
+    state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let events = return_value(state.reserve_attestation.clone());
+    state.assert_invariants();
+    (state, events)
+}
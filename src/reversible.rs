@@ -0,0 +1,320 @@
+//! Reversible transfers for enterprise treasury policies. An account opts in with a policy, and its
+//! transfers of at least the policy threshold are held by the contract for the dispute window of
+//! the policy instead of being credited. Until the window ends the sender or an arbiter can freeze
+//! the transfer or reverse it to the sender. Once the window has passed anyone settles an unfrozen
+//! transfer to its receiver, a frozen transfer waits for the sender or an arbiter to settle or
+//! reverse it. Every held transfer is kept in state with its outcome.
+#![cfg_attr(not(feature = "reversible-transfers"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "reversible-transfers")]
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::Hash;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "reversible-transfers")]
+use crate::admin::Role;
+#[cfg(feature = "reversible-transfers")]
+use crate::hooks::{Transfer, TransferHook};
+#[cfg(feature = "reversible-transfers")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "reversible-transfers"))]
+mod tests;
+
+/// The policy of an account holding its large transfers for a dispute window.
+///
+/// ### Fields:
+///
+///   * `threshold`: [`u128`], smallest transfer amount held.
+///   * `dispute_window`: [`i64`], time a transfer is held for, in milliseconds.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReversiblePolicy {
+    pub threshold: u128,
+    pub dispute_window: i64,
+}
+
+/// The progress of a held transfer.
+#[derive(ReadWriteState, CreateTypeSpec, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReversibleStatus {
+    /// Held, waiting for the dispute window to pass.
+    #[discriminant(0)]
+    Pending {},
+    /// Frozen by the sender or an arbiter, waiting for one of them to decide.
+    #[discriminant(1)]
+    Frozen {},
+    /// Credited to the receiver.
+    #[discriminant(2)]
+    Settled {},
+    /// Returned to the sender.
+    #[discriminant(3)]
+    Reversed {},
+}
+
+/// A transfer held for the dispute window of its sender.
+///
+/// ### Fields:
+///
+///   * `transaction`: [`Hash`], transaction of the transfer.
+///   * `from`: [`Address`], sender of the transfer.
+///   * `to`: [`Address`], receiver of the transfer.
+///   * `amount`: [`u128`], amount held, what the receiver is credited on settlement.
+///   * `settles_at`: [`i64`], block production time the dispute window ends at, in milliseconds.
+///   * `status`: [`ReversibleStatus`], progress of the transfer.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReversibleTransfer {
+    pub transaction: Hash,
+    pub from: Address,
+    pub to: Address,
+    pub amount: u128,
+    pub settles_at: i64,
+    pub status: ReversibleStatus,
+}
+
+/// Holds the transfers reaching the threshold of the policy of their sender, taking what the
+/// receiver would be credited.
+#[cfg(feature = "reversible-transfers")]
+pub struct ReversibleHold;
+
+#[cfg(feature = "reversible-transfers")]
+impl TransferHook for ReversibleHold {
+    fn before_transfer(
+        ctx: &ContractContext,
+        state: &mut TashiTokenState,
+        transfer: &mut Transfer,
+    ) {
+        let policy = match state.reversible_policies.get(&transfer.from) {
+            Some(policy) if transfer.amount >= policy.threshold && transfer.received > 0 => *policy,
+            _ => return,
+        };
+        let amount = transfer.received;
+        transfer.take(amount);
+        state.reversible_transfers.insert(
+            state.next_reversible_transfer_id,
+            ReversibleTransfer {
+                transaction: ctx.original_transaction,
+                from: transfer.from,
+                to: transfer.to,
+                amount,
+                settles_at: ctx.block_production_time + policy.dispute_window,
+                status: ReversibleStatus::Pending {},
+            },
+        );
+        state.next_reversible_transfer_id += 1;
+    }
+}
+
+#[cfg(feature = "reversible-transfers")]
+impl TashiTokenState {
+    /// Gets the held transfer `transfer_id`.
+    ///
+    /// Panics if no held transfer has the id.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `transfer_id`: [`u64`], the id of the transfer.
+    ///
+    /// ### Returns:
+    ///
+    /// A mutable reference to the [`ReversibleTransfer`].
+    fn reversible_transfer(&mut self, transfer_id: u64) -> &mut ReversibleTransfer {
+        self.reversible_transfers
+            .get_mut(&transfer_id)
+            .expect("Unknown reversible transfer.")
+    }
+
+    /// Checks that `caller` is the sender of a held transfer or an arbiter.
+    ///
+    /// Panics if `caller` is neither.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `sender`: [`Address`], sender of the transfer.
+    ///   * `caller`: [`Address`], account calling the action.
+    fn assert_sender_or_arbiter(&self, sender: &Address, caller: &Address) {
+        assert!(
+            caller == sender || self.has_role(Role::Arbiter {}, caller),
+            "Only the sender or an arbiter can act on a reversible transfer."
+        );
+    }
+}
+
+/// Set the policy holding the transfers of caller address for a dispute window, or opt out with
+/// `None`. Transfers already held keep their dispute window.
+///
+/// Panics if the dispute window is not positive.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `policy`: [`Option`]<[`ReversiblePolicy`]>, the policy, `None` to opt out.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "reversible-transfers")]
+#[action(shortname = 0xd1)]
+fn set_reversible_policy(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    policy: Option<ReversiblePolicy>,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "set_reversible_policy");
+    match policy {
+        Some(policy) => {
+            assert!(
+                policy.dispute_window > 0,
+                "Dispute window must be positive."
+            );
+            state.reversible_policies.insert(ctx.sender, policy);
+        }
+        None => {
+            state.reversible_policies.remove(&ctx.sender);
+        }
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Freeze the held transfer `transfer_id` during its dispute window, so it no longer settles on
+/// its own. Only the sender of the transfer or an account with the [`Role::Arbiter`] role can call
+/// this action.
+///
+/// Panics if the caller is neither, if the transfer is not pending, or if its dispute window has
+/// passed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `transfer_id`: [`u64`], the id of the transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "reversible-transfers")]
+#[action(shortname = 0xd2)]
+fn freeze_reversible_transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    transfer_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "freeze_reversible_transfer");
+    let from = state.reversible_transfer(transfer_id).from;
+    state.assert_sender_or_arbiter(&from, &ctx.sender);
+    let transfer = state.reversible_transfer(transfer_id);
+    assert!(
+        transfer.status == ReversibleStatus::Pending {},
+        "Transfer is not pending, status: {:?}",
+        transfer.status
+    );
+    assert!(
+        ctx.block_production_time < transfer.settles_at,
+        "Dispute window ended at: {}",
+        transfer.settles_at
+    );
+    transfer.status = ReversibleStatus::Frozen {};
+    state.assert_invariants();
+
+    state
+}
+
+/// Reverse the held transfer `transfer_id`, returning the amount to its sender. A pending transfer
+/// can only be reversed during its dispute window, a frozen one at any time. Only the sender of
+/// the transfer or an account with the [`Role::Arbiter`] role can call this action.
+///
+/// Panics if the caller is neither, if the transfer is settled or reversed, or if it is pending and
+/// its dispute window has passed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `transfer_id`: [`u64`], the id of the transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "reversible-transfers")]
+#[action(shortname = 0xd3)]
+fn reverse_reversible_transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    transfer_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "reverse_reversible_transfer");
+    let from = state.reversible_transfer(transfer_id).from;
+    state.assert_sender_or_arbiter(&from, &ctx.sender);
+    let transfer = state.reversible_transfer(transfer_id);
+    match transfer.status {
+        ReversibleStatus::Pending {} => assert!(
+            ctx.block_production_time < transfer.settles_at,
+            "Dispute window ended at: {}",
+            transfer.settles_at
+        ),
+        ReversibleStatus::Frozen {} => {}
+        status => panic!("Transfer is already decided, status: {:?}", status),
+    }
+    transfer.status = ReversibleStatus::Reversed {};
+    let amount = transfer.amount;
+    state.release(&ctx, from, amount);
+    state.assert_invariants();
+
+    state
+}
+
+/// Settle the held transfer `transfer_id`, crediting the amount to its receiver. Anyone can settle
+/// a pending transfer once its dispute window has passed, a frozen transfer can only be settled by
+/// its sender or an account with the [`Role::Arbiter`] role.
+///
+/// Panics if the transfer is settled or reversed, if it is pending and its dispute window has not
+/// passed, or if it is frozen and the caller is neither its sender nor an arbiter.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `transfer_id`: [`u64`], the id of the transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "reversible-transfers")]
+#[action(shortname = 0xd4)]
+fn settle_reversible_transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    transfer_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "settle_reversible_transfer");
+    let (from, status) = {
+        let transfer = state.reversible_transfer(transfer_id);
+        (transfer.from, transfer.status)
+    };
+    match status {
+        ReversibleStatus::Pending {} => {}
+        ReversibleStatus::Frozen {} => state.assert_sender_or_arbiter(&from, &ctx.sender),
+        status => panic!("Transfer is already decided, status: {:?}", status),
+    }
+    let transfer = state.reversible_transfer(transfer_id);
+    if status == (ReversibleStatus::Pending {}) {
+        assert!(
+            ctx.block_production_time >= transfer.settles_at,
+            "Transfer settles at: {}",
+            transfer.settles_at
+        );
+    }
+    transfer.status = ReversibleStatus::Settled {};
+    let (to, amount) = (transfer.to, transfer.amount);
+    state.release(&ctx, to, amount);
+    state.assert_invariants();
+
+    state
+}
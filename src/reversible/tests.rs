@@ -0,0 +1,122 @@
+//! Tests of reversible transfers: holding large transfers, settling them once the dispute window
+//! has passed, and freezing or reversing them before it ends.
+
+use super::{
+    freeze_reversible_transfer, reverse_reversible_transfer, set_reversible_policy,
+    settle_reversible_transfer, ReversiblePolicy, ReversibleStatus,
+};
+use crate::admin::{grant_role, Role};
+use crate::hooks::Transfer;
+use crate::testing::{account, admin_context, context, context_at, new_state};
+use crate::token::TashiTokenState;
+
+/// The dispute window of the policy.
+const DAY: i64 = 86_400_000;
+
+/// A token where account 1 holds 10_000 and holds its transfers of at least 1_000 for a day, and
+/// whose arbiter is account 9.
+fn policy_state() -> TashiTokenState {
+    let mut state = grant_role(admin_context(), new_state(), Role::Arbiter {}, account(9));
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    set_reversible_policy(
+        context(account(1)),
+        state,
+        Some(ReversiblePolicy {
+            threshold: 1_000,
+            dispute_window: DAY,
+        }),
+    )
+}
+
+/// The state of [`policy_state`] after account 1 sent 2_000 to account 2, held as transfer 0.
+fn held_state() -> TashiTokenState {
+    let mut state = policy_state();
+    state.execute_transfer(
+        &context(account(1)),
+        Transfer::new(account(1), account(2), 2_000, None),
+    );
+    state
+}
+
+#[test]
+fn large_transfer_is_held() {
+    let state = held_state();
+
+    assert_eq!(state.balance_of(&account(1)), 8_000);
+    assert_eq!(state.balance_of(&account(2)), 0);
+    let transfer = state.reversible_transfers.get(&0).unwrap();
+    assert_eq!(transfer.amount, 2_000);
+    assert_eq!(transfer.settles_at, DAY);
+    assert_eq!(transfer.status, ReversibleStatus::Pending {});
+}
+
+#[test]
+fn small_transfer_is_credited_right_away() {
+    let mut state = policy_state();
+    state.execute_transfer(
+        &context(account(1)),
+        Transfer::new(account(1), account(2), 999, None),
+    );
+
+    assert_eq!(state.balance_of(&account(2)), 999);
+    assert!(state.reversible_transfers.is_empty());
+}
+
+#[test]
+fn anyone_settles_once_the_window_has_passed() {
+    let state = settle_reversible_transfer(context_at(account(5), DAY), held_state(), 0);
+
+    assert_eq!(state.balance_of(&account(2)), 2_000);
+    assert_eq!(
+        state.reversible_transfers.get(&0).unwrap().status,
+        ReversibleStatus::Settled {}
+    );
+}
+
+#[test]
+#[should_panic(expected = "Transfer settles at: 86400000")]
+fn transfer_is_not_settled_during_the_window() {
+    settle_reversible_transfer(context_at(account(5), DAY - 1), held_state(), 0);
+}
+
+#[test]
+fn sender_reverses_during_the_window() {
+    let state = reverse_reversible_transfer(context_at(account(1), DAY - 1), held_state(), 0);
+
+    assert_eq!(state.balance_of(&account(1)), 10_000);
+    assert_eq!(
+        state.reversible_transfers.get(&0).unwrap().status,
+        ReversibleStatus::Reversed {}
+    );
+}
+
+#[test]
+#[should_panic(expected = "Dispute window ended at: 86400000")]
+fn pending_transfer_is_not_reversed_after_the_window() {
+    reverse_reversible_transfer(context_at(account(1), DAY), held_state(), 0);
+}
+
+#[test]
+fn arbiter_reverses_a_frozen_transfer_after_the_window() {
+    let state = freeze_reversible_transfer(context(account(9)), held_state(), 0);
+    let state = reverse_reversible_transfer(context_at(account(9), 2 * DAY), state, 0);
+
+    assert_eq!(state.balance_of(&account(1)), 10_000);
+    assert_eq!(state.balance_of(&account(2)), 0);
+}
+
+#[test]
+#[should_panic(expected = "Only the sender or an arbiter can act on a reversible transfer.")]
+fn frozen_transfer_does_not_settle_on_its_own() {
+    let state = freeze_reversible_transfer(context(account(1)), held_state(), 0);
+    settle_reversible_transfer(context_at(account(5), DAY), state, 0);
+}
+
+#[test]
+#[should_panic(expected = "Only the sender or an arbiter can act on a reversible transfer.")]
+fn receiver_cannot_reverse() {
+    reverse_reversible_transfer(context(account(2)), held_state(), 0);
+}
@@ -0,0 +1,177 @@
+//! Pull based rewards using an accumulated reward per share. Funding the pool raises the
+//! accumulator, and each account settles its rewards lazily whenever its balance changes or it
+//! claims, so distributing touches no holder and claiming touches only the claimer.
+#![cfg_attr(not(feature = "rewards"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "rewards")]
+use pbc_contract_common::context::ContractContext;
+use read_write_state_derive::ReadWriteState;
+
+use crate::token::{mul_div, TashiTokenState};
+
+#[cfg(all(test, feature = "rewards"))]
+mod tests;
+
+/// The scale of `reward_per_share`.
+pub const REWARD_SCALE: u128 = 1_000_000_000_000_000_000;
+
+/// The rewards pool shared by every holder.
+///
+/// ### Fields:
+///
+///   * `reward_per_share`: [`u128`], rewards accumulated per share of balance since deployment,
+///     scaled by 10^18.
+///   * `tracked_shares`: [`u128`], sum of all balances, in shares.
+///   * `balance`: [`u128`], funded rewards not yet claimed, including rounding dust.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RewardsPool {
+    pub reward_per_share: u128,
+    pub tracked_shares: u128,
+    pub balance: u128,
+}
+
+impl RewardsPool {
+    /// Creates an empty pool tracking the initial balances.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `tracked_shares`: [`u128`], sum of the initial balances, in shares.
+    ///
+    /// ### Returns:
+    ///
+    /// The new [`RewardsPool`].
+    pub fn new(tracked_shares: u128) -> Self {
+        RewardsPool {
+            reward_per_share: 0,
+            tracked_shares,
+            balance: 0,
+        }
+    }
+}
+
+/// The rewards of a single account.
+///
+/// ### Fields:
+///
+///   * `checkpoint`: [`u128`], `reward_per_share` the account last settled at.
+///   * `claimable`: [`u128`], rewards settled but not yet claimed.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RewardAccount {
+    pub checkpoint: u128,
+    pub claimable: u128,
+}
+
+impl TashiTokenState {
+    /// Settles the rewards `account` earned since its last checkpoint.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], the account to settle.
+    pub fn settle_rewards(&mut self, account: Address) {
+        let shares = self.shares_of(&account);
+        let reward_per_share = self.rewards.reward_per_share;
+        // accounts without an entry either hold nothing or have held their balance since deployment
+        let (checkpoint, claimable) = match self.reward_accounts.get(&account) {
+            Some(reward_account) => (reward_account.checkpoint, reward_account.claimable),
+            None if shares == 0 => (reward_per_share, 0),
+            None => (0, 0),
+        };
+        let earned = mul_div(shares, reward_per_share - checkpoint, REWARD_SCALE);
+        self.reward_accounts.insert(
+            account,
+            RewardAccount {
+                checkpoint: reward_per_share,
+                claimable: claimable + earned,
+            },
+        );
+    }
+
+    /// Settles the rewards of `account` before its balance changes to `new_shares`. Called for
+    /// every balance update.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], the account whose balance changes.
+    ///   * `new_shares`: [`u128`], the new balance of the account, in shares.
+    pub fn update_reward_shares(&mut self, account: Address, new_shares: u128) {
+        if !cfg!(feature = "rewards") {
+            return;
+        }
+        self.settle_rewards(account);
+        self.rewards.tracked_shares =
+            self.rewards.tracked_shares - self.shares_of(&account) + new_shares;
+        let claimable = self.reward_accounts.get(&account).unwrap().claimable;
+        if new_shares == 0 && claimable == 0 {
+            self.reward_accounts.remove(&account); // nothing left to track
+        }
+    }
+}
+
+/// Fund the rewards pool with `amount` tokens from caller address, shared by all holders in
+/// proportion to their balances. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner, if there is insufficient balance in caller account, or if
+/// no other balances are left to share the rewards.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to distribute.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "rewards")]
+#[action(shortname = 0x3e)]
+fn fund_rewards(ctx: ContractContext, mut state: TashiTokenState, amount: u128) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "fund_rewards", amount);
+    state.escrow(&ctx, ctx.sender, amount);
+    assert!(
+        state.rewards.tracked_shares > 0,
+        "No balances to distribute rewards to."
+    );
+    let increase = mul_div(amount, REWARD_SCALE, state.rewards.tracked_shares);
+    state.rewards.reward_per_share = state
+        .rewards
+        .reward_per_share
+        .checked_add(increase)
+        .expect("Overflow when adding to reward per share.");
+    state.rewards.balance += amount;
+    state.assert_invariants();
+
+    state
+}
+
+/// Claim the rewards earned by caller address.
+///
+/// Panics if the caller has no rewards to claim.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "rewards")]
+#[action(shortname = 0x3f)]
+pub(crate) fn claim_rewards(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "claim_rewards");
+    state.settle_rewards(ctx.sender);
+    let reward_account = state.reward_accounts.get_mut(&ctx.sender).unwrap();
+    let claimed = reward_account.claimable;
+    assert!(claimed > 0, "No rewards to claim.");
+    reward_account.claimable = 0;
+    state.rewards.balance -= claimed; // settled rewards never exceed what was funded
+    state.credit(ctx.sender, claimed);
+    state.assert_invariants();
+
+    state
+}
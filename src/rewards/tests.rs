@@ -0,0 +1,69 @@
+//! Tests of pull based rewards: sharing a funding by balance, settling before balances change,
+//! late holders which earn nothing of earlier fundings, and rounding dust kept in the pool.
+
+use super::{claim_rewards, fund_rewards};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, new_state};
+use crate::token::TashiTokenState;
+
+/// A token where accounts 1 and 2 hold 300_000 and 600_000, and the owner funded its remaining
+/// 100_000 as rewards.
+fn funded_state() -> TashiTokenState {
+    let mut state = new_state();
+    for (index, amount) in [(1, 300_000), (2, 600_000)] {
+        state.execute_transfer(
+            &context(account(0)),
+            Transfer::new(account(0), account(index), amount, None),
+        );
+    }
+    fund_rewards(context(account(0)), state, 100_000)
+}
+
+#[test]
+fn rewards_are_shared_by_balance() {
+    let state = claim_rewards(context(account(1)), funded_state());
+    let state = claim_rewards(context(account(2)), state);
+
+    assert_eq!(state.balance_of(&account(1)), 333_333);
+    assert_eq!(state.balance_of(&account(2)), 666_666);
+    // the rounding dust stays in the pool
+    assert_eq!(state.rewards.balance, 1);
+    assert_eq!(state.rewards.tracked_shares, 999_999);
+}
+
+#[test]
+fn rewards_are_settled_before_a_balance_changes() {
+    let mut state = funded_state();
+    state.execute_transfer(
+        &context(account(1)),
+        Transfer::new(account(1), account(3), 300_000, None),
+    );
+    let state = claim_rewards(context(account(1)), state);
+
+    assert_eq!(state.balance_of(&account(1)), 33_333);
+    assert_eq!(state.balance_of(&account(3)), 300_000);
+}
+
+#[test]
+#[should_panic(expected = "No rewards to claim.")]
+fn late_holder_earns_nothing_of_earlier_fundings() {
+    let mut state = funded_state();
+    state.execute_transfer(
+        &context(account(1)),
+        Transfer::new(account(1), account(3), 300_000, None),
+    );
+    claim_rewards(context(account(3)), state);
+}
+
+#[test]
+#[should_panic(expected = "No rewards to claim.")]
+fn rewards_cannot_be_claimed_twice() {
+    let state = claim_rewards(context(account(1)), funded_state());
+    claim_rewards(context(account(1)), state);
+}
+
+#[test]
+#[should_panic(expected = "No balances to distribute rewards to.")]
+fn funding_without_other_holders_is_rejected() {
+    fund_rewards(context(account(0)), new_state(), 1_000_000);
+}
@@ -0,0 +1,120 @@
+//! Charity round-ups. Holders opt in with a precision, and each of their outgoing transfers is
+//! rounded up to a multiple of it, the difference being donated to the charity set by the owner.
+//! The total each holder donated is kept in state. A round-up the sender cannot afford is skipped
+//! rather than failing the transfer.
+#![cfg_attr(not(feature = "round-up"), allow(dead_code))]
+
+#[cfg(feature = "round-up")]
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "round-up"))]
+mod tests;
+
+/// Donates the round-up of every transfer from an opted in account to the charity.
+pub struct CharityRoundUp;
+
+impl TransferHook for CharityRoundUp {
+    fn after_transfer(_ctx: &ContractContext, state: &mut TashiTokenState, transfer: &Transfer) {
+        let Some(charity) = state.charity else {
+            return;
+        };
+        let Some(precision) = state.round_up_precisions.get(&transfer.from).copied() else {
+            return;
+        };
+        let donation = round_up_of(transfer.amount, precision);
+        if donation == 0 || transfer.from == charity || state.balance_of(&transfer.from) < donation
+        {
+            return;
+        }
+        state.debit(transfer.from, donation);
+        state.credit(charity, donation);
+        let donated = state
+            .round_up_donated
+            .get(&transfer.from)
+            .copied()
+            .unwrap_or(0);
+        state
+            .round_up_donated
+            .insert(transfer.from, donated.saturating_add(donation));
+    }
+}
+
+/// Computes what rounds `amount` up to a multiple of `precision`.
+///
+/// ### Parameters:
+///
+///   * `amount`: [`u128`], the amount transferred.
+///   * `precision`: [`u128`], the positive precision to round up to.
+///
+/// ### Returns:
+///
+/// The [`u128`] difference, zero if `amount` is already a multiple of `precision`.
+fn round_up_of(amount: u128, precision: u128) -> u128 {
+    match amount % precision {
+        0 => 0,
+        remainder => precision - remainder,
+    }
+}
+
+/// Set the charity receiving round-ups, none to stop donating them. Only the owner can call this
+/// action.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `charity`: [`Option`]<[`Address`]>, the charity.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "round-up")]
+#[action(shortname = 0xaa)]
+fn set_charity(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    charity: Option<Address>,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_charity", charity);
+    state.charity = charity;
+    state.assert_invariants();
+
+    state
+}
+
+/// Round up every outgoing transfer of the caller to a multiple of `precision`, donating the
+/// difference to the charity. A zero precision opts out.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `precision`: [`u128`], the precision to round up to, zero to opt out.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "round-up")]
+#[action(shortname = 0xab)]
+fn set_round_up(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    precision: u128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "set_round_up");
+    if precision == 0 {
+        state.round_up_precisions.remove(&ctx.sender);
+    } else {
+        state.round_up_precisions.insert(ctx.sender, precision);
+    }
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,67 @@
+//! Tests of charity round-ups: donating the difference to the next multiple, skipping round-ups
+//! that cannot be afforded, and opting out.
+
+use super::{set_charity, set_round_up};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, new_state};
+use crate::token::TashiTokenState;
+
+/// A token donating to charity account 3, where account 1 holds `funds` and rounds its transfers up
+/// to multiples of 100.
+fn round_up_state(funds: u128) -> TashiTokenState {
+    let mut state = set_charity(context(account(0)), new_state(), Some(account(3)));
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), funds, None),
+    );
+    set_round_up(context(account(1)), state, 100)
+}
+
+/// Transfers `amount` from account 1 to account 2.
+fn send(state: &mut TashiTokenState, amount: u128) {
+    state.execute_transfer(
+        &context(account(1)),
+        Transfer::new(account(1), account(2), amount, None),
+    );
+}
+
+#[test]
+fn round_up_is_donated_to_the_charity() {
+    let mut state = round_up_state(1_000);
+    send(&mut state, 250);
+    send(&mut state, 300);
+
+    assert_eq!(state.balance_of(&account(1)), 400);
+    assert_eq!(state.balance_of(&account(2)), 550);
+    assert_eq!(state.balance_of(&account(3)), 50);
+    assert_eq!(state.round_up_donated.get(&account(1)).copied(), Some(50));
+}
+
+#[test]
+fn unaffordable_round_up_is_skipped() {
+    let mut state = round_up_state(280);
+    send(&mut state, 250);
+
+    assert_eq!(state.balance_of(&account(1)), 30);
+    assert_eq!(state.balance_of(&account(2)), 250);
+    assert_eq!(state.balance_of(&account(3)), 0);
+    assert!(state.round_up_donated.get(&account(1)).is_none());
+}
+
+#[test]
+fn opted_out_transfers_are_not_rounded_up() {
+    let mut state = set_round_up(context(account(1)), round_up_state(1_000), 0);
+    send(&mut state, 250);
+
+    assert_eq!(state.balance_of(&account(1)), 750);
+    assert_eq!(state.balance_of(&account(3)), 0);
+}
+
+#[test]
+fn nothing_is_donated_without_a_charity() {
+    let mut state = set_charity(context(account(0)), round_up_state(1_000), None);
+    send(&mut state, 250);
+
+    assert_eq!(state.balance_of(&account(1)), 750);
+    assert_eq!(state.balance_of(&account(3)), 0);
+}
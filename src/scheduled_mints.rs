@@ -0,0 +1,132 @@
+//! Scheduled mints, an issuance calendar enforced by the contract. The owner schedules mints of an
+//! amount to a destination at a future time, and anyone can trigger a mint once it is due. A
+//! scheduled mint cannot be changed or cancelled, so the calendar in state binds the owner.
+#![cfg_attr(not(feature = "scheduled-mints"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "scheduled-mints")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "scheduled-mints")]
+use pbc_contract_common::events::EventGroup;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "scheduled-mints")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "scheduled-mints"))]
+mod tests;
+
+/// The largest number of mints that can be scheduled at once.
+pub const MAX_SCHEDULED_MINTS: usize = 256;
+
+/// A mint scheduled for a future time.
+///
+/// ### Fields:
+///
+///   * `destination`: [`Address`], account receiving the mint.
+///   * `amount`: [`u128`], amount to mint.
+///   * `execute_at`: [`i64`], block production time the mint can be triggered from, in
+///     milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScheduledMint {
+    pub destination: Address,
+    pub amount: u128,
+    pub execute_at: i64,
+}
+
+/// Schedule a mint of `amount` tokens to `destination` at `execute_at`. Only the owner can call
+/// this action.
+///
+/// Panics if the caller is not the owner, if `amount` is zero, if `execute_at` is not in the
+/// future, or if [`MAX_SCHEDULED_MINTS`] mints are already scheduled.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `destination`: [`Address`], account receiving the mint.
+///   * `amount`: [`u128`], amount to mint.
+///   * `execute_at`: [`i64`], block production time the mint can be triggered from, in
+///     milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "scheduled-mints")]
+#[action(shortname = 0xa0)]
+fn schedule_mint(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    destination: Address,
+    amount: u128,
+    execute_at: i64,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "schedule_mint", destination, amount, execute_at);
+    assert!(amount > 0, "Scheduled mint amount cannot be zero.");
+    assert!(
+        execute_at > ctx.block_production_time,
+        "Scheduled mint time must be in the future."
+    );
+    assert!(
+        state.scheduled_mints.len() < MAX_SCHEDULED_MINTS,
+        "Scheduled mints: {}, maximum is: {}",
+        state.scheduled_mints.len(),
+        MAX_SCHEDULED_MINTS
+    );
+    state.scheduled_mints.insert(
+        state.next_scheduled_mint_id,
+        ScheduledMint {
+            destination,
+            amount,
+            execute_at,
+        },
+    );
+    state.next_scheduled_mint_id += 1;
+    state.assert_invariants();
+
+    state
+}
+
+/// Mint the scheduled mint `mint_id` to its destination once it is due. Anyone can call this
+/// action.
+///
+/// Panics if no mint with the id is scheduled, if it is not due yet, or if minting causes an
+/// overflow.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `mint_id`: [`u64`], the id of the scheduled mint.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "scheduled-mints")]
+#[action(shortname = 0xa1)]
+fn trigger_scheduled_mint(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    mint_id: u64,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "trigger_scheduled_mint");
+    let scheduled = state
+        .scheduled_mints
+        .remove(&mint_id)
+        .expect("No mint is scheduled with this id.");
+    assert!(
+        ctx.block_production_time >= scheduled.execute_at,
+        "Mint is scheduled for: {}",
+        scheduled.execute_at
+    );
+    let supply = state.supply();
+    state.mint_balance(scheduled.destination, scheduled.amount);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
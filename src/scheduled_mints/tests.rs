@@ -0,0 +1,53 @@
+//! Tests of scheduled mints: minting once due, and mints which are not due, unknown or triggered
+//! twice.
+
+use super::{schedule_mint, trigger_scheduled_mint};
+use crate::testing::{account, context, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token where the owner scheduled mints of 500 to account 1 at 1_000 and of 700 to account 2 at
+/// 2_000.
+fn scheduled_state() -> TashiTokenState {
+    let state = schedule_mint(context(account(0)), new_state(), account(1), 500, 1_000);
+    schedule_mint(context(account(0)), state, account(2), 700, 2_000)
+}
+
+#[test]
+fn due_mint_is_minted_by_anyone() {
+    let (state, _) = trigger_scheduled_mint(context_at(account(5), 1_000), scheduled_state(), 0);
+
+    assert_eq!(state.balance_of(&account(1)), 500);
+    assert_eq!(state.supply(), INITIAL_SUPPLY + 500);
+    assert_eq!(state.scheduled_mints.len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "Mint is scheduled for: 2000")]
+fn mint_is_not_minted_before_it_is_due() {
+    trigger_scheduled_mint(context_at(account(5), 1_999), scheduled_state(), 1);
+}
+
+#[test]
+#[should_panic(expected = "No mint is scheduled with this id.")]
+fn mint_is_minted_once() {
+    let (state, _) = trigger_scheduled_mint(context_at(account(5), 1_000), scheduled_state(), 0);
+    trigger_scheduled_mint(context_at(account(5), 1_000), state, 0);
+}
+
+#[test]
+#[should_panic(expected = "Scheduled mint time must be in the future.")]
+fn mint_cannot_be_scheduled_in_the_past() {
+    schedule_mint(
+        context_at(account(0), 1_000),
+        new_state(),
+        account(1),
+        500,
+        1_000,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can perform this action.")]
+fn only_the_owner_schedules_mints() {
+    schedule_mint(context(account(1)), new_state(), account(1), 500, 1_000);
+}
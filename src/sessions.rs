@@ -0,0 +1,297 @@
+//! Session keys, secondary accounts transferring and approving on behalf of an account within a
+//! scope, so the key of the account can stay cold. An account registers a session key with the
+//! actions it may send, the largest amount of a single action, a cap on the total amount of its
+//! actions and an expiry. The session key then calls the session actions naming the account, and
+//! each is executed as if the account sent it.
+#![cfg_attr(not(feature = "session-keys"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "session-keys")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "session-keys")]
+use pbc_contract_common::events::EventGroup;
+#[cfg(feature = "session-keys")]
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "session-keys")]
+use crate::hooks::Transfer;
+#[cfg(feature = "session-keys")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "session-keys"))]
+mod tests;
+
+/// The largest number of session keys an account can have.
+pub const MAX_SESSION_KEYS: usize = 16;
+
+/// An action a session key can send.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SessionAction {
+    /// A transfer from the account.
+    #[discriminant(0)]
+    Transfer {},
+    /// An approval by the account.
+    #[discriminant(1)]
+    Approve {},
+}
+
+/// What a session key may do on behalf of an account.
+///
+/// ### Fields:
+///
+///   * `actions`: [`Vec`]<[`SessionAction`]>, actions the key may send.
+///   * `max_amount`: [`u128`], largest amount of a single action.
+///   * `total_cap`: [`u128`], largest total amount of the actions of the key.
+///   * `expires_at`: [`i64`], block production time the key stops working at, in milliseconds.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionScope {
+    pub actions: Vec<SessionAction>,
+    pub max_amount: u128,
+    pub total_cap: u128,
+    pub expires_at: i64,
+}
+
+/// A registered session key.
+///
+/// ### Fields:
+///
+///   * `scope`: [`SessionScope`], what the key may do.
+///   * `spent`: [`u128`], total amount of the actions the key sent, counted against the cap.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionKey {
+    pub scope: SessionScope,
+    pub spent: u128,
+}
+
+#[cfg(feature = "session-keys")]
+impl TashiTokenState {
+    /// Authorizes `key` to send `action` of `amount` on behalf of `account`, counting the amount
+    /// against the cap of the key.
+    ///
+    /// Panics if `key` is not a session key of `account`, if it has expired, if its scope does not
+    /// include `action`, or if `amount` exceeds the largest amount of an action or the rest of the
+    /// cap.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account the action is sent for.
+    ///   * `key`: [`Address`], the session key sending the action.
+    ///   * `action`: [`SessionAction`], the action.
+    ///   * `amount`: [`u128`], amount of the action.
+    ///   * `now`: [`i64`], the current block production time.
+    pub fn authorize_session_action(
+        &mut self,
+        account: &Address,
+        key: &Address,
+        action: SessionAction,
+        amount: u128,
+        now: i64,
+    ) {
+        let session = self
+            .session_keys
+            .get_mut(account)
+            .and_then(|account_keys| account_keys.get_mut(key))
+            .expect("Caller is not a session key of the account.");
+        let scope = &session.scope;
+        assert!(
+            now < scope.expires_at,
+            "Session key expired at: {}",
+            scope.expires_at
+        );
+        assert!(
+            scope.actions.contains(&action),
+            "Session key is not allowed this action."
+        );
+        assert!(
+            amount <= scope.max_amount,
+            "Amount: {}, exceeds the session maximum: {}",
+            amount,
+            scope.max_amount
+        );
+        let spent = session.spent.saturating_add(amount);
+        assert!(
+            spent <= scope.total_cap,
+            "Session key would spend: {}, cap is: {}",
+            spent,
+            scope.total_cap
+        );
+        session.spent = spent;
+    }
+}
+
+/// Register `key` as a session key of caller address within `scope`, replacing any previous
+/// registration of the key and resetting what it spent.
+///
+/// Panics if `key` is the caller, if the scope has no actions or has already expired, or if the
+/// caller would have more than [`MAX_SESSION_KEYS`] session keys.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `key`: [`Address`], the session key.
+///   * `scope`: [`SessionScope`], what the key may do.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "session-keys")]
+#[action(shortname = 0x81)]
+fn register_session_key(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    key: Address,
+    scope: SessionScope,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "register_session_key");
+    assert!(
+        key != ctx.sender,
+        "An account cannot be its own session key."
+    );
+    assert!(
+        !scope.actions.is_empty(),
+        "A session key must be allowed some action."
+    );
+    assert!(
+        scope.expires_at > ctx.block_production_time,
+        "Session expiry has already passed."
+    );
+    if !state.session_keys.contains_key(&ctx.sender) {
+        state.session_keys.insert(ctx.sender, SortedVecMap::new());
+    }
+    let account_keys = state.session_keys.get_mut(&ctx.sender).unwrap();
+    assert!(
+        account_keys.contains_key(&key) || account_keys.len() < MAX_SESSION_KEYS,
+        "Account already has: {} session keys",
+        MAX_SESSION_KEYS
+    );
+    account_keys.insert(key, SessionKey { scope, spent: 0 });
+    state.assert_invariants();
+
+    state
+}
+
+/// Revoke the session key `key` of caller address.
+///
+/// Panics if `key` is not a session key of the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `key`: [`Address`], the session key.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "session-keys")]
+#[action(shortname = 0x82)]
+fn revoke_session_key(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    key: Address,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "revoke_session_key");
+    let account_keys = state
+        .session_keys
+        .get_mut(&ctx.sender)
+        .expect("Caller has no session keys.");
+    account_keys
+        .remove(&key)
+        .expect("Not a session key of the caller.");
+    if account_keys.is_empty() {
+        state.session_keys.remove(&ctx.sender);
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Transfer `amount` tokens to address `to` from `account`, sent by a session key of `account`.
+///
+/// Panics if the caller is not a session key of `account` allowed the transfer, or if there is
+/// insufficient balance in `account`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account to transfer from.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`u128`], amount to transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "session-keys")]
+#[action(shortname = 0x83)]
+fn session_transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+    to: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "session_transfer");
+    state.authorize_session_action(
+        &account,
+        &ctx.sender,
+        SessionAction::Transfer {},
+        amount,
+        ctx.block_production_time,
+    );
+    let supply = state.supply();
+    state.execute_transfer(&ctx, Transfer::new(account, to, amount, None));
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Approve `amount` tokens for address `spender` from `account`, sent by a session key of
+/// `account`.
+///
+/// Panics if the caller is not a session key of `account` allowed the approval, or if the address
+/// guards reject `spender`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account approving.
+///   * `spender`: [`Address`], account which will spend the funds.
+///   * `amount`: [`u128`], amount to allot to `spender`.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "session-keys")]
+#[action(shortname = 0x84)]
+fn session_approve(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+    spender: Address,
+    amount: u128,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "session_approve");
+    state.authorize_session_action(
+        &account,
+        &ctx.sender,
+        SessionAction::Approve {},
+        amount,
+        ctx.block_production_time,
+    );
+    state.assert_valid_destination(&ctx, &spender);
+    state.approve_spender(account, spender, amount);
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,115 @@
+//! Tests of session keys: transfers and approvals within the scope, the largest amount, the cap,
+//! the expiry and revocation.
+
+use super::{
+    register_session_key, revoke_session_key, session_approve, session_transfer, SessionAction,
+    SessionScope,
+};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state};
+use crate::token::TashiTokenState;
+
+/// Block production time the session key expires at.
+const EXPIRES_AT: i64 = 3_600_000;
+
+/// A token where account 1 holds 10_000 and lets session key account 9 transfer up to 500 at a
+/// time and 1_000 in total.
+fn session_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    register_session_key(
+        context(account(1)),
+        state,
+        account(9),
+        SessionScope {
+            actions: vec![SessionAction::Transfer {}],
+            max_amount: 500,
+            total_cap: 1_000,
+            expires_at: EXPIRES_AT,
+        },
+    )
+}
+
+/// Transfers `amount` from account 1 to account 2 with the session key at time `time`.
+fn transfer_at(state: TashiTokenState, amount: u128, time: i64) -> TashiTokenState {
+    session_transfer(
+        context_at(account(9), time),
+        state,
+        account(1),
+        account(2),
+        amount,
+    )
+    .0
+}
+
+#[test]
+fn session_key_transfers_for_the_account() {
+    let state = transfer_at(session_state(), 500, 0);
+    let state = transfer_at(state, 500, 0);
+
+    assert_eq!(state.balance_of(&account(1)), 9_000);
+    assert_eq!(state.balance_of(&account(2)), 1_000);
+    assert_eq!(state.balance_of(&account(9)), 0);
+}
+
+#[test]
+#[should_panic(expected = "Amount: 501, exceeds the session maximum: 500")]
+fn transfer_above_the_largest_amount_is_rejected() {
+    transfer_at(session_state(), 501, 0);
+}
+
+#[test]
+#[should_panic(expected = "Session key would spend: 1100, cap is: 1000")]
+fn transfers_beyond_the_cap_are_rejected() {
+    let state = transfer_at(session_state(), 500, 0);
+    let state = transfer_at(state, 500, 0);
+    transfer_at(state, 100, 0);
+}
+
+#[test]
+#[should_panic(expected = "Session key expired at: 3600000")]
+fn expired_session_key_is_rejected() {
+    transfer_at(session_state(), 100, EXPIRES_AT);
+}
+
+#[test]
+#[should_panic(expected = "Session key is not allowed this action.")]
+fn action_outside_the_scope_is_rejected() {
+    session_approve(
+        context(account(9)),
+        session_state(),
+        account(1),
+        account(3),
+        100,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Caller is not a session key of the account.")]
+fn revoked_session_key_is_rejected() {
+    let state = revoke_session_key(context(account(1)), session_state(), account(9));
+    assert!(state.session_keys.is_empty());
+    transfer_at(state, 100, 0);
+}
+
+#[test]
+fn reregistering_the_key_resets_what_it_spent() {
+    let state = transfer_at(session_state(), 500, 0);
+    let state = register_session_key(
+        context(account(1)),
+        state,
+        account(9),
+        SessionScope {
+            actions: vec![SessionAction::Approve {}],
+            max_amount: 2_000,
+            total_cap: 2_000,
+            expires_at: EXPIRES_AT,
+        },
+    );
+    let state = session_approve(context(account(9)), state, account(1), account(3), 2_000);
+
+    assert_eq!(state.allowance(&account(1), &account(3)), 2_000);
+}
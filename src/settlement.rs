@@ -0,0 +1,213 @@
+//! Settlement batches for clearing processes, where one failed leg must not hold back the others.
+//! `settle_batch` checks each instruction before executing it, and instead of failing the batch
+//! skips the instructions that would fail, reporting why. The checks cover paused transfers,
+//! frozen accounts, missing allowance and balances that are short or frozen or self-locked. A leg
+//! rejected by another transfer hook still fails the whole batch, as any panic aborts the
+//! transaction. The report is returned to the caller and kept in the settlement log, which holds
+//! the latest [`SETTLEMENT_LOG_CAPACITY`] reports.
+#![cfg_attr(not(feature = "settlement"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "settlement")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "settlement")]
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "settlement")]
+use crate::hooks::Transfer;
+use crate::token::TashiTokenState;
+#[cfg(feature = "settlement")]
+use crate::views::return_value;
+
+#[cfg(all(test, feature = "settlement"))]
+mod tests;
+
+/// The largest number of instructions in a settlement batch.
+pub const MAX_SETTLEMENT_INSTRUCTIONS: usize = 256;
+
+/// The number of reports the settlement log keeps, older reports are dropped.
+pub const SETTLEMENT_LOG_CAPACITY: usize = 64;
+
+/// A leg of a settlement batch.
+///
+/// ### Fields:
+///
+///   * `from`: [`Address`], account to transfer from, the caller or an account it has an
+///     allowance of or is an operator of.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`u128`], amount to transfer.
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub struct TransferInstruction {
+    pub from: Address,
+    pub to: Address,
+    pub amount: u128,
+}
+
+/// Why an instruction of a settlement batch was skipped.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SettlementFailure {
+    /// Transfers are paused.
+    #[discriminant(0)]
+    Paused {},
+    /// The sender, the receiver or the caller is frozen.
+    #[discriminant(1)]
+    Frozen {},
+    /// The caller has insufficient allowance from the sender.
+    #[discriminant(2)]
+    InsufficientAllowance {},
+    /// The sender has insufficient balance, or would be left with less than its frozen or
+    /// self-locked amount.
+    #[discriminant(3)]
+    InsufficientBalance {},
+}
+
+/// An instruction of a settlement batch that was skipped.
+///
+/// ### Fields:
+///
+///   * `index`: [`u32`], position of the instruction in the batch.
+///   * `reason`: [`SettlementFailure`], why it was skipped.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SettlementItemFailure {
+    pub index: u32,
+    pub reason: SettlementFailure,
+}
+
+/// The outcome of a settlement batch.
+///
+/// ### Fields:
+///
+///   * `settler`: [`Address`], account that sent the batch.
+///   * `timestamp`: [`i64`], block production time of the batch, in milliseconds.
+///   * `settled`: [`u32`], number of instructions executed.
+///   * `failures`: [`Vec`]<[`SettlementItemFailure`]>, instructions skipped, in batch order.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SettlementReport {
+    pub settler: Address,
+    pub timestamp: i64,
+    pub settled: u32,
+    pub failures: Vec<SettlementItemFailure>,
+}
+
+impl TashiTokenState {
+    /// Checks whether `instruction` sent by `settler` would fail, without panicking.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `settler`: [`Address`], account sending the batch.
+    ///   * `instruction`: [`TransferInstruction`], the instruction to check.
+    ///   * `now`: [`i64`], the current block production time.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`Option`]<[`SettlementFailure`]> the instruction would fail with, none if it can be
+    /// executed.
+    pub fn settlement_failure(
+        &self,
+        settler: &Address,
+        instruction: &TransferInstruction,
+        now: i64,
+    ) -> Option<SettlementFailure> {
+        let TransferInstruction { from, to, amount } = instruction;
+        if self.paused || self.is_emergency_paused(now) {
+            return Some(SettlementFailure::Paused {});
+        }
+        if [from, to, settler]
+            .into_iter()
+            .any(|account| self.frozen_accounts.contains(account))
+        {
+            return Some(SettlementFailure::Frozen {});
+        }
+        if from != settler
+            && !self.is_operator(from, settler)
+            && self.allowance(from, settler) < *amount
+        {
+            return Some(SettlementFailure::InsufficientAllowance {});
+        }
+        let locked = self
+            .frozen_amount(from)
+            .max(self.self_locked_amount(from, now));
+        if self.balance_of(from) < amount.saturating_add(locked) {
+            return Some(SettlementFailure::InsufficientBalance {});
+        }
+        None
+    }
+}
+
+/// Settle `instructions` in order, executing each one that passes its checks and skipping the
+/// others. Otherwise each instruction is handled as by `transfer`, or by `transfer_from` with the
+/// caller as spender if it transfers from another account. The report of the batch is returned
+/// and appended to the settlement log.
+///
+/// Panics if the batch holds more than [`MAX_SETTLEMENT_INSTRUCTIONS`] instructions, or if a
+/// transfer hook rejects an instruction that passed the checks.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `instructions`: [`Vec`]<[`TransferInstruction`]>, the legs to settle.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state, the report as a [`SettlementReport`] in the return data and
+/// the supply notifications.
+#[cfg(feature = "settlement")]
+#[action(shortname = 0xfc)]
+fn settle_batch(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    instructions: Vec<TransferInstruction>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "settle_batch");
+    assert!(
+        instructions.len() <= MAX_SETTLEMENT_INSTRUCTIONS,
+        "Batch holds: {} instructions, maximum is: {}",
+        instructions.len(),
+        MAX_SETTLEMENT_INSTRUCTIONS
+    );
+    let supply = state.supply();
+    let mut report = SettlementReport {
+        settler: ctx.sender,
+        timestamp: ctx.block_production_time,
+        settled: 0,
+        failures: Vec::new(),
+    };
+    for (index, instruction) in instructions.into_iter().enumerate() {
+        match state.settlement_failure(&ctx.sender, &instruction, ctx.block_production_time) {
+            Some(reason) => report.failures.push(SettlementItemFailure {
+                index: index as u32,
+                reason,
+            }),
+            None => {
+                let spender = (instruction.from != ctx.sender).then_some(ctx.sender);
+                state.execute_transfer(
+                    &ctx,
+                    Transfer::new(
+                        instruction.from,
+                        instruction.to,
+                        instruction.amount,
+                        spender,
+                    ),
+                );
+                report.settled += 1;
+            }
+        }
+    }
+    if state.settlement_log.len() >= SETTLEMENT_LOG_CAPACITY {
+        state.settlement_log.remove(0);
+    }
+    state.settlement_log.push(report.clone());
+
+    let mut events = return_value(report);
+    events.extend(state.supply_change_events(supply));
+    state.assert_invariants();
+
+    (state, events)
+}
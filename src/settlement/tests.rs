@@ -0,0 +1,92 @@
+//! Tests of settlement batches: legs that pass their checks are executed, and the others are
+//! skipped and reported with why they would fail.
+
+use super::{settle_batch, SettlementFailure, TransferInstruction};
+use crate::allowance::approve;
+use crate::testing::{account, context, new_state, INITIAL_SUPPLY};
+use crate::token::{stored_amount, TashiTokenState};
+
+/// A leg moving `amount` from `from` to `to`.
+fn leg(from: u8, to: u8, amount: u128) -> TransferInstruction {
+    TransferInstruction {
+        from: account(from),
+        to: account(to),
+        amount,
+    }
+}
+
+/// Settles `instructions` sent by `settler`.
+fn settle(
+    state: TashiTokenState,
+    settler: u8,
+    instructions: Vec<TransferInstruction>,
+) -> TashiTokenState {
+    settle_batch(context(account(settler)), state, instructions).0
+}
+
+#[test]
+fn failing_legs_are_skipped_and_reported() {
+    let mut state = new_state();
+    state.frozen_accounts.push(account(4));
+    let state = settle(
+        state,
+        0,
+        vec![
+            leg(0, 1, 500),
+            leg(2, 3, 100),
+            leg(0, 4, 100),
+            leg(0, 2, INITIAL_SUPPLY),
+        ],
+    );
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 500);
+    assert_eq!(state.balance_of(&account(1)), 500);
+    assert_eq!(state.balance_of(&account(4)), 0);
+    let report = &state.settlement_log[0];
+    assert_eq!(report.settler, account(0));
+    assert_eq!(report.settled, 1);
+    let failures: Vec<(u32, SettlementFailure)> = report
+        .failures
+        .iter()
+        .map(|failure| (failure.index, failure.reason))
+        .collect();
+    assert_eq!(
+        failures,
+        vec![
+            (1, SettlementFailure::InsufficientAllowance {}),
+            (2, SettlementFailure::Frozen {}),
+            (3, SettlementFailure::InsufficientBalance {}),
+        ]
+    );
+}
+
+#[test]
+fn legs_from_other_accounts_spend_the_allowance() {
+    let state = settle(new_state(), 0, vec![leg(0, 1, 500)]);
+    let state = approve(context(account(1)), state, account(5), stored_amount(300));
+    let state = settle(state, 5, vec![leg(1, 2, 300), leg(1, 2, 1)]);
+
+    assert_eq!(state.balance_of(&account(2)), 300);
+    assert_eq!(state.allowance(&account(1), &account(5)), 0);
+    let report = &state.settlement_log[1];
+    assert_eq!(report.settled, 1);
+    assert_eq!(
+        report.failures[0].reason,
+        SettlementFailure::InsufficientAllowance {}
+    );
+}
+
+#[test]
+fn paused_batch_settles_nothing() {
+    let mut state = new_state();
+    state.paused = true;
+    let state = settle(state, 0, vec![leg(0, 1, 500), leg(0, 2, 500)]);
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY);
+    let report = &state.settlement_log[0];
+    assert_eq!(report.settled, 0);
+    assert!(report
+        .failures
+        .iter()
+        .all(|failure| failure.reason == SettlementFailure::Paused {}));
+}
@@ -0,0 +1,180 @@
+//! Balances sharded by address, feature `sharded-balances`. The flat `balances` map of the MPC-20
+//! standard is loaded and written whole by every call, which bounds the number of holders a
+//! deployment can serve. With this feature `balances` is an [`AvlTreeMap`] of shards instead,
+//! which is loaded lazily: a call only reads and writes the shards of the accounts it touches. An
+//! account is assigned to a shard by the first two bytes of its identifier, and the owner can
+//! reshard the balances as the holder count grows.
+//!
+//! The feature changes the state layout, so a deployment cannot switch between the flat and the
+//! sharded balances. Readers of the state go through [`BalanceEntries`], which both layouts
+//! implement.
+#![cfg_attr(not(feature = "sharded-balances"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::avl_tree_map::AvlTreeMap;
+#[cfg(feature = "sharded-balances")]
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "sharded-balances")]
+use crate::token::TashiTokenState;
+use crate::token::{Amount, BalanceMap};
+
+#[cfg(all(test, feature = "sharded-balances"))]
+mod tests;
+
+/// The number of shards the balances of a new deployment are split into.
+pub const DEFAULT_BALANCE_SHARDS: u16 = 16;
+
+/// The maximum number of shards the balances can be split into.
+pub const MAX_BALANCE_SHARDS: u16 = 4096;
+
+/// Read access to the balances, whether stored in the flat map of the MPC-20 standard or in
+/// shards.
+pub trait BalanceEntries {
+    /// Gets the raw entry of `account`, if it holds a balance.
+    fn entry(&self, account: &Address) -> Option<Amount>;
+
+    /// Gets every entry, ordered by address.
+    fn entries(&self) -> Vec<(Address, Amount)>;
+}
+
+impl BalanceEntries for SortedVecMap<Address, Amount> {
+    fn entry(&self, account: &Address) -> Option<Amount> {
+        self.get(account).copied()
+    }
+
+    fn entries(&self) -> Vec<(Address, Amount)> {
+        self.iter()
+            .map(|(account, balance)| (*account, *balance))
+            .collect()
+    }
+}
+
+/// Balances split into shards by address.
+///
+/// ### Fields:
+///
+///   * `shard_count`: [`u16`], number of shards accounts are assigned to.
+///   * `shards`: [`AvlTreeMap`]<[`u16`], [`SortedVecMap`]<[`Address`], [`Amount`]>>, balances of the accounts of each shard, by shard, empty shards are removed.
+#[derive(ReadWriteState, CreateTypeSpec)]
+pub struct ShardedBalances {
+    pub shard_count: u16,
+    pub shards: AvlTreeMap<u16, SortedVecMap<Address, Amount>>,
+}
+
+impl ShardedBalances {
+    /// Creates empty balances split into `shard_count` shards.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `shard_count`: [`u16`], number of shards.
+    pub fn new(shard_count: u16) -> Self {
+        ShardedBalances {
+            shard_count,
+            shards: AvlTreeMap::new(),
+        }
+    }
+
+    /// Gets the shard `account` is assigned to, by the first two bytes of its identifier.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], the account.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u16`] shard of the account.
+    pub fn shard_of(&self, account: &Address) -> u16 {
+        let prefix = u16::from_be_bytes([account.identifier[0], account.identifier[1]]);
+        prefix % self.shard_count
+    }
+
+    /// Moves every balance to its shard among `shard_count` shards.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `shard_count`: [`u16`], the new number of shards.
+    ///
+    /// ### Returns:
+    ///
+    /// The resharded [`ShardedBalances`].
+    pub fn resharded(&self, shard_count: u16) -> Self {
+        let mut resharded = ShardedBalances::new(shard_count);
+        for (account, balance) in self.entries() {
+            resharded.insert_balance(account, balance);
+        }
+        resharded
+    }
+}
+
+impl BalanceEntries for ShardedBalances {
+    fn entry(&self, account: &Address) -> Option<Amount> {
+        self.shards
+            .get(&self.shard_of(account))
+            .and_then(|shard| shard.get(account).copied())
+    }
+
+    fn entries(&self) -> Vec<(Address, Amount)> {
+        let mut entries: Vec<(Address, Amount)> = self
+            .shards
+            .iter()
+            .flat_map(|(_, shard)| shard.entries())
+            .collect();
+        // shards split the address space by identifier, not in address order
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        entries
+    }
+}
+
+impl BalanceMap<Address, Amount> for ShardedBalances {
+    fn insert_balance(&mut self, key: Address, amount: Amount) {
+        let shard_id = self.shard_of(&key);
+        let mut shard = self.shards.get(&shard_id).unwrap_or_else(SortedVecMap::new);
+        shard.insert_balance(key, amount);
+        if shard.is_empty() {
+            self.shards.remove(&shard_id);
+        } else {
+            self.shards.insert(shard_id, shard);
+        }
+    }
+}
+
+/// Reshard the balances into `shard_count` shards, moving every balance to its new shard. Every
+/// shard is read and written once, so resharding is best done before the holder count outgrows
+/// the current shards. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner, or if `shard_count` is zero or above
+/// [`MAX_BALANCE_SHARDS`].
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `shard_count`: [`u16`], the new number of shards.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "sharded-balances")]
+#[action(shortname = 0x102)]
+fn reshard_balances(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    shard_count: u16,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "reshard_balances", shard_count);
+    assert!(
+        (1..=MAX_BALANCE_SHARDS).contains(&shard_count),
+        "Shard count: {}, must be between 1 and {}",
+        shard_count,
+        MAX_BALANCE_SHARDS
+    );
+    state.balances = state.balances.resharded(shard_count);
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,90 @@
+//! Tests of the sharded balances: accounts sharing a shard, emptied shards, and resharding by the
+//! owner. Account `n` has `n` in every byte of its identifier, so its shard is `n * 257` modulo the
+//! shard count.
+
+use super::{reshard_balances, BalanceEntries, DEFAULT_BALANCE_SHARDS};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token where accounts 1, 2 and 17 hold 100, 200 and 300. Accounts 1 and 17 share shard 1 of
+/// the default 16 shards.
+fn sharded_state() -> TashiTokenState {
+    let mut state = new_state();
+    for (index, amount) in [(1, 100), (2, 200), (17, 300)] {
+        state.execute_transfer(
+            &context(account(0)),
+            Transfer::new(account(0), account(index), amount, None),
+        );
+    }
+    state
+}
+
+#[test]
+fn accounts_sharing_a_shard_keep_their_own_balances() {
+    let state = sharded_state();
+
+    assert_eq!(state.balances.shard_count, DEFAULT_BALANCE_SHARDS);
+    assert_eq!(state.balances.shard_of(&account(1)), 1);
+    assert_eq!(state.balances.shard_of(&account(17)), 1);
+    assert_eq!(state.balances.shards.get(&1).unwrap().len(), 2);
+    assert_eq!(state.balance_of(&account(1)), 100);
+    assert_eq!(state.balance_of(&account(2)), 200);
+    assert_eq!(state.balance_of(&account(17)), 300);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 600);
+}
+
+#[test]
+fn emptied_shard_is_removed() {
+    let mut state = sharded_state();
+    state.execute_transfer(
+        &context(account(2)),
+        Transfer::new(account(2), account(0), 200, None),
+    );
+
+    assert!(state.balances.entry(&account(2)).is_none());
+    assert!(state.balances.shards.get(&2).is_none());
+    state.assert_invariants();
+}
+
+#[test]
+fn entries_are_in_address_order() {
+    let state = sharded_state();
+    let accounts: Vec<_> = state
+        .balances
+        .entries()
+        .into_iter()
+        .map(|(account, _)| account)
+        .collect();
+
+    assert_eq!(
+        accounts,
+        vec![account(0), account(1), account(2), account(17)]
+    );
+}
+
+#[test]
+fn resharding_moves_every_balance_to_its_new_shard() {
+    let state = sharded_state();
+    let entries = state.balances.entries();
+    let state = reshard_balances(context(account(0)), state, 4);
+
+    assert_eq!(state.balances.shard_count, 4);
+    assert_eq!(state.balances.shard_of(&account(2)), 2);
+    assert_eq!(state.balances.shards.get(&1).unwrap().len(), 2);
+    assert_eq!(state.balances.entries(), entries);
+    assert_eq!(state.balance_of(&account(17)), 300);
+    state.assert_invariants();
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can perform this action.")]
+fn only_the_owner_can_reshard() {
+    reshard_balances(context(account(1)), sharded_state(), 4);
+}
+
+#[test]
+#[should_panic(expected = "Shard count: 0, must be between 1 and 4096")]
+fn resharding_into_zero_shards_is_rejected() {
+    reshard_balances(context(account(0)), sharded_state(), 0);
+}
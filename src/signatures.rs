@@ -0,0 +1,293 @@
+//! Verification of signed messages, shared by every action accepting off-chain signatures. Keys
+//! and signatures are tagged with their scheme, secp256k1 or ed25519, and every message is hashed
+//! with a domain separator and the address of this contract so a signature made for one purpose
+//! or contract cannot be replayed for another. Ed25519 verification requires the `ed25519`
+//! feature.
+// only used by optional features
+#![allow(dead_code)]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+use pbc_contract_common::public_key::PublicKey;
+use pbc_contract_common::signature::Signature;
+use pbc_contract_common::Hash;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(all(test, feature = "ed25519"))]
+mod tests;
+
+/// A key signed messages are verified against.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SignerKey {
+    /// A secp256k1 public key.
+    #[discriminant(0)]
+    Secp256k1 { key: PublicKey },
+    /// An ed25519 public key.
+    #[discriminant(1)]
+    Ed25519 { key: [u8; 32] },
+}
+
+/// A signature of a message, in the scheme of the key that made it.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SchemeSignature {
+    /// A recoverable secp256k1 signature.
+    #[discriminant(0)]
+    Secp256k1 { signature: Signature },
+    /// An ed25519 signature.
+    #[discriminant(1)]
+    Ed25519 {
+        #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::byte_array"))]
+        signature: [u8; 64],
+    },
+}
+
+impl SignerKey {
+    /// Checks whether `signature` is a signature of `message` by this key. A signature in another
+    /// scheme than the key is never valid.
+    ///
+    /// Panics if the signature is an ed25519 signature and the `ed25519` feature is disabled.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `message`: [`Hash`], the signed message, as built by [`signed_message_hash`].
+    ///   * `signature`: [`SchemeSignature`], the signature to check.
+    ///
+    /// ### Returns:
+    ///
+    /// True if the signature is valid, false otherwise.
+    pub fn verify(&self, message: &Hash, signature: &SchemeSignature) -> bool {
+        match (self, signature) {
+            (SignerKey::Secp256k1 { key }, SchemeSignature::Secp256k1 { signature }) => {
+                signature.recover_public_key(message).as_ref() == Some(key)
+            }
+            (SignerKey::Ed25519 { key }, SchemeSignature::Ed25519 { signature }) => {
+                verify_ed25519(key, message, signature)
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks that `signature` is a signature of `message` by this key.
+    ///
+    /// Panics with `error` if the signature is invalid.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `message`: [`Hash`], the signed message, as built by [`signed_message_hash`].
+    ///   * `signature`: [`SchemeSignature`], the signature to check.
+    ///   * `error`: [`str`], the panic message of an invalid signature.
+    pub fn assert_signed(&self, message: &Hash, signature: &SchemeSignature, error: &str) {
+        assert!(self.verify(message, signature), "{}", error);
+    }
+}
+
+/// Computes the hash signed for `payload` under `domain`. The domain separator and the contract
+/// address are prefixed so a signature only applies to one kind of message on one contract.
+///
+/// ### Parameters:
+///
+///   * `domain`: [`u8`] slice, domain separator of the kind of message.
+///   * `contract`: [`Address`], address of this contract.
+///   * `payload`: [`u8`] slice, the fields of the message, serialized by the caller.
+///
+/// ### Returns:
+///
+/// The [`Hash`] to sign.
+pub fn signed_message_hash(domain: &[u8], contract: &Address, payload: &[u8]) -> Hash {
+    let mut message = domain.to_vec();
+    message.extend_from_slice(&contract.identifier);
+    message.extend_from_slice(payload);
+    Hash::digest(message)
+}
+
+/// Checks an ed25519 signature of `message`, signed as its 32 hash bytes.
+#[cfg(feature = "ed25519")]
+fn verify_ed25519(key: &[u8; 32], message: &Hash, signature: &[u8; 64]) -> bool {
+    ed25519_compact::PublicKey::new(*key)
+        .verify(message.bytes, &ed25519_compact::Signature::new(*signature))
+        .is_ok()
+}
+
+/// Rejects ed25519 signatures in builds without the `ed25519` feature.
+#[cfg(not(feature = "ed25519"))]
+fn verify_ed25519(_key: &[u8; 32], _message: &Hash, _signature: &[u8; 64]) -> bool {
+    panic!("Ed25519 signatures are not supported by this contract.");
+}
+
+/// The field prime of secp256k1, `2^256 - 2^32 - 977`, in little endian 64 bit limbs.
+const FIELD_PRIME: [u64; 4] = [
+    0xffff_fffe_ffff_fc2f,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+];
+
+/// `2^256` modulo [`FIELD_PRIME`], the value a carry out of the top limb wraps to.
+const FIELD_WRAP: u64 = 0x1_0000_03d1;
+
+/// `(p + 1) / 4`, the exponent giving a square root modulo [`FIELD_PRIME`].
+const SQRT_EXPONENT: [u64; 4] = [
+    0xffff_ffff_bfff_ff0c,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0x3fff_ffff_ffff_ffff,
+];
+
+/// An element of the field of secp256k1, in little endian 64 bit limbs.
+type FieldElement = [u64; 4];
+
+/// Reads a big endian 32 byte number.
+fn field_from_bytes(bytes: &[u8]) -> FieldElement {
+    let mut value = [0u64; 4];
+    for (index, chunk) in bytes.chunks(8).enumerate() {
+        value[3 - index] = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    value
+}
+
+/// Writes a number as 32 big endian bytes.
+fn field_to_bytes(value: &FieldElement) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (index, limb) in value.iter().enumerate() {
+        bytes[24 - 8 * index..32 - 8 * index].copy_from_slice(&limb.to_be_bytes());
+    }
+    bytes
+}
+
+/// Subtracts `b` from `a`, with the borrow out of the top limb.
+fn limbs_sub(a: &FieldElement, b: &FieldElement) -> (FieldElement, bool) {
+    let mut difference = [0u64; 4];
+    let mut borrow = false;
+    for index in 0..4 {
+        let (value, first) = a[index].overflowing_sub(b[index]);
+        let (value, second) = value.overflowing_sub(u64::from(borrow));
+        difference[index] = value;
+        borrow = first || second;
+    }
+    (difference, borrow)
+}
+
+/// Adds `small` to the limbs of `value` from `offset` on, returning the carry out of the top limb.
+fn limbs_add_small(value: &mut FieldElement, offset: usize, small: u128) -> u128 {
+    let mut carry = small;
+    for limb in value.iter_mut().skip(offset) {
+        if carry == 0 {
+            break;
+        }
+        let sum = u128::from(*limb) + (carry & u128::from(u64::MAX));
+        *limb = sum as u64;
+        carry = (carry >> 64) + (sum >> 64);
+    }
+    carry
+}
+
+/// Reduces `value`, below twice the prime, modulo [`FIELD_PRIME`].
+fn field_reduce(value: FieldElement) -> FieldElement {
+    match limbs_sub(&value, &FIELD_PRIME) {
+        (reduced, false) => reduced,
+        (_, true) => value,
+    }
+}
+
+/// Adds `a` and `b` modulo [`FIELD_PRIME`].
+fn field_add(a: &FieldElement, b: &FieldElement) -> FieldElement {
+    let mut sum = *a;
+    let mut carry = 0;
+    for (index, limb) in b.iter().enumerate() {
+        carry += limbs_add_small(&mut sum, index, u128::from(*limb));
+    }
+    if carry > 0 {
+        limbs_add_small(&mut sum, 0, u128::from(FIELD_WRAP));
+    }
+    field_reduce(sum)
+}
+
+/// Multiplies `a` and `b` modulo [`FIELD_PRIME`], folding the high half of the product into the
+/// low half as `2^256 = FIELD_WRAP`.
+fn field_mul(a: &FieldElement, b: &FieldElement) -> FieldElement {
+    let mut product = [0u128; 8];
+    for (i, a_limb) in a.iter().enumerate() {
+        for (j, b_limb) in b.iter().enumerate() {
+            let term = u128::from(*a_limb) * u128::from(*b_limb);
+            product[i + j] += term & u128::from(u64::MAX);
+            product[i + j + 1] += term >> 64;
+        }
+    }
+    // propagate the carries so every limb fits in 64 bits
+    let mut limbs = [0u64; 8];
+    let mut carry = 0u128;
+    for (limb, value) in limbs.iter_mut().zip(product.iter()) {
+        let sum = value + carry;
+        *limb = sum as u64;
+        carry = sum >> 64;
+    }
+
+    let mut result: FieldElement = [limbs[0], limbs[1], limbs[2], limbs[3]];
+    let mut overflow = 0;
+    for (index, high) in limbs[4..].iter().enumerate() {
+        overflow += limbs_add_small(
+            &mut result,
+            index,
+            u128::from(*high) * u128::from(FIELD_WRAP),
+        );
+    }
+    while overflow > 0 {
+        overflow = limbs_add_small(&mut result, 0, overflow * u128::from(FIELD_WRAP));
+    }
+    field_reduce(result)
+}
+
+/// Raises `base` to `exponent` modulo [`FIELD_PRIME`].
+fn field_pow(base: &FieldElement, exponent: &FieldElement) -> FieldElement {
+    let mut result: FieldElement = [1, 0, 0, 0];
+    for limb in exponent.iter().rev() {
+        for bit in (0..64).rev() {
+            result = field_mul(&result, &result);
+            if limb >> bit & 1 == 1 {
+                result = field_mul(&result, base);
+            }
+        }
+    }
+    result
+}
+
+/// Computes the 65 byte uncompressed encoding, `04 || x || y`, of a secp256k1 key, recovering
+/// `y` from `y^2 = x^3 + 7` and the parity given by the prefix of the compressed key.
+///
+/// Panics if `key` is not a point on the curve.
+///
+/// ### Parameters:
+///
+///   * `key`: [`PublicKey`], the key, in its 33 byte compressed encoding.
+///
+/// ### Returns:
+///
+/// The uncompressed encoding of the key.
+pub fn uncompressed_secp256k1_key(key: &PublicKey) -> [u8; 65] {
+    let mut compressed: Vec<u8> = Vec::new();
+    pbc_traits::ReadWriteRPC::rpc_write_to(key, &mut compressed)
+        .expect("Writing to a vector cannot fail.");
+    let prefix = compressed[0];
+    let x = field_from_bytes(&compressed[1..33]);
+    assert!(
+        (prefix == 2 || prefix == 3) && limbs_sub(&x, &FIELD_PRIME).1,
+        "Invalid secp256k1 public key."
+    );
+    let y_squared = field_add(&field_mul(&field_mul(&x, &x), &x), &[7, 0, 0, 0]);
+    let mut y = field_pow(&y_squared, &SQRT_EXPONENT);
+    assert!(
+        field_mul(&y, &y) == y_squared,
+        "Invalid secp256k1 public key."
+    );
+    if y[0] & 1 != u64::from(prefix & 1) {
+        y = limbs_sub(&FIELD_PRIME, &y).0;
+    }
+    let mut uncompressed = [0u8; 65];
+    uncompressed[0] = 0x04;
+    uncompressed[1..33].copy_from_slice(&field_to_bytes(&x));
+    uncompressed[33..].copy_from_slice(&field_to_bytes(&y));
+    uncompressed
+}
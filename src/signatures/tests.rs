@@ -0,0 +1,167 @@
+//! Tests of signed messages: valid signatures, signatures replayed on another domain or contract,
+//! tampered payloads, and the message layout of each domain.
+
+use ed25519_compact::{KeyPair, Seed};
+use pbc_contract_common::public_key::PublicKey;
+use pbc_contract_common::Hash;
+use pbc_traits::ReadWriteRPC;
+
+use super::{signed_message_hash, SchemeSignature, SignerKey};
+use crate::channels::balance_update_hash;
+use crate::migration::snapshot_leaf;
+use crate::reserves::reserve_attestation_hash;
+use crate::sponsorship::{sponsored_action_hash, SponsoredAction};
+use crate::testing::{account, contract, token_address};
+
+/// The domain separators of the contract: channel balance updates, reserve attestations,
+/// sponsored actions and migration snapshot leaves.
+const DOMAINS: [&[u8]; 4] = [
+    b"TASHI_TOKEN_CHANNEL_BALANCE",
+    b"TASHI_TOKEN_RESERVE_ATTESTATION",
+    b"TASHI_TOKEN_SPONSORED_ACTION",
+    b"TASHI_TOKEN_MIGRATION",
+];
+
+/// The ed25519 key pair derived from a seed of ones.
+fn key_pair() -> KeyPair {
+    KeyPair::from_seed(Seed::new([1; 32]))
+}
+
+/// The key of [`key_pair`].
+fn signer_key() -> SignerKey {
+    SignerKey::Ed25519 {
+        key: *key_pair().pk,
+    }
+}
+
+/// Signs `message` with [`key_pair`].
+fn sign(message: &Hash) -> SchemeSignature {
+    SchemeSignature::Ed25519 {
+        signature: *key_pair().sk.sign(message.bytes, None),
+    }
+}
+
+#[test]
+fn message_is_the_hash_of_domain_contract_and_payload() {
+    let expected = Hash::digest([b"DOMAIN".to_vec(), vec![0xff; 20], vec![1, 2, 3]].concat());
+
+    assert_eq!(
+        signed_message_hash(b"DOMAIN", &token_address(), &[1, 2, 3]),
+        expected
+    );
+}
+
+#[test]
+fn valid_signature_verifies() {
+    let message = signed_message_hash(DOMAINS[0], &token_address(), &[1, 2, 3]);
+
+    assert!(signer_key().verify(&message, &sign(&message)));
+    signer_key().assert_signed(&message, &sign(&message), "Invalid signature.");
+}
+
+#[test]
+fn signature_for_another_domain_is_rejected() {
+    let signed = signed_message_hash(DOMAINS[0], &token_address(), &[1, 2, 3]);
+
+    for domain in &DOMAINS[1..] {
+        let message = signed_message_hash(domain, &token_address(), &[1, 2, 3]);
+        assert!(!signer_key().verify(&message, &sign(&signed)));
+    }
+}
+
+#[test]
+fn signature_for_another_contract_is_rejected() {
+    let signed = signed_message_hash(DOMAINS[0], &contract(1), &[1, 2, 3]);
+    let message = signed_message_hash(DOMAINS[0], &token_address(), &[1, 2, 3]);
+
+    assert!(!signer_key().verify(&message, &sign(&signed)));
+}
+
+#[test]
+fn tampered_payload_is_rejected() {
+    let signed = balance_update_hash(&token_address(), 0, 100);
+    let signature = sign(&signed);
+
+    assert!(!signer_key().verify(&balance_update_hash(&token_address(), 0, 101), &signature));
+    assert!(!signer_key().verify(&balance_update_hash(&token_address(), 1, 100), &signature));
+}
+
+#[test]
+#[should_panic(expected = "Invalid signature.")]
+fn invalid_signature_panics_with_the_error() {
+    let signed = signed_message_hash(DOMAINS[0], &token_address(), &[1, 2, 3]);
+    let message = signed_message_hash(DOMAINS[0], &token_address(), &[1, 2, 4]);
+
+    signer_key().assert_signed(&message, &sign(&signed), "Invalid signature.");
+}
+
+#[test]
+fn signature_of_another_scheme_is_rejected() {
+    let message = signed_message_hash(DOMAINS[0], &token_address(), &[1, 2, 3]);
+    let mut bytes = vec![0x02];
+    bytes.extend([0x79; 32]);
+    let secp256k1 = SignerKey::Secp256k1 {
+        key: PublicKey::rpc_read_from(&mut &bytes[..]),
+    };
+
+    assert!(!secp256k1.verify(&message, &sign(&message)));
+}
+
+#[test]
+fn domains_separate_equal_payloads() {
+    let hashes: Vec<Hash> = DOMAINS
+        .iter()
+        .map(|domain| signed_message_hash(domain, &token_address(), &[0; 24]))
+        .collect();
+
+    for (index, hash) in hashes.iter().enumerate() {
+        assert!(!hashes[index + 1..].contains(hash));
+    }
+}
+
+#[test]
+fn messages_are_built_under_their_domain() {
+    let contract = token_address();
+
+    assert_eq!(
+        balance_update_hash(&contract, 7, 100),
+        signed_message_hash(
+            DOMAINS[0],
+            &contract,
+            &[7u64.to_be_bytes().to_vec(), 100u128.to_be_bytes().to_vec()].concat()
+        )
+    );
+    assert_eq!(
+        reserve_attestation_hash(&contract, 100, 7),
+        signed_message_hash(
+            DOMAINS[1],
+            &contract,
+            &[100u128.to_be_bytes().to_vec(), 7i64.to_be_bytes().to_vec()].concat()
+        )
+    );
+    assert_eq!(
+        sponsored_action_hash(&contract, SponsoredAction::Approve {}, &account(2), 100, 7),
+        signed_message_hash(
+            DOMAINS[2],
+            &contract,
+            &[
+                vec![0x01, 0x00],
+                vec![0x02; 20],
+                100u128.to_be_bytes().to_vec(),
+                7u64.to_be_bytes().to_vec(),
+            ]
+            .concat()
+        )
+    );
+    assert_eq!(
+        snapshot_leaf(&account(2), 100),
+        Hash::digest(
+            [
+                DOMAINS[3].to_vec(),
+                vec![0x02; 20],
+                100u128.to_be_bytes().to_vec()
+            ]
+            .concat()
+        )
+    );
+}
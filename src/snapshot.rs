@@ -0,0 +1,126 @@
+//! Serde support for state snapshots, so indexers, test fixtures and analytics can dump and load
+//! the state as JSON or any other serde format instead of the on-chain binary codec. The state and
+//! every type it holds derive `Serialize` and `Deserialize` with the `serde` feature, which is for
+//! off-chain tooling and cannot be compiled to WASM. Maps are written as sequences of key and value
+//! pairs in key order, since their keys, such as addresses, are not strings.
+
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use serde::ser::{Serialize, Serializer};
+
+/// Serializes the entries of a map as a sequence of key and value pairs.
+struct Entries<'a, K, V>(&'a SortedVecMap<K, V>);
+
+impl<K: Serialize + Ord, V: Serialize> Serialize for Entries<'_, K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+/// Collects key and value pairs into a map.
+///
+/// ### Parameters:
+///
+///   * `entries`: [`Vec`]<(`K`, `V`)>, the pairs, in any order.
+///
+/// ### Returns:
+///
+/// The [`SortedVecMap`] of the pairs, the last value winning for a repeated key.
+fn collect<K: Ord, V>(entries: Vec<(K, V)>) -> SortedVecMap<K, V> {
+    let mut map = SortedVecMap::new();
+    for (key, value) in entries {
+        map.insert(key, value);
+    }
+    map
+}
+
+/// `serde(with)` adapter for a [`SortedVecMap`] field.
+pub mod sorted_vec_map {
+    use pbc_contract_common::sorted_vec_map::SortedVecMap;
+    use serde::de::{Deserialize, Deserializer};
+    use serde::ser::{Serialize, Serializer};
+
+    use super::{collect, Entries};
+
+    pub fn serialize<K, V, S>(map: &SortedVecMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize + Ord,
+        V: Serialize,
+        S: Serializer,
+    {
+        Entries(map).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<SortedVecMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Ord,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Vec::<(K, V)>::deserialize(deserializer).map(collect)
+    }
+}
+
+/// `serde(with)` adapter for a [`SortedVecMap`] field whose values are maps, such as allowances by
+/// owner and spender.
+pub mod nested_sorted_vec_map {
+    use pbc_contract_common::sorted_vec_map::SortedVecMap;
+    use serde::de::{Deserialize, Deserializer};
+    use serde::ser::{Serialize, Serializer};
+
+    use super::{collect, Entries};
+
+    pub fn serialize<K, L, V, S>(
+        map: &SortedVecMap<K, SortedVecMap<L, V>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize + Ord,
+        L: Serialize + Ord,
+        V: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(map.iter().map(|(key, inner)| (key, Entries(inner))))
+    }
+
+    pub fn deserialize<'de, K, L, V, D>(
+        deserializer: D,
+    ) -> Result<SortedVecMap<K, SortedVecMap<L, V>>, D::Error>
+    where
+        K: Deserialize<'de> + Ord,
+        L: Deserialize<'de> + Ord,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let entries = Vec::<(K, Vec<(L, V)>)>::deserialize(deserializer)?;
+        Ok(collect(
+            entries
+                .into_iter()
+                .map(|(key, inner)| (key, collect(inner)))
+                .collect(),
+        ))
+    }
+}
+
+/// `serde(with)` adapter for a byte array field longer than the 32 bytes serde supports, such as
+/// an ed25519 signature.
+pub mod byte_array {
+    use serde::de::{Deserialize, Deserializer, Error};
+    use serde::ser::Serializer;
+
+    pub fn serialize<const N: usize, S: Serializer>(
+        bytes: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(bytes)
+    }
+
+    pub fn deserialize<'de, const N: usize, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        let bytes = Vec::<u8>::deserialize(deserializer)?;
+        let length = bytes.len();
+        bytes
+            .try_into()
+            .map_err(|_| D::Error::invalid_length(length, &"a byte array of the declared length"))
+    }
+}
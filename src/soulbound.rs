@@ -0,0 +1,220 @@
+//! Non-transferable tokens, for reputation or credential points. The owner makes the whole token or
+//! single accounts soulbound, after which minting and burning still work but transfers from them
+//! are rejected, as are escrows and payouts, see [`TashiTokenState::assert_can_spend`]. To move to
+//! a new address a holder needs a one-time migration approved by the owner, which moves its whole
+//! balance. Transfers sent by the contract itself are not restricted.
+#![cfg_attr(not(feature = "soulbound"), allow(dead_code))]
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+
+use crate::hooks::{Transfer, TransferHook};
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "soulbound"))]
+mod tests;
+
+/// Rejects every transfer from a soulbound account, or from any account if the token is
+/// soulbound.
+pub struct SoulboundGuard;
+
+impl TransferHook for SoulboundGuard {
+    fn before_transfer(
+        ctx: &ContractContext,
+        state: &mut TashiTokenState,
+        transfer: &mut Transfer,
+    ) {
+        state.assert_not_soulbound(ctx, &transfer.from);
+    }
+}
+
+impl TashiTokenState {
+    /// Whether the tokens of `account` cannot be transferred.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], the account to check.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`bool`], true if the token or `account` is soulbound.
+    pub fn is_soulbound(&self, account: &Address) -> bool {
+        self.soulbound || self.soulbound_accounts.contains(account)
+    }
+
+    /// Checks that the tokens of `account` can be moved. The contract itself is not restricted.
+    ///
+    /// Panics if the token or `account` is soulbound.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context.
+    ///   * `account`: [`Address`], the account to check.
+    pub fn assert_not_soulbound(&self, ctx: &ContractContext, account: &Address) {
+        if *account != ctx.contract_address {
+            assert!(
+                !self.is_soulbound(account),
+                "Tokens of this account are non-transferable."
+            );
+        }
+    }
+}
+
+/// Set whether the tokens of every account are non-transferable. Only the owner can call this
+/// action.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `soulbound`: [`bool`], whether the token is non-transferable.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "soulbound")]
+#[action(shortname = 0xd7)]
+fn set_soulbound(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    soulbound: bool,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_soulbound", soulbound);
+    state.soulbound = soulbound;
+    state.assert_invariants();
+
+    state
+}
+
+/// Set whether the tokens of `account` are non-transferable. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if `soulbound` does not change whether `account` is
+/// soulbound.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], the account.
+///   * `soulbound`: [`bool`], whether the tokens of `account` are non-transferable.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "soulbound")]
+#[action(shortname = 0xd8)]
+fn set_account_soulbound(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+    soulbound: bool,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_account_soulbound", account, soulbound);
+    assert!(
+        state.soulbound_accounts.contains(&account) != soulbound,
+        "Account is already {}.",
+        if soulbound {
+            "soulbound"
+        } else {
+            "transferable"
+        }
+    );
+    if soulbound {
+        state.soulbound_accounts.push(account);
+    } else {
+        state
+            .soulbound_accounts
+            .retain(|soulbound_account| *soulbound_account != account);
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Approve `account` to move its whole balance to `new_account` once, or withdraw the approval
+/// with `None`. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], the account to migrate.
+///   * `new_account`: [`Option`]<[`Address`]>, the address to migrate to, `None` to withdraw the
+///     approval.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "soulbound")]
+#[action(shortname = 0xd9)]
+fn approve_soulbound_migration(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    account: Address,
+    new_account: Option<Address>,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(
+        state,
+        ctx,
+        "approve_soulbound_migration",
+        account,
+        new_account
+    );
+    match new_account {
+        Some(new_account) => {
+            state.soulbound_migrations.insert(account, new_account);
+        }
+        None => {
+            state.soulbound_migrations.remove(&account);
+        }
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Move the whole balance of caller address to the address the owner approved, consuming the
+/// approval. A soulbound caller leaves the new address soulbound in its place. Transfer hooks do
+/// not apply.
+///
+/// Panics if the owner approved no migration for the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "soulbound")]
+#[action(shortname = 0xda)]
+fn migrate_soulbound_balance(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "migrate_soulbound_balance");
+    let new_account = state
+        .soulbound_migrations
+        .remove(&ctx.sender)
+        .expect("No migration is approved for the caller.");
+    let balance = state.balance_of(&ctx.sender);
+    state.debit(ctx.sender, balance);
+    state.credit(new_account, balance);
+
+    if state.soulbound_accounts.contains(&ctx.sender) {
+        state
+            .soulbound_accounts
+            .retain(|soulbound_account| *soulbound_account != ctx.sender);
+        if !state.soulbound_accounts.contains(&new_account) {
+            state.soulbound_accounts.push(new_account);
+        }
+    }
+    state.assert_invariants();
+
+    state
+}
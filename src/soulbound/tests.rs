@@ -0,0 +1,59 @@
+//! Tests of soulbound tokens, on transfers and on spends that skip the transfer hooks, such as
+//! escrows.
+
+use super::{approve_soulbound_migration, migrate_soulbound_balance, set_account_soulbound};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, new_state};
+use crate::token::TashiTokenState;
+
+/// Balance of account 1 in the tests.
+const BALANCE: u128 = 1_000;
+
+/// A token where account 1 holds [`BALANCE`] and is soulbound.
+fn soulbound_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), BALANCE, None),
+    );
+    set_account_soulbound(context(account(0)), state, account(1), true)
+}
+
+#[test]
+#[should_panic(expected = "Tokens of this account are non-transferable.")]
+fn soulbound_account_cannot_transfer() {
+    let mut state = soulbound_state();
+    state.execute_transfer(
+        &context(account(1)),
+        Transfer::new(account(1), account(2), 1, None),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Tokens of this account are non-transferable.")]
+fn soulbound_account_cannot_escrow() {
+    let mut state = soulbound_state();
+    state.escrow(&context(account(1)), account(1), 1);
+}
+
+#[test]
+fn other_accounts_can_escrow() {
+    let mut state = soulbound_state();
+    state.escrow(&context(account(0)), account(0), BALANCE);
+    assert_eq!(state.balance_of(&account(1)), BALANCE);
+}
+
+#[test]
+fn approved_migration_moves_the_balance_and_the_flag() {
+    let state = approve_soulbound_migration(
+        context(account(0)),
+        soulbound_state(),
+        account(1),
+        Some(account(2)),
+    );
+    let state = migrate_soulbound_balance(context(account(1)), state);
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.balance_of(&account(2)), BALANCE);
+    assert!(state.is_soulbound(&account(2)));
+    assert!(!state.is_soulbound(&account(1)));
+}
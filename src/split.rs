@@ -0,0 +1,104 @@
+//! Split payments, dividing one transfer among several recipients by basis points. Every share is
+//! rounded down and the rounding remainder goes to the first recipient, so the parts always add up
+//! to the transferred amount.
+#![cfg_attr(not(feature = "split-payments"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "split-payments")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "split-payments")]
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+
+#[cfg(feature = "split-payments")]
+use crate::hooks::Transfer;
+#[cfg(feature = "split-payments")]
+use crate::token::TashiTokenState;
+use crate::token::{mul_div, BPS_DENOMINATOR};
+
+#[cfg(all(test, feature = "split-payments"))]
+mod tests;
+
+/// A recipient of a split payment.
+///
+/// ### Fields:
+///
+///   * `recipient`: [`Address`], account receiving a share.
+///   * `share_bps`: [`u16`], share of the payment, in basis points.
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub struct SplitShare {
+    pub recipient: Address,
+    pub share_bps: u16,
+}
+
+/// Divides `amount` among `shares`.
+///
+/// Panics if `shares` is empty or if the shares do not add up to 100%.
+///
+/// ### Parameters:
+///
+///   * `shares`: [`SplitShare`] slice, the recipients and their shares.
+///   * `amount`: [`u128`], amount to divide.
+///
+/// ### Returns:
+///
+/// A [`Vec`]<[`u128`]> with the part of each recipient, in the order of `shares`.
+pub fn split_amount(shares: &[SplitShare], amount: u128) -> Vec<u128> {
+    assert!(!shares.is_empty(), "A split needs at least one recipient.");
+    let total_bps: u128 = shares.iter().map(|share| u128::from(share.share_bps)).sum();
+    assert!(
+        total_bps == BPS_DENOMINATOR,
+        "Split shares add up to: {} basis points, must add up to: {}",
+        total_bps,
+        BPS_DENOMINATOR
+    );
+
+    let mut parts: Vec<u128> = shares
+        .iter()
+        .map(|share| mul_div(amount, u128::from(share.share_bps), BPS_DENOMINATOR))
+        .collect();
+    let remainder = amount - parts.iter().sum::<u128>();
+    parts[0] += remainder; // rounding remainder, less than one unit per recipient
+
+    parts
+}
+
+/// Transfer `amount` tokens from caller address, divided among `recipients` by their shares. Each
+/// part is a separate transfer, zero parts are skipped.
+///
+/// Panics if `recipients` is empty, if the shares do not add up to 100%, or if any part of the
+/// transfer fails.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `recipients`: [`Vec`]<[`SplitShare`]>, the recipients and their shares.
+///   * `amount`: [`u128`], amount to divide.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "split-payments")]
+#[action(shortname = 0x61)]
+fn transfer_split(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    recipients: Vec<SplitShare>,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "transfer_split");
+    let supply = state.supply();
+    let parts = split_amount(&recipients, amount);
+    for (share, part) in recipients.iter().zip(parts) {
+        if part > 0 {
+            state.execute_transfer(&ctx, Transfer::new(ctx.sender, share.recipient, part, None));
+        }
+    }
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
@@ -0,0 +1,60 @@
+//! Tests of split payments: the rounding remainder paid to the first recipient, zero parts which
+//! are skipped, and shares which do not add up to 100%.
+
+use super::{split_amount, transfer_split, SplitShare};
+use crate::testing::{account, context, new_state, INITIAL_SUPPLY};
+
+/// Shares of `bps` for accounts 1, 2 and so on.
+fn shares(bps: &[u16]) -> Vec<SplitShare> {
+    bps.iter()
+        .zip(1..)
+        .map(|(share_bps, index)| SplitShare {
+            recipient: account(index),
+            share_bps: *share_bps,
+        })
+        .collect()
+}
+
+#[test]
+fn remainder_goes_to_the_first_recipient() {
+    assert_eq!(
+        split_amount(&shares(&[3_333, 3_333, 3_334]), 1_001),
+        vec![335, 333, 333]
+    );
+}
+
+#[test]
+fn transfer_is_divided_among_the_recipients() {
+    let (state, _) = transfer_split(
+        context(account(0)),
+        new_state(),
+        shares(&[5_000, 3_000, 2_000]),
+        10_000,
+    );
+
+    assert_eq!(state.balance_of(&account(1)), 5_000);
+    assert_eq!(state.balance_of(&account(2)), 3_000);
+    assert_eq!(state.balance_of(&account(3)), 2_000);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 10_000);
+}
+
+#[test]
+fn zero_parts_are_skipped() {
+    let (state, _) = transfer_split(context(account(0)), new_state(), shares(&[5_000, 5_000]), 1);
+
+    assert_eq!(state.balance_of(&account(1)), 1);
+    assert_eq!(state.balance_of(&account(2)), 0);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 1);
+}
+
+#[test]
+#[should_panic(expected = "Split shares add up to: 9999 basis points, must add up to: 10000")]
+fn shares_must_add_up_to_everything() {
+    split_amount(&shares(&[5_000, 4_999]), 1_000);
+}
+
+#[test]
+#[should_panic(expected = "A split needs at least one recipient.")]
+fn split_needs_a_recipient() {
+    split_amount(&[], 1_000);
+}
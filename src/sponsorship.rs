@@ -0,0 +1,368 @@
+//! Gas sponsorship, letting accounts that hold no gas coin use the token. The account signs its
+//! transfer or approval off-chain and a sponsor submits it, paying the execution cost. Sponsors
+//! register which actions they relay and, optionally, the cohort of accounts they relay for, and
+//! only registered sponsors can submit sponsored actions within their registration.
+//!
+//! Sponsored actions are signed with the secp256k1 or ed25519 key of the account and checked by the
+//! shared verifier of signed messages, the address of the account being derived from its key.
+//! Each sponsored action consumes the nonce of the account, so a signed action can only be
+//! submitted once.
+#![cfg_attr(not(feature = "sponsorship"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::{Address, AddressType};
+#[cfg(feature = "sponsorship")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "sponsorship")]
+use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::Hash;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "sponsorship")]
+use crate::hooks::Transfer;
+use crate::signatures::{
+    signed_message_hash, uncompressed_secp256k1_key, SchemeSignature, SignerKey,
+};
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "sponsorship", feature = "ed25519"))]
+mod tests;
+
+/// Domain separator of sponsored actions.
+const SPONSORED_ACTION_DOMAIN: &[u8] = b"TASHI_TOKEN_SPONSORED_ACTION";
+
+/// The largest cohort a sponsor can restrict itself to.
+pub const MAX_SPONSOR_COHORT: usize = 256;
+
+/// An action a sponsor can relay.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SponsoredAction {
+    /// A transfer from the signing account.
+    #[discriminant(0)]
+    Transfer {},
+    /// An approval by the signing account.
+    #[discriminant(1)]
+    Approve {},
+}
+
+impl SponsoredAction {
+    /// Gets the byte identifying the action in signed messages.
+    fn tag(&self) -> u8 {
+        match self {
+            SponsoredAction::Transfer {} => 0,
+            SponsoredAction::Approve {} => 1,
+        }
+    }
+}
+
+/// The registration of a sponsor.
+///
+/// ### Fields:
+///
+///   * `actions`: [`Vec`]<[`SponsoredAction`]>, actions the sponsor relays.
+///   * `cohort`: [`Option`]<[`Vec`]<[`Address`]>>, accounts the sponsor relays for, `None` for
+///     every account.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sponsorship {
+    pub actions: Vec<SponsoredAction>,
+    pub cohort: Option<Vec<Address>>,
+}
+
+impl Sponsorship {
+    /// Whether the sponsor relays `action` for `account`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `action`: [`SponsoredAction`], the relayed action.
+    ///   * `account`: [`Address`], the signing account.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`bool`], true if the registration covers the action.
+    pub fn covers(&self, action: SponsoredAction, account: &Address) -> bool {
+        let in_cohort = match &self.cohort {
+            Some(cohort) => cohort.contains(account),
+            None => true,
+        };
+        self.actions.contains(&action) && in_cohort
+    }
+}
+
+/// Derives the address of the account owning `key`, the last 20 bytes of the hash of the key: of
+/// its uncompressed encoding `04 || x || y` for a secp256k1 key, as the chain derives account
+/// addresses, and of its 32 bytes for an ed25519 key.
+///
+/// ### Parameters:
+///
+///   * `key`: [`SignerKey`], the key of the account.
+///
+/// ### Returns:
+///
+/// The account [`Address`].
+pub fn account_address(key: &SignerKey) -> Address {
+    let key_bytes: Vec<u8> = match key {
+        SignerKey::Secp256k1 { key } => uncompressed_secp256k1_key(key).to_vec(),
+        SignerKey::Ed25519 { key } => key.to_vec(),
+    };
+    let hash = Hash::digest(key_bytes);
+    let mut identifier = [0u8; 20];
+    identifier.copy_from_slice(&hash.bytes[12..]);
+    Address {
+        address_type: AddressType::Account,
+        identifier,
+    }
+}
+
+/// Computes the hash an account signs to authorize a sponsored action.
+///
+/// ### Parameters:
+///
+///   * `contract`: [`Address`], address of this contract.
+///   * `action`: [`SponsoredAction`], the action.
+///   * `counterparty`: [`Address`], receiver of a transfer or spender of an approval.
+///   * `amount`: [`u128`], amount of the action.
+///   * `nonce`: [`u64`], the nonce of the account.
+///
+/// ### Returns:
+///
+/// The [`Hash`] to sign.
+pub fn sponsored_action_hash(
+    contract: &Address,
+    action: SponsoredAction,
+    counterparty: &Address,
+    amount: u128,
+    nonce: u64,
+) -> Hash {
+    let mut payload = vec![action.tag()];
+    pbc_traits::ReadWriteRPC::rpc_write_to(counterparty, &mut payload)
+        .expect("Writing to a vector cannot fail.");
+    payload.extend_from_slice(&amount.to_be_bytes());
+    payload.extend_from_slice(&nonce.to_be_bytes());
+    signed_message_hash(SPONSORED_ACTION_DOMAIN, contract, &payload)
+}
+
+impl TashiTokenState {
+    /// Authorizes a sponsored action submitted by `sponsor`, consuming the nonce of the signing
+    /// account.
+    ///
+    /// Panics if `sponsor` is not a registered sponsor covering the action and the account, if the
+    /// signature is invalid, or if `nonce` is not the next nonce of the account.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `sponsor`: [`Address`], account submitting the action.
+    ///   * `contract`: [`Address`], address of this contract.
+    ///   * `action`: [`SponsoredAction`], the action.
+    ///   * `counterparty`: [`Address`], receiver of a transfer or spender of an approval.
+    ///   * `amount`: [`u128`], amount of the action.
+    ///   * `nonce`: [`u64`], the nonce signed by the account.
+    ///   * `key`: [`SignerKey`], key of the account.
+    ///   * `signature`: [`SchemeSignature`], signature of the account.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`Address`] of the signing account.
+    #[allow(clippy::too_many_arguments)]
+    pub fn authorize_sponsored_action(
+        &mut self,
+        sponsor: &Address,
+        contract: &Address,
+        action: SponsoredAction,
+        counterparty: &Address,
+        amount: u128,
+        nonce: u64,
+        key: &SignerKey,
+        signature: &SchemeSignature,
+    ) -> Address {
+        let message = sponsored_action_hash(contract, action, counterparty, amount, nonce);
+        key.assert_signed(&message, signature, "Invalid sponsored action signature.");
+        let account = account_address(key);
+
+        let sponsorship = self
+            .sponsors
+            .get(sponsor)
+            .expect("Caller is not a registered sponsor.");
+        assert!(
+            sponsorship.covers(action, &account),
+            "Caller does not sponsor this action for the signing account."
+        );
+        self.consume_nonce(account, nonce);
+
+        account
+    }
+}
+
+/// Register caller address as a sponsor relaying `actions` for `cohort`, replacing its previous
+/// registration. Anyone can sponsor.
+///
+/// Panics if `actions` is empty or if `cohort` has more than [`MAX_SPONSOR_COHORT`] accounts.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `actions`: [`Vec`]<[`SponsoredAction`]>, actions the caller relays.
+///   * `cohort`: [`Option`]<[`Vec`]<[`Address`]>>, accounts the caller relays for, `None` for
+///     every account.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "sponsorship")]
+#[action(shortname = 0x74)]
+fn register_sponsor(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    actions: Vec<SponsoredAction>,
+    cohort: Option<Vec<Address>>,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "register_sponsor");
+    assert!(!actions.is_empty(), "A sponsor must relay some action.");
+    if let Some(cohort) = &cohort {
+        assert!(
+            cohort.len() <= MAX_SPONSOR_COHORT,
+            "Cohort has: {} accounts, maximum is: {}",
+            cohort.len(),
+            MAX_SPONSOR_COHORT
+        );
+    }
+    state
+        .sponsors
+        .insert(ctx.sender, Sponsorship { actions, cohort });
+    state.assert_invariants();
+
+    state
+}
+
+/// Remove the sponsor registration of caller address.
+///
+/// Panics if the caller is not a registered sponsor.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "sponsorship")]
+#[action(shortname = 0x75)]
+fn unregister_sponsor(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "unregister_sponsor");
+    state
+        .sponsors
+        .remove(&ctx.sender)
+        .expect("Caller is not a registered sponsor.");
+    state.assert_invariants();
+
+    state
+}
+
+/// Submit a transfer of `amount` tokens to address `to` signed by the sending account. The caller
+/// pays the execution cost and must sponsor transfers for the sending account.
+///
+/// Panics if the caller does not sponsor the transfer, if the signature or nonce is invalid, or if
+/// there is insufficient balance in the sending account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`u128`], amount to transfer.
+///   * `nonce`: [`u64`], the nonce of the sending account.
+///   * `key`: [`SignerKey`], key of the sending account, secp256k1 or ed25519.
+///   * `signature`: [`SchemeSignature`], signature of the sending account, over the hash built by
+///     [`sponsored_action_hash`].
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "sponsorship")]
+#[action(shortname = 0x76)]
+fn sponsored_transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    to: Address,
+    amount: u128,
+    nonce: u64,
+    key: SignerKey,
+    signature: SchemeSignature,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "sponsored_transfer");
+    let from = state.authorize_sponsored_action(
+        &ctx.sender,
+        &ctx.contract_address,
+        SponsoredAction::Transfer {},
+        &to,
+        amount,
+        nonce,
+        &key,
+        &signature,
+    );
+    let supply = state.supply();
+    let transfer = Transfer {
+        #[cfg(feature = "nonces")]
+        nonce_consumed: true, // consumed by the authorization
+        ..Transfer::new(from, to, amount, None)
+    };
+    state.execute_transfer(&ctx, transfer);
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Submit an approval of `amount` tokens for address `spender` signed by the approving account.
+/// The caller pays the execution cost and must sponsor approvals for the approving account.
+///
+/// Panics if the caller does not sponsor the approval, if the signature or nonce is invalid, or if
+/// the address guards reject `spender`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `spender`: [`Address`], account which will spend the funds.
+///   * `amount`: [`u128`], amount to allot to `spender`.
+///   * `nonce`: [`u64`], the nonce of the approving account.
+///   * `key`: [`SignerKey`], key of the approving account, secp256k1 or ed25519.
+///   * `signature`: [`SchemeSignature`], signature of the approving account, over the hash built by
+///     [`sponsored_action_hash`].
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "sponsorship")]
+#[action(shortname = 0x77)]
+fn sponsored_approve(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    spender: Address,
+    amount: u128,
+    nonce: u64,
+    key: SignerKey,
+    signature: SchemeSignature,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "sponsored_approve");
+    let owner = state.authorize_sponsored_action(
+        &ctx.sender,
+        &ctx.contract_address,
+        SponsoredAction::Approve {},
+        &spender,
+        amount,
+        nonce,
+        &key,
+        &signature,
+    );
+    state.assert_valid_destination(&ctx, &spender);
+    state.approve_spender(owner, spender, amount);
+    state.assert_invariants();
+
+    state
+}
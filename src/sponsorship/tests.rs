@@ -0,0 +1,211 @@
+//! Tests of sponsored actions signed with ed25519 keys: authorization by registered sponsors,
+//! the nonce consumed by each action, and rejected signatures. Also checks the account addresses
+//! derived from secp256k1 keys against the addresses the chain gives them.
+
+use ed25519_compact::{KeyPair, Seed};
+use pbc_contract_common::address::{Address, AddressType};
+use pbc_contract_common::public_key::PublicKey;
+use pbc_traits::ReadWriteRPC;
+
+use super::{
+    account_address, register_sponsor, sponsored_action_hash, sponsored_approve,
+    sponsored_transfer, SponsoredAction,
+};
+use crate::hooks::Transfer;
+use crate::signatures::{SchemeSignature, SignerKey};
+use crate::testing::{account, context, new_state, token_address};
+use crate::token::TashiTokenState;
+
+/// The ed25519 key pair derived from a seed of `index` in every byte.
+fn key_pair(index: u8) -> KeyPair {
+    KeyPair::from_seed(Seed::new([index; 32]))
+}
+
+/// The key of `pair`.
+fn signer_key(pair: &KeyPair) -> SignerKey {
+    SignerKey::Ed25519 { key: *pair.pk }
+}
+
+/// Decodes the hexadecimal string `hex`.
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).unwrap())
+        .collect()
+}
+
+/// The secp256k1 key with the compressed encoding `hex`.
+fn secp256k1_key(hex: &str) -> SignerKey {
+    let bytes = from_hex(hex);
+    SignerKey::Secp256k1 {
+        key: PublicKey::rpc_read_from(&mut &bytes[..]),
+    }
+}
+
+/// The account address with the identifier `hex`.
+fn account_with_identifier(hex: &str) -> Address {
+    Address {
+        address_type: AddressType::Account,
+        identifier: from_hex(hex).try_into().unwrap(),
+    }
+}
+
+/// Signs `action` of `amount` with `counterparty` and `nonce` with `pair`.
+fn sign(
+    pair: &KeyPair,
+    action: SponsoredAction,
+    counterparty: &Address,
+    amount: u128,
+    nonce: u64,
+) -> SchemeSignature {
+    let message = sponsored_action_hash(&token_address(), action, counterparty, amount, nonce);
+    SchemeSignature::Ed25519 {
+        signature: *pair.sk.sign(message.bytes, None),
+    }
+}
+
+/// A token where account 5 sponsors transfers and approvals for everyone, and the account of
+/// key pair 1 holds 1000 tokens.
+fn sponsored_state() -> TashiTokenState {
+    let mut state = register_sponsor(
+        context(account(5)),
+        new_state(),
+        vec![SponsoredAction::Transfer {}, SponsoredAction::Approve {}],
+        None,
+    );
+    let signer = account_address(&signer_key(&key_pair(1)));
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), signer, 1_000, None),
+    );
+    state
+}
+
+/// Submits a sponsored transfer of `amount` to account 2 with `nonce`, signed by `pair`.
+fn transfer(state: TashiTokenState, pair: &KeyPair, amount: u128, nonce: u64) -> TashiTokenState {
+    let signature = sign(
+        pair,
+        SponsoredAction::Transfer {},
+        &account(2),
+        amount,
+        nonce,
+    );
+    let key = signer_key(&key_pair(1));
+    let (state, _) = sponsored_transfer(
+        context(account(5)),
+        state,
+        account(2),
+        amount,
+        nonce,
+        key,
+        signature,
+    );
+    state
+}
+
+#[test]
+fn ed25519_account_transfers_through_a_sponsor() {
+    let signer = account_address(&signer_key(&key_pair(1)));
+    let state = transfer(sponsored_state(), &key_pair(1), 400, 0);
+
+    assert_eq!(state.balance_of(&signer), 600);
+    assert_eq!(state.balance_of(&account(2)), 400);
+    assert_eq!(state.nonce_of(&signer), 1);
+}
+
+#[test]
+#[cfg(feature = "nonces")]
+fn sponsored_transfer_bumps_the_nonce_once() {
+    let signer = account_address(&signer_key(&key_pair(1)));
+    let mut state = sponsored_state();
+    state.nonces_on_transfers = true;
+    let state = transfer(state, &key_pair(1), 400, 0);
+    let state = transfer(state, &key_pair(1), 100, 1);
+
+    assert_eq!(state.nonce_of(&signer), 2);
+    assert_eq!(state.balance_of(&account(2)), 500);
+}
+
+#[test]
+#[should_panic(expected = "Nonce: 0, expected nonce: 1")]
+fn sponsored_transfer_cannot_be_replayed() {
+    let state = transfer(sponsored_state(), &key_pair(1), 400, 0);
+    transfer(state, &key_pair(1), 400, 0);
+}
+
+#[test]
+#[should_panic(expected = "Invalid sponsored action signature.")]
+fn signature_of_another_key_is_rejected() {
+    transfer(sponsored_state(), &key_pair(2), 400, 0);
+}
+
+#[test]
+#[should_panic(expected = "Caller is not a registered sponsor.")]
+fn unregistered_sponsor_is_rejected() {
+    let signature = sign(
+        &key_pair(1),
+        SponsoredAction::Transfer {},
+        &account(2),
+        400,
+        0,
+    );
+    sponsored_transfer(
+        context(account(6)),
+        sponsored_state(),
+        account(2),
+        400,
+        0,
+        signer_key(&key_pair(1)),
+        signature,
+    );
+}
+
+#[test]
+fn ed25519_account_approves_through_a_sponsor() {
+    let signer = account_address(&signer_key(&key_pair(1)));
+    let signature = sign(
+        &key_pair(1),
+        SponsoredAction::Approve {},
+        &account(3),
+        250,
+        0,
+    );
+    let state = sponsored_approve(
+        context(account(5)),
+        sponsored_state(),
+        account(3),
+        250,
+        0,
+        signer_key(&key_pair(1)),
+        signature,
+    );
+
+    assert_eq!(state.allowance(&signer, &account(3)), 250);
+    assert_eq!(state.nonce_of(&signer), 1);
+}
+
+#[test]
+fn secp256k1_address_is_derived_from_the_uncompressed_key() {
+    // the key of private key 1, the generator, with an even y
+    let even = secp256k1_key("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+    // the key of private key 6, with an odd y
+    let odd = secp256k1_key("03fff97bd5755eeea420453a14355235d382f6472f8568a18b2f057a1460297556");
+
+    assert_eq!(
+        account_address(&even),
+        account_with_identifier("35e97a5e078a5a0f28ec96d547bfee9ace803ac0")
+    );
+    assert_eq!(
+        account_address(&odd),
+        account_with_identifier("cd14096779c5a0636be35119b4d123a0722384cd")
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid secp256k1 public key.")]
+fn secp256k1_key_off_the_curve_is_rejected() {
+    // x = 5 gives x^3 + 7 = 132, which is not a square modulo the field prime
+    account_address(&secp256k1_key(
+        "020000000000000000000000000000000000000000000000000000000000000005",
+    ));
+}
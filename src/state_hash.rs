@@ -0,0 +1,133 @@
+//! Deterministic hashes of the token state, letting two parties, such as an issuer and a custodian,
+//! check they agree on the state at a given block by comparing one hash. The owner or a keeper
+//! records the hash of the current state, which is returned to the caller, and the latest
+//! [`STATE_HASH_HISTORY`] hashes are kept with the block they were computed at.
+//!
+//! The hash is the SHA-256 hash of the RPC encoding of, in order: the domain tag
+//! [`STATE_HASH_DOMAIN`], the name, symbol, decimals, owner, pause flag, burn rate and interest
+//! index, the total supply, the number of balances followed by each account and balance, and the
+//! number of owners with allowances followed by each owner, its number of spenders and each spender
+//! and allowance. Maps are in address order, and amounts are written as `u128` as stored, in shares
+//! when balances accrue interest, so the hash does not depend on the `u64-amounts` feature.
+#![cfg_attr(not(feature = "state-hash"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+#[cfg(feature = "state-hash")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "state-hash")]
+use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::Hash;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "state-hash")]
+use crate::admin::Role;
+use crate::shards::BalanceEntries;
+use crate::token::{amount_value, TashiTokenState};
+#[cfg(feature = "state-hash")]
+use crate::views::return_value;
+
+#[cfg(all(test, feature = "state-hash"))]
+mod tests;
+
+/// The number of state hashes kept, older hashes are dropped.
+pub const STATE_HASH_HISTORY: usize = 16;
+
+/// The domain tag the hashed encoding starts with, changed whenever the encoding changes.
+pub const STATE_HASH_DOMAIN: &str = "tashi-token/state-hash/v1";
+
+/// A hash of the token state.
+///
+/// ### Fields:
+///
+///   * `hash`: [`Hash`], hash of the state.
+///   * `block_time`: [`i64`], block the hash was computed at.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateHash {
+    pub hash: Hash,
+    pub block_time: i64,
+}
+
+/// Appends the RPC encoding of `value` to `payload`.
+fn write<T: pbc_traits::ReadWriteRPC>(payload: &mut Vec<u8>, value: &T) {
+    value
+        .rpc_write_to(payload)
+        .expect("Writing to a vector cannot fail.");
+}
+
+impl TashiTokenState {
+    /// Computes the hash of the current state, as described in the module documentation.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`Hash`] of the state.
+    pub fn state_hash(&self) -> Hash {
+        let mut payload = Vec::new();
+        write(&mut payload, &STATE_HASH_DOMAIN.to_string());
+        write(&mut payload, &self.name);
+        write(&mut payload, &self.symbol);
+        write(&mut payload, &self.decimals);
+        write(&mut payload, &self.owner);
+        write(&mut payload, &self.paused);
+        write(&mut payload, &self.burn_rate_bps);
+        write(&mut payload, &self.interest_index);
+        write(&mut payload, &amount_value(self.total_supply));
+
+        let balances = self.balances.entries();
+        write(&mut payload, &(balances.len() as u32));
+        for (account, balance) in balances.iter() {
+            write(&mut payload, account);
+            write(&mut payload, &amount_value(*balance));
+        }
+        write(&mut payload, &(self.allowed.len() as u32));
+        for (owner, spenders) in self.allowed.iter() {
+            write(&mut payload, owner);
+            write(&mut payload, &(spenders.len() as u32));
+            for (spender, amount) in spenders.iter() {
+                write(&mut payload, spender);
+                write(&mut payload, &amount_value(*amount));
+            }
+        }
+        Hash::digest(payload)
+    }
+}
+
+/// Record the hash of the current state, dropping the oldest hash when [`STATE_HASH_HISTORY`]
+/// hashes are kept. Only the owner and accounts with the [`Role::Keeper`] role can call this
+/// action.
+///
+/// Panics if the caller is neither the owner nor has the role.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state, and the recorded [`StateHash`] in the return data.
+#[cfg(feature = "state-hash")]
+#[action(shortname = 0xff)]
+fn record_state_hash(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "record_state_hash");
+    if !state.has_role(Role::Keeper {}, &ctx.sender) {
+        state.assert_owner(&ctx.sender);
+    }
+    let record = StateHash {
+        hash: state.state_hash(),
+        block_time: ctx.block_time,
+    };
+    if state.state_hashes.len() >= STATE_HASH_HISTORY {
+        state.state_hashes.remove(0);
+    }
+    state.state_hashes.push(record.clone());
+
+    let events = return_value(record);
+    state.assert_invariants();
+
+    (state, events)
+}
@@ -0,0 +1,75 @@
+//! Golden tests of the state hash. The expected hashes were computed independently from the
+//! encoding in the module documentation: when one of them fails, the encoding changed, and
+//! [`STATE_HASH_DOMAIN`] must be bumped along with the expected hashes.
+
+use pbc_contract_common::Hash;
+
+use super::{record_state_hash, STATE_HASH_DOMAIN, STATE_HASH_HISTORY};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state};
+use crate::token::TashiTokenState;
+
+/// The lowercase hexadecimal encoding of `hash`.
+fn hex(hash: &Hash) -> String {
+    hash.bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// A token where the owner sent 100 tokens to account 1 and allowed account 2 to spend 50.
+fn active_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 100, None),
+    );
+    state.approve_spender(account(0), account(2), 50);
+    state
+}
+
+#[test]
+fn domain_is_the_first_version() {
+    assert_eq!(STATE_HASH_DOMAIN, "tashi-token/state-hash/v1");
+}
+
+#[test]
+fn hash_of_a_new_token() {
+    assert_eq!(
+        hex(&new_state().state_hash()),
+        "ed044141c93a8fe2080d87ec4eb699f9582a6ee6d7fadc672405032bba0e7d1f"
+    );
+}
+
+#[test]
+fn hash_of_balances_and_allowances() {
+    assert_eq!(
+        hex(&active_state().state_hash()),
+        "34db518558e9d8cc1b0529aa8e9e3adbd0f4084ff257fd54fd04b471cdf47e5d"
+    );
+}
+
+#[test]
+fn recorded_hash_is_the_hash_of_the_state() {
+    let state = record_state_hash(context(account(0)), active_state()).0;
+
+    assert_eq!(state.state_hashes.len(), 1);
+    assert_eq!(state.state_hashes[0].hash, active_state().state_hash());
+}
+
+#[test]
+fn oldest_hash_is_dropped_from_a_full_history() {
+    let mut state = new_state();
+    for time in 0..=STATE_HASH_HISTORY as i64 {
+        state = record_state_hash(context_at(account(0), time * 1_000), state).0;
+    }
+
+    assert_eq!(state.state_hashes.len(), STATE_HASH_HISTORY);
+    assert_eq!(state.state_hashes[0].block_time, 1);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can perform this action.")]
+fn only_the_owner_or_a_keeper_records_a_hash() {
+    record_state_hash(context(account(1)), new_state());
+}
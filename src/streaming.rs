@@ -0,0 +1,224 @@
+//! Payment streams, where a deposit is released to a recipient continuously over time.
+#![cfg_attr(not(feature = "streaming"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "streaming")]
+use pbc_contract_common::context::ContractContext;
+use read_write_state_derive::ReadWriteState;
+
+use crate::token::{mul_div, TashiTokenState};
+
+#[cfg(all(test, feature = "streaming"))]
+mod tests;
+
+/// A deposit released linearly from `sender` to `recipient` between `start_time` and `end_time`.
+///
+/// ### Fields:
+///
+///   * `sender`: [`Address`], account that funded the stream.
+///   * `recipient`: [`Address`], account the stream pays out to.
+///   * `deposit`: [`u128`], total amount paid out over the lifetime of the stream.
+///   * `withdrawn`: [`u128`], amount the recipient has withdrawn so far.
+///   * `start_time`: [`i64`], block production time the stream starts at, in milliseconds.
+///   * `end_time`: [`i64`], block production time the full deposit is released at, in milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stream {
+    pub sender: Address,
+    pub recipient: Address,
+    pub deposit: u128,
+    pub withdrawn: u128,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+impl Stream {
+    /// Gets the amount released by the stream at time `now`, including what was withdrawn.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount released so far.
+    pub fn streamed(&self, now: i64) -> u128 {
+        if now <= self.start_time {
+            0
+        } else if now >= self.end_time {
+            self.deposit
+        } else {
+            mul_div(
+                self.deposit,
+                (now - self.start_time) as u128,
+                (self.end_time - self.start_time) as u128,
+            )
+        }
+    }
+
+    /// Gets the amount the recipient can withdraw at time `now`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount released but not yet withdrawn.
+    pub fn withdrawable(&self, now: i64) -> u128 {
+        self.streamed(now) - self.withdrawn
+    }
+}
+
+impl TashiTokenState {
+    /// Gets the stream with id `stream_id`.
+    ///
+    /// Panics if there is no such stream.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `stream_id`: [`u64`], id of the stream.
+    ///
+    /// ### Returns:
+    ///
+    /// A reference to the [`Stream`].
+    pub fn stream(&self, stream_id: u64) -> &Stream {
+        self.streams
+            .get(&stream_id)
+            .unwrap_or_else(|| panic!("No stream with id: {}", stream_id))
+    }
+}
+
+/// Create a stream paying `total` tokens from caller address to `recipient`, released linearly
+/// between `start_time` and `end_time`. The deposit is taken from the caller up front.
+///
+/// Panics if `end_time` is not after `start_time`, if `total` is zero, or if there is insufficient
+/// balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `recipient`: [`Address`], account the stream pays out to.
+///   * `total`: [`u128`], amount paid out over the lifetime of the stream.
+///   * `start_time`: [`i64`], block production time the stream starts at, in milliseconds.
+///   * `end_time`: [`i64`], block production time the stream ends at, in milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "streaming")]
+#[action(shortname = 0x16)]
+fn create_stream(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    recipient: Address,
+    total: u128,
+    start_time: i64,
+    end_time: i64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "create_stream");
+    assert!(end_time > start_time, "Stream must end after it starts.");
+    assert!(total > 0, "Stream must have a non-zero deposit.");
+    state.escrow(&ctx, ctx.sender, total); // escrow the deposit
+
+    let stream_id = state.next_stream_id;
+    state.next_stream_id += 1;
+    state.streams.insert(
+        stream_id,
+        Stream {
+            sender: ctx.sender,
+            recipient,
+            deposit: total,
+            withdrawn: 0,
+            start_time,
+            end_time,
+        },
+    );
+    state.assert_invariants();
+
+    state
+}
+
+/// Withdraw everything released so far by stream `stream_id` to the caller. Only the recipient of
+/// the stream can call this action. The stream is removed once its full deposit is withdrawn.
+///
+/// Panics if there is no such stream or if the caller is not its recipient.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `stream_id`: [`u64`], id of the stream to withdraw from.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "streaming")]
+#[action(shortname = 0x17)]
+fn withdraw_from_stream(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    stream_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "withdraw_from_stream");
+    let stream = state.stream(stream_id);
+    assert!(
+        stream.recipient == ctx.sender,
+        "Only the recipient can withdraw from the stream."
+    );
+    let amount = stream.withdrawable(ctx.block_production_time);
+    let sender = stream.sender;
+    let finished = stream.withdrawn + amount == stream.deposit;
+
+    if finished {
+        state.streams.remove(&stream_id);
+    } else {
+        state.streams.get_mut(&stream_id).unwrap().withdrawn += amount;
+    }
+    state.payout(&ctx, sender, ctx.sender, amount); // pay out the released amount
+    state.assert_invariants();
+
+    state
+}
+
+/// Cancel stream `stream_id`, settling it pro rata: the recipient receives everything released
+/// and not yet withdrawn, and the sender is refunded the rest. Either party can call this action.
+///
+/// Panics if there is no such stream or if the caller is neither its sender nor its recipient.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `stream_id`: [`u64`], id of the stream to cancel.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "streaming")]
+#[action(shortname = 0x18)]
+fn cancel_stream(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    stream_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "cancel_stream");
+    let stream = state.stream(stream_id);
+    assert!(
+        stream.sender == ctx.sender || stream.recipient == ctx.sender,
+        "Only the sender or recipient can cancel the stream."
+    );
+    let streamed = stream.streamed(ctx.block_production_time);
+    let recipient_amount = streamed - stream.withdrawn;
+    let sender_refund = stream.deposit - streamed;
+    let (sender, recipient) = (stream.sender, stream.recipient);
+
+    state.streams.remove(&stream_id);
+    state.payout(&ctx, sender, recipient, recipient_amount); // settle what was released
+    state.release(&ctx, sender, sender_refund); // refund what was not
+    state.assert_invariants();
+
+    state
+}
@@ -0,0 +1,120 @@
+//! Tests of payment streams: withdrawals pro rata to the time elapsed, at the start, middle and
+//! end of a stream, and cancellations splitting the deposit between recipient and sender.
+
+use super::{cancel_stream, create_stream, withdraw_from_stream};
+use crate::testing::{account, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// Block production time the streams of the tests start at.
+const START: i64 = 1_000;
+
+/// Block production time the streams of the tests end at, 3000 milliseconds after [`START`], so
+/// a third of the deposit does not divide evenly.
+const END: i64 = 4_000;
+
+/// A token where the owner streams 100 tokens to account 1 between [`START`] and [`END`], as
+/// stream 0.
+fn streaming_state() -> TashiTokenState {
+    create_stream(
+        context_at(account(0), 0),
+        new_state(),
+        account(1),
+        100,
+        START,
+        END,
+    )
+}
+
+#[test]
+fn create_escrows_the_deposit() {
+    let state = streaming_state();
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 100);
+    assert_eq!(state.stream(0).deposit, 100);
+    state.assert_invariants();
+}
+
+#[test]
+fn nothing_is_withdrawn_before_the_start() {
+    let state = withdraw_from_stream(context_at(account(1), START), streaming_state(), 0);
+
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.stream(0).withdrawn, 0);
+}
+
+#[test]
+fn withdrawal_is_pro_rata_to_the_time_elapsed() {
+    // 100 * 1000 / 3000 rounds down to 33
+    let state = withdraw_from_stream(context_at(account(1), START + 1_000), streaming_state(), 0);
+
+    assert_eq!(state.balance_of(&account(1)), 33);
+    assert_eq!(state.stream(0).withdrawn, 33);
+
+    // 100 * 2000 / 3000 rounds down to 66, of which 33 is left
+    let state = withdraw_from_stream(context_at(account(1), START + 2_000), state, 0);
+
+    assert_eq!(state.balance_of(&account(1)), 66);
+    assert_eq!(state.stream(0).withdrawn, 66);
+    state.assert_invariants();
+}
+
+#[test]
+fn withdrawal_at_the_end_pays_the_rest_and_removes_the_stream() {
+    let state = withdraw_from_stream(context_at(account(1), START + 1_000), streaming_state(), 0);
+    let state = withdraw_from_stream(context_at(account(1), END + 500), state, 0);
+
+    assert_eq!(state.balance_of(&account(1)), 100);
+    assert!(state.streams.is_empty());
+    state.assert_invariants();
+}
+
+#[test]
+#[should_panic(expected = "Only the recipient can withdraw from the stream.")]
+fn only_the_recipient_can_withdraw() {
+    withdraw_from_stream(context_at(account(0), END), streaming_state(), 0);
+}
+
+#[test]
+fn cancel_before_the_start_refunds_the_sender() {
+    let state = cancel_stream(context_at(account(1), START), streaming_state(), 0);
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY);
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert!(state.streams.is_empty());
+}
+
+#[test]
+fn cancel_rounds_the_recipient_share_down() {
+    // 100 * 1000 / 3000 is 33.3, the recipient gets 33 and the sender the remaining 67
+    let state = cancel_stream(context_at(account(0), START + 1_000), streaming_state(), 0);
+
+    assert_eq!(state.balance_of(&account(1)), 33);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 33);
+    assert!(state.streams.is_empty());
+    state.assert_invariants();
+}
+
+#[test]
+fn cancel_pays_only_what_was_not_withdrawn() {
+    let state = withdraw_from_stream(context_at(account(1), START + 1_000), streaming_state(), 0);
+    // 100 * 2000 / 3000 rounds down to 66, 33 of which was withdrawn
+    let state = cancel_stream(context_at(account(1), START + 2_000), state, 0);
+
+    assert_eq!(state.balance_of(&account(1)), 66);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 66);
+    state.assert_invariants();
+}
+
+#[test]
+fn cancel_after_the_end_pays_the_recipient_everything() {
+    let state = cancel_stream(context_at(account(0), END), streaming_state(), 0);
+
+    assert_eq!(state.balance_of(&account(1)), 100);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 100);
+}
+
+#[test]
+#[should_panic(expected = "Only the sender or recipient can cancel the stream.")]
+fn third_party_cannot_cancel() {
+    cancel_stream(context_at(account(2), START), streaming_state(), 0);
+}
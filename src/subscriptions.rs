@@ -0,0 +1,218 @@
+//! Subscriptions, where a subscriber authorizes a merchant to charge a fixed amount once per
+//! period. Unlike an allowance the cap resets every period, and each subscription records what
+//! the merchant has charged so far. Periods the merchant does not charge are not carried over.
+#![cfg_attr(not(feature = "subscriptions"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "subscriptions")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "subscriptions")]
+use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "subscriptions")]
+use crate::hooks::Transfer;
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "subscriptions"))]
+mod tests;
+
+/// A subscription of a subscriber to a merchant.
+///
+/// ### Fields:
+///
+///   * `amount_per_period`: [`u128`], amount the merchant can charge every period.
+///   * `period`: [`i64`], length of a period, in milliseconds.
+///   * `next_charge_at`: [`i64`], block production time the merchant can charge next, in
+///     milliseconds.
+///   * `charged_total`: [`u128`], amount the merchant has charged over the subscription.
+///   * `charges`: [`u64`], number of times the merchant has charged.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Subscription {
+    pub amount_per_period: u128,
+    pub period: i64,
+    pub next_charge_at: i64,
+    pub charged_total: u128,
+    pub charges: u64,
+}
+
+impl TashiTokenState {
+    /// Gets the subscription of `subscriber` to `merchant`.
+    ///
+    /// Panics if there is no such subscription.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `subscriber`: [`Address`], the paying account.
+    ///   * `merchant`: [`Address`], the charging account.
+    ///
+    /// ### Returns:
+    ///
+    /// A mutable reference to the [`Subscription`].
+    pub fn subscription(&mut self, subscriber: &Address, merchant: &Address) -> &mut Subscription {
+        self.subscriptions
+            .get_mut(subscriber)
+            .and_then(|subscriber_subscriptions| subscriber_subscriptions.get_mut(merchant))
+            .expect("No subscription to this merchant.")
+    }
+
+    /// Removes the subscription of `subscriber` to `merchant`, if any.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `subscriber`: [`Address`], the paying account.
+    ///   * `merchant`: [`Address`], the charging account.
+    pub fn remove_subscription(&mut self, subscriber: &Address, merchant: &Address) {
+        if let Some(subscriber_subscriptions) = self.subscriptions.get_mut(subscriber) {
+            subscriber_subscriptions.remove(merchant);
+            if subscriber_subscriptions.is_empty() {
+                self.subscriptions.remove(subscriber);
+            }
+        }
+    }
+
+    /// Inserts or replaces the subscription of `subscriber` to `merchant`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `subscriber`: [`Address`], the paying account.
+    ///   * `merchant`: [`Address`], the charging account.
+    ///   * `subscription`: [`Subscription`], the subscription.
+    pub fn insert_subscription(
+        &mut self,
+        subscriber: Address,
+        merchant: Address,
+        subscription: Subscription,
+    ) {
+        if !self.subscriptions.contains_key(&subscriber) {
+            self.subscriptions.insert(subscriber, SortedVecMap::new());
+        }
+        self.subscriptions
+            .get_mut(&subscriber)
+            .unwrap()
+            .insert(merchant, subscription);
+    }
+}
+
+/// Subscribe caller address to `merchant`, which can charge `amount_per_period` once every
+/// `period`, starting now. Replaces an existing subscription to `merchant`.
+///
+/// Panics if `amount_per_period` is zero, if `period` is not positive, or if the address guards
+/// reject `merchant`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `merchant`: [`Address`], account allowed to charge the caller.
+///   * `amount_per_period`: [`u128`], amount the merchant can charge every period.
+///   * `period`: [`i64`], length of a period, in milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "subscriptions")]
+#[action(shortname = 0x65)]
+fn subscribe(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    merchant: Address,
+    amount_per_period: u128,
+    period: i64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "subscribe");
+    assert!(amount_per_period > 0, "Subscription amount cannot be zero.");
+    assert!(period > 0, "Subscription period must be positive.");
+    state.assert_valid_destination(&ctx, &merchant);
+    state.insert_subscription(
+        ctx.sender,
+        merchant,
+        Subscription {
+            amount_per_period,
+            period,
+            next_charge_at: ctx.block_production_time,
+            charged_total: 0,
+            charges: 0,
+        },
+    );
+    state.assert_invariants();
+
+    state
+}
+
+/// Charge `subscriber` the amount of the current period of its subscription to caller address.
+/// The charge is a transfer from the subscriber, running every transfer hook.
+///
+/// Panics if `subscriber` has no subscription to the caller, if the caller already charged the
+/// current period, or if the transfer fails.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `subscriber`: [`Address`], account to charge.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "subscriptions")]
+#[action(shortname = 0x66)]
+fn charge(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    subscriber: Address,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "charge");
+    let now = ctx.block_production_time;
+    let subscription = state.subscription(&subscriber, &ctx.sender);
+    assert!(
+        now >= subscription.next_charge_at,
+        "Subscription can be charged next at: {}",
+        subscription.next_charge_at
+    );
+    // skip the periods that were not charged, they are not carried over
+    let periods = (now - subscription.next_charge_at) / subscription.period + 1;
+    subscription.next_charge_at += subscription.period * periods;
+    subscription.charged_total += subscription.amount_per_period;
+    subscription.charges += 1;
+    let amount = subscription.amount_per_period;
+
+    let supply = state.supply();
+    state.execute_transfer(&ctx, Transfer::new(subscriber, ctx.sender, amount, None));
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Cancel the subscription of caller address to `merchant`.
+///
+/// Panics if the caller has no subscription to `merchant`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `merchant`: [`Address`], account to stop paying.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "subscriptions")]
+#[action(shortname = 0x67)]
+fn cancel_subscription(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    merchant: Address,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "cancel_subscription");
+    state.subscription(&ctx.sender, &merchant); // panics without a subscription
+    state.remove_subscription(&ctx.sender, &merchant);
+    state.assert_invariants();
+
+    state
+}
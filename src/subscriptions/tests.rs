@@ -0,0 +1,69 @@
+//! Tests of subscriptions: one charge per period, periods not carried over, and cancelled
+//! subscriptions.
+
+use super::{cancel_subscription, charge, subscribe};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state};
+use crate::token::TashiTokenState;
+
+/// One subscription period, in milliseconds.
+const DAY: i64 = 86_400_000;
+
+/// A token where account 1 holds 10_000 and lets merchant account 2 charge 100 a day.
+fn subscribed_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    subscribe(context(account(1)), state, account(2), 100, DAY)
+}
+
+/// Charges account 1 by account 2 at block production time `time`.
+fn charge_at(state: TashiTokenState, time: i64) -> TashiTokenState {
+    charge(context_at(account(2), time), state, account(1)).0
+}
+
+#[test]
+fn merchant_charges_once_per_period() {
+    let state = charge_at(subscribed_state(), 0);
+    let mut state = charge_at(state, DAY);
+
+    assert_eq!(state.balance_of(&account(1)), 9_800);
+    assert_eq!(state.balance_of(&account(2)), 200);
+    let subscription = state.subscription(&account(1), &account(2));
+    assert_eq!(subscription.charged_total, 200);
+    assert_eq!(subscription.charges, 2);
+}
+
+#[test]
+#[should_panic(expected = "Subscription can be charged next at: 86400000")]
+fn second_charge_in_a_period_is_rejected() {
+    let state = charge_at(subscribed_state(), 0);
+    charge_at(state, DAY - 1);
+}
+
+#[test]
+fn missed_periods_are_not_carried_over() {
+    let mut state = charge_at(subscribed_state(), 3 * DAY + DAY / 2);
+
+    assert_eq!(state.balance_of(&account(2)), 100);
+    assert_eq!(
+        state.subscription(&account(1), &account(2)).next_charge_at,
+        4 * DAY
+    );
+}
+
+#[test]
+#[should_panic(expected = "No subscription to this merchant.")]
+fn cancelled_subscription_cannot_be_charged() {
+    let state = cancel_subscription(context(account(1)), subscribed_state(), account(2));
+    assert!(state.subscriptions.is_empty());
+    charge_at(state, 0);
+}
+
+#[test]
+#[should_panic(expected = "Subscription amount cannot be zero.")]
+fn subscription_amount_cannot_be_zero() {
+    subscribe(context(account(1)), new_state(), account(2), 0, DAY);
+}
@@ -0,0 +1,84 @@
+//! Helpers shared by the unit tests: accounts, contexts and a freshly initialized token. Actions
+//! are called directly as functions, with the sender and block time of the context.
+
+use pbc_contract_common::address::{Address, AddressType};
+use pbc_contract_common::context::{CallbackContext, ContractContext, ExecutionResult};
+use pbc_contract_common::Hash;
+
+use crate::token::{initialize, stored_amount, TashiTokenState};
+
+/// Supply minted to the owner, account 0, on initialization.
+pub const INITIAL_SUPPLY: u128 = 1_000_000;
+
+/// The account with `index` in every byte of its identifier, account 0 being the owner.
+pub fn account(index: u8) -> Address {
+    Address {
+        address_type: AddressType::Account,
+        identifier: [index; 20],
+    }
+}
+
+/// The public contract with `index` in every byte of its identifier.
+pub fn contract(index: u8) -> Address {
+    Address {
+        address_type: AddressType::PublicContract,
+        identifier: [index; 20],
+    }
+}
+
+/// The address of the token contract itself.
+pub fn token_address() -> Address {
+    contract(0xff)
+}
+
+/// A context for a call from `sender` at block time zero.
+pub fn context(sender: Address) -> ContractContext {
+    context_at(sender, 0)
+}
+
+/// A context for a call from `sender` at block production time `time`, in milliseconds.
+pub fn context_at(sender: Address, time: i64) -> ContractContext {
+    ContractContext {
+        contract_address: token_address(),
+        sender,
+        block_time: time / 1000,
+        block_production_time: time,
+        current_transaction: Hash::digest(vec![1]),
+        original_transaction: Hash::digest(vec![1]),
+    }
+}
+
+/// A context for a privileged call, from the owner, or from the contract itself when privileged
+/// actions go through the admin timelock.
+pub fn admin_context() -> ContractContext {
+    if cfg!(feature = "admin-timelock") {
+        context(token_address())
+    } else {
+        context(account(0))
+    }
+}
+
+/// The result of an interaction whose single call succeeded or failed, returning nothing.
+pub fn callback_context(success: bool) -> CallbackContext {
+    CallbackContext {
+        success,
+        results: vec![ExecutionResult {
+            succeeded: success,
+            return_data: Vec::new(),
+        }],
+    }
+}
+
+/// A token initialized by account 0 with [`INITIAL_SUPPLY`] and 8 decimals, trading enabled for
+/// everyone.
+pub fn new_state() -> TashiTokenState {
+    let mut state = initialize(
+        context(account(0)),
+        "Tashi Token".to_string(),
+        "TAS".to_string(),
+        8,
+        stored_amount(INITIAL_SUPPLY),
+    );
+    state.trading_enabled = true;
+    state
+}
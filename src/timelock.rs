@@ -0,0 +1,318 @@
+//! A delay queue for privileged actions, giving holders and exchanges visible advance notice of
+//! admin changes. With the `admin-timelock` feature, the owner can no longer mint, change fees,
+//! grant or revoke roles, hand over ownership, start a migration, loosen the mint rate limit or
+//! redenominate directly. The owner queues the change instead, and executes it once the admin timelock delay has
+//! passed. Until then the owner or governance can cancel it.
+//!
+//! A queued action is executed by calling its action with the contract itself as the sender.
+#![cfg_attr(not(feature = "admin-timelock"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "admin-timelock")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "admin-timelock")]
+use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+use crate::admin::Role;
+use crate::migration::MigrationMode;
+use crate::mint_limit::MintRateLimit;
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "admin-timelock", feature = "mintable"))]
+mod tests;
+
+/// The shortest delay of the admin timelock, one day in milliseconds.
+pub const MIN_ADMIN_TIMELOCK_DELAY: i64 = 24 * 60 * 60 * 1000;
+
+/// The delay of the admin timelock of a newly initialized token, two days in milliseconds.
+pub const DEFAULT_ADMIN_TIMELOCK_DELAY: i64 = 2 * 24 * 60 * 60 * 1000;
+
+/// A privileged action routed through the admin timelock, named after the action it executes and
+/// carrying its arguments.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AdminAction {
+    /// Executes `mint`, requires the `mintable` feature.
+    #[discriminant(0)]
+    Mint { receiver: Address, amount: u128 },
+    /// Executes `set_burn_rate`, requires the `burnable` feature.
+    #[discriminant(1)]
+    SetBurnRate { rate_bps: u16 },
+    /// Executes `set_flash_mint_config`, requires the `flash-mint` feature.
+    #[discriminant(2)]
+    SetFlashMintConfig { max_amount: u128, fee_bps: u16 },
+    /// Executes `set_reference_fees`, requires the `oracle-fees` feature.
+    #[discriminant(3)]
+    SetReferenceFees { flat_fee: u128, min_transfer: u128 },
+    /// Executes `grant_role`.
+    #[discriminant(4)]
+    GrantRole { role: Role, account: Address },
+    /// Executes `revoke_role`.
+    #[discriminant(5)]
+    RevokeRole { role: Role, account: Address },
+    /// Executes `transfer_ownership`.
+    #[discriminant(6)]
+    TransferOwnership { new_owner: Address },
+    /// Executes `configure_migration`, requires the `migration` feature.
+    #[discriminant(7)]
+    ConfigureMigration {
+        legacy_token: Address,
+        mode: MigrationMode,
+    },
+    /// Sets the delay of the admin timelock, at least [`MIN_ADMIN_TIMELOCK_DELAY`].
+    #[discriminant(8)]
+    SetTimelockDelay { delay: i64 },
+    /// Executes `set_mint_rate_limit`, requires the `mint-rate-limit` feature.
+    #[discriminant(9)]
+    SetMintRateLimit { limit: Option<MintRateLimit> },
+    /// Executes `redenominate`, requires the `redenomination` feature.
+    #[discriminant(10)]
+    Redenominate { decimals: u8 },
+    /// Executes `set_flash_mint_receiver`, requires the `flash-mint` feature.
+    #[discriminant(11)]
+    SetFlashMintReceiver { receiver: Address, allowed: bool },
+}
+
+impl AdminAction {
+    /// Checks that the action can be executed with the enabled features.
+    ///
+    /// Panics if the action requires a feature that is not enabled, or if it sets a delay shorter
+    /// than [`MIN_ADMIN_TIMELOCK_DELAY`].
+    pub fn assert_executable(&self) {
+        let (enabled, feature) = match self {
+            AdminAction::Mint { .. } => (cfg!(feature = "mintable"), "mintable"),
+            AdminAction::SetBurnRate { .. } => (cfg!(feature = "burnable"), "burnable"),
+            AdminAction::SetFlashMintConfig { .. } | AdminAction::SetFlashMintReceiver { .. } => {
+                (cfg!(feature = "flash-mint"), "flash-mint")
+            }
+            AdminAction::SetReferenceFees { .. } => (cfg!(feature = "oracle-fees"), "oracle-fees"),
+            AdminAction::ConfigureMigration { .. } => (cfg!(feature = "migration"), "migration"),
+            AdminAction::SetMintRateLimit { .. } => {
+                (cfg!(feature = "mint-rate-limit"), "mint-rate-limit")
+            }
+            AdminAction::Redenominate { .. } => {
+                (cfg!(feature = "redenomination"), "redenomination")
+            }
+            AdminAction::SetTimelockDelay { delay } => {
+                assert!(
+                    *delay >= MIN_ADMIN_TIMELOCK_DELAY,
+                    "Timelock delay: {}, is shorter than the minimum: {}",
+                    delay,
+                    MIN_ADMIN_TIMELOCK_DELAY
+                );
+                (true, "")
+            }
+            AdminAction::GrantRole { .. }
+            | AdminAction::RevokeRole { .. }
+            | AdminAction::TransferOwnership { .. } => (true, ""),
+        };
+        assert!(
+            enabled,
+            "Action requires the `{}` feature, which is not enabled.",
+            feature
+        );
+    }
+}
+
+/// A privileged action waiting in the admin timelock.
+///
+/// ### Fields:
+///
+///   * `action`: [`AdminAction`], the action.
+///   * `queued_at`: [`i64`], block production time the action was queued at, in milliseconds.
+///   * `executable_at`: [`i64`], block production time the action can be executed from, in
+///     milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QueuedAction {
+    pub action: AdminAction,
+    pub queued_at: i64,
+    pub executable_at: i64,
+}
+
+impl TashiTokenState {
+    /// Removes the queued action `action_id` from the admin timelock.
+    ///
+    /// Panics if no action with the id is queued.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `action_id`: [`u64`], the id of the queued action.
+    pub fn cancel_queued_action(&mut self, action_id: u64) {
+        self.timelock_queue
+            .remove(&action_id)
+            .expect("No queued action with this id.");
+    }
+}
+
+/// Queue `action` in the admin timelock. It can be executed once the timelock delay has passed.
+/// Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if the action cannot be executed with the enabled
+/// features.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `action`: [`AdminAction`], the action to queue.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "admin-timelock")]
+#[action(shortname = 0x9c)]
+fn queue_action(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    action: AdminAction,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "queue_action", action);
+    action.assert_executable();
+    state.timelock_queue.insert(
+        state.next_timelock_id,
+        QueuedAction {
+            action,
+            queued_at: ctx.block_production_time,
+            executable_at: ctx.block_production_time + state.admin_timelock_delay,
+        },
+    );
+    state.next_timelock_id += 1;
+    state.assert_invariants();
+
+    state
+}
+
+/// Execute the queued action `action_id` once the timelock delay has passed, calling its action
+/// with the contract itself as the sender. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner, if no action with the id is queued, if its delay has not
+/// passed, or if the executed action panics.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `action_id`: [`u64`], the id of the queued action.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "admin-timelock")]
+#[action(shortname = 0x9d)]
+fn execute_action(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    action_id: u64,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "execute_action", action_id);
+    let queued = state
+        .timelock_queue
+        .remove(&action_id)
+        .expect("No queued action with this id.");
+    assert!(
+        ctx.block_production_time >= queued.executable_at,
+        "Action can be executed from: {}",
+        queued.executable_at
+    );
+
+    let call_ctx = ContractContext {
+        contract_address: ctx.contract_address,
+        sender: ctx.contract_address,
+        block_time: ctx.block_time,
+        block_production_time: ctx.block_production_time,
+        current_transaction: ctx.current_transaction,
+        original_transaction: ctx.original_transaction,
+    };
+    let supply = state.supply();
+    state = match queued.action {
+        #[cfg(feature = "mintable")]
+        AdminAction::Mint { receiver, amount } => {
+            crate::mintable::mint(call_ctx, state, receiver, amount).0
+        }
+        #[cfg(feature = "burnable")]
+        AdminAction::SetBurnRate { rate_bps } => {
+            crate::burnable::set_burn_rate(call_ctx, state, rate_bps)
+        }
+        #[cfg(feature = "flash-mint")]
+        AdminAction::SetFlashMintConfig {
+            max_amount,
+            fee_bps,
+        } => crate::flash_mint::set_flash_mint_config(call_ctx, state, max_amount, fee_bps),
+        #[cfg(feature = "flash-mint")]
+        AdminAction::SetFlashMintReceiver { receiver, allowed } => {
+            crate::flash_mint::set_flash_mint_receiver(call_ctx, state, receiver, allowed)
+        }
+        #[cfg(feature = "oracle-fees")]
+        AdminAction::SetReferenceFees {
+            flat_fee,
+            min_transfer,
+        } => crate::oracle_fees::set_reference_fees(call_ctx, state, flat_fee, min_transfer),
+        AdminAction::GrantRole { role, account } => {
+            crate::admin::grant_role(call_ctx, state, role, account)
+        }
+        AdminAction::RevokeRole { role, account } => {
+            crate::admin::revoke_role(call_ctx, state, role, account)
+        }
+        AdminAction::TransferOwnership { new_owner } => {
+            crate::admin::transfer_ownership(call_ctx, state, new_owner)
+        }
+        #[cfg(feature = "migration")]
+        AdminAction::ConfigureMigration { legacy_token, mode } => {
+            crate::migration::configure_migration(call_ctx, state, legacy_token, mode)
+        }
+        #[cfg(feature = "mint-rate-limit")]
+        AdminAction::SetMintRateLimit { limit } => {
+            crate::mint_limit::set_mint_rate_limit(call_ctx, state, limit)
+        }
+        #[cfg(feature = "redenomination")]
+        AdminAction::Redenominate { decimals } => {
+            crate::redenomination::redenominate(call_ctx, state, decimals).0
+        }
+        AdminAction::SetTimelockDelay { delay } => {
+            state.admin_timelock_delay = delay;
+            state
+        }
+        #[allow(unreachable_patterns)]
+        _ => panic!("Action requires a feature that is not enabled."),
+    };
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Cancel the queued action `action_id`. Only the owner can call this action, governance can
+/// cancel it with a proposal.
+///
+/// Panics if the caller is not the owner or if no action with the id is queued.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `action_id`: [`u64`], the id of the queued action.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "admin-timelock")]
+#[action(shortname = 0x9e)]
+fn cancel_action(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    action_id: u64,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "cancel_action", action_id);
+    state.cancel_queued_action(action_id);
+    state.assert_invariants();
+
+    state
+}
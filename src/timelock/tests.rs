@@ -0,0 +1,71 @@
+//! Tests of the admin timelock: queued mints executed once the delay has passed, cancelled actions,
+//! and privileged actions the owner can no longer call directly.
+
+use super::{
+    cancel_action, execute_action, queue_action, AdminAction, DEFAULT_ADMIN_TIMELOCK_DELAY,
+};
+use crate::mintable::mint;
+use crate::testing::{account, context, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token where the owner queued a mint of 500 to account 1 at time 0, as action 0.
+fn queued_state() -> TashiTokenState {
+    queue_action(
+        context(account(0)),
+        new_state(),
+        AdminAction::Mint {
+            receiver: account(1),
+            amount: 500,
+        },
+    )
+}
+
+#[test]
+fn queued_mint_is_executed_after_the_delay() {
+    let (state, _) = execute_action(
+        context_at(account(0), DEFAULT_ADMIN_TIMELOCK_DELAY),
+        queued_state(),
+        0,
+    );
+
+    assert_eq!(state.balance_of(&account(1)), 500);
+    assert_eq!(state.supply(), INITIAL_SUPPLY + 500);
+    assert!(state.timelock_queue.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Action can be executed from: 172800000")]
+fn action_is_not_executed_before_the_delay() {
+    execute_action(
+        context_at(account(0), DEFAULT_ADMIN_TIMELOCK_DELAY - 1),
+        queued_state(),
+        0,
+    );
+}
+
+#[test]
+#[should_panic(expected = "No queued action with this id.")]
+fn cancelled_action_is_not_executed() {
+    let state = cancel_action(context(account(0)), queued_state(), 0);
+    execute_action(
+        context_at(account(0), DEFAULT_ADMIN_TIMELOCK_DELAY),
+        state,
+        0,
+    );
+}
+
+#[test]
+#[should_panic(expected = "This action must be queued in the admin timelock.")]
+fn owner_cannot_mint_directly() {
+    mint(context(account(0)), new_state(), account(1), 500);
+}
+
+#[test]
+#[should_panic(expected = "Timelock delay: 1, is shorter than the minimum: 86400000")]
+fn delay_below_the_minimum_cannot_be_queued() {
+    queue_action(
+        context(account(0)),
+        new_state(),
+        AdminAction::SetTimelockDelay { delay: 1 },
+    );
+}
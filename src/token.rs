@@ -0,0 +1,1347 @@
+//! Core token state, balance bookkeeping and plain transfers.
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use pbc_contract_common::Hash;
+use read_write_state_derive::ReadWriteState;
+use std::ops::Sub;
+
+use crate::admin::Role;
+use crate::allowance::TransferTarget;
+use crate::amm::AmmPool;
+use crate::auctions::Auction;
+use crate::audit::AdminLogEntry;
+use crate::balance_roots::BalanceRoot;
+use crate::bonding_curve::BondingCurve;
+use crate::budgets::Budget;
+use crate::burn_stats::BurnStats;
+use crate::channels::Channel;
+use crate::circulating::NonCirculatingKind;
+use crate::clawback::Clawback;
+use crate::compliance::ComplianceRule;
+use crate::cosigner::{CoSignedTransfer, CoSigner};
+use crate::crowdsale::Sale;
+use crate::demurrage::DemurrageConfig;
+use crate::dormancy::{DormancyPolicy, EscheatmentRecord};
+use crate::dust::DustDestination;
+use crate::emergency::DEFAULT_EMERGENCY_PAUSE_DURATION;
+use crate::emission::EmissionSchedule;
+use crate::enforcement::EnforcementRecord;
+use crate::external_compliance::ExternalCompliance;
+use crate::fee_burn::FeeBurnConfig;
+use crate::flash_mint::FlashMint;
+use crate::governance::{GovernanceConfig, Proposal, DEFAULT_GOVERNANCE_CONFIG};
+use crate::grants::MilestoneGrant;
+use crate::guards::{AddressGuards, DEFAULT_ADDRESS_GUARDS};
+use crate::history::TransferRecord;
+use crate::hooks::{run_after_transfer_hooks, run_before_transfer_hooks, Transfer};
+use crate::htlc::Htlc;
+use crate::idempotency::IdempotentTransfer;
+use crate::interest::INDEX_SCALE;
+use crate::invoices::Invoice;
+use crate::lending::{LendingConfig, LendingPool, Loan, EMPTY_LENDING_POOL};
+use crate::lockbox::SelfLock;
+use crate::memos::MemoEntry;
+use crate::migration::Migration;
+use crate::mint_limit::MintRateLimit;
+use crate::multi_token::SubToken;
+use crate::organizations::Organization;
+use crate::payroll::PayrollEntry;
+use crate::pending::PendingTransfer;
+use crate::raffles::Raffle;
+use crate::recovery::Recovery;
+use crate::redemption::RedemptionReserve;
+use crate::redenomination::Redenomination;
+use crate::referrals::BonusSource;
+use crate::reserves::{ReserveAttestation, ReserveAttester};
+use crate::reversible::{ReversiblePolicy, ReversibleTransfer};
+use crate::rewards::{RewardAccount, RewardsPool};
+use crate::scheduled_mints::ScheduledMint;
+use crate::sessions::SessionKey;
+use crate::settlement::SettlementReport;
+use crate::shards::BalanceEntries;
+#[cfg(feature = "sharded-balances")]
+use crate::shards::{ShardedBalances, DEFAULT_BALANCE_SHARDS};
+use crate::sponsorship::Sponsorship;
+use crate::state_hash::StateHash;
+use crate::streaming::Stream;
+use crate::subscriptions::Subscription;
+use crate::timelock::{QueuedAction, DEFAULT_ADMIN_TIMELOCK_DELAY};
+use crate::treasury::{Disbursement, TreasurySpendingLimit};
+use crate::vesting::{VestingGrant, VestingRevocation};
+use crate::vote_escrow::VeLock;
+
+#[cfg(test)]
+mod proptests;
+#[cfg(test)]
+mod tests;
+
+/// The denominator used for basis point calculations.
+pub const BPS_DENOMINATOR: u128 = 10_000;
+
+/// The integer type balances, allowances and the total supply are stored and exchanged in through
+/// the MPC-20 actions. [`u128`] by default, [`u64`] with the `u64-amounts` feature, which halves
+/// the size of the state and of the arguments for tokens that never need more than 19 digits.
+/// Arithmetic is always done in [`u128`].
+#[cfg(not(feature = "u64-amounts"))]
+pub type Amount = u128;
+
+/// The integer type balances, allowances and the total supply are stored and exchanged in through
+/// the MPC-20 actions. [`u128`] by default, [`u64`] with the `u64-amounts` feature, which halves
+/// the size of the state and of the arguments for tokens that never need more than 19 digits.
+/// Arithmetic is always done in [`u128`].
+#[cfg(feature = "u64-amounts")]
+pub type Amount = u64;
+
+/// The map balances are stored in. The flat [`SortedVecMap`] of the MPC-20 standard by default,
+/// [`ShardedBalances`] with the `sharded-balances` feature, which only loads the shards of the
+/// accounts a call touches.
+#[cfg(not(feature = "sharded-balances"))]
+pub type Balances = SortedVecMap<Address, Amount>;
+
+/// The map balances are stored in. The flat [`SortedVecMap`] of the MPC-20 standard by default,
+/// [`ShardedBalances`] with the `sharded-balances` feature, which only loads the shards of the
+/// accounts a call touches.
+#[cfg(feature = "sharded-balances")]
+pub type Balances = ShardedBalances;
+
+/// Converts an amount to the [`Amount`] type it is stored in.
+///
+/// Panics if `amount` does not fit in an [`Amount`].
+///
+/// ### Parameters:
+///
+///   * `amount`: [`u128`], the amount to store.
+///
+/// ### Returns:
+///
+/// The [`Amount`] to store.
+#[allow(clippy::useless_conversion, clippy::unnecessary_fallible_conversions)]
+pub fn stored_amount(amount: u128) -> Amount {
+    Amount::try_from(amount)
+        .unwrap_or_else(|_| panic!("Amount: {}, does not fit in the amount type.", amount))
+}
+
+/// Converts a stored [`Amount`] to [`u128`] for arithmetic.
+///
+/// ### Parameters:
+///
+///   * `amount`: [`Amount`], the stored amount.
+///
+/// ### Returns:
+///
+/// The [`u128`] amount.
+#[allow(clippy::useless_conversion)]
+pub fn amount_value(amount: Amount) -> u128 {
+    u128::from(amount)
+}
+
+/// Computes `amount * numerator / denominator` rounded down. Does not overflow as long as
+/// `numerator` is at most `denominator` and both fit in a [`u64`].
+///
+/// ### Parameters:
+///
+///   * `amount`: [`u128`], amount to scale.
+///   * `numerator`: [`u128`], numerator of the fraction to scale by.
+///   * `denominator`: [`u128`], denominator of the fraction to scale by.
+///
+/// ### Returns:
+///
+/// The scaled [`u128`] amount.
+pub fn mul_div(amount: u128, numerator: u128, denominator: u128) -> u128 {
+    // split the multiplication so large amounts cannot overflow
+    amount / denominator * numerator + amount % denominator * numerator / denominator
+}
+
+/// Computes `amount * numerator / denominator` rounded up, under the same conditions as
+/// [`mul_div`].
+///
+/// ### Parameters:
+///
+///   * `amount`: [`u128`], amount to scale.
+///   * `numerator`: [`u128`], numerator of the fraction to scale by.
+///   * `denominator`: [`u128`], denominator of the fraction to scale by.
+///
+/// ### Returns:
+///
+/// The scaled [`u128`] amount.
+pub fn mul_div_ceil(amount: u128, numerator: u128, denominator: u128) -> u128 {
+    amount / denominator * numerator + (amount % denominator * numerator).div_ceil(denominator)
+}
+
+/// Computes the full 256-bit product of `a` and `b`.
+///
+/// ### Parameters:
+///
+///   * `a`: [`u128`], first factor.
+///   * `b`: [`u128`], second factor.
+///
+/// ### Returns:
+///
+/// The high and low [`u128`] halves of the product.
+fn wide_mul(a: u128, b: u128) -> (u128, u128) {
+    let mask = u128::from(u64::MAX);
+    let (a_high, a_low) = (a >> 64, a & mask);
+    let (b_high, b_low) = (b >> 64, b & mask);
+    // four 64-bit partial products, none of which can overflow
+    let low_low = a_low * b_low;
+    let high_low = a_high * b_low;
+    let low_high = a_low * b_high;
+    let middle = (low_low >> 64) + (high_low & mask) + (low_high & mask);
+    let low = (low_low & mask) | (middle << 64);
+    let high = a_high * b_high + (high_low >> 64) + (low_high >> 64) + (middle >> 64);
+    (high, low)
+}
+
+/// Computes `amount * numerator / denominator` rounded down for any operands, unlike [`mul_div`],
+/// as the product is computed in 256 bits.
+///
+/// Panics if `denominator` is zero or if the result does not fit in a [`u128`].
+///
+/// ### Parameters:
+///
+///   * `amount`: [`u128`], amount to scale.
+///   * `numerator`: [`u128`], numerator of the fraction to scale by.
+///   * `denominator`: [`u128`], denominator of the fraction to scale by.
+///
+/// ### Returns:
+///
+/// The scaled [`u128`] amount.
+#[cfg_attr(
+    not(any(
+        feature = "redemption",
+        feature = "amm",
+        feature = "lending",
+        feature = "interest"
+    )),
+    allow(dead_code)
+)]
+pub fn mul_div_wide(amount: u128, numerator: u128, denominator: u128) -> u128 {
+    let (high, low) = wide_mul(amount, numerator);
+    assert!(high < denominator, "Overflow when scaling amount.");
+
+    // long division, one bit of the low half at a time
+    let mut remainder = high;
+    let mut quotient = 0;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1;
+        }
+    }
+    quotient
+}
+
+/// Computes the square root of `a * b` rounded down, the product computed in 256 bits.
+///
+/// ### Parameters:
+///
+///   * `a`: [`u128`], first factor.
+///   * `b`: [`u128`], second factor.
+///
+/// ### Returns:
+///
+/// The [`u128`] square root.
+#[cfg_attr(not(feature = "amm"), allow(dead_code))]
+pub fn sqrt_of_product(a: u128, b: u128) -> u128 {
+    let product = wide_mul(a, b);
+    let mut root: u128 = 0;
+    for bit in (0..128).rev() {
+        let candidate = root | (1 << bit);
+        if wide_mul(candidate, candidate) <= product {
+            root = candidate;
+        }
+    }
+    root
+}
+
+/// This is the state of the token which is persisted on chain.
+///
+/// The layout is the same for every feature set except `u64-amounts`, which changes the
+/// [`Amount`] type, and `sharded-balances`, which changes the type of `balances`. Other features
+/// only decide which actions and hooks are compiled in: the fields of every feature are kept,
+/// empty, in builds without it. This is a deliberate limitation, so every other build shares one
+/// state ABI that off-chain readers and snapshots decode the same way, at the cost of a few bytes
+/// per unused field in the stored state.
+///
+/// ### Fields:
+///
+///   * `total_supply`: [`Amount`], total supply of coins, in shares when balances accrue interest.
+///   * `name`: [`String`], name of the token.
+///   * `symbol`: [`String`], symbol of the token.
+///   * `balances`: [`Balances`], balances of each address, in shares when balances accrue interest.
+///   * `allowed`: [`SortedVecMap`]<[`Address`], [`SortedVecMap`]<[`Address`], [`Amount`]>, amounts each address allows other addresses to transfer from its balance.
+///   * `decimals`: [`u8`], the number of decimals the token uses.
+///   * `owner`: [`Address`], the owner of the contract.
+///   * `burn_rate_bps`: [`u16`], share of every transfer that is burned, in basis points.
+///   * `paused`: [`bool`], whether transfers are currently paused.
+///   * `streams`: [`SortedVecMap`]<[`u64`], [`Stream`]>, open payment streams by id.
+///   * `next_stream_id`: [`u64`], id assigned to the next payment stream.
+///   * `channels`: [`SortedVecMap`]<[`u64`], [`Channel`]>, open payment channels by id.
+///   * `next_channel_id`: [`u64`], id assigned to the next payment channel.
+///   * `sale`: [`Option`]<[`Sale`]>, the configured token sale, if any.
+///   * `sale_contributions`: [`SortedVecMap`]<[`Address`], [`u128`]>, unclaimed payments of each buyer in the sale.
+///   * `bonding_curve`: [`Option`]<[`BondingCurve`]>, the configured bonding curve, if any.
+///   * `htlcs`: [`SortedVecMap`]<[`Hash`], [`Htlc`]>, hash time locked transfers by hashlock.
+///   * `active_flash_mint`: [`Option`]<[`FlashMint`]>, the flash mint waiting to be settled, if any.
+///   * `flash_mint_max`: [`u128`], largest amount a single flash mint can mint, zero disables flash mints.
+///   * `flash_mint_fee_bps`: [`u16`], fee owed on top of a flash mint, in basis points.
+///   * `flash_mint_debts`: [`SortedVecMap`]<[`Address`], [`u128`]>, unrecovered flash mint repayments of each receiver.
+///   * `flash_mint_receivers`: [`Vec`]<[`Address`]>, contracts flash mints can be made to.
+///   * `roles`: [`SortedVecMap`]<[`Role`], [`Vec`]<[`Address`]>>, accounts granted each role.
+///   * `interest_index`: [`u128`], index balances are scaled by when they accrue interest, scaled by 10^18.
+///   * `proposals`: [`SortedVecMap`]<[`u64`], [`Proposal`]>, governance proposals by id.
+///   * `next_proposal_id`: [`u64`], id assigned to the next proposal.
+///   * `governance`: [`GovernanceConfig`], parameters of the governance process.
+///   * `operators`: [`SortedVecMap`]<[`Address`], [`Vec`]<[`Address`]>>, accounts each address authorized to transfer any amount on its behalf.
+///   * `max_transfer_amount`: [`Option`]<[`u128`]>, maximum amount of a single transfer, if limited.
+///   * `max_wallet_balance`: [`Option`]<[`u128`]>, maximum balance a transfer can leave its receiver with, if limited.
+///   * `limit_exempt`: [`Vec`]<[`Address`]>, accounts exempt from the transfer limits besides the owner and the contract.
+///   * `transfer_cooldown`: [`i64`], minimum delay between transfers from the same account in milliseconds, zero for none.
+///   * `last_transfer_at`: [`SortedVecMap`]<[`Address`], [`i64`]>, block production time of the last transfer from each throttled account.
+///   * `trading_enabled`: [`bool`], whether everyone can transfer, or only allowlisted accounts during the launch.
+///   * `launch_allowlist`: [`Vec`]<[`Address`]>, accounts besides the owner and the contract that can move tokens before trading is enabled.
+///   * `emission`: [`Option`]<[`EmissionSchedule`]>, the emission schedule, if any.
+///   * `address_guards`: [`AddressGuards`], destinations rejected for transfers and approvals.
+///   * `known_receivers`: [`Vec`]<[`Address`]>, contracts registered to receive transfers when unknown contracts are rejected.
+///   * `burn_allowed`: [`SortedVecMap`]<[`Address`], [`SortedVecMap`]<[`Address`], [`u128`]>>, balances allotted by an address to other addresses to burn.
+///   * `rewards`: [`RewardsPool`], the rewards pool shared by every holder.
+///   * `reward_accounts`: [`SortedVecMap`]<[`Address`], [`RewardAccount`]>, reward checkpoints and unclaimed rewards of each account.
+///   * `referrers`: [`SortedVecMap`]<[`Address`], [`Address`]>, the referrer of each referred account.
+///   * `referral_bonus_bps`: [`u16`], share of every transfer paid to the referrer of the sender, in basis points.
+///   * `referral_source`: [`BonusSource`], where referral bonuses are paid from.
+///   * `referral_pool`: [`u128`], tokens set aside for referral bonuses.
+///   * `migration`: [`Option`]<[`Migration`]>, the migration from a legacy token, if any.
+///   * `migration_claims`: [`SortedVecMap`]<[`Address`], [`u128`]>, snapshot balances claimed by each migrated holder.
+///   * `frozen_accounts`: [`Vec`]<[`Address`]>, accounts frozen by an enforcer.
+///   * `enforcement_records`: [`Vec`]<[`EnforcementRecord`]>, append-only audit records of every forced transfer.
+///   * `compliance_rules`: [`SortedVecMap`]<[`String`], [`ComplianceRule`]>, enabled compliance rules by name.
+///   * `jurisdictions`: [`SortedVecMap`]<[`Address`], [`u16`]>, jurisdiction code of each registered account.
+///   * `admin_log`: [`Vec`]<[`AdminLogEntry`]>, the latest privileged actions, oldest first.
+///   * `admin_actions_recorded`: [`u64`], number of privileged actions recorded since deployment, including dropped entries.
+///   * `deposits`: [`SortedVecMap`]<[`Address`], [`SortedVecMap`]<[`Address`], [`u128`]>>, amounts of other MPC-20 tokens held for each depositor, by token.
+///   * `supply_observers`: [`Vec`]<[`Address`]>, contracts notified of every supply change.
+///   * `dust_threshold`: [`u128`], balances below this amount can be swept, zero disables sweeping.
+///   * `dust_destination`: [`DustDestination`], where swept dust goes.
+///   * `min_transfer_amount`: [`u128`], minimum amount of a single transfer, zero for none.
+///   * `payroll`: [`SortedVecMap`]<[`Address`], [`PayrollEntry`]>, payroll recipients and their next payment.
+///   * `payroll_pool`: [`u128`], tokens set aside for the payroll.
+///   * `subscriptions`: [`SortedVecMap`]<[`Address`], [`SortedVecMap`]<[`Address`], [`Subscription`]>>, subscriptions of each subscriber, by merchant.
+///   * `invoices`: [`SortedVecMap`]<[`u64`], [`Invoice`]>, invoices by id.
+///   * `next_invoice_id`: [`u64`], id of the next invoice.
+///   * `pending_conditional_transfers`: [`u128`], tokens of conditional transfers waiting for their condition.
+///   * `price_oracle`: [`Option`]<[`Address`]>, account pushing the reference price.
+///   * `reference_price`: [`u128`], reference units one display unit of the token is worth, zero until a price is pushed.
+///   * `reference_price_updated_at`: [`i64`], block production time the reference price was pushed at, in milliseconds.
+///   * `reference_flat_fee`: [`u128`], fee charged on every transfer, in reference units.
+///   * `reference_min_transfer`: [`u128`], minimum amount of a single transfer, in reference units.
+///   * `redemption_reserve`: [`Option`]<[`RedemptionReserve`]>, the reserve tokens are redeemed against, if configured.
+///   * `sponsors`: [`SortedVecMap`]<[`Address`], [`Sponsorship`]>, registered sponsors and what they relay.
+///   * `nonces`: [`SortedVecMap`]<[`Address`], [`u64`]>, next nonce of each account, consumed by signed messages.
+///   * `auctions`: [`SortedVecMap`]<[`u64`], [`Auction`]>, sealed-bid auctions by id.
+///   * `next_auction_id`: [`u64`], id of the next auction.
+///   * `raffles`: [`SortedVecMap`]<[`u64`], [`Raffle`]>, raffles by id.
+///   * `next_raffle_id`: [`u64`], id of the next raffle.
+///   * `session_keys`: [`SortedVecMap`]<[`Address`], [`SortedVecMap`]<[`Address`], [`SessionKey`]>>, session keys of each account, by key.
+///   * `ve_locks`: [`SortedVecMap`]<[`Address`], [`VeLock`]>, vote-escrow lock of each account.
+///   * `recoveries`: [`SortedVecMap`]<[`Address`], [`Recovery`]>, guardians of each account and their recovery approvals.
+///   * `dormancy_policy`: [`Option`]<[`DormancyPolicy`]>, when accounts become dormant, unset if accounts are never swept.
+///   * `last_active_at`: [`SortedVecMap`]<[`Address`], [`i64`]>, block production time of the last activity of each account under the dormancy policy.
+///   * `dormant_accounts`: [`SortedVecMap`]<[`Address`], [`i64`]>, block production time each flagged account can be swept from.
+///   * `escheated`: [`SortedVecMap`]<[`Address`], [`u128`]>, tokens swept from each account to the recovery pool.
+///   * `escheatment_records`: [`Vec`]<[`EscheatmentRecord`]>, every step of the dormancy process.
+///   * `clawbacks`: [`SortedVecMap`]<[`u64`], [`Clawback`]>, every clawback by id.
+///   * `next_clawback_id`: [`u64`], id of the next clawback.
+///   * `emergency_paused_until`: [`i64`], block production time the emergency pause ends at, in milliseconds.
+///   * `emergency_pause_duration`: [`i64`], how long an emergency pause lasts unless extended, in milliseconds.
+///   * `timelock_queue`: [`SortedVecMap`]<[`u64`], [`QueuedAction`]>, privileged actions waiting in the admin timelock, by id.
+///   * `next_timelock_id`: [`u64`], id of the next queued action.
+///   * `admin_timelock_delay`: [`i64`], delay before a queued action can be executed, in milliseconds.
+///   * `mint_rate_limit`: [`Option`]<[`MintRateLimit`]>, largest amount `mint` can mint per epoch, unset if unlimited.
+///   * `mint_epoch_start`: [`i64`], block production time the current mint epoch started at, in milliseconds.
+///   * `minted_in_epoch`: [`u128`], amount minted in the current mint epoch.
+///   * `scheduled_mints`: [`SortedVecMap`]<[`u64`], [`ScheduledMint`]>, mints waiting for their time, by id.
+///   * `next_scheduled_mint_id`: [`u64`], id of the next scheduled mint.
+///   * `burn_stats`: [`BurnStats`], cumulative statistics of the burned tokens.
+///   * `non_circulating`: [`SortedVecMap`]<[`Address`], [`NonCirculatingKind`]>, accounts whose balances do not count towards the circulating supply.
+///   * `treasury_spending_limit`: [`Option`]<[`TreasurySpendingLimit`]>, largest amount treasurers can pay out per period, unset if unlimited.
+///   * `treasury_period_start`: [`i64`], block production time the current spending period started at, in milliseconds.
+///   * `treasury_spent_in_period`: [`u128`], amount paid out by treasurers in the current spending period.
+///   * `treasury_disbursements`: [`Vec`]<[`Disbursement`]>, every payment out of the treasury.
+///   * `fee_burn`: [`Option`]<[`FeeBurnConfig`]>, how collected fees are burned, unset if they are not.
+///   * `collected_fees`: [`u128`], transfer fees credited to the treasury since they were last burned.
+///   * `charity`: [`Option`]<[`Address`]>, account receiving the round-ups of transfers, unset if they are not donated.
+///   * `round_up_precisions`: [`SortedVecMap`]<[`Address`], [`u128`]>, precision the outgoing transfers of each opted in account are rounded up to.
+///   * `round_up_donated`: [`SortedVecMap`]<[`Address`], [`u128`]>, total round-ups donated by each account.
+///   * `demurrage`: [`Option`]<[`DemurrageConfig`]>, yearly decay of balances and the pool it goes to, unset if balances do not decay.
+///   * `demurrage_accrued_at`: [`i64`], block production time balances last decayed at, in milliseconds.
+///   * `burns_by_reason`: [`SortedVecMap`]<[`u32`], [`BurnStats`]>, statistics of the tokens burned with each reason code.
+///   * `sub_tokens`: [`SortedVecMap`]<[`u64`], [`SubToken`]>, tokens of the contract other than the main token, by id.
+///   * `next_token_id`: [`u64`], id of the next token created.
+///   * `underlying_token`: [`Option`]<[`Address`]>, MPC-20 token this token wraps, if set.
+///   * `wrap_reserve`: [`u128`], amount of the underlying token held for wrapped tokens, including unwraps still being sent.
+///   * `wrapped_supply`: [`u128`], amount of wrapped tokens minted by wraps and not unwrapped.
+///   * `amm_pool`: [`Option`]<[`AmmPool`]>, the liquidity pool against the native coin, if set up.
+///   * `memo_log`: [`Vec`]<[`MemoEntry`]>, the latest transfers made with a memo.
+///   * `transfer_history`: [`Vec`]<[`TransferRecord`]>, ring buffer of the latest transfers.
+///   * `transfer_history_head`: [`u32`], position in `transfer_history` the next transfer is written to.
+///   * `nonces_on_transfers`: [`bool`], whether transfers bump the nonce of their sending account.
+///   * `reserve_attester`: [`Option`]<[`ReserveAttester`]>, attester of the reserve backing the token, if set.
+///   * `reserve_attestation`: [`Option`]<[`ReserveAttestation`]>, the latest reserve attestation.
+///   * `frozen_amounts`: [`SortedVecMap`]<[`Address`], [`u128`]>, part of the balance of each account frozen by a compliance officer.
+///   * `label_fee`: [`u128`], fee paid to the treasury to claim a label.
+///   * `labels`: [`SortedVecMap`]<[`String`], [`Address`]>, account holding each label.
+///   * `account_labels`: [`SortedVecMap`]<[`Address`], [`String`]>, label held by each account.
+///   * `acceptance_required`: [`Vec`]<[`Address`]>, spenders whose allowances must be accepted.
+///   * `allowance_offers`: [`SortedVecMap`]<[`Address`], [`SortedVecMap`]<[`Address`], [`u128`]>>, allowances waiting for acceptance, by spender and owner.
+///   * `pending_transfers`: [`SortedVecMap`]<[`u64`], [`PendingTransfer`]>, transfers held until claimed, by id.
+///   * `next_pending_transfer_id`: [`u64`], id of the next pending transfer.
+///   * `reversible_policies`: [`SortedVecMap`]<[`Address`], [`ReversiblePolicy`]>, policy of each account holding its large transfers.
+///   * `reversible_transfers`: [`SortedVecMap`]<[`u64`], [`ReversibleTransfer`]>, held transfers and their outcome, by id.
+///   * `next_reversible_transfer_id`: [`u64`], id of the next held transfer.
+///   * `external_compliance`: [`Option`]<[`ExternalCompliance`]>, contract approving large transfers, if set.
+///   * `soulbound`: [`bool`], whether the tokens of every account are non-transferable.
+///   * `soulbound_accounts`: [`Vec`]<[`Address`]>, accounts whose tokens are non-transferable.
+///   * `soulbound_migrations`: [`SortedVecMap`]<[`Address`], [`Address`]>, address each account is approved to migrate its balance to.
+///   * `balance_roots`: [`Vec`]<[`BalanceRoot`]>, the latest merkle roots of the balances, oldest first.
+///   * `vesting_grants`: [`SortedVecMap`]<[`u64`], [`VestingGrant`]>, vesting grants not fully claimed, by id.
+///   * `next_vesting_grant_id`: [`u64`], id of the next vesting grant.
+///   * `vesting_revocations`: [`Vec`]<[`VestingRevocation`]>, append-only records of every revoked grant.
+///   * `milestone_grants`: [`SortedVecMap`]<[`u64`], [`MilestoneGrant`]>, milestone grants not fully released, by id.
+///   * `next_milestone_grant_id`: [`u64`], id of the next milestone grant.
+///   * `self_locks`: [`SortedVecMap`]<[`Address`], [`SelfLock`]>, part of the balance each holder locked until a chosen time.
+///   * `cosigners`: [`SortedVecMap`]<[`Address`], [`CoSigner`]>, the co-signer of each account requiring one.
+///   * `cosigned_transfers`: [`SortedVecMap`]<[`u64`], [`CoSignedTransfer`]>, transfers waiting for co-signer confirmation, by id.
+///   * `next_cosigned_transfer_id`: [`u64`], id of the next co-signed transfer.
+///   * `budgets`: [`SortedVecMap`]<[`u64`], [`Budget`]>, budget sub-accounts, by id.
+///   * `next_budget_id`: [`u64`], id of the next budget.
+///   * `organizations`: [`SortedVecMap`]<[`u64`], [`Organization`]>, organization accounts, by id.
+///   * `next_organization_id`: [`u64`], id of the next organization.
+///   * `lending_config`: [`Option`]<[`LendingConfig`]>, interest rate and loan-to-value ratios of the lending pool, unset until the owner configures lending.
+///   * `lending_pool`: [`LendingPool`], totals and borrow index of the lending pool.
+///   * `lending_shares`: [`SortedVecMap`]<[`Address`], [`u128`]>, shares of the lending pool of each depositor.
+///   * `loans`: [`SortedVecMap`]<[`Address`], [`Loan`]>, collateral and debt of each borrower.
+///   * `redenominations`: [`Vec`]<[`Redenomination`]>, append-only records of every redenomination.
+///   * `settlement_log`: [`Vec`]<[`SettlementReport`]>, reports of the latest settlement batches.
+///   * `idempotency_keys`: [`SortedVecMap`]<[`Address`], [`Vec`]<[`IdempotentTransfer`]>>, the latest transfers each sender made with an idempotency key.
+///   * `transfer_count`: [`u64`], number of transfers since initialization.
+///   * `transfer_volume`: [`u128`], amount transferred since initialization.
+///   * `sender_sketch`: [`Vec`]<[`u8`]>, registers of the sketch estimating the number of distinct senders, empty before the first transfer.
+///   * `action_counts`: [`SortedVecMap`]<[`String`], [`u64`]>, calls of each counted action, by name.
+///   * `state_hashes`: [`Vec`]<[`StateHash`]>, the latest hashes of the state, oldest first.
+///   * `_padding`: [[`u16`]; `5`], padding bytes to align the struct.
+#[state]
+#[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TashiTokenState {
+    pub total_supply: Amount,
+    pub name: String,
+    pub symbol: String,
+    #[cfg_attr(
+        all(feature = "serde", not(feature = "sharded-balances")),
+        serde(with = "crate::snapshot::sorted_vec_map")
+    )]
+    pub balances: Balances,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::snapshot::nested_sorted_vec_map")
+    )]
+    pub allowed: SortedVecMap<Address, SortedVecMap<Address, Amount>>,
+    pub decimals: u8,
+    pub owner: Address,
+    pub burn_rate_bps: u16,
+    pub paused: bool,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub streams: SortedVecMap<u64, Stream>,
+    pub next_stream_id: u64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub channels: SortedVecMap<u64, Channel>,
+    pub next_channel_id: u64,
+    pub sale: Option<Sale>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub sale_contributions: SortedVecMap<Address, u128>,
+    pub bonding_curve: Option<BondingCurve>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub htlcs: SortedVecMap<Hash, Htlc>,
+    pub active_flash_mint: Option<FlashMint>,
+    pub flash_mint_max: u128,
+    pub flash_mint_fee_bps: u16,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub flash_mint_debts: SortedVecMap<Address, u128>,
+    pub flash_mint_receivers: Vec<Address>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub roles: SortedVecMap<Role, Vec<Address>>,
+    pub interest_index: u128,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub proposals: SortedVecMap<u64, Proposal>,
+    pub next_proposal_id: u64,
+    pub governance: GovernanceConfig,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub operators: SortedVecMap<Address, Vec<Address>>,
+    pub max_transfer_amount: Option<u128>,
+    pub max_wallet_balance: Option<u128>,
+    pub limit_exempt: Vec<Address>,
+    pub transfer_cooldown: i64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub last_transfer_at: SortedVecMap<Address, i64>,
+    pub trading_enabled: bool,
+    pub launch_allowlist: Vec<Address>,
+    pub emission: Option<EmissionSchedule>,
+    pub address_guards: AddressGuards,
+    pub known_receivers: Vec<Address>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::snapshot::nested_sorted_vec_map")
+    )]
+    pub burn_allowed: SortedVecMap<Address, SortedVecMap<Address, u128>>,
+    pub rewards: RewardsPool,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub reward_accounts: SortedVecMap<Address, RewardAccount>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub referrers: SortedVecMap<Address, Address>,
+    pub referral_bonus_bps: u16,
+    pub referral_source: BonusSource,
+    pub referral_pool: u128,
+    pub migration: Option<Migration>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub migration_claims: SortedVecMap<Address, u128>,
+    pub frozen_accounts: Vec<Address>,
+    pub enforcement_records: Vec<EnforcementRecord>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub compliance_rules: SortedVecMap<String, ComplianceRule>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub jurisdictions: SortedVecMap<Address, u16>,
+    pub admin_log: Vec<AdminLogEntry>,
+    pub admin_actions_recorded: u64,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::snapshot::nested_sorted_vec_map")
+    )]
+    pub deposits: SortedVecMap<Address, SortedVecMap<Address, u128>>,
+    pub supply_observers: Vec<Address>,
+    pub dust_threshold: u128,
+    pub dust_destination: DustDestination,
+    pub min_transfer_amount: u128,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub payroll: SortedVecMap<Address, PayrollEntry>,
+    pub payroll_pool: u128,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::snapshot::nested_sorted_vec_map")
+    )]
+    pub subscriptions: SortedVecMap<Address, SortedVecMap<Address, Subscription>>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub invoices: SortedVecMap<u64, Invoice>,
+    pub next_invoice_id: u64,
+    pub pending_conditional_transfers: u128,
+    pub price_oracle: Option<Address>,
+    pub reference_price: u128,
+    pub reference_price_updated_at: i64,
+    pub reference_flat_fee: u128,
+    pub reference_min_transfer: u128,
+    pub redemption_reserve: Option<RedemptionReserve>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub sponsors: SortedVecMap<Address, Sponsorship>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub nonces: SortedVecMap<Address, u64>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub auctions: SortedVecMap<u64, Auction>,
+    pub next_auction_id: u64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub raffles: SortedVecMap<u64, Raffle>,
+    pub next_raffle_id: u64,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::snapshot::nested_sorted_vec_map")
+    )]
+    pub session_keys: SortedVecMap<Address, SortedVecMap<Address, SessionKey>>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub ve_locks: SortedVecMap<Address, VeLock>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub recoveries: SortedVecMap<Address, Recovery>,
+    pub dormancy_policy: Option<DormancyPolicy>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub last_active_at: SortedVecMap<Address, i64>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub dormant_accounts: SortedVecMap<Address, i64>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub escheated: SortedVecMap<Address, u128>,
+    pub escheatment_records: Vec<EscheatmentRecord>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub clawbacks: SortedVecMap<u64, Clawback>,
+    pub next_clawback_id: u64,
+    pub emergency_paused_until: i64,
+    pub emergency_pause_duration: i64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub timelock_queue: SortedVecMap<u64, QueuedAction>,
+    pub next_timelock_id: u64,
+    pub admin_timelock_delay: i64,
+    pub mint_rate_limit: Option<MintRateLimit>,
+    pub mint_epoch_start: i64,
+    pub minted_in_epoch: u128,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub scheduled_mints: SortedVecMap<u64, ScheduledMint>,
+    pub next_scheduled_mint_id: u64,
+    pub burn_stats: BurnStats,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub non_circulating: SortedVecMap<Address, NonCirculatingKind>,
+    pub treasury_spending_limit: Option<TreasurySpendingLimit>,
+    pub treasury_period_start: i64,
+    pub treasury_spent_in_period: u128,
+    pub treasury_disbursements: Vec<Disbursement>,
+    pub fee_burn: Option<FeeBurnConfig>,
+    pub collected_fees: u128,
+    pub charity: Option<Address>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub round_up_precisions: SortedVecMap<Address, u128>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub round_up_donated: SortedVecMap<Address, u128>,
+    pub demurrage: Option<DemurrageConfig>,
+    pub demurrage_accrued_at: i64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub burns_by_reason: SortedVecMap<u32, BurnStats>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub sub_tokens: SortedVecMap<u64, SubToken>,
+    pub next_token_id: u64,
+    pub underlying_token: Option<Address>,
+    pub wrap_reserve: u128,
+    pub wrapped_supply: u128,
+    pub amm_pool: Option<AmmPool>,
+    pub memo_log: Vec<MemoEntry>,
+    pub transfer_history: Vec<TransferRecord>,
+    pub transfer_history_head: u32,
+    pub nonces_on_transfers: bool,
+    pub reserve_attester: Option<ReserveAttester>,
+    pub reserve_attestation: Option<ReserveAttestation>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub frozen_amounts: SortedVecMap<Address, u128>,
+    pub label_fee: u128,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub labels: SortedVecMap<String, Address>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub account_labels: SortedVecMap<Address, String>,
+    pub acceptance_required: Vec<Address>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::snapshot::nested_sorted_vec_map")
+    )]
+    pub allowance_offers: SortedVecMap<Address, SortedVecMap<Address, u128>>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub pending_transfers: SortedVecMap<u64, PendingTransfer>,
+    pub next_pending_transfer_id: u64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub reversible_policies: SortedVecMap<Address, ReversiblePolicy>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub reversible_transfers: SortedVecMap<u64, ReversibleTransfer>,
+    pub next_reversible_transfer_id: u64,
+    pub external_compliance: Option<ExternalCompliance>,
+    pub soulbound: bool,
+    pub soulbound_accounts: Vec<Address>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub soulbound_migrations: SortedVecMap<Address, Address>,
+    pub balance_roots: Vec<BalanceRoot>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub vesting_grants: SortedVecMap<u64, VestingGrant>,
+    pub next_vesting_grant_id: u64,
+    pub vesting_revocations: Vec<VestingRevocation>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub milestone_grants: SortedVecMap<u64, MilestoneGrant>,
+    pub next_milestone_grant_id: u64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub self_locks: SortedVecMap<Address, SelfLock>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub cosigners: SortedVecMap<Address, CoSigner>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub cosigned_transfers: SortedVecMap<u64, CoSignedTransfer>,
+    pub next_cosigned_transfer_id: u64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub budgets: SortedVecMap<u64, Budget>,
+    pub next_budget_id: u64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub organizations: SortedVecMap<u64, Organization>,
+    pub next_organization_id: u64,
+    pub lending_config: Option<LendingConfig>,
+    pub lending_pool: LendingPool,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub lending_shares: SortedVecMap<Address, u128>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub loans: SortedVecMap<Address, Loan>,
+    pub redenominations: Vec<Redenomination>,
+    pub settlement_log: Vec<SettlementReport>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub idempotency_keys: SortedVecMap<Address, Vec<IdempotentTransfer>>,
+    pub transfer_count: u64,
+    pub transfer_volume: u128,
+    pub sender_sketch: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::sorted_vec_map"))]
+    pub action_counts: SortedVecMap<String, u64>,
+    pub state_hashes: Vec<StateHash>,
+    _padding: [u8; 10],
+}
+
+/// A map that can store balances.
+pub trait BalanceMap<K, V>
+where
+    // K is a type that implements the Ord trait
+    K: Ord,
+{
+    fn insert_balance(&mut self, key: K, amount: V);
+}
+
+/// the type SortedVecMap<Address, V> should implement the trait BalanceMap<Address, V>, where V is a type that implements the trait Sub<V, Output = V>
+impl<V: Sub<V, Output = V> + PartialEq + Copy> BalanceMap<Address, V> for SortedVecMap<Address, V> {
+    #[allow(clippy::eq_op)]
+    fn insert_balance(&mut self, key: Address, amount: V) {
+        let zero = amount - amount; // can handle different zeroes for different types
+        if amount == zero {
+            self.remove(&key); // remove address with 0 balance
+        } else {
+            self.insert(key, amount); // update or insert address with new value
+        }
+    }
+}
+
+// implement struct specific functions
+impl TashiTokenState {
+    /// Gets the balance of the specified address.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account to query balance of
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount owned by the account.
+    pub fn balance_of(&self, owner: &Address) -> u128 {
+        self.amount_of_shares(self.shares_of(owner))
+    }
+
+    /// Gets the raw entry of the specified address in `balances`. This equals its balance unless
+    /// balances accrue interest.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account to query shares of
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount of shares owned by the account.
+    pub fn shares_of(&self, owner: &Address) -> u128 {
+        self.balances.entry(owner).map_or(0, amount_value)
+    }
+
+    /// Gets the total supply of the token. This equals `total_supply` unless balances accrue
+    /// interest.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount of tokens in existence.
+    pub fn supply(&self) -> u128 {
+        self.amount_of_shares(amount_value(self.total_supply))
+    }
+
+    /// Adds `amount` to the balance of `account`.
+    ///
+    /// Panics if adding `amount` causes an overflow.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account to credit.
+    ///   * `amount`: [`u128`], amount to add.
+    pub fn credit(&mut self, account: Address, amount: u128) {
+        let new_shares = self
+            .shares_of(&account)
+            .checked_add(self.shares_rounded_down(amount)) // add amount to balance
+            .expect("Overflow when adding to balance.");
+        self.update_reward_shares(account, new_shares);
+        self.balances
+            .insert_balance(account, stored_amount(new_shares)); // update balance
+    }
+
+    /// Subtracts `amount` from the balance of `account`.
+    ///
+    /// Panics if there is insufficient balance in `account`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account to debit.
+    ///   * `amount`: [`u128`], amount to subtract.
+    pub fn debit(&mut self, account: Address, amount: u128) {
+        let new_shares = self
+            .shares_of(&account)
+            .checked_sub(self.shares_rounded_up(amount)) // subtract amount from balance
+            .unwrap_or_else(|| {
+                // panic if balance < amount
+                panic!(
+                    "Insufficient balance: {}, minimum required balance: {}",
+                    self.balance_of(&account),
+                    amount
+                )
+            });
+        self.update_reward_shares(account, new_shares);
+        self.balances
+            .insert_balance(account, stored_amount(new_shares)); // update balance
+    }
+
+    /// Moves `amount` from the balance of `account` into the custody of the contract, to escrow it
+    /// or to pay for an action. Checks that `account` can spend the amount with
+    /// [`TashiTokenState::assert_can_spend`], as no transfer hooks run. The demurrage due is
+    /// applied first, so the amount is taken at the current index.
+    ///
+    /// Panics if `account` cannot spend `amount` or if there is insufficient balance in `account`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context.
+    ///   * `account`: [`Address`], account to debit.
+    ///   * `amount`: [`u128`], amount to escrow.
+    #[cfg_attr(
+        not(any(
+            feature = "streaming",
+            feature = "channels",
+            feature = "crowdsale",
+            feature = "htlc",
+            feature = "conditional-transfers",
+            feature = "raffles",
+            feature = "milestone-grants",
+            feature = "vesting",
+            feature = "pending-transfers",
+            feature = "auctions",
+            feature = "lending",
+            feature = "organizations",
+            feature = "vote-escrow",
+            feature = "amm",
+            feature = "payroll",
+            feature = "governance",
+            feature = "labels",
+            feature = "rewards",
+            feature = "referrals"
+        )),
+        allow(dead_code)
+    )]
+    pub fn escrow(&mut self, ctx: &ContractContext, account: Address, amount: u128) {
+        self.accrue_demurrage_until(ctx.block_production_time);
+        self.assert_can_spend(ctx, &account, amount);
+        self.debit(account, amount);
+    }
+
+    /// Pays `amount` out of the custody of the contract to `to`, releasing funds `from` escrowed
+    /// for it. Checks that `to` can receive the funds from `from` with
+    /// [`TashiTokenState::assert_can_receive`], as no transfer hooks run. Refunds of escrowed
+    /// funds to the account that escrowed them are released with [`TashiTokenState::release`].
+    ///
+    /// Panics if `to` cannot receive `amount` from `from`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context.
+    ///   * `from`: [`Address`], account that escrowed the funds, the contract itself for funds
+    ///     pooled from several accounts.
+    ///   * `to`: [`Address`], account to pay.
+    ///   * `amount`: [`u128`], amount to pay out.
+    #[cfg_attr(
+        not(any(
+            feature = "streaming",
+            feature = "channels",
+            feature = "htlc",
+            feature = "raffles",
+            feature = "milestone-grants",
+            feature = "vesting",
+            feature = "auctions",
+            feature = "organizations",
+            feature = "payroll"
+        )),
+        allow(dead_code)
+    )]
+    pub fn payout(&mut self, ctx: &ContractContext, from: Address, to: Address, amount: u128) {
+        self.release(ctx, to, amount);
+        self.assert_can_receive(ctx, &Transfer::new(from, to, amount, None));
+    }
+
+    /// Moves `amount` out of the custody of the contract to `account`, such as a refund of funds
+    /// it escrowed. The demurrage due is applied first, so escrowed funds do not decay. No
+    /// policies are checked, so funds can always return to the account that escrowed them.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context.
+    ///   * `account`: [`Address`], account to credit.
+    ///   * `amount`: [`u128`], amount to release.
+    #[cfg_attr(
+        not(any(
+            feature = "streaming",
+            feature = "channels",
+            feature = "htlc",
+            feature = "conditional-transfers",
+            feature = "raffles",
+            feature = "milestone-grants",
+            feature = "vesting",
+            feature = "pending-transfers",
+            feature = "auctions",
+            feature = "crowdsale",
+            feature = "lending",
+            feature = "organizations",
+            feature = "vote-escrow",
+            feature = "amm",
+            feature = "payroll",
+            feature = "governance",
+            feature = "reversible-transfers"
+        )),
+        allow(dead_code)
+    )]
+    pub fn release(&mut self, ctx: &ContractContext, account: Address, amount: u128) {
+        self.accrue_demurrage_until(ctx.block_production_time);
+        self.credit(account, amount);
+    }
+
+    /// Creates `amount` new tokens owned by `account`, increasing the total supply.
+    ///
+    /// Panics if adding `amount` to the total supply causes an overflow.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account to mint to.
+    ///   * `amount`: [`u128`], amount to mint.
+    #[cfg_attr(
+        not(any(
+            feature = "mintable",
+            feature = "bonding-curve",
+            feature = "flash-mint",
+            feature = "emission",
+            feature = "referrals",
+            feature = "migration",
+            feature = "scheduled-mints",
+            feature = "demurrage",
+            feature = "wrapper",
+            feature = "redemption"
+        )),
+        allow(dead_code)
+    )]
+    pub fn mint_balance(&mut self, account: Address, amount: u128) {
+        let total_supply = amount_value(self.total_supply)
+            .checked_add(self.shares_rounded_down(amount))
+            .expect("Overflow when adding to total supply.");
+        self.total_supply = stored_amount(total_supply);
+        self.credit(account, amount);
+    }
+
+    /// Destroys `amount` tokens held by `account`, reducing the total supply.
+    ///
+    /// Panics if there is insufficient balance in `account`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account to burn from.
+    ///   * `amount`: [`u128`], amount to burn.
+    ///   * `now`: [`i64`], the current block production time.
+    #[cfg_attr(
+        not(any(
+            feature = "burnable",
+            feature = "bonding-curve",
+            feature = "flash-mint",
+            feature = "dust-sweep",
+            feature = "redemption",
+            feature = "fee-burn",
+            feature = "wrapper"
+        )),
+        allow(dead_code)
+    )]
+    pub fn burn_balance(&mut self, account: Address, amount: u128, now: i64) {
+        self.accrue_demurrage_until(now); // burn at the current index
+        self.debit(account, amount);
+        self.decrease_supply(amount, now); // the burned balance was part of the supply
+    }
+
+    /// Reduces the total supply by `amount` that has already been taken out of circulation, and
+    /// counts it in the burn statistics.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `amount`: [`u128`], amount to remove from the supply.
+    ///   * `now`: [`i64`], the current block production time.
+    #[cfg_attr(
+        not(any(
+            feature = "burnable",
+            feature = "bonding-curve",
+            feature = "flash-mint",
+            feature = "dust-sweep",
+            feature = "redemption",
+            feature = "raffles"
+        )),
+        allow(dead_code)
+    )]
+    pub fn decrease_supply(&mut self, amount: u128, now: i64) {
+        self.total_supply =
+            stored_amount(amount_value(self.total_supply) - self.shares_rounded_up(amount));
+        self.burn_stats.record(amount, now);
+    }
+
+    /// Executes `transfer`, running every registered transfer hook around the balance updates.
+    /// The full `amount` is taken from the source of the transfer and `received` is credited to the
+    /// receiver.
+    ///
+    /// Panics if there is insufficient balance or allowance in the source of the transfer, if
+    /// adding to the receiver balance causes an overflow, or if a hook rejects the transfer.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `ctx`: [`ContractContext`], current context for the action.
+    ///   * `transfer`: [`Transfer`], the transfer to execute.
+    pub fn execute_transfer(&mut self, ctx: &ContractContext, mut transfer: Transfer) {
+        run_before_transfer_hooks(ctx, self, &mut transfer);
+
+        match transfer.spender {
+            None => self.debit(transfer.from, transfer.amount), // update sender balance
+            Some(operator) if self.is_operator(&transfer.from, &operator) => {
+                self.debit(transfer.from, transfer.amount) // operators spend the sender balance
+            }
+            Some(spender) => {
+                self.spend_allowance(transfer.from, spender, transfer.amount); // update spender allowance
+                self.debit(transfer.from, transfer.amount) // update sender balance
+            }
+        }
+        self.credit(transfer.to, transfer.received); // update receiver balance
+
+        run_after_transfer_hooks(ctx, self, &transfer);
+    }
+}
+
+/// Initial function to bootstrap the contract's state.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`] - the contract context containing sender and chain information.
+///   * `name`: [`String`], name of the token.
+///   * `symbol`: [`String`], symbol of the token.
+///   * `decimals`: [`u8`], the number of decimals the token uses.
+///   * `total_supply`: [`Amount`], total supply of the token, minted to the caller.
+///
+/// ### Returns
+///
+/// The new [`TokenState`] state.
+#[init]
+pub(crate) fn initialize(
+    ctx: ContractContext,
+    name: String,
+    symbol: String,
+    decimals: u8,
+    total_supply: Amount,
+) -> TashiTokenState {
+    #[cfg(not(feature = "sharded-balances"))]
+    let mut balances: Balances = SortedVecMap::new();
+    #[cfg(feature = "sharded-balances")]
+    let mut balances: Balances = ShardedBalances::new(DEFAULT_BALANCE_SHARDS);
+    balances.insert_balance(ctx.sender, total_supply);
+    let state = TashiTokenState {
+        total_supply,
+        name,
+        symbol,
+        balances,
+        allowed: SortedVecMap::new(),
+        decimals,
+        owner: ctx.sender,
+        burn_rate_bps: 0,
+        paused: false,
+        streams: SortedVecMap::new(),
+        next_stream_id: 0,
+        channels: SortedVecMap::new(),
+        next_channel_id: 0,
+        sale: None,
+        sale_contributions: SortedVecMap::new(),
+        bonding_curve: None,
+        htlcs: SortedVecMap::new(),
+        active_flash_mint: None,
+        flash_mint_max: 0,
+        flash_mint_fee_bps: 0,
+        flash_mint_debts: SortedVecMap::new(),
+        flash_mint_receivers: Vec::new(),
+        roles: SortedVecMap::new(),
+        interest_index: INDEX_SCALE,
+        proposals: SortedVecMap::new(),
+        next_proposal_id: 0,
+        governance: DEFAULT_GOVERNANCE_CONFIG,
+        operators: SortedVecMap::new(),
+        max_transfer_amount: None,
+        max_wallet_balance: None,
+        limit_exempt: Vec::new(),
+        transfer_cooldown: 0,
+        last_transfer_at: SortedVecMap::new(),
+        trading_enabled: false,
+        launch_allowlist: Vec::new(),
+        emission: None,
+        address_guards: DEFAULT_ADDRESS_GUARDS,
+        known_receivers: Vec::new(),
+        burn_allowed: SortedVecMap::new(),
+        rewards: RewardsPool::new(total_supply),
+        reward_accounts: SortedVecMap::new(),
+        referrers: SortedVecMap::new(),
+        referral_bonus_bps: 0,
+        referral_source: BonusSource::Pool {},
+        referral_pool: 0,
+        migration: None,
+        migration_claims: SortedVecMap::new(),
+        frozen_accounts: Vec::new(),
+        enforcement_records: Vec::new(),
+        compliance_rules: SortedVecMap::new(),
+        jurisdictions: SortedVecMap::new(),
+        admin_log: Vec::new(),
+        admin_actions_recorded: 0,
+        deposits: SortedVecMap::new(),
+        supply_observers: Vec::new(),
+        dust_threshold: 0,
+        dust_destination: DustDestination::Treasury {},
+        min_transfer_amount: 0,
+        payroll: SortedVecMap::new(),
+        payroll_pool: 0,
+        subscriptions: SortedVecMap::new(),
+        invoices: SortedVecMap::new(),
+        next_invoice_id: 0,
+        pending_conditional_transfers: 0,
+        price_oracle: None,
+        reference_price: 0,
+        reference_price_updated_at: 0,
+        reference_flat_fee: 0,
+        reference_min_transfer: 0,
+        redemption_reserve: None,
+        sponsors: SortedVecMap::new(),
+        nonces: SortedVecMap::new(),
+        auctions: SortedVecMap::new(),
+        next_auction_id: 0,
+        raffles: SortedVecMap::new(),
+        next_raffle_id: 0,
+        session_keys: SortedVecMap::new(),
+        ve_locks: SortedVecMap::new(),
+        recoveries: SortedVecMap::new(),
+        dormancy_policy: None,
+        last_active_at: SortedVecMap::new(),
+        dormant_accounts: SortedVecMap::new(),
+        escheated: SortedVecMap::new(),
+        escheatment_records: Vec::new(),
+        clawbacks: SortedVecMap::new(),
+        next_clawback_id: 0,
+        emergency_paused_until: 0,
+        emergency_pause_duration: DEFAULT_EMERGENCY_PAUSE_DURATION,
+        timelock_queue: SortedVecMap::new(),
+        next_timelock_id: 0,
+        admin_timelock_delay: DEFAULT_ADMIN_TIMELOCK_DELAY,
+        mint_rate_limit: None,
+        mint_epoch_start: 0,
+        minted_in_epoch: 0,
+        scheduled_mints: SortedVecMap::new(),
+        next_scheduled_mint_id: 0,
+        burn_stats: BurnStats {
+            total_burned: 0,
+            burn_count: 0,
+            last_burn_at: 0,
+        },
+        non_circulating: SortedVecMap::new(),
+        treasury_spending_limit: None,
+        treasury_period_start: 0,
+        treasury_spent_in_period: 0,
+        treasury_disbursements: Vec::new(),
+        fee_burn: None,
+        collected_fees: 0,
+        charity: None,
+        round_up_precisions: SortedVecMap::new(),
+        round_up_donated: SortedVecMap::new(),
+        demurrage: None,
+        demurrage_accrued_at: 0,
+        burns_by_reason: SortedVecMap::new(),
+        sub_tokens: SortedVecMap::new(),
+        next_token_id: 1,
+        underlying_token: None,
+        wrap_reserve: 0,
+        wrapped_supply: 0,
+        amm_pool: None,
+        memo_log: Vec::new(),
+        transfer_history: Vec::new(),
+        transfer_history_head: 0,
+        nonces_on_transfers: false,
+        reserve_attester: None,
+        reserve_attestation: None,
+        frozen_amounts: SortedVecMap::new(),
+        label_fee: 0,
+        labels: SortedVecMap::new(),
+        account_labels: SortedVecMap::new(),
+        acceptance_required: Vec::new(),
+        allowance_offers: SortedVecMap::new(),
+        pending_transfers: SortedVecMap::new(),
+        next_pending_transfer_id: 0,
+        reversible_policies: SortedVecMap::new(),
+        reversible_transfers: SortedVecMap::new(),
+        next_reversible_transfer_id: 0,
+        external_compliance: None,
+        soulbound: false,
+        soulbound_accounts: Vec::new(),
+        soulbound_migrations: SortedVecMap::new(),
+        balance_roots: Vec::new(),
+        vesting_grants: SortedVecMap::new(),
+        next_vesting_grant_id: 0,
+        vesting_revocations: Vec::new(),
+        milestone_grants: SortedVecMap::new(),
+        next_milestone_grant_id: 0,
+        self_locks: SortedVecMap::new(),
+        cosigners: SortedVecMap::new(),
+        cosigned_transfers: SortedVecMap::new(),
+        next_cosigned_transfer_id: 0,
+        budgets: SortedVecMap::new(),
+        next_budget_id: 0,
+        organizations: SortedVecMap::new(),
+        next_organization_id: 0,
+        lending_config: None,
+        lending_pool: EMPTY_LENDING_POOL,
+        lending_shares: SortedVecMap::new(),
+        loans: SortedVecMap::new(),
+        redenominations: Vec::new(),
+        settlement_log: Vec::new(),
+        idempotency_keys: SortedVecMap::new(),
+        transfer_count: 0,
+        transfer_volume: 0,
+        sender_sketch: Vec::new(),
+        action_counts: SortedVecMap::new(),
+        state_hashes: Vec::new(),
+        _padding: [0; 10],
+    };
+    state.assert_invariants();
+
+    state
+}
+
+/// The state of the original contract, whose `approve` set the allowance aside from the balance of
+/// the owner.
+///
+/// ### Fields:
+///
+///   * `total_supply`: [`u128`], total supply of coins.
+///   * `name`: [`String`], name of the token.
+///   * `symbol`: [`String`], symbol of the token.
+///   * `balances`: [`SortedVecMap`]<[`Address`], [`u128`]>, balances of each address, less the allowances set aside.
+///   * `allowed`: [`SortedVecMap`]<[`Address`], [`SortedVecMap`]<[`Address`], [`u128`]>, all balances allotted by an address to other addresses.
+///   * `decimals`: [`u8`], the number of decimals the token uses.
+///   * `owner`: [`Address`], the owner of the contract.
+///   * `_padding`: [[`u16`]; `5`], padding bytes to align the struct.
+#[derive(ReadWriteState)]
+#[repr(C)]
+pub struct LegacyTokenState {
+    pub total_supply: u128,
+    pub name: String,
+    pub symbol: String,
+    pub balances: SortedVecMap<Address, u128>,
+    pub allowed: SortedVecMap<Address, SortedVecMap<Address, u128>>,
+    pub decimals: u8,
+    pub owner: Address,
+    pub _padding: [u8; 10],
+}
+
+/// Upgrade a deployment of the original contract. Its allowances were set aside from the balances
+/// of their owners, so each is credited back to its owner and kept as an MPC-20 allowance, which
+/// the spender transfers from the balance of the owner.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the upgrade.
+///   * `legacy`: [`LegacyTokenState`], state of the original contract.
+///
+/// ### Returns
+///
+/// The upgraded [`TokenState`] state.
+#[upgrade]
+pub(crate) fn upgrade(ctx: ContractContext, legacy: LegacyTokenState) -> TashiTokenState {
+    let upgrader = ctx.sender;
+    let mut state = initialize(
+        ctx,
+        legacy.name,
+        legacy.symbol,
+        legacy.decimals,
+        stored_amount(legacy.total_supply),
+    );
+    state.owner = legacy.owner;
+    state.trading_enabled = true; // the original contract was trading already
+    state.balances.insert_balance(upgrader, stored_amount(0)); // drop the supply minted to the upgrader
+    for (account, balance) in legacy.balances.iter() {
+        state.credit(*account, *balance);
+    }
+    for (owner, allowances) in legacy.allowed.iter() {
+        for (spender, amount) in allowances.iter() {
+            state.credit(*owner, *amount); // return the allowance set aside by the original approve
+            state.update_allowance(*owner, *spender, *amount);
+        }
+    }
+    state.assert_invariants();
+
+    state
+}
+
+/// Transfer `amount` tokens to address `to` from caller address.
+///
+/// Panics if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`Amount`], amount to transfer.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[action(shortname = 0x01)]
+pub(crate) fn transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    to: Address,
+    amount: Amount,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "transfer");
+    let supply = state.supply();
+    state.execute_transfer(
+        &ctx,
+        Transfer::new(ctx.sender, to, amount_value(amount), None),
+    );
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Transfer tokens from caller address to many receivers in one transaction. Each entry is
+/// handled as by [`transfer`].
+///
+/// Panics if there is insufficient balance in caller account for any of the transfers.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `transfers`: [`Vec`]<[`TransferTarget`]>, the receivers and amounts.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[action(shortname = 0x02)]
+fn bulk_transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    transfers: Vec<TransferTarget>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "bulk_transfer");
+    let supply = state.supply();
+    for target in transfers {
+        state.execute_transfer(
+            &ctx,
+            Transfer::new(ctx.sender, target.to, amount_value(target.amount), None),
+        );
+    }
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
@@ -0,0 +1,259 @@
+//! Property tests running random sequences of transfers, approvals, allowance spends, mints and
+//! burns against the token bookkeeping. A plain model of the balances and allowances decides which
+//! operations are valid, and the invariants of the state are checked after every step. The wide
+//! amount math is checked against plain `u128` math where that cannot overflow.
+
+use std::collections::BTreeMap;
+
+use proptest::prelude::*;
+
+use super::{amount_value, mul_div_wide, sqrt_of_product, TashiTokenState};
+use crate::hooks::Transfer;
+use crate::shards::BalanceEntries;
+use crate::testing::{account, context, new_state, INITIAL_SUPPLY};
+
+/// Number of accounts the operations pick from, account 0 being the owner.
+const ACCOUNTS: u8 = 4;
+
+/// Largest amount of a generated operation, large enough for some operations to be invalid.
+const MAX_AMOUNT: u128 = INITIAL_SUPPLY / 2;
+
+/// An operation on the token, accounts given by index.
+#[derive(Debug, Clone)]
+enum Operation {
+    Transfer {
+        from: u8,
+        to: u8,
+        amount: u128,
+    },
+    Approve {
+        owner: u8,
+        spender: u8,
+        amount: u128,
+    },
+    TransferFrom {
+        spender: u8,
+        from: u8,
+        to: u8,
+        amount: u128,
+    },
+    Mint {
+        to: u8,
+        amount: u128,
+    },
+    Burn {
+        from: u8,
+        amount: u128,
+    },
+}
+
+/// The expected balances, allowances and supply.
+#[derive(Default)]
+struct Model {
+    balances: BTreeMap<u8, u128>,
+    allowances: BTreeMap<(u8, u8), u128>,
+    supply: u128,
+}
+
+impl Model {
+    fn balance(&self, account: u8) -> u128 {
+        self.balances.get(&account).copied().unwrap_or(0)
+    }
+
+    fn allowance(&self, owner: u8, spender: u8) -> u128 {
+        self.allowances.get(&(owner, spender)).copied().unwrap_or(0)
+    }
+
+    fn set_balance(&mut self, account: u8, amount: u128) {
+        self.balances.insert(account, amount);
+    }
+}
+
+fn operation() -> impl Strategy<Value = Operation> {
+    let index = 0..ACCOUNTS;
+    let amount = 0..=MAX_AMOUNT;
+    prop_oneof![
+        (index.clone(), index.clone(), amount.clone())
+            .prop_map(|(from, to, amount)| Operation::Transfer { from, to, amount }),
+        (index.clone(), index.clone(), amount.clone()).prop_map(|(owner, spender, amount)| {
+            Operation::Approve {
+                owner,
+                spender,
+                amount,
+            }
+        }),
+        (index.clone(), index.clone(), index.clone(), amount.clone()).prop_map(
+            |(spender, from, to, amount)| Operation::TransferFrom {
+                spender,
+                from,
+                to,
+                amount,
+            }
+        ),
+        (index.clone(), amount.clone()).prop_map(|(to, amount)| Operation::Mint { to, amount }),
+        (index, amount).prop_map(|(from, amount)| Operation::Burn { from, amount }),
+    ]
+}
+
+/// Applies `operation` to the state and the model if the model says it is valid.
+fn apply(state: &mut TashiTokenState, model: &mut Model, operation: &Operation) {
+    match *operation {
+        Operation::Transfer { from, to, amount } => {
+            if model.balance(from) < amount {
+                return;
+            }
+            state.execute_transfer(
+                &context(account(from)),
+                Transfer::new(account(from), account(to), amount, None),
+            );
+            model.set_balance(from, model.balance(from) - amount);
+            model.set_balance(to, model.balance(to) + amount);
+        }
+        Operation::Approve {
+            owner,
+            spender,
+            amount,
+        } => {
+            state.approve_spender(account(owner), account(spender), amount);
+            model.allowances.insert((owner, spender), amount);
+        }
+        Operation::TransferFrom {
+            spender,
+            from,
+            to,
+            amount,
+        } => {
+            if model.allowance(from, spender) < amount || model.balance(from) < amount {
+                return;
+            }
+            state.execute_transfer(
+                &context(account(spender)),
+                Transfer::new(account(from), account(to), amount, Some(account(spender))),
+            );
+            let allowance = model.allowance(from, spender);
+            model.allowances.insert((from, spender), allowance - amount);
+            model.set_balance(from, model.balance(from) - amount);
+            model.set_balance(to, model.balance(to) + amount);
+        }
+        Operation::Mint { to, amount } => {
+            state.mint_balance(account(to), amount);
+            model.set_balance(to, model.balance(to) + amount);
+            model.supply += amount;
+        }
+        Operation::Burn { from, amount } => {
+            if model.balance(from) < amount {
+                return;
+            }
+            state.burn_balance(account(from), amount, 0);
+            model.set_balance(from, model.balance(from) - amount);
+            model.supply -= amount;
+        }
+    }
+}
+
+/// Checks the invariants of the state and that it matches the model.
+fn check_invariants(state: &TashiTokenState, model: &Model) {
+    state.assert_invariants();
+
+    let balance_sum: u128 = state
+        .balances
+        .entries()
+        .iter()
+        .map(|(_, balance)| amount_value(*balance))
+        .sum();
+    assert_eq!(balance_sum, amount_value(state.total_supply));
+    assert_eq!(state.supply(), model.supply);
+
+    assert!(state
+        .balances
+        .entries()
+        .iter()
+        .all(|(_, balance)| amount_value(*balance) > 0));
+    assert!(state.allowed.iter().all(|(_, owner_allowances)| {
+        !owner_allowances.is_empty()
+            && owner_allowances
+                .iter()
+                .all(|(_, allowance)| amount_value(*allowance) > 0)
+    }));
+
+    for index in 0..ACCOUNTS {
+        assert_eq!(state.balance_of(&account(index)), model.balance(index));
+        for spender in 0..ACCOUNTS {
+            assert_eq!(
+                state.allowance(&account(index), &account(spender)),
+                model.allowance(index, spender)
+            );
+        }
+    }
+}
+
+/// Gets the allowance of every pair of accounts.
+fn allowance_snapshot(state: &TashiTokenState) -> BTreeMap<(u8, u8), u128> {
+    let mut snapshot = BTreeMap::new();
+    for owner in 0..ACCOUNTS {
+        for spender in 0..ACCOUNTS {
+            snapshot.insert(
+                (owner, spender),
+                state.allowance(&account(owner), &account(spender)),
+            );
+        }
+    }
+    snapshot
+}
+
+proptest! {
+    #[test]
+    fn operations_preserve_invariants(operations in prop::collection::vec(operation(), 1..64)) {
+        let mut state = new_state();
+        let mut model = Model {
+            supply: INITIAL_SUPPLY,
+            ..Model::default()
+        };
+        model.set_balance(0, INITIAL_SUPPLY);
+        check_invariants(&state, &model);
+
+        for operation in &operations {
+            let previous_allowances = allowance_snapshot(&state);
+            apply(&mut state, &mut model, operation);
+            check_invariants(&state, &model);
+            if !matches!(operation, Operation::Approve { .. }) {
+                let allowances = allowance_snapshot(&state);
+                for (key, allowance) in allowances {
+                    prop_assert!(allowance <= previous_allowances[&key]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mul_div_wide_matches_narrow_math(
+        amount in any::<u64>(),
+        numerator in any::<u64>(),
+        denominator in 1..=u128::MAX,
+    ) {
+        let (amount, numerator) = (u128::from(amount), u128::from(numerator));
+        prop_assert_eq!(
+            mul_div_wide(amount, numerator, denominator),
+            amount * numerator / denominator
+        );
+    }
+
+    #[test]
+    fn mul_div_wide_is_exact_for_large_amounts(amount in any::<u128>(), denominator in 1..=u128::MAX) {
+        prop_assert_eq!(mul_div_wide(amount, denominator, denominator), amount);
+        prop_assert_eq!(mul_div_wide(denominator, amount, denominator), amount);
+    }
+
+    #[test]
+    fn sqrt_of_product_matches_narrow_math(a in any::<u64>(), b in any::<u64>()) {
+        prop_assert_eq!(
+            sqrt_of_product(u128::from(a), u128::from(b)),
+            (u128::from(a) * u128::from(b)).isqrt()
+        );
+    }
+
+    #[test]
+    fn sqrt_of_square_is_exact(a in any::<u128>()) {
+        prop_assert_eq!(sqrt_of_product(a, a), a);
+    }
+}
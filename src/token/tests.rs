@@ -0,0 +1,59 @@
+//! Tests of upgrading a deployment of the original contract, whose allowances were set aside from
+//! the balances of their owners.
+
+use pbc_contract_common::sorted_vec_map::SortedVecMap;
+
+use super::{stored_amount, upgrade, LegacyTokenState};
+use crate::allowance::transfer_from;
+use crate::testing::{account, context};
+
+/// The state of an original deployment with a supply of 1_000, where owner account 0 holds 600,
+/// account 1 holds 100, and the owner approved account 2 for 300, set aside from its balance.
+fn legacy_state() -> LegacyTokenState {
+    let mut balances = SortedVecMap::new();
+    balances.insert(account(0), 600);
+    balances.insert(account(1), 100);
+    let mut owner_allowances = SortedVecMap::new();
+    owner_allowances.insert(account(2), 300);
+    let mut allowed = SortedVecMap::new();
+    allowed.insert(account(0), owner_allowances);
+    LegacyTokenState {
+        total_supply: 1_000,
+        name: "Tashi Token".to_string(),
+        symbol: "TAS".to_string(),
+        balances,
+        allowed,
+        decimals: 8,
+        owner: account(0),
+        _padding: [0; 10],
+    }
+}
+
+#[test]
+fn upgrade_credits_allowances_back_to_their_owners() {
+    let state = upgrade(context(account(9)), legacy_state());
+
+    assert_eq!(state.owner, account(0));
+    assert_eq!(state.balance_of(&account(0)), 900);
+    assert_eq!(state.balance_of(&account(1)), 100);
+    assert_eq!(state.balance_of(&account(9)), 0);
+    assert_eq!(state.allowance(&account(0), &account(2)), 300);
+    assert_eq!(state.supply(), 1_000);
+    state.assert_invariants();
+}
+
+#[test]
+fn spender_transfers_an_upgraded_allowance_from_the_owner_balance() {
+    let state = upgrade(context(account(9)), legacy_state());
+    let (state, _) = transfer_from(
+        context(account(2)),
+        state,
+        account(0),
+        account(3),
+        stored_amount(300),
+    );
+
+    assert_eq!(state.balance_of(&account(0)), 600);
+    assert_eq!(state.balance_of(&account(3)), 300);
+    assert_eq!(state.allowance(&account(0), &account(2)), 0);
+}
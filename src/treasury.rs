@@ -0,0 +1,201 @@
+//! Treasury management, for DAOs spending the treasury held by the contract itself. Accounts with
+//! the [`Role::Treasurer`] role pay out of the treasury with `treasury_transfer`, within a spending
+//! limit per period set by the owner. Every disbursement is recorded in state with a memo tagging
+//! its purpose, including treasury transfers executed by governance proposals.
+#![cfg_attr(not(feature = "treasury"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "treasury")]
+use pbc_contract_common::context::ContractContext;
+use read_write_rpc_derive::ReadWriteRPC;
+use read_write_state_derive::ReadWriteState;
+
+#[cfg(feature = "treasury")]
+use crate::admin::Role;
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "treasury"))]
+mod tests;
+
+/// The longest memo of a disbursement, in bytes.
+pub const MAX_MEMO_LENGTH: usize = 64;
+
+/// The largest amount treasurers can pay out per period.
+///
+/// ### Fields:
+///
+///   * `max_per_period`: [`u128`], amount that can be paid out per period.
+///   * `period_length`: [`i64`], length of a period, in milliseconds.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TreasurySpendingLimit {
+    pub max_per_period: u128,
+    pub period_length: i64,
+}
+
+/// A payment out of the treasury.
+///
+/// ### Fields:
+///
+///   * `receiver`: [`Address`], account paid.
+///   * `amount`: [`u128`], amount paid.
+///   * `memo`: [`String`], purpose of the payment, empty for governance transfers.
+///   * `authorized_by`: [`Address`], treasurer that paid, the contract itself for governance
+///     transfers.
+///   * `timestamp`: [`i64`], block production time of the payment, in milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Disbursement {
+    pub receiver: Address,
+    pub amount: u128,
+    pub memo: String,
+    pub authorized_by: Address,
+    pub timestamp: i64,
+}
+
+impl TashiTokenState {
+    /// Counts `amount` against the treasury spending limit of the period at time `now`. Does
+    /// nothing without a limit.
+    ///
+    /// Panics if paying out `amount` exceeds what is left of the limit in the period.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    ///   * `amount`: [`u128`], amount being paid out.
+    pub fn consume_treasury_allowance(&mut self, now: i64, amount: u128) {
+        let Some(limit) = self.treasury_spending_limit else {
+            return;
+        };
+        if self.treasury_spent_in_period == 0
+            || now >= self.treasury_period_start + limit.period_length
+        {
+            self.treasury_period_start = now;
+            self.treasury_spent_in_period = 0;
+        }
+        let spent = self.treasury_spent_in_period.saturating_add(amount);
+        assert!(
+            spent <= limit.max_per_period,
+            "Disbursement of: {}, exceeds the rest of the period limit: {}, until: {}",
+            amount,
+            limit.max_per_period - self.treasury_spent_in_period,
+            self.treasury_period_start + limit.period_length
+        );
+        self.treasury_spent_in_period = spent;
+    }
+
+    /// Records a payment out of the treasury. Does nothing without the `treasury` feature.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `receiver`: [`Address`], account paid.
+    ///   * `amount`: [`u128`], amount paid.
+    ///   * `memo`: [`String`], purpose of the payment.
+    ///   * `authorized_by`: [`Address`], account that authorized the payment.
+    ///   * `now`: [`i64`], the current block production time.
+    pub fn record_disbursement(
+        &mut self,
+        receiver: Address,
+        amount: u128,
+        memo: String,
+        authorized_by: Address,
+        now: i64,
+    ) {
+        if !cfg!(feature = "treasury") {
+            return;
+        }
+        self.treasury_disbursements.push(Disbursement {
+            receiver,
+            amount,
+            memo,
+            authorized_by,
+            timestamp: now,
+        });
+    }
+}
+
+/// Pay `amount` tokens of the treasury held by the contract itself to `receiver`, tagged with
+/// `memo`. Only accounts with the [`Role::Treasurer`] role can call this action.
+///
+/// Panics if the caller does not have the role, if `amount` is zero, if the memo is longer than
+/// [`MAX_MEMO_LENGTH`], if the payment exceeds the spending limit of the period, or if there is
+/// insufficient balance in the treasury.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `receiver`: [`Address`], account to pay.
+///   * `amount`: [`u128`], amount to pay.
+///   * `memo`: [`String`], purpose of the payment.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "treasury")]
+#[action(shortname = 0xa6)]
+fn treasury_transfer(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    receiver: Address,
+    amount: u128,
+    memo: String,
+) -> TashiTokenState {
+    state.assert_role(Role::Treasurer {}, &ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "treasury_transfer", receiver, amount, memo);
+    assert!(amount > 0, "Disbursement amount cannot be zero.");
+    assert!(
+        memo.len() <= MAX_MEMO_LENGTH,
+        "Memo length: {}, maximum is: {}",
+        memo.len(),
+        MAX_MEMO_LENGTH
+    );
+    state.consume_treasury_allowance(ctx.block_production_time, amount);
+    state.debit(ctx.contract_address, amount);
+    state.credit(receiver, amount);
+    state.record_disbursement(
+        receiver,
+        amount,
+        memo,
+        ctx.sender,
+        ctx.block_production_time,
+    );
+    state.assert_invariants();
+
+    state
+}
+
+/// Set the largest amount treasurers can pay out per period, none to remove the limit, starting a
+/// new period with the next disbursement. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner or if the limit has a non-positive period length.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `limit`: [`Option`]<[`TreasurySpendingLimit`]>, the new limit.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "treasury")]
+#[action(shortname = 0xa7)]
+fn set_treasury_spending_limit(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    limit: Option<TreasurySpendingLimit>,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_treasury_spending_limit", limit);
+    if let Some(limit) = limit {
+        assert!(limit.period_length > 0, "Period length must be positive.");
+    }
+    state.treasury_spending_limit = limit;
+    state.treasury_period_start = 0;
+    state.treasury_spent_in_period = 0;
+    state.assert_invariants();
+
+    state
+}
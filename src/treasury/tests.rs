@@ -0,0 +1,94 @@
+//! Tests of treasury management: recorded disbursements, the spending limit of a period, and who
+//! can pay out of the treasury.
+
+use super::{set_treasury_spending_limit, treasury_transfer, TreasurySpendingLimit};
+use crate::admin::{grant_role, Role};
+use crate::hooks::Transfer;
+use crate::testing::{account, admin_context, context, context_at, new_state, token_address};
+use crate::token::TashiTokenState;
+
+/// Length of a spending period.
+const WEEK: i64 = 7 * 86_400_000;
+
+/// A token whose treasury holds 10_000, paid out by treasurer account 9 up to 1_000 a week.
+fn treasury_state() -> TashiTokenState {
+    let mut state = grant_role(admin_context(), new_state(), Role::Treasurer {}, account(9));
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), token_address(), 10_000, None),
+    );
+    set_treasury_spending_limit(
+        context(account(0)),
+        state,
+        Some(TreasurySpendingLimit {
+            max_per_period: 1_000,
+            period_length: WEEK,
+        }),
+    )
+}
+
+/// Pays `amount` to account 1 out of the treasury at block production time `time`.
+fn pay_at(state: TashiTokenState, amount: u128, time: i64) -> TashiTokenState {
+    treasury_transfer(
+        context_at(account(9), time),
+        state,
+        account(1),
+        amount,
+        "Grant".to_string(),
+    )
+}
+
+#[test]
+fn disbursement_is_paid_and_recorded() {
+    let state = pay_at(treasury_state(), 600, 1_000);
+
+    assert_eq!(state.balance_of(&token_address()), 9_400);
+    assert_eq!(state.balance_of(&account(1)), 600);
+    let disbursement = &state.treasury_disbursements[0];
+    assert_eq!(disbursement.amount, 600);
+    assert_eq!(disbursement.memo, "Grant");
+    assert_eq!(disbursement.authorized_by, account(9));
+    assert_eq!(disbursement.timestamp, 1_000);
+}
+
+#[test]
+#[should_panic(
+    expected = "Disbursement of: 500, exceeds the rest of the period limit: 400, until: 604801000"
+)]
+fn disbursements_beyond_the_period_limit_are_rejected() {
+    let state = pay_at(treasury_state(), 600, 1_000);
+    pay_at(state, 500, WEEK);
+}
+
+#[test]
+fn next_period_starts_with_a_full_limit() {
+    let state = pay_at(treasury_state(), 1_000, 1_000);
+    let state = pay_at(state, 1_000, WEEK + 1_000);
+
+    assert_eq!(state.balance_of(&account(1)), 2_000);
+    assert_eq!(state.treasury_disbursements.len(), 2);
+}
+
+#[test]
+#[should_panic(expected = "Caller is missing role: Treasurer")]
+fn only_treasurers_pay_out() {
+    treasury_transfer(
+        context(account(1)),
+        treasury_state(),
+        account(1),
+        100,
+        String::new(),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Memo length: 65, maximum is: 64")]
+fn long_memo_is_rejected() {
+    treasury_transfer(
+        context(account(9)),
+        treasury_state(),
+        account(1),
+        100,
+        "x".repeat(65),
+    );
+}
@@ -0,0 +1,264 @@
+//! Amounts in display units, the decimal strings wallets show such as `"12.5"`. They are converted
+//! to and from base units with the `decimals` of the token using exact integer arithmetic, so a
+//! display amount either converts without loss or is rejected.
+#![cfg_attr(not(feature = "display-units"), allow(dead_code))]
+
+use pbc_contract_common::address::Address;
+#[cfg(feature = "display-units")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "display-units")]
+use pbc_contract_common::events::EventGroup;
+
+#[cfg(feature = "display-units")]
+use crate::hooks::Transfer;
+use crate::token::TashiTokenState;
+#[cfg(feature = "display-units")]
+use crate::views::return_value;
+
+#[cfg(all(test, feature = "display-units"))]
+mod tests;
+
+/// Computes the number of base units in one display unit.
+///
+/// Panics if `10^decimals` does not fit in a [`u128`].
+///
+/// ### Parameters:
+///
+///   * `decimals`: [`u8`], the number of decimals of the token.
+///
+/// ### Returns:
+///
+/// The [`u128`] scale, `10^decimals`.
+pub fn unit_scale(decimals: u8) -> u128 {
+    10u128
+        .checked_pow(u32::from(decimals))
+        .unwrap_or_else(|| panic!("Unsupported number of decimals: {}", decimals))
+}
+
+/// Converts a display amount to base units. The amount is a non-empty run of digits, optionally
+/// followed by a `.` and at most `decimals` fractional digits.
+///
+/// Panics if `display` is malformed, has more fractional digits than `decimals`, or does not fit
+/// in a [`u128`] once converted.
+///
+/// ### Parameters:
+///
+///   * `display`: [`str`], the display amount, e.g. `"12.5"`.
+///   * `decimals`: [`u8`], the number of decimals of the token.
+///
+/// ### Returns:
+///
+/// The [`u128`] amount in base units.
+pub fn parse_display_amount(display: &str, decimals: u8) -> u128 {
+    let scale = unit_scale(decimals);
+    let (whole, fraction) = display.split_once('.').unwrap_or((display, ""));
+    let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+    assert!(
+        !whole.is_empty() && is_digits(whole) && is_digits(fraction) && !display.ends_with('.'),
+        "Invalid display amount: {}",
+        display
+    );
+    assert!(
+        fraction.len() <= usize::from(decimals),
+        "Display amount: {}, has more than {} decimals",
+        display,
+        decimals
+    );
+
+    let digits_value = |digits: &str| {
+        digits.bytes().try_fold(0u128, |value, digit| {
+            value.checked_mul(10)?.checked_add(u128::from(digit - b'0'))
+        })
+    };
+    // the fraction has at most `decimals` digits, so scaling it stays below `scale`
+    let fraction_scale = unit_scale(decimals - fraction.len() as u8);
+    digits_value(whole)
+        .and_then(|whole| whole.checked_mul(scale))
+        .zip(digits_value(fraction))
+        .and_then(|(whole, fraction)| whole.checked_add(fraction * fraction_scale))
+        .unwrap_or_else(|| panic!("Display amount: {}, overflows the amount range", display))
+}
+
+/// Converts an amount in base units to its display amount, without trailing fractional zeros.
+///
+/// Panics if `10^decimals` does not fit in a [`u128`].
+///
+/// ### Parameters:
+///
+///   * `amount`: [`u128`], the amount in base units.
+///   * `decimals`: [`u8`], the number of decimals of the token.
+///
+/// ### Returns:
+///
+/// The display amount, e.g. `"12.5"`.
+pub fn format_display_amount(amount: u128, decimals: u8) -> String {
+    let scale = unit_scale(decimals);
+    let whole = amount / scale;
+    let fraction = amount % scale;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    let fraction = format!("{:0width$}", fraction, width = usize::from(decimals));
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+impl TashiTokenState {
+    /// Converts a display amount to base units with the decimals of the token.
+    ///
+    /// Panics if `display` is not a valid display amount, as by [`parse_display_amount`].
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `display`: [`str`], the display amount, e.g. `"12.5"`.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u128`] amount in base units.
+    pub fn parse_amount(&self, display: &str) -> u128 {
+        parse_display_amount(display, self.decimals)
+    }
+
+    /// Gets the balance of `owner` as a display amount.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `owner`: [`Address`], account to query balance of.
+    ///
+    /// ### Returns:
+    ///
+    /// The display amount owned by the account.
+    pub fn display_balance_of(&self, owner: &Address) -> String {
+        format_display_amount(self.balance_of(owner), self.decimals)
+    }
+}
+
+/// Transfer `amount` tokens, given in display units, to address `to` from caller address. Behaves
+/// as `transfer` otherwise.
+///
+/// Panics if `amount` is not a valid display amount or if there is insufficient balance in caller
+/// account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`String`], amount to transfer, e.g. `"12.5"`.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "display-units")]
+#[action(shortname = 0x5d)]
+fn transfer_display(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    receiver: Address,
+    amount: String,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "transfer_display");
+    let amount = state.parse_amount(&amount);
+    let supply = state.supply();
+    state.execute_transfer(&ctx, Transfer::new(ctx.sender, receiver, amount, None));
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Transfer `amount` tokens, given in display units, to address `to` from address `from`. Behaves
+/// as `transfer_from` otherwise.
+///
+/// Panics if `amount` is not a valid display amount, if there is insufficient allowance in caller
+/// account, or insufficient balance in `from` account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `from`: [`Address`], account to transfer from.
+///   * `to`: [`Address`], account to transfer to.
+///   * `amount`: [`String`], amount to transfer, e.g. `"12.5"`.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "display-units")]
+#[action(shortname = 0x5e)]
+fn transfer_from_display(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    from: Address,
+    receiver: Address,
+    amount: String,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "transfer_from_display");
+    let amount = state.parse_amount(&amount);
+    let supply = state.supply();
+    state.execute_transfer(
+        &ctx,
+        Transfer::new(from, receiver, amount, Some(ctx.sender)),
+    );
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Approve `amount` tokens, given in display units, for address `spender` from caller address.
+/// Behaves as `approve` otherwise.
+///
+/// Panics if `amount` is not a valid display amount or if the address guards reject `spender`.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `spender`: [`Address`], account which will spend the funds.
+///   * `amount`: [`String`], amount to allot to `spender`, e.g. `"12.5"`.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "display-units")]
+#[action(shortname = 0x5f)]
+fn approve_display(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    spender: Address,
+    amount: String,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "approve_display");
+    let amount = state.parse_amount(&amount);
+    state.assert_valid_destination(&ctx, &spender);
+    state.approve_spender(ctx.sender, spender, amount);
+    state.assert_invariants();
+
+    state
+}
+
+/// Return the balance of `owner` to the caller, as a display amount.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `owner`: [`Address`], account to query the balance of.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the balance as a [`String`] in the return data.
+#[cfg(feature = "display-units")]
+#[action(shortname = 0x60)]
+fn query_display_balance_of(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    owner: Address,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    state.accrue_demurrage_until(ctx.block_production_time);
+    let events = return_value(state.display_balance_of(&owner));
+    state.assert_invariants();
+    (state, events)
+}
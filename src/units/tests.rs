@@ -0,0 +1,143 @@
+//! Tests of display amounts: malformed strings, amounts beyond the decimals or the amount range,
+//! tokens without decimals, and amounts surviving a format and parse round trip.
+
+use super::{format_display_amount, parse_display_amount, unit_scale};
+
+#[test]
+fn display_amount_is_scaled_by_the_decimals() {
+    assert_eq!(parse_display_amount("12.5", 8), 1_250_000_000);
+    assert_eq!(parse_display_amount("12", 8), 1_200_000_000);
+    assert_eq!(parse_display_amount("0.00000001", 8), 1);
+    assert_eq!(parse_display_amount("007.50", 2), 750);
+}
+
+#[test]
+#[should_panic(expected = "Invalid display amount: 12.")]
+fn trailing_point_is_rejected() {
+    parse_display_amount("12.", 8);
+}
+
+#[test]
+#[should_panic(expected = "Invalid display amount: .5")]
+fn missing_whole_part_is_rejected() {
+    parse_display_amount(".5", 8);
+}
+
+#[test]
+#[should_panic(expected = "Invalid display amount:  12")]
+fn leading_whitespace_is_rejected() {
+    parse_display_amount(" 12", 8);
+}
+
+#[test]
+#[should_panic(expected = "Invalid display amount: 12 ")]
+fn trailing_whitespace_is_rejected() {
+    parse_display_amount("12 ", 8);
+}
+
+#[test]
+#[should_panic(expected = "Invalid display amount: 1.2.3")]
+fn second_point_is_rejected() {
+    parse_display_amount("1.2.3", 8);
+}
+
+#[test]
+#[should_panic(expected = "Invalid display amount: -1")]
+fn sign_is_rejected() {
+    parse_display_amount("-1", 8);
+}
+
+#[test]
+#[should_panic(expected = "Display amount: 1.123456789, has more than 8 decimals")]
+fn too_many_fractional_digits_are_rejected() {
+    parse_display_amount("1.123456789", 8);
+}
+
+#[test]
+fn largest_amount_parses() {
+    assert_eq!(
+        parse_display_amount("340282366920938463463374607431768211455", 0),
+        u128::MAX
+    );
+    assert_eq!(
+        parse_display_amount("3402823669209384634633746074317.68211455", 8),
+        u128::MAX
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "Display amount: 340282366920938463463374607431768211456, overflows the amount range"
+)]
+fn whole_part_above_the_amount_range_is_rejected() {
+    parse_display_amount("340282366920938463463374607431768211456", 0);
+}
+
+#[test]
+#[should_panic(
+    expected = "Display amount: 3402823669209384634633746074317.68211456, overflows the amount range"
+)]
+fn scaled_amount_above_the_amount_range_is_rejected() {
+    parse_display_amount("3402823669209384634633746074317.68211456", 8);
+}
+
+#[test]
+#[cfg(feature = "u64-amounts")]
+#[should_panic(expected = "Amount: 18446744073709551616, does not fit in the amount type.")]
+fn amount_above_u64_is_rejected_when_stored() {
+    use crate::token::stored_amount;
+
+    let largest = parse_display_amount("184467440737.09551615", 8);
+    assert_eq!(largest, u128::from(u64::MAX));
+    stored_amount(largest);
+
+    stored_amount(parse_display_amount("184467440737.09551616", 8));
+}
+
+#[test]
+fn token_without_decimals_takes_whole_amounts() {
+    assert_eq!(unit_scale(0), 1);
+    assert_eq!(parse_display_amount("12", 0), 12);
+    assert_eq!(format_display_amount(12, 0), "12");
+}
+
+#[test]
+#[should_panic(expected = "Display amount: 12.5, has more than 0 decimals")]
+fn token_without_decimals_rejects_fractions() {
+    parse_display_amount("12.5", 0);
+}
+
+#[test]
+#[should_panic(expected = "Unsupported number of decimals: 39")]
+fn decimals_beyond_the_amount_range_are_rejected() {
+    unit_scale(39);
+}
+
+#[test]
+fn format_drops_trailing_fractional_zeros() {
+    assert_eq!(format_display_amount(1_250_000_000, 8), "12.5");
+    assert_eq!(format_display_amount(1_200_000_000, 8), "12");
+    assert_eq!(format_display_amount(1, 8), "0.00000001");
+    assert_eq!(format_display_amount(0, 8), "0");
+}
+
+#[test]
+fn format_and_parse_round_trip() {
+    for decimals in [0, 2, 8, 18] {
+        for amount in [0, 1, 10, 99, 1_000_001, 123_456_789_012_345, u128::MAX] {
+            let display = format_display_amount(amount, decimals);
+            assert_eq!(
+                parse_display_amount(&display, decimals),
+                amount,
+                "{}",
+                display
+            );
+        }
+    }
+    for display in ["0", "12", "12.5", "0.001", "123456.78"] {
+        assert_eq!(
+            format_display_amount(parse_display_amount(display, 8), 8),
+            display
+        );
+    }
+}
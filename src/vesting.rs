@@ -0,0 +1,270 @@
+//! Vesting grants, releasing tokens to a beneficiary linearly between a start and an end time,
+//! with nothing claimable before the cliff. A grant can be made revocable by its grantor, for
+//! employee grants ending on departure. Revoking stops the vesting: what vested stays claimable by
+//! the beneficiary, the unvested remainder returns to the grantor, and the revocation is recorded
+//! in state for good.
+#![cfg_attr(not(feature = "vesting"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "vesting")]
+use pbc_contract_common::context::ContractContext;
+use read_write_state_derive::ReadWriteState;
+
+use crate::token::mul_div;
+#[cfg(feature = "vesting")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "vesting"))]
+mod tests;
+
+/// Tokens granted to `beneficiary`, vesting between `start_time` and `end_time`.
+///
+/// ### Fields:
+///
+///   * `grantor`: [`Address`], account that funded the grant.
+///   * `beneficiary`: [`Address`], account the tokens vest for.
+///   * `total`: [`u128`], amount granted, what vested at revocation once revoked.
+///   * `claimed`: [`u128`], amount the beneficiary has claimed so far.
+///   * `start_time`: [`i64`], block production time vesting starts at, in milliseconds.
+///   * `cliff_time`: [`i64`], block production time before which nothing is claimable, in
+///     milliseconds.
+///   * `end_time`: [`i64`], block production time the full grant has vested at, in milliseconds.
+///   * `revocable`: [`bool`], whether the grantor can revoke the grant.
+///   * `revoked`: [`bool`], whether the grant was revoked.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VestingGrant {
+    pub grantor: Address,
+    pub beneficiary: Address,
+    pub total: u128,
+    pub claimed: u128,
+    pub start_time: i64,
+    pub cliff_time: i64,
+    pub end_time: i64,
+    pub revocable: bool,
+    pub revoked: bool,
+}
+
+/// The record of a revoked grant.
+///
+/// ### Fields:
+///
+///   * `grant_id`: [`u64`], id of the grant.
+///   * `grantor`: [`Address`], grantor that revoked the grant.
+///   * `beneficiary`: [`Address`], beneficiary of the grant.
+///   * `vested`: [`u128`], amount vested at revocation, left to the beneficiary.
+///   * `returned`: [`u128`], unvested amount returned to the grantor.
+///   * `timestamp`: [`i64`], block production time of the revocation, in milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VestingRevocation {
+    pub grant_id: u64,
+    pub grantor: Address,
+    pub beneficiary: Address,
+    pub vested: u128,
+    pub returned: u128,
+    pub timestamp: i64,
+}
+
+impl VestingGrant {
+    /// Gets the amount vested at time `now`, including what was claimed. A revoked grant has fully
+    /// vested what it kept.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    ///
+    /// ### Returns:
+    ///
+    /// A [`u128`] amount vested so far.
+    pub fn vested(&self, now: i64) -> u128 {
+        if self.revoked || now >= self.end_time {
+            self.total
+        } else if now < self.cliff_time || now <= self.start_time {
+            0
+        } else {
+            mul_div(
+                self.total,
+                (now - self.start_time) as u128,
+                (self.end_time - self.start_time) as u128,
+            )
+        }
+    }
+}
+
+#[cfg(feature = "vesting")]
+impl TashiTokenState {
+    /// Gets the grant with id `grant_id`.
+    ///
+    /// Panics if there is no such grant.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `grant_id`: [`u64`], id of the grant.
+    ///
+    /// ### Returns:
+    ///
+    /// A mutable reference to the [`VestingGrant`].
+    fn vesting_grant(&mut self, grant_id: u64) -> &mut VestingGrant {
+        self.vesting_grants
+            .get_mut(&grant_id)
+            .unwrap_or_else(|| panic!("No vesting grant with id: {}", grant_id))
+    }
+}
+
+/// Grant `total` tokens from caller address to `beneficiary`, vesting linearly between
+/// `start_time` and `end_time` and claimable from `cliff_time`. The tokens are taken from the
+/// caller up front.
+///
+/// Panics if `total` is zero, if `end_time` is not after `start_time`, if the cliff is outside of
+/// the vesting period, or if there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `beneficiary`: [`Address`], account the tokens vest for.
+///   * `total`: [`u128`], amount granted.
+///   * `start_time`: [`i64`], block production time vesting starts at, in milliseconds.
+///   * `cliff_time`: [`i64`], block production time before which nothing is claimable, in
+///     milliseconds.
+///   * `end_time`: [`i64`], block production time the full grant has vested at, in milliseconds.
+///   * `revocable`: [`bool`], whether the caller can revoke the grant.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "vesting")]
+#[action(shortname = 0xdc)]
+#[allow(clippy::too_many_arguments)]
+fn create_vesting_grant(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    beneficiary: Address,
+    total: u128,
+    start_time: i64,
+    cliff_time: i64,
+    end_time: i64,
+    revocable: bool,
+) -> TashiTokenState {
+    assert!(total > 0, "Granted amount cannot be zero.");
+    assert!(end_time > start_time, "Vesting must end after it starts.");
+    assert!(
+        (start_time..=end_time).contains(&cliff_time),
+        "Cliff must be within the vesting period."
+    );
+    state.escrow(&ctx, ctx.sender, total); // escrow the grant
+
+    state.vesting_grants.insert(
+        state.next_vesting_grant_id,
+        VestingGrant {
+            grantor: ctx.sender,
+            beneficiary,
+            total,
+            claimed: 0,
+            start_time,
+            cliff_time,
+            end_time,
+            revocable,
+            revoked: false,
+        },
+    );
+    state.next_vesting_grant_id += 1;
+    state.assert_invariants();
+
+    state
+}
+
+/// Claim everything vested and not yet claimed of grant `grant_id`. Only the beneficiary of the
+/// grant can call this action. The grant is removed once fully claimed.
+///
+/// Panics if there is no such grant or if the caller is not its beneficiary.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `grant_id`: [`u64`], id of the grant.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "vesting")]
+#[action(shortname = 0xdd)]
+fn claim_vested(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    grant_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "claim_vested");
+    let grant = state.vesting_grant(grant_id);
+    assert!(
+        grant.beneficiary == ctx.sender,
+        "Only the beneficiary can claim from the grant."
+    );
+    let amount = grant.vested(ctx.block_production_time) - grant.claimed;
+    let grantor = grant.grantor;
+    grant.claimed += amount;
+    if grant.claimed == grant.total {
+        state.vesting_grants.remove(&grant_id);
+    }
+    state.payout(&ctx, grantor, ctx.sender, amount); // pay out the vested amount
+    state.assert_invariants();
+
+    state
+}
+
+/// Revoke grant `grant_id`, stopping its vesting. What vested stays claimable by the beneficiary,
+/// the unvested remainder is returned to the caller, and the revocation is recorded. Only the
+/// grantor of a revocable grant can call this action.
+///
+/// Panics if there is no such grant, if the caller is not its grantor, or if the grant is not
+/// revocable or already revoked.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `grant_id`: [`u64`], id of the grant.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "vesting")]
+#[action(shortname = 0xde)]
+fn revoke_vesting_grant(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    grant_id: u64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "revoke_vesting_grant");
+    let grant = state.vesting_grant(grant_id);
+    assert!(
+        grant.grantor == ctx.sender,
+        "Only the grantor can revoke the grant."
+    );
+    assert!(grant.revocable, "Grant is not revocable.");
+    assert!(!grant.revoked, "Grant is already revoked.");
+    let vested = grant.vested(ctx.block_production_time);
+    let returned = grant.total - vested;
+    grant.total = vested;
+    grant.revoked = true;
+    let beneficiary = grant.beneficiary;
+    if grant.claimed == grant.total {
+        state.vesting_grants.remove(&grant_id);
+    }
+    state.release(&ctx, ctx.sender, returned); // return the unvested remainder
+
+    state.vesting_revocations.push(VestingRevocation {
+        grant_id,
+        grantor: ctx.sender,
+        beneficiary,
+        vested,
+        returned,
+        timestamp: ctx.block_production_time,
+    });
+    state.assert_invariants();
+
+    state
+}
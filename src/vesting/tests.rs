@@ -0,0 +1,89 @@
+//! Tests of vesting grants: nothing claimable before the cliff, linear vesting after it, and
+//! revocation returning the unvested remainder while leaving what vested to the beneficiary.
+
+use super::{claim_vested, create_vesting_grant, revoke_vesting_grant};
+use crate::testing::{account, context, context_at, new_state, INITIAL_SUPPLY};
+use crate::token::TashiTokenState;
+
+/// A token where the owner granted account 1 1_000, vesting from time 0 to 1_000 with a cliff at
+/// 250, as grant 0.
+fn granted_state(revocable: bool) -> TashiTokenState {
+    create_vesting_grant(
+        context(account(0)),
+        new_state(),
+        account(1),
+        1_000,
+        0,
+        250,
+        1_000,
+        revocable,
+    )
+}
+
+#[test]
+fn grant_is_taken_from_the_grantor() {
+    let state = granted_state(false);
+
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 1_000);
+    assert_eq!(state.vesting_grants.get(&0).unwrap().total, 1_000);
+}
+
+#[test]
+fn nothing_is_claimable_before_the_cliff() {
+    let state = claim_vested(context_at(account(1), 249), granted_state(false), 0);
+
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.vesting_grants.get(&0).unwrap().claimed, 0);
+}
+
+#[test]
+fn grant_vests_linearly_and_is_removed_once_claimed() {
+    let state = claim_vested(context_at(account(1), 500), granted_state(false), 0);
+    assert_eq!(state.balance_of(&account(1)), 500);
+
+    let state = claim_vested(context_at(account(1), 2_000), state, 0);
+    assert_eq!(state.balance_of(&account(1)), 1_000);
+    assert!(state.vesting_grants.get(&0).is_none());
+}
+
+#[test]
+fn revocation_returns_the_unvested_remainder() {
+    let state = revoke_vesting_grant(context_at(account(0), 400), granted_state(true), 0);
+    assert_eq!(state.balance_of(&account(0)), INITIAL_SUPPLY - 400);
+    let revocation = &state.vesting_revocations[0];
+    assert_eq!(revocation.vested, 400);
+    assert_eq!(revocation.returned, 600);
+    assert_eq!(revocation.timestamp, 400);
+
+    // what vested stays claimable, and nothing vests after the revocation
+    let state = claim_vested(context_at(account(1), 2_000), state, 0);
+    assert_eq!(state.balance_of(&account(1)), 400);
+    assert!(state.vesting_grants.get(&0).is_none());
+}
+
+#[test]
+#[should_panic(expected = "Grant is not revocable.")]
+fn irrevocable_grant_cannot_be_revoked() {
+    revoke_vesting_grant(context_at(account(0), 400), granted_state(false), 0);
+}
+
+#[test]
+#[should_panic(expected = "Only the beneficiary can claim from the grant.")]
+fn only_the_beneficiary_claims() {
+    claim_vested(context_at(account(2), 500), granted_state(false), 0);
+}
+
+#[test]
+#[should_panic(expected = "Cliff must be within the vesting period.")]
+fn cliff_after_the_end_is_rejected() {
+    create_vesting_grant(
+        context(account(0)),
+        new_state(),
+        account(1),
+        1_000,
+        0,
+        1_001,
+        1_000,
+        false,
+    );
+}
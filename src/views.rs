@@ -0,0 +1,234 @@
+//! Getters other contracts call to read the token on-chain. Each action returns its value as the
+//! return data of the interaction, which the calling contract reads in its callback. Getters of
+//! balances and of the supply apply the demurrage due first, so they return current amounts.
+
+use pbc_contract_common::address::Address;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::events::EventGroup;
+
+use crate::allowance::SpenderAllowance;
+use crate::token::{stored_amount, Amount, TashiTokenState};
+
+#[cfg(test)]
+mod tests;
+
+/// The most accounts whose balances `query_balances_of` returns in one call.
+pub const MAX_BALANCES_QUERY: usize = 256;
+
+/// The most allowances `query_allowances_of` returns in one call.
+pub const MAX_ALLOWANCES_QUERY: u32 = 256;
+
+/// Builds the event group returning `value` to the calling contract.
+///
+/// ### Parameters:
+///
+///   * `value`: the value to return.
+///
+/// ### Returns:
+///
+/// The [`Vec`]<[`EventGroup`]> to return from the action.
+pub fn return_value<T: pbc_traits::ReadWriteRPC>(value: T) -> Vec<EventGroup> {
+    let mut event_group = EventGroup::builder();
+    event_group.return_data(value);
+    vec![event_group.build()]
+}
+
+/// Return the name of the token to the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the name as a [`String`] in the return data.
+#[action(shortname = 0x4a)]
+fn query_name(_ctx: ContractContext, state: TashiTokenState) -> (TashiTokenState, Vec<EventGroup>) {
+    let events = return_value(state.name.clone());
+    state.assert_invariants();
+    (state, events)
+}
+
+/// Return the symbol of the token to the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the symbol as a [`String`] in the return data.
+#[action(shortname = 0x4b)]
+fn query_symbol(
+    _ctx: ContractContext,
+    state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let events = return_value(state.symbol.clone());
+    state.assert_invariants();
+    (state, events)
+}
+
+/// Return the number of decimals of the token to the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the decimals as a [`u8`] in the return data.
+#[action(shortname = 0x4c)]
+fn query_decimals(
+    _ctx: ContractContext,
+    state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let events = return_value(state.decimals);
+    state.assert_invariants();
+    (state, events)
+}
+
+/// Return the total supply of the token to the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The [`TokenState`] state with the demurrage due applied, and the total supply as an [`Amount`]
+/// in the return data.
+#[action(shortname = 0x4d)]
+fn query_total_supply(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    state.accrue_demurrage_until(ctx.block_production_time);
+    let events = return_value(stored_amount(state.supply()));
+    state.assert_invariants();
+    (state, events)
+}
+
+/// Return the balance of `owner` to the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `owner`: [`Address`], account to query the balance of.
+///
+/// ### Returns
+///
+/// The [`TokenState`] state with the demurrage due applied, and the balance as an [`Amount`] in
+/// the return data.
+#[action(shortname = 0x4e)]
+pub(crate) fn query_balance_of(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    owner: Address,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    state.accrue_demurrage_until(ctx.block_production_time);
+    let events = return_value(stored_amount(state.balance_of(&owner)));
+    state.assert_invariants();
+    (state, events)
+}
+
+/// Return the allowance `owner` allotted `spender` to the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `owner`: [`Address`], account which owns the funds.
+///   * `spender`: [`Address`], account which spends the funds.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the allowance as an [`Amount`] in the return data.
+#[action(shortname = 0x4f)]
+fn query_allowance(
+    _ctx: ContractContext,
+    state: TashiTokenState,
+    owner: Address,
+    spender: Address,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let events = return_value(stored_amount(state.allowance(&owner, &spender)));
+    state.assert_invariants();
+    (state, events)
+}
+
+/// Return the balances of `owners` to the caller, in the order of `owners`.
+///
+/// Panics if `owners` has more than [`MAX_BALANCES_QUERY`] accounts.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `owners`: [`Vec`]<[`Address`]>, accounts to query the balances of.
+///
+/// ### Returns
+///
+/// The [`TokenState`] state with the demurrage due applied, and the balances as a
+/// [`Vec`]<[`Amount`]> in the return data.
+#[action(shortname = 0xc1)]
+fn query_balances_of(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    owners: Vec<Address>,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    assert!(
+        owners.len() <= MAX_BALANCES_QUERY,
+        "Queried: {} accounts, maximum is: {}",
+        owners.len(),
+        MAX_BALANCES_QUERY
+    );
+    state.accrue_demurrage_until(ctx.block_production_time);
+    let balances: Vec<Amount> = owners
+        .iter()
+        .map(|owner| stored_amount(state.balance_of(owner)))
+        .collect();
+    let events = return_value(balances);
+    state.assert_invariants();
+    (state, events)
+}
+
+/// Return a page of the allowances `owner` has allotted to the caller, ordered by spender. The
+/// next page starts at `offset + limit`, and a page shorter than `limit` is the last.
+///
+/// Panics if `limit` is more than [`MAX_ALLOWANCES_QUERY`].
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `owner`: [`Address`], account which owns the funds.
+///   * `offset`: [`u32`], number of allowances to skip.
+///   * `limit`: [`u32`], maximum number of allowances to return.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the allowances as a [`Vec`]<[`SpenderAllowance`]> in
+/// the return data.
+#[action(shortname = 0x101)]
+fn query_allowances_of(
+    _ctx: ContractContext,
+    state: TashiTokenState,
+    owner: Address,
+    offset: u32,
+    limit: u32,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    assert!(
+        limit <= MAX_ALLOWANCES_QUERY,
+        "Queried: {} allowances, maximum is: {}",
+        limit,
+        MAX_ALLOWANCES_QUERY
+    );
+    let allowances = state.allowances_of(&owner, offset as usize, limit as usize);
+    let events = return_value(allowances);
+    state.assert_invariants();
+    (state, events)
+}
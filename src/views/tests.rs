@@ -0,0 +1,66 @@
+//! Tests of the getters: paging through the allowances of an owner.
+
+use super::{query_allowances_of, MAX_ALLOWANCES_QUERY};
+use crate::allowance::SpenderAllowance;
+use crate::testing::{account, context, new_state};
+use crate::token::{amount_value, TashiTokenState};
+
+/// A token whose owner allotted accounts 1 to 5 an allowance of 100 times their index.
+fn approved_state() -> TashiTokenState {
+    let mut state = new_state();
+    for index in 1..=5 {
+        state.approve_spender(account(0), account(index), 100 * u128::from(index));
+    }
+    state
+}
+
+/// The first identifier byte of the spender and the amount of each of `allowances`.
+fn entries(allowances: Vec<SpenderAllowance>) -> Vec<(u8, u128)> {
+    allowances
+        .into_iter()
+        .map(|allowance| {
+            (
+                allowance.spender.identifier[0],
+                amount_value(allowance.amount),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn allowances_are_listed_one_page_at_a_time() {
+    let state = approved_state();
+
+    assert_eq!(
+        entries(state.allowances_of(&account(0), 0, 2)),
+        vec![(1, 100), (2, 200)]
+    );
+    assert_eq!(
+        entries(state.allowances_of(&account(0), 2, 2)),
+        vec![(3, 300), (4, 400)]
+    );
+    assert_eq!(
+        entries(state.allowances_of(&account(0), 4, 2)),
+        vec![(5, 500)]
+    );
+    assert!(state.allowances_of(&account(0), 6, 2).is_empty());
+    assert!(state.allowances_of(&account(1), 0, 2).is_empty());
+}
+
+#[test]
+fn query_returns_the_page() {
+    let (_, events) = query_allowances_of(context(account(1)), approved_state(), account(0), 0, 2);
+    assert_eq!(events.len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "Queried: 257 allowances, maximum is: 256")]
+fn query_beyond_the_maximum_page_is_rejected() {
+    query_allowances_of(
+        context(account(1)),
+        approved_state(),
+        account(0),
+        0,
+        MAX_ALLOWANCES_QUERY + 1,
+    );
+}
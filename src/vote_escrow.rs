@@ -0,0 +1,236 @@
+//! Vote-escrow locking, rewarding long-term alignment over raw balances. Holders lock tokens until
+//! a chosen time at most [`MAX_LOCK_DURATION`] away, and the lock carries a weight of up to
+//! [`VE_MAX_BOOST`] times the locked amount, proportional to the time left until it unlocks. The
+//! weight decays linearly to zero at the unlock time, and extending the lock restores it. Locked
+//! tokens are withdrawn once the lock has expired.
+//!
+//! Governance counts the weight as votes in the vote-escrowed voting mode, and reward programs
+//! read it with `query_ve_weight`.
+#![cfg_attr(not(feature = "vote-escrow"), allow(dead_code))]
+
+use create_type_spec_derive::CreateTypeSpec;
+use pbc_contract_common::address::Address;
+#[cfg(feature = "vote-escrow")]
+use pbc_contract_common::context::ContractContext;
+#[cfg(feature = "vote-escrow")]
+use pbc_contract_common::events::EventGroup;
+use read_write_state_derive::ReadWriteState;
+
+use crate::token::{mul_div, TashiTokenState};
+#[cfg(feature = "vote-escrow")]
+use crate::views::return_value;
+
+#[cfg(all(test, feature = "vote-escrow"))]
+mod tests;
+
+/// The longest a lock can run, four years in milliseconds.
+pub const MAX_LOCK_DURATION: i64 = 4 * 365 * 24 * 60 * 60 * 1000;
+
+/// The weight of a token locked for [`MAX_LOCK_DURATION`].
+pub const VE_MAX_BOOST: u128 = 4;
+
+/// Tokens locked by an account.
+///
+/// ### Fields:
+///
+///   * `amount`: [`u128`], amount locked.
+///   * `unlock_at`: [`i64`], block production time the tokens can be withdrawn from, in
+///     milliseconds.
+#[derive(ReadWriteState, CreateTypeSpec)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VeLock {
+    pub amount: u128,
+    pub unlock_at: i64,
+}
+
+impl VeLock {
+    /// Gets the weight of the lock at time `now`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `now`: [`i64`], the current block production time.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u128`] weight, zero once the lock has expired.
+    pub fn weight_at(&self, now: i64) -> u128 {
+        let remaining = (self.unlock_at - now).clamp(0, MAX_LOCK_DURATION);
+        mul_div(
+            self.amount * VE_MAX_BOOST,
+            remaining as u128,
+            MAX_LOCK_DURATION as u128,
+        )
+    }
+}
+
+impl TashiTokenState {
+    /// Gets the vote-escrow weight of `account` at time `now`.
+    ///
+    /// ### Parameters:
+    ///
+    ///   * `account`: [`Address`], account to query.
+    ///   * `now`: [`i64`], the current block production time.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`u128`] weight, zero if the account has no lock.
+    pub fn ve_weight(&self, account: &Address, now: i64) -> u128 {
+        self.ve_locks
+            .get(account)
+            .map_or(0, |lock| lock.weight_at(now))
+    }
+}
+
+/// Panics unless `unlock_at` is in the future and at most [`MAX_LOCK_DURATION`] away.
+#[cfg(feature = "vote-escrow")]
+fn assert_valid_unlock(unlock_at: i64, now: i64) {
+    assert!(unlock_at > now, "Unlock time has already passed.");
+    assert!(
+        unlock_at - now <= MAX_LOCK_DURATION,
+        "Unlock time: {}, is further than the maximum lock duration: {}",
+        unlock_at,
+        MAX_LOCK_DURATION
+    );
+}
+
+/// Lock `amount` tokens of caller address until `unlock_at`.
+///
+/// Panics if the caller already has a lock, if `amount` is zero, if `unlock_at` is not in the
+/// future or is further than [`MAX_LOCK_DURATION`] away, or if there is insufficient balance in
+/// caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to lock.
+///   * `unlock_at`: [`i64`], block production time the tokens can be withdrawn from, in
+///     milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "vote-escrow")]
+#[action(shortname = 0x85)]
+fn create_lock(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+    unlock_at: i64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "create_lock");
+    assert!(amount > 0, "Locked amount cannot be zero.");
+    assert!(
+        !state.ve_locks.contains_key(&ctx.sender),
+        "Caller already has a lock."
+    );
+    assert_valid_unlock(unlock_at, ctx.block_production_time);
+    state.escrow(&ctx, ctx.sender, amount); // lock the tokens
+    state
+        .ve_locks
+        .insert(ctx.sender, VeLock { amount, unlock_at });
+    state.assert_invariants();
+
+    state
+}
+
+/// Add `amount` tokens to the lock of caller address and move its unlock time to `unlock_at`.
+/// Either can be left as is, by adding zero or passing the current unlock time.
+///
+/// Panics if the caller has no lock, if the lock has expired, if `unlock_at` is before the current
+/// unlock time or further than [`MAX_LOCK_DURATION`] away, or if there is insufficient balance in
+/// caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to add to the lock.
+///   * `unlock_at`: [`i64`], new block production time the tokens can be withdrawn from, in
+///     milliseconds.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "vote-escrow")]
+#[action(shortname = 0x86)]
+fn extend_lock(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+    unlock_at: i64,
+) -> TashiTokenState {
+    crate::metrics::count_action!(state, "extend_lock");
+    let now = ctx.block_production_time;
+    let lock = state
+        .ve_locks
+        .get_mut(&ctx.sender)
+        .expect("Caller has no lock.");
+    assert!(now < lock.unlock_at, "Lock has expired.");
+    assert!(
+        unlock_at >= lock.unlock_at,
+        "Unlock time cannot move earlier than: {}",
+        lock.unlock_at
+    );
+    assert_valid_unlock(unlock_at, now);
+    lock.amount += amount;
+    lock.unlock_at = unlock_at;
+    state.escrow(&ctx, ctx.sender, amount); // lock the added tokens
+    state.assert_invariants();
+
+    state
+}
+
+/// Withdraw the tokens locked by caller address once the lock has expired.
+///
+/// Panics if the caller has no lock or if it has not expired.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "vote-escrow")]
+#[action(shortname = 0x87)]
+fn withdraw_after_expiry(ctx: ContractContext, mut state: TashiTokenState) -> TashiTokenState {
+    crate::metrics::count_action!(state, "withdraw_after_expiry");
+    let lock = state
+        .ve_locks
+        .remove(&ctx.sender)
+        .expect("Caller has no lock.");
+    assert!(
+        ctx.block_production_time >= lock.unlock_at,
+        "Lock expires at: {}",
+        lock.unlock_at
+    );
+    state.release(&ctx, ctx.sender, lock.amount);
+    state.assert_invariants();
+
+    state
+}
+
+/// Return the vote-escrow weight of `account` to the caller.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account to query.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state, and the weight as a [`u128`] in the return data.
+#[cfg(feature = "vote-escrow")]
+#[action(shortname = 0x88)]
+fn query_ve_weight(
+    ctx: ContractContext,
+    state: TashiTokenState,
+    account: Address,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let events = return_value(state.ve_weight(&account, ctx.block_production_time));
+    state.assert_invariants();
+    (state, events)
+}
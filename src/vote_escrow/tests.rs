@@ -0,0 +1,89 @@
+//! Tests of vote-escrow locks: the weight decaying to the unlock time, extending a lock, and
+//! withdrawing once it expired.
+
+use super::{create_lock, extend_lock, withdraw_after_expiry, MAX_LOCK_DURATION};
+use crate::hooks::Transfer;
+use crate::testing::{account, context, context_at, new_state};
+use crate::token::TashiTokenState;
+
+/// A token where account 1 holds 10_000 and locked 1_000 of it for half the maximum duration.
+fn locked_state() -> TashiTokenState {
+    let mut state = new_state();
+    state.execute_transfer(
+        &context(account(0)),
+        Transfer::new(account(0), account(1), 10_000, None),
+    );
+    create_lock(context(account(1)), state, 1_000, MAX_LOCK_DURATION / 2)
+}
+
+#[test]
+fn weight_decays_until_the_unlock_time() {
+    let state = locked_state();
+
+    assert_eq!(state.balance_of(&account(1)), 9_000);
+    assert_eq!(state.ve_weight(&account(1), 0), 2_000);
+    assert_eq!(state.ve_weight(&account(1), MAX_LOCK_DURATION / 4), 1_000);
+    assert_eq!(state.ve_weight(&account(1), MAX_LOCK_DURATION / 2), 0);
+    assert_eq!(state.ve_weight(&account(2), 0), 0);
+}
+
+#[test]
+fn extending_the_lock_restores_and_adds_weight() {
+    let state = extend_lock(
+        context_at(account(1), MAX_LOCK_DURATION / 4),
+        locked_state(),
+        1_000,
+        MAX_LOCK_DURATION / 4 + MAX_LOCK_DURATION,
+    );
+
+    assert_eq!(state.balance_of(&account(1)), 8_000);
+    assert_eq!(state.ve_weight(&account(1), MAX_LOCK_DURATION / 4), 8_000);
+}
+
+#[test]
+fn expired_lock_is_withdrawn() {
+    let state = withdraw_after_expiry(
+        context_at(account(1), MAX_LOCK_DURATION / 2),
+        locked_state(),
+    );
+
+    assert_eq!(state.balance_of(&account(1)), 10_000);
+    assert!(state.ve_locks.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Lock expires at: 63072000000")]
+fn lock_is_not_withdrawn_before_it_expires() {
+    withdraw_after_expiry(
+        context_at(account(1), MAX_LOCK_DURATION / 2 - 1),
+        locked_state(),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Unlock time cannot move earlier than: 63072000000")]
+fn unlock_time_cannot_move_earlier() {
+    extend_lock(
+        context(account(1)),
+        locked_state(),
+        0,
+        MAX_LOCK_DURATION / 2 - 1,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Unlock time: 126144000001, is further than the maximum lock duration")]
+fn lock_beyond_the_maximum_duration_is_rejected() {
+    create_lock(
+        context(account(0)),
+        new_state(),
+        1_000,
+        MAX_LOCK_DURATION + 1,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Caller already has a lock.")]
+fn account_has_a_single_lock() {
+    create_lock(context(account(1)), locked_state(), 1_000, 1_000);
+}
@@ -0,0 +1,239 @@
+//! A wrapped version of another MPC-20 token. Holders wrap the underlying token by letting this
+//! contract pull it with `transfer_from`, and the wrapped tokens are minted 1:1 once the transfer
+//! is confirmed. Unwrapping burns the wrapped tokens and sends the underlying back, minting them
+//! again if the transfer fails. The wrapped supply, the tokens minted by wraps, is tracked apart
+//! from the wrap reserve, the underlying held, which keeps the underlying of an unwrap until its
+//! transfer is confirmed. Tokens minted any other way, such as by the owner, are not backed, so at
+//! most the wrapped supply can be unwrapped.
+#![cfg_attr(not(feature = "wrapper"), allow(dead_code))]
+
+#[cfg(feature = "wrapper")]
+use pbc_contract_common::address::Address;
+#[cfg(feature = "wrapper")]
+use pbc_contract_common::context::{CallbackContext, ContractContext};
+#[cfg(feature = "wrapper")]
+use pbc_contract_common::events::EventGroup;
+#[cfg(feature = "wrapper")]
+use pbc_contract_common::shortname::ShortnameCallback;
+
+#[cfg(feature = "wrapper")]
+use crate::mpc20::{call_transfer, call_transfer_from};
+#[cfg(feature = "wrapper")]
+use crate::token::TashiTokenState;
+
+#[cfg(all(test, feature = "wrapper"))]
+mod tests;
+
+/// Shortname of the callback confirming the underlying of a wrap was received.
+#[cfg(feature = "wrapper")]
+const WRAP_CALLBACK_SHORTNAME: u32 = 0x09;
+
+/// Shortname of the callback confirming the underlying of an unwrap was sent.
+#[cfg(feature = "wrapper")]
+const UNWRAP_CALLBACK_SHORTNAME: u32 = 0x0a;
+
+/// Set the underlying MPC-20 token this token wraps. Only the owner can call this action.
+///
+/// Panics if the caller is not the owner, if `token` is this contract, or if underlying tokens are
+/// still held for wrapped tokens.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `token`: [`Address`], the underlying MPC-20 token contract.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state.
+#[cfg(feature = "wrapper")]
+#[action(shortname = 0xb4)]
+fn set_underlying_token(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    token: Address,
+) -> TashiTokenState {
+    state.assert_owner(&ctx.sender);
+    crate::audit::log_admin_action!(state, ctx, "set_underlying_token", token);
+    assert!(
+        token != ctx.contract_address,
+        "Cannot wrap this token into itself."
+    );
+    assert!(
+        state.wrap_reserve == 0,
+        "Underlying tokens are held for wrapped tokens: {}",
+        state.wrap_reserve
+    );
+    state.underlying_token = Some(token);
+    state.assert_invariants();
+
+    state
+}
+
+/// Wrap `amount` of the underlying token of caller address. The caller must first approve this
+/// contract for `amount` on the underlying token. The wrapped tokens are minted once the transfer
+/// is confirmed.
+///
+/// Panics if no underlying token is set or if `amount` is zero.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to wrap.
+///
+/// ### Returns
+///
+/// The unchanged [`TokenState`] state and the transfer interaction.
+#[cfg(feature = "wrapper")]
+#[action(shortname = 0xb5)]
+fn wrap(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "wrap");
+    let underlying = state.underlying_token.expect("No underlying token is set.");
+    assert!(amount > 0, "Wrapped amount cannot be zero.");
+
+    let mut event_group = EventGroup::builder();
+    call_transfer_from(
+        &mut event_group,
+        underlying,
+        ctx.sender,
+        ctx.contract_address,
+        amount,
+    );
+    event_group
+        .with_callback(ShortnameCallback::from_u32(WRAP_CALLBACK_SHORTNAME))
+        .argument(ctx.sender)
+        .argument(amount)
+        .done();
+    state.assert_invariants();
+
+    (state, vec![event_group.build()])
+}
+
+/// Mint the wrapped tokens of a wrap once its transfer is confirmed. Nothing was minted up front,
+/// so a failed transfer needs no rollback.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the incoming transfer.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account that wrapped.
+///   * `amount`: [`u128`], amount wrapped.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "wrapper")]
+#[callback(shortname = 0x09)]
+fn wrap_callback(
+    _ctx: ContractContext,
+    callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+    account: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let supply = state.supply();
+    if callback_ctx.success {
+        state.wrap_reserve += amount;
+        state.wrapped_supply += amount;
+        state.mint_balance(account, amount);
+    }
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Unwrap `amount` tokens of caller address, burning them and sending the same amount of the
+/// underlying token. The tokens are minted again if the transfer fails.
+///
+/// Panics if no underlying token is set, if `amount` is zero or exceeds the wrapped supply, or if
+/// there is insufficient balance in caller account.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the action.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `amount`: [`u128`], amount to unwrap.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state, the transfer interaction and the supply notifications.
+#[cfg(feature = "wrapper")]
+#[action(shortname = 0xb6)]
+fn unwrap(
+    ctx: ContractContext,
+    mut state: TashiTokenState,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    crate::metrics::count_action!(state, "unwrap");
+    let underlying = state.underlying_token.expect("No underlying token is set.");
+    assert!(amount > 0, "Unwrapped amount cannot be zero.");
+    assert!(
+        amount <= state.wrapped_supply,
+        "Unwrapped amount: {}, exceeds the wrapped supply: {}",
+        amount,
+        state.wrapped_supply
+    );
+    let supply = state.supply();
+    state.assert_can_spend(&ctx, &ctx.sender, amount);
+    state.burn_balance(ctx.sender, amount, ctx.block_production_time);
+    state.wrapped_supply -= amount;
+
+    let mut event_group = EventGroup::builder();
+    call_transfer(&mut event_group, underlying, ctx.sender, amount);
+    event_group
+        .with_callback(ShortnameCallback::from_u32(UNWRAP_CALLBACK_SHORTNAME))
+        .argument(ctx.sender)
+        .argument(amount)
+        .done();
+
+    let mut events = vec![event_group.build()];
+    events.extend(state.supply_change_events(supply));
+    state.assert_invariants();
+
+    (state, events)
+}
+
+/// Release the underlying of an unwrap from the wrap reserve once it was sent, or mint the tokens
+/// again if sending it failed.
+///
+/// ### Parameters
+///
+///   * `ctx`: [`ContractContext`], current context for the callback.
+///   * `callback_ctx`: [`CallbackContext`], result of the outgoing transfer.
+///   * `state`: [`TokenState`], current state of the contract.
+///   * `account`: [`Address`], account that unwrapped.
+///   * `amount`: [`u128`], amount unwrapped.
+///
+/// ### Returns
+///
+/// The updated [`TokenState`] state and the supply notifications.
+#[cfg(feature = "wrapper")]
+#[callback(shortname = 0x0a)]
+fn unwrap_callback(
+    _ctx: ContractContext,
+    callback_ctx: CallbackContext,
+    mut state: TashiTokenState,
+    account: Address,
+    amount: u128,
+) -> (TashiTokenState, Vec<EventGroup>) {
+    let supply = state.supply();
+    if callback_ctx.success {
+        state.wrap_reserve -= amount;
+    } else {
+        state.wrapped_supply += amount;
+        state.mint_balance(account, amount);
+    }
+
+    let events = state.supply_change_events(supply);
+    state.assert_invariants();
+
+    (state, events)
+}
@@ -0,0 +1,113 @@
+//! Tests of wrapping: tokens minted once the underlying is received, unwraps capped at the wrapped
+//! supply, and the wrap reserve kept until the underlying of an unwrap is sent.
+
+use super::{set_underlying_token, unwrap, unwrap_callback, wrap, wrap_callback};
+use crate::testing::{
+    account, callback_context, context, contract, new_state, token_address, INITIAL_SUPPLY,
+};
+use crate::token::TashiTokenState;
+
+/// A token wrapping contract 1, where account 1 wrapped `amount` and the transfer of the
+/// underlying was confirmed with `success`.
+fn wrapped_state(amount: u128, success: bool) -> TashiTokenState {
+    let state = set_underlying_token(context(account(0)), new_state(), contract(1));
+    let (state, events) = wrap(context(account(1)), state, amount);
+    assert_eq!(events.len(), 1);
+    wrap_callback(
+        context(token_address()),
+        callback_context(success),
+        state,
+        account(1),
+        amount,
+    )
+    .0
+}
+
+/// Unwraps `amount` from `sender` and confirms the transfer of the underlying with `success`.
+fn unwrapped_state(
+    state: TashiTokenState,
+    sender: u8,
+    amount: u128,
+    success: bool,
+) -> TashiTokenState {
+    let (state, _) = unwrap(context(account(sender)), state, amount);
+    unwrap_callback(
+        context(token_address()),
+        callback_context(success),
+        state,
+        account(sender),
+        amount,
+    )
+    .0
+}
+
+#[test]
+fn wrap_mints_once_the_underlying_is_received() {
+    let state = wrapped_state(500, true);
+
+    assert_eq!(state.balance_of(&account(1)), 500);
+    assert_eq!(state.wrapped_supply, 500);
+    assert_eq!(state.wrap_reserve, 500);
+    assert_eq!(state.supply(), INITIAL_SUPPLY + 500);
+}
+
+#[test]
+fn failed_wrap_mints_nothing() {
+    let state = wrapped_state(500, false);
+
+    assert_eq!(state.balance_of(&account(1)), 0);
+    assert_eq!(state.wrapped_supply, 0);
+    assert_eq!(state.wrap_reserve, 0);
+}
+
+#[test]
+fn reserve_is_kept_until_the_unwrap_is_sent() {
+    let (state, events) = unwrap(context(account(1)), wrapped_state(500, true), 200);
+    assert_eq!(events.len(), 1);
+    assert_eq!(state.balance_of(&account(1)), 300);
+    assert_eq!(state.wrapped_supply, 300);
+    assert_eq!(state.wrap_reserve, 500);
+
+    let (state, _) = unwrap_callback(
+        context(token_address()),
+        callback_context(true),
+        state,
+        account(1),
+        200,
+    );
+    assert_eq!(state.wrapped_supply, 300);
+    assert_eq!(state.wrap_reserve, 300);
+}
+
+#[test]
+fn failed_unwrap_mints_the_tokens_again() {
+    let state = unwrapped_state(wrapped_state(500, true), 1, 200, false);
+
+    assert_eq!(state.balance_of(&account(1)), 500);
+    assert_eq!(state.wrapped_supply, 500);
+    assert_eq!(state.wrap_reserve, 500);
+}
+
+#[test]
+#[should_panic(expected = "Unwrapped amount: 501, exceeds the wrapped supply: 500")]
+fn unbacked_tokens_cannot_be_unwrapped_beyond_the_wrapped_supply() {
+    // the owner holds the initial supply, which was never wrapped
+    unwrap(context(account(0)), wrapped_state(500, true), 501);
+}
+
+#[test]
+#[should_panic(expected = "Underlying tokens are held for wrapped tokens: 500")]
+fn underlying_cannot_change_while_an_unwrap_is_sent() {
+    let (state, _) = unwrap(context(account(1)), wrapped_state(500, true), 500);
+    assert_eq!(state.wrapped_supply, 0);
+    set_underlying_token(context(account(0)), state, contract(2));
+}
+
+#[test]
+fn underlying_can_change_once_everything_is_unwrapped() {
+    let state = unwrapped_state(wrapped_state(500, true), 1, 500, true);
+    let state = set_underlying_token(context(account(0)), state, contract(2));
+
+    assert_eq!(state.underlying_token, Some(contract(2)));
+    assert_eq!(state.supply(), INITIAL_SUPPLY);
+}
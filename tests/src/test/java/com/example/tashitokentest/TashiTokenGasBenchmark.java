@@ -0,0 +1,191 @@
+package com.example.tashitokentest;
+
+import java.io.IOException;
+import java.io.UncheckedIOException;
+import java.math.BigInteger;
+import java.nio.file.Files;
+import java.nio.file.Path;
+import java.nio.file.StandardOpenOption;
+import java.util.ArrayList;
+import java.util.LinkedHashMap;
+import java.util.List;
+import java.util.Map;
+
+import org.junit.jupiter.api.condition.EnabledIfSystemProperty;
+
+import com.partisiablockchain.BlockchainAddress;
+import com.partisiablockchain.language.abicodegen.TashiToken;
+import com.partisiablockchain.language.junit.ContractBytes;
+import com.partisiablockchain.language.junit.ContractTest;
+import com.partisiablockchain.language.junit.JunitContractTest;
+
+/**
+ * Gas benchmark of the token actions against states holding 10, 1,000 and
+ * 100,000 balances. The whole state is deserialized and serialized by every
+ * action, so the cost of an action grows with the number of holders.
+ *
+ * <p>
+ * The gas of an action is the least gas it succeeds with, found by a binary
+ * search over the gas sent with the transaction. Each measurement is printed
+ * and appended to {@code target/gas-benchmark.csv}. Only runs with
+ * {@code -Dbenchmark=true}.
+ */
+@EnabledIfSystemProperty(named = "benchmark", matches = "true")
+public final class TashiTokenGasBenchmark extends JunitContractTest {
+
+	private static final ContractBytes TASHI_TOKEN_CONTRACT_BYTES = ContractBytes.fromPaths(
+			Path.of("../target/wasm32-unknown-unknown/release/tashi_token.wasm"),
+			Path.of("../target/wasm32-unknown-unknown/release/tashi_token.abi"),
+			Path.of("../target/wasm32-unknown-unknown/release/tashi_token_runner"));
+
+	private static final Path REPORT = Path.of("target/gas-benchmark.csv");
+
+	private static final BigInteger totalSupply = BigInteger.TEN.pow(30);
+
+	/** Balance given to every generated holder. */
+	private static final BigInteger holderBalance = BigInteger.valueOf(1000);
+
+	/** Number of holders funded by a single bulk transfer. */
+	private static final int FUNDING_BATCH = 250;
+
+	/** Gas sent with the funding transfers, and the upper bound of the search. */
+	private static final long MAX_GAS = 100_000_000L;
+
+	/** The search stops once the bounds are this close. */
+	private static final long GAS_PRECISION = 100L;
+
+	private BlockchainAddress owner;
+	private BlockchainAddress alice;
+	private BlockchainAddress bob;
+	private BlockchainAddress contract;
+
+	/**
+	 * Deploys the token, instantiates accounts, enables trading and gives Alice
+	 * and Bob a balance.
+	 */
+	@ContractTest
+	void setUp() {
+		owner = blockchain.newAccount(1);
+		alice = blockchain.newAccount(2);
+		bob = blockchain.newAccount(3);
+
+		byte[] initializeRpc = TashiToken.initialize("Tashi Token", "TAS", (byte) 8, totalSupply);
+		contract = blockchain.deployContract(owner, TASHI_TOKEN_CONTRACT_BYTES, initializeRpc);
+		blockchain.sendAction(owner, contract, TashiToken.enableTrading());
+		blockchain.sendAction(owner, contract, TashiToken.transfer(alice, holderBalance));
+		blockchain.sendAction(owner, contract, TashiToken.transfer(bob, holderBalance));
+	}
+
+	/** Measures every action with 10 holders. */
+	@ContractTest(previous = "setUp")
+	public void tenHolders() {
+		benchmark(10);
+	}
+
+	/** Measures every action with 1,000 holders. */
+	@ContractTest(previous = "setUp")
+	public void thousandHolders() {
+		benchmark(1_000);
+	}
+
+	/** Measures every action with 100,000 holders. */
+	@ContractTest(previous = "setUp")
+	public void hundredThousandHolders() {
+		benchmark(100_000);
+	}
+
+	/**
+	 * Funds generated holders until the state holds {@code holders} balances,
+	 * then measures each action.
+	 */
+	private void benchmark(int holders) {
+		fundHolders(holders);
+		int stateSize = blockchain.getContractState(contract).length;
+
+		// alice gives bob a large allowance so every probe of transfer_from succeeds
+		blockchain.sendAction(alice, contract, TashiToken.approve(bob, holderBalance));
+
+		List<TashiToken.TransferTarget> bulkTargets = new ArrayList<>();
+		for (int i = 0; i < 10; i++) {
+			bulkTargets.add(new TashiToken.TransferTarget(holder(i), BigInteger.ONE));
+		}
+
+		Map<String, Long> gas = new LinkedHashMap<>();
+		gas.put("transfer", measureGas(owner, TashiToken.transfer(alice, BigInteger.ONE)));
+		gas.put("bulk_transfer(10)", measureGas(owner, TashiToken.bulkTransfer(bulkTargets)));
+		gas.put("approve", measureGas(alice, TashiToken.approve(bob, holderBalance)));
+		gas.put("transfer_from", measureGas(bob, TashiToken.transferFrom(alice, owner, BigInteger.ONE)));
+		gas.put("mint", measureGas(owner, TashiToken.mint(alice, BigInteger.ONE)));
+		gas.put("burn", measureGas(owner, TashiToken.burn(BigInteger.ONE)));
+
+		for (Map.Entry<String, Long> entry : gas.entrySet()) {
+			report(holders, stateSize, entry.getKey(), entry.getValue());
+		}
+	}
+
+	/**
+	 * Funds generated holders with bulk transfers from the owner until the state
+	 * holds {@code holders} balances, the owner, Alice and Bob included.
+	 */
+	private void fundHolders(int holders) {
+		int generated = Math.max(holders - 3, 0);
+		for (int start = 0; start < generated; start += FUNDING_BATCH) {
+			List<TashiToken.TransferTarget> targets = new ArrayList<>();
+			for (int i = start; i < Math.min(start + FUNDING_BATCH, generated); i++) {
+				targets.add(new TashiToken.TransferTarget(holder(i), holderBalance));
+			}
+			blockchain.sendAction(owner, contract, TashiToken.bulkTransfer(targets), MAX_GAS);
+		}
+	}
+
+	/** Gets the address of the generated holder {@code index}. */
+	private static BlockchainAddress holder(int index) {
+		return BlockchainAddress.fromString(String.format("00%040x", index));
+	}
+
+	/**
+	 * Finds the least gas {@code rpc} succeeds with when sent by {@code sender}.
+	 * Every successful probe executes the action, so the action must keep
+	 * succeeding when repeated.
+	 */
+	private long measureGas(BlockchainAddress sender, byte[] rpc) {
+		long low = 0;
+		long high = MAX_GAS;
+		if (!succeeds(sender, rpc, high)) {
+			throw new IllegalStateException("Action fails with the maximum gas.");
+		}
+		while (high - low > GAS_PRECISION) {
+			long middle = low + (high - low) / 2;
+			if (succeeds(sender, rpc, middle)) {
+				high = middle;
+			} else {
+				low = middle;
+			}
+		}
+		return high;
+	}
+
+	/** Whether {@code rpc} sent by {@code sender} with {@code gas} succeeds. */
+	private boolean succeeds(BlockchainAddress sender, byte[] rpc, long gas) {
+		try {
+			blockchain.sendAction(sender, contract, rpc, gas);
+			return true;
+		} catch (RuntimeException e) {
+			return false;
+		}
+	}
+
+	/** Prints a measurement and appends it to the report. */
+	private static void report(int holders, int stateSize, String action, long gas) {
+		String line = String.format("%d,%d,%s,%d", holders, stateSize, action, gas);
+		System.out.println("gas-benchmark: " + line);
+		try {
+			if (!Files.exists(REPORT)) {
+				Files.writeString(REPORT, "holders,state_bytes,action,gas\n");
+			}
+			Files.writeString(REPORT, line + "\n", StandardOpenOption.APPEND);
+		} catch (IOException e) {
+			throw new UncheckedIOException(e);
+		}
+	}
+}
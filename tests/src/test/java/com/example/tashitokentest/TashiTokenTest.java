@@ -10,6 +10,7 @@ import com.partisiablockchain.language.abicodegen.TashiToken;
 import com.partisiablockchain.language.junit.ContractBytes;
 import com.partisiablockchain.language.junit.ContractTest;
 import com.partisiablockchain.language.junit.JunitContractTest;
+import com.partisiablockchain.language.junit.exceptions.ActionFailureException;
 
 /** Test suite for the Voting contract. */
 public final class TashiTokenTest extends JunitContractTest {
@@ -27,8 +28,8 @@ public final class TashiTokenTest extends JunitContractTest {
 	private static final BigInteger totalSupply = BigInteger.valueOf(21000000);
 
 	/**
-	 * Setup for all the other tests. Deploys a voting contract and instantiates
-	 * accounts.
+	 * Setup for all the other tests. Deploys a voting contract, instantiates
+	 * accounts and enables trading.
 	 */
 	@ContractTest
 	void setUp() {
@@ -36,8 +37,9 @@ public final class TashiTokenTest extends JunitContractTest {
 		alice = blockchain.newAccount(2);
 		bob = blockchain.newAccount(3);
 
-		byte[] initializeRpc = TashiToken.initialize(totalSupply, "Tashi Token", "TAS", (byte) 8);
+		byte[] initializeRpc = TashiToken.initialize("Tashi Token", "TAS", (byte) 8, totalSupply);
 		contract = blockchain.deployContract(owner, TASHI_TOKEN_CONTRACT_BYTES, initializeRpc);
+		blockchain.sendAction(owner, contract, TashiToken.enableTrading());
 	}
 
 	/** Owner will transfer transferAmount TAC to Alice and Bob each. */
@@ -60,8 +62,9 @@ public final class TashiTokenTest extends JunitContractTest {
 	}
 
 	/**
-	 * Alice first gives Bob an allowance of approvalAmount TAC. She then updates
-	 * Bob's allowance relatively by asking for disapprovalAmount TAC back.
+	 * Alice first gives Bob an allowance of approvalAmount TAC. She then lowers
+	 * Bob's allowance relatively by disapprovalAmount TAC. Alice's balance is not
+	 * touched by either.
 	 */
 	@ContractTest(previous = "transfer")
 	public void approveBob() {
@@ -75,22 +78,19 @@ public final class TashiTokenTest extends JunitContractTest {
 				.deserialize(blockchain.getContractState(contract));
 
 		Assertions.assertThat(state.allowed().get(alice).get(bob)).isEqualTo(approvalAmount);
-		Assertions.assertThat(state.balances().get(alice)).isEqualTo(BigInteger.valueOf(15)
-				.subtract(approvalAmount));
+		Assertions.assertThat(state.balances().get(alice)).isEqualTo(BigInteger.valueOf(15));
 
 		blockchain.sendAction(alice, contract, approveRelativeRpc);
 		state = TashiToken.TashiTokenState.deserialize(blockchain.getContractState(contract));
 
 		Assertions.assertThat(state.allowed().get(alice).get(bob)).isEqualTo(approvalAmount
 				.subtract(disapprovalAmount));
-		Assertions.assertThat(state.balances().get(alice)).isEqualTo(BigInteger.valueOf(15)
-				.subtract(approvalAmount)
-				.add(disapprovalAmount));
+		Assertions.assertThat(state.balances().get(alice)).isEqualTo(BigInteger.valueOf(15));
 	}
 
 	/**
 	 * Bob transfers transferAmount TAC to contract owner from his allowance
-	 * from Alice.
+	 * from Alice, which is taken from Alice's balance.
 	 */
 	@ContractTest(previous = "approveBob")
 	public void transferFrom() {
@@ -103,8 +103,167 @@ public final class TashiTokenTest extends JunitContractTest {
 
 		Assertions.assertThat(state.allowed().get(alice).get(bob)).isEqualTo(BigInteger.valueOf(4)
 				.subtract(transferAmount));
+		Assertions.assertThat(state.balances().get(alice)).isEqualTo(BigInteger.valueOf(15)
+				.subtract(transferAmount));
 		Assertions.assertThat(state.balances().get(owner)).isEqualTo(totalSupply
 				.subtract(BigInteger.valueOf(30))
 				.add(transferAmount));
 	}
+
+	/**
+	 * Alice makes Bob her operator, who then transfers transferAmount TAC from
+	 * Alice to the owner without any allowance.
+	 */
+	@ContractTest(previous = "transfer")
+	public void operatorTransfer() {
+		final BigInteger transferAmount = BigInteger.valueOf(5);
+		byte[] setOperatorRpc = TashiToken.setOperator(bob, true);
+		byte[] transferFromRpc = TashiToken.transferFrom(alice, owner, transferAmount);
+
+		blockchain.sendAction(alice, contract, setOperatorRpc);
+		blockchain.sendAction(bob, contract, transferFromRpc);
+		TashiToken.TashiTokenState state = TashiToken.TashiTokenState
+				.deserialize(blockchain.getContractState(contract));
+
+		Assertions.assertThat(state.operators().get(alice)).containsExactly(bob);
+		Assertions.assertThat(state.balances().get(alice)).isEqualTo(BigInteger.valueOf(15)
+				.subtract(transferAmount));
+		Assertions.assertThat(state.balances().get(owner)).isEqualTo(totalSupply
+				.subtract(BigInteger.valueOf(30))
+				.add(transferAmount));
+	}
+
+	/**
+	 * Owner sets a burn rate of burnRate basis points and transfers transferAmount
+	 * TAC to Alice, who receives the amount less the burned share.
+	 */
+	@ContractTest(previous = "setUp")
+	public void burnOnTransfer() {
+		final short burnRate = 100;
+		final BigInteger transferAmount = BigInteger.valueOf(1000);
+		final BigInteger burnedAmount = BigInteger.valueOf(10);
+		byte[] setBurnRateRpc = TashiToken.setBurnRate(burnRate);
+		byte[] transferRpc = TashiToken.transfer(alice, transferAmount);
+
+		blockchain.sendAction(owner, contract, setBurnRateRpc);
+		blockchain.sendAction(owner, contract, transferRpc);
+		TashiToken.TashiTokenState state = TashiToken.TashiTokenState
+				.deserialize(blockchain.getContractState(contract));
+
+		Assertions.assertThat(state.burnRateBps()).isEqualTo(burnRate);
+		Assertions.assertThat(state.totalSupply()).isEqualTo(totalSupply.subtract(burnedAmount));
+		Assertions.assertThat(state.balances().get(owner)).isEqualTo(totalSupply.subtract(transferAmount));
+		Assertions.assertThat(state.balances().get(alice)).isEqualTo(transferAmount.subtract(burnedAmount));
+	}
+
+	/** Owner mints mintAmount TAC to Alice, increasing the total supply. */
+	@ContractTest(previous = "setUp")
+	public void mint() {
+		final BigInteger mintAmount = BigInteger.valueOf(500);
+		byte[] mintRpc = TashiToken.mint(alice, mintAmount);
+
+		blockchain.sendAction(owner, contract, mintRpc);
+		TashiToken.TashiTokenState state = TashiToken.TashiTokenState
+				.deserialize(blockchain.getContractState(contract));
+
+		Assertions.assertThat(state.totalSupply()).isEqualTo(totalSupply.add(mintAmount));
+		Assertions.assertThat(state.balances().get(alice)).isEqualTo(mintAmount);
+	}
+
+	/** Owner burns burnAmount TAC, decreasing the total supply. */
+	@ContractTest(previous = "setUp")
+	public void burn() {
+		final BigInteger burnAmount = BigInteger.valueOf(1000);
+		byte[] burnRpc = TashiToken.burn(burnAmount);
+
+		blockchain.sendAction(owner, contract, burnRpc);
+		TashiToken.TashiTokenState state = TashiToken.TashiTokenState
+				.deserialize(blockchain.getContractState(contract));
+
+		Assertions.assertThat(state.totalSupply()).isEqualTo(totalSupply.subtract(burnAmount));
+		Assertions.assertThat(state.balances().get(owner)).isEqualTo(totalSupply.subtract(burnAmount));
+	}
+
+	/**
+	 * Owner allows Bob to burn approvalAmount TAC, of which Bob burns burnAmount
+	 * TAC, decreasing the total supply.
+	 */
+	@ContractTest(previous = "setUp")
+	public void burnFrom() {
+		final BigInteger approvalAmount = BigInteger.valueOf(300);
+		final BigInteger burnAmount = BigInteger.valueOf(100);
+		byte[] approveBurnRpc = TashiToken.approveBurn(bob, approvalAmount);
+		byte[] burnFromRpc = TashiToken.burnFrom(owner, burnAmount);
+
+		blockchain.sendAction(owner, contract, approveBurnRpc);
+		blockchain.sendAction(bob, contract, burnFromRpc);
+		TashiToken.TashiTokenState state = TashiToken.TashiTokenState
+				.deserialize(blockchain.getContractState(contract));
+
+		Assertions.assertThat(state.burnAllowed().get(owner).get(bob)).isEqualTo(approvalAmount
+				.subtract(burnAmount));
+		Assertions.assertThat(state.totalSupply()).isEqualTo(totalSupply.subtract(burnAmount));
+		Assertions.assertThat(state.balances().get(owner)).isEqualTo(totalSupply.subtract(approvalAmount));
+	}
+
+	/** Owner pauses the token, after which transfers fail until it is unpaused. */
+	@ContractTest(previous = "setUp")
+	public void pause() {
+		final BigInteger transferAmount = BigInteger.valueOf(15);
+		byte[] transferRpc = TashiToken.transfer(alice, transferAmount);
+
+		blockchain.sendAction(owner, contract, TashiToken.pause());
+		Assertions.assertThatThrownBy(() -> blockchain.sendAction(owner, contract, transferRpc))
+				.isInstanceOf(ActionFailureException.class)
+				.hasMessageContaining("Transfers are paused.");
+
+		blockchain.sendAction(owner, contract, TashiToken.unpause());
+		blockchain.sendAction(owner, contract, transferRpc);
+		TashiToken.TashiTokenState state = TashiToken.TashiTokenState
+				.deserialize(blockchain.getContractState(contract));
+
+		Assertions.assertThat(state.balances().get(alice)).isEqualTo(transferAmount);
+	}
+
+	/**
+	 * Owner streams streamAmount TAC to Alice over a window that has already
+	 * passed, so Alice can withdraw the full deposit at once.
+	 */
+	@ContractTest(previous = "setUp")
+	public void withdrawFromStream() {
+		final BigInteger streamAmount = BigInteger.valueOf(1000);
+		byte[] createStreamRpc = TashiToken.createStream(alice, streamAmount, 0L, 1L);
+
+		blockchain.sendAction(owner, contract, createStreamRpc);
+		TashiToken.TashiTokenState state = TashiToken.TashiTokenState
+				.deserialize(blockchain.getContractState(contract));
+
+		Assertions.assertThat(state.streams().get(0L).deposit()).isEqualTo(streamAmount);
+		Assertions.assertThat(state.balances().get(owner)).isEqualTo(totalSupply.subtract(streamAmount));
+
+		blockchain.sendAction(alice, contract, TashiToken.withdrawFromStream(0L));
+		state = TashiToken.TashiTokenState.deserialize(blockchain.getContractState(contract));
+
+		Assertions.assertThat(state.streams().get(0L)).isNull();
+		Assertions.assertThat(state.balances().get(alice)).isEqualTo(streamAmount);
+	}
+
+	/**
+	 * Owner streams streamAmount TAC to Alice starting far in the future and
+	 * cancels it, which refunds the whole deposit.
+	 */
+	@ContractTest(previous = "setUp")
+	public void cancelStream() {
+		final BigInteger streamAmount = BigInteger.valueOf(1000);
+		byte[] createStreamRpc = TashiToken.createStream(alice, streamAmount, Long.MAX_VALUE - 1, Long.MAX_VALUE);
+
+		blockchain.sendAction(owner, contract, createStreamRpc);
+		blockchain.sendAction(owner, contract, TashiToken.cancelStream(0L));
+		TashiToken.TashiTokenState state = TashiToken.TashiTokenState
+				.deserialize(blockchain.getContractState(contract));
+
+		Assertions.assertThat(state.streams().get(0L)).isNull();
+		Assertions.assertThat(state.balances().get(owner)).isEqualTo(totalSupply);
+		Assertions.assertThat(state.balances().get(alice)).isNull();
+	}
 }